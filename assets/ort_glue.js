@@ -0,0 +1,40 @@
+// assets/ort_glue.js
+// Thin wrapper around onnxruntime-web (the global `ort` loaded by index.html) for
+// src/web/segmentation.rs. Tries WebGPU first and falls back to the wasm backend.
+let session = null;
+
+export async function loadSession(url) {
+  for (const provider of ["webgpu", "wasm"]) {
+    try {
+      session = await ort.InferenceSession.create(url, {
+        executionProviders: [provider],
+        graphOptimizationLevel: "all",
+      });
+      return provider;
+    } catch (e) {
+      console.warn(`onnxruntime-web: ${provider} unavailable:`, e);
+    }
+  }
+  throw new Error(`Could not load ${url} with WebGPU or wasm`);
+}
+
+// input: Float32Array in NCHW, 0..1. Returns the detection head and the mask prototypes.
+export async function runSession(input, size) {
+  if (session === null) {
+    throw new Error("Model not loaded");
+  }
+  const feeds = {};
+  feeds[session.inputNames[0]] = new ort.Tensor("float32", input, [1, 3, size, size]);
+  const results = await session.run(feeds);
+  const tensors = session.outputNames.map((name) => results[name]);
+  const output = tensors.find((t) => t.dims.length === 3);
+  const protos = tensors.find((t) => t.dims.length === 4);
+  if (output === undefined || protos === undefined) {
+    throw new Error("Model is not a segmentation model (needs a 3D and a 4D output)");
+  }
+  // WebGPU tensors may live on the GPU; getData() downloads them
+  return {
+    output: { data: await output.getData(), dims: Array.from(output.dims) },
+    protos: { data: await protos.getData(), dims: Array.from(protos.dims) },
+  };
+}
@@ -1,9 +1,9 @@
-var cacheName = 'egui-template-pwa';
+var cacheName = 'sam-cam-bam-pwa';
 var filesToCache = [
   './',
   './index.html',
-  './eframe_template.js',
-  './eframe_template_bg.wasm',
+  './SAM_CAM_BAM.js',
+  './SAM_CAM_BAM_bg.wasm',
 ];
 
 /* Start the service worker and cache all of the app's content */
@@ -0,0 +1,137 @@
+// benches/visuals.rs
+// The app is a binary crate, so the visuals module is compiled straight into the bench.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use image::{GrayImage, Luma, Rgb, RgbImage};
+use imageproc::rect::Rect;
+use rand::{rngs::SmallRng, SeedableRng};
+
+#[allow(dead_code)]
+#[path = "../src/visuals.rs"]
+mod visuals;
+
+use visuals::{VisualQuality, VisualStyle};
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 480;
+
+// Ellipse covering the middle of the frame, roughly what one person-sized detection looks like
+fn fixture() -> (RgbImage, GrayImage, Rect) {
+    let frame = RgbImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        Rgb([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8])
+    });
+    let bbox = Rect::at(WIDTH as i32 / 4, HEIGHT as i32 / 4).of_size(WIDTH / 2, HEIGHT / 2);
+    let (cx, cy) = (WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0);
+    let (rx, ry) = (WIDTH as f32 / 4.0, HEIGHT as f32 / 4.0);
+    let mask = GrayImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        let nx = (x as f32 - cx) / rx;
+        let ny = (y as f32 - cy) / ry;
+        Luma([if nx * nx + ny * ny <= 1.0 { 255 } else { 0 }])
+    });
+    (frame, mask, bbox)
+}
+
+fn bench_helpers(c: &mut Criterion) {
+    c.bench_function("lerp_color", |b| {
+        b.iter(|| {
+            visuals::lerp_color(
+                black_box(Rgb([10, 20, 30])),
+                black_box(Rgb([200, 150, 100])),
+                black_box(0.37),
+            )
+        })
+    });
+
+    let mut group = c.benchmark_group("spatial_noise_320px_row");
+    group.bench_function("scalar", |b| {
+        let mut out = vec![0.0f32; 320];
+        b.iter(|| {
+            for (i, o) in out.iter_mut().enumerate() {
+                *o = visuals::spatial_noise(i as f32 * 0.08, black_box(12.0), black_box(3.5));
+            }
+            black_box(&out);
+        })
+    });
+    group.bench_function("row_simd", |b| {
+        let mut out = vec![0.0f32; 320];
+        b.iter(|| {
+            visuals::spatial_noise_row(0.0, 0.08, black_box(12.0), black_box(3.5), &mut out);
+            black_box(&out);
+        })
+    });
+    group.finish();
+}
+
+fn bench_styles(c: &mut Criterion) {
+    let (frame, mask, bbox) = fixture();
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("draw_visuals_640x480");
+    for style in VisualStyle::ALL {
+        group.bench_with_input(BenchmarkId::from_parameter(style.label()), &style, |b, &style| {
+            let mut display = frame.clone();
+            let mut frame_count = 0u64;
+            let mut state = visuals::SlotState::default();
+            b.iter(|| {
+                frame_count += 1;
+                visuals::draw_visuals(
+                    &mut display,
+                    &frame,
+                    &mask,
+                    bbox,
+                    false,
+                    style,
+                    &visuals::StyleParams::defaults(style),
+                    VisualQuality::Full,
+                    0.7,
+                    frame_count,
+                    0.5,
+                    &mut state,
+                    &mut rng,
+                );
+            })
+        });
+    }
+    group.finish();
+}
+
+// Reduced-quality path: the style runs on a downscaled bbox and is upsampled under the mask
+fn bench_quality(c: &mut Criterion) {
+    let (frame, mask, bbox) = fixture();
+    let mut rng = SmallRng::seed_from_u64(0);
+    for quality in [VisualQuality::Half, VisualQuality::Quarter] {
+        let mut group =
+            c.benchmark_group(format!("draw_visuals_640x480_{}", quality.label().to_lowercase()));
+        for style in VisualStyle::ALL {
+            group.bench_with_input(
+                BenchmarkId::from_parameter(style.label()),
+                &style,
+                |b, &style| {
+                    let mut display = frame.clone();
+                    let mut frame_count = 0u64;
+                    let mut state = visuals::SlotState::default();
+                    b.iter(|| {
+                        frame_count += 1;
+                        visuals::draw_visuals(
+                            &mut display,
+                            &frame,
+                            &mask,
+                            bbox,
+                            false,
+                            style,
+                            &visuals::StyleParams::defaults(style),
+                            quality,
+                            0.7,
+                            frame_count,
+                            0.5,
+                            &mut state,
+                            &mut rng,
+                        );
+                    })
+                },
+            );
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_helpers, bench_styles, bench_quality);
+criterion_main!(benches);
@@ -0,0 +1,185 @@
+// src/alerting.rs
+// Webhook alerts for sustained error states. A thread watches the StatusBoard the UI keeps
+// current; once the camera or the model has been failing for `after_secs` it posts a message,
+// and another when it recovers. Payloads carry both "text" (Slack) and "content" (Discord).
+// Deliveries run on their own thread, so a slow webhook never stalls the watch loop.
+use anyhow::Result;
+use async_channel::{unbounded, Sender};
+use crossbeam_channel::{bounded, TrySendError};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::supervisor::{self, StopSignal};
+use crate::telemetry::{AppStatus, StatusBoard};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_PENDING: usize = 8; // Undelivered messages beyond this are dropped
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    pub webhook_url: Option<String>, // None = off
+    pub after_secs: u64,             // How long an error must persist before alerting
+    pub min_interval_secs: u64,      // At most one error alert per this many seconds
+    pub name: String,                // Prefixed to every message
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            after_secs: 60,
+            min_interval_secs: 900,
+            name: "SAM_CAM_BAM".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookMessage<'a> {
+    text: &'a str,
+    content: &'a str,
+}
+
+// One watched error source: when it started failing, and whether that was alerted
+#[derive(Default)]
+struct Watch {
+    since: Option<Instant>,
+    alerted: bool,
+}
+
+pub struct Alerter {
+    test_tx: Sender<()>,
+    last_result: Arc<Mutex<Option<String>>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+fn post(agent: &ureq::Agent, url: &str, text: &str) -> Result<()> {
+    let body = serde_json::to_string(&WebhookMessage {
+        text,
+        content: text,
+    })?;
+    agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&body)?;
+    Ok(())
+}
+
+impl Alerter {
+    pub fn start(
+        config: &AlertConfig,
+        status: StatusBoard,
+        stop_signal: StopSignal,
+    ) -> Option<Self> {
+        let url = config.webhook_url.clone()?;
+        let config = config.clone();
+        let (test_tx, test_rx) = unbounded();
+        let last_result = Arc::new(Mutex::new(None));
+        let result_slot = last_result.clone();
+        info!("Webhook alerts after {}s of errors", config.after_secs);
+        // Posts until the watch thread drops its sender. Never joined: a request stuck on an
+        // unreachable webhook must not hold up shutdown.
+        let (outbox_tx, outbox_rx) = bounded::<String>(MAX_PENDING);
+        let name = config.name.clone();
+        thread::spawn(move || {
+            let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+            for text in outbox_rx {
+                let outcome = match post(&agent, &url, &format!("[{}] {}", name, text)) {
+                    Ok(()) => {
+                        info!("Alert sent: {}", text);
+                        format!("Sent: {}", text)
+                    }
+                    Err(e) => {
+                        warn!("Alert failed: {:#}", e);
+                        format!("Failed: {:#}", e)
+                    }
+                };
+                if let Ok(mut slot) = result_slot.lock() {
+                    *slot = Some(outcome);
+                }
+            }
+        });
+        let handle = thread::spawn(move || {
+            let after = Duration::from_secs(config.after_secs);
+            let min_interval = Duration::from_secs(config.min_interval_secs);
+            let mut watches: [Watch; 2] = Default::default();
+            let mut last_alert: Option<Instant> = None;
+            let send = |text: String| match outbox_tx.try_send(text) {
+                Ok(()) => {}
+                Err(TrySendError::Full(text)) => warn!("Alert queue full, dropped: {}", text),
+                Err(TrySendError::Disconnected(_)) => {}
+            };
+            supervisor::runtime().block_on(async {
+                loop {
+                    tokio::select! {
+                        _ = test_rx.recv(), if !test_rx.is_closed() => {
+                            send("Test alert".to_string())
+                        }
+                        _ = stop_signal.stopped() => break,
+                        _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+                    }
+                    let AppStatus {
+                        camera_error,
+                        model_error,
+                        standby,
+                    } = status.get();
+                    // Standby releases the camera on purpose; nothing is failing
+                    let sources = [("Camera", camera_error), ("Model", model_error)];
+                    for (watch, (label, error)) in watches.iter_mut().zip(sources) {
+                        match error.filter(|_| !standby) {
+                            Some(error) => {
+                                let since = *watch.since.get_or_insert_with(Instant::now);
+                                let limited =
+                                    last_alert.is_some_and(|t| t.elapsed() < min_interval);
+                                if !watch.alerted && since.elapsed() >= after && !limited {
+                                    let secs = since.elapsed().as_secs();
+                                    send(format!("{} failing for {}s: {}", label, secs, error));
+                                    watch.alerted = true;
+                                    last_alert = Some(Instant::now());
+                                }
+                            }
+                            None => {
+                                if let Some(since) = watch.since.take() {
+                                    if watch.alerted {
+                                        let secs = since.elapsed().as_secs();
+                                        send(format!("{} recovered after {}s", label, secs));
+                                    }
+                                }
+                                watch.alerted = false;
+                            }
+                        }
+                    }
+                }
+            });
+            info!("Alert thread finished.");
+        });
+        Some(Self {
+            test_tx,
+            last_result,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn send_test(&self) {
+        let _ = self.test_tx.try_send(());
+    }
+
+    // Outcome of the most recent delivery, for the settings panel
+    pub fn last_result(&self) -> Option<String> {
+        self.last_result.lock().ok().and_then(|r| r.clone())
+    }
+
+    // Stop signal must already be set. Waits for the watch loop only, not a delivery in flight.
+    pub fn join(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,244 @@
+// src/audio_record.rs
+// Archive of the raw capture for offline re-rendering: the primary input's interleaved samples at
+// the device rate and channel count, before gain, the mic mix or the analysis downmix. Written as
+// 32-bit float WAV by a writer thread so the audio callback never touches the disk.
+use anyhow::{Context, Result};
+use crossbeam_channel::{bounded, Receiver, Sender};
+use log::{error, info, warn};
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const RECORDINGS_DIR: &str = "recordings";
+const QUEUE_SAMPLES: usize = 1 << 18; // ~2.7 s of 48 kHz stereo held while the disk catches up
+const WRITER_IDLE: Duration = Duration::from_millis(10);
+const HEADER_BYTES: u32 = 44;
+
+#[derive(Debug)]
+struct TapShared {
+    active: AtomicBool,
+    sender: Sender<f32>,
+    receiver: Receiver<f32>,
+    dropped_blocks: AtomicUsize, // Reported by the writer thread, never from the callback
+}
+
+// --- Shared with the primary capture callback; only forwards while a recorder is running ---
+// The queue is preallocated and always present, so the callback never locks or allocates
+#[derive(Debug, Clone)]
+pub struct AudioRecordTap(Arc<TapShared>);
+
+impl Default for AudioRecordTap {
+    fn default() -> Self {
+        let (sender, receiver) = bounded(QUEUE_SAMPLES);
+        Self(Arc::new(TapShared {
+            active: AtomicBool::new(false),
+            sender,
+            receiver,
+            dropped_blocks: AtomicUsize::new(0),
+        }))
+    }
+}
+
+impl AudioRecordTap {
+    // Whole blocks or nothing, so a full queue never splits a frame across channels
+    pub fn forward(&self, samples: &[f32]) {
+        let shared = &self.0;
+        if !shared.active.load(Ordering::Acquire) {
+            return;
+        }
+        if QUEUE_SAMPLES - shared.sender.len() < samples.len() {
+            shared.dropped_blocks.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        for &sample in samples {
+            let _ = shared.sender.try_send(sample);
+        }
+    }
+
+    // Discards whatever a previous recording left behind, then starts forwarding
+    fn attach(&self) -> Receiver<f32> {
+        let shared = &self.0;
+        while shared.receiver.try_recv().is_ok() {}
+        shared.dropped_blocks.store(0, Ordering::Relaxed);
+        shared.active.store(true, Ordering::Release);
+        shared.receiver.clone()
+    }
+
+    fn detach(&self) {
+        self.0.active.store(false, Ordering::Release);
+    }
+
+    fn take_dropped_blocks(&self) -> usize {
+        self.0.dropped_blocks.swap(0, Ordering::Relaxed)
+    }
+}
+
+// RIFF/WAVE header for IEEE float samples; sizes are patched once the length is known
+fn write_header(
+    out: &mut impl Write,
+    sample_rate: u32,
+    channels: u16,
+    data_bytes: u32,
+) -> Result<()> {
+    let block_align = channels as u32 * 4;
+    out.write_all(b"RIFF")?;
+    out.write_all(&(HEADER_BYTES - 8 + data_bytes).to_le_bytes())?;
+    out.write_all(b"WAVEfmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&3u16.to_le_bytes())?; // WAVE_FORMAT_IEEE_FLOAT
+    out.write_all(&channels.to_le_bytes())?;
+    out.write_all(&sample_rate.to_le_bytes())?;
+    out.write_all(&(sample_rate * block_align).to_le_bytes())?;
+    out.write_all(&(block_align as u16).to_le_bytes())?;
+    out.write_all(&32u16.to_le_bytes())?;
+    out.write_all(b"data")?;
+    out.write_all(&data_bytes.to_le_bytes())?;
+    Ok(())
+}
+
+pub struct WavRecorder {
+    path: PathBuf,
+    tap: AudioRecordTap,
+    stop: Arc<AtomicBool>,
+    writer_handle: Option<JoinHandle<Result<u32>>>,
+}
+
+impl WavRecorder {
+    pub fn start(tap: &AudioRecordTap, sample_rate: u32, channels: u16) -> Result<Self> {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        fs::create_dir_all(RECORDINGS_DIR)
+            .with_context(|| format!("Failed to create {}", RECORDINGS_DIR))?;
+        let path = Path::new(RECORDINGS_DIR).join(format!("audio-{}.wav", stamp));
+        let mut file = BufWriter::new(
+            File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?,
+        );
+        write_header(&mut file, sample_rate, channels, 0)?;
+
+        let receiver = tap.attach();
+        let stop = Arc::new(AtomicBool::new(false));
+        let writer_stop = stop.clone();
+        let writer_tap = tap.clone();
+        let writer_path = path.clone();
+        let writer_handle = thread::spawn(move || -> Result<u32> {
+            // WAV sizes are 32-bit: stop short of 4 GiB (about three hours of 48 kHz stereo),
+            // on a frame boundary; later samples are drained and discarded
+            let block_align = channels.max(1) as u32 * 4;
+            let max_bytes = (u32::MAX - HEADER_BYTES) / block_align * block_align;
+            let mut data_bytes: u32 = 0;
+            let mut at_limit = false;
+            loop {
+                // Read before draining, so everything queued ahead of the detach is written
+                let stopping = writer_stop.load(Ordering::Acquire);
+                let mut wrote = false;
+                for sample in receiver.try_iter() {
+                    wrote = true;
+                    if data_bytes + 4 > max_bytes {
+                        if !at_limit {
+                            warn!(
+                                "Audio recording {} hit the WAV size limit.",
+                                writer_path.display()
+                            );
+                            at_limit = true;
+                        }
+                        continue;
+                    }
+                    file.write_all(&sample.to_le_bytes())?;
+                    data_bytes += 4;
+                }
+                let dropped = writer_tap.take_dropped_blocks();
+                if dropped > 0 {
+                    warn!("Audio recorder queue full, dropped {} blocks.", dropped);
+                }
+                if stopping {
+                    break;
+                }
+                if !wrote {
+                    thread::sleep(WRITER_IDLE);
+                }
+            }
+            // A callback still forwarding when the tap detached can leave half a frame behind
+            file.flush()?;
+            let data_bytes = data_bytes / block_align * block_align;
+            file.get_ref().set_len((HEADER_BYTES + data_bytes) as u64)?;
+            file.seek(SeekFrom::Start(0))?;
+            write_header(&mut file, sample_rate, channels, data_bytes)?;
+            file.flush()?;
+            Ok(data_bytes)
+        });
+
+        info!(
+            "Recording audio to {} ({} Hz, {} ch)",
+            path.display(),
+            sample_rate,
+            channels
+        );
+        Ok(Self {
+            path,
+            tap: tap.clone(),
+            stop,
+            writer_handle: Some(writer_handle),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn stop_writer(&self) {
+        self.tap.detach();
+        self.stop.store(true, Ordering::Release);
+    }
+
+    // Detaches the tap so the writer drains, then finalizes the header
+    pub fn finish(mut self) -> Result<PathBuf> {
+        self.stop_writer();
+        if let Some(handle) = self.writer_handle.take() {
+            let data_bytes = handle
+                .join()
+                .map_err(|_| anyhow::anyhow!("Audio recorder writer panicked"))?
+                .with_context(|| format!("Failed to write {}", self.path.display()))?;
+            info!(
+                "Audio recording saved to {} ({} KiB)",
+                self.path.display(),
+                data_bytes / 1024
+            );
+        }
+        Ok(std::mem::take(&mut self.path))
+    }
+}
+
+// Moves a finished recording; copies when the target is on another volume
+pub fn save_as(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)
+            .with_context(|| format!("Failed to copy {} to {}", from.display(), to.display()))?;
+        fs::remove_file(from)
+            .with_context(|| format!("Failed to remove {}", from.display()))?;
+    }
+    info!("Audio recording moved to {}", to.display());
+    Ok(())
+}
+
+impl Drop for WavRecorder {
+    fn drop(&mut self) {
+        self.stop_writer();
+        if let Some(handle) = self.writer_handle.take() {
+            match handle.join() {
+                Ok(Err(e)) => error!("Audio recording {} failed: {:#}", self.path.display(), e),
+                Err(e) => error!("Audio recorder join err: {:?}", e),
+                Ok(Ok(_)) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,349 @@
+// src/automation.rs
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::BufWriter, path::Path, time::Instant};
+
+use crate::deck::Look;
+use crate::mask_edit::BrushStroke;
+use crate::music::AnalysisChannel;
+use crate::overlay::TextOverlaySettings;
+use crate::segmentation::{PointPrompt, UserInteractionSegMsg, MAX_TRACKS};
+
+const MAX_UNDO: usize = 100;
+
+// --- One recorded parameter move, relative to the start of the take ---
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationEvent {
+    pub t_ms: u64,
+    pub msg: UserInteractionSegMsg,
+}
+
+// Look/mix parameters are automatable; selection, ROI, recording etc. are not
+pub fn is_automatable(msg: &UserInteractionSegMsg) -> bool {
+    matches!(
+        msg,
+        UserInteractionSegMsg::SetSlotRouting { .. }
+            | UserInteractionSegMsg::SetSlotStyle { .. }
+            | UserInteractionSegMsg::SetSlotParams { .. }
+            | UserInteractionSegMsg::SetSlotOutline { .. }
+            | UserInteractionSegMsg::SetSlotLayers { .. }
+            | UserInteractionSegMsg::SetBackgroundLayers(_)
+            | UserInteractionSegMsg::SetOutlineBlend(_)
+            | UserInteractionSegMsg::SetShimmer(_)
+            | UserInteractionSegMsg::SetDrumFx(_)
+            | UserInteractionSegMsg::SetBloom(_)
+            | UserInteractionSegMsg::SetPostChain(_)
+            | UserInteractionSegMsg::SetTextOverlay(_)
+            | UserInteractionSegMsg::SetDeckB(_)
+            | UserInteractionSegMsg::SetCrossfader(_)
+            | UserInteractionSegMsg::SetMasterMix(_)
+    )
+}
+
+// --- Captures automatable messages while armed ---
+#[derive(Debug, Default)]
+pub struct AutomationRecorder {
+    started: Option<Instant>,
+    events: Vec<AutomationEvent>,
+}
+
+impl AutomationRecorder {
+    pub fn start(&mut self) {
+        self.events.clear();
+        self.started = Some(Instant::now());
+    }
+
+    // Returns the finished take
+    pub fn stop(&mut self) -> Vec<AutomationEvent> {
+        self.started = None;
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn record(&mut self, msg: &UserInteractionSegMsg) {
+        if let Some(start) = self.started {
+            if is_automatable(msg) {
+                self.events.push(AutomationEvent {
+                    t_ms: start.elapsed().as_millis() as u64,
+                    msg: msg.clone(),
+                });
+            }
+        }
+    }
+}
+
+// --- Plays a take back against the wall clock ---
+#[derive(Debug, Default)]
+pub struct AutomationPlayer {
+    started: Option<Instant>,
+    next: usize,
+}
+
+impl AutomationPlayer {
+    pub fn start(&mut self) {
+        self.started = Some(Instant::now());
+        self.next = 0;
+    }
+
+    pub fn stop(&mut self) {
+        self.started = None;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.started.is_some()
+    }
+
+    // Messages whose time has come; stops by itself at the end of the take
+    pub fn due(&mut self, events: &[AutomationEvent]) -> Vec<UserInteractionSegMsg> {
+        let Some(start) = self.started else {
+            return Vec::new();
+        };
+        let now_ms = start.elapsed().as_millis() as u64;
+        let mut out = Vec::new();
+        while let Some(event) = events.get(self.next) {
+            if event.t_ms > now_ms {
+                break;
+            }
+            out.push(event.msg.clone());
+            self.next += 1;
+        }
+        if self.next >= events.len() {
+            self.started = None;
+        }
+        out
+    }
+}
+
+// --- Everything undo can restore ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParamSnapshot {
+    pub look: Look,
+    pub deck_b: Look,
+    pub crossfader: f32,
+    pub slot_routing: [AnalysisChannel; MAX_TRACKS],
+    pub text_overlay: TextOverlaySettings,
+}
+
+// --- One slot's selection: prompt points, lock and brush dabs ---
+// Not part of presets; selections only make sense against the live scene
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SlotSelection {
+    pub prompt: PointPrompt,
+    pub locked: bool,
+    pub strokes: Vec<BrushStroke>,
+}
+
+// --- One undoable operator edit ---
+#[derive(Debug, Clone)]
+pub enum UndoStep {
+    // Control edits, found by comparing parameter snapshots once the pointer is released
+    Params {
+        before: ParamSnapshot,
+        after: ParamSnapshot,
+    },
+    // Select, release, lock or brush, recorded by the control that made the edit
+    Slot {
+        slot: usize,
+        before: SlotSelection,
+        after: SlotSelection,
+    },
+}
+
+// Commits a parameter step once they change and the pointer is released, so one slider drag
+// is one step; slot edits are recorded as they happen. A new edit drops the redo steps
+#[derive(Debug, Default)]
+pub struct UndoStack {
+    steps: Vec<UndoStep>,
+    redo_steps: Vec<UndoStep>,
+    committed: Option<ParamSnapshot>,
+}
+
+impl UndoStack {
+    pub fn observe(&mut self, current: &ParamSnapshot, settling: bool) {
+        if settling {
+            return;
+        }
+        match &self.committed {
+            None => self.committed = Some(current.clone()),
+            Some(prev) if prev != current => {
+                let before = prev.clone();
+                self.committed = Some(current.clone());
+                self.record(UndoStep::Params {
+                    before,
+                    after: current.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    // Adopts `current` without an undo step: for changes the operator didn't make by hand
+    // (automation playback, cues, MIDI, gamepad, remote control)
+    pub fn resync(&mut self, current: &ParamSnapshot) {
+        if self.committed.as_ref() != Some(current) {
+            self.committed = Some(current.clone());
+        }
+    }
+
+    pub fn record(&mut self, step: UndoStep) {
+        self.steps.push(step);
+        if self.steps.len() > MAX_UNDO {
+            self.steps.remove(0);
+        }
+        self.redo_steps.clear();
+    }
+
+    // The caller restores the step's `before` side
+    pub fn undo(&mut self) -> Option<UndoStep> {
+        let step = self.steps.pop()?;
+        if let UndoStep::Params { before, .. } = &step {
+            self.committed = Some(before.clone());
+        }
+        self.redo_steps.push(step.clone());
+        Some(step)
+    }
+
+    // The caller restores the step's `after` side
+    pub fn redo(&mut self) -> Option<UndoStep> {
+        let step = self.redo_steps.pop()?;
+        if let UndoStep::Params { after, .. } = &step {
+            self.committed = Some(after.clone());
+        }
+        self.steps.push(step.clone());
+        Some(step)
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo_steps.len()
+    }
+}
+
+// --- Preset file: the current parameters plus the recorded automation take ---
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Preset {
+    pub params: ParamSnapshot,
+    #[serde(default)]
+    pub automation: Vec<AutomationEvent>,
+}
+
+impl Preset {
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read preset {}", path.display()))?;
+        serde_json::from_str(&source)
+            .with_context(|| format!("Failed to parse preset {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create preset {}", path.display()))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(crossfader: f32) -> ParamSnapshot {
+        ParamSnapshot {
+            look: Look::default(),
+            deck_b: Look::default(),
+            crossfader,
+            slot_routing: [AnalysisChannel::Low; MAX_TRACKS],
+            text_overlay: TextOverlaySettings::default(),
+        }
+    }
+
+    fn slot_step(slot: usize) -> UndoStep {
+        let after = SlotSelection {
+            locked: true,
+            ..SlotSelection::default()
+        };
+        UndoStep::Slot {
+            slot,
+            before: SlotSelection::default(),
+            after,
+        }
+    }
+
+    // Crossfader values a Params step goes between, or the slot of a Slot step
+    fn describe(step: Option<UndoStep>) -> Option<(f32, f32, usize)> {
+        Some(match step? {
+            UndoStep::Params { before, after } => (before.crossfader, after.crossfader, 0),
+            UndoStep::Slot { slot, .. } => (-1.0, -1.0, slot),
+        })
+    }
+
+    #[test]
+    fn params_and_slot_steps_undo_in_order() {
+        let mut undo = UndoStack::default();
+        undo.observe(&snapshot(0.0), false);
+        assert!(undo.is_empty());
+        undo.observe(&snapshot(0.5), true); // Mid-drag: nothing yet
+        assert!(undo.is_empty());
+        undo.observe(&snapshot(0.5), false);
+        undo.record(slot_step(2));
+        undo.observe(&snapshot(1.0), false);
+        assert_eq!(undo.len(), 3);
+
+        assert_eq!(describe(undo.undo()), Some((0.5, 1.0, 0)));
+        // The restored value is the committed one again, so seeing it records nothing
+        undo.observe(&snapshot(0.5), false);
+        assert_eq!(describe(undo.undo()), Some((-1.0, -1.0, 2)));
+        assert_eq!(describe(undo.undo()), Some((0.0, 0.5, 0)));
+        assert!(undo.undo().is_none());
+        assert_eq!(undo.redo_len(), 3);
+
+        assert_eq!(describe(undo.redo()), Some((0.0, 0.5, 0)));
+        assert_eq!(describe(undo.redo()), Some((-1.0, -1.0, 2)));
+        assert_eq!(undo.len(), 2);
+    }
+
+    #[test]
+    fn a_new_edit_clears_redo() {
+        let mut undo = UndoStack::default();
+        undo.observe(&snapshot(0.0), false);
+        undo.observe(&snapshot(0.3), false);
+        undo.undo();
+        assert_eq!(undo.redo_len(), 1);
+        undo.record(slot_step(1));
+        assert_eq!(undo.redo_len(), 0);
+        assert!(undo.redo().is_none());
+
+        // A parameter edit clears it too; resyncing to an outside change doesn't
+        undo.undo();
+        undo.resync(&snapshot(0.9));
+        assert_eq!(undo.redo_len(), 1);
+        undo.observe(&snapshot(0.6), false);
+        assert_eq!(undo.redo_len(), 0);
+        assert_eq!(describe(undo.undo()), Some((0.9, 0.6, 0)));
+    }
+
+    #[test]
+    fn keeps_only_the_newest_max_undo_steps() {
+        let mut undo = UndoStack::default();
+        for slot in 0..MAX_UNDO + 50 {
+            undo.record(slot_step(slot));
+        }
+        assert_eq!(undo.len(), MAX_UNDO);
+        let mut oldest = None;
+        while let Some(step) = describe(undo.undo()) {
+            oldest = Some(step.2);
+        }
+        assert_eq!(oldest, Some(50));
+        assert_eq!(undo.redo_len(), MAX_UNDO);
+    }
+}
@@ -0,0 +1,211 @@
+// src/bench.rs
+use anyhow::{Context, Result};
+use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+use imageproc::rect::Rect;
+use log::info;
+use rand::{rngs::SmallRng, SeedableRng};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::BufWriter,
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use usls::models::YOLO;
+
+use crate::post::{self, BloomSettings, PostChain};
+use crate::segmentation;
+use crate::visuals::{self, VisualStyle};
+
+const BENCH_WIDTH: u32 = 640;
+const BENCH_HEIGHT: u32 = 480;
+const WARMUP_FRAMES: usize = 3; // Excluded from stats (first runs pay for graph/kernel setup)
+
+#[derive(Debug, Serialize)]
+struct StageStats {
+    samples: usize,
+    mean_ms: f64,
+    p50_ms: f64,
+    p95_ms: f64,
+    min_ms: f64,
+    max_ms: f64,
+}
+
+impl StageStats {
+    fn from_durations(durations: &[Duration]) -> Self {
+        let mut ms: Vec<f64> = durations.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        ms.sort_by(|a, b| a.total_cmp(b));
+        let percentile = |p: f64| -> f64 {
+            if ms.is_empty() {
+                return 0.0;
+            }
+            let rank = ((p / 100.0) * (ms.len() - 1) as f64).round() as usize;
+            ms[rank.min(ms.len() - 1)]
+        };
+        Self {
+            samples: ms.len(),
+            mean_ms: if ms.is_empty() {
+                0.0
+            } else {
+                ms.iter().sum::<f64>() / ms.len() as f64
+            },
+            p50_ms: percentile(50.0),
+            p95_ms: percentile(95.0),
+            min_ms: ms.first().copied().unwrap_or(0.0),
+            max_ms: ms.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BenchReport {
+    frames: usize,
+    width: u32,
+    height: u32,
+    total_detections: usize,
+    stages: BTreeMap<String, StageStats>,
+}
+
+// Moving gradient with a few bright blobs so the model has something to segment
+fn synthetic_frame(index: usize) -> RgbImage {
+    let t = index as f32 * 0.1;
+    let blobs = [
+        (0.3 + 0.1 * t.sin(), 0.4, 80.0, Rgb([230, 60, 40])),
+        (0.65, 0.5 + 0.1 * t.cos(), 110.0, Rgb([40, 200, 90])),
+        (0.5 + 0.15 * (t * 0.7).cos(), 0.75, 60.0, Rgb([60, 80, 240])),
+    ];
+    RgbImage::from_fn(BENCH_WIDTH, BENCH_HEIGHT, |x, y| {
+        for (cx, cy, r, color) in blobs {
+            let dx = x as f32 - cx * BENCH_WIDTH as f32;
+            let dy = y as f32 - cy * BENCH_HEIGHT as f32;
+            if dx * dx + dy * dy < r * r {
+                return color;
+            }
+        }
+        let g = ((x + y + index as u32 * 4) % 256) as u8 / 3;
+        Rgb([g, g, g.saturating_add(20)])
+    })
+}
+
+// Elliptical mask + bbox covering the middle of the frame, standing in for a detection
+fn synthetic_mask() -> (GrayImage, Rect) {
+    let bbox = Rect::at(BENCH_WIDTH as i32 / 4, BENCH_HEIGHT as i32 / 4)
+        .of_size(BENCH_WIDTH / 2, BENCH_HEIGHT / 2);
+    let (cx, cy) = (BENCH_WIDTH as f32 / 2.0, BENCH_HEIGHT as f32 / 2.0);
+    let (rx, ry) = (bbox.width() as f32 / 2.0, bbox.height() as f32 / 2.0);
+    let mask = GrayImage::from_fn(BENCH_WIDTH, BENCH_HEIGHT, |x, y| {
+        let nx = (x as f32 - cx) / rx;
+        let ny = (y as f32 - cy) / ry;
+        Luma([if nx * nx + ny * ny <= 1.0 { 255 } else { 0 }])
+    });
+    (mask, bbox)
+}
+
+fn time<T>(stages: &mut BTreeMap<String, Vec<Duration>>, name: &str, f: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let out = f();
+    stages.entry(name.to_string()).or_default().push(start.elapsed());
+    out
+}
+
+// --- `--bench N`: headless throughput run over model inference and every visual stage ---
+pub fn run(frames: usize) -> Result<()> {
+    info!("Benchmark: {} frames at {}x{}", frames, BENCH_WIDTH, BENCH_HEIGHT);
+    let mut model = YOLO::new(segmentation::default_model_options()?)
+        .map_err(|e| anyhow::anyhow!("Model load failed: {}", e))?;
+    let (mask, bbox) = synthetic_mask();
+    let bloom = BloomSettings {
+        enabled: true,
+        ..Default::default()
+    };
+    let mut chain = PostChain::default();
+    for effect in chain.effects.iter_mut() {
+        effect.enabled = true;
+        effect.driver = None;
+    }
+    let intensities = [0.7f32; crate::music::NUM_ANALYSIS_CHANNELS];
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut stages: BTreeMap<String, Vec<Duration>> = BTreeMap::new();
+    let mut total_detections = 0;
+
+    for i in 0..(frames + WARMUP_FRAMES) {
+        let source = synthetic_frame(i);
+        let mut warmup_stages = BTreeMap::new();
+        let stats = if i < WARMUP_FRAMES {
+            &mut warmup_stages
+        } else {
+            &mut stages
+        };
+
+        let input = DynamicImage::ImageRgb8(source.clone());
+        let ys = time(stats, "model_forward", || model.forward(&[input]))
+            .map_err(|e| anyhow::anyhow!("Inference failed: {}", e))?;
+        if let Some(y) = ys.first() {
+            let detections = time(stats, "collect_detections", || {
+                segmentation::collect_detections(y, None, (BENCH_WIDTH, BENCH_HEIGHT))
+            });
+            if i >= WARMUP_FRAMES {
+                total_detections += detections.len();
+            }
+        }
+
+        let mut display = source.clone();
+        for style in VisualStyle::ALL {
+            let mut state = visuals::SlotState::default();
+            time(stats, &format!("visual_{}", style.label()), || {
+                visuals::draw_visuals(
+                    &mut display,
+                    &source,
+                    &mask,
+                    bbox,
+                    false,
+                    style,
+                    &visuals::StyleParams::defaults(style),
+                    visuals::VisualQuality::Full,
+                    0.7,
+                    i as u64,
+                    0.0,
+                    &mut state,
+                    &mut rng,
+                )
+            });
+        }
+        time(stats, "post_bloom", || {
+            post::apply_bloom(&mut display, &bloom, 0.7)
+        });
+        time(stats, "post_chain", || {
+            post::apply_chain(&mut display, &chain, &intensities, i as u64, &mut rng)
+        });
+    }
+
+    let report = BenchReport {
+        frames,
+        width: BENCH_WIDTH,
+        height: BENCH_HEIGHT,
+        total_detections,
+        stages: stages
+            .iter()
+            .map(|(name, durations)| (name.clone(), StageStats::from_durations(durations)))
+            .collect(),
+    };
+
+    println!("{:<28} {:>10} {:>10} {:>10}", "stage", "mean ms", "p50 ms", "p95 ms");
+    for (name, s) in &report.stages {
+        println!(
+            "{:<28} {:>10.2} {:>10.2} {:>10.2}",
+            name, s.mean_ms, s.p50_ms, s.p95_ms
+        );
+    }
+
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = format!("bench-report-{}.json", stamp);
+    let file = File::create(Path::new(&path))
+        .with_context(|| format!("Failed to create {}", path))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &report)?;
+    info!("Benchmark report written to {}", path);
+    Ok(())
+}
@@ -2,17 +2,22 @@
 use std::{
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, Mutex,
     },
     thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
-use crossbeam_channel::{SendError, Sender};
 use image::RgbImage;
 use log::{error, info, warn};
+use crate::metrics;
+use crate::pipeline::{PolicySender, SendError, TrySendError};
+use crate::platform;
+use crate::session::ReplayStep;
+use crate::supervisor::{Heartbeat, StopSignal};
 use nokhwa::{
     pixel_format::{RgbFormat, YuyvFormat},
     utils::{
-        ApiBackend, CameraFormat, CameraIndex, FrameFormat, RequestedFormat, RequestedFormatType,
+        CameraFormat, CameraIndex, FrameFormat, RequestedFormat, RequestedFormatType,
         Resolution,
     },
     Camera, NokhwaError,
@@ -22,31 +27,103 @@ use nokhwa::{
 const REQUESTED_WIDTH: u32 = 640;
 const REQUESTED_HEIGHT: u32 = 480;
 const REQUESTED_FPS: u32 = 30;
+const STANDBY_POLL: Duration = Duration::from_secs(1);
 
 #[derive(Debug)]
 pub enum CameraThreadMsg {
-    Frame(Arc<RgbImage>),
+    // With its capture time, for frame pacing, and its sequence number from the source
+    // (the recorded frame index in a replay, which keys the seeded streams)
+    Frame(Arc<RgbImage>, Instant, u64),
+    Replay(ReplayStep), // Seeded replays only: rendered in order, on the recorded clock
     Error(String),
 }
 
+// --- Most recent raw frame, shared with the UI so it can bypass a stalled segmentation thread ---
+#[derive(Debug, Clone, Default)]
+pub struct LatestFrame(Arc<Mutex<Option<(Instant, Arc<RgbImage>)>>>);
+
+impl LatestFrame {
+    pub fn publish(&self, frame: Arc<RgbImage>) {
+        if let Ok(mut slot) = self.0.lock() {
+            *slot = Some((Instant::now(), frame));
+        }
+    }
+
+    pub fn get(&self) -> Option<(Instant, Arc<RgbImage>)> {
+        self.0.lock().ok().and_then(|slot| slot.clone())
+    }
+}
+
+// --- Scheduler requests to the capture thread: release the device, or reopen it once ---
+#[derive(Debug, Clone, Default)]
+pub struct CameraControl {
+    standby: Arc<AtomicBool>,
+    cycle: Arc<AtomicBool>,
+}
+
+impl CameraControl {
+    pub fn set_standby(&self, standby: bool) {
+        self.standby.store(standby, Ordering::Relaxed);
+    }
+
+    pub fn request_cycle(&self) {
+        self.cycle.store(true, Ordering::Relaxed);
+    }
+
+    fn in_standby(&self) -> bool {
+        self.standby.load(Ordering::Relaxed)
+    }
+
+    fn take_cycle(&self) -> bool {
+        self.cycle.swap(false, Ordering::Relaxed)
+    }
+}
+
 pub fn start_camera_thread(
     index: CameraIndex,
-    msg_sender: Sender<CameraThreadMsg>, 
-    stop_signal: Arc<AtomicBool>,
+    msg_sender: PolicySender<CameraThreadMsg>,
+    latest_frame: LatestFrame,
+    stop_signal: StopSignal,
+    control: CameraControl,
     ctx: egui::Context,
+    heartbeat: Heartbeat,
 ) -> JoinHandle<()> {
     info!("Spawning camera capture thread.");
     thread::spawn(move || {
-        camera_capture_loop(index, msg_sender, stop_signal, ctx);
+        while !stop_signal.is_stopped() {
+            // Standby: the device stays closed; a pending cycle is moot once it reopens
+            if control.in_standby() {
+                control.take_cycle();
+                heartbeat.beat();
+                stop_signal.wait(STANDBY_POLL);
+                continue;
+            }
+            let released = camera_capture_loop(
+                &index,
+                &msg_sender,
+                &latest_frame,
+                &stop_signal,
+                &control,
+                &ctx,
+                &heartbeat,
+            );
+            if !released {
+                break;
+            }
+        }
     })
 }
 
+// Returns true when the camera was closed on a CameraControl request and should be reopened
 fn camera_capture_loop(
-    index: CameraIndex,
-    msg_sender: Sender<CameraThreadMsg>, 
-    stop_signal: Arc<AtomicBool>,
-    ctx: egui::Context,
-) {
+    index: &CameraIndex,
+    msg_sender: &PolicySender<CameraThreadMsg>,
+    latest_frame: &LatestFrame,
+    stop_signal: &StopSignal,
+    control: &CameraControl,
+    ctx: &egui::Context,
+    heartbeat: &Heartbeat,
+) -> bool {
     info!("Camera capture loop started. Requesting YUYV format.");
     let requested_resolution = Resolution::new(REQUESTED_WIDTH, REQUESTED_HEIGHT);
     let requested_cam_format =
@@ -55,14 +132,18 @@ fn camera_capture_loop(
         RequestedFormat::new::<YuyvFormat>(RequestedFormatType::Closest(requested_cam_format));
     info!("Requested camera format: {:?}", requested_format);
 
-    // --- Initialize Camera ---
-    let camera_result = Camera::new(index.clone(), requested_format).or_else(|err| {
-        warn!(
-            "Default backend failed: {}. Trying AVFoundation explicitly...",
-            err
-        );
-        Camera::with_backend(index, requested_format, ApiBackend::AVFoundation)
-    });
+    // --- Initialize Camera (platform backends in order of preference) ---
+    let mut camera_result = Err(NokhwaError::GeneralError("No camera backend".to_string()));
+    for &backend in platform::camera_backends() {
+        camera_result = Camera::with_backend(index.clone(), requested_format, backend);
+        match &camera_result {
+            Ok(_) => {
+                info!("Camera opened with backend {:?}", backend);
+                break;
+            }
+            Err(err) => warn!("Camera backend {:?} failed: {}", backend, err),
+        }
+    }
 
     let mut camera = match camera_result {
         Ok(cam) => {
@@ -74,7 +155,7 @@ fn camera_capture_loop(
             error!("{}", error_msg);
             let _ = msg_sender.send(CameraThreadMsg::Error(error_msg));
             ctx.request_repaint();
-            return;
+            return false;
         }
     };
 
@@ -86,30 +167,43 @@ fn camera_capture_loop(
         error!("{}", error_msg);
         let _ = msg_sender.send(CameraThreadMsg::Error(error_msg));
         ctx.request_repaint();
-        return;
+        return false;
     }
     info!("Camera stream opened successfully.");
 
     // --- Frame Capture Loop ---
-    while !stop_signal.load(Ordering::Relaxed) {
+    let mut released = false;
+    let mut frame_index: u64 = 0;
+    while !stop_signal.is_stopped() {
+        heartbeat.beat();
+        if control.in_standby() || control.take_cycle() {
+            info!("Closing camera on request (standby or scheduled cycle).");
+            released = true;
+            break;
+        }
         match camera.frame() {
             Ok(frame) => match frame.decode_image::<RgbFormat>() {
                 Ok(decoded_rgb_image) => {
+                    let captured = Instant::now();
+                    frame_index += 1;
                     let frame_arc = Arc::new(decoded_rgb_image);
-                    if let Err(SendError(_)) = msg_sender.send(CameraThreadMsg::Frame(frame_arc)) {
+                    latest_frame.publish(frame_arc.clone());
+                    let msg = CameraThreadMsg::Frame(frame_arc, captured, frame_index);
+                    if let Err(TrySendError::Closed(_)) = msg_sender.try_send(msg) {
                         info!("Segmentation thread receiver disconnected. Stopping camera loop.");
                         break;
                     }
+                    metrics::global().inc(metrics::CAMERA_FRAMES, 1);
                 }
                 Err(err) => {
                     warn!("Failed to decode frame to RGB: {}", err);
-                    thread::sleep(std::time::Duration::from_millis(50));
+                    stop_signal.wait(Duration::from_millis(50));
                 }
             },
             Err(err) => match err {
                 NokhwaError::ReadFrameError(msg) if msg.contains("Timeout") => {
                     warn!("Camera frame read timeout.");
-                    thread::sleep(std::time::Duration::from_millis(100));
+                    stop_signal.wait(Duration::from_millis(100));
                 }
                 _ => {
                     let error_msg = format!("Failed to capture frame: {}", err);
@@ -118,7 +212,7 @@ fn camera_capture_loop(
                         info!("Segmentation thread receiver disconnected after capture error.");
                         break;
                     }
-                    thread::sleep(std::time::Duration::from_secs(1));
+                    stop_signal.wait(Duration::from_secs(1));
                 }
             },
         }
@@ -129,4 +223,5 @@ fn camera_capture_loop(
         error!("Failed to stop camera stream cleanly: {}", e);
     }
     info!("Camera capture loop finished.");
+    released
 }
@@ -0,0 +1,62 @@
+// src/cli.rs
+use log::warn;
+use std::path::PathBuf;
+
+use crate::profile::AppProfile;
+
+const DEFAULT_BENCH_FRAMES: usize = 100;
+
+// --- Options parsed from the command line at launch ---
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    pub replay_dir: Option<PathBuf>, // Replay a recorded session instead of live camera/audio
+    pub stem_model: Option<PathBuf>, // ONNX source-separation model for stem analysis channels
+    pub bench_frames: Option<usize>, // Run the headless benchmark instead of the app
+    pub config_path: Option<PathBuf>, // JSON config file (defaults to ./sam_cam_bam.json)
+    pub seed: Option<u64>,            // Seed all visual randomness for reproducible renders
+    pub touch: bool,                  // Kiosk layout: large hit targets, tap-to-select
+    pub profile: Option<AppProfile>,  // Installation / VJ / Streaming starting values
+}
+
+pub fn parse_args() -> LaunchOptions {
+    let mut options = LaunchOptions::default();
+    let mut args = std::env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--replay" => match args.next() {
+                Some(dir) => options.replay_dir = Some(PathBuf::from(dir)),
+                None => warn!("--replay requires a session directory"),
+            },
+            "--stems" => match args.next() {
+                Some(path) => options.stem_model = Some(PathBuf::from(path)),
+                None => warn!("--stems requires a path to an ONNX model"),
+            },
+            "--config" => match args.next() {
+                Some(path) => options.config_path = Some(PathBuf::from(path)),
+                None => warn!("--config requires a path to a JSON file"),
+            },
+            "--seed" => match args.next().and_then(|n| n.parse::<u64>().ok()) {
+                Some(seed) => options.seed = Some(seed),
+                None => warn!("--seed requires an unsigned integer"),
+            },
+            "--touch" => options.touch = true,
+            "--profile" => match args.next().as_deref().and_then(AppProfile::parse) {
+                Some(profile) => options.profile = Some(profile),
+                None => warn!("--profile requires one of: installation, vj, streaming"),
+            },
+            "--bench" => {
+                // Frame count is optional: `--bench` or `--bench 200`
+                let frames = args
+                    .peek()
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .filter(|&n| n > 0);
+                if frames.is_some() {
+                    args.next();
+                }
+                options.bench_frames = Some(frames.unwrap_or(DEFAULT_BENCH_FRAMES));
+            }
+            other => warn!("Ignoring unknown argument: {}", other),
+        }
+    }
+    options
+}
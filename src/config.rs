@@ -0,0 +1,104 @@
+// src/config.rs
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::alerting::AlertConfig;
+use crate::control::ControlConfig;
+use crate::ensemble::EnsembleConfig;
+use crate::file_source::FileSourceConfig;
+use crate::keyed::KeyedConfig;
+use crate::link::LinkConfig;
+use crate::logging::LoggingConfig;
+use crate::metrics::MetricsConfig;
+use crate::music::AudioConfig;
+use crate::pipeline::PipelineConfig;
+use crate::preview::PreviewConfig;
+use crate::profile::AppProfile;
+use crate::schedule::ScheduleConfig;
+use crate::streaming::StreamConfig;
+use crate::telemetry::TelemetryConfig;
+use crate::timecode::TimecodeConfig;
+
+// Looked up in the working directory when no --config is given
+const DEFAULT_CONFIG_FILE: &str = "sam_cam_bam.json";
+
+// --- Raw pass-through when segmentation stops delivering frames ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogConfig {
+    pub enabled: bool,
+    pub deadline_ms: u64, // No composited frame for this long (while the camera runs) = lagging
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            deadline_ms: 750,
+        }
+    }
+}
+
+// --- Settings read from the JSON config file; missing fields fall back to defaults ---
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub pipeline: PipelineConfig,
+    pub metrics: MetricsConfig,
+    pub watchdog: WatchdogConfig,
+    pub audio: AudioConfig,
+    pub preview: PreviewConfig,
+    pub stream: StreamConfig,
+    pub keyed: KeyedConfig,
+    pub timecode: TimecodeConfig,
+    pub link: LinkConfig,
+    pub control: ControlConfig,
+    pub logging: LoggingConfig,
+    pub cues: Option<PathBuf>, // Cue list loaded at startup
+    pub profile: Option<AppProfile>, // Used when no --profile is given
+    pub schedule: ScheduleConfig,
+    pub telemetry: TelemetryConfig,
+    pub alerts: AlertConfig,
+    pub ensemble: EnsembleConfig,
+    pub file_source: FileSourceConfig,
+}
+
+impl AppConfig {
+    fn read(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        let config: Self = serde_json::from_str(&source)
+            .with_context(|| format!("Failed to parse config {}", path.display()))?;
+        config
+            .pipeline
+            .validate()
+            .with_context(|| format!("Invalid config {}", path.display()))?;
+        Ok(config)
+    }
+
+    // A missing default file is silent; any other failure is logged and defaults are used
+    pub fn load(explicit: Option<&Path>) -> Self {
+        let path = match explicit {
+            Some(p) => p.to_path_buf(),
+            None => {
+                let p = PathBuf::from(DEFAULT_CONFIG_FILE);
+                if !p.exists() {
+                    return Self::default();
+                }
+                p
+            }
+        };
+        match Self::read(&path) {
+            Ok(config) => {
+                info!("Loaded config from {}", path.display());
+                config
+            }
+            Err(e) => {
+                warn!("{:#}; using defaults", e);
+                Self::default()
+            }
+        }
+    }
+}
@@ -0,0 +1,315 @@
+// src/control.rs
+// Remote control over HTTP for Bitfocus Companion (Generic HTTP module) and other show
+// controllers: GET /api/actions lists the named actions, POST /api/action/<id> runs one,
+// GET /api/state reports what a Stream Deck button might show as feedback. Browsers can still
+// send simple cross-site requests (form posts, no-cors fetches) without CORS, so any request
+// carrying an Origin header is refused: show controllers never send one, web pages always do on
+// a POST. Set `token` as well when the port is reachable from other machines.
+use anyhow::{Context, Result};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::segmentation::MAX_TRACKS;
+use crate::supervisor::{self, StopSignal};
+
+const READ_TIMEOUT: Duration = Duration::from_millis(500);
+const TOKEN_HEADER: &str = "x-control-token";
+const ORIGIN_HEADER: &str = "origin";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlConfig {
+    pub http_addr: Option<String>, // e.g. "127.0.0.1:8082"; None = no control server
+    pub token: Option<String>,     // Shared secret; requests must send it as X-Control-Token
+}
+
+// --- Everything a remote can trigger; ids are stable, labels are for people ---
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ControlAction {
+    NextCue,
+    GoCue(u32), // By cue number
+    StartShow,
+    StopShow,
+    ClearSlot(usize), // Releases the prompt, lock, brush edits and box nudges
+    ToggleRecording,
+    ToggleKeyed,
+    ToggleMaskedOutput,
+    ToggleBlackout,
+    ToggleFreeze,
+    CrossfadeTo(f32),
+    RevealTo(f32), // Master mix: 0 camera, 1 composite, 2 effects only
+}
+
+impl ControlAction {
+    pub fn id(&self) -> String {
+        match self {
+            ControlAction::NextCue => "next_cue".to_string(),
+            ControlAction::GoCue(n) => format!("cue_{}", n),
+            ControlAction::StartShow => "start_show".to_string(),
+            ControlAction::StopShow => "stop_show".to_string(),
+            ControlAction::ClearSlot(slot) => format!("clear_slot_{}", slot + 1),
+            ControlAction::ToggleRecording => "toggle_recording".to_string(),
+            ControlAction::ToggleKeyed => "toggle_keyed".to_string(),
+            ControlAction::ToggleMaskedOutput => "toggle_masked_output".to_string(),
+            ControlAction::ToggleBlackout => "toggle_blackout".to_string(),
+            ControlAction::ToggleFreeze => "toggle_freeze".to_string(),
+            ControlAction::CrossfadeTo(t) => format!("crossfader_{}", (t * 100.0).round()),
+            ControlAction::RevealTo(t) => format!("reveal_{}", (t * 100.0).round()),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            ControlAction::NextCue => "GO next cue".to_string(),
+            ControlAction::GoCue(n) => format!("Go to cue {}", n),
+            ControlAction::StartShow => "Start show clock".to_string(),
+            ControlAction::StopShow => "Stop show clock".to_string(),
+            ControlAction::ClearSlot(slot) => format!("Clear slot {}", slot + 1),
+            ControlAction::ToggleRecording => "Toggle session recording".to_string(),
+            ControlAction::ToggleKeyed => "Toggle keyed recording".to_string(),
+            ControlAction::ToggleMaskedOutput => "Toggle masked output".to_string(),
+            ControlAction::ToggleBlackout => "Blackout".to_string(),
+            ControlAction::ToggleFreeze => "Freeze".to_string(),
+            ControlAction::CrossfadeTo(t) => format!("Crossfader to {:.0}%", t * 100.0),
+            ControlAction::RevealTo(t) => match *t {
+                t if t <= 0.0 => "Show raw camera".to_string(),
+                t if t >= 2.0 => "Show effects only".to_string(),
+                _ => "Show full composite".to_string(),
+            },
+        }
+    }
+
+    // The fixed actions, plus one per loaded cue
+    pub fn catalog(cue_numbers: &[u32]) -> Vec<ControlAction> {
+        let mut actions = vec![
+            ControlAction::NextCue,
+            ControlAction::StartShow,
+            ControlAction::StopShow,
+        ];
+        actions.extend(cue_numbers.iter().map(|n| ControlAction::GoCue(*n)));
+        actions.extend((0..MAX_TRACKS).map(ControlAction::ClearSlot));
+        actions.extend([
+            ControlAction::ToggleRecording,
+            ControlAction::ToggleKeyed,
+            ControlAction::ToggleMaskedOutput,
+            ControlAction::ToggleBlackout,
+            ControlAction::ToggleFreeze,
+            ControlAction::CrossfadeTo(0.0),
+            ControlAction::CrossfadeTo(0.5),
+            ControlAction::CrossfadeTo(1.0),
+            ControlAction::RevealTo(0.0),
+            ControlAction::RevealTo(1.0),
+            ControlAction::RevealTo(2.0),
+        ]);
+        actions
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ActionInfo {
+    id: String,
+    label: String,
+}
+
+// Feedback for buttons; published by the UI every frame
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ControlState {
+    pub cue: Option<String>,
+    pub show_running: bool,
+    pub recording: bool,
+    pub keyed: bool,
+    pub masked_output: bool,
+    pub blackout: bool,
+    pub frozen: bool,
+    pub crossfader: f32,
+    pub master_mix: f32,
+}
+
+#[derive(Default)]
+struct Shared {
+    actions: Mutex<Vec<ControlAction>>,
+    state: Mutex<ControlState>,
+}
+
+pub struct ControlServer {
+    shared: Arc<Shared>,
+    receiver: Receiver<ControlAction>,
+    stop_signal: StopSignal,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ControlServer {
+    pub fn start(config: &ControlConfig) -> Result<Option<Self>> {
+        let Some(addr) = &config.http_addr else {
+            return Ok(None);
+        };
+        let listener = TcpListener::bind(addr)
+            .with_context(|| format!("Failed to bind control endpoint {}", addr))?;
+        let listener = supervisor::async_listener(listener)?;
+        info!("Control server on http://{}/api/actions", addr);
+        let loopback = listener.local_addr().is_ok_and(|a| a.ip().is_loopback());
+        if config.token.is_none() && !loopback {
+            warn!("Control server on {} accepts actions from the network without a token", addr);
+        }
+        let token = config.token.clone().filter(|t| !t.is_empty());
+        let shared = Arc::new(Shared::default());
+        let (sender, receiver) = unbounded();
+        let stop_signal = StopSignal::new();
+        let (thread_shared, thread_stop) = (shared.clone(), stop_signal.clone());
+        let thread = thread::spawn(move || {
+            supervisor::runtime().block_on(async {
+                loop {
+                    let accepted = tokio::select! {
+                        accepted = supervisor::accept(&listener) => accepted,
+                        _ = thread_stop.stopped() => break,
+                    };
+                    match accepted {
+                        Ok((stream, peer)) => {
+                            let token = token.as_deref();
+                            if let Err(e) = serve(stream, &thread_shared, &sender, token) {
+                                warn!("Control request from {} failed: {:#}", peer, e);
+                            }
+                        }
+                        Err(e) => warn!("Control accept failed: {}", e),
+                    }
+                }
+            });
+            info!("Control thread finished.");
+        });
+        Ok(Some(Self {
+            shared,
+            receiver,
+            stop_signal,
+            thread: Some(thread),
+        }))
+    }
+
+    pub fn set_actions(&self, actions: Vec<ControlAction>) {
+        *self.shared.actions.lock().unwrap() = actions;
+    }
+
+    pub fn publish_state(&self, state: ControlState) {
+        *self.shared.state.lock().unwrap() = state;
+    }
+
+    // Actions received since the last call, oldest first
+    pub fn drain(&self) -> Vec<ControlAction> {
+        self.receiver.try_iter().collect()
+    }
+
+    pub fn stop(&mut self) {
+        self.stop_signal.stop();
+        if let Some(handle) = self.thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// Value of a request header, matched case-insensitively
+fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim())
+}
+
+// Requests are tiny and answered inline, so one thread serves every client
+fn serve(
+    mut stream: TcpStream,
+    shared: &Shared,
+    sender: &Sender<ControlAction>,
+    token: Option<&str>,
+) -> Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut buf = [0u8; 2048];
+    let n = stream.read(&mut buf)?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let mut parts = request.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or("/"));
+    // A web page, whichever site it came from (rebinding makes even a matching Host unreliable)
+    if header(&request, ORIGIN_HEADER).is_some() {
+        return respond(&mut stream, "403 Forbidden", r#"{"error":"browser requests refused"}"#);
+    }
+    if let Some(token) = token {
+        if header(&request, TOKEN_HEADER) != Some(token) {
+            return respond(&mut stream, "401 Unauthorized", r#"{"error":"bad token"}"#);
+        }
+    }
+    let path = path.trim_end_matches('/');
+    // Actions change the show, so they are POST only; everything else is read-only
+    let expected = if path.starts_with("/api/action/") {
+        "POST"
+    } else {
+        "GET"
+    };
+    if method != expected {
+        let body = format!(r#"{{"error":"use {}"}}"#, expected);
+        return respond(&mut stream, "405 Method Not Allowed", &body);
+    }
+    match path {
+        "/api/actions" => {
+            let actions: Vec<ActionInfo> = shared
+                .actions
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|a| ActionInfo {
+                    id: a.id(),
+                    label: a.label(),
+                })
+                .collect();
+            respond(&mut stream, "200 OK", &serde_json::to_string(&actions)?)
+        }
+        "/api/state" => {
+            let state = serde_json::to_string(&*shared.state.lock().unwrap())?;
+            respond(&mut stream, "200 OK", &state)
+        }
+        p => match p.strip_prefix("/api/action/") {
+            Some(id) => {
+                let action = shared
+                    .actions
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .find(|a| a.id() == id)
+                    .copied();
+                match action {
+                    Some(action) => {
+                        info!("Remote action: {}", action.label());
+                        let _ = sender.send(action);
+                        respond(&mut stream, "200 OK", r#"{"ok":true}"#)
+                    }
+                    None => respond(
+                        &mut stream,
+                        "404 Not Found",
+                        r#"{"error":"unknown action"}"#,
+                    ),
+                }
+            }
+            None => respond(&mut stream, "404 Not Found", r#"{"error":"not found"}"#),
+        },
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
@@ -0,0 +1,171 @@
+// src/cues.rs
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{path::Path, time::Instant};
+
+use crate::music::AnalysisChannel;
+use crate::overlay::TextOverlaySettings;
+use crate::post::{BloomSettings, PostChain};
+use crate::timecode::Timecode;
+use crate::segmentation::MAX_TRACKS;
+use crate::visuals::VisualStyle;
+
+// --- One numbered cue: the look it switches to and what can fire it ---
+// Every look field is optional; a cue only changes what it names
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Cue {
+    pub number: u32,
+    pub name: String,
+    // --- Triggers ---
+    pub at_secs: Option<f32>,        // Elapsed show time
+    pub at_timecode: Option<String>, // Chased LTC/MTC position, "HH:MM:SS:FF"
+    pub at_beat: Option<f64>,        // Ableton Link song position, beats from transport start
+    pub hotkey: Option<String>,      // egui key name, e.g. "F1" or "Num3"
+    pub midi_program: Option<u8>,    // Program change number (any channel)
+    // --- Look ---
+    pub slot_styles: Option<[VisualStyle; MAX_TRACKS]>,
+    pub slot_routing: Option<[AnalysisChannel; MAX_TRACKS]>,
+    pub slot_locks: Option<[bool; MAX_TRACKS]>,
+    pub bloom: Option<BloomSettings>,
+    pub post_chain: Option<PostChain>,
+    pub text_overlay: Option<TextOverlaySettings>,
+}
+
+impl Cue {
+    pub fn label(&self) -> String {
+        if self.name.is_empty() {
+            format!("Cue {}", self.number)
+        } else {
+            format!("{} {}", self.number, self.name)
+        }
+    }
+}
+
+// --- Cue list file (JSON) ---
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CueList {
+    pub cues: Vec<Cue>,
+}
+
+impl CueList {
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read cue list {}", path.display()))?;
+        let mut list: CueList = serde_json::from_str(&source)
+            .with_context(|| format!("Failed to parse cue list {}", path.display()))?;
+        list.cues.sort_by_key(|c| c.number);
+        for cue in &list.cues {
+            if let Some(tc) = &cue.at_timecode {
+                // Syntax only: the rate is known once a source is chased (30 = the highest)
+                if Timecode::parse_secs(tc, 30.0).is_none() {
+                    warn!("Cue {}: bad timecode '{}'", cue.number, tc);
+                }
+            }
+            if cue.at_beat.is_some_and(|b| !b.is_finite() || b < 0.0) {
+                warn!("Cue {}: at_beat must be a non-negative number", cue.number);
+            }
+            if let Some(key) = &cue.hotkey {
+                if egui::Key::from_name(key).is_none() {
+                    warn!("Cue {}: unknown hotkey '{}'", cue.number, key);
+                }
+            }
+        }
+        info!("Loaded {} cues from {}", list.cues.len(), path.display());
+        Ok(list)
+    }
+}
+
+// --- Playback position in a cue list; returns indices of cues to fire ---
+#[derive(Debug, Default)]
+pub struct CuePlayer {
+    pub list: CueList,
+    pub current: Option<usize>,
+    show_started: Option<Instant>,
+}
+
+impl CuePlayer {
+    pub fn new(list: CueList) -> Self {
+        Self {
+            list,
+            current: None,
+            show_started: None,
+        }
+    }
+
+    pub fn start_show(&mut self) {
+        self.show_started = Some(Instant::now());
+        self.current = None;
+    }
+
+    pub fn stop_show(&mut self) {
+        self.show_started = None;
+    }
+
+    pub fn show_elapsed_secs(&self) -> Option<f32> {
+        self.show_started.map(|t| t.elapsed().as_secs_f32())
+    }
+
+    pub fn fire(&mut self, index: usize) -> Option<&Cue> {
+        let cue = self.list.cues.get(index)?;
+        self.current = Some(index);
+        info!("GO {}", cue.label());
+        Some(cue)
+    }
+
+    pub fn next_index(&self) -> Option<usize> {
+        let next = self.current.map_or(0, |i| i + 1);
+        (next < self.list.cues.len()).then_some(next)
+    }
+
+    // Latest timed cue after the current one whose time has come (skips any we slept through)
+    pub fn due_timed_cue(&self) -> Option<usize> {
+        let elapsed = self.show_elapsed_secs()?;
+        let from = self.current.map_or(0, |i| i + 1);
+        (from..self.list.cues.len())
+            .filter(|&i| self.list.cues[i].at_secs.is_some_and(|t| t <= elapsed))
+            .last()
+    }
+
+    // Cue the master clock says should be live: the latest one whose timecode has passed.
+    // Jumps back to an earlier cue when the clock is rewound; frames count at the source's fps
+    pub fn timecode_cue(&self, show_secs: f64, fps: f32) -> Option<usize> {
+        self.list
+            .cues
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| Some((i, Timecode::parse_secs(c.at_timecode.as_deref()?, fps)?)))
+            .filter(|(_, t)| *t <= show_secs)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    // Same for the Link song position, in beats
+    pub fn beat_cue(&self, beats: f64) -> Option<usize> {
+        self.list
+            .cues
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| Some((i, c.at_beat?)))
+            .filter(|(_, b)| *b <= beats)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    pub fn index_for_key(&self, key: egui::Key) -> Option<usize> {
+        self.list.cues.iter().position(|c| {
+            c.hotkey
+                .as_deref()
+                .and_then(egui::Key::from_name)
+                .is_some_and(|k| k == key)
+        })
+    }
+
+    pub fn index_for_program(&self, program: u8) -> Option<usize> {
+        self.list
+            .cues
+            .iter()
+            .position(|c| c.midi_program == Some(program))
+    }
+}
@@ -0,0 +1,107 @@
+// src/damage.rs
+// Dirty-region tracking for the display texture. The segmentation thread keeps a copy of what
+// the UI texture holds and diffs each new frame against it in tiles, so a static scene (e.g.
+// masked output, or a fixed installation camera) only re-uploads the tiles around the objects.
+use image::RgbImage;
+
+const TILE: u32 = 64; // Pixels per tile side
+const TOLERANCE: u8 = 4; // Per-channel difference treated as sensor noise, not a change
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DamageRect {
+    pub pos: [usize; 2],
+    pub size: [usize; 2],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameDamage {
+    Full,
+    Regions(Vec<DamageRect>), // Empty = texture already up to date
+}
+
+#[derive(Default)]
+pub struct DamageTracker {
+    mirror: Option<RgbImage>, // Last frame the UI received, as far as this thread knows
+}
+
+fn tile_changed(a: &RgbImage, b: &RgbImage, x0: u32, y0: u32, x1: u32, y1: u32) -> bool {
+    let stride = a.width() as usize * 3;
+    let (a, b) = (a.as_raw(), b.as_raw());
+    (y0..y1).any(|y| {
+        let start = y as usize * stride + x0 as usize * 3;
+        let end = y as usize * stride + x1 as usize * 3;
+        a[start..end]
+            .iter()
+            .zip(&b[start..end])
+            .any(|(p, q)| p.abs_diff(*q) > TOLERANCE)
+    })
+}
+
+impl DamageTracker {
+    // Regions of `image` that differ from the mirror; each tile row's dirty tiles are merged
+    pub fn diff(&self, image: &RgbImage) -> FrameDamage {
+        let Some(mirror) = self
+            .mirror
+            .as_ref()
+            .filter(|m| m.dimensions() == image.dimensions())
+        else {
+            return FrameDamage::Full;
+        };
+        let (w, h) = image.dimensions();
+        let mut rects = Vec::new();
+        for y0 in (0..h).step_by(TILE as usize) {
+            let y1 = (y0 + TILE).min(h);
+            let mut run: Option<u32> = None;
+            for x0 in (0..w).step_by(TILE as usize) {
+                let x1 = (x0 + TILE).min(w);
+                let changed = tile_changed(mirror, image, x0, y0, x1, y1);
+                match (changed, run) {
+                    (true, None) => run = Some(x0),
+                    (false, Some(start)) => {
+                        rects.push(rect(start, y0, x0, y1));
+                        run = None;
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(start) = run {
+                rects.push(rect(start, y0, w, y1));
+            }
+        }
+        // Mostly dirty: one full upload beats many small ones
+        let dirty: usize = rects.iter().map(|r| r.size[0] * r.size[1]).sum();
+        if dirty * 4 > (w * h) as usize * 3 {
+            return FrameDamage::Full;
+        }
+        FrameDamage::Regions(rects)
+    }
+
+    // Call once the UI has actually been sent `image` with `damage`
+    pub fn commit(&mut self, image: &RgbImage, damage: &FrameDamage) {
+        match (damage, self.mirror.as_mut()) {
+            (FrameDamage::Regions(rects), Some(mirror)) => {
+                let stride = image.width() as usize * 3;
+                let (dst, src) = (&mut **mirror, image.as_raw());
+                for r in rects {
+                    for y in r.pos[1]..r.pos[1] + r.size[1] {
+                        let start = y * stride + r.pos[0] * 3;
+                        let end = start + r.size[0] * 3;
+                        dst[start..end].copy_from_slice(&src[start..end]);
+                    }
+                }
+            }
+            _ => self.mirror = Some(image.clone()),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.mirror = None;
+    }
+}
+
+fn rect(x0: u32, y0: u32, x1: u32, y1: u32) -> DamageRect {
+    DamageRect {
+        pos: [x0 as usize, y0 as usize],
+        size: [(x1 - x0) as usize, (y1 - y0) as usize],
+    }
+}
@@ -0,0 +1,85 @@
+// src/deck.rs
+use image::RgbImage;
+use serde::{Deserialize, Serialize};
+
+use crate::music::AnalysisChannel;
+use crate::post::{BloomSettings, PostChain};
+use crate::segmentation::MAX_TRACKS;
+use crate::visuals::{
+    BlendMode, DrumFxSettings, OutlineStyle, ShimmerSettings, StyleParams, VisualStyle,
+};
+
+pub const MAX_SLOT_LAYERS: usize = 3; // Extra layers per slot, above its base style
+
+// --- One extra style stacked on a slot's object ---
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EffectLayer {
+    pub style: VisualStyle,
+    pub params: StyleParams,
+    pub blend: BlendMode,
+    pub opacity: f32,
+    // None = the slot's own routing, Some = driven by that channel instead
+    pub driver: Option<AnalysisChannel>,
+}
+
+impl EffectLayer {
+    pub fn new(style: VisualStyle) -> Self {
+        Self {
+            style,
+            params: StyleParams::defaults(style),
+            blend: BlendMode::Screen,
+            opacity: 1.0,
+            driver: None,
+        }
+    }
+}
+
+// --- Everything that makes up one "look"; decks A and B each hold one ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Look {
+    pub slot_styles: [VisualStyle; MAX_TRACKS],
+    pub slot_params: [StyleParams; MAX_TRACKS], // Tunables of each slot's style
+    pub slot_outlines: [OutlineStyle; MAX_TRACKS],
+    pub slot_layers: [Vec<EffectLayer>; MAX_TRACKS], // Drawn in order over the base style
+    pub background_layers: Vec<EffectLayer>, // Drawn in order outside every object
+    pub outline_blend: BlendMode,
+    pub shimmer: ShimmerSettings,
+    pub drum_fx: DrumFxSettings,
+    pub bloom: BloomSettings,
+    pub post_chain: PostChain,
+}
+
+impl Default for Look {
+    fn default() -> Self {
+        Self {
+            slot_styles: std::array::from_fn(VisualStyle::default_for_slot),
+            slot_params: std::array::from_fn(|slot| {
+                StyleParams::defaults(VisualStyle::default_for_slot(slot))
+            }),
+            slot_outlines: [OutlineStyle::None; MAX_TRACKS],
+            slot_layers: Default::default(),
+            background_layers: Vec::new(),
+            outline_blend: BlendMode::Normal,
+            shimmer: ShimmerSettings::default(),
+            drum_fx: DrumFxSettings::default(),
+            bloom: BloomSettings::default(),
+            post_chain: PostChain::default(),
+        }
+    }
+}
+
+// Which decks need rendering at a fader position (0 = all A, 1 = all B)
+pub fn active_decks(crossfader: f32) -> (bool, bool) {
+    (crossfader < 1.0, crossfader > 0.0)
+}
+
+// a = a * (1 - t) + b * t, in place
+pub fn blend_into(a: &mut RgbImage, b: &RgbImage, t: f32) {
+    let t = t.clamp(0.0, 1.0);
+    let wb = (t * 256.0) as u32;
+    let wa = 256 - wb;
+    for (pa, pb) in a.iter_mut().zip(b.iter()) {
+        *pa = ((*pa as u32 * wa + *pb as u32 * wb) >> 8) as u8;
+    }
+}
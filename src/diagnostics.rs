@@ -0,0 +1,242 @@
+// src/diagnostics.rs
+use crossbeam_channel::Sender;
+use log::{info, warn};
+use nokhwa::utils::ApiBackend;
+use std::{
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::live_audio;
+use crate::platform;
+
+const MODEL_REPORT_WAIT: Duration = Duration::from_secs(60); // A cold CoreML load takes a while
+const MODEL_REPORT_POLL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticStep {
+    Camera,
+    AudioDevices,
+    Loopback,
+    Model,
+}
+
+impl DiagnosticStep {
+    pub const ALL: [DiagnosticStep; 4] = [
+        DiagnosticStep::Camera,
+        DiagnosticStep::AudioDevices,
+        DiagnosticStep::Loopback,
+        DiagnosticStep::Model,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DiagnosticStep::Camera => "Camera access",
+            DiagnosticStep::AudioDevices => "Audio input devices",
+            DiagnosticStep::Loopback => "Loopback device",
+            DiagnosticStep::Model => "Model warm-up",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+// --- One finished check: what happened and what the user should do about it ---
+#[derive(Debug, Clone)]
+pub struct DiagnosticResult {
+    pub step: DiagnosticStep,
+    pub status: DiagnosticStatus,
+    pub detail: String,
+    pub hint: Option<String>,
+}
+
+impl DiagnosticResult {
+    fn new(step: DiagnosticStep, status: DiagnosticStatus, detail: impl Into<String>) -> Self {
+        Self {
+            step,
+            status,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+fn check_camera() -> DiagnosticResult {
+    let step = DiagnosticStep::Camera;
+    let backend = platform::camera_backends()[0];
+    match nokhwa::query(backend).or_else(|_| nokhwa::query(ApiBackend::Auto)) {
+        Ok(cameras) if cameras.is_empty() => {
+            DiagnosticResult::new(step, DiagnosticStatus::Fail, "No cameras found.")
+                .with_hint(platform::CAMERA_HINT)
+        }
+        Ok(cameras) => {
+            let names: Vec<String> = cameras.iter().map(|c| c.human_name()).collect();
+            DiagnosticResult::new(
+                step,
+                DiagnosticStatus::Pass,
+                format!("{} camera(s): {}", cameras.len(), names.join(", ")),
+            )
+        }
+        Err(e) => DiagnosticResult::new(
+            step,
+            DiagnosticStatus::Fail,
+            format!("Camera query failed: {}", e),
+        )
+        .with_hint("Camera permission may be denied. Grant access in System Settings and restart."),
+    }
+}
+
+fn check_audio_devices(devices: &Result<Vec<String>, String>) -> DiagnosticResult {
+    let step = DiagnosticStep::AudioDevices;
+    match devices {
+        Ok(names) if names.is_empty() => {
+            DiagnosticResult::new(step, DiagnosticStatus::Fail, "No audio input devices found.")
+                .with_hint("Connect a microphone or install a loopback driver such as BlackHole.")
+        }
+        Ok(names) => DiagnosticResult::new(
+            step,
+            DiagnosticStatus::Pass,
+            format!("{} input(s): {}", names.len(), names.join(", ")),
+        ),
+        Err(e) => DiagnosticResult::new(
+            step,
+            DiagnosticStatus::Fail,
+            format!("Could not enumerate inputs: {}", e),
+        )
+        .with_hint("Microphone permission may be denied in System Settings > Privacy & Security."),
+    }
+}
+
+fn check_loopback(
+    devices: &Result<Vec<String>, String>,
+    loopback_names: &[String],
+) -> DiagnosticResult {
+    let step = DiagnosticStep::Loopback;
+    let found = devices
+        .as_ref()
+        .ok()
+        .and_then(|names| {
+            names
+                .iter()
+                .find(|n| live_audio::is_virtual_device_name(n, loopback_names))
+        });
+    match found {
+        Some(name) => DiagnosticResult::new(
+            step,
+            DiagnosticStatus::Pass,
+            format!("Found loopback device '{}'", name),
+        ),
+        None => DiagnosticResult::new(
+            step,
+            DiagnosticStatus::Warn,
+            "No loopback device; visuals will follow the microphone.",
+        )
+        .with_hint(platform::LOOPBACK_HINT),
+    }
+}
+
+// --- How the live model came up: filled in by the segmentation thread when it loads (or swaps)
+// its model, so the self-test reports on that instance instead of loading a second copy ---
+#[derive(Debug, Clone)]
+pub enum ModelReport {
+    Ready {
+        load: Duration,
+        warm_up: Result<Duration, String>,
+    },
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ModelReportSlot(Arc<Mutex<Option<ModelReport>>>);
+
+impl ModelReportSlot {
+    pub fn set(&self, report: ModelReport) {
+        if let Ok(mut current) = self.0.lock() {
+            *current = Some(report);
+        }
+    }
+
+    fn get(&self) -> Option<ModelReport> {
+        self.0.lock().ok()?.clone()
+    }
+}
+
+// Waits for the segmentation thread while its model is still loading
+fn check_model(report: &ModelReportSlot) -> DiagnosticResult {
+    let step = DiagnosticStep::Model;
+    let wait_start = Instant::now();
+    let report = loop {
+        match report.get() {
+            Some(r) => break r,
+            None if wait_start.elapsed() >= MODEL_REPORT_WAIT => {
+                return DiagnosticResult::new(
+                    step,
+                    DiagnosticStatus::Warn,
+                    "The model is still loading.",
+                )
+                .with_hint("Run the check again once the splash screen is gone.");
+            }
+            None => thread::sleep(MODEL_REPORT_POLL),
+        }
+    };
+    match report {
+        ModelReport::Failed(e) => DiagnosticResult::new(
+            step,
+            DiagnosticStatus::Fail,
+            format!("Model load failed: {}", e),
+        )
+        .with_hint("Check that models/FastSAM-s.onnx exists and the ONNX Runtime library is installed."),
+        ModelReport::Ready {
+            load,
+            warm_up: Ok(warm_up),
+        } => DiagnosticResult::new(
+            step,
+            DiagnosticStatus::Pass,
+            format!("Loaded in {:.2?}, warm-up inference {:.2?}", load, warm_up),
+        ),
+        ModelReport::Ready {
+            warm_up: Err(e), ..
+        } => DiagnosticResult::new(
+            step,
+            DiagnosticStatus::Fail,
+            format!("Warm-up inference failed: {}", e),
+        )
+        .with_hint("Try a different execution device (CPU) or model dtype (fp32)."),
+    }
+}
+
+// --- Runs every check on a background thread, streaming results as they complete ---
+pub fn start_self_test(
+    result_sender: Sender<DiagnosticResult>,
+    model_report: ModelReportSlot,
+    loopback_names: Vec<String>,
+    ctx: egui::Context,
+) -> JoinHandle<()> {
+    info!("Starting diagnostic self-test.");
+    thread::spawn(move || {
+        let send = |r: DiagnosticResult| {
+            if r.status != DiagnosticStatus::Pass {
+                warn!("Self-test {}: {}", r.step.label(), r.detail);
+            }
+            let _ = result_sender.send(r);
+            ctx.request_repaint();
+        };
+        send(check_camera());
+        let devices = live_audio::list_input_devices().map_err(|e| e.to_string());
+        send(check_audio_devices(&devices));
+        send(check_loopback(&devices, &loopback_names));
+        send(check_model(&model_report));
+        info!("Diagnostic self-test finished.");
+    })
+}
@@ -0,0 +1,175 @@
+// src/ensemble.rs
+// Dual-model ensemble. The regular (fast) model keeps running in the segmentation loop for
+// responsive tracking; a heavier segmenter runs here on its own thread at 1-2 Hz, and the
+// tracking stage borrows its masks for the fast boxes they overlap (see fuse_refined_masks).
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender, TrySendError};
+use image::{DynamicImage, RgbImage};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+use usls::{models::YOLO, Options};
+
+use crate::segmentation::{collect_detections, Detection};
+use crate::supervisor::StopSignal;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnsembleConfig {
+    pub model_file: Option<PathBuf>, // Heavy segmentation model, e.g. models/FastSAM-x.onnx
+    pub refresh_hz: f32,             // Refined masks per second, at most
+}
+
+impl Default for EnsembleConfig {
+    fn default() -> Self {
+        Self {
+            model_file: None,
+            refresh_hz: 1.5,
+        }
+    }
+}
+
+// A refined pass is lent to the fast boxes for this many refresh intervals after it arrives.
+// The heavy model's own inference time doesn't count: at 1-2 Hz it is often longer than a
+// fixed age limit measured from the capture would allow
+const LEND_INTERVALS: u32 = 2;
+
+// One pass of the heavy model
+pub struct RefinedMasks {
+    pub detections: Vec<Detection>,
+}
+
+type Job = Arc<RgbImage>;
+
+// Segmentation-loop side of the refiner thread; dropping it ends the thread
+pub struct Refiner {
+    jobs: Sender<Job>,
+    results: Receiver<RefinedMasks>,
+    interval: Duration,
+    last_offer: Option<Instant>,
+    latest: Option<(Instant, RefinedMasks)>, // With its arrival time
+}
+
+impl Refiner {
+    pub fn start(options: Options, refresh_hz: f32, stop_signal: StopSignal) -> Self {
+        // One job in flight: frames offered while the model is busy are simply skipped
+        let (jobs, job_rx) = bounded::<Job>(1);
+        let (result_tx, results) = unbounded();
+        thread::spawn(move || refiner_loop(options, job_rx, result_tx, stop_signal));
+        Self {
+            jobs,
+            results,
+            interval: Duration::from_secs_f32(1.0 / refresh_hz.clamp(0.1, 10.0)),
+            last_offer: None,
+            latest: None,
+        }
+    }
+
+    // Hands the frame over when the refresh interval has passed and the model is idle
+    pub fn offer(&mut self, frame: &Arc<RgbImage>) {
+        if self.last_offer.is_some_and(|t| t.elapsed() < self.interval) {
+            return;
+        }
+        match self.jobs.try_send(frame.clone()) {
+            Ok(()) => self.last_offer = Some(Instant::now()),
+            Err(TrySendError::Full(_)) => {}
+            Err(TrySendError::Disconnected(_)) => self.last_offer = Some(Instant::now()),
+        }
+    }
+
+    // Most recent refined pass, until the next one replaces it or it has gone unreplaced for
+    // LEND_INTERVALS refresh intervals (the refiner stalled or failed)
+    pub fn latest(&mut self) -> Option<&RefinedMasks> {
+        self.latest_at(Instant::now())
+    }
+
+    fn latest_at(&mut self, now: Instant) -> Option<&RefinedMasks> {
+        if let Some(result) = self.results.try_iter().last() {
+            self.latest = Some((now, result));
+        }
+        let lend_for = self.interval * LEND_INTERVALS;
+        self.latest
+            .as_ref()
+            .filter(|(arrived, _)| now.saturating_duration_since(*arrived) <= lend_for)
+            .map(|(_, result)| result)
+    }
+}
+
+fn refiner_loop(
+    options: Options,
+    jobs: Receiver<Job>,
+    results: Sender<RefinedMasks>,
+    stop_signal: StopSignal,
+) {
+    let mut model = match YOLO::new(options) {
+        Ok(m) => m,
+        Err(e) => {
+            error!("Refiner model load failed: {}", e);
+            return;
+        }
+    };
+    info!("Refiner model loaded.");
+    // Ends when the Refiner is dropped (sender gone) or the app stops
+    while let Ok(frame) = jobs.recv() {
+        if stop_signal.is_stopped() {
+            break;
+        }
+        let size = frame.dimensions();
+        let input = [DynamicImage::ImageRgb8((*frame).clone())];
+        match model.forward(&input) {
+            Ok(ys) => {
+                let detections = ys
+                    .first()
+                    .map(|y| collect_detections(y, None, size))
+                    .unwrap_or_default();
+                let _ = results.send(RefinedMasks { detections });
+            }
+            Err(e) => warn!("Refiner inference failed: {}", e),
+        }
+    }
+    info!("Refiner thread finished.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn refiner(refresh_hz: f32) -> (Refiner, Sender<RefinedMasks>) {
+        let (jobs, _) = bounded(1);
+        let (result_tx, results) = unbounded();
+        let refiner = Refiner {
+            jobs,
+            results,
+            interval: Duration::from_secs_f32(1.0 / refresh_hz),
+            last_offer: None,
+            latest: None,
+        };
+        (refiner, result_tx)
+    }
+
+    #[test]
+    fn slow_passes_are_lent_until_replaced() {
+        // 2 Hz refresh; each pass arrives long after its frame was captured
+        let (mut refiner, results) = refiner(2.0);
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+        let pass = || RefinedMasks {
+            detections: Vec::new(),
+        };
+        assert!(refiner.latest_at(at(0)).is_none());
+        results.send(pass()).unwrap();
+        assert!(refiner.latest_at(at(800)).is_some());
+        assert!(refiner.latest_at(at(1700)).is_some());
+
+        // The next pass takes over and restarts the window
+        results.send(pass()).unwrap();
+        assert!(refiner.latest_at(at(1900)).is_some());
+        assert!(refiner.latest_at(at(2800)).is_some());
+        // Unreplaced for more than two intervals: the refiner has stalled
+        assert!(refiner.latest_at(at(3000)).is_none());
+    }
+}
@@ -0,0 +1,258 @@
+// src/file_source.rs
+// Media files as a show source: ffmpeg decodes them in real time (-re) into the same channels the
+// camera and the audio capture feed, so a dropped clip goes through segmentation and analysis
+// like live input. Video is letterboxed to the camera's frame size; audio is converted to the
+// capture's rate and channel count.
+use anyhow::{Context, Result};
+use image::RgbImage;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Child, ChildStdout, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+use crate::camera::{CameraThreadMsg, LatestFrame};
+use crate::metrics;
+use crate::pipeline::{PolicySender, TrySendError};
+
+const VIDEO_WIDTH: u32 = 640; // Same as the camera request, so masks and effects size alike
+const VIDEO_HEIGHT: u32 = 480;
+const AUDIO_CHUNK_FRAMES: usize = 512; // About a capture callback's worth
+pub const VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "mkv", "webm", "avi"];
+pub const AUDIO_EXTENSIONS: &[&str] = &["wav", "mp3", "flac", "aiff", "m4a", "ogg"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FileSourceConfig {
+    pub ffmpeg_path: String,
+    pub looping: bool, // Restart at the end instead of handing back to the live inputs
+}
+
+impl Default for FileSourceConfig {
+    fn default() -> Self {
+        Self {
+            ffmpeg_path: "ffmpeg".to_string(),
+            looping: true,
+        }
+    }
+}
+
+pub fn is_media_file(path: &Path) -> bool {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    let extension = extension.as_str();
+    VIDEO_EXTENSIONS.contains(&extension) || AUDIO_EXTENSIONS.contains(&extension)
+}
+
+fn is_video_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| VIDEO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+}
+
+// --- Where decoded media goes: the camera's channel, and the raw audio channel when capturing ---
+pub struct FileSourceSinks {
+    pub frames: PolicySender<CameraThreadMsg>,
+    pub latest_frame: LatestFrame,
+    pub audio: Option<(PolicySender<Vec<f32>>, u32, u16)>, // Sender, capture rate, channels
+}
+
+// One ffmpeg child and the thread draining its stdout
+struct Decoder {
+    child: Child,
+    thread: Option<JoinHandle<()>>,
+    done: Arc<AtomicBool>,
+}
+
+impl Decoder {
+    fn spawn(
+        config: &FileSourceConfig,
+        path: &Path,
+        output_args: &[String],
+        pump: impl FnOnce(ChildStdout) + Send + 'static,
+    ) -> Result<Self> {
+        let mut command = Command::new(&config.ffmpeg_path);
+        command.args(["-hide_banner", "-loglevel", "error", "-nostdin"]);
+        if config.looping {
+            command.args(["-stream_loop", "-1"]);
+        }
+        let mut child = command
+            .arg("-re") // Decode at the file's own pace, like a live source
+            .arg("-i")
+            .arg(path)
+            .args(output_args)
+            .arg("pipe:1")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to launch {}", config.ffmpeg_path))?;
+        let stdout = child.stdout.take().context("ffmpeg stdout unavailable")?;
+        let done = Arc::new(AtomicBool::new(false));
+        let thread_done = done.clone();
+        let thread = thread::spawn(move || {
+            pump(stdout);
+            thread_done.store(true, Ordering::Relaxed);
+        });
+        Ok(Self {
+            child,
+            thread: Some(thread),
+            done,
+        })
+    }
+
+    fn is_done(&self) -> bool {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    // Killing ffmpeg closes its stdout, which ends the pump
+    fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(handle) = self.thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn pump_video(
+    mut stdout: ChildStdout,
+    frames: PolicySender<CameraThreadMsg>,
+    latest: LatestFrame,
+) {
+    let mut buf = vec![0u8; (VIDEO_WIDTH * VIDEO_HEIGHT * 3) as usize];
+    let mut frame_index: u64 = 0;
+    while stdout.read_exact(&mut buf).is_ok() {
+        frame_index += 1;
+        let Some(image) = RgbImage::from_raw(VIDEO_WIDTH, VIDEO_HEIGHT, buf.clone()) else {
+            break;
+        };
+        let frame = Arc::new(image);
+        latest.publish(frame.clone());
+        if let Err(TrySendError::Closed(_)) =
+            frames.try_send(CameraThreadMsg::Frame(frame, Instant::now(), frame_index))
+        {
+            break;
+        }
+        metrics::global().inc(metrics::CAMERA_FRAMES, 1);
+    }
+}
+
+fn pump_audio(mut stdout: ChildStdout, sender: PolicySender<Vec<f32>>, channels: u16) {
+    let mut buf = vec![0u8; AUDIO_CHUNK_FRAMES * channels.max(1) as usize * 4];
+    while stdout.read_exact(&mut buf).is_ok() {
+        let samples = buf
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        match sender.try_send(samples) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => warn!("Audio channel full, dropping file samples."),
+            Err(TrySendError::Closed(_)) => break,
+        }
+    }
+}
+
+pub struct FileSource {
+    path: PathBuf,
+    video: Option<Decoder>,
+    audio: Option<Decoder>,
+}
+
+impl FileSource {
+    // Video files feed frames (and their soundtrack, if any); audio files only the analysis
+    pub fn start(config: &FileSourceConfig, path: &Path, sinks: FileSourceSinks) -> Result<Self> {
+        let video = if is_video_file(path) {
+            let scale = format!(
+                concat!(
+                    "scale={w}:{h}:force_original_aspect_ratio=decrease,",
+                    "pad={w}:{h}:(ow-iw)/2:(oh-ih)/2"
+                ),
+                w = VIDEO_WIDTH,
+                h = VIDEO_HEIGHT
+            );
+            let args = ["-an", "-vf", scale.as_str(), "-pix_fmt", "rgb24", "-f", "rawvideo"]
+                .map(String::from);
+            let (frames, latest) = (sinks.frames, sinks.latest_frame);
+            Some(Decoder::spawn(config, path, &args, move |stdout| {
+                pump_video(stdout, frames, latest)
+            })?)
+        } else {
+            None
+        };
+        let audio = match sinks.audio {
+            Some((sender, rate, channels)) => {
+                let args = [
+                    "-vn".to_string(),
+                    "-ac".to_string(),
+                    channels.to_string(),
+                    "-ar".to_string(),
+                    rate.to_string(),
+                    "-f".to_string(),
+                    "f32le".to_string(),
+                ];
+                Some(Decoder::spawn(config, path, &args, move |stdout| {
+                    pump_audio(stdout, sender, channels)
+                })?)
+            }
+            None => None,
+        };
+        info!(
+            "Playing {} as a source (video: {}, audio: {})",
+            path.display(),
+            video.is_some(),
+            audio.is_some()
+        );
+        Ok(Self {
+            path: path.to_path_buf(),
+            video,
+            audio,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // The camera stays released while this is true
+    pub fn feeds_video(&self) -> bool {
+        self.video.as_ref().is_some_and(|d| !d.is_done())
+    }
+
+    // The live audio input stays bypassed while this is true; false as well when the file has
+    // no audio stream (ffmpeg exits straight away)
+    pub fn feeds_audio(&self) -> bool {
+        self.audio.as_ref().is_some_and(|d| !d.is_done())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.feeds_video() && !self.feeds_audio()
+    }
+
+    pub fn stop(&mut self) {
+        for decoder in [self.video.as_mut(), self.audio.as_mut()].into_iter().flatten() {
+            decoder.stop();
+        }
+        info!("File source {} stopped", self.path.display());
+    }
+}
+
+impl Drop for FileSource {
+    fn drop(&mut self) {
+        for decoder in [self.video.as_mut(), self.audio.as_mut()].into_iter().flatten() {
+            if decoder.thread.is_some() {
+                decoder.stop();
+            }
+        }
+    }
+}
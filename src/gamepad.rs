@@ -0,0 +1,97 @@
+// src/gamepad.rs
+// Hands-on control when a mouse is awkward on stage: the left stick moves a selection cursor
+// over the video, the triggers ride the intensity gain and the face buttons edit slot prompts.
+use anyhow::{anyhow, Result};
+use gilrs::{Axis, Button, EventType, Gilrs};
+use log::info;
+
+const STICK_DEADZONE: f32 = 0.15;
+const CURSOR_SPEED: f32 = 0.6; // Frame widths per second at full deflection
+const GAIN_RATE: f32 = 1.0; // Gain change per second with a trigger fully pressed
+
+// --- What a pad asks the UI to do; the UI owns the prompts and settings ---
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GamepadCommand {
+    Include((f32, f32)), // A: include point at the cursor
+    Exclude((f32, f32)), // B: exclude point at the cursor
+    ClearSlot,           // X: release the selected slot
+    ToggleLock,          // Y: lock/unlock the selected slot
+    PrevSlot,            // D-pad left / left bumper
+    NextSlot,            // D-pad right / right bumper
+    AdjustGain(f32),     // Triggers: right raises, left lowers
+}
+
+pub struct GamepadInput {
+    gilrs: Gilrs,
+    cursor: (f32, f32), // Normalized frame position
+}
+
+impl GamepadInput {
+    pub fn new() -> Result<Self> {
+        let gilrs = Gilrs::new().map_err(|e| anyhow!("Gamepad init failed: {}", e))?;
+        for (_, pad) in gilrs.gamepads() {
+            info!("Gamepad connected: {}", pad.name());
+        }
+        Ok(Self {
+            gilrs,
+            cursor: (0.5, 0.5),
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.gilrs.gamepads().next().is_some()
+    }
+
+    pub fn cursor(&self) -> (f32, f32) {
+        self.cursor
+    }
+
+    // Drains button events and integrates the analog controls over `dt` seconds
+    pub fn poll(&mut self, dt: f32) -> Vec<GamepadCommand> {
+        let mut commands = Vec::new();
+        while let Some(event) = self.gilrs.next_event() {
+            match event.event {
+                EventType::Connected => {
+                    info!("Gamepad connected: {}", self.gilrs.gamepad(event.id).name());
+                }
+                EventType::Disconnected => info!("Gamepad disconnected"),
+                EventType::ButtonPressed(button, _) => {
+                    let command = match button {
+                        Button::South => Some(GamepadCommand::Include(self.cursor)),
+                        Button::East => Some(GamepadCommand::Exclude(self.cursor)),
+                        Button::West => Some(GamepadCommand::ClearSlot),
+                        Button::North => Some(GamepadCommand::ToggleLock),
+                        Button::DPadLeft | Button::LeftTrigger => Some(GamepadCommand::PrevSlot),
+                        Button::DPadRight | Button::RightTrigger => Some(GamepadCommand::NextSlot),
+                        _ => None,
+                    };
+                    commands.extend(command);
+                }
+                _ => {}
+            }
+        }
+        // Analog state is read from the first pad; several pads would fight over the cursor
+        let Some((_, pad)) = self.gilrs.gamepads().next() else {
+            return commands;
+        };
+        let stick = |axis| {
+            let v = pad.value(axis);
+            if v.abs() < STICK_DEADZONE {
+                0.0
+            } else {
+                v
+            }
+        };
+        let (dx, dy) = (stick(Axis::LeftStickX), -stick(Axis::LeftStickY)); // Stick up is +y
+        self.cursor = (
+            (self.cursor.0 + dx * CURSOR_SPEED * dt).clamp(0.0, 1.0),
+            (self.cursor.1 + dy * CURSOR_SPEED * dt).clamp(0.0, 1.0),
+        );
+        let trigger = |button| pad.button_data(button).map_or(0.0, |d| d.value());
+        let gain = trigger(Button::RightTrigger2) - trigger(Button::LeftTrigger2);
+        if gain.abs() > STICK_DEADZONE {
+            commands.push(GamepadCommand::AdjustGain(gain * GAIN_RATE * dt));
+        }
+        commands
+    }
+}
@@ -0,0 +1,732 @@
+// src/i18n.rs
+// UI translations as a plain key map. The key is the English text itself, so a string without
+// a translation simply shows in English.
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        OnceLock,
+    },
+};
+
+static CURRENT: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Spanish];
+
+    // Each language named in itself, so the picker is readable whatever is selected
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    fn table(self) -> Option<&'static HashMap<&'static str, &'static str>> {
+        static SPANISH_MAP: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+        match self {
+            Language::English => None,
+            Language::Spanish => {
+                Some(SPANISH_MAP.get_or_init(|| SPANISH.iter().copied().collect()))
+            }
+        }
+    }
+}
+
+pub fn set_language(language: Language) {
+    CURRENT.store(language as u8, Ordering::Relaxed);
+}
+
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        x if x == Language::Spanish as u8 => Language::Spanish,
+        _ => Language::English,
+    }
+}
+
+// Translates an English UI string into the current language
+pub fn tr(text: &'static str) -> &'static str {
+    language()
+        .table()
+        .and_then(|table| table.get(text).copied())
+        .unwrap_or(text)
+}
+
+const SPANISH: &[(&str, &str)] = &[
+    // --- Menu and panels ---
+    ("File", "Archivo"),
+    ("Run self-test", "Autodiagnóstico"),
+    ("Quit", "Salir"),
+    ("⚙ Settings", "⚙ Ajustes"),
+    ("Permissions", "Permisos"),
+    ("Audio Status", "Estado del audio"),
+    ("Routing", "Enrutado"),
+    ("Styles", "Estilos"),
+    ("Plugins", "Plugins"),
+    ("Post", "Posproceso"),
+    ("Grade", "Etalonaje"),
+    ("Text Overlay", "Texto superpuesto"),
+    ("Sprite", "Sprite"),
+    ("HUD", "HUD"),
+    ("Region of Interest", "Región de interés"),
+    ("Projection", "Proyección"),
+    ("Crossfader", "Crossfader"),
+    ("Automation", "Automatización"),
+    ("Cues", "Cues"),
+    ("Select", "Selección"),
+    ("Mask Brush", "Pincel de máscara"),
+    ("Session", "Sesión"),
+    ("Info", "Información"),
+    // --- Common actions ---
+    ("Load", "Cargar"),
+    ("Save", "Guardar"),
+    ("Clear", "Borrar"),
+    ("Reset", "Restablecer"),
+    ("Restart", "Reiniciar"),
+    ("Done", "Hecho"),
+    ("Swap", "Intercambiar"),
+    ("Render", "Renderizar"),
+    ("Re-run", "Repetir"),
+    ("Compare…", "Comparar…"),
+    ("Store A → B", "Guardar A → B"),
+    ("Set target", "Fijar objetivo"),
+    ("Clear ROI", "Borrar ROI"),
+    ("Open System Settings", "Abrir Ajustes del Sistema"),
+    ("▶ Play", "▶ Reproducir"),
+    ("⏺ Rec", "⏺ Grabar"),
+    ("⏹ Stop", "⏹ Parar"),
+    ("⏹ Stop rec", "⏹ Parar grabación"),
+    ("Start show", "Iniciar show"),
+    ("Stop show", "Parar show"),
+    ("GO ▶", "GO ▶"),
+    ("Record session", "Grabar sesión"),
+    ("Stop recording", "Parar grabación"),
+    ("Record keyed", "Grabar con alfa"),
+    ("Stop keyed", "Parar con alfa"),
+    ("Calibrate latency", "Calibrar latencia"),
+    // --- Slots and prompts ---
+    ("Slot", "Ranura"),
+    ("Switch looks:", "Cambiar looks:"),
+    ("Tap", "Marcar"),
+    ("Auto", "Automático"),
+    ("BPM from beat detection", "BPM por detección de pulsos"),
+    ("Downbeat", "Primer tiempo"),
+    ("Normalization", "Normalización"),
+    ("Adaptive window", "Ventana adaptativa"),
+    ("Fixed reference", "Referencia fija"),
+    ("Slow AGC", "CAG lento"),
+    ("Window (hops)", "Ventana (saltos)"),
+    ("Reference (dBFS)", "Referencia (dBFS)"),
+    ("Floor (dBFS)", "Suelo (dBFS)"),
+    ("Time (s)", "Tiempo (s)"),
+    ("Overlap:", "Solapamiento:"),
+    ("Analysis", "Análisis"),
+    ("Playing test tone…", "Reproduciendo tono de prueba…"),
+    ("Find loopback input", "Buscar entrada de loopback"),
+    ("Capturing from:", "Capturando desde:"),
+    ("Default output", "Salida predeterminada"),
+    ("Record audio", "Grabar audio"),
+    ("Stop audio recording", "Detener grabación de audio"),
+    ("Saved", "Guardado"),
+    ("Open project…", "Abrir proyecto…"),
+    ("Save project", "Guardar proyecto"),
+    ("Recent projects", "Proyectos recientes"),
+    ("Project", "Proyecto"),
+    ("Open", "Abrir"),
+    (
+        "Drop a model (.onnx), LUT (.cube), project (.scb) or a video/audio file",
+        "Suelta un modelo (.onnx), LUT (.cube), proyecto (.scb) o un archivo de vídeo/audio",
+    ),
+    ("Play media file…", "Reproducir archivo multimedia…"),
+    ("Save project as…", "Guardar proyecto como…"),
+    ("Choose model…", "Elegir modelo…"),
+    ("Export preset…", "Exportar preset…"),
+    ("Import preset…", "Importar preset…"),
+    ("Save as…", "Guardar como…"),
+    ("Layers", "Capas"),
+    ("Add layer", "Añadir capa"),
+    ("Background layers", "Capas de fondo"),
+    ("Opacity", "Opacidad"),
+    ("Outline blend", "Fusión del contorno"),
+    ("Blackout", "Apagón"),
+    ("Freeze", "Congelar"),
+    ("Blackout fade (s)", "Fundido del apagón (s)"),
+    ("Camera", "Cámara"),
+    ("Effects", "Efectos"),
+    ("Blend", "Fusión"),
+    ("Lock", "Bloquear"),
+    ("Unlock", "Desbloquear"),
+    ("Release slot", "Liberar ranura"),
+    ("Track and zoom", "Seguir y ampliar"),
+    ("Refine mask edges", "Refinar bordes de máscara"),
+    ("Edge radius", "Radio de borde"),
+    ("High-quality mask refresh", "Refresco de máscaras de alta calidad"),
+    ("＋ Include", "＋ Incluir"),
+    ("－ Exclude", "－ Excluir"),
+    (
+        "Arrows nudge the slot's box, shift+arrows resize",
+        "Flechas mueven la caja, mayús+flechas la redimensionan",
+    ),
+    ("Listening for clicks…", "Escuchando clics…"),
+    // --- Parameters ---
+    ("Strength", "Intensidad"),
+    ("Size", "Tamaño"),
+    ("Amount", "Cantidad"),
+    ("Width", "Ancho"),
+    ("Height", "Alto"),
+    ("Threshold", "Umbral"),
+    ("Radius", "Radio"),
+    ("Jitter", "Variación"),
+    ("Gain", "Ganancia"),
+    ("Intensity gain", "Ganancia de intensidad"),
+    ("Noise gate", "Puerta de ruido"),
+    ("Floor", "Suelo"),
+    ("Hysteresis", "Histéresis"),
+    ("Curve", "Curva"),
+    ("Gamma", "Gamma"),
+    ("Contrast", "Contraste"),
+    ("Exposure (stops)", "Exposición (pasos)"),
+    ("Offset (ms)", "Desfase (ms)"),
+    ("Bass hold (s)", "Retención de graves (s)"),
+    ("Speed × intensity", "Velocidad × intensidad"),
+    ("Reacts to", "Reacciona a"),
+    ("Anchor", "Anclaje"),
+    ("Sheet grid", "Cuadrícula"),
+    ("Frames", "Fotogramas"),
+    ("Full frame", "Cuadro completo"),
+    ("Display scale", "Escala de vista"),
+    ("Pace frames", "Ritmo constante"),
+    ("Partial texture uploads", "Subida parcial de texturas"),
+    ("Effect quality", "Calidad de efectos"),
+    ("GPU effects layer", "Capa de efectos por GPU"),
+    ("Send test alert", "Enviar alerta de prueba"),
+    ("Installation", "Instalación"),
+    ("Choose profile…", "Elegir perfil…"),
+    ("Change it later from File → Choose profile…", "Cámbialo luego en Archivo → Elegir perfil…"),
+    ("Full", "Completa"),
+    ("Half", "Media"),
+    ("Quarter", "Cuarto"),
+    ("Interpolate boxes", "Interpolar cajas"),
+    // --- Toggles ---
+    ("Add", "Añadir"),
+    ("Auto levels", "Niveles automáticos"),
+    ("Bloom", "Resplandor"),
+    ("Burn in performance stats", "Incrustar estadísticas"),
+    ("Burn into output", "Incrustar en la salida"),
+    ("Calibrate", "Calibrar"),
+    ("Clock", "Reloj"),
+    ("Constant", "Constante"),
+    ("Corner pin", "Corner pin"),
+    ("Countdown", "Cuenta atrás"),
+    ("Erase", "Borrar"),
+    ("Every frame", "Cada fotograma"),
+    ("Heat shimmer", "Calima"),
+    ("Hi-hat sparkle", "Destellos de charles"),
+    ("Kick ring", "Anillo de bombo"),
+    (
+        "A ring bursts out of every object on each kick",
+        "Un anillo sale de cada objeto con cada bombo",
+    ),
+    (
+        "Glints flash across every object on each hi-hat",
+        "Destellos cruzan cada objeto con cada charles",
+    ),
+    ("Masked output", "Salida enmascarada"),
+    ("Mute", "Silenciar"),
+    ("Off", "Apagado"),
+    ("Set timer", "Temporizador"),
+    ("Show sprite", "Mostrar sprite"),
+    ("Show text", "Mostrar texto"),
+    ("Tone map", "Mapeo tonal"),
+    // --- Status ---
+    ("Initializing...", "Inicializando..."),
+    ("Inference:", "Inferencia:"),
+    (
+        "Pipeline (queued / dropped):",
+        "Pipeline (en cola / descartados):",
+    ),
+    ("Texture size unknown.", "Tamaño de textura desconocido."),
+    ("TC: not chasing", "TC: sin seguimiento"),
+    ("Cam Res: Waiting...", "Res. cámara: esperando..."),
+    ("Cam Res: Initializing...", "Res. cámara: inicializando..."),
+    ("Cam Res: Error", "Res. cámara: error"),
+    ("Check status panel.", "Revisa el panel de estado."),
+    (
+        "Enable it for this app, then restart.",
+        "Actívalo para esta app y reinicia.",
+    ),
+    (
+        "Snapshot both looks, then render.",
+        "Captura ambos looks y renderiza.",
+    ),
+    ("Initializing stream...", "Iniciando stream..."),
+    ("Camera Error:", "Error de cámara:"),
+    ("Processing Error:", "Error de procesado:"),
+    ("MODEL LAGGING · raw camera", "MODELO CON RETRASO · cámara sin procesar"),
+    ("Closed · opens", "Cerrado · abre"),
+    ("Cam Res", "Res. cámara"),
+    ("UI FPS", "FPS de la UI"),
+    ("Live pipeline", "Pipeline en directo"),
+    ("Camera thread", "Hilo de cámara"),
+    ("Segmentation thread", "Hilo de segmentación"),
+    ("Capturing", "Capturando"),
+    ("Replaying", "Reproduciendo"),
+    ("Error", "Error"),
+    ("Disabled/Stopped", "Desactivado/Detenido"),
+    ("Monitor failed", "Falló la escucha"),
+    ("Audio recording failed", "Falló la grabación de audio"),
+    ("No loopback found", "No se encontró loopback"),
+    ("Calibration failed", "Falló la calibración"),
+    ("LTC input failed", "Falló la entrada LTC"),
+    ("Measured round trip", "Ida y vuelta medida"),
+    ("MIDI inputs", "Entradas MIDI"),
+    ("Link beat", "Pulso de Link"),
+    ("Link stopped", "Link detenido"),
+    ("peers", "pares"),
+    ("allowed", "permitido"),
+    ("waiting for approval", "esperando aprobación"),
+    ("access denied", "acceso denegado"),
+    ("Loading model", "Cargando modelo"),
+    ("Playing", "Reproduciendo"),
+    ("no file path to load from", "sin ruta de archivo que cargar"),
+    ("not a file", "no es un archivo"),
+    ("unsupported file type", "tipo de archivo no compatible"),
+    // --- Labels ---
+    ("Choose a Profile", "Elige un perfil"),
+    ("Setup Check", "Comprobación de la instalación"),
+    ("Compare Looks", "Comparar looks"),
+    (
+        "Device picks, look, automation, cues, LUT/sprite paths and layout",
+        "Dispositivos, look, automatización, cues, rutas de LUT/sprite y disposición",
+    ),
+    ("Snapshot", "Captura"),
+    ("None", "Ninguno"),
+    ("Outline", "Contorno"),
+    ("Clear slot", "Vaciar slot"),
+    ("dabs", "toques"),
+    (
+        "Dab limit reached; clear the slot to paint again",
+        "Límite de toques alcanzado; vacía el slot para volver a pintar",
+    ),
+    ("slot", "slot"),
+    ("Band / song title", "Grupo / título de la canción"),
+    ("Lyrics", "Letra"),
+    ("lines", "líneas"),
+    ("Monitor", "Escucha"),
+    ("Every N frames", "Cada N fotogramas"),
+    ("Every T ms", "Cada T ms"),
+    ("Pick…", "Elegir…"),
+    ("Rescan", "Volver a buscar en"),
+    ("Move a control…", "Mueve un control…"),
+    ("MIDI learn", "Aprender MIDI"),
+    ("ch", "can."),
+    ("Take", "Toma"),
+    ("moves", "movimientos"),
+    ("Undo", "Deshacer"),
+    ("Redo", "Rehacer"),
+    // --- Hover texts ---
+    ("Store the current look for this side", "Guarda el look actual para este lado"),
+    ("Apply both looks to the next frame", "Aplica ambos looks al siguiente fotograma"),
+    ("Set stream.rtmp_url in the config file", "Define stream.rtmp_url en la configuración"),
+    (
+        "Graded visuals with alpha from the tracked masks, via ffmpeg",
+        "Visuales etalonados con alfa de las máscaras seguidas, vía ffmpeg",
+    ),
+    (
+        "Air ripples around every object once the bass stays loud",
+        "Ondas de aire alrededor de cada objeto mientras el bajo sigue fuerte",
+    ),
+    (
+        "Keep this slot's object; never reassign it",
+        "Mantiene el objeto de este slot; nunca lo reasigna",
+    ),
+    (
+        "Run the model only around prompted/locked slots while all are in view",
+        "Ejecuta el modelo solo alrededor de los slots marcados o bloqueados mientras se vean",
+    ),
+    (
+        "Refine masks with ensemble.model_file a few times per second",
+        "Refina las máscaras con ensemble.model_file unas veces por segundo",
+    ),
+    (
+        "Set ensemble.model_file in the config to enable",
+        "Define ensemble.model_file en la configuración para activarlo",
+    ),
+    (
+        "Glow around bright pixels, scaled by overall loudness",
+        "Resplandor alrededor de los píxeles brillantes, según el volumen general",
+    ),
+    (
+        "Columns × rows of a single-image sprite sheet (ignored for folders)",
+        "Columnas × filas de una hoja de sprites en una imagen (se ignora en carpetas)",
+    ),
+    (
+        "Plays the captured audio; avoid an output that feeds the capture input",
+        "Reproduce el audio capturado; evita una salida que alimente la entrada de captura",
+    ),
+    (
+        "Writes the raw capture (all input channels) to recordings/",
+        "Escribe la captura sin procesar (todos los canales) en recordings/",
+    ),
+    (
+        "Plays a short tone and picks the input that carries system audio",
+        "Reproduce un tono corto y elige la entrada que lleva el audio del sistema",
+    ),
+    (
+        "Plays clicks through the default output; route it into the capture device",
+        "Reproduce clics por la salida predeterminada; enrútala al dispositivo de captura",
+    ),
+    (
+        "Negative = look-ahead (extrapolated), positive = delay the visuals",
+        "Negativo = anticipación (extrapolada), positivo = retrasa los visuales",
+    ),
+    (
+        "Scales the level bands after the curves",
+        "Escala las bandas de nivel después de las curvas",
+    ),
+    ("Gamepad triggers adjust this", "Los gatillos del mando lo ajustan"),
+    (
+        "Each band against its own max over this many hops",
+        "Cada banda frente a su propio máximo en estos saltos",
+    ),
+    ("Level that reads as full intensity", "Nivel que cuenta como intensidad plena"),
+    ("Level that reads as zero", "Nivel que cuenta como cero"),
+    (
+        "Louder sections read louder for about this long",
+        "Las secciones más fuertes suenan más fuertes durante este tiempo",
+    ),
+    (
+        "⅓-octave bands weigh every octave alike, as the ear does",
+        "Las bandas de ⅓ de octava pesan igual cada octava, como el oído",
+    ),
+    (
+        "Longer windows resolve bass better but react later",
+        "Ventanas más largas resuelven mejor los graves pero reaccionan después",
+    ),
+    (
+        "Below this the band outputs exactly zero; 0 = off",
+        "Por debajo de esto la banda da exactamente cero; 0 = apagado",
+    ),
+    (
+        "An open gate closes only this far below the floor",
+        "Una puerta abierta solo se cierra a esta distancia bajo el suelo",
+    ),
+    (
+        "Normalization is saturating: the band sits at its max",
+        "La normalización satura: la banda está en su máximo",
+    ),
+    (
+        "Shapes how this channel's 0-1 intensity drives the visuals",
+        "Define cómo la intensidad 0-1 de este canal mueve los visuales",
+    ),
+    (
+        "< 1 lifts quiet passages, > 1 saves the effect for loud ones",
+        "< 1 realza los pasajes suaves, > 1 reserva el efecto para los fuertes",
+    ),
+    (
+        "Drag the handles on the video onto the surface's corners",
+        "Arrastra los tiradores del vídeo a las esquinas de la superficie",
+    ),
+    (
+        "Black outside the tracked objects: aim the projector back at the scene",
+        "Negro fuera de los objetos seguidos: apunta el proyector a la escena",
+    ),
+    (
+        "Shift+B: fade the output to black, and back",
+        "Mayús+B: funde la salida a negro y vuelve",
+    ),
+    (
+        "Shift+F: hold the current camera frame and audio analysis",
+        "Mayús+F: congela el fotograma de cámara y el análisis de audio",
+    ),
+    (
+        "Tap along with the beat to set the tempo",
+        "Pulsa al ritmo para fijar el tempo",
+    ),
+    (
+        "Press on the one: bars and phrases are counted from here",
+        "Pulsa en el uno: compases y frases se cuentan desde aquí",
+    ),
+    (
+        "Present at even intervals from capture timestamps instead of always the newest frame",
+        "Presenta a intervalos regulares según la captura en vez del fotograma más nuevo",
+    ),
+    (
+        "Move boxes along their velocity on frames the model skips",
+        "Mueve las cajas según su velocidad en los fotogramas que el modelo salta",
+    ),
+    (
+        "Roll off highlights instead of clipping them to white",
+        "Suaviza las altas luces en vez de recortarlas a blanco",
+    ),
+    (
+        "Follow the frame's black and white points",
+        "Sigue los puntos de negro y blanco del fotograma",
+    ),
+    ("Browse for a .cube file", "Buscar un archivo .cube"),
+    (
+        "Raw camera, full composite in the middle, effects only on black",
+        "Cámara sin procesar, composición completa en el centro, solo efectos sobre negro",
+    ),
+    ("Copy the current look into deck B", "Copia el look actual al deck B"),
+    (
+        "Render two looks side by side on one frame",
+        "Renderiza dos looks lado a lado en un fotograma",
+    ),
+    (
+        "Record control moves (replaces the current take)",
+        "Graba movimientos de controles (sustituye la toma actual)",
+    ),
+    ("Starts the clock for timed cues", "Inicia el reloj de los cues temporizados"),
+    ("Fire the next cue (space)", "Lanza el siguiente cue (espacio)"),
+    ("Off = only shown in this window", "Apagado = solo se muestra en esta ventana"),
+    (
+        "FPS, inference time, audio levels and track states",
+        "FPS, tiempo de inferencia, niveles de audio y estado de los tracks",
+    ),
+    (
+        "Camera and microphone access granted by the OS",
+        "Acceso a cámara y micrófono concedido por el sistema",
+    ),
+    (
+        "FastSAM ONNX export to use instead of the bundled one",
+        "Exportación ONNX de FastSAM a usar en vez de la incluida",
+    ),
+    (
+        "Video replaces the camera, audio the live input",
+        "El vídeo sustituye a la cámara, el audio a la entrada en directo",
+    ),
+    ("Back to the live inputs", "Volver a las entradas en directo"),
+    ("Status of live audio capture", "Estado de la captura de audio en directo"),
+    ("Audio source mixed before analysis", "Fuente de audio mezclada antes del análisis"),
+    (
+        "Which analysis channel drives each track slot",
+        "Qué canal de análisis mueve cada slot",
+    ),
+    (
+        "Visual style and outline stroke drawn on each track slot",
+        "Estilo visual y contorno dibujados en cada slot",
+    ),
+    (
+        "Visuals loaded from the plugins/ directory, drawn over the mix",
+        "Visuales cargados de la carpeta plugins/, dibujados sobre la mezcla",
+    ),
+    (
+        "Full-frame effects applied after the object visuals",
+        "Efectos de fotograma completo aplicados tras los visuales de objetos",
+    ),
+    (
+        "Tone mapping and a 3D LUT (.cube) applied to the final mix",
+        "Mapeo tonal y un LUT 3D (.cube) aplicados a la mezcla final",
+    ),
+    (
+        "PNG sequence or sprite sheet drawn on each tracked object",
+        "Secuencia PNG u hoja de sprites dibujada en cada objeto seguido",
+    ),
+    ("Clock and set timers for live shows", "Reloj y temporizadores para directos"),
+    (
+        "Drag on the video to limit inference to a region",
+        "Arrastra sobre el vídeo para limitar la inferencia a una región",
+    ),
+    (
+        "Map the output onto physical surfaces and objects",
+        "Proyecta la salida sobre superficies y objetos físicos",
+    ),
+    (
+        "Blend the live look (A) with a stored look (B)",
+        "Mezcla el look en directo (A) con un look guardado (B)",
+    ),
+    (
+        "Record and replay control moves; presets store both",
+        "Graba y reproduce movimientos de controles; los presets guardan ambos",
+    ),
+    (
+        "Numbered looks fired by GO, hotkeys, MIDI program changes or show time",
+        "Looks numerados lanzados por GO, atajos, cambios de programa MIDI o la hora del show",
+    ),
+    (
+        "Shift-click the video to include a point, alt-click to exclude one",
+        "Mayús+clic en el vídeo incluye un punto, Alt+clic lo excluye",
+    ),
+    (
+        "Paint onto or erase from a slot's mask; replaces ROI dragging while on",
+        "Pinta o borra en la máscara de un slot; sustituye al arrastre de ROI",
+    ),
+    (
+        "Record frames, intensities and interactions for replay",
+        "Graba fotogramas, intensidades e interacciones para reproducirlas",
+    ),
+    ("Performance and status details", "Detalles de rendimiento y estado"),
+    (
+        "Waiting for MIDI timecode, or LTC on timecode.ltc_device",
+        "Esperando timecode MIDI, o LTC en timecode.ltc_device",
+    ),
+    (
+        "Upload a smaller preview; effects still render at full resolution",
+        "Sube una vista previa más pequeña; los efectos siguen a resolución completa",
+    ),
+    (
+        "Re-upload only changed tiles; saves bandwidth on static scenes",
+        "Vuelve a subir solo los bloques cambiados; ahorra ancho de banda en escenas estáticas",
+    ),
+    (
+        "Render effects at reduced resolution inside each object",
+        "Renderiza los efectos a resolución reducida dentro de cada objeto",
+    ),
+    (
+        "Paint each slot's style and outline over the raw frame in this window; \
+         stream and recordings get the frame without them",
+        "Pinta el estilo y el contorno de cada slot sobre el fotograma en esta ventana; \
+         el stream y las grabaciones reciben el fotograma sin ellos",
+    ),
+    (
+        "Soften masks along the camera image's edges (guided filter)",
+        "Suaviza las máscaras siguiendo los bordes de la imagen (filtro guiado)",
+    ),
+    (
+        "Channel sizes and drop policies are set in the config file",
+        "Los tamaños de canal y las políticas de descarte están en la configuración",
+    ),
+    // --- Styles, channels and modes ---
+    ("Microphone", "Micrófono"),
+    ("Camera access", "Acceso a la cámara"),
+    ("Audio input devices", "Dispositivos de entrada de audio"),
+    ("Loopback device", "Dispositivo loopback"),
+    ("Model warm-up", "Calentamiento del modelo"),
+    ("Low", "Graves"),
+    ("Mid", "Medios"),
+    ("High", "Agudos"),
+    ("RMS", "RMS"),
+    ("Beat", "Pulso"),
+    ("Custom", "Personalizado"),
+    ("Bass", "Bajo"),
+    ("Drums", "Batería"),
+    ("Vocals", "Voces"),
+    ("Pitch", "Tono"),
+    ("Pitch Conf.", "Conf. tono"),
+    ("Pan Low", "Pan graves"),
+    ("Pan Mid", "Pan medios"),
+    ("Pan High", "Pan agudos"),
+    ("Width Low", "Anchura graves"),
+    ("Width Mid", "Anchura medios"),
+    ("Width High", "Anchura agudos"),
+    ("Transient", "Transitorio"),
+    ("Sustain", "Sostenido"),
+    ("Kick", "Bombo"),
+    ("Snare", "Caja"),
+    ("Hi-Hat", "Charles"),
+    ("Linear FFT", "FFT lineal"),
+    ("⅓-octave bank", "Banco de ⅓ de octava"),
+    ("RGB Offset", "Desfase RGB"),
+    ("Scanlines", "Líneas de barrido"),
+    ("Noise", "Ruido"),
+    ("Tape Wobble", "Vaivén de cinta"),
+    ("Reinhard", "Reinhard"),
+    ("Filmic", "Fílmico"),
+    ("Cover", "Cubrir"),
+    ("Above", "Encima"),
+    ("Immediately", "Inmediatamente"),
+    ("Next beat", "Siguiente pulso"),
+    ("Next bar", "Siguiente compás"),
+    ("Next phrase", "Siguiente frase"),
+    ("Pulse", "Pulso"),
+    ("Vortex", "Vórtice"),
+    ("Static", "Estática"),
+    ("Warp: Bulge", "Deformar: abombar"),
+    ("Warp: Ripple", "Deformar: ondas"),
+    ("Warp: Shear", "Deformar: cizalla"),
+    ("Hue (pitch)", "Tono de color (altura)"),
+    ("Extrude", "Extruir"),
+    ("Clones", "Clones"),
+    ("Stained Glass", "Vidriera"),
+    ("Reaction-Diffusion", "Reacción-difusión"),
+    ("Cellular Automaton", "Autómata celular"),
+    ("Marching Ants", "Hormigas en marcha"),
+    ("Dashes", "Guiones"),
+    ("Arrows", "Flechas"),
+    ("Particles", "Partículas"),
+    ("Normal", "Normal"),
+    ("Multiply", "Multiplicar"),
+    ("Screen", "Trama"),
+    ("Overlay", "Superponer"),
+    ("Difference", "Diferencia"),
+    ("Color dodge", "Sobreexponer color"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashSet, fs, path::Path};
+
+    // Contents of the string literal starting at `rest`, up to its closing quote
+    fn literal(rest: &str) -> String {
+        let mut out = String::new();
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('\n') => {
+                        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                    }
+                    Some(other) => out.push(other),
+                    None => break,
+                },
+                c => out.push(c),
+            }
+        }
+        out
+    }
+
+    // Every literal passed to tr() under `dir`
+    fn tr_literals(dir: &Path, found: &mut Vec<(String, String)>) {
+        for entry in fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                tr_literals(&path, found);
+                continue;
+            }
+            if path.extension().is_some_and(|ext| ext == "rs") {
+                let source = fs::read_to_string(&path).unwrap();
+                for (at, _) in source.match_indices("tr(\"") {
+                    let preceded = source[..at].chars().next_back();
+                    if preceded.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                        continue;
+                    }
+                    let text = literal(&source[at + 4..]);
+                    found.push((path.display().to_string(), text));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn spanish_covers_every_tr_literal() {
+        let mut found = Vec::new();
+        tr_literals(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut found);
+        assert!(!found.is_empty());
+        let keys: HashSet<&str> = SPANISH.iter().map(|(en, _)| *en).collect();
+        let missing: Vec<_> = found
+            .iter()
+            .filter(|(_, text)| !keys.contains(text.as_str()))
+            .collect();
+        assert!(missing.is_empty(), "untranslated: {:#?}", missing);
+    }
+
+    #[test]
+    fn spanish_keys_are_unique() {
+        let mut seen = HashSet::new();
+        for (en, _) in SPANISH {
+            assert!(seen.insert(en), "duplicate key {:?}", en);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+// src/keyed.rs
+// Keyed overlay output: the graded visuals, drawn over a transparent canvas rather than the
+// camera, with alpha from the tracked masks, so a video mixer can key them over its own clean
+// camera feed. Encoded by an ffmpeg child process into an alpha-capable codec; NDI/Syphon
+// senders are not bundled.
+use anyhow::{Context, Result};
+use crossbeam_channel::{bounded, Receiver, Sender, TrySendError};
+use image::{GrayImage, RgbImage};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Command, Stdio},
+    thread::{self, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const FRAME_QUEUE: usize = 4; // Frames waiting for ffmpeg; new ones are dropped while full
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyedCodec {
+    ProRes4444, // .mov, what most hardware and NLE workflows expect
+    Vp9,        // .webm, smaller; browsers and OBS play the alpha
+}
+
+impl KeyedCodec {
+    pub const ALL: [KeyedCodec; 2] = [KeyedCodec::ProRes4444, KeyedCodec::Vp9];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyedCodec::ProRes4444 => "ProRes 4444",
+            KeyedCodec::Vp9 => "WebM (VP9)",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            KeyedCodec::ProRes4444 => "mov",
+            KeyedCodec::Vp9 => "webm",
+        }
+    }
+
+    fn encoder_args(self) -> &'static [&'static str] {
+        match self {
+            KeyedCodec::ProRes4444 => &[
+                "-c:v",
+                "prores_ks",
+                "-profile:v",
+                "4444",
+                "-pix_fmt",
+                "yuva444p10le",
+            ],
+            // yuva420p halves the chroma planes, so odd sides get a transparent pixel of padding
+            KeyedCodec::Vp9 => &[
+                "-vf",
+                "pad=ceil(iw/2)*2:ceil(ih/2)*2:color=black@0",
+                "-c:v",
+                "libvpx-vp9",
+                "-pix_fmt",
+                "yuva420p",
+                "-b:v",
+                "0",
+                "-crf",
+                "30",
+                "-row-mt",
+                "1",
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyedConfig {
+    pub ffmpeg_path: String,
+    pub codec: KeyedCodec,
+    pub output_dir: PathBuf,
+    pub fps: u32, // Output rate; input frames are timestamped by wall clock
+}
+
+impl Default for KeyedConfig {
+    fn default() -> Self {
+        Self {
+            ffmpeg_path: "ffmpeg".to_string(),
+            codec: KeyedCodec::ProRes4444,
+            output_dir: PathBuf::from("recordings"),
+            fps: 30,
+        }
+    }
+}
+
+// Straight (unpremultiplied) RGBA: color from the keyed layer, alpha from the mask coverage
+pub fn keyed_frame(image: &RgbImage, coverage: &GrayImage) -> Vec<u8> {
+    image
+        .pixels()
+        .zip(coverage.iter())
+        .flat_map(|(px, a)| [px[0], px[1], px[2], *a])
+        .collect()
+}
+
+// --- One recording; lives in the segmentation thread ---
+pub struct KeyedRecorder {
+    path: PathBuf,
+    size: (u32, u32),
+    frame_sender: Sender<Vec<u8>>,
+    child: Child,
+    writer: JoinHandle<()>,
+}
+
+impl KeyedRecorder {
+    pub fn start(config: &KeyedConfig, size: (u32, u32)) -> Result<Self> {
+        std::fs::create_dir_all(&config.output_dir).with_context(|| {
+            format!(
+                "Failed to create output dir {}",
+                config.output_dir.display()
+            )
+        })?;
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = config
+            .output_dir
+            .join(format!("keyed-{}.{}", stamp, config.codec.extension()));
+        let mut child = Command::new(&config.ffmpeg_path)
+            .args(["-hide_banner", "-loglevel", "error", "-y"])
+            .args([
+                "-f",
+                "rawvideo",
+                "-pix_fmt",
+                "rgba",
+                "-use_wallclock_as_timestamps",
+                "1",
+            ])
+            .args(["-s", &format!("{}x{}", size.0, size.1), "-i", "pipe:0"])
+            .args(config.codec.encoder_args())
+            .args(["-r", &config.fps.to_string()])
+            .arg(&path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to launch {}", config.ffmpeg_path))?;
+        let stdin = child.stdin.take().context("ffmpeg stdin unavailable")?;
+        let (frame_sender, frame_receiver) = bounded::<Vec<u8>>(FRAME_QUEUE);
+        let writer = thread::spawn(move || write_frames(stdin, frame_receiver));
+        info!(
+            "Recording keyed overlay {}x{} ({}) to {}",
+            size.0,
+            size.1,
+            config.codec.label(),
+            path.display()
+        );
+        Ok(Self {
+            path,
+            size,
+            frame_sender,
+            child,
+            writer,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    // Never blocks the render loop; if ffmpeg falls behind the newest frame is dropped
+    pub fn push(&self, rgba: Vec<u8>) {
+        if let Err(TrySendError::Full(_)) = self.frame_sender.try_send(rgba) {
+            debug!("Keyed encoder behind; dropping a frame");
+        }
+    }
+
+    // Closing stdin ends the input; ffmpeg then finalizes the container on its own thread, so
+    // the render loop never waits for it. Join the handle only when exiting
+    pub fn finish(self) -> JoinHandle<()> {
+        let Self {
+            path,
+            frame_sender,
+            mut child,
+            writer,
+            ..
+        } = self;
+        drop(frame_sender);
+        thread::spawn(move || {
+            let _ = writer.join();
+            match child.wait() {
+                Ok(status) if status.success() => {
+                    info!("Keyed overlay saved to {}", path.display())
+                }
+                Ok(status) => warn!("ffmpeg exited ({}) for {}", status, path.display()),
+                Err(e) => warn!("Waiting for ffmpeg failed: {}", e),
+            }
+        })
+    }
+}
+
+fn write_frames(mut stdin: ChildStdin, frames: Receiver<Vec<u8>>) {
+    for frame in frames {
+        if let Err(e) = stdin.write_all(&frame) {
+            error!("Keyed recording stopped: {}", e);
+            break;
+        }
+    }
+}
@@ -0,0 +1,278 @@
+// src/latency.rs
+use anyhow::{bail, Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::Stream;
+use log::info;
+use std::collections::VecDeque;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+
+use crate::music::{self, AnalysisChannel};
+
+// --- Click train played during calibration ---
+const CLICK_COUNT: usize = 8;
+const CLICK_INTERVAL_SECS: f32 = 0.5;
+const CLICK_LENGTH_SECS: f32 = 0.004;
+const CLICK_AMPLITUDE: f32 = 0.8;
+const CALIBRATION_TAIL: Duration = Duration::from_millis(800); // Listen on after the last click
+
+// --- Onset matching ---
+const ONSET_THRESHOLD: f32 = 0.5; // On the Transient channel
+const ONSET_DEBOUNCE: Duration = Duration::from_millis(200);
+const MAX_ROUND_TRIP: Duration = Duration::from_millis(1000);
+const MIN_MATCHED_CLICKS: usize = 3;
+
+// --- Look-ahead ---
+const TREND_WINDOW: Duration = Duration::from_millis(50); // Slope measured over this span
+
+// --- Onset timestamps reported by the audio processor while a calibration is armed ---
+#[derive(Debug, Clone, Default)]
+pub struct OnsetProbe {
+    armed: Arc<AtomicBool>,
+    onsets: Arc<Mutex<Vec<Instant>>>,
+}
+
+impl OnsetProbe {
+    pub fn is_armed(&self) -> bool {
+        self.armed.load(Ordering::Relaxed)
+    }
+
+    // Called once per analysis hop with the Transient channel value
+    pub fn report(&self, transient: f32) {
+        if !self.is_armed() || transient < ONSET_THRESHOLD {
+            return;
+        }
+        let now = Instant::now();
+        let mut onsets = self.onsets.lock().unwrap();
+        if onsets
+            .last()
+            .map_or(true, |t| now.duration_since(*t) >= ONSET_DEBOUNCE)
+        {
+            onsets.push(now);
+        }
+    }
+
+    fn arm(&self) {
+        self.onsets.lock().unwrap().clear();
+        self.armed.store(true, Ordering::Relaxed);
+    }
+
+    fn disarm(&self) -> Vec<Instant> {
+        self.armed.store(false, Ordering::Relaxed);
+        std::mem::take(&mut *self.onsets.lock().unwrap())
+    }
+}
+
+// --- Plays clicks through the default output and times their arrival via the capture path ---
+pub struct Calibration {
+    _stream: Stream,
+    probe: OnsetProbe,
+    clicks: Arc<Mutex<Vec<Instant>>>, // When each click reached the speakers
+    started: Instant,
+}
+
+impl Calibration {
+    pub fn start(probe: &OnsetProbe) -> Result<Self> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .context("No default output device for the calibration clicks")?;
+        let config = device
+            .default_output_config()
+            .context("Failed to query the output device config")?;
+        let rate = config.sample_rate().0 as f32;
+        let channels = config.channels() as usize;
+        let interval = (rate * CLICK_INTERVAL_SECS) as u64;
+        let click_len = (rate * CLICK_LENGTH_SECS).max(1.0) as u64;
+
+        let clicks: Arc<Mutex<Vec<Instant>>> = Arc::new(Mutex::new(Vec::new()));
+        let stream_clicks = clicks.clone();
+        let mut frame: u64 = 0;
+        let stream = device
+            .build_output_stream(
+                &config.into(),
+                move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
+                    let ts = info.timestamp();
+                    let playback_delay =
+                        ts.playback.duration_since(&ts.callback).unwrap_or_default();
+                    let callback_at = Instant::now();
+                    for (i, out) in data.chunks_exact_mut(channels).enumerate() {
+                        let pos = frame % interval;
+                        let click = (frame / interval) as usize;
+                        let value = if click < CLICK_COUNT && pos < click_len {
+                            if pos == 0 {
+                                let offset = Duration::from_secs_f32(i as f32 / rate);
+                                stream_clicks
+                                    .lock()
+                                    .unwrap()
+                                    .push(callback_at + playback_delay + offset);
+                            }
+                            CLICK_AMPLITUDE * (1.0 - pos as f32 / click_len as f32)
+                        } else {
+                            0.0
+                        };
+                        out.fill(value);
+                        frame += 1;
+                    }
+                },
+                |err| log::error!("Calibration output stream error: {}", err),
+                None,
+            )
+            .context("Failed to open the calibration output stream (needs f32 output)")?;
+        probe.arm();
+        stream
+            .play()
+            .context("Failed to start the calibration clicks")?;
+        info!("Latency calibration: playing {} clicks", CLICK_COUNT);
+        Ok(Self {
+            _stream: stream,
+            probe: probe.clone(),
+            clicks,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn is_done(&self) -> bool {
+        let length = Duration::from_secs_f32(CLICK_INTERVAL_SECS * CLICK_COUNT as f32);
+        self.started.elapsed() >= length + CALIBRATION_TAIL
+    }
+
+    // Median click-to-onset delay over the clicks that were heard
+    pub fn finish(self) -> Result<Duration> {
+        let onsets = self.probe.disarm();
+        let clicks = self.clicks.lock().unwrap().clone();
+        let mut delays: Vec<Duration> = clicks
+            .iter()
+            .filter_map(|&click| {
+                onsets
+                    .iter()
+                    .map(|&o| o.saturating_duration_since(click))
+                    .find(|d| !d.is_zero() && *d <= MAX_ROUND_TRIP)
+            })
+            .collect();
+        if delays.len() < MIN_MATCHED_CLICKS {
+            bail!(
+                "Heard {} of {} clicks; route the output into the capture device and retry",
+                delays.len(),
+                clicks.len()
+            );
+        }
+        delays.sort();
+        let median = delays[delays.len() / 2];
+        info!(
+            "Latency calibration: {:?} over {} clicks",
+            median,
+            delays.len()
+        );
+        Ok(median)
+    }
+}
+
+// --- Shifts the intensity stream in time: positive = delay, negative = look-ahead ---
+// Look-ahead extrapolates the latest trend of the level channels, since the future audio
+// doesn't exist yet; everything else, including the meter tail, is the latest message's
+#[derive(Debug, Default)]
+pub struct IntensityOffset {
+    offset_ms: i32,
+    history: VecDeque<(Instant, Vec<f32>)>,
+}
+
+impl IntensityOffset {
+    pub fn set_offset_ms(&mut self, offset_ms: i32) {
+        self.offset_ms = offset_ms;
+    }
+
+    pub fn push(&mut self, intensities: Vec<f32>) {
+        let now = Instant::now();
+        self.history.push_back((now, intensities));
+        let keep = Duration::from_millis(self.offset_ms.unsigned_abs() as u64 + 100);
+        while self.history.len() > 2 && now.duration_since(self.history[0].0) > keep {
+            self.history.pop_front();
+        }
+    }
+
+    pub fn current(&self) -> Option<Vec<f32>> {
+        let (latest_at, latest) = self.history.back()?;
+        match self.offset_ms {
+            0 => Some(latest.clone()),
+            ms if ms > 0 => {
+                let target = Instant::now().checked_sub(Duration::from_millis(ms as u64))?;
+                let past = self.history.iter().rev().find(|(t, _)| *t <= target);
+                Some(past.map_or(&self.history[0].1, |(_, v)| v).clone())
+            }
+            ms => {
+                let earlier = self
+                    .history
+                    .iter()
+                    .rev()
+                    .skip(1)
+                    .find(|(t, _)| latest_at.duration_since(*t) >= TREND_WINDOW);
+                let Some((prev_at, prev)) = earlier.or_else(|| self.history.front()) else {
+                    return Some(latest.clone());
+                };
+                let dt = latest_at.duration_since(*prev_at).as_secs_f32().max(1e-3);
+                let ahead = ms.unsigned_abs() as f32 / 1000.0;
+                let (channels, _) = music::split_meters(latest);
+                let (prev_channels, _) = music::split_meters(prev);
+                let mut predicted = latest.clone();
+                for ((value, (b, a)), _) in predicted
+                    .iter_mut()
+                    .zip(channels.iter().zip(prev_channels))
+                    .zip(AnalysisChannel::ALL)
+                    .filter(|(_, channel)| channel.is_level())
+                {
+                    *value = (b + (b - a) / dt * ahead).clamp(0.0, 1.0);
+                }
+                Some(predicted)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music::{METERED_CHANNELS, METER_MARKER};
+
+    fn message(low: f32, pitch: f32, peak: f32) -> Vec<f32> {
+        let mut msg = music::silent_intensities();
+        msg[AnalysisChannel::Low.index()] = low;
+        msg[AnalysisChannel::Pitch.index()] = pitch;
+        msg.push(METER_MARKER);
+        msg.extend([peak; METERED_CHANNELS.len()]);
+        msg.extend([1.0; METERED_CHANNELS.len()]); // Every band clipping
+        msg
+    }
+
+    #[test]
+    fn look_ahead_keeps_meters() {
+        let mut offset = IntensityOffset::default();
+        offset.set_offset_ms(-50);
+        offset.push(message(0.2, 0.3, 0.7));
+        std::thread::sleep(TREND_WINDOW + Duration::from_millis(10));
+        offset.push(message(0.4, 0.6, 0.8));
+
+        let current = offset.current().unwrap();
+        let (channels, meters) = music::split_meters(&current);
+        let meters = meters.expect("meter tail survives the look-ahead");
+        assert!(channels[AnalysisChannel::Low.index()] > 0.4, "rising level is extrapolated");
+        assert_eq!(channels[AnalysisChannel::Pitch.index()], 0.6); // Held, not extrapolated
+        assert_eq!(channels[AnalysisChannel::PanLow.index()], 0.5);
+        let low = meters[0];
+        assert_eq!((low.peak, low.clip), (0.8, true));
+        assert_eq!(low.level, channels[AnalysisChannel::Low.index()]);
+        assert!(meters.iter().all(|m| m.clip));
+    }
+
+    #[test]
+    fn delay_and_zero_pass_messages_through() {
+        let mut offset = IntensityOffset::default();
+        offset.push(message(0.2, 0.3, 0.7));
+        assert_eq!(offset.current(), Some(message(0.2, 0.3, 0.7)));
+        offset.set_offset_ms(100);
+        assert!(music::split_meters(&offset.current().unwrap()).1.is_some());
+    }
+}
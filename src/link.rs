@@ -0,0 +1,88 @@
+// src/link.rs
+// Ableton Link session: joins the tempo/beat timeline that Live (and other Link apps) share on
+// the local network, so cues can fire on song position. Link carries no absolute song position,
+// so position is counted in beats from when the transport started (start/stop sync).
+use log::info;
+use rusty_link::{AblLink, SessionState};
+use serde::{Deserialize, Serialize};
+
+const INITIAL_BPM: f64 = 120.0; // Only used until a peer's tempo arrives
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkConfig {
+    pub enabled: bool,
+    pub quantum: f64, // Beats per bar; the transport starts on a bar boundary
+}
+
+impl Default for LinkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            quantum: 4.0,
+        }
+    }
+}
+
+pub struct LinkSession {
+    link: AblLink,
+    state: SessionState,
+    quantum: f64,
+    start_beat: Option<f64>, // Timeline beat where the transport last started
+}
+
+impl LinkSession {
+    pub fn start(config: &LinkConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let link = AblLink::new(INITIAL_BPM);
+        link.enable_start_stop_sync(true);
+        link.enable(true);
+        info!("Ableton Link enabled (quantum {})", config.quantum);
+        Some(Self {
+            link,
+            state: SessionState::new(),
+            quantum: config.quantum.max(1.0),
+            start_beat: None,
+        })
+    }
+
+    // Beats since the transport started, or None while it is stopped
+    pub fn song_position(&mut self) -> Option<f64> {
+        self.link.capture_app_session_state(&mut self.state);
+        let now = self.link.clock_micros();
+        if !self.state.is_playing() {
+            self.start_beat = None;
+            return None;
+        }
+        let start = *self.start_beat.get_or_insert_with(|| {
+            let started_at = self.state.time_for_is_playing();
+            self.state.beat_at_time(started_at, self.quantum)
+        });
+        Some((self.state.beat_at_time(now, self.quantum) - start).max(0.0))
+    }
+
+    // Session tempo and the beat now on the shared timeline, whose bar lines fall on multiples
+    // of the quantum whether or not the transport is playing
+    pub fn beat_time(&mut self) -> (f64, f64) {
+        self.link.capture_app_session_state(&mut self.state);
+        let now = self.link.clock_micros();
+        (self.state.tempo(), self.state.beat_at_time(now, self.quantum))
+    }
+
+    pub fn tempo(&mut self) -> f64 {
+        self.link.capture_app_session_state(&mut self.state);
+        self.state.tempo()
+    }
+
+    pub fn num_peers(&self) -> u64 {
+        self.link.num_peers()
+    }
+}
+
+impl Drop for LinkSession {
+    fn drop(&mut self) {
+        self.link.enable(false);
+    }
+}
@@ -1,24 +1,379 @@
 // src/live_audio.rs
 use anyhow::{anyhow, Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{BuildStreamError, Device, SampleFormat, Stream, StreamConfig};
-use crossbeam_channel::Sender;
+use cpal::{
+    BuildStreamError, Device, FromSample, Sample, SampleFormat, SizedSample, Stream, StreamConfig,
+};
+use crossbeam_channel::{bounded, Receiver, Sender};
 use log::{error, info, warn};
+use std::collections::VecDeque;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc, Mutex,
 };
 
-const VIRTUAL_DEVICE_NAME: &str = "BlackHole"; //Using Blackhole for audio input/output
+use crate::audio_record::AudioRecordTap;
+use crate::pipeline::{PolicySender, TrySendError};
+use crate::platform;
+
+const MAX_SECONDARY_BUFFER_SECS: f32 = 0.25; // Cap on queued secondary samples (drift guard)
+const SECONDARY_QUEUE_CAPACITY: usize = 48_000; // The cap above at up to 192 kHz
+const MAX_MONITOR_BUFFER_SECS: f32 = 0.1; // Monitor lag cap; new audio is dropped past it
+
+// Loopback/virtual inputs (BlackHole, PipeWire/Pulse monitors, Stereo Mix...) per platform,
+// or the configured name list when there is one
+pub fn is_virtual_device_name(name: &str, preferred: &[String]) -> bool {
+    platform::loopback_rank(name, preferred).is_some()
+}
+
+// Names of all input devices on the default host (used by diagnostics)
+pub fn list_input_devices() -> Result<Vec<String>> {
+    let host = cpal::default_host();
+    let devices = host
+        .input_devices()
+        .context("Error enumerating input devices")?;
+    Ok(devices
+        .map(|d| d.name().unwrap_or_else(|_| "Unnamed Device".into()))
+        .collect())
+}
+
+// Names of all output devices on the default host (monitor output picker)
+pub fn list_output_devices() -> Result<Vec<String>> {
+    let host = cpal::default_host();
+    let devices = host
+        .output_devices()
+        .context("Error enumerating output devices")?;
+    Ok(devices
+        .map(|d| d.name().unwrap_or_else(|_| "Unnamed Device".into()))
+        .collect())
+}
+
+// --- Per-source gain/mute shared between UI and the audio callbacks ---
+#[derive(Debug, Clone)]
+pub struct AudioSourceControl {
+    pub name: String,
+    gain_bits: Arc<AtomicU32>,
+    muted: Arc<AtomicBool>,
+    bypassed: Arc<AtomicBool>, // Primary only: a file source feeds the analysis instead
+}
+
+impl AudioSourceControl {
+    fn new(name: String, gain: f32, muted: bool) -> Self {
+        Self {
+            name,
+            gain_bits: Arc::new(AtomicU32::new(gain.to_bits())),
+            muted: Arc::new(AtomicBool::new(muted)),
+            bypassed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn gain(&self) -> f32 {
+        f32::from_bits(self.gain_bits.load(Ordering::Relaxed))
+    }
+
+    pub fn set_gain(&self, gain: f32) {
+        self.gain_bits.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted.load(Ordering::Relaxed)
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.muted.store(muted, Ordering::Relaxed);
+    }
+
+    pub fn set_bypassed(&self, bypassed: bool) {
+        self.bypassed.store(bypassed, Ordering::Relaxed);
+    }
+
+    fn is_bypassed(&self) -> bool {
+        self.bypassed.load(Ordering::Relaxed)
+    }
+
+    // Effective multiplier applied to this source's samples
+    fn effective_gain(&self) -> f32 {
+        if self.is_muted() {
+            0.0
+        } else {
+            self.gain()
+        }
+    }
+}
+
+// --- Result of starting capture: all live streams plus the primary stream format ---
+pub struct AudioCapture {
+    pub streams: Vec<Stream>,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub sources: Vec<AudioSourceControl>,
+}
+
+// Mono samples queued by a secondary source, drained by the primary callback. A bounded
+// lock-free channel, so neither audio callback can block on the other; the producer holds a
+// receiver too, to drop the oldest samples when the primary falls behind
+#[derive(Clone)]
+struct SampleQueue {
+    sender: Sender<f32>,
+    receiver: Receiver<f32>,
+}
+
+impl SampleQueue {
+    fn new() -> Self {
+        let (sender, receiver) = bounded(SECONDARY_QUEUE_CAPACITY);
+        Self { sender, receiver }
+    }
+
+    fn push(&self, sample: f32, max_queued: usize) {
+        while self.sender.len() >= max_queued.min(SECONDARY_QUEUE_CAPACITY) {
+            if self.receiver.try_recv().is_err() {
+                break;
+            }
+        }
+        let _ = self.sender.try_send(sample);
+    }
+
+    fn pop(&self) -> Option<f32> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+// Format of the open monitor output and the producer end of its ring
+#[derive(Clone)]
+struct MonitorFormat {
+    channels: usize,
+    input_rate: u32,
+    output_rate: u32,
+    ring: Sender<f32>, // Interleaved; capacity is the lag cap
+}
+
+// Captured audio converted to the open monitor output's format. Owned by the primary callback;
+// the scratch buffers are reused so feeding doesn't allocate once they reach their working size
+struct MonitorSink {
+    format: MonitorFormat,
+    converters: Vec<LinearRateConverter>, // One per output channel
+    input: Vec<f32>,
+    converted: Vec<VecDeque<f32>>,
+}
+
+impl MonitorSink {
+    fn new(format: MonitorFormat) -> Self {
+        Self {
+            converters: (0..format.channels)
+                .map(|_| LinearRateConverter::new(format.input_rate, format.output_rate))
+                .collect(),
+            input: Vec::new(),
+            converted: (0..format.channels).map(|_| VecDeque::new()).collect(),
+            format,
+        }
+    }
+
+    // Interleaved capture samples with `channels` per frame. Only whole frames are queued, and
+    // frames past the lag cap are dropped rather than evicting older ones the output may be reading
+    fn push(&mut self, samples: &[f32], channels: u16) {
+        let in_channels = channels.max(1) as usize;
+        let out_channels = self.format.channels;
+        for (c, (converter, out)) in self
+            .converters
+            .iter_mut()
+            .zip(self.converted.iter_mut())
+            .enumerate()
+        {
+            self.input.clear();
+            let frames = samples.chunks_exact(in_channels);
+            if out_channels == 1 {
+                self.input
+                    .extend(frames.map(|frame| frame.iter().sum::<f32>() / in_channels as f32));
+            } else {
+                self.input
+                    .extend(frames.map(|frame| frame[c % in_channels]));
+            }
+            converter.process(&self.input, out);
+        }
+        let ring = &self.format.ring;
+        let free = ring.capacity().unwrap_or(0).saturating_sub(ring.len()) / out_channels;
+        let frames = self.converted.iter().map(VecDeque::len).min().unwrap_or(0);
+        for i in 0..frames.min(free) {
+            for channel in &self.converted {
+                let _ = ring.try_send(channel[i]);
+            }
+        }
+        for channel in &mut self.converted {
+            channel.clear();
+        }
+    }
+}
+
+// Primary callback's end of the monitor: picks up output (re)opens through the handoff
+struct MonitorFeed {
+    handoff: Receiver<Option<MonitorFormat>>,
+    sink: Option<MonitorSink>,
+}
+
+impl MonitorFeed {
+    fn feed(&mut self, samples: &[f32], channels: u16) {
+        while let Ok(format) = self.handoff.try_recv() {
+            self.sink = format.map(MonitorSink::new);
+        }
+        if let Some(sink) = self.sink.as_mut() {
+            sink.push(samples, channels);
+        }
+    }
+}
+
+// --- Pass-through monitor: the captured mix played back out an output device ---
+// Fed by the primary callback through a lock-free ring; nothing is queued until an output is
+// open. `open` is only touched off the audio threads, to seed a capture that starts later
+#[derive(Clone)]
+pub struct MonitorControl {
+    gain_bits: Arc<AtomicU32>,
+    open: Arc<Mutex<Option<MonitorFormat>>>,
+    handoff_sender: Sender<Option<MonitorFormat>>,
+    handoff_receiver: Receiver<Option<MonitorFormat>>,
+}
+
+impl Default for MonitorControl {
+    fn default() -> Self {
+        let (handoff_sender, handoff_receiver) = bounded(1);
+        Self {
+            gain_bits: Arc::new(AtomicU32::new(1.0f32.to_bits())),
+            open: Arc::default(),
+            handoff_sender,
+            handoff_receiver,
+        }
+    }
+}
+
+impl MonitorControl {
+    pub fn gain(&self) -> f32 {
+        f32::from_bits(self.gain_bits.load(Ordering::Relaxed))
+    }
+
+    pub fn set_gain(&self, gain: f32) {
+        self.gain_bits.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    // Stops queueing; the output stream plays silence until it is dropped
+    pub fn stop(&self) {
+        self.hand_off(None);
+    }
+
+    // Replaces any handoff the capture hasn't picked up yet, so only the latest format applies
+    fn hand_off(&self, format: Option<MonitorFormat>) {
+        if let Ok(mut open) = self.open.lock() {
+            *open = format.clone();
+        }
+        while self.handoff_receiver.try_recv().is_ok() {}
+        let _ = self.handoff_sender.try_send(format);
+    }
+
+    // For a primary callback being built; starts on whatever output is already open
+    fn feeder(&self) -> MonitorFeed {
+        while self.handoff_receiver.try_recv().is_ok() {}
+        let format = self.open.lock().ok().and_then(|open| open.clone());
+        MonitorFeed {
+            handoff: self.handoff_receiver.clone(),
+            sink: format.map(MonitorSink::new),
+        }
+    }
+}
+
+// Linear-interpolating rate converter used to align secondary sources with the primary rate
+struct LinearRateConverter {
+    step: f64,
+    pos: f64,
+    prev: f32,
+}
+
+impl LinearRateConverter {
+    fn new(source_rate: u32, target_rate: u32) -> Self {
+        Self {
+            step: source_rate as f64 / target_rate.max(1) as f64,
+            pos: 0.0,
+            prev: 0.0,
+        }
+    }
 
-// Helper function
-fn try_build_input_stream(
+    fn process(&mut self, input: &[f32], out: &mut VecDeque<f32>) {
+        if input.is_empty() {
+            return;
+        }
+        // Virtual buffer is [prev, input...]; interpolate between neighbours
+        let mut i = self.pos;
+        while i < input.len() as f64 {
+            let k = i.floor() as usize;
+            let frac = (i - k as f64) as f32;
+            let a = if k == 0 { self.prev } else { input[k - 1] };
+            let b = input[k];
+            out.push_back(a + (b - a) * frac);
+            i += self.step;
+        }
+        self.pos = i - input.len() as f64;
+        self.prev = input[input.len() - 1];
+    }
+}
+
+// Into `out`, which keeps its capacity between buffers
+fn downmix_to_mono(samples: &[f32], channels: u16, out: &mut Vec<f32>) {
+    let channels = channels.max(1) as usize;
+    out.clear();
+    out.extend(
+        samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32),
+    );
+}
+
+// Sample formats we know how to convert to f32 in the data callback
+fn is_convertible_format(format: SampleFormat) -> bool {
+    matches!(
+        format,
+        SampleFormat::F32 | SampleFormat::I16 | SampleFormat::U16 | SampleFormat::U8
+    )
+}
+
+// Builds an input stream for sample type `T`, converting every buffer to f32 before handing it on.
+// The f32 buffer is reused, so it only reallocates when the device's buffer size grows
+fn build_converting_stream<T, F>(
     device: &Device,
-    raw_sample_sender: &Sender<Vec<f32>>,
+    config: &StreamConfig,
     stop_signal: &Arc<AtomicBool>,
-) -> Result<(Stream, u32, u16), anyhow::Error> {
-    // Get the device name early for logging and cloning
-    let device_name = device.name().unwrap_or_else(|_| "Unnamed Device".into());
+    device_name: &str,
+    mut on_samples: F,
+) -> Result<Stream, BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+    F: FnMut(&mut [f32]) + Send + 'static,
+{
+    // --- Clone variables needed for the closures ---
+    let stop_signal_clone = stop_signal.clone();
+    let device_name_for_err_closure = device_name.to_string();
+
+    // --- Error Callback Closure ---
+    let err_fn = move |err| {
+        error!(
+            "An error occurred on the audio stream for {}: {}",
+            device_name_for_err_closure, err
+        );
+    };
+
+    // --- Data Callback Closure ---
+    let mut converted: Vec<f32> = Vec::new();
+    let data_fn = move |data: &[T], _: &cpal::InputCallbackInfo| {
+        if stop_signal_clone.load(Ordering::Relaxed) {
+            return;
+        }
+        converted.clear();
+        converted.extend(data.iter().map(|&s| f32::from_sample(s)));
+        on_samples(&mut converted);
+    };
+
+    device.build_input_stream(config, data_fn, err_fn, None)
+}
+
+// Picks the best input config for a device: F32 if available, otherwise a convertible format
+fn select_input_config(device: &Device, device_name: &str) -> Result<(StreamConfig, SampleFormat)> {
     info!(
         "Attempting to find suitable input config for: {}",
         device_name
@@ -29,82 +384,178 @@ fn try_build_input_stream(
         .with_context(|| format!("Error querying supported input configs for {}", device_name))?;
 
     let supported_config = supported_configs_range
-        .find(|c| c.sample_format() == SampleFormat::F32) 
+        .find(|c| c.sample_format() == SampleFormat::F32)
         .or_else(|| {
             warn!(
-                "F32 sample format not supported on {}, trying any supported format...",
+                "F32 sample format not supported on {}, trying a convertible format...",
                 device_name
             );
-            device
-                .supported_input_configs()
-                .ok()
-                .and_then(|mut iter| iter.next())
+            device.supported_input_configs().ok().and_then(|mut iter| {
+                iter.find(|c| is_convertible_format(c.sample_format()))
+            })
         })
         .ok_or_else(|| anyhow!("No supported input config found for {}", device_name))?
         .with_max_sample_rate();
 
-    if supported_config.sample_format() != SampleFormat::F32 {
-        return Err(anyhow!(
-            "Selected config for {} is not F32 ({:?}). Requires conversion.",
-            device_name,
-            supported_config.sample_format()
-        ));
-    }
-
+    let sample_format = supported_config.sample_format();
     let config: StreamConfig = supported_config.into();
-    let sample_rate = config.sample_rate.0;
-    let channels = config.channels;
     info!(
-        "Selected config for {}: SampleRate={}, Channels={}, Format=F32",
-        device_name, sample_rate, channels
+        "Selected config for {}: SampleRate={}, Channels={}, Format={:?}",
+        device_name, config.sample_rate.0, config.channels, sample_format
     );
+    Ok((config, sample_format))
+}
+
+// Helper function: dispatches on sample format so the callback always sees f32
+fn build_input_stream_for_format<F>(
+    device: &Device,
+    config: &StreamConfig,
+    sample_format: SampleFormat,
+    stop_signal: &Arc<AtomicBool>,
+    device_name: &str,
+    on_samples: F,
+) -> Result<Stream, anyhow::Error>
+where
+    F: FnMut(&mut [f32]) + Send + 'static,
+{
+    let stream = match sample_format {
+        SampleFormat::F32 => {
+            build_converting_stream::<f32, F>(device, config, stop_signal, device_name, on_samples)?
+        }
+        SampleFormat::I16 => {
+            build_converting_stream::<i16, F>(device, config, stop_signal, device_name, on_samples)?
+        }
+        SampleFormat::U16 => {
+            build_converting_stream::<u16, F>(device, config, stop_signal, device_name, on_samples)?
+        }
+        SampleFormat::U8 => {
+            build_converting_stream::<u8, F>(device, config, stop_signal, device_name, on_samples)?
+        }
+        other => {
+            return Err(anyhow!(
+                "Selected config for {} uses unsupported sample format {:?}.",
+                device_name,
+                other
+            ));
+        }
+    };
+
+    info!("Audio stream built successfully for {}.", device_name);
+    Ok(stream)
+}
+
+// Primary source: applies its gain, mixes in queued secondary audio, and forwards to analysis
+fn try_build_primary_stream(
+    device: &Device,
+    raw_sample_sender: &PolicySender<Vec<f32>>,
+    stop_signal: &Arc<AtomicBool>,
+    control: AudioSourceControl,
+    secondaries: Vec<(AudioSourceControl, SampleQueue)>,
+    monitor: MonitorControl,
+    record_tap: AudioRecordTap,
+) -> Result<(Stream, u32, u16), anyhow::Error> {
+    let device_name = device.name().unwrap_or_else(|_| "Unnamed Device".into());
+    let (config, sample_format) = select_input_config(device, &device_name)?;
+    let sample_rate = config.sample_rate.0;
+    let channels = config.channels;
 
-    // --- Clone variables needed for the closures ---
     let sender_clone = raw_sample_sender.clone();
-    let stop_signal_clone = stop_signal.clone();
-    let device_name_for_data_closure = device_name.clone(); 
-    let device_name_for_err_closure = device_name.clone(); 
+    let device_name_for_data_closure = device_name.clone();
+    let mut monitor = monitor.feeder();
+    let mut mix: Vec<f32> = Vec::new(); // Reused; only grows with the device's buffer size
+    let on_samples = move |samples: &mut [f32]| {
+        record_tap.forward(samples); // Raw: before gain and the secondary mix
+        if control.is_bypassed() {
+            return; // The file source's samples take this input's place in the channel
+        }
+        let gain = control.effective_gain();
+        let channel_count = channels.max(1) as usize;
+        let frames = samples.len() / channel_count;
 
-    // --- Error Callback Closure ---
-    let err_fn = move |err| {
-        error!(
-            "An error occurred on the audio stream for {}: {}",
-            device_name_for_err_closure, err
-        );
-    };
-    
-    // --- Data Callback Closure ---
-    let data_fn = move |data: &[f32], _: &cpal::InputCallbackInfo| {
-        if stop_signal_clone.load(Ordering::Relaxed) {
-            return;
+        // Pull one mono sample per frame from each secondary (always drained, even when muted)
+        mix.clear();
+        mix.resize(frames, 0.0);
+        for (source, queue) in &secondaries {
+            let source_gain = source.effective_gain();
+            for (m, s) in mix.iter_mut().zip(std::iter::from_fn(|| queue.pop())) {
+                *m += s * source_gain;
+            }
         }
-        match sender_clone.try_send(data.to_vec()) {
+        for (frame, &mixed) in samples.chunks_exact_mut(channel_count).zip(&mix) {
+            for s in frame.iter_mut() {
+                *s = *s * gain + mixed;
+            }
+        }
+        monitor.feed(samples, channels);
+        // The analysis thread takes ownership, so this hand-off is the callback's one copy
+        match sender_clone.try_send(samples.to_vec()) {
             Ok(_) => { /* Sent */ }
-            Err(crossbeam_channel::TrySendError::Full(_)) => {
+            Err(TrySendError::Full(_)) => {
                 warn!(
                     "Audio channel full, dropping data from {}.",
                     device_name_for_data_closure
                 );
             }
-            Err(crossbeam_channel::TrySendError::Disconnected(_)) => {}
+            Err(TrySendError::Closed(_)) => {}
         }
     };
 
-    // --- Build the Stream ---
-    let stream = device.build_input_stream(
-        &config, data_fn, 
-        err_fn, 
-        None,    
+    let stream = build_input_stream_for_format(
+        device,
+        &config,
+        sample_format,
+        stop_signal,
+        &device_name,
+        on_samples,
     )?;
-
-    info!("Audio stream built successfully for {}.", device_name);
     Ok((stream, sample_rate, channels))
 }
 
+// Secondary source: downmixes, converts to the primary rate and queues for the primary callback
+fn try_build_secondary_stream(
+    device: &Device,
+    stop_signal: &Arc<AtomicBool>,
+    target_rate: u32,
+    queue: SampleQueue,
+) -> Result<Stream, anyhow::Error> {
+    let device_name = device.name().unwrap_or_else(|_| "Unnamed Device".into());
+    let (config, sample_format) = select_input_config(device, &device_name)?;
+    let channels = config.channels;
+    let mut converter = LinearRateConverter::new(config.sample_rate.0, target_rate);
+    let max_queued = (target_rate as f32 * MAX_SECONDARY_BUFFER_SECS) as usize;
+
+    let mut mono = Vec::new();
+    let mut converted = VecDeque::new();
+    let on_samples = move |samples: &mut [f32]| {
+        downmix_to_mono(samples, channels, &mut mono);
+        converter.process(&mono, &mut converted);
+        for sample in converted.drain(..) {
+            queue.push(sample, max_queued);
+        }
+    };
+
+    build_input_stream_for_format(
+        device,
+        &config,
+        sample_format,
+        stop_signal,
+        &device_name,
+        on_samples,
+    )
+}
+
+// `preferred_input` (e.g. picked by the loopback wizard) wins over the name match when present.
+// `loopback_names` are name substrings, most preferred first; empty = the platform defaults.
+// The mix is also fed to `monitor`, which plays it once an output is opened for it, and the raw
+// primary input to `record_tap`
 pub fn start_audio_capture(
-    raw_sample_sender: Sender<Vec<f32>>,
+    raw_sample_sender: PolicySender<Vec<f32>>,
     stop_signal: Arc<AtomicBool>,
-) -> Result<(Stream, u32, u16), anyhow::Error> {
+    preferred_input: Option<&str>,
+    loopback_names: &[String],
+    monitor: MonitorControl,
+    record_tap: AudioRecordTap,
+) -> Result<AudioCapture, anyhow::Error> {
     info!("Initializing audio capture...");
 
     let host = cpal::default_host();
@@ -113,18 +564,33 @@ pub fn start_audio_capture(
     let mut preferred_device: Option<Device> = None;
 
     // --- 1. Look for Virtual Device (e.g., BlackHole) ---
-    info!(
-        "Searching for virtual audio device containing '{}'...",
-        VIRTUAL_DEVICE_NAME
-    );
+    info!("Searching for a virtual/loopback audio device...");
     match host.input_devices() {
         Ok(devices) => {
-            for device in devices {
-                if let Ok(name) = device.name() {
-                    if name.contains(VIRTUAL_DEVICE_NAME) {
-                        info!("Found potential virtual device: {}", name);
-                        preferred_device = Some(device);
-                        break; // Use the first one found
+            let devices: Vec<Device> = devices.collect();
+            if let Some(name) = preferred_input {
+                preferred_device = devices
+                    .iter()
+                    .find(|d| d.name().is_ok_and(|n| n == name))
+                    .cloned();
+                match preferred_device {
+                    Some(_) => info!("Using the selected loopback input: {}", name),
+                    None => warn!("Selected loopback input '{}' is not connected", name),
+                }
+            }
+            if preferred_device.is_none() {
+                // Earliest matching name wins; ties go to the first device enumerated
+                let mut best_rank = usize::MAX;
+                for device in devices {
+                    if let Ok(name) = device.name() {
+                        match platform::loopback_rank(&name, loopback_names) {
+                            Some(rank) if rank < best_rank => {
+                                info!("Found potential virtual device: {}", name);
+                                best_rank = rank;
+                                preferred_device = Some(device);
+                            }
+                            _ => {}
+                        }
                     }
                 }
             }
@@ -137,24 +603,79 @@ pub fn start_audio_capture(
         }
     }
 
-    // --- 2. Try Preferred Device (BlackHole) if found ---
+    // --- 2. Try Preferred Device (BlackHole) if found, with the mic mixed in as a secondary ---
     if let Some(ref dev) = preferred_device {
         let dev_name = dev
             .name()
-            .unwrap_or_else(|_| VIRTUAL_DEVICE_NAME.to_string());
+            .unwrap_or_else(|_| "Loopback".to_string());
         info!(
             "Attempting capture on preferred virtual device: {}",
             dev_name
         );
-        match try_build_input_stream(dev, &raw_sample_sender, &stop_signal) {
-            Ok(result) => {
+
+        // Microphone starts muted so loopback-only behaviour is unchanged until enabled
+        let mic_device = host
+            .default_input_device()
+            .filter(|d| d.name().map_or(true, |n| n != dev_name));
+        let mic_control = mic_device.as_ref().map(|d| {
+            AudioSourceControl::new(
+                d.name().unwrap_or_else(|_| "Microphone".into()),
+                1.0,
+                true,
+            )
+        });
+        let mic_queue = SampleQueue::new();
+        let secondaries: Vec<(AudioSourceControl, SampleQueue)> = mic_control
+            .iter()
+            .map(|c| (c.clone(), mic_queue.clone()))
+            .collect();
+        let primary_control = AudioSourceControl::new(dev_name.clone(), 1.0, false);
+
+        match try_build_primary_stream(
+            dev,
+            &raw_sample_sender,
+            &stop_signal,
+            primary_control.clone(),
+            secondaries,
+            monitor.clone(),
+            record_tap.clone(),
+        ) {
+            Ok((stream, sample_rate, channels)) => {
                 info!("Capture successful on virtual device '{}'.", dev_name);
-                result
-                    .0
+                stream
                     .play()
                     .context("Failed to start audio stream on virtual device")?;
                 info!("Audio stream started playing (capturing from virtual device).");
-                return Ok(result);
+
+                let mut capture = AudioCapture {
+                    streams: vec![stream],
+                    sample_rate,
+                    channels,
+                    sources: vec![primary_control],
+                };
+
+                // --- 2b. Secondary (microphone) source, optional ---
+                if let (Some(mic), Some(control)) = (mic_device, mic_control) {
+                    match try_build_secondary_stream(&mic, &stop_signal, sample_rate, mic_queue)
+                        .and_then(|s| {
+                            s.play()
+                                .context("Failed to start secondary audio stream")?;
+                            Ok(s)
+                        }) {
+                        Ok(mic_stream) => {
+                            info!("Secondary source '{}' mixed in (muted).", control.name);
+                            capture.streams.push(mic_stream);
+                            capture.sources.push(control);
+                        }
+                        Err(e) => {
+                            warn!(
+                                "Secondary source '{}' unavailable: {}. Continuing with loopback only.",
+                                control.name, e
+                            );
+                        }
+                    }
+                }
+                return Ok(capture);
             }
             Err(e) => {
                 warn!(
@@ -165,11 +686,8 @@ pub fn start_audio_capture(
             }
         }
     } else {
-        info!(
-            "Virtual device '{}' not found. Falling back to default input.",
-            VIRTUAL_DEVICE_NAME
-        );
-        warn!("For loopback audio capture (computer output), install '{}' and configure Audio MIDI Setup.", VIRTUAL_DEVICE_NAME);
+        info!("Virtual/loopback device not found. Falling back to default input.");
+        warn!("For loopback audio capture (computer output): {}", platform::LOOPBACK_HINT);
     }
 
     // --- 3. Fallback to Default Input Device (Microphone) ---
@@ -183,18 +701,31 @@ pub fn start_audio_capture(
         "Attempting capture on fallback default input device: {}",
         dev_name
     );
-    match try_build_input_stream(&input_device, &raw_sample_sender, &stop_signal) {
-        Ok(result) => {
+    let control = AudioSourceControl::new(dev_name.clone(), 1.0, false);
+    match try_build_primary_stream(
+        &input_device,
+        &raw_sample_sender,
+        &stop_signal,
+        control.clone(),
+        Vec::new(),
+        monitor,
+        record_tap,
+    ) {
+        Ok((stream, sample_rate, channels)) => {
             info!(
                 "Capture successful on default input device (likely microphone): '{}'.",
                 dev_name
             );
-            result
-                .0
+            stream
                 .play()
                 .context("Failed to start audio stream on default input device")?;
             info!("Audio stream started playing (capturing from default input).");
-            return Ok(result);
+            Ok(AudioCapture {
+                streams: vec![stream],
+                sample_rate,
+                channels,
+                sources: vec![control],
+            })
         }
         Err(e) => {
             error!(
@@ -213,3 +744,125 @@ pub fn start_audio_capture(
         }
     }
 }
+
+// --- Monitor output: plays what `monitor` is fed, captured at `input_rate` ---
+// `device_name` None = the default output. Dropping the stream stops playback
+pub fn start_monitor_output(
+    device_name: Option<&str>,
+    monitor: &MonitorControl,
+    input_rate: u32,
+) -> Result<Stream> {
+    let host = cpal::default_host();
+    let device = match device_name {
+        Some(name) => host
+            .output_devices()
+            .context("Error enumerating output devices")?
+            .find(|d| d.name().is_ok_and(|n| n == name))
+            .ok_or_else(|| anyhow!("Output device '{}' not found", name))?,
+        None => host
+            .default_output_device()
+            .context("No default output device")?,
+    };
+    let name = device.name().unwrap_or_else(|_| "Unnamed Device".into());
+    // Running at the capture rate avoids resampling when the device allows it
+    let config: StreamConfig = device
+        .supported_output_configs()
+        .with_context(|| format!("Error querying supported output configs for {}", name))?
+        .filter(|c| c.sample_format() == SampleFormat::F32)
+        .find(|c| (c.min_sample_rate().0..=c.max_sample_rate().0).contains(&input_rate))
+        .map(|c| c.with_sample_rate(cpal::SampleRate(input_rate)))
+        .or_else(|| {
+            device
+                .default_output_config()
+                .ok()
+                .filter(|c| c.sample_format() == SampleFormat::F32)
+        })
+        .ok_or_else(|| anyhow!("No f32 output config found for {}", name))?
+        .into();
+    let channels = config.channels.max(1) as usize;
+    let rate = config.sample_rate.0;
+    info!(
+        "Monitor output on {}: SampleRate={}, Channels={}",
+        name, rate, channels
+    );
+    let max_frames = ((rate as f32 * MAX_MONITOR_BUFFER_SECS) as usize).max(1);
+    let (ring, output) = bounded(max_frames * channels);
+
+    let control = monitor.clone();
+    let stream = device
+        .build_output_stream(
+            &config,
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                let gain = control.gain();
+                // Underruns play silence; a frame is only taken once all its channels are queued
+                for frame in data.chunks_mut(channels) {
+                    if output.len() >= frame.len() {
+                        for out in frame.iter_mut() {
+                            *out = output.try_recv().unwrap_or(0.0) * gain;
+                        }
+                    } else {
+                        frame.fill(0.0);
+                    }
+                }
+            },
+            |err| error!("Monitor output stream error: {}", err),
+            None,
+        )
+        .with_context(|| format!("Failed to open monitor output on {}", name))?;
+    stream.play().context("Failed to start the monitor output")?;
+    monitor.hand_off(Some(MonitorFormat {
+        channels,
+        input_rate,
+        output_rate: rate,
+        ring,
+    }));
+    Ok(stream)
+}
+
+// --- Single channel of a named input device (e.g. LTC on a spare interface input) ---
+// Kept out of the analysis mix; `on_samples` gets the device rate and that channel's samples
+pub fn start_channel_capture<F>(
+    device_name: &str,
+    channel: usize,
+    stop_signal: Arc<AtomicBool>,
+    mut on_samples: F,
+) -> Result<Stream>
+where
+    F: FnMut(u32, &[f32]) + Send + 'static,
+{
+    let host = cpal::default_host();
+    let device = host
+        .input_devices()
+        .context("Error enumerating input devices")?
+        .find(|d| d.name().is_ok_and(|n| n == device_name))
+        .ok_or_else(|| anyhow!("Input device '{}' not found", device_name))?;
+    let (config, sample_format) = select_input_config(&device, device_name)?;
+    let channels = config.channels.max(1) as usize;
+    if channel >= channels {
+        return Err(anyhow!(
+            "'{}' has {} channels; channel {} requested",
+            device_name,
+            channels,
+            channel
+        ));
+    }
+    let sample_rate = config.sample_rate.0;
+    let mut picked = Vec::new();
+    let on_interleaved = move |samples: &mut [f32]| {
+        picked.clear();
+        picked.extend(samples.iter().skip(channel).step_by(channels).copied());
+        on_samples(sample_rate, &picked);
+    };
+    let stream = build_input_stream_for_format(
+        &device,
+        &config,
+        sample_format,
+        &stop_signal,
+        device_name,
+        on_interleaved,
+    )?;
+    stream
+        .play()
+        .with_context(|| format!("Failed to start capture on {}", device_name))?;
+    Ok(stream)
+}
@@ -0,0 +1,246 @@
+// src/logging.rs
+// Log output: always stderr (filtered by RUST_LOG as before), optionally also size-rotated files
+// for diagnosing gigs after the fact. An opt-in panic hook writes a crash report with the panic,
+// a backtrace, the pipeline metrics and the last warnings/errors.
+use anyhow::{Context, Result};
+use log::{info, Level, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::{
+    backtrace::Backtrace,
+    collections::VecDeque,
+    fmt::Write as _,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    panic::PanicHookInfo,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+
+use crate::{metrics, pipeline::ChannelStats};
+
+const LOG_FILE: &str = "sam_cam_bam.log";
+const RECENT_PROBLEMS: usize = 50; // Warnings/errors kept for the crash report
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    pub dir: Option<PathBuf>, // e.g. "logs"; None = stderr only
+    pub max_file_mb: u64,     // Rotate once the current file reaches this size
+    pub keep_files: usize,    // Rotated files kept next to the current one
+    pub crash_reports: bool,  // Write crash-<time>.txt into `dir` (or "logs") on panic
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            max_file_mb: 10,
+            keep_files: 5,
+            crash_reports: false,
+        }
+    }
+}
+
+// --- sam_cam_bam.log, rotated to .1 (newest) ... .N (oldest) ---
+struct RotatingFile {
+    dir: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    written: u64,
+}
+
+impl RotatingFile {
+    fn open(dir: &Path, max_bytes: u64, keep: usize) -> Result<Self> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create log dir {}", dir.display()))?;
+        let path = dir.join(LOG_FILE);
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            max_bytes: max_bytes.max(1),
+            keep,
+            file,
+            written,
+        })
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        self.dir.join(format!("{}.{}", LOG_FILE, n))
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let current = self.dir.join(LOG_FILE);
+        if self.keep == 0 {
+            let _ = fs::remove_file(&current);
+        } else {
+            let _ = fs::remove_file(self.rotated(self.keep));
+            for n in (1..self.keep).rev() {
+                let _ = fs::rename(self.rotated(n), self.rotated(n + 1));
+            }
+            fs::rename(&current, self.rotated(1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&current)?;
+        self.written = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.written > 0 && self.written + line.len() as u64 > self.max_bytes {
+            if let Err(e) = self.rotate() {
+                eprintln!("Log rotation failed: {}", e);
+            }
+        }
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.written += line.len() as u64;
+        }
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    file: Mutex<Option<RotatingFile>>,
+    recent: Mutex<VecDeque<String>>,
+}
+
+fn shared() -> &'static Shared {
+    static SHARED: OnceLock<Shared> = OnceLock::new();
+    SHARED.get_or_init(Shared::default)
+}
+
+// --- env_logger for the terminal; files and the problem history are added on top ---
+struct AppLogger {
+    terminal: env_logger::Logger,
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.terminal.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.terminal.matches(record) {
+            return;
+        }
+        self.terminal.log(record);
+        let line = format!(
+            "{} {:<5} {}] {}\n",
+            chrono::Local::now().format(TIMESTAMP_FORMAT),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        if record.level() <= Level::Warn {
+            if let Ok(mut recent) = shared().recent.lock() {
+                if recent.len() == RECENT_PROBLEMS {
+                    recent.pop_front();
+                }
+                recent.push_back(line.trim_end().to_string());
+            }
+        }
+        if let Ok(mut file) = shared().file.lock() {
+            if let Some(file) = file.as_mut() {
+                file.write_line(&line);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.terminal.flush();
+        if let Ok(mut file) = shared().file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = file.file.flush();
+            }
+        }
+    }
+}
+
+// Call first thing in main; files are attached once the config is known
+pub fn init() {
+    let terminal =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build();
+    let max_level = terminal.filter();
+    if log::set_boxed_logger(Box::new(AppLogger { terminal })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+pub fn start_file_logging(config: &LoggingConfig) -> Result<()> {
+    let Some(dir) = &config.dir else {
+        return Ok(());
+    };
+    let file = RotatingFile::open(dir, config.max_file_mb * 1024 * 1024, config.keep_files)?;
+    *shared().file.lock().unwrap() = Some(file);
+    info!(
+        "Logging to {} (rotating at {} MB, keeping {})",
+        dir.join(LOG_FILE).display(),
+        config.max_file_mb,
+        config.keep_files
+    );
+    Ok(())
+}
+
+// --- Crash reports: opt-in, chained in front of the default hook so stderr still shows it ---
+pub fn install_crash_reporter(config: &LoggingConfig, channels: Vec<ChannelStats>) {
+    if !config.crash_reports {
+        return;
+    }
+    let dir = config.dir.clone().unwrap_or_else(|| PathBuf::from("logs"));
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic| {
+        match write_crash_report(&dir, &channels, panic) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {:#}", e),
+        }
+        default_hook(panic);
+    }));
+    info!("Crash reports enabled");
+}
+
+fn write_crash_report(
+    dir: &Path,
+    channels: &[ChannelStats],
+    panic: &PanicHookInfo<'_>,
+) -> Result<PathBuf> {
+    let now = chrono::Local::now();
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "SAM_CAM_BAM {} crash report",
+        env!("CARGO_PKG_VERSION")
+    );
+    let _ = writeln!(report, "Time: {}", now.format(TIMESTAMP_FORMAT));
+    let thread = std::thread::current();
+    let _ = writeln!(report, "Thread: {}", thread.name().unwrap_or("<unnamed>"));
+    let _ = writeln!(report, "\n{}\n", panic);
+    let _ = writeln!(report, "--- Backtrace ---\n{}", Backtrace::force_capture());
+    let _ = writeln!(report, "\n--- Pipeline ---");
+    match metrics::json_snapshot(channels) {
+        Ok(json) => report.push_str(&json),
+        Err(e) => {
+            let _ = write!(report, "unavailable: {:#}", e);
+        }
+    }
+    let _ = writeln!(report, "\n\n--- Last warnings and errors ---");
+    // try_lock: the panic may have happened while this thread was logging
+    match shared().recent.try_lock() {
+        Ok(recent) if recent.is_empty() => report.push_str("none\n"),
+        Ok(recent) => recent.iter().for_each(|line| {
+            let _ = writeln!(report, "{}", line);
+        }),
+        Err(_) => report.push_str("unavailable\n"),
+    }
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+    fs::write(&path, report).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
@@ -0,0 +1,176 @@
+// src/loopback.rs
+// Setup assistant that finds the input carrying the system audio. It plays a test tone through
+// the default output while listening on every input device, and picks the input where the tone
+// rises furthest above that input's own level during a silent lead-in. Works whatever the
+// loopback driver is called (BlackHole, Loopback, Soundflower, VB-Cable, PipeWire monitors).
+use anyhow::{bail, Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::Stream;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+
+use crate::live_audio;
+
+const TONE_HZ: f32 = 1000.0;
+const TONE_AMPLITUDE: f32 = 0.25; // -12 dBFS
+const QUIET_SECS: f32 = 0.8; // Silent lead-in: each input's own noise/music level
+const TONE_SECS: f32 = 1.5;
+const SETTLE_SECS: f32 = 0.3; // Skipped at the start of each phase (output/input latency)
+const MIN_RISE_DB: f32 = 20.0; // Tone must stand this far above the lead-in
+const STALL_SECS: f32 = 2.0; // Gives up this long past the test if the output stopped playing
+
+// Tone power per phase of one input: (quiet sum, quiet blocks, tone sum, tone blocks)
+type Levels = Arc<Mutex<HashMap<String, (f64, u32, f64, u32)>>>;
+
+// Goertzel power at `hz`, normalized so a full-scale sine reads about 0.25
+fn tone_power(samples: &[f32], rate: u32, hz: f32) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let k = 2.0 * (2.0 * std::f64::consts::PI * hz as f64 / rate as f64).cos();
+    let (mut s1, mut s2) = (0.0f64, 0.0f64);
+    for &x in samples {
+        let s = x as f64 + k * s1 - s2;
+        s2 = s1;
+        s1 = s;
+    }
+    let n = samples.len() as f64;
+    (s1 * s1 + s2 * s2 - k * s1 * s2) / (n * n)
+}
+
+pub struct LoopbackWizard {
+    _output: Stream,
+    _inputs: Vec<Stream>,
+    stop: Arc<AtomicBool>,
+    levels: Levels,
+    played: Arc<AtomicU64>, // Frames the output has rendered; the phases are timed from this
+    rate: f32,
+    started: Instant,
+}
+
+impl LoopbackWizard {
+    pub fn start() -> Result<Self> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .context("No default output device for the test tone")?;
+        let config = device
+            .default_output_config()
+            .context("Failed to query the output device config")?;
+        let rate = config.sample_rate().0 as f32;
+        let channels = config.channels() as usize;
+        let quiet_frames = (rate * QUIET_SECS) as u64;
+        let tone_frames = (rate * TONE_SECS) as u64;
+        let played = Arc::new(AtomicU64::new(0));
+        let output_played = played.clone();
+        let mut frame: u64 = 0;
+        let output = device
+            .build_output_stream(
+                &config.into(),
+                move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                    for out in data.chunks_exact_mut(channels) {
+                        let t = frame.wrapping_sub(quiet_frames);
+                        let value = if frame >= quiet_frames && t < tone_frames {
+                            let phase = 2.0 * std::f32::consts::PI * TONE_HZ * t as f32 / rate;
+                            TONE_AMPLITUDE * phase.sin()
+                        } else {
+                            0.0
+                        };
+                        out.fill(value);
+                        frame += 1;
+                    }
+                    output_played.store(frame, Ordering::Relaxed);
+                },
+                |err| log::error!("Test tone output stream error: {}", err),
+                None,
+            )
+            .context("Failed to open the test tone output stream (needs f32 output)")?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let levels: Levels = Arc::default();
+        let mut inputs = Vec::new();
+        for name in live_audio::list_input_devices()? {
+            let levels = levels.clone();
+            let device_name = name.clone();
+            let played = played.clone();
+            let on_samples = move |in_rate: u32, samples: &[f32]| {
+                // Nothing counts until the output is actually playing
+                let frames = played.load(Ordering::Relaxed);
+                if frames == 0 {
+                    return;
+                }
+                let elapsed = frames as f32 / rate;
+                let power = tone_power(samples, in_rate, TONE_HZ);
+                let mut levels = levels.lock().unwrap();
+                let entry = levels.entry(device_name.clone()).or_default();
+                if (SETTLE_SECS..QUIET_SECS).contains(&elapsed) {
+                    entry.0 += power;
+                    entry.1 += 1;
+                } else if (QUIET_SECS + SETTLE_SECS..QUIET_SECS + TONE_SECS).contains(&elapsed) {
+                    entry.2 += power;
+                    entry.3 += 1;
+                }
+            };
+            // Busy or exotic devices are skipped; the rest still get compared
+            match live_audio::start_channel_capture(&name, 0, stop.clone(), on_samples) {
+                Ok(stream) => inputs.push(stream),
+                Err(e) => warn!("Loopback wizard: skipping '{}': {:#}", name, e),
+            }
+        }
+        if inputs.is_empty() {
+            bail!("No input device could be opened");
+        }
+        output.play().context("Failed to start the test tone")?;
+        let started = Instant::now();
+        info!("Loopback wizard: listening on {} inputs", inputs.len());
+        Ok(Self {
+            _output: output,
+            _inputs: inputs,
+            stop,
+            levels,
+            played,
+            rate,
+            started,
+        })
+    }
+
+    pub fn is_done(&self) -> bool {
+        let total = QUIET_SECS + TONE_SECS + SETTLE_SECS;
+        self.played.load(Ordering::Relaxed) as f32 >= total * self.rate
+            || self.started.elapsed() >= Duration::from_secs_f32(total + STALL_SECS)
+    }
+
+    // Name of the input where the tone rose the most, if it rose enough to be the loopback
+    pub fn finish(self) -> Result<String> {
+        self.stop.store(true, Ordering::Relaxed);
+        let levels = self.levels.lock().unwrap();
+        let mut rises: Vec<(f32, &String)> = levels
+            .iter()
+            .filter(|(_, l)| l.1 > 0 && l.3 > 0)
+            .map(|(name, &(quiet, qn, tone, tn))| {
+                let quiet = (quiet / qn as f64).max(1e-14);
+                let tone = (tone / tn as f64).max(1e-14);
+                ((10.0 * (tone / quiet).log10()) as f32, name)
+            })
+            .collect();
+        rises.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (rise, name) in &rises {
+            info!("Loopback wizard: '{}' +{:.1} dB", name, rise);
+        }
+        match rises.first() {
+            Some((rise, name)) if *rise >= MIN_RISE_DB => Ok((*name).clone()),
+            Some((rise, name)) => bail!(
+                "No input heard the tone clearly (best: '{}', +{:.0} dB); check that the \
+                 system output is routed into a loopback device",
+                name,
+                rise
+            ),
+            None => bail!("No input delivered audio during the test"),
+        }
+    }
+}
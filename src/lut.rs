@@ -0,0 +1,284 @@
+// src/lut.rs
+use anyhow::{bail, Context, Result};
+use image::RgbImage;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// Folder scanned for the LUT picker
+pub const LUT_DIR: &str = "luts";
+// Largest LUT_3D_SIZE accepted; 256³ entries is already ~200 MB of table
+const MAX_LUT_SIZE: usize = 256;
+
+// --- Output grade: a .cube file blended over the composited frame ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LutSettings {
+    pub path: String, // Empty = no grade
+    pub strength: f32,
+}
+
+impl Default for LutSettings {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            strength: 1.0,
+        }
+    }
+}
+
+// --- Adobe/Resolve .cube 3D LUT (red varies fastest) ---
+#[derive(Debug, Clone)]
+pub struct Lut3d {
+    size: usize,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    table: Vec<[f32; 3]>,
+}
+
+fn parse_triplet(parts: &[&str]) -> Option<[f32; 3]> {
+    match parts {
+        [r, g, b] => Some([r.parse().ok()?, g.parse().ok()?, b.parse().ok()?]),
+        _ => None,
+    }
+}
+
+impl Lut3d {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut size = 0;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+        for (n, raw) in source.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts[0] {
+                "TITLE" => {}
+                "LUT_3D_SIZE" => {
+                    size = parts
+                        .get(1)
+                        .and_then(|s| s.parse().ok())
+                        .with_context(|| format!("Line {}: bad LUT_3D_SIZE", n + 1))?;
+                    if size > MAX_LUT_SIZE {
+                        bail!("LUT_3D_SIZE {} is above {}", size, MAX_LUT_SIZE);
+                    }
+                }
+                "DOMAIN_MIN" | "DOMAIN_MAX" => {
+                    let value = parse_triplet(&parts[1..])
+                        .with_context(|| format!("Line {}: bad {}", n + 1, parts[0]))?;
+                    if parts[0] == "DOMAIN_MIN" {
+                        domain_min = value;
+                    } else {
+                        domain_max = value;
+                    }
+                }
+                "LUT_1D_SIZE" => bail!("1D LUTs are not supported"),
+                _ => {
+                    let value = parse_triplet(&parts)
+                        .with_context(|| format!("Line {}: expected 'r g b'", n + 1))?;
+                    table.push(value);
+                }
+            }
+        }
+        if size < 2 {
+            bail!("Missing or invalid LUT_3D_SIZE");
+        }
+        let entries = size.checked_pow(3).context("LUT_3D_SIZE overflows")?;
+        if table.len() != entries {
+            bail!("Expected {} entries, found {}", entries, table.len());
+        }
+        Ok(Self {
+            size,
+            domain_min,
+            domain_max,
+            table,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read LUT {}", path.display()))?;
+        let lut = Self::parse(&source)
+            .with_context(|| format!("Failed to parse LUT {}", path.display()))?;
+        info!("Loaded {}³ LUT from {}", lut.size, path.display());
+        Ok(lut)
+    }
+
+    #[inline]
+    fn entry(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        self.table[(b * self.size + g) * self.size + r]
+    }
+
+    // Trilinear lookup; input in 0-1
+    fn sample(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let max = (self.size - 1) as f32;
+        let pos: [f32; 3] = std::array::from_fn(|c| {
+            let range = (self.domain_max[c] - self.domain_min[c]).max(1e-6);
+            ((rgb[c] - self.domain_min[c]) / range).clamp(0.0, 1.0) * max
+        });
+        let base = pos.map(|p| (p.floor() as usize).min(self.size - 2));
+        let frac: [f32; 3] = std::array::from_fn(|c| pos[c] - base[c] as f32);
+        let mut out = [0.0f32; 3];
+        for corner in 0..8 {
+            let (dr, dg, db) = (corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+            let weight = (if dr == 1 { frac[0] } else { 1.0 - frac[0] })
+                * (if dg == 1 { frac[1] } else { 1.0 - frac[1] })
+                * (if db == 1 { frac[2] } else { 1.0 - frac[2] });
+            let value = self.entry(base[0] + dr, base[1] + dg, base[2] + db);
+            for (o, v) in out.iter_mut().zip(value) {
+                *o += v * weight;
+            }
+        }
+        out
+    }
+
+    // CPU path; there is no GPU compositor to hand this to yet
+    pub fn apply(&self, image: &mut RgbImage, strength: f32) {
+        let strength = strength.clamp(0.0, 1.0);
+        if strength <= 0.0 {
+            return;
+        }
+        for px in image.pixels_mut() {
+            let rgb = [
+                px[0] as f32 / 255.0,
+                px[1] as f32 / 255.0,
+                px[2] as f32 / 255.0,
+            ];
+            let graded = self.sample(rgb);
+            for ((out, src), dst) in px.0.iter_mut().zip(rgb).zip(graded) {
+                let v = src + (dst - src) * strength;
+                *out = (v.clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+            }
+        }
+    }
+}
+
+// --- Loaded grade for the segmentation thread; reloads only when the path changes ---
+#[derive(Default)]
+pub struct LutStage {
+    lut: Option<Lut3d>,
+    loaded: String,
+    strength: f32,
+}
+
+impl LutStage {
+    pub fn configure(&mut self, settings: &LutSettings) -> Result<()> {
+        self.strength = settings.strength;
+        let path = settings.path.trim();
+        if path == self.loaded {
+            return Ok(());
+        }
+        self.lut = None;
+        self.loaded.clear();
+        if path.is_empty() {
+            return Ok(());
+        }
+        self.lut = Some(Lut3d::load(Path::new(path))?);
+        self.loaded = path.to_string();
+        Ok(())
+    }
+
+    pub fn apply(&self, image: &mut RgbImage) {
+        if let Some(lut) = &self.lut {
+            lut.apply(image, self.strength);
+        }
+    }
+}
+
+// .cube files in LUT_DIR, sorted by name
+pub fn list_luts() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(LUT_DIR) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("cube")))
+        .collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2³ identity, red varying fastest, as Resolve writes it
+    const IDENTITY_CUBE: &str = "\
+# Created by hand
+TITLE \"Identity\"
+LUT_3D_SIZE 2
+DOMAIN_MIN 0.0 0.0 0.0
+DOMAIN_MAX 1.0 1.0 1.0
+
+0.0 0.0 0.0
+1.0 0.0 0.0
+0.0 1.0 0.0
+1.0 1.0 0.0
+0.0 0.0 1.0
+1.0 0.0 1.0
+0.0 1.0 1.0
+1.0 1.0 1.0
+";
+
+    fn inverted_cube() -> String {
+        let mut source = String::from("LUT_3D_SIZE 2\n");
+        for b in 0..2 {
+            for g in 0..2 {
+                for r in 0..2 {
+                    source.push_str(&format!("{} {} {}\n", 1 - r, 1 - g, 1 - b));
+                }
+            }
+        }
+        source
+    }
+
+    #[test]
+    fn parses_known_table() {
+        let lut = Lut3d::parse(IDENTITY_CUBE).unwrap();
+        assert_eq!(lut.size, 2);
+        assert_eq!(lut.domain_min, [0.0; 3]);
+        assert_eq!(lut.domain_max, [1.0; 3]);
+        assert_eq!(lut.entry(1, 0, 0), [1.0, 0.0, 0.0]);
+        assert_eq!(lut.entry(0, 1, 0), [0.0, 1.0, 0.0]);
+        assert_eq!(lut.entry(0, 0, 1), [0.0, 0.0, 1.0]);
+        let mid = lut.sample([0.25, 0.5, 0.75]);
+        for (got, want) in mid.iter().zip([0.25, 0.5, 0.75]) {
+            assert!((got - want).abs() < 1e-6, "{:?}", mid);
+        }
+    }
+
+    #[test]
+    fn applies_with_strength() {
+        let lut = Lut3d::parse(&inverted_cube()).unwrap();
+        let mut full = RgbImage::from_pixel(1, 1, image::Rgb([0, 255, 51]));
+        lut.apply(&mut full, 1.0);
+        assert_eq!(full.get_pixel(0, 0).0, [255, 0, 204]);
+        let mut none = RgbImage::from_pixel(1, 1, image::Rgb([0, 255, 51]));
+        lut.apply(&mut none, 0.0);
+        assert_eq!(none.get_pixel(0, 0).0, [0, 255, 51]);
+    }
+
+    #[test]
+    fn rejects_size_mismatch() {
+        let (short, _) = IDENTITY_CUBE.trim_end().rsplit_once('\n').unwrap(); // Last entry gone
+        let err = Lut3d::parse(short).unwrap_err().to_string();
+        assert_eq!(err, "Expected 8 entries, found 7");
+        assert!(Lut3d::parse("0.0 0.0 0.0\n").is_err()); // No LUT_3D_SIZE
+    }
+
+    #[test]
+    fn rejects_oversized_lut() {
+        let err = Lut3d::parse("LUT_3D_SIZE 257\n").unwrap_err();
+        assert_eq!(err.to_string(), "LUT_3D_SIZE 257 is above 256");
+        assert!(Lut3d::parse(&format!("LUT_3D_SIZE {}\n", usize::MAX)).is_err());
+    }
+
+    #[test]
+    fn rejects_1d_lut() {
+        let err = Lut3d::parse("LUT_1D_SIZE 2\n0.0 0.0 0.0\n1.0 1.0 1.0\n").unwrap_err();
+        assert_eq!(err.to_string(), "1D LUTs are not supported");
+    }
+}
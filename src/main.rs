@@ -2,17 +2,146 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+#[cfg(not(target_arch = "wasm32"))]
+mod alerting;
+#[cfg(not(target_arch = "wasm32"))]
+mod audio_record;
+#[cfg(not(target_arch = "wasm32"))]
+mod automation;
+#[cfg(not(target_arch = "wasm32"))]
+mod bench;
+#[cfg(not(target_arch = "wasm32"))]
 mod camera;
-mod segmentation; 
+#[cfg(not(target_arch = "wasm32"))]
+mod cli;
+#[cfg(not(target_arch = "wasm32"))]
+mod cues;
+#[cfg(not(target_arch = "wasm32"))]
+mod damage;
+#[cfg(not(target_arch = "wasm32"))]
+mod deck;
+#[cfg(not(target_arch = "wasm32"))]
+mod config;
+#[cfg(not(target_arch = "wasm32"))]
+mod diagnostics;
+#[cfg(not(target_arch = "wasm32"))]
+mod ensemble;
+#[cfg(not(target_arch = "wasm32"))]
+mod segmentation;
+#[cfg(not(target_arch = "wasm32"))]
+mod session;
+#[cfg(not(target_arch = "wasm32"))]
+mod sprite;
+#[cfg(not(target_arch = "wasm32"))]
+mod stems;
+#[cfg(not(target_arch = "wasm32"))]
+mod streaming;
+#[cfg(not(target_arch = "wasm32"))]
+mod supervisor;
+#[cfg(not(target_arch = "wasm32"))]
+mod tempo;
+#[cfg(not(target_arch = "wasm32"))]
+mod telemetry;
+#[cfg(not(target_arch = "wasm32"))]
 mod ui;
+#[cfg(not(target_arch = "wasm32"))]
 mod music;
+#[cfg(not(target_arch = "wasm32"))]
+mod overlay;
+#[cfg(not(target_arch = "wasm32"))]
+mod pacing;
+#[cfg(not(target_arch = "wasm32"))]
+mod paint_layer;
+#[cfg(not(target_arch = "wasm32"))]
+mod permissions;
+#[cfg(not(target_arch = "wasm32"))]
+mod pipeline;
+#[cfg(not(target_arch = "wasm32"))]
+mod platform;
+#[cfg(not(target_arch = "wasm32"))]
+mod plugin;
+#[cfg(not(target_arch = "wasm32"))]
+mod post;
+#[cfg(not(target_arch = "wasm32"))]
+mod preview;
+#[cfg(not(target_arch = "wasm32"))]
+mod profile;
+#[cfg(not(target_arch = "wasm32"))]
+mod project;
+#[cfg(not(target_arch = "wasm32"))]
+mod response;
+#[cfg(not(target_arch = "wasm32"))]
+mod schedule;
+#[cfg(not(target_arch = "wasm32"))]
 mod live_audio;
+#[cfg(not(target_arch = "wasm32"))]
+mod latency;
+#[cfg(not(target_arch = "wasm32"))]
+mod logging;
+#[cfg(not(target_arch = "wasm32"))]
+mod loopback;
+#[cfg(not(target_arch = "wasm32"))]
+mod lut;
+#[cfg(not(target_arch = "wasm32"))]
+mod mask_edit;
+#[cfg(not(target_arch = "wasm32"))]
+mod matting;
+#[cfg(not(target_arch = "wasm32"))]
+mod metrics;
+#[cfg(not(target_arch = "wasm32"))]
+mod midi;
+#[cfg(not(target_arch = "wasm32"))]
+mod warp;
+#[cfg(not(target_arch = "wasm32"))]
+mod keyed;
+#[cfg(not(target_arch = "wasm32"))]
+mod timecode;
+#[cfg(not(target_arch = "wasm32"))]
+mod link;
+#[cfg(not(target_arch = "wasm32"))]
+mod control;
+#[cfg(not(target_arch = "wasm32"))]
+mod file_source;
+#[cfg(not(target_arch = "wasm32"))]
+mod gamepad;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod i18n;
+// Shared by both targets; the web build only uses part of it
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
 mod visuals;
 
+// --- Web build: browser camera, Web Audio analysis and onnxruntime-web inference ---
+#[cfg(target_arch = "wasm32")]
+#[path = "web/camera.rs"]
+mod camera;
+#[cfg(target_arch = "wasm32")]
+#[path = "web/live_audio.rs"]
+mod live_audio;
+#[cfg(target_arch = "wasm32")]
+#[path = "web/segmentation.rs"]
+mod segmentation;
+#[cfg(target_arch = "wasm32")]
+#[path = "web/app.rs"]
+mod web_app;
+
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    logging::init();
     log::info!("Starting SAM_CAM_BAM (EdgeSAM Periodic)");
+    let launch_options = cli::parse_args();
+    if let Some(frames) = launch_options.bench_frames {
+        if let Err(e) = bench::run(frames) {
+            log::error!("Benchmark failed: {:#}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let config = config::AppConfig::load(launch_options.config_path.as_deref());
+    if let Err(e) = logging::start_file_logging(&config.logging) {
+        log::error!("File logging disabled: {:#}", e);
+    }
 
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -24,6 +153,51 @@ fn main() -> eframe::Result {
     eframe::run_native(
         "SAM_CAM_BAM", 
         native_options,
-        Box::new(|cc| Ok(Box::new(ui::WebcamAppUI::new(cc)))),
+        Box::new(move |cc| Ok(Box::new(ui::WebcamAppUI::new(cc, launch_options, config)))),
     )
 }
+
+// When compiling to web using trunk:
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use eframe::wasm_bindgen::JsCast as _;
+
+    // Redirect `log` message to `console.log` and friends:
+    eframe::WebLogger::init(log::LevelFilter::Debug).ok();
+
+    let web_options = eframe::WebOptions::default();
+
+    wasm_bindgen_futures::spawn_local(async {
+        let document = web_sys::window()
+            .expect("No window")
+            .document()
+            .expect("No document");
+
+        let canvas = document
+            .get_element_by_id("the_canvas_id")
+            .expect("Failed to find the_canvas_id")
+            .dyn_into::<web_sys::HtmlCanvasElement>()
+            .expect("the_canvas_id was not a HtmlCanvasElement");
+
+        let start_result = eframe::WebRunner::new()
+            .start(
+                canvas,
+                web_options,
+                Box::new(|cc| Ok(Box::new(web_app::WebApp::new(cc)))),
+            )
+            .await;
+
+        // Remove the loading text and spinner:
+        if let Some(loading_text) = document.get_element_by_id("loading_text") {
+            match start_result {
+                Ok(_) => loading_text.remove(),
+                Err(e) => {
+                    loading_text.set_inner_html(
+                        "<p> The app has crashed. See the developer console for details. </p>",
+                    );
+                    panic!("Failed to start eframe: {e:?}");
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,177 @@
+// src/mask_edit.rs
+use image::GrayImage;
+use imageproc::rect::Rect;
+use serde::{Deserialize, Serialize};
+
+// Dabs kept per slot; further painting is ignored until the slot is cleared
+pub const MAX_DABS: usize = 2048;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BrushMode {
+    Add,
+    Remove,
+}
+
+// --- One brush dab in normalized frame coordinates; radius is a fraction of frame width ---
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BrushStroke {
+    pub slot: usize,
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub mode: BrushMode,
+}
+
+// --- A dab pinned to the slot's bbox: position in fractions of it, radius of its width ---
+#[derive(Debug, Clone, Copy)]
+struct AnchoredDab {
+    x: f32,
+    y: f32,
+    radius: f32,
+    mode: BrushMode,
+}
+
+// --- User corrections for one slot, applied on top of the model mask every frame ---
+// Dabs follow the subject: each is anchored to the bbox the slot had when it was painted
+#[derive(Debug, Default)]
+pub struct MaskEdits {
+    dabs: Vec<AnchoredDab>,
+    // Painted since the last frame; anchored once the slot's bbox is known
+    pending: Vec<BrushStroke>,
+}
+
+impl MaskEdits {
+    pub fn push(&mut self, stroke: BrushStroke) {
+        if self.dabs.len() + self.pending.len() < MAX_DABS {
+            self.pending.push(stroke);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.dabs.clear();
+        self.pending.clear();
+    }
+
+    fn anchor_pending(&mut self, (width, height): (u32, u32), bbox: Rect) {
+        let (bw, bh) = (bbox.width() as f32, bbox.height() as f32);
+        self.dabs
+            .extend(self.pending.drain(..).map(|s| AnchoredDab {
+                x: (s.x * width as f32 - bbox.left() as f32) / bw,
+                y: (s.y * height as f32 - bbox.top() as f32) / bh,
+                radius: s.radius * width as f32 / bw,
+                mode: s.mode,
+            }));
+    }
+
+    // Model mask united with painted-in areas minus erased ones; None when nothing to apply.
+    // Later dabs override earlier ones, so erasing over a painted area works as expected
+    pub fn apply(&mut self, mask: &GrayImage, bbox: Rect) -> Option<(GrayImage, Rect)> {
+        if self.dabs.is_empty() && self.pending.is_empty() {
+            return None;
+        }
+        let (w, h) = mask.dimensions();
+        self.anchor_pending((w, h), bbox);
+        let mut edited = mask.clone();
+        let mut added_bounds: Option<Rect> = None;
+        for dab in &self.dabs {
+            let value = match dab.mode {
+                BrushMode::Add => 255,
+                BrushMode::Remove => 0,
+            };
+            let cx = bbox.left() + (dab.x * bbox.width() as f32) as i32;
+            let cy = bbox.top() + (dab.y * bbox.height() as f32) as i32;
+            let r = ((dab.radius * bbox.width() as f32) as i32).max(1);
+            // Only the dab's own square, clipped to the frame
+            let (x0, y0) = ((cx - r).max(0), (cy - r).max(0));
+            let (x1, y1) = ((cx + r).min(w as i32 - 1), (cy + r).min(h as i32 - 1));
+            if x0 > x1 || y0 > y1 {
+                continue;
+            }
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    let (dx, dy) = (x - cx, y - cy);
+                    if dx * dx + dy * dy <= r * r {
+                        edited.get_pixel_mut(x as u32, y as u32).0[0] = value;
+                    }
+                }
+            }
+            if dab.mode == BrushMode::Add {
+                let rect = Rect::at(x0, y0).of_size((x1 - x0 + 1) as u32, (y1 - y0 + 1) as u32);
+                added_bounds = Some(added_bounds.map_or(rect, |b| union(b, rect)));
+            }
+        }
+        let bbox = match added_bounds {
+            Some(added) => union(bbox, added),
+            None => bbox,
+        };
+        Some((edited, bbox))
+    }
+}
+
+fn union(a: Rect, b: Rect) -> Rect {
+    let left = a.left().min(b.left());
+    let top = a.top().min(b.top());
+    let right = a.right().max(b.right());
+    let bottom = a.bottom().max(b.bottom());
+    Rect::at(left, top).of_size((right - left + 1) as u32, (bottom - top + 1) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dab(x: f32, y: f32, mode: BrushMode) -> BrushStroke {
+        BrushStroke {
+            slot: 0,
+            x,
+            y,
+            radius: 0.05,
+            mode,
+        }
+    }
+
+    #[test]
+    fn dabs_follow_the_bbox() {
+        let mask = GrayImage::new(100, 100);
+        let mut edits = MaskEdits::default();
+        edits.push(dab(0.2, 0.2, BrushMode::Add));
+        let (edited, _) = edits
+            .apply(&mask, Rect::at(10, 10).of_size(20, 20))
+            .unwrap();
+        assert_eq!(edited.get_pixel(20, 20).0, [255]);
+        // Subject moved 50 px right: the dab goes with it
+        let (edited, bbox) = edits
+            .apply(&mask, Rect::at(60, 10).of_size(20, 20))
+            .unwrap();
+        assert_eq!(edited.get_pixel(20, 20).0, [0]);
+        assert_eq!(edited.get_pixel(70, 20).0, [255]);
+        assert_eq!(bbox, Rect::at(60, 10).of_size(20, 20));
+    }
+
+    #[test]
+    fn later_dabs_win_and_grow_the_bbox() {
+        let mask = GrayImage::from_pixel(100, 100, image::Luma([255]));
+        let mut edits = MaskEdits::default();
+        edits.push(dab(0.5, 0.5, BrushMode::Remove));
+        edits.push(dab(0.9, 0.5, BrushMode::Add));
+        let (edited, bbox) = edits
+            .apply(&mask, Rect::at(40, 40).of_size(20, 20))
+            .unwrap();
+        assert_eq!(edited.get_pixel(50, 50).0, [0]);
+        assert_eq!(edited.get_pixel(10, 10).0, [255]); // Outside every dab
+        assert_eq!((bbox.left(), bbox.right()), (40, 95));
+    }
+
+    #[test]
+    fn caps_dabs_per_slot() {
+        let mut edits = MaskEdits::default();
+        for _ in 0..MAX_DABS + 10 {
+            edits.push(dab(0.5, 0.5, BrushMode::Add));
+        }
+        assert_eq!(edits.pending.len(), MAX_DABS);
+        edits.clear();
+        assert!(edits
+            .apply(&GrayImage::new(8, 8), Rect::at(0, 0).of_size(8, 8))
+            .is_none());
+    }
+}
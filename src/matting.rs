@@ -0,0 +1,220 @@
+// src/matting.rs
+// Edge refinement for model masks. FastSAM masks come out blocky at 720p; a guided filter with
+// the camera frame as guide turns them into soft alpha mattes that follow the image's own edges
+// (hair, fingers, sleeves). Only the bbox plus a margin is filtered.
+use image::{GrayImage, Luma, RgbImage};
+use imageproc::rect::Rect;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MatteSettings {
+    pub enabled: bool,
+    pub radius: u32, // Box radius in pixels; larger follows edges further from the model's
+    pub epsilon: f32, // Regularization; larger keeps more of the raw mask, smaller hugs edges
+}
+
+impl Default for MatteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 6,
+            epsilon: 0.002,
+        }
+    }
+}
+
+// Mean over each pixel's (2r+1)^2 window, clipped at the borders, via a summed-area table
+fn box_mean(src: &[f32], w: usize, h: usize, r: usize) -> Vec<f32> {
+    let stride = w + 1;
+    let mut sums = vec![0f64; stride * (h + 1)];
+    for y in 0..h {
+        let mut row = 0f64;
+        for x in 0..w {
+            row += src[y * w + x] as f64;
+            sums[(y + 1) * stride + x + 1] = sums[y * stride + x + 1] + row;
+        }
+    }
+    let mut out = vec![0f32; w * h];
+    for y in 0..h {
+        let (y0, y1) = (y.saturating_sub(r), (y + r + 1).min(h));
+        for x in 0..w {
+            let (x0, x1) = (x.saturating_sub(r), (x + r + 1).min(w));
+            let sum = sums[y1 * stride + x1] - sums[y0 * stride + x1] - sums[y1 * stride + x0]
+                + sums[y0 * stride + x0];
+            out[y * w + x] = (sum / ((y1 - y0) * (x1 - x0)) as f64) as f32;
+        }
+    }
+    out
+}
+
+// Soft alpha matte of `mask` inside `bbox` (grown by two radii), guided by the frame's luma.
+// Outside that region the mask is copied unchanged. Sizes must match.
+pub fn refine_mask(
+    guide: &RgbImage,
+    mask: &GrayImage,
+    bbox: Rect,
+    settings: &MatteSettings,
+) -> GrayImage {
+    let mut out = mask.clone();
+    if guide.dimensions() != mask.dimensions() || settings.radius == 0 {
+        return out;
+    }
+    let (fw, fh) = mask.dimensions();
+    let margin = 2 * settings.radius as i32;
+    let x0 = (bbox.left() - margin).clamp(0, fw as i32) as u32;
+    let y0 = (bbox.top() - margin).clamp(0, fh as i32) as u32;
+    let x1 = (bbox.right() + 1 + margin).clamp(0, fw as i32) as u32;
+    let y1 = (bbox.bottom() + 1 + margin).clamp(0, fh as i32) as u32;
+    let (w, h) = ((x1 - x0) as usize, (y1 - y0) as usize);
+    if w == 0 || h == 0 {
+        return out;
+    }
+
+    let mut luma = Vec::with_capacity(w * h);
+    let mut alpha = Vec::with_capacity(w * h);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let [r, g, b] = guide.get_pixel(x, y).0;
+            luma.push((0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32) / 255.0);
+            alpha.push(mask.get_pixel(x, y).0[0] as f32 / 255.0);
+        }
+    }
+    let r = settings.radius as usize;
+    let product = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(a, b)| a * b).collect::<Vec<_>>();
+    let mean_i = box_mean(&luma, w, h, r);
+    let mean_p = box_mean(&alpha, w, h, r);
+    let corr_ii = box_mean(&product(&luma, &luma), w, h, r);
+    let corr_ip = box_mean(&product(&luma, &alpha), w, h, r);
+
+    // Per-window linear model alpha = a * luma + b, then averaged over overlapping windows
+    let mut a = vec![0f32; w * h];
+    let mut b = vec![0f32; w * h];
+    for i in 0..w * h {
+        let var_i = corr_ii[i] - mean_i[i] * mean_i[i];
+        let cov_ip = corr_ip[i] - mean_i[i] * mean_p[i];
+        a[i] = cov_ip / (var_i + settings.epsilon.max(1e-6));
+        b[i] = mean_p[i] - a[i] * mean_i[i];
+    }
+    let mean_a = box_mean(&a, w, h, r);
+    let mean_b = box_mean(&b, w, h, r);
+    for (i, (y, x)) in (y0..y1)
+        .flat_map(|y| (x0..x1).map(move |x| (y, x)))
+        .enumerate()
+    {
+        let q = (mean_a[i] * luma[i] + mean_b[i]).clamp(0.0, 1.0);
+        out.put_pixel(x, y, Luma([(q * 255.0).round() as u8]));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Mean over the clipped window, computed directly
+    fn brute_mean(src: &[f32], w: usize, h: usize, r: usize, x: usize, y: usize) -> f32 {
+        let (x0, x1) = (x.saturating_sub(r), (x + r + 1).min(w));
+        let (y0, y1) = (y.saturating_sub(r), (y + r + 1).min(h));
+        let sum: f32 = (y0..y1)
+            .flat_map(|yy| (x0..x1).map(move |xx| src[yy * w + xx]))
+            .sum();
+        sum / ((x1 - x0) * (y1 - y0)) as f32
+    }
+
+    // Vertical step: dark left of `edge`, bright from it on
+    fn step_guide(w: u32, h: u32, edge: u32) -> RgbImage {
+        RgbImage::from_fn(w, h, |x, _| image::Rgb([if x < edge { 0 } else { 255 }; 3]))
+    }
+
+    fn step_mask(w: u32, h: u32, edge: u32) -> GrayImage {
+        GrayImage::from_fn(w, h, |x, _| Luma([if x < edge { 0 } else { 255 }]))
+    }
+
+    #[test]
+    fn box_mean_of_a_constant_is_constant() {
+        let src = vec![0.25f32; 7 * 5];
+        for value in box_mean(&src, 7, 5, 3) {
+            assert!((value - 0.25).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn box_mean_clips_windows_at_the_borders() {
+        let (w, h) = (9, 6);
+        let src: Vec<f32> = (0..w * h).map(|i| ((i * 37) % 11) as f32).collect();
+        let out = box_mean(&src, w, h, 2);
+        for y in 0..h {
+            for x in 0..w {
+                let expected = brute_mean(&src, w, h, 2, x, y);
+                assert!((out[y * w + x] - expected).abs() < 1e-4, "({}, {})", x, y);
+            }
+        }
+        // A corner averages only the 3x3 that exists, not a zero-padded 5x5
+        assert!((out[0] - brute_mean(&src, w, h, 2, 0, 0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn flat_guide_keeps_a_solid_mask() {
+        let guide = RgbImage::from_pixel(24, 16, image::Rgb([90, 120, 60]));
+        let mask = GrayImage::from_pixel(24, 16, Luma([255]));
+        let bbox = Rect::at(0, 0).of_size(24, 16);
+        let out = refine_mask(&guide, &mask, bbox, &MatteSettings::default());
+        assert!(out.pixels().all(|p| p.0[0] == 255));
+    }
+
+    #[test]
+    fn step_edge_stays_sharp() {
+        let (w, h) = (32, 16);
+        let bbox = Rect::at(0, 0).of_size(w, h);
+        let settings = MatteSettings {
+            enabled: true,
+            radius: 4,
+            epsilon: 0.002,
+        };
+        let out = refine_mask(&step_guide(w, h, 16), &step_mask(w, h, 16), bbox, &settings);
+        for y in 0..h {
+            assert!(out.get_pixel(15, y).0[0] < 5);
+            assert!(out.get_pixel(16, y).0[0] > 250);
+        }
+    }
+
+    #[test]
+    fn mask_edge_moves_toward_the_image_edge() {
+        // The model's edge sits two pixels left of the real one
+        let (w, h) = (32, 16);
+        let bbox = Rect::at(0, 0).of_size(w, h);
+        let settings = MatteSettings {
+            enabled: true,
+            radius: 4,
+            epsilon: 0.002,
+        };
+        let out = refine_mask(&step_guide(w, h, 16), &step_mask(w, h, 14), bbox, &settings);
+        assert!(out.get_pixel(14, 8).0[0] < 128);
+        assert!(out.get_pixel(16, 8).0[0] > 250);
+    }
+
+    #[test]
+    fn only_the_grown_bbox_is_filtered() {
+        // Bbox in the corner: the region is clipped at the frame, and pixels past its margin
+        // are copied unchanged
+        let (w, h) = (24, 24);
+        let mut mask = step_mask(w, h, 2);
+        mask.put_pixel(23, 23, Luma([77]));
+        let settings = MatteSettings {
+            enabled: true,
+            radius: 3,
+            epsilon: 0.002,
+        };
+        let bbox = Rect::at(0, 0).of_size(4, 4);
+        let out = refine_mask(&step_guide(w, h, 2), &mask, bbox, &settings);
+        assert_eq!(out.get_pixel(23, 23).0[0], 77);
+        assert!(out.get_pixel(0, 0).0[0] < 5);
+        assert!(out.get_pixel(3, 0).0[0] > 250);
+
+        // Mismatched sizes and a zero radius leave the mask alone
+        let small_guide = RgbImage::new(8, 8);
+        assert_eq!(refine_mask(&small_guide, &mask, bbox, &settings), mask);
+        let off = MatteSettings { radius: 0, ..settings };
+        assert_eq!(refine_mask(&step_guide(w, h, 12), &mask, bbox, &off), mask);
+    }
+}
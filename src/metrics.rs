@@ -0,0 +1,333 @@
+// src/metrics.rs
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::pipeline::ChannelStats;
+use crate::supervisor::{self, StopSignal};
+
+// --- Metric names (Prometheus style) ---
+pub const UI_FPS: &str = "sam_ui_fps";
+pub const SEG_FPS: &str = "sam_segmentation_fps";
+pub const CAMERA_FRAMES: &str = "sam_camera_frames_total";
+pub const SEG_FRAMES: &str = "sam_segmentation_frames_total";
+pub const CAMERA_FRAMES_SKIPPED: &str = "sam_camera_frames_skipped_total";
+pub const INFERENCE_MS: &str = "sam_inference_ms";
+pub const SEG_LOOP_MS: &str = "sam_segmentation_loop_ms";
+pub const AUDIO_UNDERRUNS: &str = "sam_audio_underruns_total";
+pub const UI_FRAMES_DROPPED: &str = "sam_ui_frames_dropped_total";
+pub const TRACKED_OBJECTS: &str = "sam_tracked_objects";
+
+const HELP: [(&str, &str); 10] = [
+    (UI_FPS, "Frames presented by the UI per second"),
+    (SEG_FPS, "Frames composited by the segmentation thread per second"),
+    (CAMERA_FRAMES, "Frames delivered by the camera or replay thread"),
+    (SEG_FRAMES, "Frames composited by the segmentation thread"),
+    (CAMERA_FRAMES_SKIPPED, "Camera frames superseded before segmentation got to them"),
+    (INFERENCE_MS, "Model forward pass latency in milliseconds"),
+    (SEG_LOOP_MS, "Full segmentation loop latency in milliseconds"),
+    (AUDIO_UNDERRUNS, "Times the audio processor ran dry and zeroed its output"),
+    (UI_FRAMES_DROPPED, "Composited frames replaced by a newer one before the UI showed them"),
+    (TRACKED_OBJECTS, "Objects currently tracked by the segmentation thread"),
+];
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(untagged)]
+enum Metric {
+    Counter(u64),
+    Gauge(f64),
+    Summary { count: u64, sum: f64, last: f64 },
+}
+
+// --- Process-wide registry; recording is a short mutex hold, cheap at per-frame rates ---
+#[derive(Debug, Default)]
+pub struct Metrics {
+    entries: Mutex<BTreeMap<&'static str, Metric>>,
+}
+
+pub fn global() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::default)
+}
+
+impl Metrics {
+    pub fn inc(&self, name: &'static str, by: u64) {
+        if let Ok(mut entries) = self.entries.lock() {
+            match entries.entry(name).or_insert(Metric::Counter(0)) {
+                Metric::Counter(v) => *v += by,
+                other => *other = Metric::Counter(by),
+            }
+        }
+    }
+
+    pub fn set(&self, name: &'static str, value: f64) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(name, Metric::Gauge(value));
+        }
+    }
+
+    pub fn observe(&self, name: &'static str, value: f64) {
+        if let Ok(mut entries) = self.entries.lock() {
+            let entry = entries.entry(name).or_insert(Metric::Summary {
+                count: 0,
+                sum: 0.0,
+                last: 0.0,
+            });
+            match entry {
+                Metric::Summary { count, sum, last } => {
+                    *count += 1;
+                    *sum += value;
+                    *last = value;
+                }
+                other => {
+                    *other = Metric::Summary {
+                        count: 1,
+                        sum: value,
+                        last: value,
+                    }
+                }
+            }
+        }
+    }
+
+    // Current value: counter total, gauge, or a summary's latest observation
+    pub fn value(&self, name: &'static str) -> Option<f64> {
+        let entries = self.entries.lock().ok()?;
+        entries.get(name).map(|m| match *m {
+            Metric::Counter(v) => v as f64,
+            Metric::Gauge(v) => v,
+            Metric::Summary { last, .. } => last,
+        })
+    }
+
+    fn snapshot(&self) -> BTreeMap<&'static str, Metric> {
+        self.entries.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+}
+
+// --- Per-second rate for gauges like FPS, updated by the thread doing the work ---
+#[derive(Debug)]
+pub struct RateMeter {
+    name: &'static str,
+    window_start: Instant,
+    events: u32,
+}
+
+impl RateMeter {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            window_start: Instant::now(),
+            events: 0,
+        }
+    }
+
+    pub fn tick(&mut self) {
+        self.events += 1;
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            global().set(self.name, self.events as f64 / elapsed.as_secs_f64());
+            self.events = 0;
+            self.window_start = Instant::now();
+        }
+    }
+}
+
+// --- Export settings (config file "metrics" section); everything is off by default ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsConfig {
+    pub http_addr: Option<String>, // e.g. "127.0.0.1:9464"; serves /metrics and /metrics.json
+    pub json_path: Option<PathBuf>, // Overwritten every `json_interval_secs`
+    pub json_interval_secs: u64,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            http_addr: None,
+            json_path: None,
+            json_interval_secs: 10,
+        }
+    }
+}
+
+impl MetricsConfig {
+    pub fn enabled(&self) -> bool {
+        self.http_addr.is_some() || self.json_path.is_some()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ChannelSnapshot {
+    queued: usize,
+    capacity: Option<usize>,
+    dropped: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonReport {
+    timestamp_ms: u128,
+    metrics: BTreeMap<&'static str, Metric>,
+    channels: BTreeMap<&'static str, ChannelSnapshot>,
+}
+
+fn json_report(channels: &[ChannelStats]) -> JsonReport {
+    JsonReport {
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0),
+        metrics: global().snapshot(),
+        channels: channels
+            .iter()
+            .map(|c| {
+                (
+                    c.name,
+                    ChannelSnapshot {
+                        queued: c.queued(),
+                        capacity: c.config.capacity,
+                        dropped: c.drops(),
+                    },
+                )
+            })
+            .collect(),
+    }
+}
+
+// Same document as /metrics.json, e.g. for crash reports
+pub fn json_snapshot(channels: &[ChannelStats]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&json_report(channels))?)
+}
+
+fn prometheus_text(channels: &[ChannelStats]) -> String {
+    let mut out = String::new();
+    for (name, metric) in global().snapshot() {
+        if let Some((_, help)) = HELP.iter().find(|(n, _)| *n == name) {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+        }
+        match metric {
+            Metric::Counter(v) => {
+                let _ = writeln!(out, "# TYPE {} counter\n{} {}", name, name, v);
+            }
+            Metric::Gauge(v) => {
+                let _ = writeln!(out, "# TYPE {} gauge\n{} {}", name, name, v);
+            }
+            Metric::Summary { count, sum, .. } => {
+                let _ = writeln!(
+                    out,
+                    "# TYPE {} summary\n{}_sum {}\n{}_count {}",
+                    name, name, sum, name, count
+                );
+            }
+        }
+    }
+    let _ = writeln!(out, "# TYPE sam_channel_queued gauge");
+    for c in channels {
+        let _ = writeln!(out, "sam_channel_queued{{channel=\"{}\"}} {}", c.name, c.queued());
+    }
+    let _ = writeln!(out, "# TYPE sam_channel_dropped_total counter");
+    for c in channels {
+        let _ = writeln!(out, "sam_channel_dropped_total{{channel=\"{}\"}} {}", c.name, c.drops());
+    }
+    out
+}
+
+// Minimal HTTP/1.0-style responder: reads the request line, answers, closes
+fn serve_connection(mut stream: TcpStream, channels: &[ChannelStats]) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_millis(500)))?;
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf)?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+    let (status, content_type, body) = match path {
+        "/metrics" => ("200 OK", "text/plain; version=0.0.4", prometheus_text(channels)),
+        "/metrics.json" => (
+            "200 OK",
+            "application/json",
+            serde_json::to_string_pretty(&json_report(channels))?,
+        ),
+        _ => ("404 Not Found", "text/plain", "not found\n".to_string()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    Ok(())
+}
+
+fn write_json_dump(path: &Path, channels: &[ChannelStats]) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &json_report(channels))?;
+    Ok(())
+}
+
+// --- Exporter thread: optional HTTP endpoint plus periodic JSON dump ---
+pub fn start_metrics_thread(
+    config: MetricsConfig,
+    channels: Vec<ChannelStats>,
+    stop_signal: StopSignal,
+) -> Result<JoinHandle<()>> {
+    let listener = match &config.http_addr {
+        Some(addr) => {
+            let listener = TcpListener::bind(addr)
+                .with_context(|| format!("Failed to bind metrics endpoint {}", addr))?;
+            info!("Metrics endpoint on http://{}/metrics", addr);
+            Some(supervisor::async_listener(listener)?)
+        }
+        None => None,
+    };
+    let dump_interval = Duration::from_secs(config.json_interval_secs.max(1));
+    Ok(thread::spawn(move || {
+        supervisor::runtime().block_on(async {
+            let first_dump = tokio::time::Instant::now() + dump_interval;
+            let mut dump = tokio::time::interval_at(first_dump, dump_interval);
+            // Without an endpoint this never resolves and only the dump timer runs
+            let accept = || async {
+                match &listener {
+                    Some(listener) => supervisor::accept(listener).await,
+                    None => std::future::pending().await,
+                }
+            };
+            loop {
+                tokio::select! {
+                    accepted = accept() => {
+                        match accepted {
+                            Ok((stream, _)) => {
+                                if let Err(e) = serve_connection(stream, &channels) {
+                                    warn!("Metrics request failed: {:#}", e);
+                                }
+                            }
+                            Err(e) => warn!("Metrics accept failed: {}", e),
+                        }
+                    }
+                    _ = dump.tick(), if config.json_path.is_some() => {
+                        if let Some(path) = &config.json_path {
+                            if let Err(e) = write_json_dump(path, &channels) {
+                                warn!("Metrics dump failed: {:#}", e);
+                            }
+                        }
+                    }
+                    _ = stop_signal.stopped() => break,
+                }
+            }
+        });
+        info!("Metrics thread finished.");
+    }))
+}
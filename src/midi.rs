@@ -0,0 +1,98 @@
+// src/midi.rs
+use anyhow::{anyhow, Result};
+use crossbeam_channel::Sender;
+use log::{info, warn};
+use midir::{Ignore, MidiInput, MidiInputConnection};
+
+const CLIENT_NAME: &str = "SAM_CAM_BAM";
+
+// --- The subset of MIDI the app reacts to ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiMessage {
+    ProgramChange { channel: u8, program: u8 },
+    ControlChange { channel: u8, controller: u8, value: u8 },
+    // MIDI timecode: one quarter-frame piece, or a full-frame locate (hh carries the rate bits)
+    TimecodeQuarterFrame(u8),
+    TimecodeFullFrame { hours: u8, minutes: u8, seconds: u8, frames: u8 },
+}
+
+impl MidiMessage {
+    fn parse(bytes: &[u8]) -> Option<Self> {
+        let status = *bytes.first()?;
+        match bytes {
+            [0xF1, data, ..] => return Some(MidiMessage::TimecodeQuarterFrame(*data)),
+            [0xF0, 0x7F, _, 0x01, 0x01, hours, minutes, seconds, frames, 0xF7] => {
+                return Some(MidiMessage::TimecodeFullFrame {
+                    hours: *hours,
+                    minutes: *minutes,
+                    seconds: *seconds,
+                    frames: *frames,
+                });
+            }
+            _ => {}
+        }
+        let channel = status & 0x0F;
+        match status & 0xF0 {
+            0xC0 => Some(MidiMessage::ProgramChange {
+                channel,
+                program: *bytes.get(1)?,
+            }),
+            0xB0 => Some(MidiMessage::ControlChange {
+                channel,
+                controller: *bytes.get(1)?,
+                value: *bytes.get(2)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+// Keeps the port connections open; dropping it closes them
+pub struct MidiInputs {
+    _connections: Vec<MidiInputConnection<()>>,
+    pub port_names: Vec<String>,
+}
+
+// Listens on every available input port; messages arrive on `sender` from midir's thread
+pub fn start_midi_input(sender: Sender<MidiMessage>) -> Result<MidiInputs> {
+    let probe = MidiInput::new(CLIENT_NAME).map_err(|e| anyhow!("MIDI init failed: {}", e))?;
+    let port_count = probe.ports().len();
+    let mut connections = Vec::new();
+    let mut port_names = Vec::new();
+    for index in 0..port_count {
+        // midir consumes the MidiInput on connect, so each port gets its own client
+        let mut input =
+            MidiInput::new(CLIENT_NAME).map_err(|e| anyhow!("MIDI init failed: {}", e))?;
+        // SysEx and timecode stay on for MTC chase
+        input.ignore(Ignore::ActiveSense);
+        let Some(port) = input.ports().get(index).cloned() else {
+            continue;
+        };
+        let name = input.port_name(&port).unwrap_or_else(|_| format!("Port {}", index));
+        let tx = sender.clone();
+        match input.connect(
+            &port,
+            "sam-cam-bam-in",
+            move |_stamp, bytes, _| {
+                if let Some(msg) = MidiMessage::parse(bytes) {
+                    let _ = tx.try_send(msg);
+                }
+            },
+            (),
+        ) {
+            Ok(conn) => {
+                info!("MIDI input connected: {}", name);
+                connections.push(conn);
+                port_names.push(name);
+            }
+            Err(e) => warn!("Failed to connect MIDI input {}: {}", name, e),
+        }
+    }
+    if connections.is_empty() {
+        info!("No MIDI inputs connected.");
+    }
+    Ok(MidiInputs {
+        _connections: connections,
+        port_names,
+    })
+}
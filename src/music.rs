@@ -1,126 +1,1295 @@
 // src/music.rs
-use crossbeam_channel::{Receiver, Sender, TryRecvError};
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use rubato::{FftFixedIn, Resampler};
 use rustfft::{num_complex::Complex, FftPlanner};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
-};
-use std::thread;
+use std::sync::{Arc, Mutex};
 use std::time::Duration; // For moving average normalization
 
-const FFT_SIZE: usize = 512;
-const HOP_SIZE: usize = FFT_SIZE / 4;
-const SMOOTHING_FACTOR: f32 = 0.15;
+use crate::latency::OnsetProbe;
+use crate::metrics;
+use crate::pipeline::{PolicySender, Receiver, TrySendError};
+use crate::stems::StemTap;
+use crate::streaming::StreamAudioTap;
+use crate::supervisor::{self, Heartbeat, StopSignal};
+
+const DEFAULT_FFT_SIZE: usize = 512;
+const DEFAULT_HOP_SIZE: usize = DEFAULT_FFT_SIZE / 4; // Hop counts below are at this hop
+const MIN_HOP_SIZE: usize = 32;
+const SMOOTHING_FACTOR: f32 = 0.15; // Per default hop
 const NORM_WINDOW_SIZE: usize = 50; // Number of frames for moving max window
+const AGC_HEADROOM: f32 = 4.0; // Power over the AGC's tracked level that reads as full scale
+// Starting AGC level: a moderate program level, so neither a silent nor a loud first hop
+// skews the gain until the average has caught up
+const AGC_SEED_DB: f32 = -30.0;
+// --- Metering ---
+const PEAK_HOLD_HOPS: usize = 150; // ~0.4 s at 48 kHz before a held peak starts falling
+const PEAK_FALL: f32 = 0.01; // Per default hop once the hold has run out
+const CLIP_LEVEL: f32 = 0.98; // Normalized level at which the moving max is being pushed
+const CLIP_HOLD_HOPS: usize = 375; // ~1 s; the flag outlives a single saturated hop
+
+// No samples for this long = underrun; silence is sent so visuals decay instead of freezing
+const UNDERRUN_TIMEOUT: Duration = Duration::from_millis(100);
+
+// Canonical analysis rate; devices at other rates are resampled to this when enabled
+pub const INTERNAL_SAMPLE_RATE: u32 = 48_000;
+const RESAMPLER_CHUNK_SIZE: usize = 1024;
+
+// --- Beat detection (onset on bass energy) ---
+const BEAT_HISTORY_SIZE: usize = 43; // ~0.1s of default hops at 48 kHz
+const BEAT_THRESHOLD_RATIO: f32 = 1.5; // Energy must exceed recent mean by this factor
+const BEAT_REFRACTORY_FRAMES: usize = 20; // Minimum hops between beats
+const BEAT_DECAY: f32 = 0.85; // Per-hop decay of the beat envelope
+
+// --- Pitch tracking (YIN) ---
+const YIN_WINDOW: usize = 2048; // Samples kept for pitch analysis
+const YIN_INTEGRATION: usize = 1024; // Difference-function integration length
+const YIN_THRESHOLD: f32 = 0.15; // CMND dip below this counts as periodic
+const YIN_HOP_INTERVAL: usize = 8; // Run YIN every N analysis hops
+const PITCH_MIN_HZ: f32 = 80.0;
+const PITCH_MAX_HZ: f32 = 1000.0;
+
+// --- Transient/sustain split (per-hop envelope coefficients on normalized RMS) ---
+const TRANSIENT_FAST: f32 = 0.6; // ~2 hops to follow an attack
+const TRANSIENT_SLOW: f32 = 0.03; // ~0.1 s at 48 kHz
+const TRANSIENT_GAIN: f32 = 2.5; // Fast - slow rarely exceeds 0.4; scale hits toward 1
+
+// Band edges; high runs from the mid end to 90% of Nyquist
+const LOW_BAND_HZ: (f32, f32) = (50.0, 180.0); // Start a bit higher to avoid DC/rumble
+const MID_BAND_END_HZ: f32 = 3000.0;
+
+// --- Drum-hit classification (on Transient onsets) ---
+const DRUM_ONSET_LEVEL: f32 = 0.5; // Transient at or above this is a hit
+const DRUM_REFRACTORY_HOPS: usize = 15; // ~40 ms between hits at the default hop
+const DRUM_AVERAGE: f32 = 0.02; // Per-hop smoothing of the band means hits are compared against
+const KICK_CENTROID_HZ: f32 = 600.0; // Below this, a bass-led hit is a kick
+const HAT_CENTROID_HZ: f32 = 4000.0; // Above this, a treble-led hit is a hi-hat
+
+// --- ⅓-octave filter bank ---
+const THIRD_OCTAVE_Q: f32 = 4.318; // Bandwidth of one third of an octave
+const THIRD_OCTAVE_RANGE: (i32, i32) = (-16, 13); // Centers 1 kHz * 2^(n/3): 25 Hz to 20 kHz
+const OCTAVE_FULL_SCALE: f32 = 0.5; // Band power of a full-scale sine at its center
+
+// User-definable band, exposed as the "Custom" analysis channel
+const CUSTOM_BAND_HZ: (f32, f32) = (400.0, 1200.0);
+
+// --- Analysis channels: layout of the intensities vector sent to segmentation ---
+// Index = declaration order (see `index`); all values are 0-1. New channels are only ever
+// appended, so recorded sessions with a shorter vector still line up:
+//   0 Low  1 Mid  2 High  3 RMS  4 Beat  5 Custom  6-8 Stems (bass, drums, vocals)
+//   9 Pitch  10 Pitch confidence  11-13 Pan (low, mid, high)  14-16 Width (low, mid, high)
+//   17 Transient  18 Sustain  19-21 Drum hits (kick, snare, hi-hat)
+// Audio processor messages end with a meter tail (see METER_MARKER) after the channels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnalysisChannel {
+    Low,
+    Mid,
+    High,
+    Rms,
+    Beat,
+    Custom,
+    // Source-separated stems (zero unless a stem model is loaded)
+    StemBass,
+    StemDrums,
+    StemVocals,
+    // Fundamental frequency on a log scale over PITCH_MIN_HZ..PITCH_MAX_HZ, held when unvoiced
+    Pitch,
+    PitchConfidence,
+    // Stereo image per band (0.5 = centered/mono unless stereo analysis is enabled)
+    // Pan: 0 = hard left, 1 = hard right. Width: 0 = mono, 0.5 = uncorrelated, 1 = out of phase
+    PanLow,
+    PanMid,
+    PanHigh,
+    WidthLow,
+    WidthMid,
+    WidthHigh,
+    // Broadband envelope split: fast minus slow envelope (hits), and the slow envelope (pads)
+    Transient,
+    Sustain,
+    // Decaying envelopes set to 1 by classified drum hits (see DrumHit)
+    Kick,
+    Snare,
+    HiHat,
+}
+
+pub const NUM_ANALYSIS_CHANNELS: usize = 22;
+
+impl AnalysisChannel {
+    pub const ALL: [AnalysisChannel; NUM_ANALYSIS_CHANNELS] = [
+        AnalysisChannel::Low,
+        AnalysisChannel::Mid,
+        AnalysisChannel::High,
+        AnalysisChannel::Rms,
+        AnalysisChannel::Beat,
+        AnalysisChannel::Custom,
+        AnalysisChannel::StemBass,
+        AnalysisChannel::StemDrums,
+        AnalysisChannel::StemVocals,
+        AnalysisChannel::Pitch,
+        AnalysisChannel::PitchConfidence,
+        AnalysisChannel::PanLow,
+        AnalysisChannel::PanMid,
+        AnalysisChannel::PanHigh,
+        AnalysisChannel::WidthLow,
+        AnalysisChannel::WidthMid,
+        AnalysisChannel::WidthHigh,
+        AnalysisChannel::Transient,
+        AnalysisChannel::Sustain,
+        AnalysisChannel::Kick,
+        AnalysisChannel::Snare,
+        AnalysisChannel::HiHat,
+    ];
+
+    // Index of this channel in the intensities vector
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    // Whether the processor's exponential smoothing applies; the rest smooth themselves
+    // (or, like Beat, must stay sharp)
+    fn smoothed(self) -> bool {
+        match self {
+            AnalysisChannel::Low
+            | AnalysisChannel::Mid
+            | AnalysisChannel::High
+            | AnalysisChannel::Rms
+            | AnalysisChannel::Custom => true,
+            AnalysisChannel::Beat
+            | AnalysisChannel::StemBass
+            | AnalysisChannel::StemDrums
+            | AnalysisChannel::StemVocals
+            | AnalysisChannel::Pitch
+            | AnalysisChannel::PitchConfidence
+            | AnalysisChannel::PanLow
+            | AnalysisChannel::PanMid
+            | AnalysisChannel::PanHigh
+            | AnalysisChannel::WidthLow
+            | AnalysisChannel::WidthMid
+            | AnalysisChannel::WidthHigh
+            | AnalysisChannel::Transient
+            | AnalysisChannel::Sustain
+            | AnalysisChannel::Kick
+            | AnalysisChannel::Snare
+            | AnalysisChannel::HiHat => false,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AnalysisChannel::Low => "Low",
+            AnalysisChannel::Mid => "Mid",
+            AnalysisChannel::High => "High",
+            AnalysisChannel::Rms => "RMS",
+            AnalysisChannel::Beat => "Beat",
+            AnalysisChannel::Custom => "Custom",
+            AnalysisChannel::StemBass => "Bass",
+            AnalysisChannel::StemDrums => "Drums",
+            AnalysisChannel::StemVocals => "Vocals",
+            AnalysisChannel::Pitch => "Pitch",
+            AnalysisChannel::PitchConfidence => "Pitch Conf.",
+            AnalysisChannel::PanLow => "Pan Low",
+            AnalysisChannel::PanMid => "Pan Mid",
+            AnalysisChannel::PanHigh => "Pan High",
+            AnalysisChannel::WidthLow => "Width Low",
+            AnalysisChannel::WidthMid => "Width Mid",
+            AnalysisChannel::WidthHigh => "Width High",
+            AnalysisChannel::Transient => "Transient",
+            AnalysisChannel::Sustain => "Sustain",
+            AnalysisChannel::Kick => "Kick",
+            AnalysisChannel::Snare => "Snare",
+            AnalysisChannel::HiHat => "Hi-Hat",
+        }
+    }
+
+    // Levels and envelopes, which look-ahead may extrapolate; pitch and stereo position are
+    // states of the sound rather than amounts of it, so they are held instead
+    pub fn is_level(self) -> bool {
+        match self {
+            AnalysisChannel::Low
+            | AnalysisChannel::Mid
+            | AnalysisChannel::High
+            | AnalysisChannel::Rms
+            | AnalysisChannel::Beat
+            | AnalysisChannel::Custom
+            | AnalysisChannel::StemBass
+            | AnalysisChannel::StemDrums
+            | AnalysisChannel::StemVocals
+            | AnalysisChannel::Transient
+            | AnalysisChannel::Sustain
+            | AnalysisChannel::Kick
+            | AnalysisChannel::Snare
+            | AnalysisChannel::HiHat => true,
+            AnalysisChannel::Pitch
+            | AnalysisChannel::PitchConfidence
+            | AnalysisChannel::PanLow
+            | AnalysisChannel::PanMid
+            | AnalysisChannel::PanHigh
+            | AnalysisChannel::WidthLow
+            | AnalysisChannel::WidthMid
+            | AnalysisChannel::WidthHigh => false,
+        }
+    }
+
+    // Pan channel of the band this channel mostly follows (used to place effects in the mask)
+    pub fn pan_channel(self) -> Self {
+        match self {
+            AnalysisChannel::Low
+            | AnalysisChannel::Beat
+            | AnalysisChannel::StemBass
+            | AnalysisChannel::PanLow
+            | AnalysisChannel::WidthLow
+            | AnalysisChannel::Kick => AnalysisChannel::PanLow,
+            AnalysisChannel::High
+            | AnalysisChannel::PanHigh
+            | AnalysisChannel::WidthHigh
+            | AnalysisChannel::HiHat => AnalysisChannel::PanHigh,
+            _ => AnalysisChannel::PanMid,
+        }
+    }
+
+    // Default routing keeps the original slot == band coupling
+    pub fn default_for_slot(slot: usize) -> Self {
+        match slot {
+            0 => AnalysisChannel::Low,
+            1 => AnalysisChannel::Mid,
+            _ => AnalysisChannel::High,
+        }
+    }
+}
+
+// Bands shown on the level meters. The intensities message carries, after the analysis
+// channels, METER_MARKER, a held peak per metered band and then a clip flag (0/1) per band.
+// The marker (never a valid intensity) finds the tail whatever the channel count
+pub(crate) const METER_MARKER: f32 = -1.0;
+const METER_TAIL_LEN: usize = 1 + 2 * METERED_CHANNELS.len();
+pub const METERED_CHANNELS: [AnalysisChannel; 5] = [
+    AnalysisChannel::Low,
+    AnalysisChannel::Mid,
+    AnalysisChannel::High,
+    AnalysisChannel::Rms,
+    AnalysisChannel::Custom,
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BandMeter {
+    pub level: f32,
+    pub peak: f32,
+    pub clip: bool, // Adaptive normalization saturated recently
+}
+
+// Splits an intensities message into its channels and meters; meters are None for messages
+// without the tail (older sessions)
+pub fn split_meters(intensities: &[f32]) -> (&[f32], Option<[BandMeter; METERED_CHANNELS.len()]>) {
+    let n = METERED_CHANNELS.len();
+    let Some(split) = intensities.len().checked_sub(METER_TAIL_LEN) else {
+        return (intensities, None);
+    };
+    let (channels, tail) = intensities.split_at(split);
+    if tail[0] != METER_MARKER || channels.len() <= METERED_CHANNELS[n - 1].index() {
+        return (intensities, None);
+    }
+    let meters = std::array::from_fn(|i| BandMeter {
+        level: channels[METERED_CHANNELS[i].index()],
+        peak: tail[1 + i],
+        clip: tail[1 + n + i] > 0.5,
+    });
+    (channels, Some(meters))
+}
+
+// --- Peak hold and clip latch for one metered band ---
+#[derive(Debug, Clone, Copy, Default)]
+struct PeakMeter {
+    peak: f32,
+    hold: u32,
+    clip: u32,
+}
+
+impl PeakMeter {
+    // `level` is smoothed, `raw` the band's unsmoothed normalized value
+    fn update(&mut self, level: f32, raw: f32, fft: &FftSettings) {
+        if level >= self.peak {
+            self.peak = level;
+            self.hold = fft.hops(PEAK_HOLD_HOPS) as u32;
+        } else if self.hold > 0 {
+            self.hold -= 1;
+        } else {
+            self.peak = (self.peak - PEAK_FALL * fft.hop_scale()).max(level);
+        }
+        if raw >= CLIP_LEVEL {
+            self.clip = fft.hops(CLIP_HOLD_HOPS) as u32;
+        } else {
+            self.clip = self.clip.saturating_sub(1);
+        }
+    }
+}
+
+// Intensities for silence: everything zero, pans centered
+pub fn silent_intensities() -> Vec<f32> {
+    let mut out = vec![0.0; NUM_ANALYSIS_CHANNELS];
+    for pan in [AnalysisChannel::PanLow, AnalysisChannel::PanMid, AnalysisChannel::PanHigh] {
+        out[pan.index()] = 0.5;
+    }
+    out
+}
+
+// --- How band energies are scaled to 0-1 ---
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Normalization {
+    // Each band against its own recent maximum: always lively, but quiet passages read as loud.
+    // The window is counted in default-size hops, so it spans the same time at any hop.
+    Adaptive { window_hops: usize },
+    // dB scale between a floor and a reference level, relative to digital full scale
+    FixedDbfs { reference_db: f32, floor_db: f32 },
+    // Gain follows each band's average level over `time_secs`, so dynamics within that survive
+    SlowAgc { time_secs: f32 },
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Normalization::Adaptive {
+            window_hops: NORM_WINDOW_SIZE,
+        }
+    }
+}
+
+impl Normalization {
+    pub const KINDS: [&'static str; 3] = ["Adaptive window", "Fixed reference", "Slow AGC"];
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Normalization::Adaptive { .. } => Self::KINDS[0],
+            Normalization::FixedDbfs { .. } => Self::KINDS[1],
+            Normalization::SlowAgc { .. } => Self::KINDS[2],
+        }
+    }
+
+    pub fn of_kind(kind: &str) -> Self {
+        match kind {
+            "Fixed reference" => Normalization::FixedDbfs {
+                reference_db: -12.0,
+                floor_db: -60.0,
+            },
+            "Slow AGC" => Normalization::SlowAgc { time_secs: 20.0 },
+            _ => Normalization::default(),
+        }
+    }
+}
+
+// --- Analysis window function ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowFunction {
+    #[default]
+    Hann,
+    BlackmanHarris, // Lower sidelobes: less bleed from loud bass into neighbouring bands
+}
 
-const IDLE_SLEEP_DURATION: Duration = Duration::from_millis(10);
+impl WindowFunction {
+    pub const ALL: [WindowFunction; 2] = [WindowFunction::Hann, WindowFunction::BlackmanHarris];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WindowFunction::Hann => "Hann",
+            WindowFunction::BlackmanHarris => "Blackman-Harris",
+        }
+    }
+
+    fn coefficients(self, len: usize) -> Vec<f32> {
+        let iter: Box<dyn Iterator<Item = f64>> = match self {
+            WindowFunction::Hann => Box::new(apodize::hanning_iter(len)),
+            WindowFunction::BlackmanHarris => Box::new(apodize::blackman_harris_iter(len)),
+        };
+        iter.map(|v| v as f32).collect()
+    }
+}
+
+// --- FFT size, hop and window; longer windows resolve bass better but react later ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FftSettings {
+    pub size: usize, // Power of two, 512-4096
+    pub hop: usize,  // Samples between analysis frames; overlap is size - hop
+    pub window: WindowFunction,
+}
+
+impl Default for FftSettings {
+    fn default() -> Self {
+        Self {
+            size: DEFAULT_FFT_SIZE,
+            hop: DEFAULT_HOP_SIZE,
+            window: WindowFunction::Hann,
+        }
+    }
+}
+
+impl FftSettings {
+    pub const SIZES: [usize; 4] = [512, 1024, 2048, 4096];
+
+    // Nearest supported size, and a hop that fits inside it
+    pub fn sanitized(self) -> Self {
+        let size = Self::SIZES
+            .into_iter()
+            .min_by_key(|s| s.abs_diff(self.size))
+            .unwrap_or(DEFAULT_FFT_SIZE);
+        Self {
+            size,
+            hop: self.hop.clamp(MIN_HOP_SIZE, size),
+            window: self.window,
+        }
+    }
+
+    // Hop relative to the default; per-hop constants are rescaled by this
+    fn hop_scale(&self) -> f32 {
+        self.hop as f32 / DEFAULT_HOP_SIZE as f32
+    }
+
+    // A count of default hops expressed in hops of this size
+    fn hops(&self, default_hops: usize) -> usize {
+        ((default_hops as f32 / self.hop_scale()).round() as usize).max(1)
+    }
+
+    // A per-default-hop smoothing factor, for this hop
+    fn smoothing(&self, factor: f32) -> f32 {
+        1.0 - (1.0 - factor).powf(self.hop_scale())
+    }
+}
+
+// --- Where the band energies come from ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisBackend {
+    #[default]
+    Fft, // Linear bins averaged per band
+    ThirdOctave, // Constant-Q filter bank; every octave weighs the same, like hearing does
+}
+
+impl AnalysisBackend {
+    pub const ALL: [AnalysisBackend; 2] = [AnalysisBackend::Fft, AnalysisBackend::ThirdOctave];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AnalysisBackend::Fft => "Linear FFT",
+            AnalysisBackend::ThirdOctave => "⅓-octave bank",
+        }
+    }
+}
+
+// --- Analysis settings the UI can change while the AudioProcessor runs ---
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AnalysisSettings {
+    pub normalization: Normalization,
+    pub fft: FftSettings,
+    pub backend: AnalysisBackend,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisControl(Arc<Mutex<AnalysisSettings>>);
+
+impl AnalysisControl {
+    pub fn new(settings: AnalysisSettings) -> Self {
+        Self(Arc::new(Mutex::new(settings)))
+    }
+
+    pub fn get(&self) -> AnalysisSettings {
+        *self.0.lock().unwrap()
+    }
+
+    pub fn set(&self, settings: AnalysisSettings) {
+        *self.0.lock().unwrap() = settings;
+    }
+}
+
+// --- Normalization state of one band ---
+#[derive(Debug, Clone)]
+struct BandNormalizer {
+    recent_max: VecDeque<f32>,
+    agc_level: f32, // Tracked average, as a fraction of full-scale power
+}
+
+impl Default for BandNormalizer {
+    fn default() -> Self {
+        Self {
+            // Initialize normalization windows with small non-zero value
+            recent_max: VecDeque::from(vec![1e-3; NORM_WINDOW_SIZE]),
+            agc_level: 10f32.powf(AGC_SEED_DB / 10.0),
+        }
+    }
+}
+
+impl BandNormalizer {
+    // `energy` is a mean squared magnitude, `full_scale` the same for a full-scale signal.
+    // Takes the sqrt AFTER normalization for better perceptual scaling
+    fn normalize(
+        &mut self,
+        energy: f32,
+        full_scale: f32,
+        mode: Normalization,
+        hop_secs: f32,
+    ) -> f32 {
+        let value = match mode {
+            Normalization::Adaptive { window_hops } => {
+                self.recent_max.push_back(energy.max(1e-6)); // Ensure non-zero
+                while self.recent_max.len() > window_hops.max(1) {
+                    self.recent_max.pop_front();
+                }
+                let max = self.recent_max.iter().fold(0.0f32, |max, &val| max.max(val));
+                (energy / max).sqrt()
+            }
+            Normalization::FixedDbfs {
+                reference_db,
+                floor_db,
+            } => {
+                let db = 10.0 * (energy / full_scale).max(1e-12).log10();
+                (db - floor_db) / (reference_db - floor_db).max(1.0)
+            }
+            Normalization::SlowAgc { time_secs } => {
+                let relative = energy / full_scale;
+                let rate = hop_secs / time_secs.max(hop_secs);
+                self.agc_level += (relative - self.agc_level) * rate;
+                (relative / (self.agc_level * AGC_HEADROOM).max(1e-12)).sqrt()
+            }
+        };
+        value.clamp(0.0, 1.0)
+    }
+}
+
+// --- Analysis options from the config file ---
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub stereo_analysis: bool, // Per-channel FFT for the Pan/Width channels (costs a second FFT)
+    pub offset_ms: i32,        // Intensity stream shift: positive = delay, negative = look-ahead
+    pub normalization: Normalization, // Starting mode; adjustable in the settings panel
+    pub fft: FftSettings,             // Starting FFT; adjustable in the settings panel
+    pub backend: AnalysisBackend,
+    // Loopback input name substrings, most preferred first (e.g. "BlackHole", "Monitor of");
+    // empty = the platform defaults
+    pub loopback_names: Vec<String>,
+}
+
+// --- FFT bins of each fixed band, for one analysis rate and FFT size ---
+#[derive(Debug, Clone, Copy, Default)]
+struct BandBins {
+    low: (usize, usize),
+    mid: (usize, usize),
+    high: (usize, usize),
+    custom: (usize, usize),
+}
+
+impl BandBins {
+    fn new(sample_rate: u32, fft_size: usize) -> Self {
+        let bin_width = sample_rate as f32 / fft_size as f32;
+        let max_bin = (fft_size / 2) as f32; // Don't exceed max bin
+        let bin = |hz: f32| (hz / bin_width).round().min(max_bin) as usize;
+        let (low_start_hz, low_end_hz) = LOW_BAND_HZ;
+        let high_end_hz = (sample_rate as f32 / 2.0) * 0.9; // Go up to 90% of Nyquist
+        let bins = Self {
+            low: (bin(low_start_hz).max(1), bin(low_end_hz)), // Ensure start >= 1
+            mid: (bin(low_end_hz), bin(MID_BAND_END_HZ)),
+            high: (bin(MID_BAND_END_HZ), bin(high_end_hz)),
+            custom: (bin(CUSTOM_BAND_HZ.0).max(1), bin(CUSTOM_BAND_HZ.1)),
+        };
+        info!(
+            "FFT Bin Ranges (Approx Hz): Bass [{:.1}-{:.1}], Mid [{:.1}-{:.1}], High [{:.1}-{:.1}]",
+            bins.low.0 as f32 * bin_width,
+            bins.low.1 as f32 * bin_width,
+            bins.mid.0 as f32 * bin_width,
+            bins.mid.1 as f32 * bin_width,
+            bins.high.0 as f32 * bin_width,
+            bins.high.1 as f32 * bin_width
+        );
+        bins
+    }
+}
+
+// --- Drum hits the classifier tells apart; sent as events and mirrored on their own channels ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrumHit {
+    Kick,
+    Snare,
+    HiHat,
+}
+
+impl DrumHit {
+    pub fn channel(self) -> AnalysisChannel {
+        match self {
+            DrumHit::Kick => AnalysisChannel::Kick,
+            DrumHit::Snare => AnalysisChannel::Snare,
+            DrumHit::HiHat => AnalysisChannel::HiHat,
+        }
+    }
+}
+
+// --- Labels Transient onsets by spectral centroid and which band jumped most ---
+#[derive(Debug, Default)]
+struct DrumClassifier {
+    band_means: [f32; 3], // Low, mid, high energy, slowly averaged
+    envelopes: [f32; 3],  // Kick, snare, hi-hat
+    hops_since_hit: usize,
+}
+
+impl DrumClassifier {
+    // One hop: `energies` are low/mid/high band energies. Returns the hit labelled this hop, if
+    // any; `envelopes` then holds the kick/snare/hat channels
+    fn update(
+        &mut self,
+        energies: [f32; 3],
+        centroid_hz: f32,
+        transient: f32,
+        fft: &FftSettings,
+    ) -> Option<DrumHit> {
+        // How far each band rose above its own recent mean
+        let rises: [f32; 3] = std::array::from_fn(|i| energies[i] / self.band_means[i].max(1e-9));
+        let average = fft.smoothing(DRUM_AVERAGE);
+        for (mean, energy) in self.band_means.iter_mut().zip(energies) {
+            *mean += (energy - *mean) * average;
+        }
+        let decay = BEAT_DECAY.powf(fft.hop_scale());
+        for envelope in &mut self.envelopes {
+            *envelope *= decay;
+        }
+        self.hops_since_hit = self.hops_since_hit.saturating_add(1);
+        if transient < DRUM_ONSET_LEVEL || self.hops_since_hit < fft.hops(DRUM_REFRACTORY_HOPS) {
+            return None;
+        }
+        let hit = if centroid_hz < KICK_CENTROID_HZ && rises[0] >= rises[1] {
+            DrumHit::Kick
+        } else if centroid_hz > HAT_CENTROID_HZ && rises[2] > rises[0] {
+            DrumHit::HiHat
+        } else {
+            DrumHit::Snare
+        };
+        debug!("Drum hit: {:?} (centroid {:.0} Hz)", hit, centroid_hz);
+        self.envelopes[hit.channel().index() - AnalysisChannel::Kick.index()] = 1.0;
+        self.hops_since_hit = 0;
+        Some(hit)
+    }
+}
+
+// --- Band-pass biquad (RBJ, 0 dB peak gain), transposed direct form II ---
+#[derive(Debug, Clone, Copy)]
+struct BandPass {
+    b0: f32, // b1 is zero and b2 = -b0 for this design
+    a1: f32,
+    a2: f32,
+    z1: f32,
+    z2: f32,
+}
+
+impl BandPass {
+    fn new(center_hz: f32, q: f32, sample_rate: u32) -> Self {
+        let w0 = 2.0 * std::f32::consts::PI * center_hz / sample_rate as f32;
+        let alpha = w0.sin() / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: alpha / a0,
+            a1: -2.0 * w0.cos() / a0,
+            a2: (1.0 - alpha) / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = -self.a1 * y + self.z2;
+        self.z2 = -self.b0 * x - self.a2 * y;
+        y
+    }
+}
+
+// --- ⅓-octave bank: per-band power, smoothed over a couple of periods of each band ---
+struct OctaveBank {
+    bands: Vec<(f32, BandPass, f32)>, // (center Hz, filter, smoothed power)
+    sample_rate: u32,
+}
+
+impl OctaveBank {
+    fn new(sample_rate: u32) -> Self {
+        let bands: Vec<_> = (THIRD_OCTAVE_RANGE.0..=THIRD_OCTAVE_RANGE.1)
+            .map(|n| 1000.0 * 2f32.powf(n as f32 / 3.0))
+            .filter(|hz| *hz < sample_rate as f32 * 0.45)
+            .map(|hz| (hz, BandPass::new(hz, THIRD_OCTAVE_Q, sample_rate), 0.0))
+            .collect();
+        info!(
+            "⅓-octave bank: {} bands, {:.0}-{:.0} Hz",
+            bands.len(),
+            bands.first().map_or(0.0, |b| b.0),
+            bands.last().map_or(0.0, |b| b.0)
+        );
+        Self { bands, sample_rate }
+    }
+
+    // Filters one hop of new samples
+    fn process(&mut self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        let hop_secs = samples.len() as f32 / self.sample_rate as f32;
+        for (hz, filter, power) in &mut self.bands {
+            let sum_sq: f32 = samples.iter().map(|s| filter.process(*s).powi(2)).sum();
+            let mean_sq = sum_sq / samples.len() as f32;
+            // Low bands ripple at twice their frequency; average that out
+            let tau = (2.0 / *hz).max(0.01);
+            *power += (mean_sq - *power) * (1.0 - (-hop_secs / tau).exp());
+        }
+    }
+
+    // Mean power of the bands centered in [lo, hi), so a range spanning more bands doesn't read
+    // louder; the FFT backend averages its bins the same way
+    fn energy(&self, (lo, hi): (f32, f32)) -> f32 {
+        let (sum, count) = self
+            .bands
+            .iter()
+            .filter(|(hz, ..)| *hz >= lo && *hz < hi)
+            .fold((0.0, 0), |(sum, count), (.., power)| (sum + power, count + 1));
+        sum / count.max(1) as f32
+    }
+
+    // Low, mid, high and custom, like the FFT bins, and the power-weighted centroid
+    fn band_energies(&self) -> ([f32; 4], f32) {
+        let nyquist_edge = self.sample_rate as f32 / 2.0 * 0.9;
+        let energies = [
+            self.energy(LOW_BAND_HZ),
+            self.energy((LOW_BAND_HZ.1, MID_BAND_END_HZ)),
+            self.energy((MID_BAND_END_HZ, nyquist_edge)),
+            self.energy(CUSTOM_BAND_HZ),
+        ];
+        let (weighted, total) = self
+            .bands
+            .iter()
+            .fold((0.0, 0.0), |(w, t), (hz, _, power)| (w + hz * power, t + power));
+        (energies, weighted / f32::max(total, 1e-12))
+    }
+}
+
+// --- Left/right analysis at the device rate, for the Pan/Width channels ---
+struct StereoAnalyzer {
+    left: Vec<f32>,
+    right: Vec<f32>,
+    fft_left: Vec<Complex<f32>>,
+    fft_right: Vec<Complex<f32>>,
+    band_ranges: [(usize, usize); 3], // Low, mid, high bins at the device rate
+    pan: [f32; 3],
+    width: [f32; 3],
+    fft: FftSettings,
+}
+
+impl StereoAnalyzer {
+    fn new(device_rate: u32, fft: FftSettings) -> Self {
+        let size = fft.size;
+        let bin_width = device_rate as f32 / size as f32;
+        let bin = |hz: f32| ((hz / bin_width).round() as usize).clamp(1, size / 2);
+        let nyquist_edge = device_rate as f32 / 2.0 * 0.9;
+        Self {
+            left: Vec::with_capacity(size * 2),
+            right: Vec::with_capacity(size * 2),
+            fft_left: vec![Complex::new(0.0, 0.0); size],
+            fft_right: vec![Complex::new(0.0, 0.0); size],
+            band_ranges: [
+                (bin(LOW_BAND_HZ.0), bin(LOW_BAND_HZ.1)),
+                (bin(LOW_BAND_HZ.1), bin(MID_BAND_END_HZ)),
+                (bin(MID_BAND_END_HZ), bin(nyquist_edge)),
+            ],
+            pan: [0.5; 3],
+            width: [0.0; 3],
+            fft,
+        }
+    }
+
+    // First two channels of an interleaved block
+    fn push(&mut self, interleaved: &[f32], channels: usize) {
+        for frame in interleaved.chunks_exact(channels) {
+            self.left.push(frame[0]);
+            self.right.push(frame[1]);
+        }
+    }
+
+    // Runs every full window, hopping like the mono analysis; results are smoothed
+    fn process(
+        &mut self,
+        fft: &dyn rustfft::Fft<f32>,
+        window: &[f32],
+        scratch: &mut [Complex<f32>],
+    ) {
+        let (size, hop) = (self.fft.size, self.fft.hop);
+        let smoothing = self.fft.smoothing(SMOOTHING_FACTOR);
+        while self.left.len() >= size {
+            for i in 0..size {
+                let w = window.get(i).copied().unwrap_or(1.0);
+                self.fft_left[i] = Complex::new(self.left[i] * w, 0.0);
+                self.fft_right[i] = Complex::new(self.right[i] * w, 0.0);
+            }
+            fft.process_with_scratch(&mut self.fft_left, scratch);
+            fft.process_with_scratch(&mut self.fft_right, scratch);
+            for (band, &(start, end)) in self.band_ranges.iter().enumerate() {
+                // Mid/side per bin by linearity: M = (L + R) / 2, S = (L - R) / 2
+                let (mut e_l, mut e_r, mut e_m, mut e_s) = (0.0f32, 0.0f32, 0.0f32, 0.0f32);
+                for (l, r) in self.fft_left[start..end].iter().zip(&self.fft_right[start..end]) {
+                    e_l += l.norm_sqr();
+                    e_r += r.norm_sqr();
+                    e_m += ((l + r) * 0.5).norm_sqr();
+                    e_s += ((l - r) * 0.5).norm_sqr();
+                }
+                let (pan, width) = if e_l + e_r > 1e-9 {
+                    (e_r / (e_l + e_r), e_s / (e_m + e_s).max(1e-9))
+                } else {
+                    (0.5, 0.0) // Silence reads as centered
+                };
+                self.pan[band] += (pan - self.pan[band]) * smoothing;
+                self.width[band] += (width - self.width[band]) * smoothing;
+            }
+            self.left.drain(0..hop);
+            self.right.drain(0..hop);
+        }
+    }
+}
 
 pub struct AudioProcessor {
     raw_sample_receiver: Receiver<Vec<f32>>,
-    intensity_sender: Sender<Vec<f32>>,
+    intensity_sender: PolicySender<Vec<f32>>,
     sample_rate: u32,
+    device_rate: u32, // Stereo analysis runs at this rate, before resampling
     channels: u16,
+    // --- Optional resampling to a canonical analysis rate ---
+    resampler: Option<FftFixedIn<f32>>,
+    resample_input: Vec<f32>,
+    // --- Optional stem separation fed from the analysis stream ---
+    stem_tap: Option<StemTap>,
+    // --- Optional stereo image analysis (needs a 2+ channel device) ---
+    stereo: Option<StereoAnalyzer>,
+    // --- Latency calibration listens for the clicks here ---
+    onset_probe: Option<OnsetProbe>,
+    // --- Live stream output takes the analysis audio as its soundtrack ---
+    stream_tap: Option<StreamAudioTap>,
+    // --- Classified drum hits, for visuals that react to each drum ---
+    drum_hits: Option<PolicySender<DrumHit>>,
+    // --- FFT, replanned when the UI changes its settings ---
+    fft_settings: FftSettings,
+    bins: BandBins,
+    octave_bank: Option<OctaveBank>, // Replaces the FFT bins when the ⅓-octave backend is on
     fft_planner: FftPlanner<f32>,
     fft_buffer: Vec<Complex<f32>>,
     scratch_buffer: Vec<Complex<f32>>,
     window: Vec<f32>,
     sample_buffer: Vec<f32>,
     smoothed_intensities: Vec<f32>,
-    // --- State for adaptive normalization ---
-    recent_max_low: VecDeque<f32>,
-    recent_max_mid: VecDeque<f32>,
-    recent_max_high: VecDeque<f32>,
-    // --- Store bin indices ---
-    low_bin_range: (usize, usize),
-    mid_bin_range: (usize, usize),
-    high_bin_range: (usize, usize),
+    peak_meters: [PeakMeter; METERED_CHANNELS.len()],
+    // --- Normalization, one state per metered band (same order) ---
+    analysis: AnalysisControl,
+    normalizers: [BandNormalizer; METERED_CHANNELS.len()],
+    // --- Beat detection state ---
+    low_energy_history: VecDeque<f32>,
+    beat_envelope: f32,
+    frames_since_beat: usize,
+    // --- Pitch tracking state ---
+    pitch_history: VecDeque<f32>,
+    yin_buffer: Vec<f32>,
+    hops_since_pitch: usize,
+    pitch_hz: f32,
+    pitch_confidence: f32,
+    // --- Transient/sustain envelopes ---
+    envelope_fast: f32,
+    envelope_slow: f32,
+    drums: DrumClassifier,
 }
 
 impl AudioProcessor {
     pub fn new(
         raw_sample_receiver: Receiver<Vec<f32>>,
-        intensity_sender: Sender<Vec<f32>>,
+        intensity_sender: PolicySender<Vec<f32>>,
         sample_rate: u32,
         channels: u16,
         num_bands: usize,
+        target_sample_rate: Option<u32>,
+        stem_tap: Option<StemTap>,
+        config: &AudioConfig,
     ) -> Self {
         info!(
             "Initializing AudioProcessor: SampleRate={}, Channels={}, Bands={}",
             sample_rate, channels, num_bands
         );
-        let window = apodize::hanning_iter(FFT_SIZE).map(|v| v as f32).collect();
-
-        // --- Calculate bin ranges based on sample rate ---
-        let bin_width = sample_rate as f32 / FFT_SIZE as f32;
-        // Adjust Hz ranges as needed
-        let low_start_hz = 50.0; // Start a bit higher to avoid DC/rumble
-        let low_end_hz = 180.0;
-        let mid_start_hz = low_end_hz;
-        let mid_end_hz = 3000.0; // Extend mids slightly?
-        let high_start_hz = mid_end_hz;
-        let high_end_hz = (sample_rate as f32 / 2.0) * 0.9; // Go up to 90% of Nyquist
+        let fft_settings = config.fft.sanitized();
+        let stereo = match (config.stereo_analysis, channels) {
+            (true, c) if c >= 2 => {
+                info!("Stereo analysis enabled (Pan/Width channels)");
+                Some(StereoAnalyzer::new(sample_rate, fft_settings))
+            }
+            (true, _) => {
+                warn!("Stereo analysis needs a stereo input; device has {} channel(s)", channels);
+                None
+            }
+            _ => None,
+        };
 
-        let low_bin_start = (low_start_hz / bin_width).round() as usize;
-        let low_bin_end = (low_end_hz / bin_width).round() as usize;
-        let mid_bin_start = (mid_start_hz / bin_width).round() as usize;
-        let mid_bin_end = (mid_end_hz / bin_width).round() as usize;
-        let high_bin_start = (high_start_hz / bin_width).round() as usize;
-        let high_bin_end = (high_end_hz / bin_width).round().min((FFT_SIZE / 2) as f32) as usize; // Don't exceed max bin
+        // --- Set up resampler if the device rate differs from the requested analysis rate ---
+        let resampler = match target_sample_rate {
+            Some(target) if target != sample_rate => {
+                match FftFixedIn::<f32>::new(
+                    sample_rate as usize,
+                    target as usize,
+                    RESAMPLER_CHUNK_SIZE,
+                    2,
+                    1,
+                ) {
+                    Ok(r) => {
+                        info!("Resampling audio {} Hz -> {} Hz for analysis", sample_rate, target);
+                        Some(r)
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to create resampler ({} -> {} Hz): {}. Analysing at device rate.",
+                            sample_rate, target, e
+                        );
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        let analysis_rate = match (&resampler, target_sample_rate) {
+            (Some(_), Some(target)) => target,
+            _ => sample_rate,
+        };
 
-        info!(
-            "FFT Bin Ranges (Approx Hz): Bass [{:.1}-{:.1}], Mid [{:.1}-{:.1}], High [{:.1}-{:.1}]",
-            low_bin_start as f32 * bin_width,
-            low_bin_end as f32 * bin_width,
-            mid_bin_start as f32 * bin_width,
-            mid_bin_end as f32 * bin_width,
-            high_bin_start as f32 * bin_width,
-            high_bin_end as f32 * bin_width
-        );
+        let device_rate = sample_rate;
+        let sample_rate = analysis_rate; // From here on, the rate the FFT actually sees
 
         Self {
             raw_sample_receiver,
             intensity_sender,
             sample_rate,
+            device_rate,
             channels,
+            resampler,
+            resample_input: Vec::with_capacity(RESAMPLER_CHUNK_SIZE * 2),
+            stem_tap,
+            stereo,
+            onset_probe: None,
+            stream_tap: None,
+            drum_hits: None,
+            fft_settings,
+            bins: BandBins::default(), // Buffers, window and bins are set up by apply_fft
+            octave_bank: (config.backend == AnalysisBackend::ThirdOctave)
+                .then(|| OctaveBank::new(sample_rate)),
             fft_planner: FftPlanner::new(),
-            fft_buffer: vec![Complex::new(0.0, 0.0); FFT_SIZE],
-            scratch_buffer: vec![Complex::new(0.0, 0.0); FFT_SIZE],
-            window,
-            sample_buffer: Vec::with_capacity(FFT_SIZE * 2),
-            smoothed_intensities: vec![0.0; num_bands],
-            // Initialize normalization windows with small non-zero value
-            recent_max_low: VecDeque::from(vec![1e-3; NORM_WINDOW_SIZE]),
-            recent_max_mid: VecDeque::from(vec![1e-3; NORM_WINDOW_SIZE]),
-            recent_max_high: VecDeque::from(vec![1e-3; NORM_WINDOW_SIZE]),
-            // Store bin ranges
-            low_bin_range: (low_bin_start.max(1), low_bin_end), // Ensure start >= 1
-            mid_bin_range: (mid_bin_start, mid_bin_end),
-            high_bin_range: (high_bin_start, high_bin_end),
+            fft_buffer: Vec::new(),
+            scratch_buffer: Vec::new(),
+            window: Vec::new(),
+            sample_buffer: Vec::with_capacity(fft_settings.size * 2),
+            smoothed_intensities: {
+                let mut v = silent_intensities();
+                v.resize(num_bands, 0.0);
+                v
+            },
+            peak_meters: Default::default(),
+            analysis: AnalysisControl::new(AnalysisSettings {
+                normalization: config.normalization,
+                fft: fft_settings,
+                backend: config.backend,
+            }),
+            normalizers: Default::default(),
+            low_energy_history: VecDeque::with_capacity(BEAT_HISTORY_SIZE + 1),
+            beat_envelope: 0.0,
+            frames_since_beat: BEAT_REFRACTORY_FRAMES,
+            pitch_history: VecDeque::with_capacity(YIN_WINDOW + fft_settings.hop),
+            yin_buffer: vec![0.0; YIN_WINDOW / 2],
+            hops_since_pitch: 0,
+            pitch_hz: 0.0,
+            pitch_confidence: 0.0,
+            envelope_fast: 0.0,
+            envelope_slow: 0.0,
+            drums: DrumClassifier::default(),
+        }
+    }
+
+    // Plans the FFT for `settings` and rebuilds everything sized by it
+    fn apply_fft(&mut self, settings: FftSettings) -> Arc<dyn rustfft::Fft<f32>> {
+        let settings = settings.sanitized();
+        info!(
+            "FFT: {} samples, hop {}, {} window",
+            settings.size,
+            settings.hop,
+            settings.window.label()
+        );
+        let fft = self.fft_planner.plan_fft_forward(settings.size);
+        let zero = Complex::new(0.0, 0.0);
+        self.fft_buffer = vec![zero; settings.size];
+        self.scratch_buffer = vec![zero; fft.get_inplace_scratch_len().max(settings.size)];
+        self.window = settings.window.coefficients(settings.size);
+        self.bins = BandBins::new(self.sample_rate, settings.size);
+        if self.stereo.is_some() {
+            self.stereo = Some(StereoAnalyzer::new(self.device_rate, settings));
+        }
+        self.fft_settings = settings;
+        fft
+    }
+
+    // Mean power of the bins in each band (low, mid, high, custom) for the current window,
+    // and the spectral centroid in Hz
+    fn fft_band_energies(&mut self, fft: &dyn rustfft::Fft<f32>, size: usize) -> ([f32; 4], f32) {
+        // Prepare FFT input (unchanged)
+        for (i, s) in self.sample_buffer[0..size].iter().enumerate() {
+            let w = s * self.window.get(i).copied().unwrap_or(1.0);
+            self.fft_buffer[i] = Complex::new(w, 0.0);
+        }
+        fft.process_with_scratch(&mut self.fft_buffer, &mut self.scratch_buffer);
+        let magnitudes: Vec<f32> = self.fft_buffer[0..size / 2]
+            .iter()
+            .map(|c| c.norm_sqr())
+            .collect(); // Use norm_sqr (cheaper)
+
+        // Calculate band averages (using stored ranges)
+        let calc_avg = |(start_bin, end_bin): (usize, usize)| -> f32 {
+            let start_bin = start_bin.min(magnitudes.len());
+            let end_bin = end_bin.min(magnitudes.len());
+            if start_bin >= end_bin {
+                return 0.0;
+            }
+            let slice = &magnitudes[start_bin..end_bin];
+            slice.iter().sum::<f32>() / slice.len() as f32
+        };
+        let energies = [
+            calc_avg(self.bins.low),
+            calc_avg(self.bins.mid),
+            calc_avg(self.bins.high),
+            calc_avg(self.bins.custom),
+        ];
+        let bin_width = self.sample_rate as f32 / size as f32;
+        let (weighted, total) = magnitudes
+            .iter()
+            .enumerate()
+            .skip(1)
+            .fold((0.0, 0.0), |(w, t), (k, m)| (w + k as f32 * bin_width * m, t + m));
+        (energies, weighted / f32::max(total, 1e-12))
+    }
+
+    // --- Simple onset detector on bass energy; returns a decaying beat envelope ---
+    fn update_beat(&mut self, low_energy: f32) -> f32 {
+        let mean = if self.low_energy_history.is_empty() {
+            0.0
+        } else {
+            self.low_energy_history.iter().sum::<f32>() / self.low_energy_history.len() as f32
+        };
+        self.low_energy_history.push_back(low_energy);
+        while self.low_energy_history.len() > self.fft_settings.hops(BEAT_HISTORY_SIZE) {
+            self.low_energy_history.pop_front();
+        }
+
+        self.frames_since_beat = self.frames_since_beat.saturating_add(1);
+        self.beat_envelope *= BEAT_DECAY.powf(self.fft_settings.hop_scale());
+        if low_energy > mean * BEAT_THRESHOLD_RATIO
+            && low_energy > 1e-6
+            && self.frames_since_beat >= self.fft_settings.hops(BEAT_REFRACTORY_FRAMES)
+        {
+            self.beat_envelope = 1.0;
+            self.frames_since_beat = 0;
+        }
+        self.beat_envelope
+    }
+
+    // Replaces the config's starting settings with ones the UI can change
+    pub fn with_analysis_control(mut self, control: AnalysisControl) -> Self {
+        self.analysis = control;
+        self
+    }
+
+    pub fn with_onset_probe(mut self, probe: OnsetProbe) -> Self {
+        self.onset_probe = Some(probe);
+        self
+    }
+
+    pub fn with_stream_tap(mut self, tap: StreamAudioTap) -> Self {
+        self.stream_tap = Some(tap);
+        self
+    }
+
+    pub fn with_drum_hits(mut self, sender: PolicySender<DrumHit>) -> Self {
+        self.drum_hits = Some(sender);
+        self
+    }
+
+    // --- Fast/slow envelope followers; returns (transient, sustain) ---
+    fn update_envelopes(&mut self, level: f32) -> (f32, f32) {
+        let (fast, slow) = (
+            self.fft_settings.smoothing(TRANSIENT_FAST),
+            self.fft_settings.smoothing(TRANSIENT_SLOW),
+        );
+        self.envelope_fast += (level - self.envelope_fast) * fast;
+        self.envelope_slow += (level - self.envelope_slow) * slow;
+        let transient = (self.envelope_fast - self.envelope_slow) * TRANSIENT_GAIN;
+        (transient.clamp(0.0, 1.0), self.envelope_slow.clamp(0.0, 1.0))
+    }
+
+    // --- YIN pitch estimate over the pitch history; returns (f0 Hz, confidence) ---
+    fn detect_pitch(&mut self) -> Option<(f32, f32)> {
+        if self.pitch_history.len() < YIN_WINDOW {
+            return None;
+        }
+        let samples = self.pitch_history.make_contiguous();
+        let tau_min = (self.sample_rate as f32 / PITCH_MAX_HZ).floor().max(2.0) as usize;
+        let tau_max = ((self.sample_rate as f32 / PITCH_MIN_HZ).ceil() as usize)
+            .min(YIN_WINDOW - YIN_INTEGRATION)
+            .min(self.yin_buffer.len() - 1);
+        if tau_min >= tau_max {
+            return None;
+        }
+
+        // Difference function + cumulative mean normalization in one pass
+        let d = &mut self.yin_buffer;
+        d[0] = 1.0;
+        let mut running_sum = 0.0f32;
+        for tau in 1..=tau_max {
+            let diff: f32 = samples[..YIN_INTEGRATION]
+                .iter()
+                .zip(&samples[tau..tau + YIN_INTEGRATION])
+                .map(|(a, b)| (a - b) * (a - b))
+                .sum();
+            running_sum += diff;
+            d[tau] = if running_sum > 0.0 {
+                diff * tau as f32 / running_sum
+            } else {
+                1.0
+            };
+        }
+
+        // First dip under the threshold, then walk down to its local minimum
+        let mut tau = tau_min;
+        while tau < tau_max && d[tau] >= YIN_THRESHOLD {
+            tau += 1;
+        }
+        if tau >= tau_max {
+            return None;
+        }
+        while tau + 1 < tau_max && d[tau + 1] < d[tau] {
+            tau += 1;
+        }
+
+        // Parabolic interpolation around the minimum
+        let (s0, s1, s2) = (d[tau - 1], d[tau], d[tau + 1]);
+        let denom = s0 + s2 - 2.0 * s1;
+        let refined_tau = if denom.abs() > 1e-9 {
+            tau as f32 + (s0 - s2) / (2.0 * denom)
+        } else {
+            tau as f32
+        };
+        let f0 = self.sample_rate as f32 / refined_tau;
+        Some((f0, (1.0 - s1).clamp(0.0, 1.0)))
+    }
+
+    // Log-frequency mapping so octaves are evenly spaced
+    fn normalize_pitch(hz: f32) -> f32 {
+        if hz <= 0.0 {
+            return 0.0;
+        }
+        ((hz / PITCH_MIN_HZ).log2() / (PITCH_MAX_HZ / PITCH_MIN_HZ).log2()).clamp(0.0, 1.0)
+    }
+
+    // The message sent downstream: smoothed intensities plus the meter tail
+    fn intensities_message(&self) -> Vec<f32> {
+        let mut msg = self.smoothed_intensities.clone();
+        msg.resize(NUM_ANALYSIS_CHANNELS, 0.0);
+        msg.push(METER_MARKER);
+        msg.extend(self.peak_meters.iter().map(|m| m.peak));
+        msg.extend(self.peak_meters.iter().map(|m| if m.clip > 0 { 1.0 } else { 0.0 }));
+        msg
+    }
+
+    // Latest-wins hand-off; the stem thread and the stream tolerate gaps
+    fn forward_to_taps(&self, samples: &[f32]) {
+        if let Some(tap) = &self.stem_tap {
+            let _ = tap.sample_sender.try_send((self.sample_rate, samples.to_vec()));
+        }
+        if let Some(tap) = &self.stream_tap {
+            tap.forward(self.sample_rate, samples);
         }
     }
 
-    // --- Helper to update and get moving maximum ---
-    fn update_and_get_moving_max(window: &mut VecDeque<f32>, new_value: f32) -> f32 {
-        window.push_back(new_value.max(1e-6)); // Add new value (ensure non-zero)
-        if window.len() > NORM_WINDOW_SIZE {
-            window.pop_front();
+    // --- Feed mono samples into the analysis buffer, resampling if configured ---
+    fn push_mono_samples(&mut self, samples: Vec<f32>) {
+        let Some(resampler) = self.resampler.as_mut() else {
+            self.forward_to_taps(&samples);
+            self.sample_buffer.extend(samples);
+            return;
+        };
+        self.resample_input.extend(samples);
+        loop {
+            let needed = resampler.input_frames_next();
+            if self.resample_input.len() < needed {
+                break;
+            }
+            match resampler.process(&[&self.resample_input[..needed]], None) {
+                Ok(mut out) => {
+                    if let Some(channel) = out.pop() {
+                        if let Some(tap) = &self.stem_tap {
+                            let _ = tap.sample_sender.try_send((self.sample_rate, channel.clone()));
+                        }
+                        if let Some(tap) = &self.stream_tap {
+                            tap.forward(self.sample_rate, &channel);
+                        }
+                        self.sample_buffer.extend(channel);
+                    }
+                }
+                Err(e) => {
+                    error!("Resampler failed: {}. Passing samples through.", e);
+                    self.sample_buffer
+                        .extend_from_slice(&self.resample_input[..needed]);
+                }
+            }
+            self.resample_input.drain(0..needed);
         }
-        // Find the maximum in the current window
-        window.iter().fold(0.0f32, |max, &val| max.max(val))
     }
 
-    pub fn run(&mut self, stop_signal: Arc<AtomicBool>) {
+    pub fn run(&mut self, stop_signal: StopSignal, heartbeat: Heartbeat) {
         info!("AudioProcessor thread started.");
-        let fft = self.fft_planner.plan_fft_forward(FFT_SIZE);
+        supervisor::runtime().block_on(self.process(stop_signal, heartbeat));
+        info!("AudioProcessor thread finished.");
+    }
+
+    async fn process(&mut self, stop_signal: StopSignal, heartbeat: Heartbeat) {
+        let mut fft = self.apply_fft(self.fft_settings);
         let mut last_send_time = std::time::Instant::now();
         let mut frame_counter = 0; // For debug logging interval
 
-        while !stop_signal.load(Ordering::Relaxed) {
-            // 1. Receive samples (unchanged)
-            match self.raw_sample_receiver.try_recv() {
+        while !stop_signal.is_stopped() {
+            heartbeat.beat();
+            let settings = self.analysis.get();
+            if settings.fft.sanitized() != self.fft_settings {
+                fft = self.apply_fft(settings.fft);
+            }
+            let octave = settings.backend == AnalysisBackend::ThirdOctave;
+            if octave != self.octave_bank.is_some() {
+                info!("Analysis backend: {}", settings.backend.label());
+                self.octave_bank = octave.then(|| OctaveBank::new(self.sample_rate));
+            }
+            let (size, hop) = (self.fft_settings.size, self.fft_settings.hop);
+            // 1. Await samples, a stop request or an underrun
+            let received = tokio::select! {
+                msg = self.raw_sample_receiver.recv() => msg,
+                _ = stop_signal.stopped() => break,
+                _ = tokio::time::sleep(UNDERRUN_TIMEOUT) => {
+                    if self.sample_buffer.is_empty() && last_send_time.elapsed() >= UNDERRUN_TIMEOUT
+                    {
+                        metrics::global().inc(metrics::AUDIO_UNDERRUNS, 1);
+                        let silent = silent_intensities();
+                        for (out, s) in self.smoothed_intensities.iter_mut().zip(silent) {
+                            *out = s;
+                        }
+                        self.peak_meters = Default::default();
+                        let _ = self.intensity_sender.try_send(self.intensities_message());
+                        last_send_time = std::time::Instant::now();
+                    }
+                    continue;
+                }
+            };
+            match received {
                 Ok(new_samples) => {
+                    if let Some(stereo) = self.stereo.as_mut() {
+                        stereo.push(&new_samples, self.channels as usize);
+                        stereo.process(fft.as_ref(), &self.window, &mut self.scratch_buffer);
+                    }
                     /* Downmix */
                     if self.channels > 1 {
                         let n = new_samples.len() / self.channels as usize;
@@ -133,75 +1302,78 @@ impl AudioProcessor {
                                 / self.channels as f32;
                             m.push(o);
                         }
-                        self.sample_buffer.extend(m);
+                        self.push_mono_samples(m);
                     } else {
-                        self.sample_buffer.extend(new_samples);
-                    }
-                }
-                Err(TryRecvError::Empty) => {
-                    /* Handle empty */
-                    thread::sleep(IDLE_SLEEP_DURATION);
-                    if self.sample_buffer.is_empty()
-                        && last_send_time.elapsed() > Duration::from_millis(100)
-                    {
-                        self.smoothed_intensities.fill(0.0);
-                        let _ = self
-                            .intensity_sender
-                            .try_send(self.smoothed_intensities.clone());
-                        last_send_time = std::time::Instant::now();
+                        self.push_mono_samples(new_samples);
                     }
-                    continue;
                 }
-                Err(TryRecvError::Disconnected) => {
+                Err(_) => {
                     error!("Audio capture disconnected.");
                     break;
                 }
             }
 
             // 2. Process buffer
-            while self.sample_buffer.len() >= FFT_SIZE {
+            while self.sample_buffer.len() >= size {
                 frame_counter += 1;
-                // Prepare FFT input (unchanged)
-                for (i, s) in self.sample_buffer[0..FFT_SIZE].iter().enumerate() {
-                    let w = s * self.window.get(i).copied().unwrap_or(1.0);
-                    self.fft_buffer[i] = Complex::new(w, 0.0);
-                }
-                fft.process_with_scratch(&mut self.fft_buffer, &mut self.scratch_buffer);
-                let magnitudes: Vec<f32> = self.fft_buffer[0..FFT_SIZE / 2]
+                // The bank filters each sample once: the newest hop of the window
+                let ([low_avg_sq, mid_avg_sq, high_avg_sq, custom_avg_sq], centroid_hz) =
+                    match self.octave_bank.as_mut() {
+                        Some(bank) => {
+                            bank.process(&self.sample_buffer[size - hop..size]);
+                            bank.band_energies()
+                        }
+                        None => self.fft_band_energies(fft.as_ref(), size),
+                    };
+                let rms_sq = self.sample_buffer[0..size]
                     .iter()
-                    .map(|c| c.norm_sqr())
-                    .collect(); // Use norm_sqr (cheaper)
-
-                // Calculate band averages (using stored ranges)
-                let calc_avg = |start_bin: usize, end_bin: usize| -> f32 {
-                    let start_bin = start_bin.min(magnitudes.len());
-                    let end_bin = end_bin.min(magnitudes.len());
-                    if start_bin >= end_bin {
-                        return 0.0;
-                    }
-                    let slice = &magnitudes[start_bin..end_bin];
-                    if slice.is_empty() {
-                        0.0
-                    } else {
-                        slice.iter().sum::<f32>() / slice.len() as f32
-                    }
-                };
-
-                let low_avg_sq = calc_avg(self.low_bin_range.0, self.low_bin_range.1);
-                let mid_avg_sq = calc_avg(self.mid_bin_range.0, self.mid_bin_range.1);
-                let high_avg_sq = calc_avg(self.high_bin_range.0, self.high_bin_range.1);
+                    .map(|s| s * s)
+                    .sum::<f32>()
+                    / size as f32;
+                let beat = self.update_beat(low_avg_sq);
 
-                // --- Adaptive Normalization ---
-                let max_low = Self::update_and_get_moving_max(&mut self.recent_max_low, low_avg_sq);
-                let max_mid = Self::update_and_get_moving_max(&mut self.recent_max_mid, mid_avg_sq);
-                let max_high =
-                    Self::update_and_get_moving_max(&mut self.recent_max_high, high_avg_sq);
+                // --- Pitch (less often; YIN is the most expensive stage here) ---
+                self.pitch_history
+                    .extend(self.sample_buffer[0..hop].iter().copied());
+                while self.pitch_history.len() > YIN_WINDOW {
+                    self.pitch_history.pop_front();
+                }
+                self.hops_since_pitch += 1;
+                if self.hops_since_pitch >= self.fft_settings.hops(YIN_HOP_INTERVAL) {
+                    self.hops_since_pitch = 0;
+                    match self.detect_pitch() {
+                        Some((hz, confidence)) => {
+                            self.pitch_hz = hz;
+                            self.pitch_confidence = confidence;
+                        }
+                        // Unvoiced: hold the last pitch, let confidence decay
+                        None => self.pitch_confidence *= 0.5,
+                    }
+                }
+                let norm_pitch = Self::normalize_pitch(self.pitch_hz);
 
-                // Normalize each band relative to its own recent maximum
-                // Take sqrt AFTER normalization for better perceptual scaling
-                let norm_low = (low_avg_sq / max_low).sqrt().clamp(0.0, 1.0);
-                let norm_mid = (mid_avg_sq / max_mid).sqrt().clamp(0.0, 1.0);
-                let norm_high = (high_avg_sq / max_high).sqrt().clamp(0.0, 1.0);
+                // --- Normalization (order follows METERED_CHANNELS) ---
+                let mode = match settings.normalization {
+                    Normalization::Adaptive { window_hops } => Normalization::Adaptive {
+                        window_hops: self.fft_settings.hops(window_hops),
+                    },
+                    other => other,
+                };
+                let hop_secs = hop as f32 / self.sample_rate as f32;
+                // A full-scale sine peaks at (window sum / 2)^2 in its bin; full-scale RMS^2 is 1
+                let bin_full_scale = match self.octave_bank {
+                    Some(_) => OCTAVE_FULL_SCALE,
+                    None => (self.window.iter().sum::<f32>() / 2.0).powi(2),
+                };
+                let energies = [low_avg_sq, mid_avg_sq, high_avg_sq, rms_sq, custom_avg_sq];
+                let [norm_low, norm_mid, norm_high, norm_rms, norm_custom]: [f32; 5] =
+                    std::array::from_fn(|i| {
+                        let full_scale = match METERED_CHANNELS[i] {
+                            AnalysisChannel::Rms => 1.0,
+                            _ => bin_full_scale,
+                        };
+                        self.normalizers[i].normalize(energies[i], full_scale, mode, hop_secs)
+                    });
 
                 // Optional: Debug print occasionally
                 if frame_counter % 100 == 0 {
@@ -210,56 +1382,115 @@ impl AudioProcessor {
                         "AvgSq (L,M,H): {:.4}, {:.4}, {:.4}",
                         low_avg_sq, mid_avg_sq, high_avg_sq
                     );
-                    debug!(
-                        "Max (L,M,H):   {:.4}, {:.4}, {:.4}",
-                        max_low, max_mid, max_high
-                    );
                     debug!(
                         "Norm (L,M,H):  {:.2}, {:.2}, {:.2}",
                         norm_low, norm_mid, norm_high
                     );
                 }
 
-                let current_intensities = [norm_low, norm_mid, norm_high];
-
-                // Apply smoothing
-                for i in 0..self
-                    .smoothed_intensities
-                    .len()
-                    .min(current_intensities.len())
-                {
-                    self.smoothed_intensities[i] = self.smoothed_intensities[i]
-                        * (1.0 - SMOOTHING_FACTOR)
-                        + current_intensities[i] * SMOOTHING_FACTOR;
+                let (transient, sustain) = self.update_envelopes(norm_rms);
+                if let Some(probe) = &self.onset_probe {
+                    probe.report(transient);
+                }
+                let hit = self.drums.update(
+                    [low_avg_sq, mid_avg_sq, high_avg_sq],
+                    centroid_hz,
+                    transient,
+                    &self.fft_settings,
+                );
+                if let (Some(hit), Some(sender)) = (hit, &self.drum_hits) {
+                    let _ = sender.try_send(hit);
+                }
+                let [kick, snare, hihat] = self.drums.envelopes;
+
+                let (stereo_pan, stereo_width) =
+                    self.stereo.as_ref().map_or(([0.5; 3], [0.0; 3]), |s| (s.pan, s.width));
+
+                let [stem_bass, stem_drums, stem_vocals] = self
+                    .stem_tap
+                    .as_ref()
+                    .map_or([0.0; 3], |tap| tap.levels.get());
+
+                // Layout follows AnalysisChannel::ALL
+                let current_intensities = [
+                    norm_low,
+                    norm_mid,
+                    norm_high,
+                    norm_rms,
+                    beat,
+                    norm_custom,
+                    stem_bass,
+                    stem_drums,
+                    stem_vocals,
+                    norm_pitch,
+                    self.pitch_confidence,
+                    stereo_pan[0],
+                    stereo_pan[1],
+                    stereo_pan[2],
+                    stereo_width[0],
+                    stereo_width[1],
+                    stereo_width[2],
+                    transient,
+                    sustain,
+                    kick,
+                    snare,
+                    hihat,
+                ];
+
+                // Apply smoothing (beat envelope, stems, pitch and the stereo and envelope
+                // channels, which do their own smoothing, are passed through as-is)
+                let smoothing = self.fft_settings.smoothing(SMOOTHING_FACTOR);
+                for channel in AnalysisChannel::ALL {
+                    let i = channel.index();
+                    if i >= self.smoothed_intensities.len().min(current_intensities.len()) {
+                        continue;
+                    }
+                    if !channel.smoothed() {
+                        self.smoothed_intensities[i] = current_intensities[i];
+                        continue;
+                    }
+                    self.smoothed_intensities[i] = self.smoothed_intensities[i] * (1.0 - smoothing)
+                        + current_intensities[i] * smoothing;
+                }
+                for (meter, channel) in self.peak_meters.iter_mut().zip(METERED_CHANNELS) {
+                    let i = channel.index();
+                    let level = self.smoothed_intensities.get(i).copied().unwrap_or(0.0);
+                    meter.update(level, current_intensities[i], &self.fft_settings);
                 }
 
                 // Send smoothed intensities
-                match self
-                    .intensity_sender
-                    .try_send(self.smoothed_intensities.clone())
-                {
+                match self.intensity_sender.try_send(self.intensities_message()) {
                     Ok(_) => {
                         last_send_time = std::time::Instant::now();
                     }
-                    Err(crossbeam_channel::TrySendError::Full(_)) => {}
-                    Err(crossbeam_channel::TrySendError::Disconnected(_)) => {
+                    Err(TrySendError::Full(_)) => {}
+                    Err(TrySendError::Closed(_)) => {
                         error!("Seg thread disconnected.");
-                        stop_signal.store(true, Ordering::Relaxed);
+                        stop_signal.stop();
                         break;
                     }
                 }
 
-                self.sample_buffer.drain(0..HOP_SIZE);
+                self.sample_buffer.drain(0..hop);
             }
-            if stop_signal.load(Ordering::Relaxed) {
+            if stop_signal.is_stopped() {
                 break;
             }
         }
-        info!("AudioProcessor thread finished.");
     }
 }
 
 mod apodize {
+    // 4-term Blackman-Harris (-92 dB sidelobes)
+    pub fn blackman_harris_iter(len: usize) -> impl Iterator<Item = f64> {
+        const A: [f64; 4] = [0.35875, 0.48829, 0.14128, 0.01168];
+        let denom = len.saturating_sub(1).max(1) as f64;
+        (0..len.max(1)).map(move |x| {
+            let phase = 2.0 * std::f64::consts::PI * x as f64 / denom;
+            A[0] - A[1] * phase.cos() + A[2] * (2.0 * phase).cos() - A[3] * (3.0 * phase).cos()
+        })
+    }
+
     pub fn hanning_iter(len: usize) -> impl Iterator<Item = f64> {
         if len <= 1 {
             vec![1.0].into_iter()
@@ -273,3 +1504,172 @@ mod apodize {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A classifier whose band means have settled on equal energies in every band
+    fn settled_drums(fft: &FftSettings) -> DrumClassifier {
+        let mut drums = DrumClassifier::default();
+        for _ in 0..300 {
+            assert_eq!(drums.update([1.0; 3], 2000.0, 0.0, fft), None);
+        }
+        drums
+    }
+
+    #[test]
+    fn labels_drum_hits() {
+        let fft = FftSettings::default();
+        let cases = [
+            ([8.0, 1.0, 1.0], 150.0, DrumHit::Kick),
+            ([1.0, 4.0, 2.0], 2000.0, DrumHit::Snare),
+            ([1.0, 1.0, 8.0], 8000.0, DrumHit::HiHat),
+        ];
+        for (energies, centroid_hz, expected) in cases {
+            let mut drums = settled_drums(&fft);
+            assert_eq!(drums.update(energies, centroid_hz, 1.0, &fft), Some(expected));
+            let envelope = expected.channel().index() - AnalysisChannel::Kick.index();
+            assert_eq!(drums.envelopes[envelope], 1.0);
+        }
+    }
+
+    #[test]
+    fn ignores_quiet_and_refractory_hops() {
+        let fft = FftSettings::default();
+        let mut drums = settled_drums(&fft);
+        assert_eq!(drums.update([8.0, 1.0, 1.0], 150.0, 0.1, &fft), None);
+        assert!(drums.update([8.0, 1.0, 1.0], 150.0, 1.0, &fft).is_some());
+        // A second onset inside the refractory window is the same hit ringing on
+        assert_eq!(drums.update([1.0, 1.0, 8.0], 8000.0, 1.0, &fft), None);
+    }
+
+    #[test]
+    fn sanitized_picks_the_nearest_size_and_a_hop_inside_it() {
+        let sanitized = |size, hop| {
+            let fft = FftSettings {
+                size,
+                hop,
+                window: WindowFunction::Hann,
+            }
+            .sanitized();
+            (fft.size, fft.hop)
+        };
+        assert_eq!(sanitized(1024, 256), (1024, 256));
+        assert_eq!(sanitized(600, 128), (512, 128));
+        assert_eq!(sanitized(3000, 128), (2048, 128));
+        assert_eq!(sanitized(0, 128), (512, 128));
+        assert_eq!(sanitized(100_000, 128), (4096, 128));
+        assert_eq!(sanitized(1024, 0), (1024, MIN_HOP_SIZE));
+        assert_eq!(sanitized(1024, 5000), (1024, 1024));
+    }
+
+    #[test]
+    fn hops_keep_the_same_duration_at_any_hop() {
+        let at_hop = |hop| FftSettings {
+            hop,
+            ..FftSettings::default()
+        };
+        assert_eq!(at_hop(DEFAULT_HOP_SIZE).hops(50), 50);
+        assert_eq!(at_hop(DEFAULT_HOP_SIZE * 2).hops(50), 25);
+        assert_eq!(at_hop(DEFAULT_HOP_SIZE / 2).hops(50), 100);
+        // Never rounds down to no hops at all
+        assert_eq!(at_hop(DEFAULT_HOP_SIZE * 4).hops(1), 1);
+    }
+
+    #[test]
+    fn band_bins_follow_the_band_edges() {
+        for (rate, size) in [(48_000, 512), (48_000, 2048), (44_100, 1024)] {
+            let bins = BandBins::new(rate, size);
+            let width = rate as f32 / size as f32;
+            let near = |bin: usize, hz: f32| (bin as f32 * width - hz).abs() <= width / 2.0;
+            assert!(bins.low.0 >= 1);
+            assert!(near(bins.low.1, LOW_BAND_HZ.1) && near(bins.mid.1, MID_BAND_END_HZ));
+            assert!(near(bins.custom.0, CUSTOM_BAND_HZ.0) && near(bins.custom.1, CUSTOM_BAND_HZ.1));
+            // The bands tile the spectrum without gaps, and stay below Nyquist
+            assert_eq!(bins.mid.0, bins.low.1);
+            assert_eq!(bins.high.0, bins.mid.1);
+            assert!(bins.high.1 > bins.high.0 && bins.high.1 <= size / 2);
+        }
+    }
+
+    #[test]
+    fn octave_bank_puts_a_sine_in_its_own_band() {
+        let rate = 48_000;
+        let mut bank = OctaveBank::new(rate);
+        let sine: Vec<f32> = (0..rate)
+            .map(|i| (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / rate as f32).sin())
+            .collect();
+        for hop in sine.chunks(512) {
+            bank.process(hop);
+        }
+        let band_power = |center: f32| {
+            let (_, _, power) = bank.bands.iter().find(|(hz, ..)| (hz - center).abs() < 1.0)?;
+            Some(*power)
+        };
+        let at_1k = band_power(1000.0).unwrap();
+        assert!((at_1k / OCTAVE_FULL_SCALE - 1.0).abs() < 0.05, "1 kHz band: {}", at_1k);
+        let ([low, mid, high, _], centroid) = bank.band_energies();
+        assert!(low < OCTAVE_FULL_SCALE * 0.01, "low band: {}", low);
+        assert!(high < OCTAVE_FULL_SCALE * 0.01, "high band: {}", high);
+        assert!(mid > low * 10.0 && mid > high * 10.0);
+        assert!((centroid - 1000.0).abs() < 100.0, "centroid: {}", centroid);
+    }
+
+    // Power `db` below (negative) full scale
+    fn power_at(db: f32, full_scale: f32) -> f32 {
+        full_scale * 10f32.powf(db / 10.0)
+    }
+
+    #[test]
+    fn fixed_dbfs_maps_floor_to_zero_and_reference_to_one() {
+        let mode = Normalization::FixedDbfs {
+            reference_db: -12.0,
+            floor_db: -60.0,
+        };
+        let full_scale = 250.0;
+        let mut band = BandNormalizer::default();
+        let mut at = |db: f32| band.normalize(power_at(db, full_scale), full_scale, mode, 0.01);
+        assert!(at(-60.0).abs() < 1e-4);
+        assert!((at(-36.0) - 0.5).abs() < 1e-4);
+        assert!((at(-12.0) - 1.0).abs() < 1e-4);
+        // Clamped outside the range, including digital silence
+        assert_eq!(at(-90.0), 0.0);
+        assert_eq!(at(0.0), 1.0);
+        assert_eq!(band.normalize(0.0, full_scale, mode, 0.01), 0.0);
+    }
+
+    #[test]
+    fn slow_agc_starts_at_the_seed_and_converges_on_the_average() {
+        let mode = Normalization::SlowAgc { time_secs: 1.0 };
+        let (full_scale, hop_secs) = (250.0, 0.01);
+        // A first hop at the seed level reads as the headroom below full intensity
+        let mut band = BandNormalizer::default();
+        let seeded = band.normalize(power_at(AGC_SEED_DB, full_scale), full_scale, mode, hop_secs);
+        assert!((seeded - AGC_HEADROOM.recip().sqrt()).abs() < 1e-3);
+        // Silence first doesn't leave the gain wide open for the next sound
+        let mut band = BandNormalizer::default();
+        assert_eq!(band.normalize(0.0, full_scale, mode, hop_secs), 0.0);
+        assert!(band.normalize(power_at(-20.0, full_scale), full_scale, mode, hop_secs) >= 1.0);
+        assert!(band.normalize(power_at(-50.0, full_scale), full_scale, mode, hop_secs) < 0.2);
+        // Five time constants of a steady level: the gain has followed it
+        let steady = power_at(-10.0, full_scale);
+        let mut value = 0.0;
+        for _ in 0..500 {
+            value = band.normalize(steady, full_scale, mode, hop_secs);
+        }
+        assert!((value - AGC_HEADROOM.recip().sqrt()).abs() < 0.02);
+    }
+
+    #[test]
+    fn adaptive_forgets_a_peak_after_its_window() {
+        let mode = Normalization::Adaptive { window_hops: 10 };
+        let mut band = BandNormalizer::default();
+        assert_eq!(band.normalize(4.0, 1.0, mode, 0.01), 1.0);
+        // A quarter of the peak's power reads as half while the peak is in the window
+        for _ in 0..9 {
+            assert!((band.normalize(1.0, 1.0, mode, 0.01) - 0.5).abs() < 1e-6);
+        }
+        assert_eq!(band.normalize(1.0, 1.0, mode, 0.01), 1.0);
+    }
+}
@@ -0,0 +1,358 @@
+// src/overlay.rs
+use ab_glyph::{Font, FontArc, Glyph, PxScale, ScaleFont};
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveTime, TimeZone};
+use image::{Rgb, RgbImage};
+use log::warn;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::music::AnalysisChannel;
+
+const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+
+// --- Text / lyrics overlay rendered into the composited frame ---
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextOverlaySettings {
+    pub enabled: bool,
+    pub text: String, // Static text (band name, song title); lyrics take priority when loaded
+    pub size: f32,    // Base glyph height in pixels
+    pub jitter: f32,  // 0-1, per-glyph shake at full intensity
+    pub driver: AnalysisChannel,
+    pub y_position: f32, // Baseline as a fraction of frame height
+    pub color: [u8; 3],
+}
+
+impl Default for TextOverlaySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            text: String::new(),
+            size: 64.0,
+            jitter: 0.3,
+            driver: AnalysisChannel::Low,
+            y_position: 0.85,
+            color: [255, 255, 255],
+        }
+    }
+}
+
+// --- LRC lyrics: "[mm:ss.xx]line", several timestamps per line allowed ---
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct LyricTrack {
+    lines: Vec<(u64, String)>, // (start ms, text), sorted by time
+}
+
+fn parse_lrc_timestamp(tag: &str) -> Option<u64> {
+    let (min, sec) = tag.split_once(':')?;
+    let minutes: u64 = min.trim().parse().ok()?;
+    let seconds: f64 = sec.trim().parse().ok()?;
+    Some(minutes * 60_000 + (seconds * 1000.0).round() as u64)
+}
+
+impl LyricTrack {
+    pub fn parse(source: &str) -> Self {
+        let mut lines = Vec::new();
+        for raw in source.lines() {
+            let mut rest = raw.trim();
+            let mut stamps = Vec::new();
+            while let Some(stripped) = rest.strip_prefix('[') {
+                let Some((tag, after)) = stripped.split_once(']') else {
+                    break;
+                };
+                // Metadata tags like [ar:Artist] simply fail to parse as timestamps
+                if let Some(ms) = parse_lrc_timestamp(tag) {
+                    stamps.push(ms);
+                }
+                rest = after;
+            }
+            for ms in stamps {
+                lines.push((ms, rest.trim().to_string()));
+            }
+        }
+        lines.sort_by_key(|(ms, _)| *ms);
+        Self { lines }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read LRC file {}", path.display()))?;
+        let track = Self::parse(&source);
+        if track.is_empty() {
+            warn!("No timed lines found in {}", path.display());
+        }
+        Ok(track)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    // Latest line whose timestamp has passed
+    pub fn line_at(&self, elapsed: Duration) -> Option<&str> {
+        let ms = elapsed.as_millis() as u64;
+        let idx = self.lines.partition_point(|(start, _)| *start <= ms);
+        idx.checked_sub(1).map(|i| self.lines[i].1.as_str())
+    }
+}
+
+// --- HUD: wall clock, elapsed set time, countdown (e.g. to doors) ---
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HudSettings {
+    pub show_clock: bool,
+    pub show_set_timer: bool,
+    pub show_countdown: bool,
+    pub countdown_label: String,
+    pub set_started_ms: Option<i64>,      // Unix ms, None = set not started
+    pub countdown_target_ms: Option<i64>, // Unix ms
+    pub burn_in: bool,                    // Draw into the output video, not just the operator view
+    pub burn_stats: bool,                 // Performance stats in the output, for diagnostic recordings
+}
+
+fn format_hms(total_secs: i64) -> String {
+    let s = total_secs.max(0);
+    format!("{:02}:{:02}:{:02}", s / 3600, (s / 60) % 60, s % 60)
+}
+
+impl HudSettings {
+    pub fn any_visible(&self) -> bool {
+        self.show_clock || self.show_set_timer || self.show_countdown
+    }
+
+    // Next occurrence of a local "HH:MM" wall-clock time, as Unix ms
+    pub fn parse_countdown_target(hh_mm: &str) -> Option<i64> {
+        let time = NaiveTime::parse_from_str(hh_mm.trim(), "%H:%M").ok()?;
+        let now = Local::now();
+        let mut target = now.date_naive().and_time(time);
+        if target <= now.naive_local() {
+            target += chrono::Duration::days(1);
+        }
+        Local
+            .from_local_datetime(&target)
+            .earliest()
+            .map(|t| t.timestamp_millis())
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let now = Local::now();
+        let now_ms = now.timestamp_millis();
+        let mut lines = Vec::new();
+        if self.show_clock {
+            lines.push(now.format("%H:%M:%S").to_string());
+        }
+        if self.show_set_timer {
+            match self.set_started_ms {
+                Some(start) => lines.push(format!("SET {}", format_hms((now_ms - start) / 1000))),
+                None => lines.push("SET --:--:--".to_string()),
+            }
+        }
+        if self.show_countdown {
+            let label = if self.countdown_label.trim().is_empty() {
+                "DOORS"
+            } else {
+                self.countdown_label.trim()
+            };
+            match self.countdown_target_ms {
+                Some(target) => lines.push(format!(
+                    "{} -{}",
+                    label,
+                    format_hms((target - now_ms + 999) / 1000)
+                )),
+                None => lines.push(format!("{} --:--:--", label)),
+            }
+        }
+        lines
+    }
+}
+
+// Reuses the font egui already embeds, so no asset file is needed
+fn default_font() -> Option<FontArc> {
+    let definitions = egui::FontDefinitions::default();
+    let data = definitions
+        .font_data
+        .get("Ubuntu-Light")
+        .or_else(|| definitions.font_data.values().next())?;
+    FontArc::try_from_vec(data.font.to_vec()).ok()
+}
+
+pub struct TextOverlay {
+    font: Option<FontArc>,
+    lyrics: Option<LyricTrack>,
+    lyrics_started: Instant,
+}
+
+impl Default for TextOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextOverlay {
+    pub fn new() -> Self {
+        let font = default_font();
+        if font.is_none() {
+            warn!("Text overlay: no font available, overlay disabled.");
+        }
+        Self {
+            font,
+            lyrics: None,
+            lyrics_started: Instant::now(),
+        }
+    }
+
+    // Replacing the lyrics restarts their clock
+    pub fn set_lyrics(&mut self, lyrics: Option<LyricTrack>) {
+        self.lyrics = lyrics;
+        self.lyrics_started = Instant::now();
+    }
+
+    pub fn draw(
+        &self,
+        image: &mut RgbImage,
+        settings: &TextOverlaySettings,
+        intensities: &[f32],
+        rng: &mut impl Rng,
+    ) {
+        if !settings.enabled {
+            return;
+        }
+        let Some(font) = &self.font else {
+            return;
+        };
+        let text = match &self.lyrics {
+            Some(track) => track
+                .line_at(self.lyrics_started.elapsed())
+                .unwrap_or_default(),
+            None => settings.text.as_str(),
+        };
+        if text.trim().is_empty() {
+            return;
+        }
+
+        let intensity = intensities
+            .get(settings.driver.index())
+            .copied()
+            .unwrap_or(0.0)
+            .clamp(0.0, 1.0);
+        let scale = PxScale::from(settings.size.max(4.0) * (1.0 + intensity * 0.5));
+        let (glyphs, width) = layout_line(font, text, scale);
+        let (w, h) = image.dimensions();
+        let origin_x = (w as f32 - width) / 2.0;
+        let origin_y = h as f32 * settings.y_position.clamp(0.0, 1.0);
+        let max_shake = settings.jitter.clamp(0.0, 1.0) * intensity * scale.y * 0.15;
+        let color = Rgb(settings.color);
+
+        for mut glyph in glyphs {
+            glyph.position.x += origin_x;
+            glyph.position.y += origin_y;
+            if max_shake > 0.0 {
+                glyph.position.x += rng.gen_range(-max_shake..=max_shake);
+                glyph.position.y += rng.gen_range(-max_shake..=max_shake);
+            }
+            rasterize_glyph(font, image, glyph, color);
+        }
+    }
+
+    // Top-left stack of HUD lines with a drop shadow for legibility
+    pub fn draw_hud(&self, image: &mut RgbImage, hud: &HudSettings) {
+        if !hud.burn_in || !hud.any_visible() {
+            return;
+        }
+        let Some(font) = &self.font else {
+            return;
+        };
+        let line_height = (image.height() as f32 / 18.0).max(14.0);
+        let scale = PxScale::from(line_height);
+        for (i, line) in hud.lines().iter().enumerate() {
+            let (glyphs, _) = layout_line(font, line, scale);
+            let baseline = line_height * (i as f32 + 1.2);
+            for (offset, color) in [(2.0, BLACK), (0.0, WHITE)] {
+                for mut glyph in glyphs.iter().cloned() {
+                    glyph.position.x += line_height * 0.5 + offset;
+                    glyph.position.y += baseline + offset;
+                    rasterize_glyph(font, image, glyph, color);
+                }
+            }
+        }
+    }
+
+    // Compact bottom-left stats block on a dark backing so it reads over any visual
+    pub fn draw_stats(&self, image: &mut RgbImage, lines: &[String]) {
+        let Some(font) = &self.font else {
+            return;
+        };
+        let (w, h) = image.dimensions();
+        let line_height = (h as f32 / 32.0).max(11.0);
+        let scale = PxScale::from(line_height);
+        let laid_out: Vec<(Vec<Glyph>, f32)> =
+            lines.iter().map(|l| layout_line(font, l, scale)).collect();
+        let width = laid_out.iter().map(|(_, w)| *w).fold(0.0, f32::max);
+        let pad = line_height * 0.4;
+        let top = h as f32 - line_height * lines.len() as f32 - pad * 2.0;
+        let box_x1 = ((width + pad * 2.0) as u32).min(w);
+        for y in (top.max(0.0) as u32)..h {
+            for x in 0..box_x1 {
+                let px = image.get_pixel_mut(x, y);
+                for channel in px.0.iter_mut() {
+                    *channel /= 3;
+                }
+            }
+        }
+        for (i, (glyphs, _)) in laid_out.into_iter().enumerate() {
+            let baseline = top + pad + line_height * (i as f32 + 0.8);
+            for mut glyph in glyphs {
+                glyph.position.x += pad;
+                glyph.position.y += baseline;
+                rasterize_glyph(font, image, glyph, WHITE);
+            }
+        }
+    }
+}
+
+// Single-line layout starting at the origin; returns glyphs and total advance
+fn layout_line(font: &FontArc, text: &str, scale: PxScale) -> (Vec<Glyph>, f32) {
+    let scaled = font.as_scaled(scale);
+    let mut glyphs: Vec<Glyph> = Vec::with_capacity(text.len());
+    let mut caret = 0.0f32;
+    let mut previous = None;
+    for ch in text.chars() {
+        let id = scaled.glyph_id(ch);
+        if let Some(prev) = previous {
+            caret += scaled.kern(prev, id);
+        }
+        glyphs.push(id.with_scale_and_position(scale, ab_glyph::point(caret, 0.0)));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    (glyphs, caret)
+}
+
+fn rasterize_glyph(font: &FontArc, image: &mut RgbImage, glyph: Glyph, color: Rgb<u8>) {
+    let Some(outlined) = font.outline_glyph(glyph) else {
+        return; // Whitespace has no outline
+    };
+    let (w, h) = image.dimensions();
+    let bounds = outlined.px_bounds();
+    outlined.draw(|gx, gy, coverage| {
+        let x = bounds.min.x as i32 + gx as i32;
+        let y = bounds.min.y as i32 + gy as i32;
+        if x < 0 || y < 0 || x >= w as i32 || y >= h as i32 {
+            return;
+        }
+        let px = image.get_pixel_mut(x as u32, y as u32);
+        for (channel, target) in px.0.iter_mut().zip(color.0) {
+            *channel = (*channel as f32 * (1.0 - coverage) + target as f32 * coverage) as u8;
+        }
+    });
+}
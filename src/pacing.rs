@@ -0,0 +1,140 @@
+// src/pacing.rs
+// Chooses which queued camera frame the segmentation loop renders next. "Latest wins" always
+// takes the newest frame, so output intervals follow whatever inference happened to cost.
+// Paced mode steps through capture timestamps at a whole multiple of the camera interval and
+// holds each result until its slot, trading a little latency for even motion.
+use image::RgbImage;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+const MAX_QUEUED: usize = 8; // Older frames are dropped beyond this
+const MAX_STRIDE: u32 = 8; // Never present less than every 8th camera frame
+const MAX_CAMERA_GAP: Duration = Duration::from_secs(1); // Longer (replay loop) isn't an interval
+const EMA_ALPHA: f32 = 0.1;
+const FALLBACK_INTERVAL: Duration = Duration::from_millis(33);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FramePacing {
+    pub paced: bool,       // Constant presentation intervals instead of latest-wins
+    pub interpolate: bool, // Move held boxes along their velocity between inference passes
+}
+
+impl Default for FramePacing {
+    fn default() -> Self {
+        Self {
+            paced: false,
+            interpolate: true,
+        }
+    }
+}
+
+pub struct PacedFrame {
+    pub frame: Arc<RgbImage>,
+    pub captured: Instant,
+    pub index: u64,   // Source sequence number
+    pub skipped: u64, // Queued frames dropped to reach this one
+}
+
+#[derive(Default)]
+pub struct FramePacer {
+    queue: VecDeque<(Instant, u64, Arc<RgbImage>)>,
+    last_pushed: Option<Instant>,
+    camera_interval: Option<f32>, // Seconds, smoothed
+    work_time: Option<f32>,       // Seconds per rendered frame, smoothed
+    last_captured: Option<Instant>,
+    last_presented: Option<Instant>,
+}
+
+fn smooth(current: Option<f32>, sample: f32) -> f32 {
+    current.map_or(sample, |c| c + (sample - c) * EMA_ALPHA)
+}
+
+impl FramePacer {
+    pub fn push(&mut self, captured: Instant, index: u64, frame: Arc<RgbImage>) -> u64 {
+        if let Some(prev) = self.last_pushed {
+            let gap = captured.saturating_duration_since(prev);
+            if !gap.is_zero() && gap < MAX_CAMERA_GAP {
+                self.camera_interval = Some(smooth(self.camera_interval, gap.as_secs_f32()));
+            }
+        }
+        self.last_pushed = Some(captured);
+        self.queue.push_back((captured, index, frame));
+        let overflow = self.queue.len().saturating_sub(MAX_QUEUED);
+        self.queue.drain(..overflow);
+        overflow as u64
+    }
+
+    pub fn camera_interval(&self) -> Duration {
+        self.camera_interval
+            .map_or(FALLBACK_INTERVAL, Duration::from_secs_f32)
+    }
+
+    // Camera interval times the number of frames one render takes, rounded up
+    pub fn target_interval(&self) -> Duration {
+        let interval = self.camera_interval();
+        let stride = self.work_time.map_or(1, |w| {
+            ((w / interval.as_secs_f32()).ceil() as u32).clamp(1, MAX_STRIDE)
+        });
+        interval * stride
+    }
+
+    pub fn next(&mut self, pacing: FramePacing) -> Option<PacedFrame> {
+        let newest = self.queue.back()?.0;
+        let pick = match self.last_captured.filter(|_| pacing.paced) {
+            None => self.queue.len() - 1,
+            Some(last) => {
+                let step = self.target_interval();
+                let target = last + step;
+                let half = self.camera_interval() / 2;
+                if newest + half < target {
+                    return None; // The frame for this slot hasn't been captured yet
+                }
+                if newest.saturating_duration_since(target) > step * 2 {
+                    self.queue.len() - 1 // Fell behind (stall, restart): resync on the newest
+                } else {
+                    let distance = |t: Instant| {
+                        t.saturating_duration_since(target)
+                            .max(target.saturating_duration_since(t))
+                    };
+                    (0..self.queue.len())
+                        .min_by_key(|&i| distance(self.queue[i].0))
+                        .unwrap_or(0)
+                }
+            }
+        };
+        let skipped = pick as u64;
+        self.queue.drain(..pick);
+        let (captured, index, frame) = self.queue.pop_front()?;
+        self.last_captured = Some(captured);
+        Some(PacedFrame {
+            frame,
+            captured,
+            index,
+            skipped,
+        })
+    }
+
+    // When the frame being rendered should reach the UI; None = send immediately
+    pub fn present_at(&self, pacing: FramePacing) -> Option<Instant> {
+        if !pacing.paced {
+            return None;
+        }
+        self.last_presented.map(|at| at + self.target_interval())
+    }
+
+    // Call right after sending; a late frame restarts the rhythm rather than bunching up
+    pub fn presented(&mut self, work: Duration) {
+        self.work_time = Some(smooth(self.work_time, work.as_secs_f32()));
+        self.last_presented = Some(Instant::now());
+    }
+
+    pub fn reset(&mut self) {
+        self.last_captured = None;
+        self.last_presented = None;
+    }
+}
@@ -0,0 +1,264 @@
+// src/paint_layer.rs
+// Alternate compositing mode: the segmentation thread leaves object pixels alone and sends a
+// description of each object instead (downscaled mask, contour, drive level, slot style). The
+// UI paints a mesh version of each slot's style and outline over the camera image, so they stay
+// sharp at any window size and cost the CPU compositor nothing. Only this window sees them; the
+// stream, preview and recordings carry the frame as composited.
+use egui::{
+    ecolor::Hsva, epaint::Vertex, Color32, ColorImage, ImageData, Mesh, Painter, Pos2, Shape,
+    Stroke, TextureHandle, TextureId, TextureOptions,
+};
+use image::{imageops, GrayImage};
+use imageproc::rect::Rect as PixelRect;
+use std::{f32::consts::TAU, sync::Arc};
+
+use crate::visuals::{Motion, OutlineStyle, VisualStyle};
+
+const MASK_TEXTURE_SIDE: u32 = 128; // Longest side of an uploaded mask; the GPU filters it up
+const MAX_PARTICLES: usize = 48; // Per object at full drive
+const PARTICLE_RISE: f32 = 24.0; // Screen pixels a particle drifts before fading out
+const DASH: f32 = 8.0; // Screen pixels per dash, and per gap
+const SLOT_COLORS: [Color32; 3] = [
+    Color32::from_rgb(255, 70, 40),  // Bass
+    Color32::from_rgb(60, 200, 255), // Mid
+    Color32::from_rgb(230, 90, 255), // High
+];
+
+pub struct PaintObject {
+    pub slot: usize,
+    pub bounds: [f32; 4],      // Normalized x0, y0, x1, y1 of the mask texture
+    pub mask: Arc<ColorImage>, // White, with the mask as alpha
+    pub contour: Vec<(f32, f32)>, // Normalized
+    pub intensity: f32,
+    pub phase: f32,
+    pub style: VisualStyle,
+    pub outline: OutlineStyle,
+}
+
+// Mesh stand-in for a slot's style: the paint layer can only tint and move the mask texture
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Fill {
+    Glow,    // Tinted mask, the outer copy swelling with the drive
+    Spin,    // Glow turning about the object's center
+    Flicker, // Glow whose strength jumps every frame
+    Hue,     // Glow cycling through the colour wheel
+}
+
+impl Fill {
+    fn for_style(style: VisualStyle) -> Self {
+        match style {
+            VisualStyle::Vortex => Fill::Spin,
+            VisualStyle::Static => Fill::Flicker,
+            VisualStyle::Hue => Fill::Hue,
+            _ => Fill::Glow,
+        }
+    }
+}
+
+pub struct PaintLayer {
+    pub objects: Vec<PaintObject>,
+    pub frame_count: u64,
+}
+
+impl PaintObject {
+    // None when the bbox misses the frame
+    pub fn new(
+        slot: usize,
+        mask: &GrayImage,
+        bbox: PixelRect,
+        contour: &[(f32, f32)],
+        intensity: f32,
+        phase: f32,
+        (style, outline): (VisualStyle, OutlineStyle),
+    ) -> Option<Self> {
+        let (w, h) = mask.dimensions();
+        let x0 = bbox.left().clamp(0, w as i32) as u32;
+        let y0 = bbox.top().clamp(0, h as i32) as u32;
+        let x1 = (bbox.right().clamp(0, w as i32) as u32).max(x0);
+        let y1 = (bbox.bottom().clamp(0, h as i32) as u32).max(y0);
+        let (cw, ch) = (x1 - x0, y1 - y0);
+        if cw == 0 || ch == 0 {
+            return None;
+        }
+        let scale = (MASK_TEXTURE_SIDE as f32 / cw.max(ch) as f32).min(1.0);
+        let tw = ((cw as f32 * scale).round() as u32).max(1);
+        let th = ((ch as f32 * scale).round() as u32).max(1);
+        let crop = imageops::crop_imm(mask, x0, y0, cw, ch).to_image();
+        let small = imageops::resize(&crop, tw, th, imageops::FilterType::Triangle);
+        let texture = ColorImage {
+            size: [tw as usize, th as usize],
+            pixels: small
+                .pixels()
+                .map(|p| Color32::from_white_alpha(p[0]))
+                .collect(),
+        };
+        let (fw, fh) = (w as f32, h as f32);
+        Some(Self {
+            slot,
+            bounds: [
+                x0 as f32 / fw,
+                y0 as f32 / fh,
+                x1 as f32 / fw,
+                y1 as f32 / fh,
+            ],
+            mask: Arc::new(texture),
+            contour: contour.iter().map(|&(x, y)| (x / fw, y / fh)).collect(),
+            intensity: intensity.clamp(0.0, 1.0),
+            phase,
+            style,
+            outline,
+        })
+    }
+}
+
+// UI side: keeps one mask texture per object and re-uploads only when a new layer arrives
+#[derive(Default)]
+pub struct PaintLayerRenderer {
+    textures: Vec<TextureHandle>,
+    uploaded: Option<Arc<PaintLayer>>,
+    motions: Vec<(Motion, Motion)>, // Per slot: fill spin, marching ants
+}
+
+impl PaintLayerRenderer {
+    // `to_screen` maps normalized frame coordinates into the window (display rect, corner pin);
+    // `fade` dims everything painted, 1 = full, 0 = gone (blackout)
+    pub fn draw(
+        &mut self,
+        ctx: &egui::Context,
+        painter: &Painter,
+        layer: &Arc<PaintLayer>,
+        fade: f32,
+        to_screen: impl Fn((f32, f32)) -> Pos2,
+    ) {
+        if !self
+            .uploaded
+            .as_ref()
+            .is_some_and(|l| Arc::ptr_eq(l, layer))
+        {
+            for (i, object) in layer.objects.iter().enumerate() {
+                let data = ImageData::Color(object.mask.clone());
+                match self.textures.get_mut(i) {
+                    Some(t) => t.set(data, TextureOptions::LINEAR),
+                    None => self.textures.push(ctx.load_texture(
+                        format!("paint_mask_{}", i),
+                        data,
+                        TextureOptions::LINEAR,
+                    )),
+                }
+            }
+            self.uploaded = Some(layer.clone());
+        }
+        let time = layer.frame_count as f32;
+        let slots = layer.objects.iter().map(|o| o.slot + 1).max().unwrap_or(0);
+        if self.motions.len() < slots {
+            self.motions.resize_with(slots, Default::default);
+        }
+        for (object, texture) in layer.objects.iter().zip(&self.textures) {
+            let (spin_motion, ants_motion) = &mut self.motions[object.slot];
+            let fill = Fill::for_style(object.style);
+            let color = match fill {
+                Fill::Hue => {
+                    let hue = (time * 0.005 + object.phase / TAU).rem_euclid(1.0);
+                    Color32::from(Hsva::new(hue, 0.8, 1.0, 1.0))
+                }
+                _ => SLOT_COLORS[object.slot % SLOT_COLORS.len()],
+            }
+            .gamma_multiply(fade);
+            let drive = object.intensity;
+            let [x0, y0, x1, y1] = object.bounds;
+
+            // --- Fill: the mask tinted twice, the outer copy swelling with the drive ---
+            let strength = match fill {
+                // Cheap per-frame hash, so the flicker doesn't need an RNG on the UI side
+                Fill::Flicker => {
+                    let n = (layer.frame_count ^ object.slot as u64).wrapping_mul(0x9E37_79B9);
+                    0.3 + 0.7 * ((n >> 16) & 0xff) as f32 / 255.0
+                }
+                _ => 1.0,
+            };
+            let spin = match fill {
+                Fill::Spin => {
+                    object.phase + spin_motion.advance(layer.frame_count, 0.02 * (0.5 + drive))
+                }
+                _ => 0.0,
+            };
+            let center = to_screen(((x0 + x1) / 2.0, (y0 + y1) / 2.0));
+            let (sin, cos) = spin.sin_cos();
+            for (grow, alpha) in [(1.0 + 0.15 * drive, 0.25 * drive), (1.0, 0.2 + 0.4 * drive)] {
+                let corner = |x: f32, y: f32| {
+                    let d = (to_screen((x, y)) - center) * grow;
+                    center + egui::vec2(d.x * cos - d.y * sin, d.x * sin + d.y * cos)
+                };
+                painter.add(textured_quad(
+                    texture.id(),
+                    [
+                        corner(x0, y0),
+                        corner(x1, y0),
+                        corner(x1, y1),
+                        corner(x0, y1),
+                    ],
+                    color.gamma_multiply(alpha * strength),
+                ));
+            }
+
+            // --- Outline along the model contour, in the slot's outline style ---
+            if object.contour.len() < 3 || object.outline == OutlineStyle::None {
+                continue;
+            }
+            let mut points: Vec<Pos2> = object.contour.iter().map(|&p| to_screen(p)).collect();
+            let stroke = Stroke::new(1.5 + 3.0 * drive, color);
+            match object.outline {
+                OutlineStyle::Ants | OutlineStyle::Dashes | OutlineStyle::Arrows => {
+                    // Ants march with the drive; dashes and arrows hold still
+                    let offset = match object.outline {
+                        OutlineStyle::Ants => ants_motion
+                            .advance(layer.frame_count, 0.5 + 2.0 * drive)
+                            .rem_euclid(2.0 * DASH),
+                        _ => 0.0,
+                    };
+                    points.push(points[0]);
+                    painter.extend(Shape::dashed_line_with_offset(
+                        &points,
+                        stroke,
+                        &[DASH],
+                        &[DASH],
+                        offset,
+                    ));
+                }
+                OutlineStyle::Particles => {
+                    // Rise off the contour and fade, more of them as the drive grows
+                    let count = (MAX_PARTICLES as f32 * drive) as usize;
+                    for k in 0..count {
+                        let along = (k as f32 / count as f32 + time * 0.002 + object.phase).fract();
+                        let origin = points[(along * points.len() as f32) as usize % points.len()];
+                        let age = (time * 0.03 + k as f32 * 0.618).fract();
+                        let sway = ((k as f32 * 2.3 + time * 0.1).sin()) * 4.0;
+                        let pos = origin + egui::vec2(sway, -age * PARTICLE_RISE * (0.5 + drive));
+                        painter.circle_filled(
+                            pos,
+                            1.5 + 2.0 * drive,
+                            color.gamma_multiply((1.0 - age) * drive),
+                        );
+                    }
+                }
+                OutlineStyle::None => {}
+            }
+        }
+    }
+}
+
+// Quad with the full texture stretched over `corners` (clockwise from top-left)
+fn textured_quad(texture: TextureId, corners: [Pos2; 4], tint: Color32) -> Shape {
+    let mut mesh = Mesh::with_texture(texture);
+    let uvs = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+    for (pos, (u, v)) in corners.into_iter().zip(uvs) {
+        mesh.vertices.push(Vertex {
+            pos,
+            uv: Pos2::new(u, v),
+            color: tint,
+        });
+    }
+    mesh.add_triangle(0, 1, 2);
+    mesh.add_triangle(0, 2, 3);
+    Shape::mesh(mesh)
+}
@@ -0,0 +1,127 @@
+// src/permissions.rs
+use log::{info, warn};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Camera,
+    Microphone,
+}
+
+impl MediaKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            MediaKind::Camera => "Camera",
+            MediaKind::Microphone => "Microphone",
+        }
+    }
+}
+
+// Mirrors AVAuthorizationStatus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionStatus {
+    NotDetermined,
+    Restricted,
+    Denied,
+    Authorized,
+}
+
+// --- macOS: AVCaptureDevice authorization via the Objective-C runtime ---
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{MediaKind, PermissionStatus};
+    use block::ConcreteBlock;
+    use objc::runtime::{Object, BOOL, YES};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {
+        static AVMediaTypeVideo: *mut Object;
+        static AVMediaTypeAudio: *mut Object;
+    }
+
+    fn media_type(kind: MediaKind) -> *mut Object {
+        unsafe {
+            match kind {
+                MediaKind::Camera => AVMediaTypeVideo,
+                MediaKind::Microphone => AVMediaTypeAudio,
+            }
+        }
+    }
+
+    pub fn status(kind: MediaKind) -> PermissionStatus {
+        let raw: isize = unsafe {
+            msg_send![class!(AVCaptureDevice), authorizationStatusForMediaType: media_type(kind)]
+        };
+        match raw {
+            0 => PermissionStatus::NotDetermined,
+            1 => PermissionStatus::Restricted,
+            2 => PermissionStatus::Denied,
+            _ => PermissionStatus::Authorized,
+        }
+    }
+
+    pub fn request(kind: MediaKind, on_complete: impl Fn(bool) + Send + 'static) {
+        let block = ConcreteBlock::new(move |granted: BOOL| on_complete(granted == YES)).copy();
+        unsafe {
+            let _: () = msg_send![
+                class!(AVCaptureDevice),
+                requestAccessForMediaType: media_type(kind)
+                completionHandler: &*block
+            ];
+        }
+    }
+
+    pub fn settings_url(kind: MediaKind) -> &'static str {
+        match kind {
+            MediaKind::Camera => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Camera"
+            }
+            MediaKind::Microphone => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone"
+            }
+        }
+    }
+}
+
+// --- Other platforms: no OS-level gate we can query ---
+#[cfg(not(target_os = "macos"))]
+mod platform {
+    use super::{MediaKind, PermissionStatus};
+
+    pub fn status(_kind: MediaKind) -> PermissionStatus {
+        PermissionStatus::Authorized
+    }
+
+    pub fn request(_kind: MediaKind, on_complete: impl Fn(bool) + Send + 'static) {
+        on_complete(true);
+    }
+}
+
+pub fn status(kind: MediaKind) -> PermissionStatus {
+    platform::status(kind)
+}
+
+// Prompts the user if the status is undetermined; the callback runs on an arbitrary thread
+pub fn request(kind: MediaKind, on_complete: impl Fn(bool) + Send + 'static) {
+    info!("Requesting {} permission", kind.label());
+    platform::request(kind, on_complete);
+}
+
+pub fn open_settings(kind: MediaKind) {
+    #[cfg(target_os = "macos")]
+    {
+        if let Err(e) = std::process::Command::new("open")
+            .arg(platform::settings_url(kind))
+            .spawn()
+        {
+            warn!("Failed to open System Settings: {}", e);
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        warn!(
+            "No settings page to open for {} permission on this platform",
+            kind.label()
+        );
+    }
+}
@@ -0,0 +1,260 @@
+// src/pipeline.rs
+// Data channels between the pipeline stages. They are async channels: worker loops await them
+// in a `select!` next to their stop signal, while producers on plain threads (the cpal callback,
+// the camera) send without a runtime.
+use anyhow::{bail, Result};
+use async_channel::{bounded, unbounded, Sender};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+pub use async_channel::{Receiver, SendError, TryRecvError, TrySendError};
+
+// --- What a bounded channel does with a message when it is full ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropPolicy {
+    DropNewest, // Discard the message being sent (previous hardcoded behaviour)
+    DropOldest, // Evict the oldest queued message so the freshest data gets through
+    Block,      // Wait for room; backpressure reaches the producer
+}
+
+impl DropPolicy {
+    pub fn label(self) -> &'static str {
+        match self {
+            DropPolicy::DropNewest => "drop newest",
+            DropPolicy::DropOldest => "drop oldest",
+            DropPolicy::Block => "block",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    pub capacity: Option<usize>, // None = unbounded (policy never applies)
+    pub policy: DropPolicy,
+}
+
+impl ChannelConfig {
+    pub const fn unbounded() -> Self {
+        Self {
+            capacity: None,
+            policy: DropPolicy::DropNewest,
+        }
+    }
+
+    const fn bounded(capacity: usize, policy: DropPolicy) -> Self {
+        Self {
+            capacity: Some(capacity),
+            policy,
+        }
+    }
+}
+
+// --- Sizes and policies for the inter-thread data channels ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PipelineConfig {
+    pub camera_frames: ChannelConfig, // Camera/replay -> segmentation
+    pub seg_to_ui: ChannelConfig,     // Segmentation -> UI status (frames use a LatestSlot)
+    pub raw_audio: ChannelConfig,     // cpal callback -> audio processor
+    pub intensities: ChannelConfig,   // Audio processor -> segmentation
+    pub drum_hits: ChannelConfig,     // Audio processor -> segmentation, classified hits
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            camera_frames: ChannelConfig::unbounded(),
+            seg_to_ui: ChannelConfig::bounded(8, DropPolicy::DropNewest),
+            raw_audio: ChannelConfig::bounded(10, DropPolicy::DropNewest),
+            intensities: ChannelConfig::bounded(5, DropPolicy::DropNewest),
+            drum_hits: ChannelConfig::bounded(16, DropPolicy::DropOldest),
+        }
+    }
+}
+
+impl PipelineConfig {
+    // Raw audio is sent from the cpal callback, which must never wait for the processor
+    pub fn validate(&self) -> Result<()> {
+        if self.raw_audio.policy == DropPolicy::Block {
+            bail!("pipeline.raw_audio cannot use the block policy: it would stall audio capture");
+        }
+        Ok(())
+    }
+}
+
+// --- Sender that applies the channel's drop policy on `try_send` and counts drops ---
+pub struct PolicySender<T> {
+    sender: Sender<T>,
+    policy: DropPolicy,
+    drops: Arc<AtomicU64>,
+}
+
+impl<T> Clone for PolicySender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            policy: self.policy,
+            drops: self.drops.clone(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for PolicySender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PolicySender")
+            .field("policy", &self.policy)
+            .field("drops", &self.drops.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl<T> PolicySender<T> {
+    // Data path: never blocks unless the policy is Block. Full means the message was dropped;
+    // Closed means the receiver is gone, for every policy
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        match self.policy {
+            DropPolicy::Block => self
+                .sender
+                .send_blocking(msg)
+                .map_err(|SendError(m)| TrySendError::Closed(m)),
+            DropPolicy::DropNewest => match self.sender.try_send(msg) {
+                Err(TrySendError::Full(m)) => {
+                    self.drops.fetch_add(1, Ordering::Relaxed);
+                    Err(TrySendError::Full(m))
+                }
+                other => other,
+            },
+            // Replaces the oldest queued message in one step; fails once the receiver is gone
+            DropPolicy::DropOldest => match self.sender.force_send(msg) {
+                Ok(evicted) => {
+                    if evicted.is_some() {
+                        self.drops.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Ok(())
+                }
+                Err(SendError(m)) => Err(TrySendError::Closed(m)),
+            },
+        }
+    }
+
+    // Control messages (errors, status) always wait for room regardless of policy
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.sender.send_blocking(msg)
+    }
+
+    // Fewer than `limit` messages queued, and room for one more: lets a lossless producer hold
+    // back on an unbounded channel, and know that `send` won't block on a bounded one
+    pub fn queued_below(&self, limit: usize) -> bool {
+        let capacity = self.sender.capacity().unwrap_or(usize::MAX);
+        self.sender.len() < limit.min(capacity)
+    }
+}
+
+// --- Read-only view of a channel for the diagnostics panel ---
+#[derive(Clone)]
+pub struct ChannelStats {
+    pub name: &'static str,
+    pub config: ChannelConfig,
+    drops: Arc<AtomicU64>,
+    len: Arc<dyn Fn() -> usize + Send + Sync>,
+}
+
+impl ChannelStats {
+    pub fn drops(&self) -> u64 {
+        self.drops.load(Ordering::Relaxed)
+    }
+
+    pub fn queued(&self) -> usize {
+        (self.len)()
+    }
+}
+
+pub fn channel<T: Send + 'static>(
+    name: &'static str,
+    config: ChannelConfig,
+) -> (PolicySender<T>, Receiver<T>, ChannelStats) {
+    let (sender, receiver) = match config.capacity {
+        // A zero-capacity channel is a rendezvous: drop policies would drop everything
+        Some(capacity) => bounded(capacity.max(1)),
+        None => unbounded(),
+    };
+    let drops = Arc::new(AtomicU64::new(0));
+    // Weak, so the stats never keep the channel open for the receiver
+    let len_sender = sender.downgrade();
+    let stats = ChannelStats {
+        name,
+        config,
+        drops: drops.clone(),
+        len: Arc::new(move || len_sender.upgrade().map_or(0, |s| s.len())),
+    };
+    (
+        PolicySender {
+            sender,
+            policy: config.policy,
+            drops,
+        },
+        receiver,
+        stats,
+    )
+}
+
+// --- Latest-wins hand-off: the producer overwrites, the consumer takes the newest ---
+// No queue to fill up, so the consumer always gets the freshest value and the producer never
+// waits; values overwritten before being taken are counted instead.
+pub struct LatestSlot<T>(Arc<SlotInner<T>>);
+
+struct SlotInner<T> {
+    latest: Mutex<Option<(u64, T)>>,
+    published: AtomicU64,
+    overwritten: AtomicU64,
+}
+
+impl<T> Clone for LatestSlot<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Default for LatestSlot<T> {
+    fn default() -> Self {
+        Self(Arc::new(SlotInner {
+            latest: Mutex::new(None),
+            published: AtomicU64::new(0),
+            overwritten: AtomicU64::new(0),
+        }))
+    }
+}
+
+impl<T> LatestSlot<T> {
+    // True if this overwrote a value the consumer never took
+    pub fn publish(&self, value: T) -> bool {
+        let seq = self.0.published.fetch_add(1, Ordering::Relaxed) + 1;
+        // Swap under the lock, drop the superseded value after releasing it
+        let previous = match self.0.latest.lock() {
+            Ok(mut latest) => latest.replace((seq, value)),
+            Err(_) => return false,
+        };
+        let overwrote = previous.is_some();
+        if overwrote {
+            self.0.overwritten.fetch_add(1, Ordering::Relaxed);
+        }
+        overwrote
+    }
+
+    // Newest value with its sequence number (1, 2, ...); gaps mean values were overwritten
+    pub fn take(&self) -> Option<(u64, T)> {
+        self.0.latest.lock().ok()?.take()
+    }
+
+    pub fn published(&self) -> u64 {
+        self.0.published.load(Ordering::Relaxed)
+    }
+
+    pub fn overwritten(&self) -> u64 {
+        self.0.overwritten.load(Ordering::Relaxed)
+    }
+}
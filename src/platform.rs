@@ -0,0 +1,96 @@
+// src/platform.rs
+use nokhwa::utils::ApiBackend;
+
+// --- Per-OS device preferences: camera backends, loopback audio names, setup hints ---
+#[cfg(target_os = "macos")]
+mod imp {
+    use nokhwa::utils::ApiBackend;
+
+    pub const CAMERA_BACKENDS: &[ApiBackend] = &[ApiBackend::Auto, ApiBackend::AVFoundation];
+    pub const LOOPBACK_NAMES: &[&str] = &["blackhole"]; // Matched case-insensitively
+    pub const LOOPBACK_HINT: &str = "Install BlackHole and add it to a Multi-Output Device in \
+        Audio MIDI Setup to visualize computer audio.";
+    pub const CAMERA_HINT: &str = "Connect a webcam and check that camera access is allowed for \
+        this app in System Settings > Privacy & Security > Camera.";
+}
+
+// V4L2 first; PipeWire and PulseAudio expose sink monitors as "Monitor of ..." sources (named
+// "<sink>.monitor"), and the snd-aloop kernel module shows up as an ALSA card named "Loopback".
+// A bare "monitor" would also match hardware named after studio monitors
+#[cfg(target_os = "linux")]
+mod imp {
+    use nokhwa::utils::ApiBackend;
+
+    pub const CAMERA_BACKENDS: &[ApiBackend] = &[ApiBackend::Video4Linux, ApiBackend::Auto];
+    pub const LOOPBACK_NAMES: &[&str] = &["monitor of", ".monitor", "loopback"];
+    pub const LOOPBACK_HINT: &str = "Capture a sink monitor: list them with \
+        `pactl list short sources | grep monitor`, then run with PULSE_SOURCE=<name>.monitor \
+        (works for PipeWire too), or load snd-aloop for an ALSA Loopback card.";
+    pub const CAMERA_HINT: &str = "Connect a webcam and check that your user can open \
+        /dev/video* (usually the 'video' group).";
+}
+
+#[cfg(target_os = "windows")]
+mod imp {
+    use nokhwa::utils::ApiBackend;
+
+    pub const CAMERA_BACKENDS: &[ApiBackend] = &[ApiBackend::MediaFoundation, ApiBackend::Auto];
+    pub const LOOPBACK_NAMES: &[&str] = &["stereo mix", "cable output"];
+    pub const LOOPBACK_HINT: &str = "Enable 'Stereo Mix' in Sound settings or install VB-CABLE \
+        to visualize computer audio.";
+    pub const CAMERA_HINT: &str = "Connect a webcam and allow camera access in Settings > \
+        Privacy & security > Camera.";
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+mod imp {
+    use nokhwa::utils::ApiBackend;
+
+    pub const CAMERA_BACKENDS: &[ApiBackend] = &[ApiBackend::Auto];
+    pub const LOOPBACK_NAMES: &[&str] = &["loopback"];
+    pub const LOOPBACK_HINT: &str = "Install a loopback audio driver to visualize computer audio.";
+    pub const CAMERA_HINT: &str = "Connect a webcam and check camera permissions.";
+}
+
+pub use imp::{CAMERA_HINT, LOOPBACK_HINT};
+
+// Backends to try when opening a camera, most preferred first
+pub fn camera_backends() -> &'static [ApiBackend] {
+    imp::CAMERA_BACKENDS
+}
+
+// Position of the first name substring `name` contains (lower = preferred), matched
+// case-insensitively. `preferred` comes from the config file; empty = this platform's defaults
+pub fn loopback_rank(name: &str, preferred: &[String]) -> Option<usize> {
+    let name = name.to_lowercase();
+    if preferred.is_empty() {
+        imp::LOOPBACK_NAMES.iter().position(|n| name.contains(n))
+    } else {
+        preferred
+            .iter()
+            .position(|n| !n.is_empty() && name.contains(&n.to_lowercase()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_configured_names_in_order() {
+        let preferred = vec!["Cable".to_string(), String::new(), "loopback".to_string()];
+        assert_eq!(loopback_rank("VB-CABLE Output", &preferred), Some(0));
+        assert_eq!(loopback_rank("Loopback Audio", &preferred), Some(2));
+        assert_eq!(loopback_rank("Built-in Microphone", &preferred), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn ranks_linux_monitor_sources() {
+        let rank = |name| loopback_rank(name, &[]);
+        assert_eq!(rank("Monitor of Built-in Audio Analog Stereo"), Some(0));
+        assert_eq!(rank("alsa_output.pci-0000_00_1f.3.monitor"), Some(1));
+        assert_eq!(rank("Loopback, Loopback PCM"), Some(2));
+        assert_eq!(rank("Studio Monitor USB"), None);
+    }
+}
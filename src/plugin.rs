@@ -0,0 +1,325 @@
+// src/plugin.rs
+// Third-party visuals loaded from cdylibs in `plugins/`. Rust has no stable ABI, so the boundary
+// is plain C: a plugin exports `sam_visual_plugin`, returning a static `PluginVTable`. Inside the
+// app every plugin is wrapped as a `Visual`, the same trait any future built-in visual can use.
+// See plugins/README.md for a minimal plugin.
+use anyhow::{bail, Context, Result};
+use image::{GrayImage, RgbImage};
+use imageproc::rect::Rect;
+use libloading::Library;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::{c_char, c_void, CStr},
+    path::Path,
+};
+
+pub const PLUGIN_DIR: &str = "plugins";
+pub const PLUGIN_ABI_VERSION: u32 = 1; // Bumped on any change to the #[repr(C)] types below
+const ENTRY_SYMBOL: &[u8] = b"sam_visual_plugin\0";
+
+// --- C ABI ---
+#[repr(C)]
+pub struct RawParamSpec {
+    pub name: *const c_char, // NUL-terminated, 'static
+    pub unit: *const c_char, // NUL-terminated, 'static; may be empty
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+}
+
+// One masked object on one frame; pointers are only valid for the duration of `update`
+#[repr(C)]
+pub struct RawFrame {
+    pub target: *mut u8,     // RGB8, width * height * 3, drawn into in place
+    pub original: *const u8, // RGB8 camera frame before any effects, same size
+    pub mask: *const u8,     // 8-bit alpha, width * height; 0 = outside, 255 = inside
+    pub width: u32,
+    pub height: u32,
+    pub bbox: [i32; 4], // x, y, width, height in pixels
+    pub features: *const f32, // Audio intensities, layout as music::AnalysisChannel
+    pub num_features: u32,
+    pub params: *const f32, // Current values, same order as the vtable's params
+    pub num_params: u32,
+    pub slot: u32,
+    pub frame_count: u64,
+}
+
+#[repr(C)]
+pub struct PluginVTable {
+    pub abi_version: u32,
+    pub name: *const c_char,
+    pub params: *const RawParamSpec,
+    pub num_params: u32,
+    // Called again on resize. Null is a valid state for stateless plugins
+    pub init: extern "C" fn(width: u32, height: u32) -> *mut c_void,
+    pub update: extern "C" fn(state: *mut c_void, frame: *const RawFrame), // State may be null
+    pub destroy: extern "C" fn(state: *mut c_void),
+}
+
+type EntryFn = unsafe extern "C" fn() -> *const PluginVTable;
+
+// --- Safe side ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParamSpec {
+    pub name: String,
+    pub unit: String,
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+}
+
+// What the UI needs to list a plugin and build its controls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub params: Vec<ParamSpec>,
+}
+
+pub struct VisualFrame<'a> {
+    pub target: &'a mut RgbImage,
+    pub original: &'a RgbImage,
+    pub mask: &'a GrayImage, // Full-frame, like the built-in visuals get
+    pub bbox: Rect,
+    pub features: &'a [f32],
+    pub params: &'a [f32],
+    pub slot: usize,
+    pub frame_count: u64,
+}
+
+pub trait Visual {
+    fn info(&self) -> &PluginInfo;
+    fn init(&mut self, width: u32, height: u32);
+    fn update(&mut self, frame: VisualFrame<'_>);
+}
+
+// --- A loaded cdylib; `state` is destroyed before the library is unloaded ---
+pub struct DylibVisual {
+    info: PluginInfo,
+    vtable: &'static PluginVTable,
+    state: *mut c_void,
+    size: (u32, u32),
+    _library: Library,
+}
+
+unsafe fn c_str(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(ptr).to_string_lossy().into_owned()
+}
+
+impl DylibVisual {
+    pub fn load(path: &Path) -> Result<Self> {
+        // SAFETY: loading runs the library's initializers; plugins are trusted like the app itself
+        let library = unsafe { Library::new(path) }
+            .with_context(|| format!("Failed to load plugin {}", path.display()))?;
+        let vtable = unsafe {
+            let entry = library
+                .get::<EntryFn>(ENTRY_SYMBOL)
+                .with_context(|| format!("{} has no sam_visual_plugin export", path.display()))?;
+            let vtable = entry();
+            if vtable.is_null() {
+                bail!("{} returned no plugin table", path.display());
+            }
+            &*vtable
+        };
+        if vtable.abi_version != PLUGIN_ABI_VERSION {
+            bail!(
+                "{} targets plugin ABI {}, this build supports {}",
+                path.display(),
+                vtable.abi_version,
+                PLUGIN_ABI_VERSION
+            );
+        }
+        let raw_params: &[RawParamSpec] = if vtable.params.is_null() || vtable.num_params == 0 {
+            &[]
+        } else {
+            unsafe { std::slice::from_raw_parts(vtable.params, vtable.num_params as usize) }
+        };
+        // Bounds feed f32::clamp, which panics on NaN or min > max
+        for p in raw_params {
+            if !(p.min.is_finite() && p.max.is_finite() && p.min <= p.max) {
+                bail!(
+                    "{}: parameter '{}' has invalid bounds {}..{}",
+                    path.display(),
+                    unsafe { c_str(p.name) },
+                    p.min,
+                    p.max
+                );
+            }
+        }
+        let info = PluginInfo {
+            name: unsafe { c_str(vtable.name) },
+            params: raw_params
+                .iter()
+                .map(|p| ParamSpec {
+                    name: unsafe { c_str(p.name) },
+                    unit: unsafe { c_str(p.unit) },
+                    min: p.min,
+                    max: p.max,
+                    default: if p.default.is_finite() {
+                        p.default.clamp(p.min, p.max)
+                    } else {
+                        p.min
+                    },
+                })
+                .collect(),
+        };
+        if info.name.is_empty() {
+            bail!("{} has an empty plugin name", path.display());
+        }
+        Ok(Self {
+            info,
+            vtable,
+            state: std::ptr::null_mut(),
+            size: (0, 0),
+            _library: library,
+        })
+    }
+
+    // A null state (stateless plugin) is never passed to `destroy`
+    fn destroy_state(&mut self) {
+        if !self.state.is_null() {
+            (self.vtable.destroy)(self.state);
+            self.state = std::ptr::null_mut();
+        }
+    }
+}
+
+impl Visual for DylibVisual {
+    fn info(&self) -> &PluginInfo {
+        &self.info
+    }
+
+    fn init(&mut self, width: u32, height: u32) {
+        self.destroy_state();
+        self.state = (self.vtable.init)(width, height);
+        self.size = (width, height);
+    }
+
+    fn update(&mut self, frame: VisualFrame<'_>) {
+        let (width, height) = frame.target.dimensions();
+        // The plugin reads width * height from every buffer, so they must all match the target
+        if frame.original.dimensions() != (width, height)
+            || frame.mask.dimensions() != (width, height)
+        {
+            debug!(
+                "Skipping plugin '{}': source {:?} / mask {:?} don't match target {}x{}",
+                self.info.name,
+                frame.original.dimensions(),
+                frame.mask.dimensions(),
+                width,
+                height
+            );
+            return;
+        }
+        if self.size != (width, height) {
+            self.init(width, height);
+        }
+        let raw = RawFrame {
+            target: frame.target.as_mut_ptr(),
+            original: frame.original.as_ptr(),
+            mask: frame.mask.as_ptr(),
+            width,
+            height,
+            bbox: [
+                frame.bbox.left(),
+                frame.bbox.top(),
+                frame.bbox.width() as i32,
+                frame.bbox.height() as i32,
+            ],
+            features: frame.features.as_ptr(),
+            num_features: frame.features.len() as u32,
+            params: frame.params.as_ptr(),
+            num_params: frame.params.len() as u32,
+            slot: frame.slot as u32,
+            frame_count: frame.frame_count,
+        };
+        (self.vtable.update)(self.state, &raw);
+    }
+}
+
+impl Drop for DylibVisual {
+    fn drop(&mut self) {
+        self.destroy_state();
+    }
+}
+
+// --- Everything found in `plugins/`, with each plugin's current parameter values ---
+#[derive(Default)]
+pub struct PluginHost {
+    plugins: Vec<(Box<dyn Visual>, Vec<f32>)>,
+}
+
+impl PluginHost {
+    // Broken plugins are logged and skipped; a missing directory just means no plugins
+    pub fn discover(dir: &Path) -> Self {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Self::default();
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|e| e == std::env::consts::DLL_EXTENSION))
+            .collect();
+        paths.sort();
+        let mut host = Self::default();
+        for path in paths {
+            match DylibVisual::load(&path) {
+                Ok(plugin) if host.index_of(&plugin.info.name).is_some() => {
+                    warn!("Skipping {}: duplicate plugin name", path.display());
+                }
+                Ok(plugin) => {
+                    info!("Loaded visual plugin '{}' from {}", plugin.info.name, path.display());
+                    let values = plugin.info.params.iter().map(|p| p.default).collect();
+                    host.plugins.push((Box::new(plugin), values));
+                }
+                Err(e) => warn!("{:#}", e),
+            }
+        }
+        host
+    }
+
+    pub fn infos(&self) -> Vec<PluginInfo> {
+        self.plugins.iter().map(|(p, _)| p.info().clone()).collect()
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.plugins.iter().position(|(p, _)| p.info().name == name)
+    }
+
+    pub fn set_param(&mut self, plugin: usize, param: usize, value: f32) {
+        let Some((visual, values)) = self.plugins.get_mut(plugin) else {
+            return;
+        };
+        if let (Some(spec), Some(v)) = (visual.info().params.get(param), values.get_mut(param)) {
+            *v = value.clamp(spec.min, spec.max);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &mut self,
+        plugin: usize,
+        target: &mut RgbImage,
+        original: &RgbImage,
+        mask: &GrayImage,
+        bbox: Rect,
+        features: &[f32],
+        slot: usize,
+        frame_count: u64,
+    ) {
+        if let Some((visual, values)) = self.plugins.get_mut(plugin) {
+            visual.update(VisualFrame {
+                target,
+                original,
+                mask,
+                bbox,
+                features,
+                params: values,
+                slot,
+                frame_count,
+            });
+        }
+    }
+}
@@ -0,0 +1,486 @@
+// src/post.rs
+use image::{GrayImage, RgbImage};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::music::AnalysisChannel;
+
+// --- Full-frame post processing, applied after all per-object visuals ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BloomSettings {
+    pub enabled: bool,
+    pub threshold: f32, // Luma (0-1) above which pixels contribute to the glow
+    pub strength: f32,  // Glow gain at full loudness
+    pub radius: u32,    // Blur radius in half-resolution pixels
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 0.7,
+            strength: 1.0,
+            radius: 6,
+        }
+    }
+}
+
+// Sliding-window box blur over one axis of a planar RGB f32 buffer
+fn box_blur_axis(src: &[f32], dst: &mut [f32], w: usize, h: usize, radius: usize, horizontal: bool) {
+    let (outer, inner) = if horizontal { (h, w) } else { (w, h) };
+    let idx = |o: usize, i: usize| if horizontal { (o * w + i) * 3 } else { (i * w + o) * 3 };
+    let norm = 1.0 / (2 * radius + 1) as f32;
+    for o in 0..outer {
+        let mut sum = [0.0f32; 3];
+        // Prime window with clamped edge samples
+        for k in 0..=(2 * radius) {
+            let i = k.saturating_sub(radius).min(inner - 1);
+            let p = idx(o, i);
+            for (s, v) in sum.iter_mut().zip(&src[p..p + 3]) {
+                *s += v;
+            }
+        }
+        for i in 0..inner {
+            let p = idx(o, i);
+            for (d, s) in dst[p..p + 3].iter_mut().zip(&sum) {
+                *d = s * norm;
+            }
+            let add = idx(o, (i + radius + 1).min(inner - 1));
+            let sub = idx(o, i.saturating_sub(radius));
+            for (c, s) in sum.iter_mut().enumerate() {
+                *s += src[add + c] - src[sub + c];
+            }
+        }
+    }
+}
+
+// Threshold -> blur (two box passes ~ Gaussian) at half resolution -> additive blend
+pub fn apply_bloom(image: &mut RgbImage, settings: &BloomSettings, loudness: f32) {
+    let gain = settings.strength * (0.25 + loudness.clamp(0.0, 1.0));
+    if !settings.enabled || gain <= 0.0 {
+        return;
+    }
+    let (w, h) = image.dimensions();
+    let (bw, bh) = ((w / 2).max(1) as usize, (h / 2).max(1) as usize);
+    let threshold = settings.threshold.clamp(0.0, 0.99);
+
+    let mut bright = vec![0.0f32; bw * bh * 3];
+    for by in 0..bh {
+        for bx in 0..bw {
+            let px = image.get_pixel(((bx * 2) as u32).min(w - 1), ((by * 2) as u32).min(h - 1));
+            let rgb = [px[0] as f32 / 255.0, px[1] as f32 / 255.0, px[2] as f32 / 255.0];
+            let luma = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+            if luma > threshold {
+                let k = (luma - threshold) / (1.0 - threshold);
+                let p = (by * bw + bx) * 3;
+                for (b, v) in bright[p..p + 3].iter_mut().zip(&rgb) {
+                    *b = v * k;
+                }
+            }
+        }
+    }
+
+    let radius = (settings.radius as usize).max(1);
+    let mut scratch = vec![0.0f32; bright.len()];
+    for _ in 0..2 {
+        box_blur_axis(&bright, &mut scratch, bw, bh, radius, true);
+        box_blur_axis(&scratch, &mut bright, bw, bh, radius, false);
+    }
+
+    for (x, y, px) in image.enumerate_pixels_mut() {
+        let bx = ((x / 2) as usize).min(bw - 1);
+        let by = ((y / 2) as usize).min(bh - 1);
+        let p = (by * bw + bx) * 3;
+        for (channel, glow) in px.0.iter_mut().zip(&bright[p..p + 3]) {
+            *channel = (*channel as f32 + glow * gain * 255.0).min(255.0) as u8;
+        }
+    }
+}
+
+// --- Glitch chain: ordered effects, each with an optional audio driver ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PostEffectKind {
+    ChannelOffset,
+    Scanlines,
+    Noise,
+    TapeWobble,
+}
+
+impl PostEffectKind {
+    pub const ALL: [PostEffectKind; 4] = [
+        PostEffectKind::ChannelOffset,
+        PostEffectKind::Scanlines,
+        PostEffectKind::Noise,
+        PostEffectKind::TapeWobble,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PostEffectKind::ChannelOffset => "RGB Offset",
+            PostEffectKind::Scanlines => "Scanlines",
+            PostEffectKind::Noise => "Noise",
+            PostEffectKind::TapeWobble => "Tape Wobble",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PostEffect {
+    pub kind: PostEffectKind,
+    pub enabled: bool,
+    pub amount: f32, // 0-1 base strength
+    // None = constant, Some = amount scaled by that channel
+    pub driver: Option<AnalysisChannel>,
+}
+
+impl PostEffect {
+    fn effective_amount(&self, intensities: &[f32]) -> f32 {
+        let scale = match self.driver {
+            Some(channel) => intensities.get(channel.index()).copied().unwrap_or(0.0),
+            None => 1.0,
+        };
+        (self.amount * scale).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostChain {
+    pub effects: Vec<PostEffect>,
+}
+
+impl Default for PostChain {
+    fn default() -> Self {
+        let driver = |kind| match kind {
+            PostEffectKind::ChannelOffset | PostEffectKind::TapeWobble => AnalysisChannel::Low,
+            PostEffectKind::Scanlines => AnalysisChannel::Mid,
+            PostEffectKind::Noise => AnalysisChannel::High,
+        };
+        Self {
+            effects: PostEffectKind::ALL
+                .iter()
+                .map(|&kind| PostEffect {
+                    kind,
+                    enabled: false,
+                    amount: 0.5,
+                    driver: Some(driver(kind)),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn apply_channel_offset(image: &mut RgbImage, amount: f32) {
+    let shift = (amount * 20.0).round() as i64;
+    if shift == 0 {
+        return;
+    }
+    let source = image.clone();
+    let max_x = source.width() as i64 - 1;
+    for (x, y, px) in image.enumerate_pixels_mut() {
+        let rx = (x as i64 + shift).clamp(0, max_x) as u32;
+        let bx = (x as i64 - shift).clamp(0, max_x) as u32;
+        px[0] = source.get_pixel(rx, y)[0];
+        px[2] = source.get_pixel(bx, y)[2];
+    }
+}
+
+fn apply_scanlines(image: &mut RgbImage, amount: f32, frame_count: u64) {
+    let darken = 1.0 - amount * 0.6;
+    let roll = (frame_count / 2) as u32;
+    for (_, y, px) in image.enumerate_pixels_mut() {
+        if (y + roll) % 2 == 0 {
+            for channel in px.0.iter_mut() {
+                *channel = (*channel as f32 * darken) as u8;
+            }
+        }
+    }
+}
+
+fn apply_noise(image: &mut RgbImage, amount: f32, rng: &mut impl Rng) {
+    let range = amount * 64.0;
+    if range < 1.0 {
+        return;
+    }
+    for px in image.pixels_mut() {
+        let n = rng.gen_range(-range..range);
+        for channel in px.0.iter_mut() {
+            *channel = (*channel as f32 + n).clamp(0.0, 255.0) as u8;
+        }
+    }
+}
+
+fn apply_tape_wobble(image: &mut RgbImage, amount: f32, frame_count: u64, rng: &mut impl Rng) {
+    if amount <= 0.0 {
+        return;
+    }
+    let source = image.clone();
+    let (w, h) = source.dimensions();
+    let time = frame_count as f32 * 0.15;
+    // Occasional tracking tear: a band of rows with a much larger offset
+    let tear = if rng.gen::<f32>() < amount * 0.3 {
+        let start = rng.gen_range(0..h);
+        Some((start, (start + rng.gen_range(4..24)).min(h)))
+    } else {
+        None
+    };
+    for y in 0..h {
+        let mut offset = (y as f32 * 0.03 + time).sin() * amount * 12.0;
+        if tear.is_some_and(|(start, end)| y >= start && y < end) {
+            offset += amount * 60.0;
+        }
+        let offset = offset.round() as i64;
+        for x in 0..w {
+            let sx = (x as i64 + offset).rem_euclid(w as i64) as u32;
+            image.put_pixel(x, y, *source.get_pixel(sx, y));
+        }
+    }
+}
+
+// Runs the enabled effects in chain order
+pub fn apply_chain(
+    image: &mut RgbImage,
+    chain: &PostChain,
+    intensities: &[f32],
+    frame_count: u64,
+    rng: &mut impl Rng,
+) {
+    for effect in chain.effects.iter().filter(|e| e.enabled) {
+        let amount = effect.effective_amount(intensities);
+        match effect.kind {
+            PostEffectKind::ChannelOffset => apply_channel_offset(image, amount),
+            PostEffectKind::Scanlines => apply_scanlines(image, amount, frame_count),
+            PostEffectKind::Noise => apply_noise(image, amount, rng),
+            PostEffectKind::TapeWobble => apply_tape_wobble(image, amount, frame_count, rng),
+        }
+    }
+}
+
+// --- Masked output: black outside every tracked mask, for projecting back onto the objects ---
+// Union of the full-frame masks; soft edges stay soft
+pub fn mask_coverage<'a>(
+    (width, height): (u32, u32),
+    masks: impl IntoIterator<Item = &'a GrayImage>,
+) -> GrayImage {
+    let mut coverage = GrayImage::new(width, height);
+    for mask in masks {
+        if mask.dimensions() != coverage.dimensions() {
+            continue;
+        }
+        for (c, m) in coverage.iter_mut().zip(mask.iter()) {
+            *c = (*c).max(*m);
+        }
+    }
+    coverage
+}
+
+// Soft mask edges fade to black rather than cutting off
+pub fn black_outside_masks(image: &mut RgbImage, coverage: &GrayImage) {
+    for (px, c) in image.pixels_mut().zip(coverage.iter()) {
+        let weight = *c as u32;
+        for channel in px.0.iter_mut() {
+            *channel = ((*channel as u32 * weight) / 255) as u8;
+        }
+    }
+}
+
+// --- Master reveal fader: 0 = raw camera, 1 = full composite, 2 = effects only on black ---
+// Past 1, pixels outside the objects' `coverage` fade to black. Keyed on the masks rather than
+// the change from the camera, since grading and full-frame post change every pixel.
+pub fn master_mix(
+    composite: &mut RgbImage,
+    camera: &RgbImage,
+    mix: f32,
+    coverage: Option<&GrayImage>,
+) {
+    let mix = mix.clamp(0.0, 2.0);
+    if mix == 1.0 || composite.dimensions() != camera.dimensions() {
+        return;
+    }
+    if mix < 1.0 {
+        let w = (mix * 256.0) as u32;
+        for (c, r) in composite.iter_mut().zip(camera.iter()) {
+            *c = ((*c as u32 * w + *r as u32 * (256 - w)) >> 8) as u8;
+        }
+        return;
+    }
+    let Some(coverage) = coverage.filter(|c| c.dimensions() == composite.dimensions()) else {
+        return;
+    };
+    let amount = ((mix - 1.0) * 256.0) as u32;
+    for (c, k) in composite.pixels_mut().zip(coverage.iter()) {
+        let key = *k as u32 * 256 / 255;
+        let keep = 256 - ((amount * (256 - key)) >> 8);
+        for channel in c.0.iter_mut() {
+            *channel = ((*channel as u32 * keep) >> 8) as u8;
+        }
+    }
+}
+
+// --- Output tone mapping: keeps effects from clipping on projectors ---
+const TONE_WHITE_POINT: f32 = 1.0; // Scene value shown as display white
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToneCurve {
+    Reinhard,
+    Filmic,
+}
+
+impl ToneCurve {
+    pub const ALL: [ToneCurve; 2] = [ToneCurve::Reinhard, ToneCurve::Filmic];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ToneCurve::Reinhard => "Reinhard",
+            ToneCurve::Filmic => "Filmic",
+        }
+    }
+
+    // Scene value (0 = black, 1 = nominal white, may exceed 1) -> display 0-1. Scaled so
+    // nominal white stays display white; the curve only shapes the tones below it and the
+    // exposure boost above it
+    fn map(self, x: f32) -> f32 {
+        self.curve(x) / self.curve(TONE_WHITE_POINT)
+    }
+
+    fn curve(self, x: f32) -> f32 {
+        match self {
+            // Extended Reinhard with white point 2
+            ToneCurve::Reinhard => x * (1.0 + x / 4.0) / (1.0 + x),
+            // Narkowicz's ACES fit
+            ToneCurve::Filmic => (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ToneMapSettings {
+    pub enabled: bool,
+    pub curve: ToneCurve,
+    pub exposure: f32,     // Stops
+    pub auto_levels: bool, // Stretch black/white points from the frame histogram
+}
+
+impl Default for ToneMapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            curve: ToneCurve::Filmic,
+            exposure: 0.0,
+            auto_levels: true,
+        }
+    }
+}
+
+const LEVELS_SMOOTHING: f32 = 0.1; // Per-frame step toward the measured levels, avoids pumping
+const LEVELS_LOW_PERCENTILE: f32 = 0.01;
+const LEVELS_HIGH_PERCENTILE: f32 = 0.995;
+
+// Smoothed auto-levels state; lives in the segmentation thread
+#[derive(Debug)]
+pub struct ToneMapper {
+    black: f32, // 0-255
+    white: f32,
+}
+
+impl Default for ToneMapper {
+    fn default() -> Self {
+        Self {
+            black: 0.0,
+            white: 255.0,
+        }
+    }
+}
+
+impl ToneMapper {
+    // Black and white points from a subsampled luma histogram
+    fn measure(image: &RgbImage) -> (f32, f32) {
+        let mut histogram = [0u32; 256];
+        let mut total = 0u32;
+        for px in image.pixels().step_by(4) {
+            let luma = (0.2126 * px[0] as f32 + 0.7152 * px[1] as f32 + 0.0722 * px[2] as f32)
+                as usize;
+            histogram[luma.min(255)] += 1;
+            total += 1;
+        }
+        let percentile = |p: f32| {
+            let target = (total as f32 * p) as u32;
+            let mut seen = 0;
+            for (value, count) in histogram.iter().enumerate() {
+                seen += count;
+                if seen > target {
+                    return value as f32;
+                }
+            }
+            255.0
+        };
+        (
+            percentile(LEVELS_LOW_PERCENTILE),
+            percentile(LEVELS_HIGH_PERCENTILE),
+        )
+    }
+
+    pub fn apply(&mut self, image: &mut RgbImage, settings: &ToneMapSettings) {
+        if settings.enabled && settings.auto_levels {
+            let (low, high) = Self::measure(image);
+            self.black += (low - self.black) * LEVELS_SMOOTHING;
+            self.white += (high - self.white) * LEVELS_SMOOTHING;
+        }
+        self.apply_held(image, settings);
+    }
+
+    // Same curve at the levels as they stand, for extra layers graded like the main output
+    pub fn apply_held(&self, image: &mut RgbImage, settings: &ToneMapSettings) {
+        if !settings.enabled {
+            return;
+        }
+        let (black, white) = if settings.auto_levels {
+            // Never stretch a nearly flat frame into noise
+            (self.black, self.white.max(self.black + 64.0))
+        } else {
+            (0.0, 255.0)
+        };
+        let gain = 2f32.powf(settings.exposure);
+        // Same curve for every channel, so one table per frame covers it
+        let table: [u8; 256] = std::array::from_fn(|v| {
+            let x = ((v as f32 - black) / (white - black)).max(0.0) * gain;
+            (settings.curve.map(x).clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+        });
+        for channel in image.iter_mut() {
+            *channel = table[*channel as usize];
+        }
+    }
+}
+
+// --- Blackout: the whole output fades to black (and back) over a set time ---
+#[derive(Debug, Default)]
+pub struct BlackoutFader {
+    level: f32, // 0 = live, 1 = black
+}
+
+impl BlackoutFader {
+    pub fn step(&mut self, engaged: bool, fade_secs: f32, dt: f32) {
+        let target = if engaged { 1.0 } else { 0.0 };
+        let rate = if fade_secs > 0.0 { dt / fade_secs } else { 1.0 };
+        self.level = if self.level < target {
+            (self.level + rate).min(target)
+        } else {
+            (self.level - rate).max(target)
+        };
+    }
+
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    pub fn apply(&self, image: &mut RgbImage) {
+        if self.level <= 0.0 {
+            return;
+        }
+        let gain = ((1.0 - self.level) * 256.0) as u32;
+        for channel in image.iter_mut() {
+            *channel = ((*channel as u32 * gain) >> 8) as u8;
+        }
+    }
+}
@@ -0,0 +1,319 @@
+// src/preview.rs
+use anyhow::{Context, Result};
+use egui::ColorImage;
+use image::codecs::jpeg::JpegEncoder;
+use image::ExtendedColorType;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::supervisor::{self, StopSignal};
+
+const FRAME_WAIT: Duration = Duration::from_millis(250); // Clients re-check for a stop this often
+const BOUNDARY: &str = "sambframe";
+const INDEX_PAGE: &str = "<!doctype html><html><head><title>SAM_CAM_BAM</title></head>\
+    <body style=\"margin:0;background:#000\">\
+    <img src=\"/stream\" style=\"width:100vw;height:100vh;object-fit:contain\"></body></html>";
+
+// --- MJPEG-over-HTTP preview of the composited output ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreviewConfig {
+    pub http_addr: Option<String>, // e.g. "0.0.0.0:8081"; serves /, /stream and /snapshot.jpg
+    pub quality: u8,               // JPEG quality, 1-100
+    pub max_fps: f32,              // Per-client cap; lower saves bandwidth and encode time
+    pub max_clients: usize,        // Connections served at once; more get 503 (one thread each)
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            http_addr: None,
+            quality: 75,
+            max_fps: 30.0,
+            max_clients: 8,
+        }
+    }
+}
+
+#[derive(Default)]
+struct FeedState {
+    frame: Option<Arc<ColorImage>>,
+    sequence: u64,
+    jpeg: Option<(u64, Arc<Vec<u8>>)>, // Encoded once per frame, shared by all clients
+    encoding: Option<u64>,             // Frame a client is encoding now; the rest wait for it
+}
+
+// Latest composited frame; the UI publishes, client threads wait on it
+#[derive(Default)]
+struct Feed {
+    state: Mutex<FeedState>,
+    updated: Condvar,
+}
+
+impl Feed {
+    // Blocks until a frame newer than `after` arrives (or the timeout); encodes it if needed
+    // The lock is only held to read the frame and store the result, never across the encode,
+    // so `publish` on the UI thread never waits for a JPEG. The first client to want a frame
+    // encodes it; the others wait for its result instead of encoding the same frame again
+    fn next_jpeg(&self, after: u64, quality: u8) -> Option<(u64, Arc<Vec<u8>>)> {
+        let state = self.state.lock().unwrap();
+        let (mut state, _) = self
+            .updated
+            .wait_timeout_while(state, FRAME_WAIT, |s| {
+                s.sequence <= after || s.encoding == Some(s.sequence)
+            })
+            .unwrap();
+        if state.sequence <= after || state.encoding == Some(state.sequence) {
+            return None;
+        }
+        let sequence = state.sequence;
+        if let Some((seq, jpeg)) = &state.jpeg {
+            if *seq == sequence {
+                return Some((*seq, jpeg.clone()));
+            }
+        }
+        let frame = state.frame.clone()?;
+        state.encoding = Some(sequence);
+        drop(state);
+
+        let encoded = encode_jpeg(&frame, quality).map(|bytes| (sequence, Arc::new(bytes)));
+        let mut state = self.state.lock().unwrap();
+        // A newer frame may be encoding by now; only clear our own marker
+        if state.encoding == Some(sequence) {
+            state.encoding = None;
+        }
+        let result = match encoded {
+            Ok(encoded) => {
+                // A newer frame may have been cached meanwhile
+                if state.jpeg.as_ref().map_or(true, |(seq, _)| *seq < sequence) {
+                    state.jpeg = Some(encoded.clone());
+                }
+                Some(encoded)
+            }
+            Err(e) => {
+                warn!("Preview encode failed: {:#}", e);
+                None
+            }
+        };
+        drop(state);
+        self.updated.notify_all(); // Wakes clients waiting on this encode
+        result
+    }
+}
+
+fn encode_jpeg(frame: &ColorImage, quality: u8) -> Result<Vec<u8>> {
+    let rgb: Vec<u8> = frame
+        .pixels
+        .iter()
+        .flat_map(|c| [c.r(), c.g(), c.b()])
+        .collect();
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, quality.clamp(1, 100)).encode(
+        &rgb,
+        frame.size[0] as u32,
+        frame.size[1] as u32,
+        ExtendedColorType::Rgb8,
+    )?;
+    Ok(out)
+}
+
+pub struct PreviewServer {
+    feed: Arc<Feed>,
+    stop_signal: StopSignal,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PreviewServer {
+    pub fn start(config: &PreviewConfig) -> Result<Option<Self>> {
+        let Some(addr) = &config.http_addr else {
+            return Ok(None);
+        };
+        let listener = TcpListener::bind(addr)
+            .with_context(|| format!("Failed to bind preview endpoint {}", addr))?;
+        let listener = supervisor::async_listener(listener)?;
+        info!("Preview stream on http://{}/", addr);
+        let feed = Arc::new(Feed::default());
+        let stop_signal = StopSignal::new();
+        let (thread_feed, thread_stop) = (feed.clone(), stop_signal.clone());
+        let quality = config.quality;
+        let max_clients = config.max_clients.max(1);
+        let active_clients = Arc::new(AtomicUsize::new(0));
+        let frame_interval = Duration::from_secs_f32(1.0 / config.max_fps.max(1.0));
+        let thread = thread::spawn(move || {
+            supervisor::runtime().block_on(async {
+                loop {
+                    let (mut stream, peer) = tokio::select! {
+                        accepted = supervisor::accept(&listener) => match accepted {
+                            Ok(accepted) => accepted,
+                            Err(e) => {
+                                warn!("Preview accept failed: {}", e);
+                                continue;
+                            }
+                        },
+                        _ = thread_stop.stopped() => break,
+                    };
+                    let Some(slot) = ClientSlot::claim(&active_clients, max_clients) else {
+                        warn!("Preview client {} refused: {} connected", peer, max_clients);
+                        let _ = respond(
+                            &mut stream,
+                            "503 Service Unavailable",
+                            "text/plain",
+                            b"too many preview clients\n",
+                        );
+                        continue;
+                    };
+                    let (feed, stop) = (thread_feed.clone(), thread_stop.clone());
+                    thread::spawn(move || {
+                        let _slot = slot;
+                        let client = Client {
+                            feed: &feed,
+                            stop: &stop,
+                            quality,
+                            frame_interval,
+                        };
+                        if let Err(e) = client.serve(stream) {
+                            info!("Preview client {} left: {:#}", peer, e);
+                        }
+                    });
+                }
+            });
+            info!("Preview thread finished.");
+        });
+        Ok(Some(Self {
+            feed,
+            stop_signal,
+            thread: Some(thread),
+        }))
+    }
+
+    // Cheap: stores the Arc under a short lock; encoding happens on the client threads, outside
+    // it, and only when someone watches
+    pub fn publish(&self, frame: Arc<ColorImage>) {
+        let mut state = self.feed.state.lock().unwrap();
+        state.frame = Some(frame);
+        state.sequence += 1;
+        drop(state);
+        self.feed.updated.notify_all();
+    }
+
+    pub fn stop(&mut self) {
+        self.stop_signal.stop();
+        self.feed.updated.notify_all(); // Wakes clients waiting for a frame
+        if let Some(handle) = self.thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+// One of the `max_clients` connections; released when the client thread ends
+struct ClientSlot(Arc<AtomicUsize>);
+
+impl ClientSlot {
+    fn claim(active: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| (n < max).then_some(n + 1))
+            .ok()
+            .map(|_| Self(active.clone()))
+    }
+}
+
+impl Drop for ClientSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+struct Client<'a> {
+    feed: &'a Feed,
+    stop: &'a StopSignal,
+    quality: u8,
+    frame_interval: Duration,
+}
+
+impl Client<'_> {
+    fn serve(&self, mut stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(Duration::from_millis(500)))?;
+        let mut buf = [0u8; 1024];
+        let n = stream.read(&mut buf)?;
+        let request = String::from_utf8_lossy(&buf[..n]);
+        let path = request.split_whitespace().nth(1).unwrap_or("/");
+        match path {
+            "/" => respond(&mut stream, "200 OK", "text/html", INDEX_PAGE.as_bytes()),
+            "/snapshot.jpg" => match self.wait_for_frame(0) {
+                Some((_, jpeg)) => respond(&mut stream, "200 OK", "image/jpeg", &jpeg),
+                None => respond(
+                    &mut stream,
+                    "503 Service Unavailable",
+                    "text/plain",
+                    b"no frame\n",
+                ),
+            },
+            "/stream" => self.stream(stream),
+            _ => respond(&mut stream, "404 Not Found", "text/plain", b"not found\n"),
+        }
+    }
+
+    fn wait_for_frame(&self, after: u64) -> Option<(u64, Arc<Vec<u8>>)> {
+        while !self.stop.is_stopped() {
+            if let Some(frame) = self.feed.next_jpeg(after, self.quality) {
+                return Some(frame);
+            }
+        }
+        None
+    }
+
+    // multipart/x-mixed-replace: browsers and OBS browser sources show each part as it lands
+    fn stream(&self, mut stream: TcpStream) -> Result<()> {
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: multipart/x-mixed-replace; boundary={}\r\n\
+             Cache-Control: no-cache\r\nConnection: close\r\n\r\n",
+            BOUNDARY
+        )?;
+        let mut last_sequence = 0;
+        let mut last_sent: Option<Instant> = None;
+        loop {
+            // Rate cap first, so the frame sent afterwards is the freshest one
+            if let Some(at) = last_sent {
+                if self.stop.wait(self.frame_interval.saturating_sub(at.elapsed())) {
+                    return Ok(());
+                }
+            }
+            let Some((sequence, jpeg)) = self.wait_for_frame(last_sequence) else {
+                return Ok(());
+            };
+            write!(
+                stream,
+                "--{}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+                BOUNDARY,
+                jpeg.len()
+            )?;
+            stream.write_all(&jpeg)?;
+            stream.write_all(b"\r\n")?;
+            last_sequence = sequence;
+            last_sent = Some(Instant::now());
+        }
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8]) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+// src/profile.rs
+// Named operating profiles: one binary, tuned at launch for an unattended gallery install,
+// an operator-driven club set, or a streaming rig. A profile only sets starting values; every
+// setting it touches stays adjustable afterwards.
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppProfile {
+    Installation,
+    Vj,
+    Streaming,
+}
+
+// What a profile sets when applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProfileSettings {
+    pub track_zoom: bool,    // Follow tracked people instead of inferring on the full frame
+    pub masked_output: bool, // Black surround, so a projector lights only the objects
+    pub show_settings: bool, // Settings panel open at launch
+    pub fullscreen: bool,
+    pub auto_go_live: bool,        // Start the RTMP push right away when a URL is configured
+    pub watchdog_deadline_ms: u64, // Shorter = falls back to raw camera sooner
+}
+
+impl AppProfile {
+    pub const ALL: [AppProfile; 3] = [
+        AppProfile::Installation,
+        AppProfile::Vj,
+        AppProfile::Streaming,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AppProfile::Installation => "Installation",
+            AppProfile::Vj => "VJ",
+            AppProfile::Streaming => "Streaming",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            AppProfile::Installation => {
+                "Unattended: fullscreen, no settings panel, follows people, masked projection"
+            }
+            AppProfile::Vj => "Operator at the controls: settings panel open, full-frame tracking",
+            AppProfile::Streaming => "Goes live on launch; tolerates model stalls before raw video",
+        }
+    }
+
+    // Accepts the label in any case, e.g. `--profile vj`
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.label().eq_ignore_ascii_case(name))
+    }
+
+    pub fn settings(self) -> ProfileSettings {
+        match self {
+            AppProfile::Installation => ProfileSettings {
+                track_zoom: true,
+                masked_output: true,
+                show_settings: false,
+                fullscreen: true,
+                auto_go_live: false,
+                watchdog_deadline_ms: 300,
+            },
+            AppProfile::Vj => ProfileSettings {
+                track_zoom: false,
+                masked_output: false,
+                show_settings: true,
+                fullscreen: false,
+                auto_go_live: false,
+                watchdog_deadline_ms: 750,
+            },
+            AppProfile::Streaming => ProfileSettings {
+                track_zoom: false,
+                masked_output: false,
+                show_settings: true,
+                fullscreen: false,
+                auto_go_live: true,
+                watchdog_deadline_ms: 2000,
+            },
+        }
+    }
+}
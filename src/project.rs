@@ -0,0 +1,250 @@
+// src/project.rs
+// Show project files (.scb): the whole setup of a show in one JSON file, so it travels between
+// machines. Asset paths (LUT, sprites) are stored relative to the project when they live next to
+// it, and resolved against the project's directory on load.
+use anyhow::{Context, Result};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use crate::automation::Preset;
+use crate::cues::CueList;
+use crate::lut::LutSettings;
+use crate::sprite::SpriteSettings;
+use crate::warp::CornerPin;
+
+pub const PROJECT_EXTENSION: &str = "scb";
+pub const MAX_RECENT_PROJECTS: usize = 8;
+const PROJECT_VERSION: u32 = 1;
+
+// Device picks by name; a missing device falls back to the usual search
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectDevices {
+    pub audio_input: Option<String>,    // Capture switches to it on load
+    pub monitor_output: Option<String>, // None = the default output
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectLayout {
+    pub corner_pin: Option<CornerPin>,
+    pub show_settings: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub version: u32,
+    #[serde(default)]
+    pub devices: ProjectDevices,
+    pub preset: Preset, // Look, deck B, routing and the automation take
+    #[serde(default)]
+    pub cues: CueList,
+    #[serde(default)]
+    pub lut: LutSettings,
+    #[serde(default)]
+    pub sprite: SpriteSettings,
+    #[serde(default)]
+    pub layout: ProjectLayout,
+}
+
+// Relative to `base` when the asset is inside it; other paths are kept as they are
+fn relativize(path: &str, base: &Path) -> String {
+    match Path::new(path).strip_prefix(base) {
+        Ok(relative) if !path.is_empty() => relative.to_string_lossy().into_owned(),
+        _ => path.to_string(),
+    }
+}
+
+fn resolve(path: &str, base: &Path) -> String {
+    if path.is_empty() || Path::new(path).is_absolute() {
+        path.to_string()
+    } else {
+        base.join(path).to_string_lossy().into_owned()
+    }
+}
+
+// Directory the project's relative paths are anchored to
+fn base_dir(path: &Path) -> PathBuf {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    std::path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf())
+}
+
+impl Project {
+    pub fn new(
+        devices: ProjectDevices,
+        preset: Preset,
+        cues: CueList,
+        lut: LutSettings,
+        sprite: SpriteSettings,
+        layout: ProjectLayout,
+    ) -> Self {
+        Self {
+            version: PROJECT_VERSION,
+            devices,
+            preset,
+            cues,
+            lut,
+            sprite,
+            layout,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read project {}", path.display()))?;
+        let mut project: Project = serde_json::from_str(&source)
+            .with_context(|| format!("Failed to parse project {}", path.display()))?;
+        let base = base_dir(path);
+        project.lut.path = resolve(&project.lut.path, &base);
+        project.sprite.path = resolve(&project.sprite.path, &base);
+        project.cues.cues.sort_by_key(|c| c.number);
+        info!("Loaded project {}", path.display());
+        Ok(project)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let base = base_dir(path);
+        let mut project = self.clone();
+        for asset in [&mut project.lut.path, &mut project.sprite.path] {
+            let absolute = std::path::absolute(asset.as_str())
+                .map(|p| p.to_string_lossy().into_owned())
+                .unwrap_or_else(|_| asset.clone());
+            *asset = relativize(&absolute, &base);
+        }
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create project {}", path.display()))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &project)?;
+        info!("Saved project {}", path.display());
+        Ok(())
+    }
+}
+
+// Adds the `.scb` extension when the operator left it off
+pub fn with_extension(path: &str) -> PathBuf {
+    let path = PathBuf::from(path.trim());
+    if path.extension().is_some() {
+        path
+    } else {
+        path.with_extension(PROJECT_EXTENSION)
+    }
+}
+
+// Most recent first, without duplicates
+pub fn push_recent(recent: &mut Vec<String>, path: &Path) {
+    let path = path.display().to_string();
+    recent.retain(|p| *p != path);
+    recent.insert(0, path);
+    recent.truncate(MAX_RECENT_PROJECTS);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::ParamSnapshot;
+    use crate::deck::Look;
+    use crate::music::AnalysisChannel;
+    use crate::overlay::TextOverlaySettings;
+    use crate::segmentation::MAX_TRACKS;
+
+    fn project(lut: &Path, sprite: &Path) -> Project {
+        let params = ParamSnapshot {
+            look: Look::default(),
+            deck_b: Look::default(),
+            crossfader: 0.0,
+            slot_routing: [AnalysisChannel::Low; MAX_TRACKS],
+            text_overlay: TextOverlaySettings::default(),
+        };
+        Project::new(
+            ProjectDevices::default(),
+            Preset {
+                params,
+                automation: Vec::new(),
+            },
+            CueList::default(),
+            LutSettings {
+                path: lut.to_string_lossy().into_owned(),
+                strength: 0.5,
+            },
+            SpriteSettings {
+                path: sprite.to_string_lossy().into_owned(),
+                ..SpriteSettings::default()
+            },
+            ProjectLayout::default(),
+        )
+    }
+
+    #[test]
+    fn relativize_only_strips_paths_inside_the_base() {
+        let base = Path::new("/shows/tour");
+        let inside = Path::new("luts").join("grade.cube");
+        assert_eq!(
+            relativize("/shows/tour/luts/grade.cube", base),
+            inside.to_string_lossy()
+        );
+        assert_eq!(
+            relativize("/shows/other/grade.cube", base),
+            "/shows/other/grade.cube"
+        );
+        assert_eq!(
+            relativize("/shows/tour2/grade.cube", base),
+            "/shows/tour2/grade.cube"
+        );
+        assert_eq!(relativize("", base), "");
+    }
+
+    #[test]
+    fn resolve_anchors_relative_paths_at_the_base() {
+        let base = Path::new("/shows/tour");
+        let joined = base.join("luts/grade.cube");
+        assert_eq!(resolve("luts/grade.cube", base), joined.to_string_lossy());
+        assert_eq!(
+            resolve("/elsewhere/grade.cube", base),
+            "/elsewhere/grade.cube"
+        );
+        assert_eq!(resolve("", base), "");
+    }
+
+    #[test]
+    fn with_extension_adds_scb_only_when_missing() {
+        assert_eq!(with_extension("show"), PathBuf::from("show.scb"));
+        assert_eq!(with_extension("  show  "), PathBuf::from("show.scb"));
+        assert_eq!(with_extension("show.json"), PathBuf::from("show.json"));
+        assert_eq!(
+            with_extension("dir.v2/show"),
+            PathBuf::from("dir.v2/show.scb")
+        );
+    }
+
+    #[test]
+    fn save_stores_inside_assets_relative_and_load_resolves_them() {
+        let root = std::env::temp_dir().join(format!("sam_cam_bam_project_{}", std::process::id()));
+        let show = root.join("show");
+        std::fs::create_dir_all(show.join("luts")).unwrap();
+        let inside = show.join("luts").join("grade.cube");
+        let outside = root.join("shared.cube");
+        for lut in [&inside, &outside] {
+            std::fs::write(lut, "LUT_3D_SIZE 2\n").unwrap();
+        }
+        let file = show.join("set.scb");
+        let saved = project(&inside, &outside);
+        saved.save(&file).unwrap();
+
+        let raw: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+        let relative = Path::new("luts").join("grade.cube");
+        assert_eq!(raw["lut"]["path"], &*relative.to_string_lossy());
+        assert_eq!(raw["sprite"]["path"], &*outside.to_string_lossy());
+
+        let loaded = Project::load(&file).unwrap();
+        assert_eq!(Path::new(&loaded.lut.path), inside);
+        assert_eq!(Path::new(&loaded.sprite.path), outside);
+        assert_eq!(loaded.lut.strength, 0.5);
+        assert_eq!(loaded.version, PROJECT_VERSION);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
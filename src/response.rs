@@ -0,0 +1,200 @@
+// src/response.rs
+use serde::{Deserialize, Serialize};
+
+use crate::music::{AnalysisChannel, NUM_ANALYSIS_CHANNELS};
+
+const MIN_GAMMA: f32 = 0.1;
+const MAX_SPLINE_POINTS: usize = 8;
+
+// --- Maps a 0-1 intensity to perceived effect strength; shaped per analysis channel ---
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    Gamma(f32),  // < 1 lifts quiet passages, > 1 saves the effect for loud ones
+    SCurve(f32), // Contrast around 0.5; 0 = linear
+    // Control points (x, y) between the fixed (0, 0) and (1, 1) ends, sorted by x
+    Spline(Vec<(f32, f32)>),
+}
+
+impl ResponseCurve {
+    pub const KINDS: [&'static str; 4] = ["Linear", "Gamma", "S-curve", "Spline"];
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ResponseCurve::Linear => Self::KINDS[0],
+            ResponseCurve::Gamma(_) => Self::KINDS[1],
+            ResponseCurve::SCurve(_) => Self::KINDS[2],
+            ResponseCurve::Spline(_) => Self::KINDS[3],
+        }
+    }
+
+    // Default parameters for each kind
+    pub fn of_kind(kind: &str) -> Self {
+        match kind {
+            "Gamma" => ResponseCurve::Gamma(2.0),
+            "S-curve" => ResponseCurve::SCurve(0.5),
+            "Spline" => ResponseCurve::Spline(vec![(0.5, 0.5)]),
+            _ => ResponseCurve::Linear,
+        }
+    }
+
+    pub fn apply(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            ResponseCurve::Linear => x,
+            ResponseCurve::Gamma(g) => x.powf(g.max(MIN_GAMMA)),
+            ResponseCurve::SCurve(amount) => {
+                // Blend toward smootherstep, which flattens both ends and steepens the middle
+                let s = x * x * x * (x * (x * 6.0 - 15.0) + 10.0);
+                x + (s - x) * amount.clamp(0.0, 1.0)
+            }
+            ResponseCurve::Spline(points) => eval_spline(points, x),
+        }
+    }
+
+    // Keeps spline points inside the unit square, ordered and within the point budget
+    pub fn normalize(&mut self) {
+        if let ResponseCurve::Spline(points) = self {
+            for p in points.iter_mut() {
+                *p = (p.0.clamp(0.0, 1.0), p.1.clamp(0.0, 1.0));
+            }
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+            points.truncate(MAX_SPLINE_POINTS);
+        }
+    }
+
+    pub fn can_add_point(&self) -> bool {
+        matches!(self, ResponseCurve::Spline(p) if p.len() < MAX_SPLINE_POINTS)
+    }
+}
+
+// Monotone cubic (Fritsch-Carlson) through the end points and the control points, so a curve
+// drawn rising never overshoots into dips
+fn eval_spline(points: &[(f32, f32)], x: f32) -> f32 {
+    let mut knots: Vec<(f32, f32)> = Vec::with_capacity(points.len() + 2);
+    knots.push((0.0, 0.0));
+    knots.extend(points.iter().copied().filter(|p| p.0 > 0.0 && p.0 < 1.0));
+    knots.push((1.0, 1.0));
+    let n = knots.len();
+    let secants: Vec<f32> = knots
+        .windows(2)
+        .map(|w| (w[1].1 - w[0].1) / (w[1].0 - w[0].0).max(1e-6))
+        .collect();
+    let mut tangents = vec![0.0f32; n];
+    tangents[0] = secants[0];
+    tangents[n - 1] = secants[n - 2];
+    for i in 1..n - 1 {
+        tangents[i] = if secants[i - 1] * secants[i] <= 0.0 {
+            0.0
+        } else {
+            (secants[i - 1] + secants[i]) / 2.0
+        };
+    }
+    for i in 0..n - 1 {
+        if secants[i] == 0.0 {
+            tangents[i] = 0.0;
+            tangents[i + 1] = 0.0;
+            continue;
+        }
+        let (a, b) = (tangents[i] / secants[i], tangents[i + 1] / secants[i]);
+        let h = a * a + b * b;
+        if h > 9.0 {
+            let t = 3.0 / h.sqrt();
+            tangents[i] = t * a * secants[i];
+            tangents[i + 1] = t * b * secants[i];
+        }
+    }
+    let i = knots.iter().rposition(|k| k.0 <= x).unwrap_or(0).min(n - 2);
+    let ((x0, y0), (x1, y1)) = (knots[i], knots[i + 1]);
+    let h = (x1 - x0).max(1e-6);
+    let t = ((x - x0) / h).clamp(0.0, 1.0);
+    let (t2, t3) = (t * t, t * t * t);
+    let y = (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+        + (t3 - 2.0 * t2 + t) * h * tangents[i]
+        + (-2.0 * t3 + 3.0 * t2) * y1
+        + (t3 - t2) * h * tangents[i + 1];
+    y.clamp(0.0, 1.0)
+}
+
+// --- One curve per analysis channel, applied to the intensities right before the visuals ---
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseCurves {
+    curves: Vec<ResponseCurve>, // Indexed like the intensities; missing entries are linear
+}
+
+impl ResponseCurves {
+    pub fn get(&self, channel: AnalysisChannel) -> ResponseCurve {
+        self.curves.get(channel.index()).cloned().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, channel: AnalysisChannel, mut curve: ResponseCurve) {
+        curve.normalize();
+        if self.curves.len() < NUM_ANALYSIS_CHANNELS {
+            self.curves.resize(NUM_ANALYSIS_CHANNELS, ResponseCurve::Linear);
+        }
+        self.curves[channel.index()] = curve;
+    }
+
+    pub fn apply(&self, intensities: &mut [f32]) {
+        for (value, curve) in intensities.iter_mut().zip(&self.curves) {
+            if *curve != ResponseCurve::Linear {
+                *value = curve.apply(*value);
+            }
+        }
+    }
+}
+
+// --- Noise gate: below the floor a channel reads exactly zero, so room noise can't twitch the
+// visuals. Opens at `threshold`, closes below `threshold - hysteresis` ---
+pub const GATED_CHANNELS: [AnalysisChannel; 5] = [
+    AnalysisChannel::Low,
+    AnalysisChannel::Mid,
+    AnalysisChannel::High,
+    AnalysisChannel::Rms,
+    AnalysisChannel::Custom,
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GateSettings {
+    pub threshold: f32, // 0 = gate off
+    pub hysteresis: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NoiseGate {
+    settings: [GateSettings; GATED_CHANNELS.len()],
+    open: [bool; GATED_CHANNELS.len()],
+}
+
+impl NoiseGate {
+    pub fn set(&mut self, channel: AnalysisChannel, settings: GateSettings) {
+        if let Some(i) = GATED_CHANNELS.iter().position(|c| *c == channel) {
+            self.settings[i] = GateSettings {
+                threshold: settings.threshold.clamp(0.0, 1.0),
+                hysteresis: settings.hysteresis.clamp(0.0, settings.threshold.max(0.0)),
+            };
+        }
+    }
+
+    pub fn apply(&mut self, intensities: &mut [f32]) {
+        for (i, channel) in GATED_CHANNELS.iter().enumerate() {
+            let gate = self.settings[i];
+            let Some(value) = intensities.get_mut(channel.index()) else {
+                continue;
+            };
+            if gate.threshold <= 0.0 {
+                continue;
+            }
+            let open = &mut self.open[i];
+            if *open && *value < gate.threshold - gate.hysteresis {
+                *open = false;
+            } else if !*open && *value >= gate.threshold {
+                *open = true;
+            }
+            if !*open {
+                *value = 0.0;
+            }
+        }
+    }
+}
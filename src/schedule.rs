@@ -0,0 +1,183 @@
+// src/schedule.rs
+// Opening hours for long-running installations. Outside them the camera is released, outputs
+// stop and the window shows black; an optional nightly camera cycle clears drivers that drift
+// or wedge after days of continuous capture.
+use anyhow::{anyhow, Context, Result};
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+const TIME_FORMAT: &str = "%H:%M";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    pub enabled: bool,
+    pub open: String,  // Local "HH:MM"
+    pub close: String, // Earlier than `open` = open overnight; equal = open all day
+    // e.g. ["Mon"]; an overnight opening belongs to the day it starts on
+    pub closed_days: Vec<String>,
+    // Local "HH:MM" at which the camera is closed and reopened, daily
+    pub camera_cycle_at: Option<String>,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            open: "10:00".to_string(),
+            close: "18:00".to_string(),
+            closed_days: Vec::new(),
+            camera_cycle_at: None,
+        }
+    }
+}
+
+pub struct Scheduler {
+    open: NaiveTime,
+    close: NaiveTime,
+    closed_days: Vec<Weekday>,
+    camera_cycle_at: Option<NaiveTime>,
+    last_cycle: Option<NaiveDate>,
+}
+
+fn parse_time(field: &str, value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value, TIME_FORMAT)
+        .with_context(|| format!("schedule.{} must be HH:MM, got {:?}", field, value))
+}
+
+impl Scheduler {
+    pub fn new(config: &ScheduleConfig) -> Result<Self> {
+        let closed_days = config
+            .closed_days
+            .iter()
+            .map(|d| {
+                d.parse::<Weekday>()
+                    .map_err(|_| anyhow!("schedule.closed_days: unknown day {:?}", d))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let camera_cycle_at = config
+            .camera_cycle_at
+            .as_deref()
+            .map(|t| parse_time("camera_cycle_at", t))
+            .transpose()?;
+        // Launched after today's cycle time: the fresh camera counts as today's cycle
+        let now = Local::now().naive_local();
+        let last_cycle = camera_cycle_at
+            .filter(|&at| now.time() >= at)
+            .map(|_| now.date());
+        Ok(Self {
+            open: parse_time("open", &config.open)?,
+            close: parse_time("close", &config.close)?,
+            closed_days,
+            camera_cycle_at,
+            last_cycle,
+        })
+    }
+
+    fn day_open(&self, date: NaiveDate) -> bool {
+        !self.closed_days.contains(&date.weekday())
+    }
+
+    pub fn is_open(&self, now: NaiveDateTime) -> bool {
+        let (date, time) = (now.date(), now.time());
+        if self.open == self.close {
+            return self.day_open(date);
+        }
+        if self.open < self.close {
+            return self.day_open(date) && time >= self.open && time < self.close;
+        }
+        // Overnight: the tail after midnight belongs to yesterday's opening
+        let yesterday = date.pred_opt().unwrap_or(date);
+        (time >= self.open && self.day_open(date))
+            || (time < self.close && self.day_open(yesterday))
+    }
+
+    // True once per day, at the first check past the cycle time
+    pub fn camera_cycle_due(&mut self, now: NaiveDateTime) -> bool {
+        let Some(at) = self.camera_cycle_at else {
+            return false;
+        };
+        if now.time() < at || self.last_cycle == Some(now.date()) {
+            return false;
+        }
+        self.last_cycle = Some(now.date());
+        true
+    }
+
+    // Next opening time, for the standby screen
+    pub fn opens_at(&self) -> String {
+        self.open.format(TIME_FORMAT).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(open: &str, close: &str, closed_days: &[&str]) -> Scheduler {
+        Scheduler::new(&ScheduleConfig {
+            enabled: true,
+            open: open.to_string(),
+            close: close.to_string(),
+            closed_days: closed_days.iter().map(|d| d.to_string()).collect(),
+            camera_cycle_at: None,
+        })
+        .unwrap()
+    }
+
+    // 2024-01-01 was a Monday
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        let date = NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        date.and_time(NaiveTime::parse_from_str(time, TIME_FORMAT).unwrap())
+    }
+
+    #[test]
+    fn daytime_hours_close_at_the_closing_minute() {
+        let s = scheduler("10:00", "18:00", &[]);
+        assert!(!s.is_open(at(1, "09:59")));
+        assert!(s.is_open(at(1, "10:00")));
+        assert!(s.is_open(at(1, "17:59")));
+        assert!(!s.is_open(at(1, "18:00")));
+    }
+
+    #[test]
+    fn overnight_hours_span_midnight() {
+        let s = scheduler("20:00", "02:00", &[]);
+        assert!(s.is_open(at(1, "23:30")));
+        assert!(s.is_open(at(2, "01:59")));
+        assert!(!s.is_open(at(2, "02:00")));
+        assert!(!s.is_open(at(2, "19:59")));
+        assert!(s.is_open(at(2, "20:00")));
+    }
+
+    #[test]
+    fn closed_day_owns_its_overnight_tail() {
+        // Closed Monday: no opening Monday evening, so early Tuesday is closed too, while
+        // Sunday night's opening still runs into Monday morning
+        let s = scheduler("20:00", "02:00", &["Mon"]);
+        assert!(s.is_open(at(1, "01:00")));
+        assert!(!s.is_open(at(1, "21:00")));
+        assert!(!s.is_open(at(2, "01:00")));
+        assert!(s.is_open(at(2, "21:00")));
+    }
+
+    #[test]
+    fn equal_times_mean_all_day_except_closed_days() {
+        let s = scheduler("09:00", "09:00", &["Sun"]);
+        assert!(s.is_open(at(1, "03:00")));
+        assert!(s.is_open(at(6, "23:59")));
+        assert!(!s.is_open(at(7, "12:00")));
+    }
+
+    #[test]
+    fn bad_config_is_rejected() {
+        let mut config = ScheduleConfig {
+            open: "25:00".to_string(),
+            ..ScheduleConfig::default()
+        };
+        assert!(Scheduler::new(&config).is_err());
+        config.open = "10:00".to_string();
+        config.closed_days = vec!["Someday".to_string()];
+        assert!(Scheduler::new(&config).is_err());
+    }
+}
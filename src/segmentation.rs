@@ -1,41 +1,1030 @@
 // src/segmentation.rs
-use crossbeam_channel::{
-    Receiver as CrossbeamReceiver, Sender as CrossbeamSender, TryRecvError, TrySendError,
-};
-use egui::ColorImage;
-use image::{DynamicImage, RgbImage};
+use egui::{Color32, ColorImage};
+use image::{imageops, DynamicImage, GrayImage, RgbImage};
 use imageproc::rect::Rect;
 use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
 use rand::rngs::SmallRng;
 use rand::Rng;
 use rand::SeedableRng;
 use std::{
-    collections::{HashMap, HashSet}, 
+    borrow::Cow,
+    collections::{HashMap, HashSet, VecDeque}, 
     f32::consts::PI,                 
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+    path::{Path, PathBuf},
+    sync::Arc,
     thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
 
-use usls::{models::YOLO, Bbox, Nms, Options};
+use usls::{models::YOLO, Bbox, Nms, Options, Y};
 
 use crate::camera::CameraThreadMsg;
-use crate::visuals;
-#[derive(Debug, Clone)]
-pub enum UserInteractionSegMsg {}
+use crate::damage::{DamageTracker, FrameDamage};
+use crate::deck::{self, EffectLayer, Look, MAX_SLOT_LAYERS};
+use crate::diagnostics::{ModelReport, ModelReportSlot};
+use crate::ensemble::{EnsembleConfig, RefinedMasks, Refiner};
+use crate::lut::{LutSettings, LutStage};
+use crate::latency::IntensityOffset;
+use crate::keyed::{self, KeyedConfig, KeyedRecorder};
+use crate::mask_edit::{BrushStroke, MaskEdits};
+use crate::matting::{self, MatteSettings};
+use crate::metrics::{self, RateMeter};
+use crate::music::{
+    self, AnalysisChannel, BandMeter, DrumHit, METERED_CHANNELS, NUM_ANALYSIS_CHANNELS,
+};
+use crate::overlay::{HudSettings, LyricTrack, TextOverlay, TextOverlaySettings};
+use crate::pacing::{FramePacer, FramePacing, PacedFrame};
+use crate::paint_layer::{PaintLayer, PaintObject};
+use crate::pipeline::{LatestSlot, PolicySender, Receiver, TryRecvError};
+use crate::plugin::{self, PluginHost, PluginInfo};
+use crate::post::{self, BlackoutFader, BloomSettings, PostChain, ToneMapSettings, ToneMapper};
+use crate::response::{GateSettings, NoiseGate, ResponseCurve, ResponseCurves, GATED_CHANNELS};
+use crate::session::{ReplayStep, SessionRecorder};
+use crate::sprite::{SpriteOverlay, SpriteSettings};
+use crate::supervisor::{self, Heartbeat, StopSignal};
+use crate::tempo::{QuantizeGrid, SwitchQuantizer};
+use crate::visuals::{
+    self, BlendMode, DrumFxSettings, OutlineStyle, ShimmerSettings, StyleParams, VisualQuality,
+    VisualStyle,
+};
+use crate::warp::{CornerPin, CornerPinStage};
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum UserInteractionSegMsg {
+    // Feed a track slot from a specific analysis channel instead of its default band
+    SetSlotRouting {
+        slot: usize,
+        channel: AnalysisChannel,
+    },
+    // Start/stop dumping frames, intensities and interactions to disk (not itself recorded)
+    SetSessionRecording(bool),
+    // Restrict inference to a region of the frame (normalized coords), None = full frame
+    SetRoi(Option<NormalizedRect>),
+    // Infer only on windows around the selected (prompted/locked) slots while all are in view;
+    // takes precedence over the ROI. Unselected objects are refreshed by periodic full frames
+    SetTrackZoom(bool),
+    // Refresh masks from a heavier model on its own thread; None runs the fast model alone
+    SetEnsemble(Option<EnsembleConfig>),
+    // Swap the segmentation model for another FastSAM export; the old one stays on failure
+    LoadModel(PathBuf),
+    SetSlotStyle {
+        slot: usize,
+        style: VisualStyle,
+    },
+    // Tunables of the slot's style (see VisualStyle::params); ignored once the style changes
+    SetSlotParams {
+        slot: usize,
+        params: StyleParams,
+    },
+    // Extra styles drawn over the slot's base style, in order (at most MAX_SLOT_LAYERS)
+    SetSlotLayers {
+        slot: usize,
+        layers: Vec<EffectLayer>,
+    },
+    // Styles drawn over everything outside the objects, in order (at most MAX_SLOT_LAYERS)
+    SetBackgroundLayers(Vec<EffectLayer>),
+    // How every slot's outline combines with the video under it
+    SetOutlineBlend(BlendMode),
+    SetSlotOutline {
+        slot: usize,
+        outline: OutlineStyle,
+    },
+    SetShimmer(ShimmerSettings),
+    // Reactions to classified drum hits (kick ring, hi-hat sparkle)
+    SetDrumFx(DrumFxSettings),
+    SetBloom(BloomSettings),
+    SetPostChain(PostChain),
+    SetTextOverlay(TextOverlaySettings),
+    // Timed lyric lines (parsed LRC); their clock starts when received
+    SetLyrics(Option<LyricTrack>),
+    SetHud(HudSettings),
+    // Composite at full resolution but send the UI a frame scaled by this factor (0.25-1.0)
+    SetDisplayScale(f32),
+    // Diff frames against what the UI texture holds so only changed tiles are re-uploaded
+    SetPartialUploads(bool),
+    // Resolution the object effects render at inside each bbox
+    SetVisualQuality(VisualQuality),
+    // Leave object pixels untouched and send masks/contours for the UI to paint effects over
+    SetPaintLayer(bool),
+    // Guided-filter edge refinement: soft alpha mattes instead of the model's hard masks
+    SetMatte(MatteSettings),
+    // How often the model runs; frames in between reuse the last masks
+    SetInferenceCadence(InferenceCadence),
+    // Latest-wins vs. constant presentation intervals from capture timestamps
+    SetFramePacing(FramePacing),
+    // Shift the intensity stream (ms): positive = delay, negative = look-ahead
+    SetAudioOffset(i32),
+    // Reshape one analysis channel before it reaches the visuals
+    SetResponseCurve {
+        channel: AnalysisChannel,
+        curve: ResponseCurve,
+    },
+    // Record the graded visuals with mask alpha for keying; None stops. Not session-recorded
+    SetKeyedRecording(Option<KeyedConfig>),
+    // Black outside the tracked masks, so a projector lights only the objects
+    SetMaskedOutput(bool),
+    // Performance escape hatches: fade everything to black over `fade_secs`, and hold the
+    // current camera frame and audio analysis until released
+    SetBlackout {
+        engaged: bool,
+        fade_secs: f32,
+    },
+    SetFreeze(bool),
+    // Hold look changes (styles, layers, outlines, post chain, LUT) until the next
+    // beat/bar/phrase boundary; only while a tempo is known
+    SetQuantize(QuantizeGrid),
+    // Tap tempo in BPM; None = estimate from the Beat channel's onsets
+    SetTempo(Option<f32>),
+    // The bar starts now; quantize boundaries are counted from here
+    MarkDownbeat,
+    // Link session (tempo, beat at the time of sending); None = no session. Sent on change and
+    // drift only; quantize boundaries then follow the session's bar lines
+    SetLinkTempo(Option<(f32, f64)>),
+    // Show position chased from LTC/MTC (seconds at the time of sending); None = free-run.
+    // Sent on start, stop and drift only; the thread extrapolates in between
+    SetTimecode(Option<f64>),
+    // Keystone the final output onto a projection surface
+    SetCornerPin(CornerPin),
+    // Scales the level channels after shaping (1.0 = unchanged), e.g. from gamepad triggers
+    SetIntensityGain(f32),
+    // Zero a level channel while it sits under its noise floor
+    SetNoiseGate {
+        channel: AnalysisChannel,
+        gate: GateSettings,
+    },
+    // Render the next frame with both looks and send them back side by side
+    CompareLooks(Box<Look>, Box<Look>),
+    // Draw a plugin visual (by name) over a slot's object, None = no plugin
+    SetSlotPlugin {
+        slot: usize,
+        plugin: Option<String>,
+    },
+    SetPluginParam {
+        plugin: String,
+        param: usize,
+        value: f32,
+    },
+    // Brush correction for a slot's mask (paint in / erase), kept until cleared
+    PaintMask(BrushStroke),
+    ClearMaskEdits {
+        slot: usize,
+    },
+    // Pin a slot to the detection covering every positive point and no negative one
+    // (empty prompt releases the slot back to automatic assignment)
+    SelectObject {
+        slot: usize,
+        prompt: PointPrompt,
+    },
+    // Locked slots keep their current track (coasting when unmatched) and never take new ones
+    SetSlotLock {
+        slot: usize,
+        locked: bool,
+    },
+    // Manual correction of a slot's drawn bbox, as fractions of the frame size; accumulates
+    // across messages and follows the slot's object until cleared
+    AdjustTrack {
+        slot: usize,
+        dx: f32,
+        dy: f32,
+        dw: f32,
+        dh: f32,
+    },
+    ClearTrackAdjust {
+        slot: usize,
+    },
+    // Deck B look and the A/B fader (0 = all A, 1 = all B); the Set* style/post messages edit A
+    SetDeckB(Look),
+    SetCrossfader(f32),
+    // Master reveal: 0 = raw camera, 1 = full composite, 2 = effects only on black
+    SetMasterMix(f32),
+    // Animated PNG sequence / sprite sheet drawn on tracked objects (loaded by this thread)
+    SetSprite(SpriteSettings),
+    // .cube grade applied to the composited frame (loaded by this thread)
+    SetLut(LutSettings),
+    SetToneMap(ToneMapSettings),
+}
+
+impl UserInteractionSegMsg {
+    // Look changes that tempo quantization may hold back
+    fn is_look_switch(&self) -> bool {
+        matches!(
+            self,
+            UserInteractionSegMsg::SetSlotStyle { .. }
+                | UserInteractionSegMsg::SetSlotLayers { .. }
+                | UserInteractionSegMsg::SetBackgroundLayers(_)
+                | UserInteractionSegMsg::SetSlotOutline { .. }
+                | UserInteractionSegMsg::SetOutlineBlend(_)
+                | UserInteractionSegMsg::SetPostChain(_)
+                | UserInteractionSegMsg::SetLut(_)
+        )
+    }
+}
+
+// --- Click prompts for one slot, normalized frame coordinates ---
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PointPrompt {
+    pub positives: Vec<(f32, f32)>,
+    pub negatives: Vec<(f32, f32)>,
+}
+
+impl PointPrompt {
+    pub fn is_empty(&self) -> bool {
+        self.positives.is_empty() && self.negatives.is_empty()
+    }
+}
+
+// --- Resolution-independent rectangle, corners in [0, 1] ---
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NormalizedRect {
+    pub x0: f32,
+    pub y0: f32,
+    pub x1: f32,
+    pub y1: f32,
+}
+
+// Pixel-space crop rectangle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl PixelRect {
+    fn intersects(&self, other: &PixelRect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+
+    fn union(&self, other: &PixelRect) -> PixelRect {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        PixelRect {
+            x,
+            y,
+            width: (self.x + self.width).max(other.x + other.width) - x,
+            height: (self.y + self.height).max(other.y + other.height) - y,
+        }
+    }
+}
+
+impl NormalizedRect {
+    // Builds a rect from two arbitrary corners, clamped to the unit square
+    pub fn from_corners(a: (f32, f32), b: (f32, f32)) -> Self {
+        Self {
+            x0: a.0.min(b.0).clamp(0.0, 1.0),
+            y0: a.1.min(b.1).clamp(0.0, 1.0),
+            x1: a.0.max(b.0).clamp(0.0, 1.0),
+            y1: a.1.max(b.1).clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn to_pixel_rect(&self, frame_w: u32, frame_h: u32) -> Option<PixelRect> {
+        let x = (self.x0 * frame_w as f32).floor() as u32;
+        let y = (self.y0 * frame_h as f32).floor() as u32;
+        let x1 = ((self.x1 * frame_w as f32).ceil() as u32).min(frame_w);
+        let y1 = ((self.y1 * frame_h as f32).ceil() as u32).min(frame_h);
+        let width = x1.saturating_sub(x);
+        let height = y1.saturating_sub(y);
+        if width < MIN_ROI_SIZE || height < MIN_ROI_SIZE {
+            return None;
+        }
+        Some(PixelRect {
+            x,
+            y,
+            width,
+            height,
+        })
+    }
+}
+
+// --- Composited output, handed to the UI through a LatestSlot ---
+pub struct ComposedFrame {
+    pub image: Arc<ColorImage>,
+    pub damage: FrameDamage, // What changed since the previously published frame
+    pub paint: Option<Arc<PaintLayer>>, // Set in paint-layer mode; the UI draws the effects
+    pub meters: Option<[BandMeter; METERED_CHANNELS.len()]>, // Levels behind this frame
+}
 
 #[derive(Debug)]
 pub enum SegmentationThreadMsg {
-    Frame(Arc<ColorImage>),
     Error(String),
+    SessionRecording(Option<String>), // Directory being recorded to, None when stopped
+    KeyedRecording(Option<String>),   // File the keyed overlay goes to, None when stopped
+    ModelStatus(Option<String>),      // Splash text while loading/warming up, None once ready
+    ModelLoaded(PathBuf, Result<(), String>), // Answer to LoadModel
+    Comparison(Arc<ColorImage>, Arc<ColorImage>), // A/B compare renders of one frame
+    Plugins(Vec<PluginInfo>),                     // Visual plugins found at startup
 }
 
 // --- Constants ---
 pub const MAX_TRACKS: usize = 3; // Still represents Bass, Mid, High bands
 const IOU_THRESHOLD: f32 = 0.3; // Threshold for matching track
+const MODEL_CONF_THRESHOLD: f32 = 0.1; // Tracker's model keeps low-confidence boxes for pass two
+const TIMECODE_VISUAL_FPS: f64 = 30.0; // Animation frames per second of chased timecode
+const HIGH_CONF_THRESHOLD: f32 = 0.35; // Only these can start tracks or be prompted
+const LOW_CONF_IOU_THRESHOLD: f32 = 0.5; // Stricter overlap for low-confidence matches
+const MAX_MISSED_FRAMES: u32 = 15; // Lost tracks are kept this long before being dropped
+const MIN_ROI_SIZE: u32 = 32; // Smaller ROIs fall back to full-frame inference
+const FALLBACK_MASK_FEATHER: f32 = 0.2; // Edge fade of bbox-only detections, share of short side
+const WARM_UP_WIDTH: u32 = 640; // Dummy frame for the warm-up pass
+const WARM_UP_HEIGHT: u32 = 480;
+const COMPARE_SEED: u64 = 0xAB; // Both compare renders share this seed so only the look differs
+const SEEDED_FRAME_DT: f32 = 1.0 / 30.0; // Seeded runs: time step per source frame
+const GLOBAL_RNG_STREAM: u64 = u64::MAX; // Seeded stream for tracking, text and post effects
+const BACKGROUND_STATE: usize = MAX_TRACKS; // Index of the background in a deck's visual states
+const SHIMMER_BASS_THRESHOLD: f32 = 0.5; // Low band at or above this counts as sustained bass
+const ZOOM_MARGIN: f32 = 0.5; // Zoom windows grow by this share of the box on every side
+const ZOOM_MIN_WINDOW: f32 = 128.0; // Pixels; tiny objects still get some context
+const ZOOM_FULL_FRAME_EVERY: u64 = 30; // Frames between full-frame passes while zoomed
+const IDLE_WAIT: Duration = Duration::from_millis(250); // Longest block without a frame (heartbeat)
+const FUSE_IOU_THRESHOLD: f32 = 0.5; // Refined box overlap needed to lend a fast box its mask
+const KICK_RING_SECS: f32 = 0.45; // How long a kick's ring takes to expand and fade
+const HAT_SPARKLE_SECS: f32 = 0.25;
+
+// --- One model detection in full-frame coordinates ---
+// Masks are shared, so held and replayed detections don't copy a frame-sized buffer each
+#[derive(Debug, Clone)]
+pub(crate) struct Detection {
+    bbox: Bbox,
+    mask: Option<Arc<GrayImage>>,
+    contour: Vec<(f32, f32)>, // Model polygon exterior in frame pixels, empty if none
+}
+
+impl Detection {
+    // Low-confidence detections only extend existing tracks (ByteTrack second pass)
+    fn is_confident(&self) -> bool {
+        self.bbox.confidence() >= HIGH_CONF_THRESHOLD
+    }
+}
+
+// --- Inference cadence: run the model every frame, every Nth frame or every T ms ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InferenceCadence {
+    EveryFrame,
+    Frames(u32),
+    Millis(u32),
+}
+
+impl InferenceCadence {
+    fn is_due(self, frames_since: u64, since: Duration) -> bool {
+        match self {
+            InferenceCadence::EveryFrame => true,
+            InferenceCadence::Frames(n) => frames_since >= n.max(1) as u64,
+            InferenceCadence::Millis(ms) => since >= Duration::from_millis(ms as u64),
+        }
+    }
+
+    pub fn describe(self) -> String {
+        match self {
+            InferenceCadence::EveryFrame => "every frame".to_string(),
+            InferenceCadence::Frames(n) => format!("every {} frames", n),
+            InferenceCadence::Millis(ms) => format!("every {} ms", ms),
+        }
+    }
+}
+
+// --- Frame numbers the visuals and the inference cadence key on ---
+// Unseeded runs count loop passes and time the cadence by the wall clock. Seeded runs use the
+// source frame index for both, so idle waits and UI wakes between frames can't change a replay
+#[derive(Debug, Clone, Copy)]
+struct FrameClock {
+    seeded: bool,
+    passes: u64,
+}
+
+impl FrameClock {
+    fn new(seeded: bool) -> Self {
+        Self { seeded, passes: 0 }
+    }
+
+    // Once per loop pass, whether or not it composites a frame
+    fn tick(&mut self) {
+        self.passes += 1;
+    }
+
+    fn frame(&self, source_index: u64) -> u64 {
+        if self.seeded {
+            source_index
+        } else {
+            self.passes
+        }
+    }
+
+    // Whether `cadence` calls for inference at `frame`, given when the held result was taken
+    fn inference_due(
+        &self,
+        cadence: InferenceCadence,
+        frame: u64,
+        held_frame: u64,
+        held_at: Instant,
+    ) -> bool {
+        if !self.seeded {
+            return cadence.is_due(frame.saturating_sub(held_frame), held_at.elapsed());
+        }
+        if frame < held_frame {
+            return true; // The replay looped
+        }
+        let frames_since = frame - held_frame;
+        let since = Duration::from_secs_f64(frames_since as f64 * SEEDED_FRAME_DT as f64);
+        cadence.is_due(frames_since, since)
+    }
+}
+
+// --- Last inference result, replayed on the frames the cadence skips ---
+struct HeldDetections {
+    detections: Vec<Detection>,
+    info: HashMap<usize, (usize, f32)>, // det_idx -> (slot, animation phase), as drawn
+    velocities: HashMap<usize, (f32, f32)>, // det_idx -> pixels per second of capture time
+    frame: u64,
+    at: Instant,
+    captured: Instant,
+}
+
+impl HeldDetections {
+    // Drawn detections shifted along their velocity; masks move with their bbox
+    fn predict(&self, captured: Instant) -> Vec<Detection> {
+        let elapsed = captured.saturating_duration_since(self.captured).as_secs_f32();
+        self.detections
+            .iter()
+            .enumerate()
+            .map(|(i, d)| {
+                let (vx, vy) = self.velocities.get(&i).copied().unwrap_or((0.0, 0.0));
+                let (dx, dy) = ((vx * elapsed).round(), (vy * elapsed).round());
+                if !self.info.contains_key(&i) || (dx == 0.0 && dy == 0.0) {
+                    return d.clone();
+                }
+                let b = &d.bbox;
+                let mask = d.mask.as_deref().map(|m| {
+                    let mut shifted = GrayImage::new(m.width(), m.height());
+                    imageops::replace(&mut shifted, m, dx as i64, dy as i64);
+                    Arc::new(shifted)
+                });
+                Detection {
+                    bbox: b.clone().with_xyxy(
+                        b.xmin() + dx,
+                        b.ymin() + dy,
+                        b.xmax() + dx,
+                        b.ymax() + dy,
+                    ),
+                    mask,
+                    contour: d.contour.iter().map(|&(x, y)| (x + dx, y + dy)).collect(),
+                }
+            })
+            .collect()
+    }
+}
+
+// --- Ensemble: the refiner's masks, moved onto the fast model's boxes ---
+fn fuse_refined_masks(
+    detections: &mut [Detection],
+    refined: &RefinedMasks,
+    frame_size: (u32, u32),
+) {
+    for detection in detections.iter_mut() {
+        let best = refined
+            .detections
+            .iter()
+            .filter(|r| r.mask.as_ref().is_some_and(|m| m.dimensions() == frame_size))
+            .map(|r| (detection.bbox.iou(&r.bbox), r))
+            .filter(|(iou, _)| *iou > FUSE_IOU_THRESHOLD)
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        let Some((_, source)) = best else {
+            continue;
+        };
+        let Some(mask) = source.mask.as_deref() else {
+            continue;
+        };
+        // Scaled from the refined box onto the fast one, which has moved and resized since
+        let (from, to) = (bbox_rect(&source.bbox), bbox_rect(&detection.bbox));
+        detection.mask = Some(Arc::new(fit_mask(mask, from, to)));
+        detection.contour = fit_contour(&source.contour, from, to);
+    }
+}
+
+// --- Track-and-zoom: one window per selected slot, overlapping windows merged ---
+// None (= infer on the full frame/ROI) when nothing is selected or a selected slot was not found
+fn zoom_windows(
+    boxes: &[Option<Bbox>; MAX_TRACKS],
+    selected: impl Fn(usize) -> bool,
+    (frame_w, frame_h): (u32, u32),
+) -> Option<Vec<PixelRect>> {
+    let mut windows: Vec<PixelRect> = Vec::new();
+    for slot in (0..MAX_TRACKS).filter(|&s| selected(s)) {
+        let bbox = boxes[slot].as_ref()?;
+        let cx = bbox.xmin() + bbox.width() / 2.0;
+        let cy = bbox.ymin() + bbox.height() / 2.0;
+        let half_w = (bbox.width() * (0.5 + ZOOM_MARGIN)).max(ZOOM_MIN_WINDOW / 2.0);
+        let half_h = (bbox.height() * (0.5 + ZOOM_MARGIN)).max(ZOOM_MIN_WINDOW / 2.0);
+        let (fw, fh) = (frame_w as f32, frame_h as f32);
+        let mut window = NormalizedRect::from_corners(
+            ((cx - half_w) / fw, (cy - half_h) / fh),
+            ((cx + half_w) / fw, (cy + half_h) / fh),
+        )
+        .to_pixel_rect(frame_w, frame_h)?;
+        while let Some(i) = windows.iter().position(|w| w.intersects(&window)) {
+            window = window.union(&windows.swap_remove(i));
+        }
+        windows.push(window);
+    }
+    (!windows.is_empty()).then_some(windows)
+}
+
+// All windows in one forward pass, demultiplexed back into full-frame detections (None = the
+// whole frame). Exports with a fixed batch size of 1 reject this; they get a pass per window
+fn infer_windows(
+    model: &mut YOLO,
+    frame: &RgbImage,
+    windows: &[Option<PixelRect>],
+    batch: &mut bool,
+) -> anyhow::Result<Vec<Detection>> {
+    let frame_size = frame.dimensions();
+    let inputs: Vec<DynamicImage> = windows
+        .iter()
+        .map(|window| match window {
+            Some(r) => DynamicImage::ImageRgb8(
+                imageops::crop_imm(frame, r.x, r.y, r.width, r.height).to_image(),
+            ),
+            None => DynamicImage::ImageRgb8(frame.clone()),
+        })
+        .collect();
+    if *batch && inputs.len() > 1 {
+        match model.forward(&inputs) {
+            // One Y per input, in input order
+            Ok(ys) if ys.len() == inputs.len() => {
+                return Ok(windows
+                    .iter()
+                    .zip(&ys)
+                    .flat_map(|(window, y)| collect_detections(y, *window, frame_size))
+                    .collect());
+            }
+            Ok(ys) => warn!(
+                "Batched inference returned {} of {} results; using one pass per window",
+                ys.len(),
+                inputs.len()
+            ),
+            Err(e) => warn!("Batched inference failed; using one pass per window: {}", e),
+        }
+        *batch = false;
+    }
+    let mut detections = Vec::new();
+    for (window, input) in windows.iter().zip(&inputs) {
+        if let Some(y) = model.forward(std::slice::from_ref(input))?.first() {
+            detections.extend(collect_detections(y, *window, frame_size));
+        }
+    }
+    Ok(detections)
+}
+
+// Converts model output to full-frame detections, undoing the ROI crop offset if any
+pub(crate) fn collect_detections(
+    y: &Y,
+    roi: Option<PixelRect>,
+    frame_size: (u32, u32),
+) -> Vec<Detection> {
+    let bboxes = y.bboxes().unwrap_or_default();
+    let masks = y.masks().unwrap_or_default();
+    let polygons = y.polygons().unwrap_or_default();
+    bboxes
+        .iter()
+        .enumerate()
+        .map(|(i, bbox)| {
+            let mask = masks.get(i).map(|m| m.mask());
+            let (ox, oy) = roi.map_or((0.0, 0.0), |r| (r.x as f32, r.y as f32));
+            let contour = polygons
+                .get(i)
+                .map(|p| {
+                    p.polygon()
+                        .exterior()
+                        .coords()
+                        .map(|c| (c.x as f32 + ox, c.y as f32 + oy))
+                        .collect()
+                })
+                .unwrap_or_default();
+            match roi {
+                None => Detection {
+                    bbox: bbox.clone(),
+                    mask: mask.cloned().map(Arc::new),
+                    contour,
+                },
+                Some(r) => {
+                    let bbox = bbox.clone().with_xyxy(
+                        bbox.xmin() + ox,
+                        bbox.ymin() + oy,
+                        bbox.xmax() + ox,
+                        bbox.ymax() + oy,
+                    );
+                    let mask = mask.map(|m| {
+                        let mut full = GrayImage::new(frame_size.0, frame_size.1);
+                        imageops::replace(&mut full, m, r.x as i64, r.y as i64);
+                        Arc::new(full)
+                    });
+                    Detection {
+                        bbox,
+                        mask,
+                        contour,
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+// --- Burned-in diagnostics: rates, latencies, audio levels and per-slot track state ---
+fn stats_lines(
+    intensities: &[f32],
+    tracks: &[TrackedObject],
+    slot_locks: &[bool; MAX_TRACKS],
+) -> Vec<String> {
+    let registry = metrics::global();
+    let value = |name| registry.value(name).unwrap_or(0.0);
+    let bar = |channel: AnalysisChannel| {
+        let level = intensities.get(channel.index()).copied().unwrap_or(0.0);
+        let filled = (level.clamp(0.0, 1.0) * 8.0).round() as usize;
+        format!("{} {}{}", channel.label(), "#".repeat(filled), ".".repeat(8 - filled))
+    };
+    let slots: Vec<String> = (0..MAX_TRACKS)
+        .map(|slot| {
+            let state = match tracks.iter().find(|t| t.band_index == slot) {
+                Some(t) if t.missed_frames == 0 => "ON".to_string(),
+                Some(t) => format!("LOST {}", t.missed_frames),
+                None => "--".to_string(),
+            };
+            let lock = if slot_locks[slot] { "*" } else { "" };
+            format!("S{}{} {}", slot, lock, state)
+        })
+        .collect();
+    vec![
+        format!(
+            "SEG {:.1} fps  UI {:.1} fps",
+            value(metrics::SEG_FPS),
+            value(metrics::UI_FPS)
+        ),
+        format!(
+            "INF {:.1} ms  LOOP {:.1} ms",
+            value(metrics::INFERENCE_MS),
+            value(metrics::SEG_LOOP_MS)
+        ),
+        [AnalysisChannel::Low, AnalysisChannel::Mid, AnalysisChannel::High]
+            .map(bar)
+            .join("  "),
+        slots.join("  "),
+    ]
+}
+
+// Smallest detection whose mask covers all positives and none of the negatives
+fn pick_prompted_detection(
+    detections: &[Detection],
+    prompt: &PointPrompt,
+    exclude: &HashSet<usize>,
+) -> Option<usize> {
+    let covers = |mask: &GrayImage, (nx, ny): (f32, f32)| {
+        let x = (nx * mask.width() as f32) as u32;
+        let y = (ny * mask.height() as f32) as u32;
+        x < mask.width() && y < mask.height() && mask.get_pixel(x, y).0[0] >= 128
+    };
+    detections
+        .iter()
+        .enumerate()
+        .filter(|(i, d)| !exclude.contains(i) && d.is_confident())
+        .filter_map(|(i, d)| d.mask.as_deref().map(|m| (i, d, m)))
+        .filter(|(_, _, m)| prompt.positives.iter().all(|&p| covers(m, p)))
+        .filter(|(_, _, m)| !prompt.negatives.iter().any(|&p| covers(m, p)))
+        .min_by(|(_, a, _), (_, b, _)| {
+            let area = |d: &Detection| d.bbox.width() * d.bbox.height();
+            area(a).total_cmp(&area(b))
+        })
+        .map(|(i, _, _)| i)
+}
+
+// Converts into the persistent buffer when nobody else holds it, otherwise allocates a new one
+fn fill_color_image(buffer: &mut Option<Arc<ColorImage>>, image: &RgbImage) -> Arc<ColorImage> {
+    let size = [image.width() as usize, image.height() as usize];
+    let reusable = buffer
+        .as_mut()
+        .and_then(Arc::get_mut)
+        .filter(|c| c.size == size);
+    match reusable {
+        Some(color_image) => {
+            for (dst, src) in color_image.pixels.iter_mut().zip(image.as_raw().chunks_exact(3)) {
+                *dst = Color32::from_rgb(src[0], src[1], src[2]);
+            }
+        }
+        None => {
+            *buffer = Some(Arc::new(ColorImage::from_rgb(size, image.as_raw())));
+        }
+    }
+    buffer.clone().expect("buffer filled above")
+}
+
+// --- FastSAM model configuration shared by the app, self-test and benchmark ---
+pub fn default_model_options() -> anyhow::Result<Options> {
+    fastsam_options("models/FastSAM-s.onnx")
+}
+
+// A model file picked by the operator or the ensemble's heavier refiner, same configuration
+pub fn model_options_for(file: &Path) -> anyhow::Result<Options> {
+    fastsam_options(&file.to_string_lossy())
+}
+
+// Only the live tracker lowers the threshold: its second association pass extends tracks with
+// low-confidence boxes, while the self-test, benchmark and refiner keep the shared one
+fn tracker_options(options: Options) -> Options {
+    options.with_class_confs(&[MODEL_CONF_THRESHOLD])
+}
+
+// Builds and warms up a replacement model on its own thread, so the loop keeps running
+fn spawn_model_loader(file: PathBuf) -> JoinHandle<anyhow::Result<(YOLO, ModelReport)>> {
+    thread::spawn(move || {
+        let load_start = Instant::now();
+        let mut model = YOLO::new(tracker_options(model_options_for(&file)?))?;
+        let load = load_start.elapsed();
+        let warm_up = warm_up(&mut model);
+        Ok((model, ModelReport::Ready { load, warm_up }))
+    })
+}
+
+// The first forward pass compiles the execution provider's kernels (seconds on CoreML);
+// pay for it before the model sees a camera frame.
+// TensorRT engines are cached on disk by usls itself; CoreML has no cache to reuse
+fn warm_up(model: &mut YOLO) -> Result<Duration, String> {
+    let warm_up_start = Instant::now();
+    match model.forward(&[DynamicImage::ImageRgb8(RgbImage::new(WARM_UP_WIDTH, WARM_UP_HEIGHT))]) {
+        Ok(_) => {
+            let took = warm_up_start.elapsed();
+            info!("Model warm-up took {:.2?}", took);
+            Ok(took)
+        }
+        Err(e) => {
+            warn!("Model warm-up failed (continuing): {}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
+fn fastsam_options(model_file: &str) -> anyhow::Result<Options> {
+    let device_str = "mps";
+    let dtype_str = "fp16";
+    let device: usls::Device = device_str
+        .try_into()
+        .map_err(|e| anyhow::anyhow!("Bad device '{}': {}", device_str, e))?;
+    Ok(Options::fastsam_s()
+        .with_model_device(device)
+        .with_model_dtype(dtype_str.try_into().unwrap_or(usls::DType::Fp32))
+        .with_model_file(model_file)
+        .with_nc(1)
+        .with_class_names(&["object"])
+        .with_class_confs(&[HIGH_CONF_THRESHOLD])
+        .with_iou(0.45)
+        .with_find_contours(true)
+        // Dynamic batch, so track-and-zoom windows share one forward pass
+        .with_model_ixx(0, 0, (1, 1, MAX_TRACKS).into())
+        .commit()?)
+}
+
+// --- Deterministic mode: an independent RNG stream per (frame, stream) ---
+// Streams are the deck/slot pairs, one background per deck and GLOBAL_RNG_STREAM, so one slot's
+// randomness never depends on what else was drawn that frame and re-rendering a session gives
+// identical frames
+fn seeded_rng(seed: u64, frame: u64, stream: u64) -> SmallRng {
+    // SplitMix64 finalizer to decorrelate neighbouring frames/streams
+    let mut z = seed
+        ^ frame.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ stream.wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    SmallRng::seed_from_u64(z ^ (z >> 31))
+}
+
+// --- Time since the last kick and hi-hat, driving the drum reactions ---
+#[derive(Debug, Clone, Copy, Default)]
+struct DrumPulse {
+    kick_secs: Option<f32>,
+    hat_secs: Option<f32>,
+}
+
+impl DrumPulse {
+    fn trigger(&mut self, hit: DrumHit) {
+        match hit {
+            DrumHit::Kick => self.kick_secs = Some(0.0),
+            DrumHit::HiHat => self.hat_secs = Some(0.0),
+            DrumHit::Snare => {}
+        }
+    }
+
+    fn advance(&mut self, dt: f32) {
+        self.kick_secs = self.kick_secs.map(|s| s + dt).filter(|s| *s < KICK_RING_SECS);
+        self.hat_secs = self.hat_secs.map(|s| s + dt).filter(|s| *s < HAT_SPARKLE_SECS);
+    }
+
+    // Progress of each reaction, 0 at the hit to 1 once faded out
+    fn kick(&self) -> Option<f32> {
+        self.kick_secs.map(|s| s / KICK_RING_SECS)
+    }
+
+    fn hat(&self) -> Option<f32> {
+        self.hat_secs.map(|s| s / HAT_SPARKLE_SECS)
+    }
+}
+
+// --- One object as drawn this frame: slot, final bbox and mask (after edits/fallback) ---
+struct SceneObject<'a> {
+    slot: usize,
+    phase: f32,
+    bbox: Rect,
+    mask: Cow<'a, GrayImage>,
+    contour: Cow<'a, [(f32, f32)]>,
+}
+
+// --- Everything a look is rendered against; the live decks and the A/B compare share it ---
+struct Scene<'a> {
+    original: &'a RgbImage,
+    objects: &'a [SceneObject<'a>],
+    intensities: &'a [f32],
+    routing: &'a [AnalysisChannel; MAX_TRACKS],
+    bass_sustained_secs: f32,
+    drums: DrumPulse,
+    frame_count: u64,
+    seed: Option<(u64, u64)>, // (seed, source frame index) for the per-slot streams
+    quality: VisualQuality,
+    feathered: bool, // Masks are mattes: effects fade out across their soft edges
+}
+
+// Background layers of one look: each style over everything outside the objects, full frame
+fn draw_look_background(
+    target: &mut RgbImage,
+    scene: &Scene<'_>,
+    look: &Look,
+    deck: usize,
+    state: &mut visuals::SlotState,
+    rng: &mut SmallRng,
+) {
+    if look.background_layers.is_empty() {
+        return;
+    }
+    let (w, h) = target.dimensions();
+    let mut mask = post::mask_coverage((w, h), scene.objects.iter().map(|o| &*o.mask));
+    imageops::invert(&mut mask);
+    let full_frame = Rect::at(0, 0).of_size(w, h);
+    let stream = (2 * MAX_TRACKS + deck) as u64;
+    let mut background_rng = scene.seed.map(|(s, frame)| seeded_rng(s, frame, stream));
+    let beat = scene.intensities[AnalysisChannel::Beat.index()];
+    state.layers.resize_with(look.background_layers.len(), Default::default);
+    for (layer, state) in look.background_layers.iter().zip(state.layers.iter_mut()) {
+        state.beat = beat;
+        let channel = layer.driver.unwrap_or(AnalysisChannel::Rms);
+        visuals::draw_blended(
+            target,
+            scene.original,
+            &mask,
+            full_frame,
+            scene.feathered,
+            layer.style,
+            &layer.params,
+            (layer.blend, layer.opacity),
+            scene.quality,
+            scene.intensities[channel.index()],
+            scene.frame_count,
+            0.0,
+            state,
+            background_rng.as_mut().unwrap_or(&mut *rng),
+        );
+    }
+}
+
+// Background, then object visuals of one look over `target`; `deck` only selects the seeded RNG
+// streams
+fn draw_look_objects(
+    target: &mut RgbImage,
+    scene: &Scene<'_>,
+    look: &Look,
+    deck: usize,
+    states: &mut [visuals::SlotState; MAX_TRACKS + 1],
+    rng: &mut SmallRng,
+) {
+    draw_look_background(target, scene, look, deck, &mut states[BACKGROUND_STATE], rng);
+    let high = scene.intensities[AnalysisChannel::High.index()];
+    let beat = scene.intensities[AnalysisChannel::Beat.index()];
+    for object in scene.objects {
+        let slot = object.slot;
+        let channel = scene.routing[slot];
+        let stream = (deck * MAX_TRACKS + slot) as u64;
+        let mut slot_rng = scene.seed.map(|(s, frame)| seeded_rng(s, frame, stream));
+        states[slot].pan = scene.intensities[channel.pan_channel().index()] * 2.0 - 1.0;
+        states[slot].beat = beat;
+        visuals::draw_visuals(
+            target,
+            scene.original,
+            &object.mask,
+            object.bbox,
+            scene.feathered,
+            look.slot_styles[slot],
+            &look.slot_params[slot],
+            scene.quality,
+            scene.intensities[channel.index()],
+            scene.frame_count,
+            object.phase,
+            &mut states[slot],
+            slot_rng.as_mut().unwrap_or(&mut *rng),
+        );
+        // Effect chain: extra layers in order, each blended over the object as drawn so far
+        let layers = &look.slot_layers[slot];
+        let pan = states[slot].pan;
+        states[slot].layers.resize_with(layers.len(), Default::default);
+        for (layer, state) in layers.iter().zip(states[slot].layers.iter_mut()) {
+            state.pan = pan;
+            state.beat = beat;
+            let channel = layer.driver.unwrap_or(channel);
+            visuals::draw_blended(
+                target,
+                scene.original,
+                &object.mask,
+                object.bbox,
+                scene.feathered,
+                layer.style,
+                &layer.params,
+                (layer.blend, layer.opacity),
+                scene.quality,
+                scene.intensities[channel.index()],
+                scene.frame_count,
+                object.phase,
+                state,
+                slot_rng.as_mut().unwrap_or(&mut *rng),
+            );
+        }
+        visuals::draw_heat_shimmer(
+            target,
+            scene.original,
+            &object.mask,
+            object.bbox,
+            &look.shimmer,
+            look.shimmer.strength(scene.bass_sustained_secs),
+            scene.frame_count,
+        );
+        if let Some(progress) = scene.drums.kick().filter(|_| look.drum_fx.kick_ring) {
+            visuals::draw_kick_ring(target, object.bbox, progress);
+        }
+        if let Some(progress) = scene.drums.hat().filter(|_| look.drum_fx.hat_sparkle) {
+            let sparkle_rng = slot_rng.as_mut().unwrap_or(&mut *rng);
+            visuals::draw_hat_sparkle(target, &object.mask, object.bbox, progress, sparkle_rng);
+        }
+        visuals::draw_outline(
+            target,
+            &object.contour,
+            look.slot_outlines[slot],
+            look.outline_blend,
+            high,
+            scene.frame_count,
+            &mut states[slot].outline,
+        );
+    }
+}
+
+// Keyed overlay: each live deck's object visuals over a transparent (black) canvas, crossfaded
+// like the mix. Styles that sample the camera still read scene.original, but the camera itself
+// never lands in the layer, so the mixer keys only the effects. Own states, so the simulation
+// styles don't step twice per frame
+fn render_keyed_layer(
+    scene: &Scene<'_>,
+    decks: [Option<&Look>; 2],
+    crossfader: f32,
+    states: &mut [[visuals::SlotState; MAX_TRACKS + 1]; 2],
+    rng: &mut SmallRng,
+) -> RgbImage {
+    let (w, h) = scene.original.dimensions();
+    let mut layers = decks.map(|look| look.map(|_| RgbImage::new(w, h)));
+    for (deck, (layer, look)) in layers.iter_mut().zip(decks).enumerate() {
+        if let (Some(layer), Some(look)) = (layer, look) {
+            draw_look_objects(layer, scene, look, deck, &mut states[deck], rng);
+        }
+    }
+    match layers {
+        [Some(mut a), Some(b)] => {
+            deck::blend_into(&mut a, &b, crossfader);
+            a
+        }
+        [Some(a), None] | [None, Some(a)] => a,
+        [None, None] => RgbImage::new(w, h),
+    }
+}
+
+// Full-frame stage of one look (bloom, then the post chain)
+fn apply_look_post(target: &mut RgbImage, scene: &Scene<'_>, look: &Look, rng: &mut SmallRng) {
+    post::apply_bloom(
+        target,
+        &look.bloom,
+        scene.intensities[AnalysisChannel::Rms.index()],
+    );
+    post::apply_chain(
+        target,
+        &look.post_chain,
+        scene.intensities,
+        scene.frame_count,
+        rng,
+    );
+}
 
 // --- TrackedObject Struct --- (Persistent Assignment Version) ---
 #[derive(Debug, Clone)]
@@ -43,76 +1032,696 @@ struct TrackedObject {
     bbox: Bbox,           // Bbox from the *last known* frame it was seen in
     band_index: usize,    // 0, 1, or 2 (Bass, Mid, High) - Persists for the object's lifetime
     animation_phase: f32, // For visual effects
+    appearance: Option<Appearance>, // Running colour descriptor, keeps identity through occlusions
+    missed_frames: u32,   // Consecutive frames without a matching detection
+}
+
+// --- Operator correction of a slot's box, as fractions of the frame size ---
+// Applied where the object is drawn, so every new detection of the slot keeps the nudge
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct BboxAdjust {
+    dx: f32,
+    dy: f32,
+    dw: f32,
+    dh: f32,
+}
+
+impl BboxAdjust {
+    fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn apply(&self, rect: Rect, (frame_w, frame_h): (u32, u32)) -> Rect {
+        let (fw, fh) = (frame_w as f32, frame_h as f32);
+        let x = rect.left() as f32 + self.dx * fw;
+        let y = rect.top() as f32 + self.dy * fh;
+        let w = (rect.width() as f32 + self.dw * fw).max(1.0);
+        let h = (rect.height() as f32 + self.dh * fh).max(1.0);
+        Rect::at(x.round() as i32, y.round() as i32).of_size(w.round() as u32, h.round() as u32)
+    }
+}
+
+fn bbox_rect(b: &Bbox) -> Rect {
+    Rect::at(b.xmin() as i32, b.ymin() as i32)
+        .of_size(b.width().max(1.0) as u32, b.height().max(1.0) as u32)
+}
+
+// Moves and scales the part of a frame-sized mask inside `from` so that it fills `to`
+fn fit_mask(mask: &GrayImage, from: Rect, to: Rect) -> GrayImage {
+    let mut fitted = GrayImage::new(mask.width(), mask.height());
+    let (x, y) = (from.left().max(0) as u32, from.top().max(0) as u32);
+    let region = imageops::crop_imm(mask, x, y, from.width(), from.height()).to_image();
+    if region.width() == 0 || region.height() == 0 {
+        return fitted;
+    }
+    let scaled = if region.dimensions() == (to.width(), to.height()) {
+        region
+    } else {
+        imageops::resize(&region, to.width(), to.height(), imageops::FilterType::Triangle)
+    };
+    imageops::replace(&mut fitted, &scaled, to.left() as i64, to.top() as i64);
+    fitted
+}
+
+fn fit_contour(contour: &[(f32, f32)], from: Rect, to: Rect) -> Vec<(f32, f32)> {
+    let sx = to.width() as f32 / from.width() as f32;
+    let sy = to.height() as f32 / from.height() as f32;
+    contour
+        .iter()
+        .map(|&(x, y)| {
+            (
+                to.left() as f32 + (x - from.left() as f32) * sx,
+                to.top() as f32 + (y - from.top() as f32) * sy,
+            )
+        })
+        .collect()
+}
+
+// --- Re-ID descriptor: coarse RGB histogram of the masked pixels ---
+const HIST_BINS: usize = 4; // Per channel -> 64 bins
+const HIST_STRIDE: usize = 2; // Sample every other pixel/row; plenty for a histogram
+const APPEARANCE_WEIGHT: f32 = 0.5; // How much appearance distance costs against IoU
+const APPEARANCE_MOMENTUM: f32 = 0.8; // Keep this much of the old descriptor per update
+const REID_MAX_DISTANCE: f32 = 0.25; // Below this, a low-IoU match is still accepted
+const REID_MIN_IOU: f32 = 0.05;
+
+#[derive(Debug, Clone)]
+struct Appearance([f32; HIST_BINS * HIST_BINS * HIST_BINS]);
+
+impl Appearance {
+    fn from_masked(image: &RgbImage, mask: &GrayImage, bbox: &Bbox) -> Self {
+        let mut hist = [0.0f32; HIST_BINS * HIST_BINS * HIST_BINS];
+        let w = image.width().min(mask.width());
+        let h = image.height().min(mask.height());
+        let x0 = (bbox.xmin().max(0.0) as u32).min(w);
+        let y0 = (bbox.ymin().max(0.0) as u32).min(h);
+        let x1 = (bbox.xmax().max(0.0) as u32).min(w);
+        let y1 = (bbox.ymax().max(0.0) as u32).min(h);
+        let mut total = 0.0f32;
+        for y in (y0..y1).step_by(HIST_STRIDE) {
+            for x in (x0..x1).step_by(HIST_STRIDE) {
+                if mask.get_pixel(x, y).0[0] < 128 {
+                    continue;
+                }
+                let [r, g, b] = image.get_pixel(x, y).0;
+                let bin = |v: u8| v as usize * HIST_BINS / 256;
+                hist[(bin(r) * HIST_BINS + bin(g)) * HIST_BINS + bin(b)] += 1.0;
+                total += 1.0;
+            }
+        }
+        if total > 0.0 {
+            hist.iter_mut().for_each(|v| *v /= total);
+        }
+        Self(hist)
+    }
+
+    // Hellinger distance: 0 = identical, 1 = disjoint
+    fn distance(&self, other: &Self) -> f32 {
+        let bc: f32 = self.0.iter().zip(&other.0).map(|(a, b)| (a * b).sqrt()).sum();
+        (1.0 - bc.min(1.0)).sqrt()
+    }
+
+    fn blend(&self, new: &Self) -> Self {
+        let mut out = self.clone();
+        for (o, n) in out.0.iter_mut().zip(&new.0) {
+            *o = *o * APPEARANCE_MOMENTUM + n * (1.0 - APPEARANCE_MOMENTUM);
+        }
+        out
+    }
 }
 
 pub fn start_segmentation_thread(
-    ui_sender: CrossbeamSender<SegmentationThreadMsg>,
-    camera_receiver: CrossbeamReceiver<CameraThreadMsg>,
-    _user_interaction_receiver: CrossbeamReceiver<UserInteractionSegMsg>,
-    intensity_receiver: CrossbeamReceiver<Vec<f32>>,
-    stop_signal: Arc<AtomicBool>,
+    ui_sender: PolicySender<SegmentationThreadMsg>,
+    frame_slot: LatestSlot<ComposedFrame>,
+    camera_receiver: Receiver<CameraThreadMsg>,
+    user_interaction_receiver: Receiver<UserInteractionSegMsg>,
+    intensity_receiver: Receiver<Vec<f32>>,
+    drum_receiver: Receiver<DrumHit>,
+    stop_signal: StopSignal,
     ctx: egui::Context,
     model_options: Options,
+    model_report: ModelReportSlot,
+    seed: Option<u64>,
+    heartbeat: Heartbeat,
 ) -> JoinHandle<()> {
     info!("Spawning segmentation thread (Persistent Random Assignment - Individual Viz)");
     thread::spawn(move || {
         segmentation_loop(
             ui_sender,
+            frame_slot,
             camera_receiver,
+            user_interaction_receiver,
             intensity_receiver,
+            drum_receiver,
             stop_signal,
             ctx,
             model_options,
+            model_report,
+            seed,
+            heartbeat,
         );
     })
 }
 
 fn segmentation_loop(
-    ui_sender: CrossbeamSender<SegmentationThreadMsg>,
-    camera_receiver: CrossbeamReceiver<CameraThreadMsg>,
-    intensity_receiver: CrossbeamReceiver<Vec<f32>>,
-    stop_signal: Arc<AtomicBool>,
+    ui_sender: PolicySender<SegmentationThreadMsg>,
+    frame_slot: LatestSlot<ComposedFrame>,
+    camera_receiver: Receiver<CameraThreadMsg>,
+    user_interaction_receiver: Receiver<UserInteractionSegMsg>,
+    intensity_receiver: Receiver<Vec<f32>>,
+    drum_receiver: Receiver<DrumHit>,
+    stop_signal: StopSignal,
     ctx: egui::Context,
     model_options: Options,
+    model_report: ModelReportSlot,
+    seed: Option<u64>,
+    heartbeat: Heartbeat,
 ) {
     info!("Segmentation loop started (Persistent Random Assignment - Individual Viz).");
 
-    let mut model = match YOLO::new(model_options) {
+    let set_status = |status: Option<&str>| {
+        let _ = ui_sender.send(SegmentationThreadMsg::ModelStatus(status.map(String::from)));
+        ctx.request_repaint();
+    };
+    set_status(Some("Loading model…"));
+    let load_start = Instant::now();
+    let mut model = match YOLO::new(tracker_options(model_options)) {
         Ok(m) => m,
         Err(e) => {
             let emsg = format!("Model load failed: {}", e);
             error!("{}", emsg);
+            model_report.set(ModelReport::Failed(e.to_string()));
+            set_status(None);
             let _ = ui_sender.send(SegmentationThreadMsg::Error(emsg));
             ctx.request_repaint();
             return;
         }
     };
+    // Behind the splash instead of on the first camera frame
+    let load = load_start.elapsed();
+    set_status(Some("Warming up model…"));
+    let warm_up = warm_up(&mut model);
+    model_report.set(ModelReport::Ready { load, warm_up });
+    set_status(None);
 
     let mut tracked_objects: Vec<TrackedObject> = Vec::new();
     let mut processing_time = Duration::from_secs(0);
-    let mut current_band_intensities = vec![0.0f32; MAX_TRACKS];
+    let mut current_band_intensities = music::silent_intensities();
+    let mut band_meters = None;
+    let mut intensity_offset = IntensityOffset::default();
+    let mut response_curves = ResponseCurves::default();
+    let mut noise_gate = NoiseGate::default();
+    let mut intensity_gain = 1.0f32;
+    let mut corner_pin = CornerPin::default();
+    let mut masked_output = false;
+    let mut blackout = (false, 0.0f32); // (engaged, fade seconds)
+    let mut blackout_fader = BlackoutFader::default();
+    let mut frozen_frame: Option<Arc<RgbImage>> = None;
+    let mut frozen_intensities: Option<Vec<f32>> = None;
+    let mut quantizer: SwitchQuantizer<UserInteractionSegMsg> = SwitchQuantizer::default();
+    // Held look changes whose boundary has come, handled ahead of new messages
+    let mut released_switches: Vec<UserInteractionSegMsg> = Vec::new();
+    let mut woken_interaction: Option<UserInteractionSegMsg> = None; // Received by the idle wait
+
+    // Seeded replays: steps queued in recorded order, and the recorded clock they run on
+    let replay_epoch = Instant::now();
+    let mut replay_steps: VecDeque<ReplayStep> = VecDeque::new();
+    let mut replayed_interactions: VecDeque<UserInteractionSegMsg> = VecDeque::new();
+    let mut replay_intensities: Option<Vec<f32>> = None;
+    let mut replay_frame: Option<PacedFrame> = None;
+    let mut source_now: Option<Instant> = None;
+    let mut timecode_anchor: Option<(f64, Instant)> = None;
+    let mut keyed_config: Option<KeyedConfig> = None;
+    let mut keyed_recorder: Option<KeyedRecorder> = None;
+    let mut corner_pin_stage = CornerPinStage::default();
+    let mut slot_routing: [AnalysisChannel; MAX_TRACKS] =
+        std::array::from_fn(AnalysisChannel::default_for_slot);
     let mut rng = SmallRng::from_rng(&mut rand::thread_rng()); 
-    let mut frame_count: u64 = 0;
+    if let Some(seed) = seed {
+        info!("Deterministic mode: seed {}", seed);
+    }
+    let mut frame_clock = FrameClock::new(seed.is_some());
+    let mut seg_rate = RateMeter::new(metrics::SEG_FPS);
+    let mut session_recorder: Option<SessionRecorder> = None;
+    let mut roi: Option<NormalizedRect> = None;
+    let mut deck_a = Look::default();
+    let mut deck_b = Look::default();
+    let mut crossfader = 0.0f32;
+    let mut master_mix = 1.0f32;
+    let mut text_overlay = TextOverlay::new();
+    let mut text_settings = TextOverlaySettings::default();
+    let mut hud_settings = HudSettings::default();
+    let mut sprite_overlay = SpriteOverlay::default();
+    let mut sprite_settings = SpriteSettings::default();
+    let mut lut_stage = LutStage::default();
+    let mut tone_mapper = ToneMapper::default();
+    let mut tone_settings = ToneMapSettings::default();
+    let mut last_frame_tick = Instant::now();
+    // Seeded runs key everything on the source's frame index, so dropped or late frames
+    // don't change what the others render
+    let mut last_index: Option<u64> = None;
+    // Seconds the Low band has stayed above SHIMMER_BASS_THRESHOLD (decays twice as fast)
+    let mut bass_sustained_secs = 0.0f32;
+    let mut drum_pulse = DrumPulse::default();
+    let mut display_scale = 1.0f32;
+    let mut mask_edits: [MaskEdits; MAX_TRACKS] = std::array::from_fn(|_| MaskEdits::default());
+    let mut prompts: [PointPrompt; MAX_TRACKS] = std::array::from_fn(|_| PointPrompt::default());
+    let mut prompt_phases = [0.0f32; MAX_TRACKS];
+    // Simulation styles keep state per deck and slot, plus the deck's background
+    let mut visual_states: [[visuals::SlotState; MAX_TRACKS + 1]; 2] = Default::default();
+    let mut keyed_states: [[visuals::SlotState; MAX_TRACKS + 1]; 2] = Default::default();
+    let mut compare_request: Option<(Look, Look)> = None;
+    let mut plugin_host = PluginHost::discover(Path::new(plugin::PLUGIN_DIR));
+    let mut slot_plugins: [Option<usize>; MAX_TRACKS] = [None; MAX_TRACKS];
+    let _ = ui_sender.send(SegmentationThreadMsg::Plugins(plugin_host.infos()));
+    let mut slot_locks = [false; MAX_TRACKS];
+    let mut track_adjusts = [BboxAdjust::default(); MAX_TRACKS];
+    let mut cadence = InferenceCadence::EveryFrame;
+    let mut pacing = FramePacing::default();
+    let mut pacer = FramePacer::default();
+    let mut partial_uploads = false;
+    let mut visual_quality = VisualQuality::Full;
+    let mut paint_layer = false;
+    let mut matte = MatteSettings::default();
+    let mut damage_tracker = DamageTracker::default();
+    let mut held_detections: Option<HeldDetections> = None;
+    // Unselected objects as the last full-frame pass drew them: (detection, slot, phase)
+    let mut unzoomed_objects: Vec<(Detection, usize, f32)> = Vec::new();
+    let mut track_zoom = false;
+    let mut refiner: Option<Refiner> = None;
+    // Model picked by the operator, loading; the current one keeps running until it is ready
+    let mut model_loader: Option<(PathBuf, JoinHandle<anyhow::Result<(YOLO, ModelReport)>>)> =
+        None;
+    let mut batch_inference = true; // Cleared if the model rejects multi-image batches
+    let mut last_full_frame: u64 = 0;
+    // Latest box of each selected (prompted or locked) slot; None = not found last inference
+    let mut selected_boxes: [Option<Bbox>; MAX_TRACKS] = std::array::from_fn(|_| None);
+    // Reused across frames once the UI has released its reference
+    let mut color_buffer: Option<Arc<ColorImage>> = None;
 
-    while !stop_signal.load(Ordering::Relaxed) {
-        frame_count += 1;
-        // Interactions Removed
-        // Receive Frame
-        let mut latest_frame_arc: Option<Arc<RgbImage>> = None;
+    while !stop_signal.is_stopped() {
+        heartbeat.beat();
+        frame_clock.tick();
+        if model_loader.as_ref().is_some_and(|(_, h)| h.is_finished()) {
+            let (file, handle) = model_loader.take().expect("checked above");
+            let loaded = handle
+                .join()
+                .unwrap_or_else(|_| Err(anyhow::anyhow!("loader thread panicked")));
+            let result = match loaded {
+                Ok((m, report)) => {
+                    info!("Segmentation model switched to {}", file.display());
+                    model = m;
+                    model_report.set(report);
+                    batch_inference = true; // The new model gets its own chance at batches
+                    Ok(())
+                }
+                Err(e) => {
+                    let emsg = format!("Model {} failed to load: {:#}", file.display(), e);
+                    error!("{}", emsg);
+                    Err(emsg)
+                }
+            };
+            let _ = ui_sender.send(SegmentationThreadMsg::ModelLoaded(file, result));
+            set_status(None);
+        }
+        // A seeded replay renders one step per pass, at its recorded time, with its recorded inputs
+        if let Some(step) = replay_steps.pop_front() {
+            let captured = replay_epoch + Duration::from_millis(step.t_ms);
+            source_now = Some(captured);
+            replayed_interactions.extend(step.interactions);
+            replay_intensities = step.intensities;
+            replay_frame = Some(PacedFrame {
+                frame: step.frame,
+                captured,
+                index: step.index,
+                skipped: 0,
+            });
+        }
+        let now = source_now.unwrap_or_else(Instant::now);
+        // Receive Interactions
+        released_switches.extend(quantizer.release(now));
+        released_switches.reverse();
+        loop {
+            let (next, released) = match released_switches.pop() {
+                Some(msg) => (Ok(msg), true),
+                None => match woken_interaction.take() {
+                    Some(msg) => (Ok(msg), false),
+                    None => match replayed_interactions.pop_front() {
+                        Some(msg) => (Ok(msg), false),
+                        None => (user_interaction_receiver.try_recv(), false),
+                    },
+                },
+            };
+            match next {
+                Ok(UserInteractionSegMsg::SetSessionRecording(enable)) => {
+                    if enable && session_recorder.is_none() {
+                        match SessionRecorder::start() {
+                            Ok(r) => {
+                                let dir = r.dir().display().to_string();
+                                session_recorder = Some(r);
+                                let _ = ui_sender
+                                    .send(SegmentationThreadMsg::SessionRecording(Some(dir)));
+                            }
+                            Err(e) => {
+                                let emsg = format!("Session recording failed: {:#}", e);
+                                error!("{}", emsg);
+                                let _ = ui_sender.send(SegmentationThreadMsg::Error(emsg));
+                            }
+                        }
+                    } else if !enable {
+                        if let Some(r) = session_recorder.take() {
+                            r.finish();
+                        }
+                        let _ = ui_sender.send(SegmentationThreadMsg::SessionRecording(None));
+                    }
+                    ctx.request_repaint();
+                }
+                Ok(UserInteractionSegMsg::SetKeyedRecording(config)) => {
+                    // Started lazily on the next frame, once its size is known
+                    keyed_config = config;
+                    if keyed_config.is_none() {
+                        if let Some(r) = keyed_recorder.take() {
+                            drop(r.finish()); // ffmpeg finalizes in the background
+                        }
+                        let _ = ui_sender.send(SegmentationThreadMsg::KeyedRecording(None));
+                    }
+                    ctx.request_repaint();
+                }
+                Ok(msg) if !released && msg.is_look_switch() && quantizer.active() => {
+                    if let Some(r) = &session_recorder {
+                        r.record_interaction(&msg);
+                    }
+                    debug!("Holding look change for the next {:?}", quantizer.grid);
+                    quantizer.defer(msg, now);
+                }
+                Ok(msg) => {
+                    // Held changes were recorded when they arrived
+                    if let Some(r) = session_recorder.as_ref().filter(|_| !released) {
+                        r.record_interaction(&msg);
+                    }
+                    match msg {
+                        UserInteractionSegMsg::SetSlotRouting { slot, channel } => {
+                            if let Some(route) = slot_routing.get_mut(slot) {
+                                info!("Slot {} now driven by {} channel", slot, channel.label());
+                                *route = channel;
+                            }
+                        }
+                        UserInteractionSegMsg::SetRoi(r) => {
+                            info!("Inference ROI set to {:?}", r);
+                            roi = r;
+                        }
+                        UserInteractionSegMsg::SetTrackZoom(enabled) => {
+                            info!("Track-and-zoom: {}", enabled);
+                            track_zoom = enabled;
+                        }
+                        UserInteractionSegMsg::SetEnsemble(config) => {
+                            refiner = None; // Dropping the handle ends the old thread
+                            let config = config.and_then(|c| c.model_file.clone().map(|f| (c, f)));
+                            match config {
+                                Some((config, file)) => match model_options_for(&file) {
+                                    Ok(options) => {
+                                        info!("Ensemble: refining masks with {}", file.display());
+                                        refiner = Some(Refiner::start(
+                                            options,
+                                            config.refresh_hz,
+                                            stop_signal.clone(),
+                                        ));
+                                    }
+                                    Err(e) => {
+                                        let emsg = format!("Refiner model failed: {:#}", e);
+                                        error!("{}", emsg);
+                                        let _ = ui_sender.send(SegmentationThreadMsg::Error(emsg));
+                                    }
+                                },
+                                None => info!("Ensemble: off"),
+                            }
+                        }
+                        UserInteractionSegMsg::LoadModel(file) => {
+                            // A newer pick supersedes one still loading; that thread is left
+                            // to finish and its model dropped
+                            set_status(Some("Loading model…"));
+                            model_loader = Some((file.clone(), spawn_model_loader(file)));
+                        }
+                        UserInteractionSegMsg::SetSlotStyle { slot, style } => {
+                            if let Some(s) = deck_a.slot_styles.get_mut(slot) {
+                                info!("Slot {} style set to {}", slot, style.label());
+                                *s = style;
+                            }
+                        }
+                        UserInteractionSegMsg::SetSlotParams { slot, params } => {
+                            if let Some(p) = deck_a.slot_params.get_mut(slot) {
+                                *p = params;
+                            }
+                        }
+                        UserInteractionSegMsg::SetSlotLayers { slot, mut layers } => {
+                            if let Some(l) = deck_a.slot_layers.get_mut(slot) {
+                                layers.truncate(MAX_SLOT_LAYERS);
+                                info!("Slot {} has {} effect layer(s)", slot, layers.len());
+                                *l = layers;
+                            }
+                        }
+                        UserInteractionSegMsg::SetBackgroundLayers(mut layers) => {
+                            layers.truncate(MAX_SLOT_LAYERS);
+                            info!("Background has {} effect layer(s)", layers.len());
+                            deck_a.background_layers = layers;
+                        }
+                        UserInteractionSegMsg::SetSlotOutline { slot, outline } => {
+                            if let Some(o) = deck_a.slot_outlines.get_mut(slot) {
+                                info!("Slot {} outline set to {}", slot, outline.label());
+                                *o = outline;
+                            }
+                        }
+                        UserInteractionSegMsg::SetOutlineBlend(blend) => {
+                            info!("Outline blend: {}", blend.label());
+                            deck_a.outline_blend = blend;
+                        }
+                        UserInteractionSegMsg::SetShimmer(settings) => {
+                            deck_a.shimmer = settings;
+                        }
+                        UserInteractionSegMsg::SetDrumFx(settings) => {
+                            deck_a.drum_fx = settings;
+                        }
+                        UserInteractionSegMsg::SetBloom(settings) => {
+                            deck_a.bloom = settings;
+                        }
+                        UserInteractionSegMsg::SetPostChain(chain) => {
+                            deck_a.post_chain = chain;
+                        }
+                        UserInteractionSegMsg::SetTextOverlay(settings) => {
+                            text_settings = settings;
+                        }
+                        UserInteractionSegMsg::SetLyrics(lyrics) => {
+                            info!(
+                                "Lyrics {}",
+                                lyrics.as_ref().map_or("cleared".to_string(), |l| {
+                                    format!("loaded ({} lines)", l.len())
+                                })
+                            );
+                            text_overlay.set_lyrics(lyrics);
+                        }
+                        UserInteractionSegMsg::SetHud(settings) => {
+                            hud_settings = settings;
+                        }
+                        UserInteractionSegMsg::SetDisplayScale(scale) => {
+                            display_scale = scale.clamp(0.25, 1.0);
+                        }
+                        UserInteractionSegMsg::CompareLooks(a, b) => {
+                            compare_request = Some((*a, *b));
+                        }
+                        UserInteractionSegMsg::SetSlotPlugin { slot, plugin } => {
+                            if let Some(p) = slot_plugins.get_mut(slot) {
+                                *p = plugin.as_deref().and_then(|n| plugin_host.index_of(n));
+                                if plugin.is_some() && p.is_none() {
+                                    warn!("Slot {}: plugin {:?} is not loaded", slot, plugin);
+                                }
+                            }
+                        }
+                        UserInteractionSegMsg::SetPluginParam { plugin, param, value } => {
+                            if let Some(index) = plugin_host.index_of(&plugin) {
+                                plugin_host.set_param(index, param, value);
+                            }
+                        }
+                        UserInteractionSegMsg::SetResponseCurve { channel, curve } => {
+                            info!("{} response: {}", channel.label(), curve.kind());
+                            response_curves.set(channel, curve);
+                        }
+                        UserInteractionSegMsg::SetTimecode(secs) => {
+                            timecode_anchor = secs.map(|s| (s, now));
+                        }
+                        UserInteractionSegMsg::SetMaskedOutput(enabled) => {
+                            info!("Masked output: {}", enabled);
+                            masked_output = enabled;
+                        }
+                        UserInteractionSegMsg::SetBlackout { engaged, fade_secs } => {
+                            info!("Blackout: {} ({:.1}s fade)", engaged, fade_secs);
+                            blackout = (engaged, fade_secs);
+                        }
+                        UserInteractionSegMsg::SetFreeze(enabled) => {
+                            info!("Freeze: {}", enabled);
+                            // The frame is taken at the next paced frame; the analysis right now
+                            frozen_frame = None;
+                            frozen_intensities = enabled.then(|| current_band_intensities.clone());
+                        }
+                        UserInteractionSegMsg::SetQuantize(grid) => {
+                            info!("Look changes quantized to: {}", grid.label());
+                            quantizer.grid = grid;
+                        }
+                        UserInteractionSegMsg::SetTempo(bpm) => {
+                            info!("Tempo: {:?} BPM", bpm);
+                            quantizer.clock.set_bpm(bpm);
+                        }
+                        UserInteractionSegMsg::MarkDownbeat => {
+                            info!("Downbeat marked");
+                            quantizer.clock.mark_downbeat(now);
+                        }
+                        UserInteractionSegMsg::SetLinkTempo(link) => {
+                            quantizer.clock.set_link(link, now);
+                        }
+                        UserInteractionSegMsg::SetCornerPin(pin) => {
+                            corner_pin = pin;
+                        }
+                        UserInteractionSegMsg::SetNoiseGate { channel, gate } => {
+                            info!(
+                                "{} gate: threshold {:.2}, hysteresis {:.2}",
+                                channel.label(),
+                                gate.threshold,
+                                gate.hysteresis
+                            );
+                            noise_gate.set(channel, gate);
+                        }
+                        UserInteractionSegMsg::SetIntensityGain(gain) => {
+                            intensity_gain = gain.max(0.0);
+                        }
+                        UserInteractionSegMsg::SetAudioOffset(ms) => {
+                            info!("Audio offset: {} ms", ms);
+                            intensity_offset.set_offset_ms(ms);
+                        }
+                        UserInteractionSegMsg::PaintMask(stroke) => {
+                            if let Some(edits) = mask_edits.get_mut(stroke.slot) {
+                                edits.push(stroke);
+                            }
+                        }
+                        UserInteractionSegMsg::ClearMaskEdits { slot } => {
+                            if let Some(edits) = mask_edits.get_mut(slot) {
+                                info!("Slot {} mask edits cleared", slot);
+                                edits.clear();
+                            }
+                        }
+                        UserInteractionSegMsg::SelectObject { slot, prompt } => {
+                            if let Some(p) = prompts.get_mut(slot) {
+                                info!(
+                                    "Slot {} prompt: {} positive, {} negative points",
+                                    slot,
+                                    prompt.positives.len(),
+                                    prompt.negatives.len()
+                                );
+                                *p = prompt;
+                            }
+                        }
+                        UserInteractionSegMsg::SetSlotLock { slot, locked } => {
+                            if let Some(lock) = slot_locks.get_mut(slot) {
+                                info!("Slot {} {}", slot, if locked { "locked" } else { "unlocked" });
+                                *lock = locked;
+                            }
+                        }
+                        UserInteractionSegMsg::AdjustTrack {
+                            slot,
+                            dx,
+                            dy,
+                            dw,
+                            dh,
+                        } => {
+                            if let Some(adjust) = track_adjusts.get_mut(slot) {
+                                adjust.dx += dx;
+                                adjust.dy += dy;
+                                adjust.dw += dw;
+                                adjust.dh += dh;
+                            }
+                        }
+                        UserInteractionSegMsg::ClearTrackAdjust { slot } => {
+                            if let Some(adjust) = track_adjusts.get_mut(slot) {
+                                *adjust = BboxAdjust::default();
+                            }
+                        }
+                        UserInteractionSegMsg::SetDeckB(look) => {
+                            deck_b = look;
+                        }
+                        UserInteractionSegMsg::SetCrossfader(value) => {
+                            crossfader = value.clamp(0.0, 1.0);
+                        }
+                        UserInteractionSegMsg::SetMasterMix(value) => {
+                            master_mix = value.clamp(0.0, 2.0);
+                        }
+                        UserInteractionSegMsg::SetSprite(settings) => {
+                            sprite_overlay.configure(&settings);
+                            sprite_settings = settings;
+                        }
+                        UserInteractionSegMsg::SetToneMap(settings) => {
+                            tone_settings = settings;
+                        }
+                        UserInteractionSegMsg::SetInferenceCadence(c) => {
+                            info!("Inference cadence: {}", c.describe());
+                            cadence = c;
+                        }
+                        UserInteractionSegMsg::SetPartialUploads(enabled) => {
+                            info!("Partial texture uploads: {}", enabled);
+                            partial_uploads = enabled;
+                            damage_tracker.reset();
+                        }
+                        UserInteractionSegMsg::SetVisualQuality(q) => {
+                            info!("Visual quality: {}", q.label());
+                            visual_quality = q;
+                        }
+                        UserInteractionSegMsg::SetPaintLayer(enabled) => {
+                            info!("Paint layer: {}", enabled);
+                            paint_layer = enabled;
+                        }
+                        UserInteractionSegMsg::SetMatte(settings) => {
+                            info!("Mask edge refinement: {:?}", settings);
+                            matte = settings;
+                        }
+                        UserInteractionSegMsg::SetFramePacing(p) => {
+                            info!("Frame pacing: {:?}", p);
+                            pacing = p;
+                            pacer.reset();
+                        }
+                        UserInteractionSegMsg::SetLut(settings) => {
+                            if let Err(e) = lut_stage.configure(&settings) {
+                                let emsg = format!("LUT load failed: {:#}", e);
+                                error!("{}", emsg);
+                                let _ = ui_sender.send(SegmentationThreadMsg::Error(emsg));
+                            }
+                        }
+                        UserInteractionSegMsg::SetSessionRecording(_)
+                        | UserInteractionSegMsg::SetKeyedRecording(_) => {}
+                    }
+                }
+                Err(TryRecvError::Empty) => {
+                    break;
+                }
+                Err(TryRecvError::Closed) => {
+                    // UI keeps the sender alive; losing it just means no more interactions
+                    break;
+                }
+            }
+        }
+        // Receive Frames; the pacer picks which one to render
         loop {
             match camera_receiver.try_recv() {
-                Ok(CameraThreadMsg::Frame(f)) => {
-                    latest_frame_arc = Some(f);
+                Ok(CameraThreadMsg::Frame(f, captured, index)) => {
+                    let dropped = pacer.push(captured, index, f);
+                    metrics::global().inc(metrics::CAMERA_FRAMES_SKIPPED, dropped);
                 }
+                Ok(CameraThreadMsg::Replay(step)) => replay_steps.push_back(step),
                 Ok(CameraThreadMsg::Error(e)) => {
                     warn!("Cam Err: {}", e);
                 }
                 Err(TryRecvError::Empty) => {
                     break;
                 }
-                Err(TryRecvError::Disconnected) => {
+                Err(TryRecvError::Closed) => {
                     error!("Cam disconnected.");
-                    stop_signal.store(true, Ordering::Relaxed);
+                    stop_signal.stop();
                     break;
                 }
             }
@@ -121,167 +1730,627 @@ fn segmentation_loop(
         loop {
             match intensity_receiver.try_recv() {
                 Ok(i) => {
-                    if i.len() >= MAX_TRACKS {
-                        current_band_intensities.copy_from_slice(&i[0..MAX_TRACKS]);
-                    } else {
-                        current_band_intensities.fill(0.0);
-                        current_band_intensities[0..i.len()].copy_from_slice(&i);
+                    if let Some(r) = &session_recorder {
+                        r.record_intensities(&i);
                     }
+                    intensity_offset.push(i);
                 }
                 Err(TryRecvError::Empty) => {
                     break;
                 }
-                Err(TryRecvError::Disconnected) => {
+                Err(TryRecvError::Closed) => {
                     error!("Audio proc disconnected.");
-                    stop_signal.store(true, Ordering::Relaxed);
+                    stop_signal.stop();
                     break;
                 }
             }
         }
+        // Hits restart their reactions; without an audio processor the channel is just closed
+        while let Ok(hit) = drum_receiver.try_recv() {
+            drum_pulse.trigger(hit);
+        }
+        let message = replay_intensities.take().or_else(|| intensity_offset.current());
+        if let Some(message) = message {
+            let (i, meters) = music::split_meters(&message);
+            band_meters = meters;
+            if i.len() >= NUM_ANALYSIS_CHANNELS {
+                current_band_intensities.copy_from_slice(&i[0..NUM_ANALYSIS_CHANNELS]);
+            } else {
+                // Older layouts (e.g. replayed sessions) lack the newer channels
+                current_band_intensities = music::silent_intensities();
+                current_band_intensities[0..i.len()].copy_from_slice(i);
+            }
+            // Shaped here, on the fresh copy, so held values are never curved twice. The gate
+            // sees the raw levels, so its thresholds are independent of the curves
+            noise_gate.apply(&mut current_band_intensities);
+            response_curves.apply(&mut current_band_intensities);
+            if intensity_gain != 1.0 {
+                for channel in GATED_CHANNELS {
+                    let value = &mut current_band_intensities[channel.index()];
+                    *value = (*value * intensity_gain).min(1.0);
+                }
+            }
+        }
+        if let Some(held) = &frozen_intensities {
+            current_band_intensities.clone_from(held);
+        }
+        quantizer
+            .clock
+            .observe_beat(current_band_intensities[AnalysisChannel::Beat.index()], now);
 
-        if stop_signal.load(Ordering::Relaxed) {
+        if stop_signal.is_stopped() {
             break;
         }
 
-        if let Some(frame_arc) = latest_frame_arc {
+        if let Some(paced) = replay_frame.take().or_else(|| pacer.next(pacing)) {
+            metrics::global().inc(metrics::CAMERA_FRAMES_SKIPPED, paced.skipped);
+            let (mut frame_arc, captured, frame_index) = (paced.frame, paced.captured, paced.index);
+            let frame_count = frame_clock.frame(frame_index);
+            if frozen_intensities.is_some() {
+                frame_arc = frozen_frame.get_or_insert(frame_arc).clone();
+            }
             let loop_start_time = Instant::now();
-            let _original_image = (*frame_arc).clone();
+            if let Some(r) = &session_recorder {
+                r.record_frame(&frame_arc);
+            }
+            let original_image = frame_arc.clone(); // Untouched source for warp-style visuals
             let mut display_image = (*frame_arc).clone();
-            let (_frame_w, _frame_h) = display_image.dimensions(); // Use _ if not needed
-            let dynamic_image_input = DynamicImage::ImageRgb8((*frame_arc).clone());
+            let (render_a, render_b) = deck::active_decks(crossfader);
+            let mut deck_b_image = render_b.then(|| (*frame_arc).clone());
+            let (frame_w, frame_h) = display_image.dimensions();
+            if let Some(seed) = seed {
+                rng = seeded_rng(seed, frame_index, GLOBAL_RNG_STREAM);
+            }
 
-            let proc_start = Instant::now();
-            let results = model.forward(&[dynamic_image_input]);
-            processing_time = proc_start.elapsed();
+            // --- Inference windows: zoomed crops around selected slots, else the ROI or frame ---
+            let roi_rect = roi.and_then(|r| r.to_pixel_rect(frame_w, frame_h));
+            let run_inference = held_detections.as_ref().map_or(true, |h| {
+                frame_clock.inference_due(cadence, frame_count, h.frame, h.at)
+            });
+            let zoom_due = frame_count.saturating_sub(last_full_frame) < ZOOM_FULL_FRAME_EVERY;
+            let selected = |slot: usize| slot_locks[slot] || !prompts[slot].is_empty();
+            let zoom = (track_zoom && zoom_due)
+                .then(|| zoom_windows(&selected_boxes, selected, (frame_w, frame_h)))
+                .flatten();
+            let windows: Vec<Option<PixelRect>> = match &zoom {
+                Some(rects) => rects.iter().copied().map(Some).collect(),
+                None => vec![roi_rect],
+            };
+            let results = run_inference.then(|| {
+                let proc_start = Instant::now();
+                let found = infer_windows(&mut model, &frame_arc, &windows, &mut batch_inference);
+                processing_time = proc_start.elapsed();
+                metrics::global().observe(
+                    metrics::INFERENCE_MS,
+                    processing_time.as_secs_f64() * 1000.0,
+                );
+                found
+            });
+            if run_inference && zoom.is_none() {
+                last_full_frame = frame_count;
+            }
+            if let Some(r) = refiner.as_mut() {
+                r.offer(&frame_arc);
+            }
 
             // Store mapping from CURRENT detection index to relevant info for drawing
             // Value: (band_idx, animation_phase)
             let mut current_detection_info: HashMap<usize, (usize, f32)> = HashMap::new();
+            // (slot, bbox) of everything drawn this frame, for the sprite overlay
+            let mut drawn_objects: Vec<(usize, Rect)> = Vec::new();
             let mut next_tracked_objects: Vec<TrackedObject> = Vec::new();
+            let mut detections: Vec<Detection> = Vec::new();
 
             match results {
-                Ok(ys) => {
-                    if let Some(y) = ys.first() {
-                        let current_bboxes = y.bboxes().unwrap_or_default();
-                        let current_masks = y.masks().unwrap_or_default();
-
-                        // Selection Logic Removed
-
-                        // Match Existing Tracks
-                        let mut matched_current_indices: HashSet<usize> = HashSet::new();
-                        for tracked_obj in tracked_objects.iter() {
-                            let mut best_match_for_this_track: Option<(usize, f32)> = None;
-                            for (det_idx, current_bbox) in current_bboxes.iter().enumerate() {
-                                if matched_current_indices.contains(&det_idx) {
-                                    continue;
-                                }
-                                let iou = tracked_obj.bbox.iou(current_bbox);
-                                if iou > IOU_THRESHOLD {
-                                    let is_better = match best_match_for_this_track {
-                                        Some((_, cur_iou)) => iou > cur_iou,
-                                        None => true,
-                                    };
-                                    if is_better {
-                                        best_match_for_this_track = Some((det_idx, iou));
-                                    }
-                                }
+                Some(Ok(found)) => {
+                    detections = found;
+                    if let Some(refined) = refiner.as_mut().and_then(|r| r.latest()) {
+                        fuse_refined_masks(&mut detections, refined, (frame_w, frame_h));
+                    }
+                    // Pixels per second since the previous inference, for the skipped frames
+                    let mut velocities: HashMap<usize, (f32, f32)> = HashMap::new();
+                    let secs_since_inference = held_detections
+                        .as_ref()
+                        .map_or(pacer.camera_interval(), |h| {
+                            captured.saturating_duration_since(h.captured)
+                        })
+                        .as_secs_f32()
+                        .max(1e-3);
+
+                    // Prompted slots claim their detection before tracking sees it
+                    let mut matched_current_indices: HashSet<usize> = HashSet::new();
+                    selected_boxes = std::array::from_fn(|_| None);
+                    for (slot, prompt) in prompts.iter().enumerate() {
+                        if prompt.is_empty() {
+                            continue;
+                        }
+                        if let Some(det_idx) =
+                            pick_prompted_detection(&detections, prompt, &matched_current_indices)
+                        {
+                            prompt_phases[slot] +=
+                                0.05 + current_band_intensities[slot_routing[slot].index()] * 0.1;
+                            current_detection_info.insert(det_idx, (slot, prompt_phases[slot]));
+                            matched_current_indices.insert(det_idx);
+                            selected_boxes[slot] = Some(detections[det_idx].bbox.clone());
+                        }
+                    }
+
+                    // Appearance of each detection, for Re-ID during association
+                    let appearances: Vec<Option<Appearance>> = detections
+                        .iter()
+                        .map(|d| {
+                            d.mask
+                                .as_deref()
+                                .map(|m| Appearance::from_masked(&original_image, m, &d.bbox))
+                        })
+                        .collect();
+
+                    // Match Existing Tracks: best pairs first, IoU minus appearance distance
+                    let mut candidates: Vec<(f32, usize, usize)> = Vec::new();
+                    for (track_idx, tracked_obj) in tracked_objects.iter().enumerate() {
+                        for (det_idx, detection) in detections.iter().enumerate() {
+                            if !detection.is_confident()
+                                || matched_current_indices.contains(&det_idx)
+                            {
+                                continue;
                             }
-                            if let Some((matched_det_idx, _iou)) = best_match_for_this_track {
-                                let updated_obj = TrackedObject {
-                                    bbox: current_bboxes[matched_det_idx].clone(),
-                                    band_index: tracked_obj.band_index,
-                                    animation_phase: tracked_obj.animation_phase
-                                        + 0.05
-                                        + current_band_intensities[tracked_obj.band_index] * 0.1,
-                                };
-                                // Store info needed for drawing THIS frame
-                                current_detection_info.insert(
-                                    matched_det_idx,
-                                    (updated_obj.band_index, updated_obj.animation_phase),
-                                );
-                                next_tracked_objects.push(updated_obj); // Add to list for NEXT frame
-                                matched_current_indices.insert(matched_det_idx);
+                            let iou = tracked_obj.bbox.iou(&detection.bbox);
+                            let distance = match (&tracked_obj.appearance, &appearances[det_idx]) {
+                                (Some(a), Some(b)) => Some(a.distance(b)),
+                                _ => None,
+                            };
+                            let looks_same = distance.is_some_and(|d| d < REID_MAX_DISTANCE);
+                            if iou > IOU_THRESHOLD || (iou > REID_MIN_IOU && looks_same) {
+                                let score = iou - APPEARANCE_WEIGHT * distance.unwrap_or(0.5);
+                                candidates.push((score, track_idx, det_idx));
                             }
                         }
+                    }
+                    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+                    let mut matched_tracks: HashSet<usize> = HashSet::new();
+                    let mut assignments: Vec<(usize, usize)> = Vec::new();
+                    for (_, track_idx, det_idx) in candidates {
+                        if matched_tracks.contains(&track_idx)
+                            || matched_current_indices.contains(&det_idx)
+                        {
+                            continue;
+                        }
+                        assignments.push((track_idx, det_idx));
+                        matched_current_indices.insert(det_idx);
+                        matched_tracks.insert(track_idx);
+                    }
 
-                        // Assign New Detections
-                        for det_idx in 0..current_masks.len() {
-                            if !matched_current_indices.contains(&det_idx) {
-                                let assigned_band = rng.gen_range(0..MAX_TRACKS);
-                                let new_obj = TrackedObject {
-                                    bbox: current_bboxes[det_idx].clone(),
-                                    band_index: assigned_band,
-                                    animation_phase: rng.gen::<f32>() * 2.0 * PI,
-                                };
-                                // Store info needed for drawing THIS frame
-                                current_detection_info
-                                    .insert(det_idx, (new_obj.band_index, new_obj.animation_phase));
-                                next_tracked_objects.push(new_obj); // Add to list for NEXT frame
+                    // Second pass (ByteTrack): leftover tracks try the low-confidence boxes,
+                    // which keeps partially occluded objects alive. IoU only; these are noisy
+                    let mut low_candidates: Vec<(f32, usize, usize)> = Vec::new();
+                    for (track_idx, tracked_obj) in tracked_objects.iter().enumerate() {
+                        if matched_tracks.contains(&track_idx) {
+                            continue;
+                        }
+                        for (det_idx, detection) in detections.iter().enumerate() {
+                            if detection.is_confident()
+                                || matched_current_indices.contains(&det_idx)
+                            {
+                                continue;
                             }
+                            let iou = tracked_obj.bbox.iou(&detection.bbox);
+                            if iou > LOW_CONF_IOU_THRESHOLD {
+                                low_candidates.push((iou, track_idx, det_idx));
+                            }
+                        }
+                    }
+                    low_candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+                    for (_, track_idx, det_idx) in low_candidates {
+                        if matched_tracks.contains(&track_idx)
+                            || matched_current_indices.contains(&det_idx)
+                        {
+                            continue;
                         }
+                        assignments.push((track_idx, det_idx));
+                        matched_current_indices.insert(det_idx);
+                        matched_tracks.insert(track_idx);
+                    }
 
-                        // Update tracked objects state for the *NEXT* frame
-                        tracked_objects = next_tracked_objects;
+                    for (track_idx, det_idx) in assignments {
+                        let tracked_obj = &tracked_objects[track_idx];
+                        let appearance = match (&tracked_obj.appearance, &appearances[det_idx]) {
+                            (Some(old), Some(new)) => Some(old.blend(new)),
+                            (old, new) => new.clone().or_else(|| old.clone()),
+                        };
+                        let updated_obj = TrackedObject {
+                            bbox: detections[det_idx].bbox.clone(),
+                            band_index: tracked_obj.band_index,
+                            animation_phase: tracked_obj.animation_phase
+                                + 0.05
+                                + current_band_intensities
+                                    [slot_routing[tracked_obj.band_index].index()]
+                                    * 0.1,
+                            appearance,
+                            missed_frames: 0,
+                        };
+                        let (old, new) = (&tracked_obj.bbox, &updated_obj.bbox);
+                        velocities.insert(
+                            det_idx,
+                            (
+                                (new.xmin() - old.xmin()) / secs_since_inference,
+                                (new.ymin() - old.ymin()) / secs_since_inference,
+                            ),
+                        );
+                        // Store info needed for drawing THIS frame
+                        current_detection_info.insert(
+                            det_idx,
+                            (updated_obj.band_index, updated_obj.animation_phase),
+                        );
+                        next_tracked_objects.push(updated_obj); // Add to list for NEXT frame
+                    }
 
-                        // Combined Canvas Logic Removed
+                    // Unmatched tracks are "lost": kept (not drawn) for a while so they can be
+                    // picked up again; locked slots never expire. Zoomed passes never looked at
+                    // unselected tracks, so those wait unchanged for the next full-frame pass
+                    for (track_idx, tracked_obj) in tracked_objects.iter().enumerate() {
+                        if matched_tracks.contains(&track_idx) {
+                            continue;
+                        }
+                        let slot = tracked_obj.band_index;
+                        if zoom.is_some() && !slot_locks[slot] && prompts[slot].is_empty() {
+                            next_tracked_objects.push(tracked_obj.clone());
+                            continue;
+                        }
+                        if slot_locks[slot] || tracked_obj.missed_frames < MAX_MISSED_FRAMES {
+                            let mut lost = tracked_obj.clone();
+                            lost.missed_frames = lost.missed_frames.saturating_add(1);
+                            next_tracked_objects.push(lost);
+                        }
+                    }
 
-                        // Drawing Logic (Visualize ALL *currently detected* and assigned objects)
-                        for (det_idx, (band_idx, anim_phase)) in &current_detection_info {
-                            // Get required data (mask, bbox, intensity) using det_idx
-                            if let (Some(mask_to_draw), Some(bbox_to_draw)) =
-                                (current_masks.get(*det_idx), current_bboxes.get(*det_idx))
-                            {
-                                let intensity = current_band_intensities[*band_idx];
-                                let mask_image = mask_to_draw.mask();
-                                let bbox_rect = Rect::at(
-                                    bbox_to_draw.xmin() as i32,
-                                    bbox_to_draw.ymin() as i32,
-                                )
-                                .of_size(
-                                    bbox_to_draw.width().max(1.0) as u32,
-                                    bbox_to_draw.height().max(1.0) as u32,
-                                );
+                    // New objects only go to slots that aren't locked
+                    let open_slots: Vec<usize> =
+                        (0..MAX_TRACKS).filter(|&s| !slot_locks[s]).collect();
 
-                                // Call the visuals drawing function FOR THIS OBJECT
-                                visuals::draw_visuals(
-                                    &mut display_image,
-                                    mask_image,
-                                    bbox_rect,
-                                    *band_idx,
-                                    intensity,
-                                    frame_count,
-                                    *anim_phase,
-                                    &mut rng,
-                                );
+                    // Assign New Detections
+                    for (det_idx, detection) in detections.iter().enumerate() {
+                        if detection.mask.is_some()
+                            && detection.is_confident()
+                            && !matched_current_indices.contains(&det_idx)
+                        {
+                            if open_slots.is_empty() {
+                                break;
                             }
-                        } // End drawing loop
-                    } // End if let Some(y)
-                } // End Ok(ys)
-                Err(e) => {
+                            let assigned_band = open_slots[rng.gen_range(0..open_slots.len())];
+                            let new_obj = TrackedObject {
+                                bbox: detection.bbox.clone(),
+                                band_index: assigned_band,
+                                animation_phase: rng.gen::<f32>() * 2.0 * PI,
+                                appearance: appearances[det_idx].clone(),
+                                missed_frames: 0,
+                            };
+                            // Store info needed for drawing THIS frame
+                            current_detection_info
+                                .insert(det_idx, (new_obj.band_index, new_obj.animation_phase));
+                            next_tracked_objects.push(new_obj); // Add to list for NEXT frame
+                        }
+                    }
+
+                    // Zoomed passes only see the selected slots; the rest stay drawn where the
+                    // last full-frame pass found them until the next one
+                    if zoom.is_some() {
+                        let drawn: HashSet<usize> =
+                            current_detection_info.values().map(|(slot, _)| *slot).collect();
+                        for (detection, slot, phase) in &unzoomed_objects {
+                            if !selected(*slot) && !drawn.contains(slot) {
+                                current_detection_info.insert(detections.len(), (*slot, *phase));
+                                detections.push(detection.clone());
+                            }
+                        }
+                    } else {
+                        unzoomed_objects.clear();
+                        if track_zoom {
+                            unzoomed_objects.extend(
+                                current_detection_info
+                                    .iter()
+                                    .filter(|(_, (slot, _))| !selected(*slot))
+                                    .map(|(&i, &(slot, phase))| {
+                                        (detections[i].clone(), slot, phase)
+                                    }),
+                            );
+                        }
+                    }
+
+                    // Locked slots zoom on their track while it is in view
+                    for track in &next_tracked_objects {
+                        let slot = track.band_index;
+                        if slot_locks[slot] && prompts[slot].is_empty() && track.missed_frames == 0
+                        {
+                            selected_boxes[slot] = Some(track.bbox.clone());
+                        }
+                    }
+
+                    // Update tracked objects state for the *NEXT* frame
+                    tracked_objects = next_tracked_objects;
+                    held_detections = Some(HeldDetections {
+                        detections: detections.clone(),
+                        info: current_detection_info.clone(),
+                        velocities,
+                        frame: frame_count,
+                        at: source_now.unwrap_or_else(Instant::now),
+                        captured,
+                    });
+                } // End Ok(found)
+                Some(Err(e)) => {
                     warn!("FastSAM model forward pass failed: {}", e);
                 }
+                None => {
+                    // Skipped by the cadence: replay the last result, moved along its velocity
+                    if let Some(held) = &held_detections {
+                        detections = if pacing.interpolate {
+                            held.predict(captured)
+                        } else {
+                            held.detections.clone()
+                        };
+                        current_detection_info = held.info.clone();
+                    }
+                }
             } // End match results
 
-            // --- Send Final Image to UI ---
-            let final_color_image = {
-                let size = [
-                    display_image.width() as usize,
-                    display_image.height() as usize,
-                ];
-                let pixels = display_image.into_raw();
-                ColorImage::from_rgb(size, &pixels)
+            // Drawing Logic (Visualize ALL *currently detected* and assigned objects, or held ones)
+            let mut objects: Vec<SceneObject<'_>> = Vec::new();
+            for (det_idx, (band_idx, anim_phase)) in &current_detection_info {
+                let Some(detection) = detections.get(*det_idx) else {
+                    continue;
+                };
+                let detected_rect = bbox_rect(&detection.bbox);
+                // Operator nudges move and scale the mask and contour along with the box
+                let adjust = track_adjusts[*band_idx];
+                let bbox_rect = adjust.apply(detected_rect, (frame_w, frame_h));
+                let contour: Cow<'_, [(f32, f32)]> = if adjust.is_identity() {
+                    Cow::Borrowed(&detection.contour)
+                } else {
+                    Cow::Owned(fit_contour(&detection.contour, detected_rect, bbox_rect))
+                };
+                // No mask from the model: effects fill the bbox with a soft rounded edge
+                let mask = match detection.mask.as_deref() {
+                    Some(m) if adjust.is_identity() => Cow::Borrowed(m),
+                    Some(m) => Cow::Owned(fit_mask(m, detected_rect, bbox_rect)),
+                    None => Cow::Owned(visuals::bbox_falloff_mask(
+                        (frame_w, frame_h),
+                        bbox_rect,
+                        FALLBACK_MASK_FEATHER,
+                    )),
+                };
+                // User brush corrections for this slot, if any
+                let (mask, bbox_rect) = match mask_edits[*band_idx].apply(&mask, bbox_rect) {
+                    Some((m, r)) => (Cow::Owned(m), r),
+                    None => (mask, bbox_rect),
+                };
+                let mask = if matte.enabled {
+                    Cow::Owned(matting::refine_mask(&original_image, &mask, bbox_rect, &matte))
+                } else {
+                    mask
+                };
+                drawn_objects.push((*band_idx, bbox_rect));
+                objects.push(SceneObject {
+                    slot: *band_idx,
+                    phase: *anim_phase,
+                    bbox: bbox_rect,
+                    mask,
+                    contour,
+                });
+            }
+            // Animations run off the master clock while chasing, so reruns of a show line up;
+            // seeded runs stay on the source frame so they never read the wall clock
+            let visual_frame = match timecode_anchor.filter(|_| seed.is_none()) {
+                Some((secs, at)) => {
+                    ((secs + at.elapsed().as_secs_f64()) * TIMECODE_VISUAL_FPS) as u64
+                }
+                None => frame_count,
             };
-            match ui_sender.try_send(SegmentationThreadMsg::Frame(Arc::new(final_color_image))) {
-                Ok(_) => {
-                    ctx.request_repaint();
+            let scene = Scene {
+                original: &original_image,
+                objects: &objects,
+                intensities: &current_band_intensities,
+                routing: &slot_routing,
+                bass_sustained_secs,
+                drums: drum_pulse,
+                frame_count: visual_frame,
+                seed: seed.map(|s| (s, frame_index)),
+                quality: visual_quality,
+                feathered: matte.enabled,
+            };
+            // Object visuals once per live deck, unless the UI paints them
+            let targets = [
+                render_a.then_some((&mut display_image, &deck_a, 0)),
+                deck_b_image.as_mut().map(|img| (img, &deck_b, 1)),
+            ];
+            for (target, look, deck) in targets.into_iter().flatten().filter(|_| !paint_layer) {
+                draw_look_objects(target, &scene, look, deck, &mut visual_states[deck], &mut rng);
+            }
+            let paint = paint_layer.then(|| {
+                let objects = objects.iter().filter_map(|o| {
+                    let intensity = current_band_intensities[slot_routing[o.slot].index()];
+                    let look = (deck_a.slot_styles[o.slot], deck_a.slot_outlines[o.slot]);
+                    PaintObject::new(o.slot, &o.mask, o.bbox, &o.contour, intensity, o.phase, look)
+                });
+                Arc::new(PaintLayer {
+                    objects: objects.collect(),
+                    frame_count: visual_frame,
+                })
+            });
+
+            // --- A/B compare: both looks over this frame's identical inputs ---
+            if let Some((look_a, look_b)) = compare_request.take() {
+                let [a, b] = [&look_a, &look_b].map(|look| {
+                    let mut image = (*original_image).clone();
+                    let mut states: [visuals::SlotState; MAX_TRACKS + 1] = Default::default();
+                    let mut compare_rng =
+                        seeded_rng(COMPARE_SEED, frame_index, GLOBAL_RNG_STREAM);
+                    draw_look_objects(&mut image, &scene, look, 0, &mut states, &mut compare_rng);
+                    apply_look_post(&mut image, &scene, look, &mut compare_rng);
+                    let size = [image.width() as usize, image.height() as usize];
+                    Arc::new(ColorImage::from_rgb(size, image.as_raw()))
+                });
+                let _ = ui_sender.send(SegmentationThreadMsg::Comparison(a, b));
+            }
+
+            // --- Time step: wall clock, or source frames when seeded so that a replay animates
+            // identically however fast it renders and whichever frames the pacer skips ---
+            let frame_dt = match (seed, last_index) {
+                (Some(_), Some(previous)) if frame_index > previous => {
+                    (frame_index - previous) as f32 * SEEDED_FRAME_DT
+                }
+                (Some(_), _) => SEEDED_FRAME_DT, // First frame, or the replay looped
+                (None, _) => last_frame_tick.elapsed().as_secs_f32(),
+            };
+            last_index = Some(frame_index);
+
+            // --- Sustained bass drives the heat shimmer ---
+            if current_band_intensities[AnalysisChannel::Low.index()] >= SHIMMER_BASS_THRESHOLD {
+                bass_sustained_secs += frame_dt;
+            } else {
+                bass_sustained_secs = (bass_sustained_secs - frame_dt * 2.0).max(0.0);
+            }
+            drum_pulse.advance(frame_dt);
+
+            // --- Sprites advance per slot at a rate following that slot's intensity ---
+            let slot_intensities: [f32; MAX_TRACKS] = std::array::from_fn(|slot| {
+                current_band_intensities[slot_routing[slot].index()]
+            });
+            if let Some(Err(e)) = sprite_overlay.poll() {
+                let emsg = format!("Sprite load failed: {:#}", e);
+                error!("{}", emsg);
+                let _ = ui_sender.send(SegmentationThreadMsg::Error(emsg));
+            }
+            sprite_overlay.advance(&sprite_settings, &slot_intensities, frame_dt);
+            last_frame_tick = Instant::now();
+
+            // --- Overlays + Post Processing (full frame), per live deck ---
+            let decks = [
+                render_a.then_some((&mut display_image, &deck_a)),
+                deck_b_image.as_mut().map(|img| (img, &deck_b)),
+            ];
+            for (target, look) in decks.into_iter().flatten() {
+                for (slot, bbox_rect) in &drawn_objects {
+                    sprite_overlay.draw(target, &sprite_settings, *slot, *bbox_rect);
+                }
+                text_overlay.draw(target, &text_settings, &current_band_intensities, &mut rng);
+                apply_look_post(target, &scene, look, &mut rng);
+            }
+            match (render_a, deck_b_image) {
+                (true, Some(b)) => deck::blend_into(&mut display_image, &b, crossfader),
+                (false, Some(b)) => display_image = b,
+                _ => {}
+            }
+            // Plugin visuals draw over the deck mix, so stateful plugins step once per frame
+            for object in &objects {
+                if let Some(index) = slot_plugins[object.slot] {
+                    plugin_host.draw(
+                        index,
+                        &mut display_image,
+                        &original_image,
+                        &object.mask,
+                        object.bbox,
+                        &current_band_intensities,
+                        object.slot,
+                        visual_frame,
+                    );
+                }
+            }
+            // Tone map, then grade the final mix, so both decks match the show's look
+            tone_mapper.apply(&mut display_image, &tone_settings);
+            lut_stage.apply(&mut display_image);
+            let coverage = (masked_output || keyed_config.is_some() || master_mix > 1.0).then(|| {
+                post::mask_coverage((frame_w, frame_h), objects.iter().map(|o| &*o.mask))
+            });
+            // --- Keyed overlay: the graded mix, with alpha from the masks ---
+            if let (Some(config), Some(coverage)) = (&keyed_config, &coverage) {
+                // A new camera size starts a new file; the old one finalizes in the background
+                if keyed_recorder.as_ref().is_some_and(|r| r.size() != (frame_w, frame_h)) {
+                    if let Some(r) = keyed_recorder.take() {
+                        drop(r.finish());
+                    }
+                }
+                if keyed_recorder.is_none() {
+                    match KeyedRecorder::start(config, (frame_w, frame_h)) {
+                        Ok(r) => {
+                            let path = r.path().display().to_string();
+                            keyed_recorder = Some(r);
+                            let _ =
+                                ui_sender.send(SegmentationThreadMsg::KeyedRecording(Some(path)));
+                        }
+                        Err(e) => {
+                            let emsg = format!("Keyed recording failed: {:#}", e);
+                            error!("{}", emsg);
+                            keyed_config = None;
+                            let _ = ui_sender.send(SegmentationThreadMsg::KeyedRecording(None));
+                            let _ = ui_sender.send(SegmentationThreadMsg::Error(emsg));
+                        }
+                    }
                 }
-                Err(TrySendError::Full(_)) => {}
-                Err(TrySendError::Disconnected(_)) => {
-                    info!("UI disconnected.");
-                    stop_signal.store(true, Ordering::Relaxed);
+                if let Some(r) = &keyed_recorder {
+                    let decks = [render_a.then_some(&deck_a), render_b.then_some(&deck_b)];
+                    let mut layer =
+                        render_keyed_layer(&scene, decks, crossfader, &mut keyed_states, &mut rng);
+                    tone_mapper.apply_held(&mut layer, &tone_settings);
+                    lut_stage.apply(&mut layer);
+                    r.push(keyed::keyed_frame(&layer, coverage));
+                }
+            }
+            // After grading, so a LUT can't lift the black surround off zero
+            if let Some(coverage) = coverage.as_ref().filter(|_| masked_output) {
+                post::black_outside_masks(&mut display_image, coverage);
+            }
+            post::master_mix(&mut display_image, &original_image, master_mix, coverage.as_ref());
+            // HUD goes on last so glitch effects never make it unreadable
+            text_overlay.draw_hud(&mut display_image, &hud_settings);
+            if hud_settings.burn_stats {
+                let lines =
+                    stats_lines(&current_band_intensities, &tracked_objects, &slot_locks);
+                text_overlay.draw_stats(&mut display_image, &lines);
+            }
+            // Geometry last: everything above lands on the surface the same way
+            corner_pin_stage.apply(&mut display_image, &corner_pin);
+            blackout_fader.step(blackout.0, blackout.1, frame_dt);
+            blackout_fader.apply(&mut display_image);
+
+            // --- Send Final Image to UI ---
+            let display_image = if display_scale < 1.0 {
+                let w = ((frame_w as f32 * display_scale).round() as u32).max(1);
+                let h = ((frame_h as f32 * display_scale).round() as u32).max(1);
+                imageops::resize(&display_image, w, h, imageops::FilterType::Triangle)
+            } else {
+                display_image
+            };
+            let final_color_image = fill_color_image(&mut color_buffer, &display_image);
+            let damage = if partial_uploads {
+                damage_tracker.diff(&display_image)
+            } else {
+                FrameDamage::Full
+            };
+            let work_time = loop_start_time.elapsed();
+            let registry = metrics::global();
+            registry.inc(metrics::SEG_FRAMES, 1);
+            registry.set(metrics::TRACKED_OBJECTS, tracked_objects.len() as f64);
+            registry.observe(metrics::SEG_LOOP_MS, work_time.as_secs_f64() * 1000.0);
+            // Paced: hold the frame until its presentation slot
+            if let Some(at) = pacer.present_at(pacing) {
+                if stop_signal.wait(at.saturating_duration_since(Instant::now())) {
                     break;
                 }
             }
+            seg_rate.tick();
+            if partial_uploads {
+                damage_tracker.commit(&display_image, &damage);
+            }
+            let composed = ComposedFrame {
+                image: final_color_image,
+                damage,
+                paint,
+                meters: band_meters,
+            };
+            if frame_slot.publish(composed) {
+                registry.inc(metrics::UI_FRAMES_DROPPED, 1);
+            }
+            ctx.request_repaint();
+            pacer.presented(work_time);
             debug!(
                 "Seg loop: {:.2?}, Model: {:.2?}, Tracked: {}, AudioInt: [{:.2}, {:.2}, {:.2}]",
                 loop_start_time.elapsed(),
@@ -292,9 +2361,128 @@ fn segmentation_loop(
                 current_band_intensities.get(2).cloned().unwrap_or(0.0)
             );
         } else {
-            thread::sleep(Duration::from_millis(5));
+            // Nothing to render: await a frame, an interaction or a stop request. Whatever
+            // arrives is handled here or on the next pass; closed channels are left to try_recv
+            supervisor::runtime().block_on(async {
+                tokio::select! {
+                    msg = camera_receiver.recv(), if !camera_receiver.is_closed() => match msg {
+                        Ok(CameraThreadMsg::Frame(f, captured, index)) => {
+                            let dropped = pacer.push(captured, index, f);
+                            metrics::global().inc(metrics::CAMERA_FRAMES_SKIPPED, dropped);
+                        }
+                        Ok(CameraThreadMsg::Replay(step)) => replay_steps.push_back(step),
+                        Ok(CameraThreadMsg::Error(e)) => warn!("Cam Err: {}", e),
+                        Err(_) => {}
+                    },
+                    msg = user_interaction_receiver.recv(),
+                        if !user_interaction_receiver.is_closed() =>
+                    {
+                        woken_interaction = msg.ok();
+                    }
+                    _ = stop_signal.stopped() => {}
+                    _ = tokio::time::sleep(IDLE_WAIT) => {}
+                }
+            });
         }
     } // End while !stop_signal
 
+    if let Some(r) = session_recorder.take() {
+        r.finish();
+    }
+    if let Some(r) = keyed_recorder.take() {
+        let _ = r.finish().join();
+    }
     info!("Segmentation loop finishing.");
 } 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    // Frame numbers and inference decisions of the composited frames, with `idle[i]` extra loop
+    // passes (idle waits, UI wakes) ahead of source frame `i`
+    fn run(seeded: bool, cadence: InferenceCadence, idle: &[u64]) -> Vec<(u64, bool)> {
+        let mut clock = FrameClock::new(seeded);
+        let mut held: Option<u64> = None;
+        idle.iter()
+            .enumerate()
+            .map(|(index, &passes)| {
+                for _ in 0..passes {
+                    clock.tick();
+                }
+                clock.tick();
+                let frame = clock.frame(index as u64);
+                let due = held.map_or(true, |h| {
+                    clock.inference_due(cadence, frame, h, Instant::now() - Duration::from_secs(1))
+                });
+                if due {
+                    held = Some(frame);
+                }
+                (frame, due)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn seeded_frames_ignore_idle_passes() {
+        let calm = [0; 12];
+        let busy = [3, 0, 7, 1, 0, 0, 12, 2, 0, 5, 1, 9];
+        for cadence in [
+            InferenceCadence::EveryFrame,
+            InferenceCadence::Frames(3),
+            InferenceCadence::Millis(100),
+        ] {
+            let (a, b) = (run(true, cadence, &calm), run(true, cadence, &busy));
+            assert_eq!(a, b, "{:?}", cadence);
+        }
+        // 100 ms at 30 source frames per second: every third frame
+        let due: Vec<bool> = run(true, InferenceCadence::Millis(100), &busy)
+            .into_iter()
+            .map(|(_, due)| due)
+            .collect();
+        let every_third: Vec<bool> = (0..12).map(|i| i % 3 == 0).collect();
+        assert_eq!(due, every_third);
+
+        // Unseeded runs keep counting loop passes
+        let cadence = InferenceCadence::EveryFrame;
+        assert_ne!(run(false, cadence, &calm), run(false, cadence, &busy));
+    }
+
+    #[test]
+    fn slow_refined_pass_is_fused_onto_the_moved_box() {
+        // The heavy model saw the object 10 px left of where the fast model finds it now
+        let size = (160, 120);
+        let refined_box = Bbox::default().with_xyxy(20.0, 30.0, 60.0, 90.0);
+        let mut refined_mask = GrayImage::new(size.0, size.1);
+        for y in 30..90 {
+            for x in 20..60 {
+                refined_mask.put_pixel(x, y, Luma([255]));
+            }
+        }
+        let refined = RefinedMasks {
+            detections: vec![Detection {
+                bbox: refined_box,
+                mask: Some(Arc::new(refined_mask)),
+                contour: Vec::new(),
+            }],
+        };
+        let mut detections = vec![Detection {
+            bbox: Bbox::default().with_xyxy(30.0, 30.0, 70.0, 90.0),
+            mask: None,
+            contour: Vec::new(),
+        }];
+        fuse_refined_masks(&mut detections, &refined, size);
+        let mask = detections[0].mask.as_deref().expect("refined mask lent");
+        assert_eq!(mask.get_pixel(66, 60)[0], 255);
+        assert_eq!(mask.get_pixel(25, 60)[0], 0);
+    }
+
+    #[test]
+    fn seeded_cadence_restarts_when_the_replay_loops() {
+        let clock = FrameClock::new(true);
+        let held_at = Instant::now();
+        assert!(!clock.inference_due(InferenceCadence::Frames(4), 12, 10, held_at));
+        assert!(clock.inference_due(InferenceCadence::Frames(4), 0, 10, held_at));
+    }
+}
@@ -0,0 +1,475 @@
+// src/session.rs
+use anyhow::{Context, Result};
+use crossbeam_channel::{unbounded, Sender, TrySendError};
+use image::{ImageFormat, RgbImage};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::camera::{CameraThreadMsg, LatestFrame};
+use crate::metrics;
+use crate::pipeline::{self, PolicySender};
+use crate::segmentation::UserInteractionSegMsg;
+use crate::supervisor::{Heartbeat, StopSignal};
+
+const SESSIONS_DIR: &str = "sessions";
+const EVENTS_FILE: &str = "events.jsonl";
+const FRAMES_DIR: &str = "frames";
+const SEEDED_MAX_QUEUED: usize = 4; // Seeded replay steps decoded ahead of the segmentation thread
+const SEEDED_RETRY: Duration = Duration::from_millis(5);
+
+// --- One line of events.jsonl ---
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+enum SessionEvent {
+    Frame { t_ms: u64, file: String },
+    Intensities { t_ms: u64, values: Vec<f32> },
+    Interaction { t_ms: u64, msg: UserInteractionSegMsg },
+}
+
+impl SessionEvent {
+    fn t_ms(&self) -> u64 {
+        match self {
+            SessionEvent::Frame { t_ms, .. }
+            | SessionEvent::Intensities { t_ms, .. }
+            | SessionEvent::Interaction { t_ms, .. } => *t_ms,
+        }
+    }
+}
+
+// Work items for the writer thread (frame encoding happens off the segmentation thread)
+enum WriterMsg {
+    Frame(u64, Arc<RgbImage>),
+    Intensities(u64, Vec<f32>),
+    Interaction(u64, UserInteractionSegMsg),
+}
+
+// --- Recorder: lives in the segmentation thread, hands data to a writer thread ---
+pub struct SessionRecorder {
+    dir: PathBuf,
+    start: Instant,
+    writer_sender: Option<Sender<WriterMsg>>,
+    writer_handle: Option<JoinHandle<()>>,
+}
+
+impl SessionRecorder {
+    pub fn start() -> Result<Self> {
+        let stamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let dir = Path::new(SESSIONS_DIR).join(format!("session-{}", stamp));
+        fs::create_dir_all(dir.join(FRAMES_DIR))
+            .with_context(|| format!("Failed to create session dir {}", dir.display()))?;
+        let events_file = File::create(dir.join(EVENTS_FILE))
+            .with_context(|| format!("Failed to create events file in {}", dir.display()))?;
+
+        let (writer_sender, writer_receiver) = unbounded::<WriterMsg>();
+        let writer_dir = dir.clone();
+        let writer_handle = thread::spawn(move || {
+            let mut events = BufWriter::new(events_file);
+            let mut frame_index: u64 = 0;
+            for msg in writer_receiver {
+                let event = match msg {
+                    WriterMsg::Frame(t_ms, frame) => {
+                        frame_index += 1;
+                        let file = format!("{}/{:06}.jpg", FRAMES_DIR, frame_index);
+                        if let Err(e) =
+                            frame.save_with_format(writer_dir.join(&file), ImageFormat::Jpeg)
+                        {
+                            warn!("Session recorder: failed to write frame {}: {}", file, e);
+                            continue;
+                        }
+                        SessionEvent::Frame { t_ms, file }
+                    }
+                    WriterMsg::Intensities(t_ms, values) => {
+                        SessionEvent::Intensities { t_ms, values }
+                    }
+                    WriterMsg::Interaction(t_ms, msg) => SessionEvent::Interaction { t_ms, msg },
+                };
+                let line = match serde_json::to_string(&event) {
+                    Ok(l) => l,
+                    Err(e) => {
+                        warn!("Session recorder: failed to serialize event: {}", e);
+                        continue;
+                    }
+                };
+                if let Err(e) = writeln!(events, "{}", line) {
+                    error!("Session recorder: write failed: {}", e);
+                    break;
+                }
+            }
+            if let Err(e) = events.flush() {
+                error!("Session recorder: flush failed: {}", e);
+            }
+            info!("Session recorder writer finished ({} frames).", frame_index);
+        });
+
+        info!("Recording session to {}", dir.display());
+        Ok(Self {
+            dir,
+            start: Instant::now(),
+            writer_sender: Some(writer_sender),
+            writer_handle: Some(writer_handle),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    fn send(&self, msg: WriterMsg) {
+        if let Some(sender) = &self.writer_sender {
+            if let Err(TrySendError::Disconnected(_)) = sender.try_send(msg) {
+                warn!("Session recorder writer disconnected.");
+            }
+        }
+    }
+
+    pub fn record_frame(&self, frame: &Arc<RgbImage>) {
+        self.send(WriterMsg::Frame(self.elapsed_ms(), frame.clone()));
+    }
+
+    pub fn record_intensities(&self, values: &[f32]) {
+        self.send(WriterMsg::Intensities(self.elapsed_ms(), values.to_vec()));
+    }
+
+    pub fn record_interaction(&self, msg: &UserInteractionSegMsg) {
+        self.send(WriterMsg::Interaction(self.elapsed_ms(), msg.clone()));
+    }
+
+    pub fn finish(mut self) {
+        self.writer_sender.take(); // Close channel so the writer drains and exits
+        if let Some(h) = self.writer_handle.take() {
+            if let Err(e) = h.join() {
+                error!("Session writer join err: {:?}", e);
+            }
+        }
+        info!("Session recording saved to {}", self.dir.display());
+    }
+}
+
+// --- Replay: feeds a recorded session back through the same channels as the live sources ---
+fn load_events(dir: &Path) -> Result<Vec<SessionEvent>> {
+    let file = File::open(dir.join(EVENTS_FILE))
+        .with_context(|| format!("Failed to open {} in {}", EVENTS_FILE, dir.display()))?;
+    let mut events = Vec::new();
+    for (line_no, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: SessionEvent = serde_json::from_str(&line)
+            .with_context(|| format!("Bad event on line {}", line_no + 1))?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+// --- Seeded replay step: a recorded frame with the inputs recorded since the previous one ---
+#[derive(Debug)]
+pub struct ReplayStep {
+    pub frame: Arc<RgbImage>,
+    pub index: u64,                    // Recorded frame index, from 1 on each loop
+    pub t_ms: u64,                     // Recorded offset, continued across loops
+    pub intensities: Option<Vec<f32>>, // Latest recorded since the previous frame, if any
+    pub interactions: Vec<UserInteractionSegMsg>,
+}
+
+// Walks the recorded events, looping, holding the inputs that go with the next frame
+struct SeededReplay<'a> {
+    events: &'a [SessionEvent],
+    position: usize,
+    loop_offset_ms: u64,
+    index: u64,
+    intensities: Option<Vec<f32>>,
+    interactions: Vec<UserInteractionSegMsg>,
+}
+
+impl<'a> SeededReplay<'a> {
+    fn new(events: &'a [SessionEvent]) -> Self {
+        Self {
+            events,
+            position: 0,
+            loop_offset_ms: 0,
+            index: 0,
+            intensities: None,
+            interactions: Vec::new(),
+        }
+    }
+
+    // None once a whole loop has passed without a frame `load` could read
+    fn next_step(
+        &mut self,
+        mut load: impl FnMut(&str) -> Option<Arc<RgbImage>>,
+    ) -> Option<ReplayStep> {
+        for _ in 0..self.events.len() {
+            if self.position == self.events.len() {
+                self.position = 0;
+                self.index = 0;
+                // Keeps the recorded clock moving forward through the wrap
+                self.loop_offset_ms += self.events.last().map_or(0, SessionEvent::t_ms) + 1;
+            }
+            let event = &self.events[self.position];
+            self.position += 1;
+            match event {
+                SessionEvent::Intensities { values, .. } => self.intensities = Some(values.clone()),
+                SessionEvent::Interaction { msg, .. } => self.interactions.push(msg.clone()),
+                SessionEvent::Frame { t_ms, file } => {
+                    let Some(frame) = load(file) else {
+                        continue;
+                    };
+                    self.index += 1;
+                    return Some(ReplayStep {
+                        frame,
+                        index: self.index,
+                        t_ms: self.loop_offset_ms + t_ms,
+                        intensities: self.intensities.take(),
+                        interactions: std::mem::take(&mut self.interactions),
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+// Never drops: waits for the segmentation thread to catch up, checking for stop meanwhile
+fn send_in_order(
+    sender: &PolicySender<CameraThreadMsg>,
+    step: ReplayStep,
+    stop_signal: &StopSignal,
+    heartbeat: &Heartbeat,
+) -> bool {
+    while !sender.queued_below(SEEDED_MAX_QUEUED) {
+        heartbeat.beat();
+        if stop_signal.wait(SEEDED_RETRY) {
+            return false;
+        }
+    }
+    sender.send(CameraThreadMsg::Replay(step)).is_ok()
+}
+
+// Seeded runs get every frame in order with exactly the inputs recorded before it, paced by the
+// segmentation thread rather than the wall clock, so the render doesn't depend on scheduling
+fn replay_seeded(
+    dir: &Path,
+    events: &[SessionEvent],
+    camera_sender: &PolicySender<CameraThreadMsg>,
+    latest_frame: &LatestFrame,
+    stop_signal: &StopSignal,
+    heartbeat: &Heartbeat,
+) {
+    let mut replay = SeededReplay::new(events);
+    let load = |file: &str| match image::open(dir.join(file)) {
+        Ok(img) => Some(Arc::new(img.to_rgb8())),
+        Err(e) => {
+            warn!("Replay: failed to load frame {}: {}", file, e);
+            None
+        }
+    };
+    while !stop_signal.is_stopped() {
+        let Some(step) = replay.next_step(&load) else {
+            warn!("Replay: no readable frames in the session.");
+            break;
+        };
+        metrics::global().inc(metrics::CAMERA_FRAMES, 1);
+        latest_frame.publish(step.frame.clone());
+        if !send_in_order(camera_sender, step, stop_signal, heartbeat) {
+            break;
+        }
+        heartbeat.beat();
+    }
+}
+
+// `seeded` hands frames over as ReplaySteps through `camera_sender` alone (see replay_seeded)
+pub fn start_replay_thread(
+    dir: PathBuf,
+    camera_sender: PolicySender<CameraThreadMsg>,
+    intensity_sender: PolicySender<Vec<f32>>,
+    interaction_sender: PolicySender<UserInteractionSegMsg>,
+    latest_frame: LatestFrame,
+    stop_signal: StopSignal,
+    ctx: egui::Context,
+    heartbeat: Heartbeat,
+    seeded: bool,
+) -> JoinHandle<()> {
+    info!("Spawning session replay thread for {}", dir.display());
+    thread::spawn(move || {
+        let events = match load_events(&dir) {
+            Ok(e) => e,
+            Err(e) => {
+                let emsg = format!("Failed to load session: {:#}", e);
+                error!("{}", emsg);
+                let _ = camera_sender.send(CameraThreadMsg::Error(emsg));
+                ctx.request_repaint();
+                return;
+            }
+        };
+        info!("Replaying {} events (looping).", events.len());
+        if seeded {
+            replay_seeded(
+                &dir,
+                &events,
+                &camera_sender,
+                &latest_frame,
+                &stop_signal,
+                &heartbeat,
+            );
+            info!("Replay thread finished.");
+            return;
+        }
+
+        // Events are sent in recorded order at their recorded offsets, looping at the end
+        'replay: while !stop_signal.is_stopped() {
+            let start = Instant::now();
+            let mut frame_index: u64 = 0; // Position among the recorded frames, from each loop
+            for event in &events {
+                let due = Duration::from_millis(event.t_ms());
+                while start.elapsed() < due {
+                    heartbeat.beat();
+                    // Capped so long gaps between events still beat the heartbeat
+                    let gap = due.saturating_sub(start.elapsed()).min(Duration::from_secs(1));
+                    if stop_signal.wait(gap) {
+                        break 'replay;
+                    }
+                }
+                let sent = match event {
+                    SessionEvent::Frame { file, .. } => match image::open(dir.join(file)) {
+                        Ok(img) => {
+                            metrics::global().inc(metrics::CAMERA_FRAMES, 1);
+                            let frame = Arc::new(img.to_rgb8());
+                            latest_frame.publish(frame.clone());
+                            frame_index += 1;
+                            let msg = CameraThreadMsg::Frame(frame, start + due, frame_index);
+                            !matches!(
+                                camera_sender.try_send(msg),
+                                Err(pipeline::TrySendError::Closed(_))
+                            )
+                        }
+                        Err(e) => {
+                            warn!("Replay: failed to load frame {}: {}", file, e);
+                            true
+                        }
+                    },
+                    SessionEvent::Intensities { values, .. } => {
+                        // Latest intensities win, same as the live audio path
+                        !matches!(
+                            intensity_sender.try_send(values.clone()),
+                            Err(pipeline::TrySendError::Closed(_))
+                        )
+                    }
+                    SessionEvent::Interaction { msg, .. } => {
+                        interaction_sender.send(msg.clone()).is_ok()
+                    }
+                };
+                if !sent {
+                    info!("Replay receiver disconnected. Stopping replay.");
+                    break 'replay;
+                }
+                heartbeat.beat();
+            }
+        }
+        info!("Replay thread finished.");
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::ChannelConfig;
+
+    // Two frames a loop, with intensities and an interaction recorded between them
+    fn session() -> Vec<SessionEvent> {
+        let intensities = |t_ms, level| SessionEvent::Intensities {
+            t_ms,
+            values: vec![level; 3],
+        };
+        let frame = |t_ms| SessionEvent::Frame {
+            t_ms,
+            file: format!("{}.png", t_ms),
+        };
+        vec![
+            intensities(0, 0.1),
+            frame(10),
+            intensities(20, 0.2),
+            intensities(30, 0.3),
+            SessionEvent::Interaction {
+                t_ms: 35,
+                msg: UserInteractionSegMsg::SetSessionRecording(false),
+            },
+            frame(40),
+            intensities(50, 0.5),
+        ]
+    }
+
+    // Steps received from one seeded replay, with `delays` ms of work on the consumer per step
+    fn run(delays: &[u64]) -> Vec<String> {
+        let (sender, receiver, _) =
+            pipeline::channel::<CameraThreadMsg>("replay", ChannelConfig::unbounded());
+        let stop_signal = StopSignal::new();
+        let producer_stop = stop_signal.clone();
+        let producer = thread::spawn(move || {
+            let events = session();
+            let mut replay = SeededReplay::new(&events);
+            let frame = Arc::new(RgbImage::new(2, 2));
+            let heartbeat = Heartbeat::new();
+            while let Some(step) = replay.next_step(|_| Some(frame.clone())) {
+                if !send_in_order(&sender, step, &producer_stop, &heartbeat) {
+                    break;
+                }
+            }
+        });
+        let steps = delays
+            .iter()
+            .map(|&ms| {
+                thread::sleep(Duration::from_millis(ms));
+                match receiver.recv_blocking() {
+                    Ok(CameraThreadMsg::Replay(s)) => {
+                        let inputs = format!("{:?} {:?}", s.intensities, s.interactions);
+                        format!("{} {} {}", s.index, s.t_ms, inputs)
+                    }
+                    other => panic!("unexpected {:?}", other),
+                }
+            })
+            .collect();
+        stop_signal.stop();
+        producer.join().unwrap();
+        steps
+    }
+
+    #[test]
+    fn seeded_replays_pair_the_same_inputs_with_each_frame() {
+        let steady = run(&[0; 6]);
+        let uneven = run(&[3, 0, 12, 1, 0, 7]);
+        assert_eq!(steady, uneven);
+        assert_eq!(steady[0], "1 10 Some([0.1, 0.1, 0.1]) []");
+        assert_eq!(
+            steady[1],
+            "2 40 Some([0.3, 0.3, 0.3]) [SetSessionRecording(false)]"
+        );
+        // The loop's trailing intensities go with the next loop's first frame, on a later clock
+        assert_eq!(steady[2], "1 61 Some([0.5, 0.5, 0.5]) []");
+    }
+
+    #[test]
+    fn seeded_replay_without_frames_ends() {
+        let events = vec![SessionEvent::Intensities {
+            t_ms: 0,
+            values: vec![0.0],
+        }];
+        assert!(SeededReplay::new(&events).next_step(|_| None).is_none());
+        assert!(SeededReplay::new(&[]).next_step(|_| None).is_none());
+    }
+}
@@ -0,0 +1,231 @@
+// src/sprite.rs
+use anyhow::{anyhow, bail, Context, Result};
+use image::{imageops, RgbImage, RgbaImage};
+use imageproc::rect::Rect;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+};
+
+use crate::segmentation::MAX_TRACKS;
+use crate::visuals::BlendMode;
+
+// --- Where the sprite sits relative to the tracked bbox ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpriteAnchor {
+    Cover, // Stretched over the bbox
+    Above, // Bbox-wide, standing on the top edge (flames on a drum)
+}
+
+impl SpriteAnchor {
+    pub const ALL: [SpriteAnchor; 2] = [SpriteAnchor::Cover, SpriteAnchor::Above];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SpriteAnchor::Cover => "Cover",
+            SpriteAnchor::Above => "Above",
+        }
+    }
+}
+
+// --- PNG sequence (directory) or sprite sheet (single image split into a grid) ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpriteSettings {
+    pub enabled: bool,
+    pub path: String,
+    pub columns: u32, // Sheet grid; ignored for directories
+    pub rows: u32,
+    pub fps: f32,             // Playback rate at zero intensity
+    pub intensity_speed: f32, // Extra speed multiplier at full intensity
+    pub anchor: SpriteAnchor,
+    pub blend: BlendMode, // Applied within the sprite's own alpha
+    pub slots: [bool; MAX_TRACKS],
+}
+
+impl Default for SpriteSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: String::new(),
+            columns: 1,
+            rows: 1,
+            fps: 12.0,
+            intensity_speed: 3.0,
+            anchor: SpriteAnchor::Cover,
+            blend: BlendMode::Normal,
+            slots: [true; MAX_TRACKS],
+        }
+    }
+}
+
+impl SpriteSettings {
+    fn source_key(&self) -> (String, u32, u32) {
+        (self.path.trim().to_string(), self.columns, self.rows)
+    }
+}
+
+fn load_frames(path: &Path, columns: u32, rows: u32) -> Result<Vec<RgbaImage>> {
+    if path.is_dir() {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+            .with_context(|| format!("Failed to read sprite directory {}", path.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+            })
+            .collect();
+        files.sort();
+        return files
+            .iter()
+            .map(|f| {
+                image::open(f)
+                    .map(|img| img.to_rgba8())
+                    .with_context(|| format!("Failed to decode {}", f.display()))
+            })
+            .collect();
+    }
+    let sheet = image::open(path)
+        .with_context(|| format!("Failed to open sprite sheet {}", path.display()))?
+        .to_rgba8();
+    let (columns, rows) = (columns.max(1), rows.max(1));
+    let (cell_w, cell_h) = (sheet.width() / columns, sheet.height() / rows);
+    if cell_w == 0 || cell_h == 0 {
+        bail!("Sprite sheet {} is smaller than its grid", path.display());
+    }
+    let mut frames = Vec::with_capacity((columns * rows) as usize);
+    for row in 0..rows {
+        for col in 0..columns {
+            frames.push(
+                imageops::crop_imm(&sheet, col * cell_w, row * cell_h, cell_w, cell_h).to_image(),
+            );
+        }
+    }
+    Ok(frames)
+}
+
+// --- Loaded animation plus per-slot playback position ---
+// Decoding runs on a loader thread so a large sheet doesn't stall the segmentation loop
+#[derive(Default)]
+pub struct SpriteOverlay {
+    frames: Vec<RgbaImage>,
+    loaded: Option<(String, u32, u32)>,
+    loading: Option<JoinHandle<Result<Vec<RgbaImage>>>>,
+    positions: [f32; MAX_TRACKS], // In frames
+    scaled: [Option<ScaledFrame>; MAX_TRACKS], // Last frame drawn per slot, at its drawn size
+}
+
+struct ScaledFrame {
+    frame: usize,
+    size: (u32, u32),
+    image: RgbaImage,
+}
+
+impl SpriteOverlay {
+    // Starts loading only when the source changed; an empty path unloads. The old animation
+    // is dropped at once, the new one appears once `poll` has picked it up
+    pub fn configure(&mut self, settings: &SpriteSettings) {
+        let key = settings.source_key();
+        if self.loaded.as_ref() == Some(&key) {
+            return;
+        }
+        self.frames.clear();
+        self.scaled = Default::default();
+        self.loading = None; // A superseded load finishes on its own and is dropped
+        self.loaded = Some(key.clone());
+        if key.0.is_empty() {
+            return;
+        }
+        self.loading = Some(thread::spawn(move || {
+            let frames = load_frames(Path::new(&key.0), key.1, key.2)?;
+            if frames.is_empty() {
+                bail!("No PNG frames found in {}", key.0);
+            }
+            info!("Sprite loaded: {} frames from {}", frames.len(), key.0);
+            Ok(frames)
+        }));
+    }
+
+    // Takes the loader's result once it is done; Some(Err) when the load failed
+    pub fn poll(&mut self) -> Option<Result<()>> {
+        if !self.loading.as_ref()?.is_finished() {
+            return None;
+        }
+        let handle = self.loading.take()?;
+        let frames = handle
+            .join()
+            .unwrap_or_else(|_| Err(anyhow!("Sprite loader panicked")));
+        match frames {
+            Ok(frames) => self.frames = frames,
+            Err(e) => {
+                self.loaded = None; // Sending the same settings again retries
+                return Some(Err(e));
+            }
+        }
+        Some(Ok(()))
+    }
+
+    pub fn advance(&mut self, settings: &SpriteSettings, intensities: &[f32; MAX_TRACKS], dt: f32) {
+        let count = self.frames.len().max(1) as f32;
+        for (pos, intensity) in self.positions.iter_mut().zip(intensities) {
+            let speed = settings.fps * (1.0 + intensity.clamp(0.0, 1.0) * settings.intensity_speed);
+            *pos = (*pos + speed * dt) % count;
+        }
+    }
+
+    pub fn draw(
+        &mut self,
+        target: &mut RgbImage,
+        settings: &SpriteSettings,
+        slot: usize,
+        bbox: Rect,
+    ) {
+        if !settings.enabled || !settings.slots.get(slot).copied().unwrap_or(false) {
+            return;
+        }
+        let index = self.positions[slot] as usize;
+        let Some(frame) = self.frames.get(index) else {
+            return;
+        };
+        let w = bbox.width();
+        let (h, top) = match settings.anchor {
+            SpriteAnchor::Cover => (bbox.height(), bbox.top()),
+            SpriteAnchor::Above => {
+                // Keep the sprite's aspect ratio when it stands on the bbox
+                let h = (w as f32 * frame.height() as f32 / frame.width().max(1) as f32) as u32;
+                (h, bbox.top() - h as i32)
+            }
+        };
+        if w == 0 || h == 0 {
+            return;
+        }
+        // Rescaled only when the frame or the size changed; both decks draw the same one
+        let cached = &mut self.scaled[slot];
+        if !cached.as_ref().is_some_and(|c| c.frame == index && c.size == (w, h)) {
+            *cached = Some(ScaledFrame {
+                frame: index,
+                size: (w, h),
+                image: imageops::resize(frame, w, h, imageops::FilterType::Triangle),
+            });
+        }
+        if let Some(scaled) = cached {
+            blend_rgba(target, &scaled.image, bbox.left(), top, settings.blend);
+        }
+    }
+}
+
+// Straight-alpha `blend` of src onto dst at (x0, y0), clipped to dst
+fn blend_rgba(dst: &mut RgbImage, src: &RgbaImage, x0: i32, y0: i32, blend: BlendMode) {
+    let (dw, dh) = (dst.width() as i32, dst.height() as i32);
+    for (sx, sy, px) in src.enumerate_pixels() {
+        let (x, y) = (x0 + sx as i32, y0 + sy as i32);
+        let alpha = px[3] as u32;
+        if alpha == 0 || x < 0 || y < 0 || x >= dw || y >= dh {
+            continue;
+        }
+        let out = dst.get_pixel_mut(x as u32, y as u32);
+        blend.composite(&mut out.0, [px[0], px[1], px[2]], px[3]);
+    }
+}
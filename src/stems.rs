@@ -0,0 +1,239 @@
+// src/stems.rs
+use anyhow::{Context, Result};
+use async_channel::{Receiver, Sender};
+use log::{error, info, warn};
+use ndarray::{Array3, Axis};
+use ort::session::{builder::GraphOptimizationLevel, Session};
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::supervisor::{self, Heartbeat, StopSignal};
+
+// --- Source separation model (Demucs-style: [1, 2, N] stereo in, [1, S, 2, N] stems out) ---
+const MODEL_SAMPLE_RATE: u32 = 44_100;
+const CHUNK_SECONDS: f32 = 1.0; // Context the model sees per run
+const HOP_SECONDS: f32 = 0.25; // New audio between runs (stem levels update at this rate)
+const NORM_WINDOW_SIZE: usize = 40; // Runs in the moving-max normalization window
+const SMOOTHING_FACTOR: f32 = 0.5;
+
+// Output source order of the model (htdemucs convention)
+const MODEL_SOURCES: [Stem; 4] = [Stem::Drums, Stem::Bass, Stem::Other, Stem::Vocals];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stem {
+    Bass,
+    Drums,
+    Vocals,
+    Other,
+}
+
+pub const NUM_STEMS: usize = 3; // Stems exposed as analysis channels (Other is discarded)
+
+impl Stem {
+    // Slot in StemLevels, None for stems that aren't exposed
+    fn level_index(self) -> Option<usize> {
+        match self {
+            Stem::Bass => Some(0),
+            Stem::Drums => Some(1),
+            Stem::Vocals => Some(2),
+            Stem::Other => None,
+        }
+    }
+}
+
+// --- Latest normalized stem levels, written by the stem thread, read by the AudioProcessor ---
+#[derive(Debug, Clone, Default)]
+pub struct StemLevels(Arc<[AtomicU32; NUM_STEMS]>);
+
+impl StemLevels {
+    pub fn get(&self) -> [f32; NUM_STEMS] {
+        std::array::from_fn(|i| f32::from_bits(self.0[i].load(Ordering::Relaxed)))
+    }
+
+    fn set(&self, index: usize, value: f32) {
+        self.0[index].store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    fn clear(&self) {
+        for i in 0..NUM_STEMS {
+            self.set(i, 0.0);
+        }
+    }
+}
+
+// --- Handed to the AudioProcessor: where to send analysis audio and where to read levels ---
+#[derive(Debug, Clone)]
+pub struct StemTap {
+    pub sample_sender: Sender<(u32, Vec<f32>)>, // (sample rate, mono samples)
+    pub levels: StemLevels,
+}
+
+fn load_session(model_path: &Path) -> Result<Session> {
+    Session::builder()?
+        .with_optimization_level(GraphOptimizationLevel::Level3)?
+        .commit_from_file(model_path)
+        .with_context(|| format!("Failed to load stem model {}", model_path.display()))
+}
+
+// Linear interpolation; quality is plenty for level estimation
+fn resample_linear(input: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || input.is_empty() {
+        return input.to_vec();
+    }
+    let ratio = from_rate as f64 / to_rate as f64;
+    let out_len = (input.len() as f64 / ratio).floor() as usize;
+    (0..out_len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let idx = pos.floor() as usize;
+            let frac = (pos - idx as f64) as f32;
+            let a = input[idx.min(input.len() - 1)];
+            let b = input[(idx + 1).min(input.len() - 1)];
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+// Runs the model on one mono chunk, returns RMS of the last `tail` samples of each exposed stem
+fn separate_chunk(session: &Session, chunk: &[f32], tail: usize) -> Result<[f32; NUM_STEMS]> {
+    let n = chunk.len();
+    let mut stereo = Vec::with_capacity(n * 2);
+    stereo.extend_from_slice(chunk);
+    stereo.extend_from_slice(chunk);
+    let input = Array3::from_shape_vec((1, 2, n), stereo)?;
+    let outputs = session.run(ort::inputs![input]?)?;
+    let stems = outputs[0].try_extract_tensor::<f32>()?;
+    let stems = stems.index_axis(Axis(0), 0); // [S, 2, N]
+
+    let mut levels = [0.0f32; NUM_STEMS];
+    for (source_idx, source) in stems.axis_iter(Axis(0)).enumerate() {
+        let Some(level_idx) = MODEL_SOURCES.get(source_idx).and_then(|s| s.level_index()) else {
+            continue;
+        };
+        let len = source.len_of(Axis(1));
+        let start = len.saturating_sub(tail);
+        let mut sum_sq = 0.0f32;
+        let mut count = 0usize;
+        for channel in source.axis_iter(Axis(0)) {
+            for s in channel.iter().skip(start) {
+                sum_sq += s * s;
+                count += 1;
+            }
+        }
+        levels[level_idx] = if count > 0 { (sum_sq / count as f32).sqrt() } else { 0.0 };
+    }
+    Ok(levels)
+}
+
+// --- Stem thread: buffers mono analysis audio and runs the model on overlapping chunks ---
+pub fn start_stem_thread(
+    model_path: PathBuf,
+    sample_receiver: Receiver<(u32, Vec<f32>)>,
+    levels: StemLevels,
+    stop_signal: StopSignal,
+    heartbeat: Heartbeat,
+) -> JoinHandle<()> {
+    info!("Spawning stem separation thread ({})", model_path.display());
+    thread::spawn(move || {
+        supervisor::runtime().block_on(stem_loop(
+            model_path,
+            sample_receiver,
+            levels,
+            stop_signal,
+            heartbeat,
+        ))
+    })
+}
+
+async fn stem_loop(
+    model_path: PathBuf,
+    sample_receiver: Receiver<(u32, Vec<f32>)>,
+    levels: StemLevels,
+    stop_signal: StopSignal,
+    heartbeat: Heartbeat,
+) {
+    let session = match load_session(&model_path) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Stem separation disabled: {:#}", e);
+            levels.clear();
+            // Stay alive (beating) so the supervisor doesn't respawn into the same failure
+            while !stop_signal.is_stopped() {
+                heartbeat.beat();
+                tokio::select! {
+                    _ = sample_receiver.recv(), if !sample_receiver.is_closed() => {}
+                    _ = stop_signal.stopped() => break,
+                    _ = tokio::time::sleep(Duration::from_millis(200)) => {}
+                }
+            }
+            return;
+        }
+    };
+    info!("Stem model loaded.");
+
+    let chunk_len = (MODEL_SAMPLE_RATE as f32 * CHUNK_SECONDS) as usize;
+    let hop_len = (MODEL_SAMPLE_RATE as f32 * HOP_SECONDS) as usize;
+    let mut buffer: VecDeque<f32> = VecDeque::with_capacity(chunk_len * 2);
+    let mut new_samples = 0usize;
+    let mut recent_max: [VecDeque<f32>; NUM_STEMS] =
+        std::array::from_fn(|_| VecDeque::from(vec![1e-3; NORM_WINDOW_SIZE]));
+    let mut smoothed = [0.0f32; NUM_STEMS];
+
+    while !stop_signal.is_stopped() {
+        heartbeat.beat();
+        let received = tokio::select! {
+            msg = sample_receiver.recv() => msg,
+            _ = stop_signal.stopped() => break,
+            _ = tokio::time::sleep(Duration::from_millis(100)) => continue,
+        };
+        match received {
+            Ok((sample_rate, samples)) => {
+                let resampled = resample_linear(&samples, sample_rate, MODEL_SAMPLE_RATE);
+                new_samples += resampled.len();
+                buffer.extend(resampled);
+                while buffer.len() > chunk_len {
+                    buffer.pop_front();
+                }
+            }
+            Err(_) => {
+                info!("Stem sample source disconnected.");
+                break;
+            }
+        }
+        if buffer.len() < chunk_len || new_samples < hop_len {
+            continue;
+        }
+        // If inference is slower than real time, skip ahead rather than queueing up
+        new_samples = 0;
+        buffer.make_contiguous();
+        let chunk = buffer.as_slices().0;
+        match separate_chunk(&session, chunk, hop_len) {
+            Ok(raw) => {
+                for (i, window) in recent_max.iter_mut().enumerate() {
+                    window.push_back(raw[i].max(1e-6));
+                    if window.len() > NORM_WINDOW_SIZE {
+                        window.pop_front();
+                    }
+                    let max = window.iter().fold(0.0f32, |m, &v| m.max(v));
+                    let norm = (raw[i] / max).clamp(0.0, 1.0);
+                    smoothed[i] =
+                        smoothed[i] * (1.0 - SMOOTHING_FACTOR) + norm * SMOOTHING_FACTOR;
+                    levels.set(i, smoothed[i]);
+                }
+            }
+            Err(e) => {
+                warn!("Stem separation failed on chunk: {:#}", e);
+            }
+        }
+    }
+    levels.clear();
+    info!("Stem separation thread finished.");
+}
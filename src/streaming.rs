@@ -0,0 +1,265 @@
+// src/streaming.rs
+use anyhow::{bail, Context, Result};
+use crossbeam_channel::{bounded, Receiver, Sender, TrySendError};
+use egui::ColorImage;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{ErrorKind, Write},
+    net::TcpListener,
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::supervisor::StopSignal;
+
+const FRAME_QUEUE: usize = 2; // Frames waiting for ffmpeg; new ones are dropped while full
+const AUDIO_QUEUE: usize = 64;
+const AUDIO_GAP: Duration = Duration::from_millis(100); // Silence fills gaps longer than this
+const AUDIO_CONNECT_TIMEOUT: Duration = Duration::from_secs(5); // ffmpeg needs a moment to dial in
+const AUDIO_CONNECT_RETRY: Duration = Duration::from_millis(100);
+
+// --- RTMP push (Twitch, YouTube, ...) through an ffmpeg child process ---
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamConfig {
+    pub rtmp_url: Option<String>, // Full ingest URL including the stream key
+    pub ffmpeg_path: String,
+    pub video_bitrate_kbps: u32,
+    pub audio_bitrate_kbps: u32,
+    pub fps: u32, // Output rate; input frames are timestamped by wall clock
+    pub keyframe_interval_secs: f32, // Most services want 2
+    pub x264_preset: String,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            rtmp_url: None,
+            ffmpeg_path: "ffmpeg".to_string(),
+            video_bitrate_kbps: 4500,
+            audio_bitrate_kbps: 160,
+            fps: 30,
+            keyframe_interval_secs: 2.0,
+            x264_preset: "veryfast".to_string(),
+        }
+    }
+}
+
+// --- Captured audio for the stream; the audio processor only forwards while one is live ---
+#[derive(Debug, Clone)]
+pub struct StreamAudioTap {
+    active: Arc<AtomicBool>,
+    sender: Sender<(u32, Vec<f32>)>, // (sample rate, mono samples)
+    receiver: Receiver<(u32, Vec<f32>)>,
+}
+
+impl Default for StreamAudioTap {
+    fn default() -> Self {
+        let (sender, receiver) = bounded(AUDIO_QUEUE);
+        Self {
+            active: Arc::new(AtomicBool::new(false)),
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl StreamAudioTap {
+    pub fn forward(&self, sample_rate: u32, samples: &[f32]) {
+        if self.active.load(Ordering::Relaxed) {
+            let _ = self.sender.try_send((sample_rate, samples.to_vec()));
+        }
+    }
+}
+
+pub struct RtmpStream {
+    frame_sender: Sender<Arc<ColorImage>>,
+    size: [usize; 2],
+    child: Child,
+    audio_tap: StreamAudioTap,
+    stop_signal: StopSignal,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl RtmpStream {
+    // Video goes over ffmpeg's stdin as raw RGBA, audio over a loopback TCP socket as f32le
+    pub fn start(
+        config: &StreamConfig,
+        size: [usize; 2],
+        sample_rate: u32,
+        audio_tap: &StreamAudioTap,
+    ) -> Result<Self> {
+        let Some(url) = &config.rtmp_url else {
+            bail!("No RTMP URL configured (stream.rtmp_url)");
+        };
+        // ffmpeg connects to our listener, which stays bound so no one else can take the port
+        let audio_listener = TcpListener::bind("127.0.0.1:0")?;
+        let audio_port = audio_listener.local_addr()?.port();
+        let gop = ((config.fps as f32 * config.keyframe_interval_secs).round() as u32).max(1);
+        let video_rate = format!("{}k", config.video_bitrate_kbps);
+        let mut child = Command::new(&config.ffmpeg_path)
+            .args(["-hide_banner", "-loglevel", "error"])
+            .args([
+                "-f",
+                "rawvideo",
+                "-pix_fmt",
+                "rgba",
+                "-use_wallclock_as_timestamps",
+                "1",
+            ])
+            .args(["-s", &format!("{}x{}", size[0], size[1]), "-i", "pipe:0"])
+            .args(["-f", "f32le", "-ar", &sample_rate.to_string(), "-ac", "1"])
+            .args(["-i", &format!("tcp://127.0.0.1:{}", audio_port)])
+            .args([
+                "-c:v",
+                "libx264",
+                "-preset",
+                &config.x264_preset,
+                "-tune",
+                "zerolatency",
+            ])
+            .args(["-b:v", &video_rate, "-maxrate", &video_rate])
+            .args(["-bufsize", &format!("{}k", config.video_bitrate_kbps * 2)])
+            .args(["-g", &gop.to_string(), "-keyint_min", &gop.to_string()])
+            // yuv420p halves the chroma planes, so x264 needs even sides; pad odd ones by a pixel
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+            .args(["-r", &config.fps.to_string(), "-pix_fmt", "yuv420p"])
+            .args([
+                "-c:a",
+                "aac",
+                "-b:a",
+                &format!("{}k", config.audio_bitrate_kbps),
+            ])
+            .args(["-ar", "48000", "-f", "flv", url])
+            .stdin(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to launch {}", config.ffmpeg_path))?;
+        let stdin = child.stdin.take().context("ffmpeg stdin unavailable")?;
+
+        let stop_signal = StopSignal::new();
+        let (frame_sender, frame_receiver) = bounded::<Arc<ColorImage>>(FRAME_QUEUE);
+        let video_thread = thread::spawn(move || write_video(stdin, frame_receiver));
+        let audio_stop = stop_signal.clone();
+        let audio_receiver = audio_tap.receiver.clone();
+        while audio_receiver.try_recv().is_ok() {} // Stale audio from a previous stream
+        let audio_thread = thread::spawn(move || {
+            if let Err(e) = write_audio(audio_listener, sample_rate, audio_receiver, &audio_stop) {
+                error!("Stream audio stopped: {:#}", e);
+            }
+        });
+        audio_tap.active.store(true, Ordering::Relaxed);
+        info!(
+            "Streaming {}x{} @ {} fps, {} kbps to RTMP",
+            size[0], size[1], config.fps, config.video_bitrate_kbps
+        );
+        Ok(Self {
+            frame_sender,
+            size,
+            child,
+            audio_tap: audio_tap.clone(),
+            stop_signal,
+            threads: vec![video_thread, audio_thread],
+        })
+    }
+
+    pub fn size(&self) -> [usize; 2] {
+        self.size
+    }
+
+    // Never blocks the UI; if ffmpeg falls behind the newest frame is dropped
+    pub fn push_frame(&self, frame: Arc<ColorImage>) {
+        if let Err(TrySendError::Full(_)) = self.frame_sender.try_send(frame) {
+            debug!("Stream encoder behind; dropping a frame");
+        }
+    }
+
+    // Some(message) once ffmpeg has exited (bad URL, rejected key, network loss, ...)
+    pub fn exit_status(&mut self) -> Option<String> {
+        match self.child.try_wait() {
+            Ok(Some(status)) => Some(format!("ffmpeg exited ({})", status)),
+            Ok(None) => None,
+            Err(e) => Some(format!("ffmpeg status unknown: {}", e)),
+        }
+    }
+
+    // Returns at once; ffmpeg's flush and exit are waited for on a thread of their own
+    pub fn stop(self) {
+        self.audio_tap.active.store(false, Ordering::Relaxed);
+        self.stop_signal.stop();
+        // Closing stdin ends the video input; ffmpeg then flushes and exits
+        drop(self.frame_sender);
+        let (threads, mut child) = (self.threads, self.child);
+        thread::spawn(move || {
+            for handle in threads {
+                let _ = handle.join();
+            }
+            if let Err(e) = child.wait() {
+                warn!("Waiting for ffmpeg failed: {}", e);
+            }
+            info!("Stream stopped.");
+        });
+    }
+}
+
+fn write_video(mut stdin: ChildStdin, frames: Receiver<Arc<ColorImage>>) {
+    for frame in frames {
+        if let Err(e) = stdin.write_all(frame.as_raw()) {
+            error!("Stream video stopped: {}", e);
+            break;
+        }
+    }
+}
+
+fn write_audio(
+    listener: TcpListener,
+    sample_rate: u32,
+    samples: Receiver<(u32, Vec<f32>)>,
+    stop_signal: &StopSignal,
+) -> Result<()> {
+    let started = Instant::now();
+    listener.set_nonblocking(true)?;
+    let mut socket = loop {
+        match listener.accept() {
+            Ok((s, _)) => break s,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                if started.elapsed() >= AUDIO_CONNECT_TIMEOUT {
+                    bail!("ffmpeg never connected to the audio input");
+                }
+                if stop_signal.wait(AUDIO_CONNECT_RETRY) {
+                    return Ok(());
+                }
+            }
+            Err(e) => return Err(e).context("Accepting ffmpeg's audio connection failed"),
+        }
+    };
+    socket.set_nonblocking(false)?;
+    drop(listener);
+    let mut warned_rate = false;
+    while !stop_signal.is_stopped() {
+        let Ok((rate, chunk)) = samples.recv_timeout(AUDIO_GAP) else {
+            // No audio (or no audio processor): keep ffmpeg's muxer fed with silence
+            let silence = vec![0u8; (sample_rate as f32 * AUDIO_GAP.as_secs_f32()) as usize * 4];
+            socket.write_all(&silence)?;
+            continue;
+        };
+        if rate != sample_rate {
+            if !warned_rate {
+                warn!(
+                    "Stream audio at {} Hz, expected {} Hz; skipping",
+                    rate, sample_rate
+                );
+                warned_rate = true;
+            }
+            continue;
+        }
+        let bytes: Vec<u8> = chunk.iter().flat_map(|s| s.to_le_bytes()).collect();
+        socket.write_all(&bytes)?;
+    }
+    Ok(())
+}
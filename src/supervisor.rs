@@ -0,0 +1,278 @@
+// src/supervisor.rs
+use log::{error, info, warn};
+use std::{
+    any::Any,
+    io,
+    net::{SocketAddr, TcpListener as StdTcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+use tokio::{net::TcpListener, runtime::Runtime};
+use tokio_util::sync::CancellationToken;
+
+// --- Constants ---
+const STALL_THRESHOLD: Duration = Duration::from_secs(5); // No heartbeat for this long = stalled
+const INITIAL_RESTART_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
+// Shared epoch so heartbeats can be stored as plain millisecond counters
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+// --- Heartbeat: worker threads call beat() once per loop iteration ---
+#[derive(Debug, Clone)]
+pub struct Heartbeat(Arc<AtomicU64>);
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        let hb = Self(Arc::new(AtomicU64::new(0)));
+        hb.beat();
+        hb
+    }
+
+    pub fn beat(&self) {
+        self.0
+            .store(epoch().elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    fn age(&self) -> Duration {
+        let last = Duration::from_millis(self.0.load(Ordering::Relaxed));
+        epoch().elapsed().saturating_sub(last)
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// --- Shared async runtime ---
+// Worker loops are async and driven by `block_on` on their own threads, so CPU-heavy stages
+// never stall the runtime; its workers only drive timers, sockets and channel wake-ups.
+pub fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("async-io")
+            .enable_all()
+            .build()
+            .expect("Failed to start the async runtime")
+    })
+}
+
+// --- Stop signal: a cancellation token ---
+// Async loops await `stopped()` in a `select!` next to their inputs, so they wake the moment
+// there is work or a stop request; child signals stop with their parent.
+#[derive(Debug, Clone, Default)]
+pub struct StopSignal(CancellationToken);
+
+impl StopSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Stopped along with `self`, but can also be stopped on its own
+    pub fn child(&self) -> Self {
+        Self(self.0.child_token())
+    }
+
+    pub fn stop(&self) {
+        self.0.cancel();
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.is_cancelled()
+    }
+
+    // Resolves once stopped; use as a `select!` arm
+    pub async fn stopped(&self) {
+        self.0.cancelled().await
+    }
+
+    // Blocking sleep for plain threads: up to `timeout`, returning early (true) if stopped
+    pub fn wait(&self, timeout: Duration) -> bool {
+        if self.is_stopped() {
+            return true;
+        }
+        runtime().block_on(async {
+            tokio::select! {
+                _ = self.stopped() => true,
+                _ = tokio::time::sleep(timeout) => false,
+            }
+        })
+    }
+}
+
+// --- HTTP endpoints: accept through the runtime instead of polling a non-blocking listener ---
+pub fn async_listener(listener: StdTcpListener) -> io::Result<TcpListener> {
+    listener.set_nonblocking(true)?;
+    let _runtime = runtime().enter();
+    TcpListener::from_std(listener)
+}
+
+// Next connection, handed back as a blocking std stream for the request handlers
+pub async fn accept(listener: &TcpListener) -> io::Result<(TcpStream, SocketAddr)> {
+    let (stream, peer) = listener.accept().await?;
+    let stream = stream.into_std()?;
+    stream.set_nonblocking(false)?;
+    Ok((stream, peer))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SupervisorEvent {
+    Healthy,
+    Stalled(Duration),
+    Died(String),
+    Restarted(u32),
+    Stopped,
+}
+
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
+
+type SpawnFn = Box<dyn FnMut(Heartbeat) -> JoinHandle<()>>;
+
+// --- A worker thread plus the closure needed to respawn it with its last-known configuration ---
+pub struct SupervisedThread {
+    name: &'static str,
+    spawn_fn: SpawnFn,
+    handle: Option<JoinHandle<()>>,
+    heartbeat: Heartbeat,
+    stop_signal: StopSignal,
+    restarts: u32,
+    backoff: Duration,
+    restart_at: Option<Instant>,
+    stall_reported: bool,
+}
+
+impl SupervisedThread {
+    pub fn spawn(
+        name: &'static str,
+        stop_signal: StopSignal,
+        mut spawn_fn: impl FnMut(Heartbeat) -> JoinHandle<()> + 'static,
+    ) -> Self {
+        let heartbeat = Heartbeat::new();
+        let handle = spawn_fn(heartbeat.clone());
+        Self {
+            name,
+            spawn_fn: Box::new(spawn_fn),
+            handle: Some(handle),
+            heartbeat,
+            stop_signal,
+            restarts: 0,
+            backoff: INITIAL_RESTART_BACKOFF,
+            restart_at: None,
+            stall_reported: false,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.handle.as_ref().is_some_and(|h| !h.is_finished())
+    }
+
+    // Called periodically from the UI thread
+    pub fn check(&mut self) -> SupervisorEvent {
+        let stopping = self.stop_signal.is_stopped();
+
+        // --- Dead thread: join, log panic payload, schedule restart ---
+        if self.handle.as_ref().is_some_and(|h| h.is_finished()) {
+            let handle = self.handle.take().expect("checked above");
+            let reason = match handle.join() {
+                Ok(()) => "exited".to_string(),
+                Err(payload) => {
+                    let msg = panic_message(payload.as_ref());
+                    error!("{} thread panicked: {}", self.name, msg);
+                    format!("panicked: {}", msg)
+                }
+            };
+            if stopping {
+                info!("{} thread stopped ({}).", self.name, reason);
+                return SupervisorEvent::Stopped;
+            }
+            warn!(
+                "{} thread {}; restarting in {:?}",
+                self.name, reason, self.backoff
+            );
+            self.restart_at = Some(Instant::now() + self.backoff);
+            return SupervisorEvent::Died(reason);
+        }
+
+        // --- Pending restart ---
+        if let Some(at) = self.restart_at {
+            if stopping {
+                self.restart_at = None;
+                return SupervisorEvent::Stopped;
+            }
+            if Instant::now() >= at {
+                self.restart_at = None;
+                self.restarts += 1;
+                self.heartbeat = Heartbeat::new();
+                self.handle = Some((self.spawn_fn)(self.heartbeat.clone()));
+                self.backoff = (self.backoff * 2).min(MAX_RESTART_BACKOFF);
+                self.stall_reported = false;
+                info!("{} thread respawned (restart #{})", self.name, self.restarts);
+                return SupervisorEvent::Restarted(self.restarts);
+            }
+            return SupervisorEvent::Died("restart pending".to_string());
+        }
+
+        if self.handle.is_none() {
+            return SupervisorEvent::Stopped;
+        }
+
+        // --- Alive but silent: report stall (threads can't be killed, only flagged) ---
+        let age = self.heartbeat.age();
+        if age > STALL_THRESHOLD {
+            if !self.stall_reported {
+                warn!("{} thread has not reported a heartbeat for {:?}", self.name, age);
+                self.stall_reported = true;
+            }
+            return SupervisorEvent::Stalled(age);
+        }
+        if self.stall_reported {
+            info!("{} thread heartbeat recovered.", self.name);
+            self.stall_reported = false;
+        }
+        SupervisorEvent::Healthy
+    }
+
+    // Stops this thread alone, without waiting for it (its stop signal must be its own child)
+    pub fn stop(self) {
+        self.stop_signal.stop();
+        info!("{} thread replaced.", self.name);
+    }
+
+    // Blocking join used at shutdown (stop signal must already be set)
+    pub fn join(&mut self) {
+        self.restart_at = None;
+        if let Some(h) = self.handle.take() {
+            info!("Joining {}...", self.name);
+            if let Err(e) = h.join() {
+                error!("{} join err: {}", self.name, panic_message(e.as_ref()));
+            }
+        }
+    }
+}
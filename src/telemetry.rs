@@ -0,0 +1,145 @@
+// src/telemetry.rs
+// Opt-in heartbeat for unattended installs: POSTs a small JSON status to a configured URL on a
+// fixed interval, so a remote operator notices a crash (beats stop) or a dead camera (errors
+// in the beats) before visitors do.
+use anyhow::Result;
+use crossbeam_channel::bounded;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::metrics;
+use crate::supervisor::StopSignal;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_PENDING: usize = 2; // Beats queued behind a slow endpoint; newer ones are dropped
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    pub url: Option<String>, // None = off
+    pub interval_secs: u64,
+    pub name: String, // Tells installations apart on a shared endpoint
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            interval_secs: 60,
+            name: "sam_cam_bam".to_string(),
+        }
+    }
+}
+
+// --- Error state the UI thread knows about, shared with the reporting threads ---
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AppStatus {
+    pub camera_error: Option<String>,
+    pub model_error: Option<String>,
+    pub standby: bool, // Closed by the schedule; errors are expected to be absent
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StatusBoard(Arc<Mutex<AppStatus>>);
+
+impl StatusBoard {
+    pub fn publish(&self, status: AppStatus) {
+        if let Ok(mut current) = self.0.lock() {
+            *current = status;
+        }
+    }
+
+    pub fn get(&self) -> AppStatus {
+        self.0.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Beat<'a> {
+    name: &'a str,
+    version: &'static str,
+    timestamp_ms: u128,
+    uptime_secs: u64,
+    ui_fps: Option<f64>,
+    seg_fps: Option<f64>,
+    tracked_objects: Option<f64>,
+    #[serde(flatten)]
+    status: AppStatus,
+}
+
+fn post_beat(agent: &ureq::Agent, url: &str, body: &str) -> Result<()> {
+    agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(body)?;
+    Ok(())
+}
+
+// --- Reporter thread: one beat at start, then every `interval_secs` until stopped ---
+// Joining it is quick; the posts themselves run on a detached sender thread
+pub fn start_telemetry_thread(
+    config: &TelemetryConfig,
+    status: StatusBoard,
+    stop_signal: StopSignal,
+) -> Option<JoinHandle<()>> {
+    let url = config.url.clone()?;
+    let name = config.name.clone();
+    let interval = Duration::from_secs(config.interval_secs.max(1));
+    info!("Telemetry heartbeat to {} every {:?}", url, interval);
+    // Posts until the reporter drops its sender. Never joined: a request stuck on an
+    // unreachable endpoint must not hold up shutdown.
+    let (outbox_tx, outbox_rx) = bounded::<String>(MAX_PENDING);
+    thread::spawn(move || {
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+        let mut failing = false;
+        for body in outbox_rx {
+            // Log transitions only; a dead endpoint shouldn't flood the log every minute
+            match post_beat(&agent, &url, &body) {
+                Ok(()) if failing => {
+                    info!("Telemetry heartbeat delivered again.");
+                    failing = false;
+                }
+                Err(e) if !failing => {
+                    warn!("Telemetry heartbeat failed: {:#}", e);
+                    failing = true;
+                }
+                _ => {}
+            }
+        }
+    });
+    Some(thread::spawn(move || {
+        let started = Instant::now();
+        loop {
+            let registry = metrics::global();
+            let beat = Beat {
+                name: &name,
+                version: env!("CARGO_PKG_VERSION"),
+                timestamp_ms: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_millis())
+                    .unwrap_or(0),
+                uptime_secs: started.elapsed().as_secs(),
+                ui_fps: registry.value(metrics::UI_FPS),
+                seg_fps: registry.value(metrics::SEG_FPS),
+                tracked_objects: registry.value(metrics::TRACKED_OBJECTS),
+                status: status.get(),
+            };
+            // A full queue means the endpoint is still busy with older beats; skip this one
+            match serde_json::to_string(&beat) {
+                Ok(body) => {
+                    let _ = outbox_tx.try_send(body);
+                }
+                Err(e) => warn!("Telemetry heartbeat not serialized: {:#}", e),
+            }
+            if stop_signal.wait(interval) {
+                break;
+            }
+        }
+        info!("Telemetry thread finished.");
+    }))
+}
@@ -0,0 +1,301 @@
+// src/tempo.rs
+// Beat clock for tempo-quantized look changes. The tempo and phase come from an Ableton Link
+// session when there is one, else from the operator (tap tempo) or the Beat channel's onsets;
+// switches the operator asks for are held until the next beat/bar/phrase boundary so they land
+// on the music instead of mid-beat.
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const ONSET_LEVEL: f32 = 0.5; // Beat channel rising through this counts as an onset
+const ONSET_WINDOW: Duration = Duration::from_secs(8); // Onsets kept for the tempo estimate
+const MIN_ONSETS: usize = 4;
+const BPM_RANGE: (f32, f32) = (80.0, 160.0); // Detected tempi are folded into this octave
+const BEATS_PER_BAR: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum QuantizeGrid {
+    #[default]
+    Off,
+    Beat,
+    Bar,
+    Phrase, // Four bars
+}
+
+impl QuantizeGrid {
+    pub const ALL: [QuantizeGrid; 4] = [
+        QuantizeGrid::Off,
+        QuantizeGrid::Beat,
+        QuantizeGrid::Bar,
+        QuantizeGrid::Phrase,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QuantizeGrid::Off => "Immediately",
+            QuantizeGrid::Beat => "Next beat",
+            QuantizeGrid::Bar => "Next bar",
+            QuantizeGrid::Phrase => "Next phrase",
+        }
+    }
+
+    fn beats(self) -> u32 {
+        match self {
+            QuantizeGrid::Off => 0,
+            QuantizeGrid::Beat => 1,
+            QuantizeGrid::Bar => BEATS_PER_BAR,
+            QuantizeGrid::Phrase => 4 * BEATS_PER_BAR,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TempoClock {
+    manual_bpm: Option<f32>,
+    detected_bpm: Option<f32>,
+    anchor: Option<Instant>, // A downbeat; beats are counted from here
+    manual_anchor: bool,     // Set by the operator, so onsets don't move it
+    link: Option<(f32, Instant)>, // Link session tempo, and where its timeline has beat 0
+    onsets: VecDeque<Instant>,
+    beat_high: bool,
+}
+
+impl TempoClock {
+    // Tap tempo from the UI; None = follow the detected beats
+    pub fn set_bpm(&mut self, bpm: Option<f32>) {
+        self.manual_bpm = bpm.filter(|b| *b > 0.0);
+    }
+
+    pub fn mark_downbeat(&mut self, at: Instant) {
+        self.anchor = Some(at);
+        self.manual_anchor = true;
+    }
+
+    // Link tempo and the session beat at `at` (bar lines on multiples of the quantum);
+    // None = no session, back to tap tempo and onsets
+    pub fn set_link(&mut self, link: Option<(f32, f64)>, at: Instant) {
+        self.link = link.filter(|(bpm, _)| *bpm > 0.0).and_then(|(bpm, beat)| {
+            let offset = Duration::try_from_secs_f64(beat.abs() * 60.0 / bpm as f64).ok()?;
+            let zero = if beat >= 0.0 {
+                at.checked_sub(offset)
+            } else {
+                at.checked_add(offset)
+            };
+            Some((bpm, zero?))
+        });
+    }
+
+    pub fn bpm(&self) -> Option<f32> {
+        self.link
+            .map(|(bpm, _)| bpm)
+            .or(self.manual_bpm)
+            .or(self.detected_bpm)
+    }
+
+    // Fed the Beat channel once per loop
+    pub fn observe_beat(&mut self, level: f32, now: Instant) {
+        let onset = level >= ONSET_LEVEL && !self.beat_high;
+        self.beat_high = level >= ONSET_LEVEL;
+        if !onset {
+            return;
+        }
+        self.onsets.push_back(now);
+        while self.onsets.front().is_some_and(|t| now - *t > ONSET_WINDOW) {
+            self.onsets.pop_front();
+        }
+        self.detected_bpm = self.estimate_bpm();
+        match (self.anchor, self.bpm()) {
+            (None, _) => self.anchor = Some(now),
+            // Nudge the phase onto the onset without changing the beat count
+            (Some(anchor), Some(bpm)) if !self.manual_anchor => {
+                let period = 60.0 / bpm;
+                let beats = ((now - anchor).as_secs_f32() / period).round();
+                self.anchor = now.checked_sub(Duration::from_secs_f32(beats * period));
+            }
+            _ => {}
+        }
+    }
+
+    // Median inter-onset interval, folded into BPM_RANGE
+    fn estimate_bpm(&self) -> Option<f32> {
+        if self.onsets.len() < MIN_ONSETS {
+            return None;
+        }
+        let mut gaps: Vec<f32> = self
+            .onsets
+            .iter()
+            .zip(self.onsets.iter().skip(1))
+            .map(|(a, b)| (*b - *a).as_secs_f32())
+            .filter(|g| *g > 0.05)
+            .collect();
+        if gaps.is_empty() {
+            return None;
+        }
+        gaps.sort_by(f32::total_cmp);
+        let mut bpm = 60.0 / gaps[gaps.len() / 2];
+        while bpm < BPM_RANGE.0 {
+            bpm *= 2.0;
+        }
+        while bpm > BPM_RANGE.1 {
+            bpm /= 2.0;
+        }
+        Some(bpm)
+    }
+
+    // Grid cell `t` falls in, counted from the anchor (Link's beat 0 when in a session)
+    fn cell(&self, t: Instant, grid: QuantizeGrid) -> Option<i64> {
+        let anchor = self.link.map(|(_, zero)| zero).or(self.anchor)?;
+        let bpm = self.bpm()?;
+        if grid.beats() == 0 {
+            return None;
+        }
+        let secs = if t >= anchor {
+            (t - anchor).as_secs_f32()
+        } else {
+            -(anchor - t).as_secs_f32()
+        };
+        let beats = secs * bpm / 60.0;
+        Some((beats / grid.beats() as f32).floor() as i64)
+    }
+}
+
+// --- Look changes held for the next boundary ---
+#[derive(Debug)]
+pub struct SwitchQuantizer<M> {
+    pub grid: QuantizeGrid,
+    pub clock: TempoClock,
+    pending: Vec<M>,
+    since: Option<Instant>,
+}
+
+impl<M> Default for SwitchQuantizer<M> {
+    fn default() -> Self {
+        Self {
+            grid: QuantizeGrid::Off,
+            clock: TempoClock::default(),
+            pending: Vec::new(),
+            since: None,
+        }
+    }
+}
+
+impl<M> SwitchQuantizer<M> {
+    // Without a tempo there is nothing to wait for
+    pub fn active(&self) -> bool {
+        self.grid != QuantizeGrid::Off && self.clock.bpm().is_some()
+    }
+
+    pub fn defer(&mut self, msg: M, now: Instant) {
+        self.since.get_or_insert(now);
+        self.pending.push(msg);
+    }
+
+    // Everything held, once a boundary has passed since the first of it (or the tempo is gone)
+    pub fn release(&mut self, now: Instant) -> Vec<M> {
+        let Some(since) = self.since else {
+            return Vec::new();
+        };
+        let due = match (
+            self.clock.cell(since, self.grid),
+            self.clock.cell(now, self.grid),
+        ) {
+            (Some(a), Some(b)) => b > a,
+            _ => true,
+        };
+        if !due && self.active() {
+            return Vec::new();
+        }
+        self.since = None;
+        std::mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEAT: Duration = Duration::from_millis(500); // 120 BPM
+
+    // Rising edges of the Beat channel every `gap`, starting at `t0`
+    fn feed_onsets(clock: &mut TempoClock, t0: Instant, gap: Duration, count: u32) {
+        for i in 0..count {
+            let at = t0 + gap * i;
+            clock.observe_beat(1.0, at);
+            clock.observe_beat(0.0, at + gap / 4);
+        }
+    }
+
+    #[test]
+    fn estimates_and_folds_onset_tempo() {
+        let t0 = Instant::now();
+        let mut clock = TempoClock::default();
+        feed_onsets(&mut clock, t0, BEAT, MIN_ONSETS as u32 - 1);
+        assert_eq!(clock.bpm(), None);
+        feed_onsets(&mut clock, t0 + BEAT * 3, BEAT, 5);
+        assert!((clock.bpm().unwrap() - 120.0).abs() < 0.5);
+
+        // Sixteenths at 120 read as 480 BPM and fold back down
+        let mut clock = TempoClock::default();
+        feed_onsets(&mut clock, t0, BEAT / 4, 8);
+        assert!((clock.bpm().unwrap() - 120.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn link_overrides_tap_and_onsets() {
+        let t0 = Instant::now() + Duration::from_secs(10);
+        let mut clock = TempoClock::default();
+        feed_onsets(&mut clock, t0, BEAT, 6);
+        clock.set_bpm(Some(100.0));
+        assert_eq!(clock.bpm(), Some(100.0));
+        clock.set_link(Some((90.0, 8.0)), t0);
+        assert_eq!(clock.bpm(), Some(90.0));
+        clock.set_link(None, t0);
+        assert_eq!(clock.bpm(), Some(100.0));
+        clock.set_bpm(None);
+        assert!((clock.bpm().unwrap() - 120.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn link_phase_places_the_boundaries() {
+        let t0 = Instant::now() + Duration::from_secs(10);
+        let mut clock = TempoClock::default();
+        // Half a beat before a bar line of the session
+        clock.set_link(Some((120.0, 3.5)), t0);
+        let bar = clock.cell(t0, QuantizeGrid::Bar).unwrap();
+        assert_eq!(clock.cell(t0 + BEAT * 2 / 5, QuantizeGrid::Bar), Some(bar));
+        assert_eq!(clock.cell(t0 + BEAT * 3 / 5, QuantizeGrid::Bar), Some(bar + 1));
+        assert_eq!(clock.cell(t0, QuantizeGrid::Off), None);
+    }
+
+    #[test]
+    fn holds_switches_until_the_boundary() {
+        let t0 = Instant::now() + Duration::from_secs(10);
+        let mut quantizer = SwitchQuantizer::default();
+        quantizer.grid = QuantizeGrid::Beat;
+        assert!(!quantizer.active()); // No tempo yet
+        quantizer.clock.set_link(Some((120.0, 0.25)), t0);
+        assert!(quantizer.active());
+
+        quantizer.defer(1, t0);
+        quantizer.defer(2, t0 + BEAT / 10);
+        assert!(quantizer.release(t0 + BEAT / 2).is_empty());
+        assert_eq!(quantizer.release(t0 + BEAT * 4 / 5), vec![1, 2]);
+        assert!(quantizer.release(t0 + BEAT * 2).is_empty());
+    }
+
+    #[test]
+    fn releases_at_once_without_a_tempo() {
+        let t0 = Instant::now();
+        let mut quantizer = SwitchQuantizer::default();
+        quantizer.grid = QuantizeGrid::Phrase;
+        quantizer.defer("look", t0);
+        assert_eq!(quantizer.release(t0), vec!["look"]);
+
+        // Tempo gone while holding: nothing left to wait for
+        quantizer.clock.set_link(Some((120.0, 0.0)), t0);
+        quantizer.defer("look", t0);
+        assert!(quantizer.release(t0 + BEAT).is_empty());
+        quantizer.clock.set_link(None, t0);
+        assert_eq!(quantizer.release(t0 + BEAT), vec!["look"]);
+    }
+}
@@ -0,0 +1,460 @@
+// src/timecode.rs
+// Chasing a show's master clock: SMPTE LTC decoded from an audio input, or MIDI timecode
+// (quarter-frame and full-frame messages). Both feed one shared `TimecodeClock`.
+use anyhow::Result;
+use cpal::Stream;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::{atomic::AtomicBool, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::live_audio;
+
+const CHASE_TIMEOUT: Duration = Duration::from_millis(500); // No timecode for this long = stopped
+const LTC_BITS_PER_FRAME: usize = 80;
+const LTC_SYNC_WORD: u128 = 0xBFFC; // 0011111111111101, as received LSB-first
+const LTC_HYSTERESIS: f32 = 0.02; // Zero-crossing dead band, in sample units
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimecodeConfig {
+    pub ltc_device: Option<String>, // Input device carrying LTC; None = MIDI timecode only
+    pub ltc_channel: usize,         // Zero-based channel on that device
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimecodeSource {
+    Ltc,
+    Mtc,
+}
+
+impl TimecodeSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            TimecodeSource::Ltc => "LTC",
+            TimecodeSource::Mtc => "MTC",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub fps: f32, // Nominal: 24, 25 or 30 (29.97 drop-frame counts as 30)
+    pub drop_frame: bool,
+}
+
+impl Timecode {
+    // Position in the show; drop-frame labels are treated as nominal 30 fps
+    pub fn as_secs(&self) -> f64 {
+        self.hours as f64 * 3600.0
+            + self.minutes as f64 * 60.0
+            + self.seconds as f64
+            + self.frames as f64 / self.fps as f64
+    }
+
+    // "HH:MM:SS:FF" ("HH:MM:SS;FF" also accepted), frames counted at the chased source's rate
+    pub fn parse_secs(text: &str, fps: f32) -> Option<f64> {
+        let parts: Vec<u8> = text
+            .split([':', ';'])
+            .map(|p| p.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        let [hours, minutes, seconds, frames] = parts[..] else {
+            return None;
+        };
+        if frames as f32 >= fps.round() {
+            return None;
+        }
+        Some(
+            Timecode {
+                hours,
+                minutes,
+                seconds,
+                frames,
+                fps,
+                drop_frame: false,
+            }
+            .as_secs(),
+        )
+    }
+
+    // MTC/LTC rate codes
+    fn rate_from_code(code: u8) -> (f32, bool) {
+        match code & 0x03 {
+            0 => (24.0, false),
+            1 => (25.0, false),
+            2 => (30.0, true),
+            _ => (30.0, false),
+        }
+    }
+
+    fn advanced(mut self, frames: u8) -> Self {
+        let fps = self.fps.round() as u8;
+        self.frames += frames;
+        while self.frames >= fps {
+            self.frames -= fps;
+            self.seconds += 1;
+        }
+        if self.seconds >= 60 {
+            self.seconds -= 60;
+            self.minutes += 1;
+        }
+        if self.minutes >= 60 {
+            self.minutes -= 60;
+            self.hours = (self.hours + 1) % 24;
+        }
+        self
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sep = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours, self.minutes, self.seconds, sep, self.frames
+        )
+    }
+}
+
+// --- Latest reading from either source, extrapolated between updates ---
+#[derive(Debug, Clone, Default)]
+pub struct TimecodeClock {
+    latest: Arc<Mutex<Option<(Timecode, TimecodeSource, Instant)>>>,
+}
+
+impl TimecodeClock {
+    pub fn update(&self, timecode: Timecode, source: TimecodeSource) {
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = Some((timecode, source, Instant::now()));
+        }
+    }
+
+    // (last timecode, source, extrapolated show seconds); None once the clock has stopped
+    pub fn now(&self) -> Option<(Timecode, TimecodeSource, f64)> {
+        let latest = *self.latest.lock().ok()?;
+        let (timecode, source, at) = latest?;
+        let since = at.elapsed();
+        (since < CHASE_TIMEOUT)
+            .then(|| (timecode, source, timecode.as_secs() + since.as_secs_f64()))
+    }
+}
+
+// --- MIDI timecode: eight quarter-frame pieces make one time, spanning two frames ---
+#[derive(Debug, Default)]
+pub struct MtcDecoder {
+    pieces: [u8; 8],
+    received: u8, // Bit per piece seen since piece 0
+}
+
+impl MtcDecoder {
+    pub fn quarter_frame(&mut self, data: u8) -> Option<Timecode> {
+        let piece = (data >> 4) as usize & 0x07;
+        if piece == 0 {
+            self.received = 0;
+        }
+        self.pieces[piece] = data & 0x0F;
+        self.received |= 1 << piece;
+        if piece != 7 || self.received != 0xFF {
+            return None;
+        }
+        let p = &self.pieces;
+        let (fps, drop_frame) = Timecode::rate_from_code(p[7] >> 1);
+        let timecode = Timecode {
+            frames: p[0] | (p[1] & 0x01) << 4,
+            seconds: p[2] | (p[3] & 0x03) << 4,
+            minutes: p[4] | (p[5] & 0x03) << 4,
+            hours: p[6] | (p[7] & 0x01) << 4,
+            fps,
+            drop_frame,
+        };
+        // The pieces describe the frame at piece 0, which is two frames ago by now
+        Some(timecode.advanced(2))
+    }
+
+    // F0 7F <device> 01 01 hh mm ss ff F7, sent on locate/seek
+    pub fn full_frame(hours: u8, minutes: u8, seconds: u8, frames: u8) -> Timecode {
+        let (fps, drop_frame) = Timecode::rate_from_code(hours >> 5);
+        Timecode {
+            hours: hours & 0x1F,
+            minutes: minutes & 0x3F,
+            seconds: seconds & 0x3F,
+            frames: frames & 0x1F,
+            fps,
+            drop_frame,
+        }
+    }
+}
+
+// --- LTC: biphase-mark audio. Every bit starts with a transition; a 1 has another mid-bit ---
+#[derive(Debug)]
+pub struct LtcDecoder {
+    sample_rate: u32,
+    high: bool,
+    since_transition: f32,
+    bit_len: f32, // Adapted to the incoming rate
+    half_pending: bool,
+    bits: u128,
+}
+
+impl LtcDecoder {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            high: false,
+            since_transition: 0.0,
+            bit_len: sample_rate as f32 / (30.0 * LTC_BITS_PER_FRAME as f32),
+            half_pending: false,
+            bits: 0,
+        }
+    }
+
+    // Feeds mono samples; returns the last complete frame found in them, if any
+    pub fn push(&mut self, samples: &[f32]) -> Option<Timecode> {
+        let mut decoded = None;
+        for &s in samples {
+            self.since_transition += 1.0;
+            let flipped = if self.high {
+                s < -LTC_HYSTERESIS
+            } else {
+                s > LTC_HYSTERESIS
+            };
+            if !flipped {
+                continue;
+            }
+            self.high = !self.high;
+            let interval = std::mem::take(&mut self.since_transition);
+            if interval > self.bit_len * 0.75 {
+                // Long: a 0 bit (a stray half before it means we were out of phase)
+                self.half_pending = false;
+                self.bit_len = self.bit_len * 0.9 + interval * 0.1;
+                if let Some(tc) = self.push_bit(false) {
+                    decoded = Some(tc);
+                }
+            } else if self.half_pending {
+                self.half_pending = false;
+                self.bit_len = self.bit_len * 0.9 + interval * 2.0 * 0.1;
+                if let Some(tc) = self.push_bit(true) {
+                    decoded = Some(tc);
+                }
+            } else {
+                self.half_pending = true;
+            }
+            // Keep the estimate sane across dropouts and rate changes
+            let nominal = self.sample_rate as f32 / LTC_BITS_PER_FRAME as f32;
+            self.bit_len = self.bit_len.clamp(nominal / 31.0, nominal / 23.0);
+        }
+        decoded
+    }
+
+    fn push_bit(&mut self, bit: bool) -> Option<Timecode> {
+        let top = LTC_BITS_PER_FRAME - 1;
+        self.bits = (self.bits >> 1) | ((bit as u128) << top);
+        if (self.bits >> 64) & 0xFFFF != LTC_SYNC_WORD {
+            return None;
+        }
+        let field = |start: usize, len: usize| ((self.bits >> start) & ((1 << len) - 1)) as u8;
+        let frames = field(0, 4) + field(8, 2) * 10;
+        let drop_frame = field(10, 1) == 1;
+        let seconds = field(16, 4) + field(24, 3) * 10;
+        let minutes = field(32, 4) + field(40, 3) * 10;
+        let hours = field(48, 4) + field(56, 2) * 10;
+        if frames >= 30 || seconds >= 60 || minutes >= 60 || hours >= 24 {
+            return None;
+        }
+        // LTC carries no rate code: infer it from the tracked bit length
+        let rate = self.sample_rate as f32 / (self.bit_len * LTC_BITS_PER_FRAME as f32);
+        let fps = if rate < 24.5 {
+            24.0
+        } else if rate < 27.5 {
+            25.0
+        } else {
+            30.0
+        };
+        let timecode = Timecode {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            fps,
+            drop_frame,
+        };
+        // The sync word ends the frame, so the one it labels has just finished playing
+        Some(timecode.advanced(1))
+    }
+}
+
+// Opens the configured LTC input, if any; the returned stream must be kept alive
+pub fn start_ltc_capture(
+    config: &TimecodeConfig,
+    stop_signal: Arc<AtomicBool>,
+    clock: TimecodeClock,
+) -> Result<Option<Stream>> {
+    let Some(device) = &config.ltc_device else {
+        return Ok(None);
+    };
+    let mut decoder: Option<LtcDecoder> = None;
+    let stream = live_audio::start_channel_capture(
+        device,
+        config.ltc_channel,
+        stop_signal,
+        move |rate, samples| {
+            if !decoder.as_ref().is_some_and(|d| d.sample_rate == rate) {
+                decoder = Some(LtcDecoder::new(rate));
+            }
+            if let Some(timecode) = decoder.as_mut().and_then(|d| d.push(samples)) {
+                clock.update(timecode, TimecodeSource::Ltc);
+            }
+        },
+    )?;
+    info!(
+        "Chasing LTC on '{}' channel {}",
+        device,
+        config.ltc_channel + 1
+    );
+    Ok(Some(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 48_000;
+
+    fn timecode(hours: u8, minutes: u8, seconds: u8, frames: u8, fps: f32) -> Timecode {
+        Timecode {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            fps,
+            drop_frame: false,
+        }
+    }
+
+    // The eight quarter-frame messages for one time, piece 0 first
+    fn quarter_frames(tc: Timecode, rate_code: u8) -> [u8; 8] {
+        let nibbles = [
+            tc.frames & 0x0F,
+            tc.frames >> 4,
+            tc.seconds & 0x0F,
+            tc.seconds >> 4,
+            tc.minutes & 0x0F,
+            tc.minutes >> 4,
+            tc.hours & 0x0F,
+            (tc.hours >> 4) | rate_code << 1,
+        ];
+        std::array::from_fn(|piece| (piece as u8) << 4 | nibbles[piece])
+    }
+
+    // Biphase-mark encodes whole LTC frames at ±0.5, ending on the next bit's leading edge
+    fn ltc_samples(frames: &[Timecode]) -> Vec<f32> {
+        let bit_len = (SAMPLE_RATE as f32 / (frames[0].fps * LTC_BITS_PER_FRAME as f32)) as usize;
+        let mut level = -0.5;
+        let mut samples = Vec::new();
+        for tc in frames {
+            let mut bits: u128 = LTC_SYNC_WORD << 64;
+            let mut put = |start: usize, value: u8| bits |= (value as u128) << start;
+            put(0, tc.frames % 10);
+            put(8, tc.frames / 10);
+            put(16, tc.seconds % 10);
+            put(24, tc.seconds / 10);
+            put(32, tc.minutes % 10);
+            put(40, tc.minutes / 10);
+            put(48, tc.hours % 10);
+            put(56, tc.hours / 10);
+            for i in 0..LTC_BITS_PER_FRAME {
+                level = -level;
+                if bits >> i & 1 == 1 {
+                    samples.resize(samples.len() + bit_len / 2, level);
+                    level = -level;
+                    samples.resize(samples.len() + bit_len / 2, level);
+                } else {
+                    samples.resize(samples.len() + bit_len, level);
+                }
+            }
+        }
+        samples.push(-level);
+        samples
+    }
+
+    #[test]
+    fn parses_show_times() {
+        assert_eq!(Timecode::parse_secs("01:00:12:15", 30.0), Some(3612.5));
+        assert_eq!(Timecode::parse_secs("00:00:01;15", 30.0), Some(1.5));
+        assert_eq!(
+            Timecode::parse_secs(" 00 : 02 : 00 : 00 ", 30.0),
+            Some(120.0)
+        );
+        assert_eq!(Timecode::parse_secs("00:02:00", 30.0), None);
+        assert_eq!(Timecode::parse_secs("00:02:xx:00", 30.0), None);
+        // Same label, other rates
+        assert_eq!(Timecode::parse_secs("00:00:01:12", 24.0), Some(1.5));
+        assert_eq!(Timecode::parse_secs("00:00:01:20", 25.0), Some(1.8));
+        assert_eq!(Timecode::parse_secs("00:00:01:24", 25.0), Some(1.96));
+        assert_eq!(Timecode::parse_secs("00:00:01:25", 25.0), None);
+    }
+
+    #[test]
+    fn decodes_mtc_sequence() {
+        let mut decoder = MtcDecoder::default();
+        let messages = quarter_frames(timecode(1, 2, 3, 4, 25.0), 1);
+        for &data in &messages[..7] {
+            assert_eq!(decoder.quarter_frame(data), None);
+        }
+        let decoded = decoder.quarter_frame(messages[7]);
+        assert_eq!(decoded, Some(timecode(1, 2, 3, 6, 25.0)));
+
+        // Catching up mid-sequence waits for the next piece 0
+        let mut decoder = MtcDecoder::default();
+        for &data in &messages[4..] {
+            assert_eq!(decoder.quarter_frame(data), None);
+        }
+        let messages = quarter_frames(timecode(0, 59, 59, 28, 30.0), 3);
+        for &data in &messages[..7] {
+            assert_eq!(decoder.quarter_frame(data), None);
+        }
+        let decoded = decoder.quarter_frame(messages[7]);
+        assert_eq!(decoded, Some(timecode(1, 0, 0, 0, 30.0)));
+    }
+
+    #[test]
+    fn decodes_mtc_full_frame_rate() {
+        let tc = MtcDecoder::full_frame(0x40 | 10, 20, 30, 12);
+        assert_eq!((tc.hours, tc.minutes, tc.seconds, tc.frames), (10, 20, 30, 12));
+        assert_eq!((tc.fps, tc.drop_frame), (30.0, true));
+        assert_eq!(MtcDecoder::full_frame(0, 0, 0, 0).fps, 24.0);
+    }
+
+    #[test]
+    fn decodes_ltc_at_30fps() {
+        let mut decoder = LtcDecoder::new(SAMPLE_RATE);
+        let frames = [timecode(1, 2, 3, 4, 30.0), timecode(1, 2, 3, 5, 30.0)];
+        let decoded = decoder.push(&ltc_samples(&frames));
+        assert_eq!(decoded, Some(timecode(1, 2, 3, 6, 30.0)));
+    }
+
+    #[test]
+    fn decodes_ltc_at_25fps_across_midnight() {
+        let mut decoder = LtcDecoder::new(SAMPLE_RATE);
+        let frames: Vec<_> = (22..25).map(|f| timecode(23, 59, 59, f, 25.0)).collect();
+        let samples = ltc_samples(&frames);
+        let (first, rest) = samples.split_at(samples.len() / 2); // Chunking doesn't matter
+        decoder.push(first);
+        assert_eq!(decoder.push(rest), Some(timecode(0, 0, 0, 0, 25.0)));
+    }
+
+    #[test]
+    fn ignores_noise_below_hysteresis() {
+        let mut decoder = LtcDecoder::new(SAMPLE_RATE);
+        let hiss: Vec<f32> = (0..4800).map(|i| if i % 2 == 0 { 0.01 } else { -0.01 }).collect();
+        assert_eq!(decoder.push(&hiss), None);
+    }
+}
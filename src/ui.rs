@@ -1,10 +1,16 @@
 // src/ui.rs
 use cpal::{traits::StreamTrait, Stream};
-use crossbeam_channel::{bounded, unbounded, Receiver, Sender, TryRecvError};
-use egui::{widgets, Align, Color32, ImageData, Layout, TextureHandle, TextureOptions, Vec2};
+use crossbeam_channel::{bounded, unbounded, Receiver, TryRecvError};
+use egui::{
+    widgets, Align, Color32, ImageData, Layout, Pos2, Rect, Sense, Stroke, StrokeKind,
+    TextureHandle, TextureOptions, Vec2,
+};
 use log::{error, info, warn};
 use nokhwa::utils::{CameraIndex, Resolution};
 use std::{
+    collections::HashMap,
+    future::Future,
+    path::PathBuf,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -14,13 +20,85 @@ use std::{
 };
 
 use crate::{
-    camera::{self},
-    live_audio,
-    music::{self},
-    segmentation::{self, SegmentationThreadMsg, UserInteractionSegMsg, MAX_TRACKS},
+    alerting::Alerter,
+    audio_record::{self, AudioRecordTap, WavRecorder},
+    automation::{
+        AutomationEvent, AutomationPlayer, AutomationRecorder, ParamSnapshot, Preset,
+        SlotSelection, UndoStack, UndoStep,
+    },
+    camera::{self, CameraControl, CameraThreadMsg, LatestFrame},
+    cli::LaunchOptions,
+    config::{AppConfig, WatchdogConfig},
+    control::{ControlAction, ControlServer, ControlState},
+    cues::{Cue, CueList, CuePlayer},
+    damage::{DamageRect, FrameDamage},
+    deck::{EffectLayer, Look, MAX_SLOT_LAYERS},
+    diagnostics::{self, DiagnosticResult, DiagnosticStatus, DiagnosticStep, ModelReportSlot},
+    ensemble::EnsembleConfig,
+    file_source::{self, FileSource, FileSourceConfig, FileSourceSinks},
+    gamepad::{GamepadCommand, GamepadInput},
+    i18n::{self, tr, Language},
+    latency::{Calibration, OnsetProbe},
+    live_audio::{self, MonitorControl},
+    keyed::{KeyedCodec, KeyedConfig},
+    link::LinkSession,
+    logging,
+    loopback::LoopbackWizard,
+    lut::{self, LutSettings},
+    mask_edit::{BrushMode, BrushStroke, MAX_DABS},
+    matting::MatteSettings,
+    metrics,
+    midi::{self, MidiInputs, MidiMessage},
+    music::{
+        self, AnalysisBackend, AnalysisChannel, AnalysisControl, AnalysisSettings, BandMeter,
+        FftSettings, Normalization, WindowFunction, METERED_CHANNELS,
+    },
+    overlay::{HudSettings, LyricTrack, TextOverlaySettings},
+    pacing::FramePacing,
+    paint_layer::{PaintLayer, PaintLayerRenderer},
+    pipeline::{self, ChannelConfig, ChannelStats, LatestSlot, PolicySender, TryRecvError},
+    post::{BlackoutFader, BloomSettings, PostChain, ToneCurve, ToneMapSettings},
+    plugin::PluginInfo,
+    preview::PreviewServer,
+    profile::AppProfile,
+    project::{self, Project, ProjectDevices, ProjectLayout},
+    schedule::Scheduler,
+    tempo::QuantizeGrid,
+    response::{GateSettings, ResponseCurve, ResponseCurves, GATED_CHANNELS},
+    visuals::{
+        BlendMode, DrumFxSettings, OutlineStyle, ShimmerSettings, StyleParams, VisualQuality,
+        VisualStyle,
+    },
+    warp::CornerPin,
+    permissions::{self, MediaKind, PermissionStatus},
+    segmentation::{
+        self, ComposedFrame, InferenceCadence, NormalizedRect, PointPrompt, SegmentationThreadMsg,
+        UserInteractionSegMsg, MAX_TRACKS,
+    },
+    session,
+    sprite::{SpriteAnchor, SpriteSettings},
+    stems,
+    streaming::{RtmpStream, StreamAudioTap, StreamConfig},
+    supervisor::{self, StopSignal, SupervisedThread, SupervisorEvent},
+    telemetry::{self, AppStatus, StatusBoard},
+    timecode::{self, MtcDecoder, TimecodeClock, TimecodeSource},
 };
 
 const FPS_UPDATE_INTERVAL: Duration = Duration::from_millis(500);
+const SELF_TEST_DONE_KEY: &str = "self_test_done";
+const TIMECODE_DRIFT_SECS: f64 = 0.1; // Past MTC/LTC jitter; beyond this the position is resent
+const LINK_DRIFT_BEATS: f64 = 0.02; // Link phase error before the beat is resent
+const CORNER_PIN_KEY: &str = "corner_pin";
+const LANGUAGE_KEY: &str = "language";
+const PROFILE_KEY: &str = "profile";
+const AUDIO_INPUT_KEY: &str = "audio_input";
+const RECENT_PROJECTS_KEY: &str = "recent_projects";
+const MODEL_FILE_KEY: &str = "model_file";
+const PIN_HANDLE_RADIUS: f32 = 8.0;
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_INTENSITY_GAIN: f32 = 3.0;
+const PERMISSION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const FILE_FEEDBACK_DURATION: Duration = Duration::from_secs(5);
 #[derive(Debug, Clone, PartialEq)]
 enum LiveAudioStatus {
     Initializing,
@@ -31,125 +109,4375 @@ enum LiveAudioStatus {
 
 pub struct WebcamAppUI {
     texture: Option<TextureHandle>,
-    seg_to_ui_rx: Receiver<SegmentationThreadMsg>,
-    _user_interaction_tx: Sender<UserInteractionSegMsg>,
-    cam_thread_handle: Option<JoinHandle<()>>,
-    cam_stop_signal: Arc<AtomicBool>,
-    seg_thread_handle: Option<JoinHandle<()>>,
-    seg_stop_signal: Arc<AtomicBool>,
-    audio_capture_thread_handle: Option<Stream>,
+    texture_stale: bool, // Holds a passthrough frame, so the next composited one goes in whole
+    partial_uploads: bool,
+    visual_quality: VisualQuality,
+    paint_layer: bool,
+    matte: MatteSettings,
+    paint_frame: Option<Arc<PaintLayer>>, // Effects to paint over the current texture
+    paint_renderer: PaintLayerRenderer,
+    seg_to_ui_rx: pipeline::Receiver<SegmentationThreadMsg>,
+    seg_frames: LatestSlot<ComposedFrame>,
+    last_seg_frame_seq: u64, // Damage only applies on top of the directly preceding frame
+    band_meters: Option<[BandMeter; METERED_CHANNELS.len()]>, // From the latest composed frame
+    user_interaction_tx: PolicySender<UserInteractionSegMsg>,
+    slot_routing: [AnalysisChannel; MAX_TRACKS],
+    slot_styles: [VisualStyle; MAX_TRACKS],
+    slot_params: [StyleParams; MAX_TRACKS],
+    slot_outlines: [OutlineStyle; MAX_TRACKS],
+    slot_layers: [Vec<EffectLayer>; MAX_TRACKS],
+    background_layers: Vec<EffectLayer>,
+    outline_blend: BlendMode,
+    shimmer: ShimmerSettings,
+    drum_fx: DrumFxSettings,
+    bloom: BloomSettings,
+    post_chain: PostChain,
+    text_overlay: TextOverlaySettings,
+    lyrics: Option<LyricTrack>,
+    lrc_path: String,
+    lyrics_error: Option<String>,
+    sprite: SpriteSettings,
+    sprite_path: String,
+    sprite_grid: [u32; 2],
+    lut: LutSettings,
+    tone_map: ToneMapSettings,
+    lut_path: String,
+    lut_files: Vec<PathBuf>,
+    hud: HudSettings,
+    countdown_input: String,
+    display_scale: f32,
+    inference_cadence: InferenceCadence,
+    frame_pacing: FramePacing,
+    channel_stats: Vec<ChannelStats>,
+    shutdown: StopSignal, // Parent of every worker thread's stop signal
+    metrics_thread: Option<JoinHandle<()>>,
+    status_board: StatusBoard, // Error state as last published for telemetry and alerts
+    telemetry_thread: Option<JoinHandle<()>>,
+    alerter: Option<Alerter>,
+    preview: Option<PreviewServer>,
+    control: Option<ControlServer>, // Companion / Stream Deck endpoint
+    // --- RTMP output: started on the next frame after "Go live" so its size is known ---
+    stream_config: StreamConfig,
+    keyed_config: KeyedConfig,
+    keyed_recording: Option<String>, // File being written, reported by the segmentation thread
+    stream_audio_tap: StreamAudioTap,
+    rtmp_stream: Option<RtmpStream>,
+    go_live: bool,
+    stream_error: Option<String>,
+    // --- Cues and MIDI ---
+    cue_player: CuePlayer,
+    cue_path: String,
+    cue_error: Option<String>,
+    midi_rx: Receiver<MidiMessage>,
+    midi_inputs: Option<MidiInputs>,
+    // --- Timecode chase (LTC audio input and/or MTC on the MIDI inputs) ---
+    timecode_clock: TimecodeClock,
+    mtc_decoder: MtcDecoder,
+    ltc_stream: Option<Stream>,
+    timecode_error: Option<String>,
+    timecode_sent: Option<(f64, Instant)>, // Last position sent to segmentation, for drift checks
+    chased_cue: Option<usize>,
+    // --- Ableton Link song position ---
+    link: Option<LinkSession>,
+    link_position: Option<f64>, // Beats since the Link transport started
+    link_sent: Option<(f64, f64, Instant)>, // Tempo and beat last sent to segmentation, and when
+    chased_beat_cue: Option<usize>,
+    // --- A/B crossfader (the regular style/post controls edit deck A) ---
+    deck_b: Look,
+    crossfader: f32,
+    crossfader_cc: Option<(u8, u8)>, // (channel, controller)
+    master_mix: f32,                 // 0 camera, 1 composite, 2 effects only
+    learning_cc: bool,
+    // --- Automation takes, undo and presets ---
+    automation_recorder: AutomationRecorder,
+    automation_player: AutomationPlayer,
+    automation_take: Vec<AutomationEvent>,
+    undo: UndoStack,
+    preset_path: String,
+    preset_error: Option<String>,
+    // --- Watchdog: raw pass-through while inference stalls ---
+    watchdog: WatchdogConfig,
+    latest_raw_frame: LatestFrame,
+    last_seg_frame_time: Option<Instant>, // None until the first frame, while the model loads
+    last_passthrough_capture: Option<Instant>,
+    model_lagging: bool,
+    cam_thread: Option<SupervisedThread>,
+    // --- Opening hours: outside them the camera is released and the output goes black ---
+    scheduler: Option<Scheduler>,
+    camera_control: CameraControl,
+    standby: bool,
+    // Outputs running when the schedule closed; they resume at opening
+    resume_live: bool,
+    resume_recording: bool,
+    resume_keyed: bool,
+    last_schedule_check: Instant,
+    seg_thread: Option<SupervisedThread>,
+    audio_capture_streams: Vec<Stream>,
+    audio_sources: Vec<live_audio::AudioSourceControl>,
+    // --- Pass-through monitor of the captured audio ---
+    monitor: MonitorControl,
+    monitor_stream: Option<Stream>,
+    monitor_device: Option<String>, // None = the default output
+    monitor_outputs: Vec<String>,
+    monitor_error: Option<String>,
+    // --- Raw capture archived to WAV ---
+    audio_record_tap: AudioRecordTap,
+    audio_recorder: Option<WavRecorder>,
+    audio_record_result: Option<Result<String, String>>,
     audio_capture_stop_signal: Arc<AtomicBool>,
-    audio_processor_thread_handle: Option<JoinHandle<()>>,
-    audio_processor_stop_signal: Arc<AtomicBool>,
+    audio_processor_thread: Option<SupervisedThread>,
+    spawn_audio_processor: ProcessorSpawner,
+    stem_thread: Option<SupervisedThread>,
     camera_error: Option<String>,
     seg_error: Option<String>,
+    model_file: Option<std::path::PathBuf>, // Picked or dropped in place of the default model
+    file_dialog: Option<PendingDialog>,
+    launch_model_file: Option<std::path::PathBuf>, // What a restarted thread loads by itself
+    file_feedback: Option<(Result<String, String>, Instant)>, // Last dropped/picked file
+    // --- Media file playing in place of the camera and/or the audio input ---
+    file_source_config: FileSourceConfig,
+    file_source: Option<FileSource>,
+    file_frames_tx: PolicySender<CameraThreadMsg>,
+    file_audio_tx: PolicySender<Vec<f32>>,
     live_audio_status: LiveAudioStatus,
     camera_resolution: Option<Resolution>,
     texture_size: Option<Vec2>,
     last_fps_update_time: Instant,
     frames_since_last_update: u32,
     last_calculated_fps: f32,
+    replay_dir: Option<PathBuf>,
+    session_recording: Option<String>,
+    model_status: Option<String>, // Loading splash text from the segmentation thread
+    roi: Option<NormalizedRect>,
+    roi_drag_start: Option<Pos2>,
+    // --- Projection corner pin (handles replace ROI dragging while calibrating) ---
+    corner_pin: CornerPin,
+    calibrating_pin: bool,
+    masked_output: bool,
+    // --- Performance escape hatches (shift+B / shift+F) ---
+    blackout: bool,
+    blackout_fade_secs: f32,
+    // Window-side fade for what skips the composite: watchdog pass-through and the paint layer
+    display_blackout: BlackoutFader,
+    frozen: bool,
+    // --- Tempo-quantized look changes ---
+    quantize_grid: QuantizeGrid,
+    tap_bpm: Option<f32>, // None = the segmentation thread follows the Beat channel
+    tap_times: Vec<Instant>,
+    pin_drag: Option<usize>,
+    // --- Mask brush (replaces ROI dragging while active) ---
+    brush_mode: Option<BrushMode>,
+    brush_slot: usize,
+    brush_radius: f32,
+    mask_strokes: Vec<BrushStroke>,
+    last_brush_pos: Option<Pos2>,
+    brush_edit: Option<(usize, SlotSelection)>, // Slot state before the stroke being painted
+    // --- Point prompts: shift-click = include, alt-click = exclude ---
+    prompt_slot: usize,
+    prompts: [PointPrompt; MAX_TRACKS],
+    // --- Touch/kiosk mode: taps add points of the chosen kind; settings hide behind a button ---
+    touch_mode: bool,
+    touch_exclude: bool,
+    show_settings: bool,
+    // --- Operating profile; None until picked ---
+    profile: Option<AppProfile>,
+    show_profile_picker: bool,
+    // --- Show project (.scb) ---
+    show_project_window: bool,
+    project_path: String,
+    recent_projects: Vec<String>,
+    project_error: Option<String>,
+    slot_locks: [bool; MAX_TRACKS],
+    track_zoom: bool,
+    // Heavy-model mask refresh; only offered when the config names a model
+    ensemble: bool,
+    ensemble_config: EnsembleConfig,
+    permission_status: Vec<(MediaKind, PermissionStatus)>,
+    last_permission_check: Instant,
+    // --- Self-test wizard ---
+    model_report: ModelReportSlot, // How the live model loaded, for the self-test
+    show_self_test: bool,
+    self_test_done: bool,
+    self_test_rx: Option<Receiver<DiagnosticResult>>,
+    self_test_results: Vec<DiagnosticResult>,
+    // --- Normalization and FFT settings, read by the audio processor every block ---
+    analysis: AnalysisControl,
+    // --- Audio latency ---
+    onset_probe: OnsetProbe,
+    latency_calibration: Option<Calibration>,
+    // Loopback wizard; capture switches to the input it picks
+    audio_input: Option<String>,
+    loopback_names: Vec<String>, // From the config file, for the self-test's loopback check
+    loopback_wizard: Option<LoopbackWizard>,
+    loopback_result: Option<Result<String, String>>,
+    latency_result: Option<Result<Duration, String>>,
+    audio_offset_ms: i32,
+    // --- Intensity response curves ---
+    response_curves: ResponseCurves,
+    curve_channel: AnalysisChannel,
+    noise_gates: [GateSettings; GATED_CHANNELS.len()],
+    intensity_gain: f32,
+    // --- Gamepad: stick cursor over the video, triggers ride the intensity gain ---
+    gamepad: Option<GamepadInput>,
+    gamepad_polled: Instant,
+    // --- Visual plugins (reported by the segmentation thread) ---
+    plugins: Vec<PluginInfo>,
+    slot_plugins: [Option<String>; MAX_TRACKS],
+    plugin_params: HashMap<String, Vec<f32>>,
+    // --- A/B compare ---
+    show_compare: bool,
+    compare_looks: [Option<Look>; 2],
+    compare_textures: Option<[TextureHandle; 2]>,
+    compare_pending: bool,
 }
 
-impl WebcamAppUI {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        info!("Initializing WebcamAppUI (Persistent Random Assignment Viz)"); // Log updated
-        let camera_index = CameraIndex::Index(0);
-        let device_str = "mps";
-        let dtype_str = "fp16";
-        let model_options = match usls::Options::fastsam_s()
-            .with_model_device(device_str.try_into().expect("Bad device"))
-            .with_model_dtype(dtype_str.try_into().unwrap_or(usls::DType::Fp32))
-            .with_model_file("models/FastSAM-s.onnx")
-            .with_nc(1)
-            .with_class_names(&["object"])
-            .with_class_confs(&[0.35])
-            .with_iou(0.45)
-            .with_find_contours(true)
-            .commit()
-        {
-            Ok(o) => o,
-            Err(e) => {
-                panic!("Model opts failed: {}", e)
+impl WebcamAppUI {
+    pub fn new(
+        cc: &eframe::CreationContext<'_>,
+        launch_options: LaunchOptions,
+        config: AppConfig,
+    ) -> Self {
+        info!("Initializing WebcamAppUI (Persistent Random Assignment Viz)"); // Log updated
+        if launch_options.touch {
+            apply_touch_style(&cc.egui_ctx);
+        }
+        let camera_index = CameraIndex::Index(0);
+        // Last model chosen in File > Choose model…, while it still exists
+        let model_file: Option<std::path::PathBuf> = cc
+            .storage
+            .and_then(|s| eframe::get_value::<Option<std::path::PathBuf>>(s, MODEL_FILE_KEY))
+            .flatten()
+            .filter(|f| f.is_file());
+        let model_options = match model_file.as_deref().map_or_else(
+            segmentation::default_model_options,
+            segmentation::model_options_for,
+        ) {
+            Ok(o) => o,
+            Err(e) => {
+                panic!("Model opts failed: {}", e)
+            }
+        };
+        // Ask up front so the camera/audio threads aren't the first to hit the OS prompt
+        let permission_status: Vec<(MediaKind, PermissionStatus)> =
+            [MediaKind::Camera, MediaKind::Microphone]
+                .into_iter()
+                .map(|kind| {
+                    let status = permissions::status(kind);
+                    if status == PermissionStatus::NotDetermined {
+                        let ctx = cc.egui_ctx.clone();
+                        permissions::request(kind, move |granted| {
+                            info!("{} permission granted: {}", kind.label(), granted);
+                            ctx.request_repaint();
+                        });
+                    }
+                    (kind, status)
+                })
+                .collect();
+        let self_test_done: bool = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, SELF_TEST_DONE_KEY))
+            .unwrap_or(false);
+        // Calibrated for the venue's projector, so it survives restarts
+        let corner_pin: CornerPin = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, CORNER_PIN_KEY))
+            .unwrap_or_default();
+        let language: Language = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, LANGUAGE_KEY))
+            .unwrap_or_default();
+        i18n::set_language(language);
+        let recent_projects: Vec<String> = cc
+            .storage
+            .and_then(|s| eframe::get_value(s, RECENT_PROJECTS_KEY))
+            .unwrap_or_default();
+        // Picked by the loopback wizard; preferred over the name match when capture starts
+        let audio_input: Option<String> = cc
+            .storage
+            .and_then(|s| eframe::get_value::<Option<String>>(s, AUDIO_INPUT_KEY))
+            .flatten();
+        // Command line beats the config file, which beats the last profile picked here
+        let profile = launch_options.profile.or(config.profile).or_else(|| {
+            cc.storage
+                .and_then(|s| eframe::get_value::<Option<AppProfile>>(s, PROFILE_KEY))
+                .flatten()
+        });
+        let model_report = ModelReportSlot::default();
+        let seg_model_report = model_report.clone();
+        let pipeline_config = &config.pipeline;
+        let (cam_to_seg_tx, cam_to_seg_rx, cam_to_seg_stats) =
+            pipeline::channel("camera frames", pipeline_config.camera_frames);
+        let (seg_to_ui_tx, seg_to_ui_rx, seg_to_ui_stats) =
+            pipeline::channel("seg -> ui", pipeline_config.seg_to_ui);
+        let seg_frames: LatestSlot<ComposedFrame> = LatestSlot::default();
+        let seg_frames_writer = seg_frames.clone();
+        let (user_interaction_tx, user_interaction_rx, _) =
+            pipeline::channel("interactions", ChannelConfig::unbounded());
+        let (raw_samples_tx, raw_samples_rx, raw_samples_stats) =
+            pipeline::channel("raw audio", pipeline_config.raw_audio);
+        let (intensities_tx, intensities_rx, intensities_stats) =
+            pipeline::channel("intensities", pipeline_config.intensities);
+        let (drum_hits_tx, drum_hits_rx, drum_hits_stats) =
+            pipeline::channel("drum hits", pipeline_config.drum_hits);
+        let channel_stats = vec![
+            cam_to_seg_stats,
+            seg_to_ui_stats,
+            raw_samples_stats,
+            intensities_stats,
+            drum_hits_stats,
+        ];
+        logging::install_crash_reporter(&config.logging, channel_stats.clone());
+        // --- Optional metrics export (HTTP endpoint and/or JSON dump) ---
+        let shutdown = StopSignal::new();
+        let metrics_stop_signal = shutdown.child();
+        let metrics_thread = if config.metrics.enabled() {
+            match metrics::start_metrics_thread(
+                config.metrics.clone(),
+                channel_stats.clone(),
+                metrics_stop_signal.clone(),
+            ) {
+                Ok(handle) => Some(handle),
+                Err(e) => {
+                    error!("Metrics export disabled: {:#}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        // --- Optional heartbeat to a remote endpoint ---
+        let status_board = StatusBoard::default();
+        let telemetry_stop_signal = shutdown.child();
+        let telemetry_thread = telemetry::start_telemetry_thread(
+            &config.telemetry,
+            status_board.clone(),
+            telemetry_stop_signal.clone(),
+        );
+        // Webhook alerts share the reporters' stop signal
+        let alerter =
+            Alerter::start(&config.alerts, status_board.clone(), telemetry_stop_signal.clone());
+        // --- Optional MJPEG preview for browsers / OBS browser sources ---
+        let preview = PreviewServer::start(&config.preview).unwrap_or_else(|e| {
+            error!("Preview stream disabled: {:#}", e);
+            None
+        });
+        let control = ControlServer::start(&config.control).unwrap_or_else(|e| {
+            error!("Control server disabled: {:#}", e);
+            None
+        });
+        let gamepad = match GamepadInput::new() {
+            Ok(gamepad) => Some(gamepad),
+            Err(e) => {
+                warn!("Gamepad unavailable: {:#}", e);
+                None
+            }
+        };
+        let (midi_tx, midi_rx) = bounded(256);
+        let midi_inputs = match midi::start_midi_input(midi_tx) {
+            Ok(inputs) => Some(inputs),
+            Err(e) => {
+                warn!("MIDI unavailable: {:#}", e);
+                None
+            }
+        };
+        let mut cue_error = None;
+        let cue_player = match &config.cues {
+            Some(path) => match CueList::load(path) {
+                Ok(list) => CuePlayer::new(list),
+                Err(e) => {
+                    error!("{:#}", e);
+                    cue_error = Some(format!("{:#}", e));
+                    CuePlayer::default()
+                }
+            },
+            None => CuePlayer::default(),
+        };
+        if let Some(control) = &control {
+            let numbers: Vec<u32> = cue_player.list.cues.iter().map(|c| c.number).collect();
+            control.set_actions(ControlAction::catalog(&numbers));
+        }
+        let cue_path = config
+            .cues
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let latest_raw_frame = LatestFrame::default();
+        let cam_latest_frame = latest_raw_frame.clone();
+        let cam_stop_signal = shutdown.child();
+        let seg_stop_signal = shutdown.child();
+        let audio_capture_stop_signal = Arc::new(AtomicBool::new(false));
+        let audio_processor_stop_signal = shutdown.child();
+        let timecode_clock = TimecodeClock::default();
+        let (ltc_stream, timecode_error) = match timecode::start_ltc_capture(
+            &config.timecode,
+            audio_capture_stop_signal.clone(),
+            timecode_clock.clone(),
+        ) {
+            Ok(stream) => (stream, None),
+            Err(e) => {
+                error!("LTC chase disabled: {:#}", e);
+                (None, Some(format!("{:#}", e)))
+            }
+        };
+        let cam_stop_clone = cam_stop_signal.clone();
+        let camera_control = CameraControl::default();
+        let cam_control = camera_control.clone();
+        let scheduler = if config.schedule.enabled {
+            match Scheduler::new(&config.schedule) {
+                Ok(scheduler) => Some(scheduler),
+                Err(e) => {
+                    error!("Schedule disabled: {:#}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let cam_ctx = cc.egui_ctx.clone();
+        let seg_stop_clone = seg_stop_signal.clone();
+        let seg_ctx = cc.egui_ctx.clone();
+        let seed = launch_options.seed;
+        let audio_config = config.audio.clone();
+        let analysis = AnalysisControl::new(AnalysisSettings {
+            normalization: config.audio.normalization,
+            fft: config.audio.fft.sanitized(),
+            backend: config.audio.backend,
+        });
+        let proc_analysis = analysis.clone();
+        let onset_probe = OnsetProbe::default();
+        let proc_onset_probe = onset_probe.clone();
+        let stream_audio_tap = StreamAudioTap::default();
+        let proc_stream_tap = stream_audio_tap.clone();
+        let audio_cap_stop = audio_capture_stop_signal.clone();
+        let monitor = MonitorControl::default();
+        let audio_record_tap = AudioRecordTap::default();
+        // A played media file feeds the same channels as the camera and the audio capture
+        let (file_frames_tx, file_audio_tx) = (cam_to_seg_tx.clone(), raw_samples_tx.clone());
+        // Spawn closures keep channel-end clones so the supervisor can respawn with the same wiring.
+        // In replay mode the recorded session stands in for both camera and audio
+        let cam_thread = Some(match launch_options.replay_dir.clone() {
+            Some(dir) => {
+                let replay_intensities_tx = intensities_tx.clone();
+                let replay_interaction_tx = user_interaction_tx.clone();
+                SupervisedThread::spawn("replay", cam_stop_signal.clone(), move |hb| {
+                    session::start_replay_thread(
+                        dir.clone(),
+                        cam_to_seg_tx.clone(),
+                        replay_intensities_tx.clone(),
+                        replay_interaction_tx.clone(),
+                        cam_latest_frame.clone(),
+                        cam_stop_clone.clone(),
+                        cam_ctx.clone(),
+                        hb,
+                        seed.is_some(),
+                    )
+                })
+            }
+            None => SupervisedThread::spawn("camera", cam_stop_signal.clone(), move |hb| {
+                camera::start_camera_thread(
+                    camera_index.clone(),
+                    cam_to_seg_tx.clone(),
+                    cam_latest_frame.clone(),
+                    cam_stop_clone.clone(),
+                    cam_control.clone(),
+                    cam_ctx.clone(),
+                    hb,
+                )
+            }),
+        });
+        let initial_audio_status;
+        let (audio_capture_streams, audio_sources) = if launch_options.replay_dir.is_some() {
+            initial_audio_status = LiveAudioStatus::Disabled;
+            (Vec::new(), Vec::new())
+        } else {
+            match live_audio::start_audio_capture(
+                raw_samples_tx,
+                audio_cap_stop,
+                audio_input.as_deref(),
+                &config.audio.loopback_names,
+                monitor.clone(),
+                audio_record_tap.clone(),
+            ) {
+                Ok(capture) => {
+                    initial_audio_status =
+                        LiveAudioStatus::Running(capture.sample_rate, capture.channels);
+                    (capture.streams, capture.sources)
+                }
+                Err(e) => {
+                    let m = format!("Audio capture failed: {}", e);
+                    error!("{}", m);
+                    initial_audio_status = LiveAudioStatus::Error(m);
+                    (Vec::new(), Vec::new())
+                }
+            }
+        };
+        // --- Optional stem separation, fed by the audio processor ---
+        let (stem_thread, stem_tap) = match (&launch_options.stem_model, &initial_audio_status) {
+            (Some(model_path), LiveAudioStatus::Running(..)) => {
+                let (stem_samples_tx, stem_samples_rx) = async_channel::bounded(20);
+                let levels = stems::StemLevels::default();
+                let thread_levels = levels.clone();
+                let model_path = model_path.clone();
+                let stem_stop = audio_processor_stop_signal.clone();
+                let thread = SupervisedThread::spawn(
+                    "stems",
+                    audio_processor_stop_signal.clone(),
+                    move |hb| {
+                        stems::start_stem_thread(
+                            model_path.clone(),
+                            stem_samples_rx.clone(),
+                            thread_levels.clone(),
+                            stem_stop.clone(),
+                            hb,
+                        )
+                    },
+                );
+                let tap = stems::StemTap {
+                    sample_sender: stem_samples_tx,
+                    levels,
+                };
+                (Some(thread), Some(tap))
+            }
+            _ => (None, None),
+        };
+        // Each processor gets its own stop signal, so switching inputs can replace it alone
+        let spawn_audio_processor: ProcessorSpawner = Box::new(move |r, c| {
+            let stop_signal = audio_processor_stop_signal.child();
+            let audio_proc_stop = stop_signal.clone();
+            let raw_samples_rx = raw_samples_rx.clone();
+            let intensities_tx = intensities_tx.clone();
+            let stem_tap = stem_tap.clone();
+            let audio_config = audio_config.clone();
+            let proc_analysis = proc_analysis.clone();
+            let proc_onset_probe = proc_onset_probe.clone();
+            let proc_stream_tap = proc_stream_tap.clone();
+            let drum_hits_tx = drum_hits_tx.clone();
+            SupervisedThread::spawn("audio processor", stop_signal, move |hb| {
+                let mut p = music::AudioProcessor::new(
+                    raw_samples_rx.clone(),
+                    intensities_tx.clone(),
+                    r,
+                    c,
+                    music::NUM_ANALYSIS_CHANNELS,
+                    Some(music::INTERNAL_SAMPLE_RATE),
+                    stem_tap.clone(),
+                    &audio_config,
+                )
+                .with_analysis_control(proc_analysis.clone())
+                .with_onset_probe(proc_onset_probe.clone())
+                .with_stream_tap(proc_stream_tap.clone())
+                .with_drum_hits(drum_hits_tx.clone());
+                let stop = audio_proc_stop.clone();
+                std::thread::spawn(move || p.run(stop, hb))
+            })
+        });
+        let audio_processor_thread = match initial_audio_status {
+            LiveAudioStatus::Running(r, c) => Some(spawn_audio_processor(r, c)),
+            _ => {
+                warn!("No audio proc started.");
+                None
+            }
+        };
+        let seg_thread = Some(SupervisedThread::spawn(
+            "segmentation",
+            seg_stop_signal.clone(),
+            move |hb| {
+                segmentation::start_segmentation_thread(
+                    seg_to_ui_tx.clone(),
+                    seg_frames_writer.clone(),
+                    cam_to_seg_rx.clone(),
+                    user_interaction_rx.clone(),
+                    intensities_rx.clone(),
+                    drum_hits_rx.clone(),
+                    seg_stop_clone.clone(),
+                    seg_ctx.clone(),
+                    model_options.clone(),
+                    seg_model_report.clone(),
+                    seed,
+                    hb,
+                )
+            },
+        ));
+
+        let mut app = Self {
+            texture: None,
+            texture_stale: false,
+            partial_uploads: false,
+            visual_quality: VisualQuality::Full,
+            paint_layer: false,
+            matte: MatteSettings::default(),
+            paint_frame: None,
+            paint_renderer: PaintLayerRenderer::default(),
+            seg_to_ui_rx,
+            seg_frames,
+            last_seg_frame_seq: 0,
+            band_meters: None,
+            user_interaction_tx,
+            slot_routing: std::array::from_fn(AnalysisChannel::default_for_slot),
+            slot_styles: std::array::from_fn(VisualStyle::default_for_slot),
+            slot_params: std::array::from_fn(|slot| {
+                StyleParams::defaults(VisualStyle::default_for_slot(slot))
+            }),
+            slot_outlines: [OutlineStyle::None; MAX_TRACKS],
+            slot_layers: Default::default(),
+            background_layers: Vec::new(),
+            outline_blend: BlendMode::Normal,
+            shimmer: ShimmerSettings::default(),
+            drum_fx: DrumFxSettings::default(),
+            bloom: BloomSettings::default(),
+            post_chain: PostChain::default(),
+            text_overlay: TextOverlaySettings::default(),
+            lyrics: None,
+            lrc_path: String::new(),
+            lyrics_error: None,
+            sprite: SpriteSettings::default(),
+            sprite_path: String::new(),
+            sprite_grid: [1, 1],
+            lut: LutSettings::default(),
+            tone_map: ToneMapSettings::default(),
+            lut_path: String::new(),
+            lut_files: lut::list_luts(),
+            hud: HudSettings::default(),
+            countdown_input: String::new(),
+            display_scale: 1.0,
+            inference_cadence: InferenceCadence::EveryFrame,
+            frame_pacing: FramePacing::default(),
+            channel_stats,
+            shutdown,
+            metrics_thread,
+            status_board,
+            telemetry_thread,
+            alerter,
+            preview,
+            control,
+            stream_config: config.stream.clone(),
+            keyed_config: config.keyed.clone(),
+            keyed_recording: None,
+            stream_audio_tap,
+            rtmp_stream: None,
+            go_live: false,
+            stream_error: None,
+            cue_player,
+            cue_path,
+            cue_error,
+            midi_rx,
+            midi_inputs,
+            timecode_clock,
+            mtc_decoder: MtcDecoder::default(),
+            ltc_stream,
+            timecode_error,
+            timecode_sent: None,
+            chased_cue: None,
+            link: LinkSession::start(&config.link),
+            link_position: None,
+            link_sent: None,
+            chased_beat_cue: None,
+            deck_b: Look::default(),
+            crossfader: 0.0,
+            crossfader_cc: None,
+            master_mix: 1.0,
+            learning_cc: false,
+            automation_recorder: AutomationRecorder::default(),
+            automation_player: AutomationPlayer::default(),
+            automation_take: Vec::new(),
+            undo: UndoStack::default(),
+            preset_path: String::new(),
+            preset_error: None,
+            watchdog: config.watchdog,
+            latest_raw_frame,
+            last_seg_frame_time: None,
+            last_passthrough_capture: None,
+            model_lagging: false,
+            cam_thread,
+            scheduler,
+            camera_control,
+            standby: false,
+            resume_live: false,
+            resume_recording: false,
+            resume_keyed: false,
+            last_schedule_check: Instant::now() - SCHEDULE_CHECK_INTERVAL,
+            seg_thread,
+            audio_capture_streams,
+            audio_sources,
+            monitor,
+            monitor_stream: None,
+            monitor_device: None,
+            monitor_outputs: live_audio::list_output_devices().unwrap_or_default(),
+            monitor_error: None,
+            audio_record_tap,
+            audio_recorder: None,
+            audio_record_result: None,
+            audio_capture_stop_signal,
+            audio_processor_thread,
+            spawn_audio_processor,
+            stem_thread,
+            camera_error: None,
+            seg_error: None,
+            model_file: model_file.clone(),
+            file_dialog: None,
+            launch_model_file: model_file,
+            file_feedback: None,
+            file_source_config: config.file_source.clone(),
+            file_source: None,
+            file_frames_tx,
+            file_audio_tx,
+            live_audio_status: initial_audio_status,
+            camera_resolution: None,
+            texture_size: None,
+            last_fps_update_time: Instant::now(),
+            frames_since_last_update: 0,
+            last_calculated_fps: 0.0,
+            replay_dir: launch_options.replay_dir,
+            session_recording: None,
+            model_status: None,
+            roi: None,
+            roi_drag_start: None,
+            corner_pin,
+            calibrating_pin: false,
+            masked_output: false,
+            blackout: false,
+            blackout_fade_secs: 2.0,
+            display_blackout: BlackoutFader::default(),
+            frozen: false,
+            quantize_grid: QuantizeGrid::Off,
+            tap_bpm: None,
+            tap_times: Vec::new(),
+            pin_drag: None,
+            brush_mode: None,
+            brush_slot: 0,
+            brush_radius: 0.03,
+            mask_strokes: Vec::new(),
+            last_brush_pos: None,
+            brush_edit: None,
+            prompt_slot: 0,
+            touch_mode: launch_options.touch,
+            touch_exclude: false,
+            show_settings: !launch_options.touch,
+            profile: None,
+            show_profile_picker: false,
+            show_project_window: false,
+            project_path: String::new(),
+            recent_projects,
+            project_error: None,
+            prompts: std::array::from_fn(|_| PointPrompt::default()),
+            slot_locks: [false; MAX_TRACKS],
+            track_zoom: false,
+            ensemble: false,
+            ensemble_config: config.ensemble.clone(),
+            permission_status,
+            last_permission_check: Instant::now(),
+            model_report,
+            show_self_test: false,
+            self_test_done,
+            self_test_rx: None,
+            self_test_results: Vec::new(),
+            analysis,
+            onset_probe,
+            latency_calibration: None,
+            latency_result: None,
+            audio_input,
+            loopback_names: config.audio.loopback_names.clone(),
+            loopback_wizard: None,
+            loopback_result: None,
+            audio_offset_ms: config.audio.offset_ms,
+            response_curves: ResponseCurves::default(),
+            curve_channel: AnalysisChannel::Low,
+            noise_gates: Default::default(),
+            intensity_gain: 1.0,
+            gamepad,
+            gamepad_polled: Instant::now(),
+            plugins: Vec::new(),
+            slot_plugins: std::array::from_fn(|_| None),
+            plugin_params: HashMap::new(),
+            show_compare: false,
+            compare_looks: [None, None],
+            compare_textures: None,
+            compare_pending: false,
+        };
+        if app.audio_offset_ms != 0 {
+            let _ = app
+                .user_interaction_tx
+                .send(UserInteractionSegMsg::SetAudioOffset(app.audio_offset_ms));
+        }
+        if app.corner_pin.enabled {
+            let _ = app
+                .user_interaction_tx
+                .send(UserInteractionSegMsg::SetCornerPin(app.corner_pin));
+        }
+        match profile {
+            Some(profile) => app.apply_profile(&cc.egui_ctx, profile),
+            None => app.show_profile_picker = true,
+        }
+        // First launch: walk the user through the setup checks
+        if !app.self_test_done {
+            app.start_self_test(&cc.egui_ctx);
+        }
+        app
+    }
+
+    // --- Profiles: starting values for the kind of show; everything stays adjustable ---
+    fn apply_profile(&mut self, ctx: &egui::Context, profile: AppProfile) {
+        info!("Profile: {}", profile.label());
+        let settings = profile.settings();
+        self.profile = Some(profile);
+        self.track_zoom = settings.track_zoom;
+        self.send_interaction(UserInteractionSegMsg::SetTrackZoom(self.track_zoom));
+        self.masked_output = settings.masked_output;
+        self.send_interaction(UserInteractionSegMsg::SetMaskedOutput(self.masked_output));
+        self.show_settings = settings.show_settings && !self.touch_mode;
+        self.watchdog.deadline_ms = settings.watchdog_deadline_ms;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(settings.fullscreen));
+        if settings.auto_go_live && self.stream_config.rtmp_url.is_some() {
+            self.go_live = true;
+        }
+    }
+
+    // --- Drag and drop: a model, a LUT or a project, told apart by extension ---
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| !i.raw.hovered_files.is_empty()) {
+            let layer = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("file_drop"));
+            let painter = ctx.layer_painter(layer);
+            let rect = ctx.screen_rect();
+            painter.rect_filled(rect, 0.0, Color32::from_black_alpha(160));
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                tr("Drop a model (.onnx), LUT (.cube), project (.scb) or a video/audio file"),
+                egui::FontId::proportional(20.0),
+                Color32::WHITE,
+            );
+        }
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        for file in dropped {
+            let result = match file.path {
+                Some(path) => self.load_dropped_file(&path),
+                None => Err(format!("{}: {}", file.name, tr("no file path to load from"))),
+            };
+            if let Err(e) = &result {
+                warn!("Dropped file rejected: {}", e);
+            }
+            self.file_feedback = Some((result, Instant::now()));
+        }
+        if let Some((_, at)) = &self.file_feedback {
+            if at.elapsed() > FILE_FEEDBACK_DURATION {
+                self.file_feedback = None;
+            } else {
+                ctx.request_repaint_after(FILE_FEEDBACK_DURATION);
+            }
+        }
+    }
+
+    // Remembered (and persisted) only once the segmentation thread reports it loaded
+    fn load_model_file(&mut self, path: &std::path::Path) -> Result<String, String> {
+        if !path.is_file() {
+            return Err(format!("{}: {}", path.display(), tr("not a file")));
+        }
+        self.seg_error = None;
+        self.send_interaction(UserInteractionSegMsg::LoadModel(path.to_path_buf()));
+        Ok(format!("{} {}", tr("Loading model"), path.display()))
+    }
+
+    fn set_lut_file(&mut self, path: &std::path::Path) {
+        let settings = LutSettings {
+            path: path.display().to_string(),
+            ..self.lut.clone()
+        };
+        self.lut_path = settings.path.clone();
+        self.lut = settings.clone();
+        self.send_interaction(UserInteractionSegMsg::SetLut(settings));
+    }
+
+    fn load_dropped_file(&mut self, path: &std::path::Path) -> Result<String, String> {
+        let name = path
+            .file_name()
+            .map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+        match extension.as_str() {
+            "onnx" => self.load_model_file(path),
+            "cube" => {
+                lut::Lut3d::load(path).map_err(|e| format!("{:#}", e))?;
+                self.set_lut_file(path);
+                Ok(format!("LUT {}", name))
+            }
+            "scb" => {
+                self.open_project(path);
+                match &self.project_error {
+                    Some(e) => Err(e.clone()),
+                    None => Ok(format!("{} {}", tr("Project"), name)),
+                }
+            }
+            _ if file_source::is_media_file(path) => self.play_media_file(path),
+            _ => Err(format!("{}: {}", name, tr("unsupported file type"))),
+        }
+    }
+
+    // --- Media file source: video replaces the camera, audio the live input, until it ends ---
+    fn play_media_file(&mut self, path: &std::path::Path) -> Result<String, String> {
+        self.stop_media_file();
+        let name = path
+            .file_name()
+            .map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+        let audio = match self.live_audio_status {
+            LiveAudioStatus::Running(rate, channels) => {
+                Some((self.file_audio_tx.clone(), rate, channels))
+            }
+            _ => None, // No analysis running to feed
+        };
+        let sinks = FileSourceSinks {
+            frames: self.file_frames_tx.clone(),
+            latest_frame: self.latest_raw_frame.clone(),
+            audio,
+        };
+        let source = FileSource::start(&self.file_source_config, path, sinks)
+            .map_err(|e| format!("{}: {:#}", name, e))?;
+        self.hand_over_inputs(source.feeds_video(), source.feeds_audio());
+        self.file_source = Some(source);
+        Ok(format!("{} {}", tr("Playing"), name))
+    }
+
+    fn stop_media_file(&mut self) {
+        if let Some(mut source) = self.file_source.take() {
+            source.stop();
+        }
+        self.hand_over_inputs(false, false);
+    }
+
+    // The camera is released and the live audio bypassed while the file feeds them; reapplied
+    // every frame so the opening-hours standby can't reopen the camera under a playing video
+    fn hand_over_inputs(&self, video: bool, audio: bool) {
+        self.camera_control.set_standby(video || self.standby);
+        if let Some(primary) = self.audio_sources.first() {
+            primary.set_bypassed(audio);
+        }
+    }
+
+    fn poll_file_source(&mut self) {
+        let Some(source) = &self.file_source else {
+            return;
+        };
+        let (video, audio) = (source.feeds_video(), source.feeds_audio());
+        self.hand_over_inputs(video, audio);
+        if !video && !audio {
+            info!("File source {} finished", source.path().display());
+            self.file_source = None;
+        }
+    }
+
+    // --- Show project: device picks, look, automation, cues, assets and layout in one file ---
+    fn current_project(&self) -> Project {
+        Project::new(
+            ProjectDevices {
+                audio_input: self.audio_input.clone(),
+                monitor_output: self.monitor_device.clone(),
+            },
+            Preset {
+                params: self.param_snapshot(),
+                automation: self.automation_take.clone(),
+            },
+            self.cue_player.list.clone(),
+            self.lut.clone(),
+            self.sprite.clone(),
+            ProjectLayout {
+                corner_pin: Some(self.corner_pin),
+                show_settings: self.show_settings,
+            },
+        )
+    }
+
+    // One dialog at a time; a second request while one is open is dropped
+    fn open_file_dialog(
+        &mut self,
+        request: FileRequest,
+        picked: impl Future<Output = Option<rfd::FileHandle>> + Send + 'static,
+    ) {
+        if self.file_dialog.is_some() {
+            return;
+        }
+        let (sender, answer) = bounded(1);
+        supervisor::runtime().spawn(async move {
+            let _ = sender.send(picked.await.map(|f| f.path().to_path_buf()));
+        });
+        self.file_dialog = Some(PendingDialog { request, answer });
+    }
+
+    fn poll_file_dialog(&mut self, ctx: &egui::Context) {
+        let Some(pending) = &self.file_dialog else {
+            return;
+        };
+        let picked = match pending.answer.try_recv() {
+            Ok(picked) => picked,
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint_after(Duration::from_millis(100));
+                return;
+            }
+            Err(TryRecvError::Disconnected) => None,
+        };
+        let Some(PendingDialog { request, .. }) = self.file_dialog.take() else {
+            return;
+        };
+        if let Some(path) = picked {
+            self.handle_picked_file(request, &path);
+        }
+    }
+
+    fn handle_picked_file(&mut self, request: FileRequest, path: &std::path::Path) {
+        match request {
+            FileRequest::OpenProject => self.open_project(path),
+            FileRequest::SaveProjectAs => {
+                self.save_project(&project::with_extension(&path.to_string_lossy()));
+            }
+            FileRequest::ExportPreset => {
+                self.save_preset(path);
+                self.preset_path = path.display().to_string();
+            }
+            FileRequest::ImportPreset => {
+                self.load_preset(path);
+                self.preset_path = path.display().to_string();
+            }
+            FileRequest::Lut => self.set_lut_file(path),
+            FileRequest::Model => {
+                let result = self.load_model_file(path);
+                self.file_feedback = Some((result, Instant::now()));
+            }
+            FileRequest::MediaFile => {
+                let result = self.play_media_file(path);
+                self.file_feedback = Some((result, Instant::now()));
+            }
+            FileRequest::SaveAudioAs(from) => {
+                self.audio_record_result = Some(
+                    audio_record::save_as(&from, path)
+                        .map(|()| path.display().to_string())
+                        .map_err(|e| format!("{:#}", e)),
+                );
+            }
+        }
+    }
+
+    fn save_preset(&mut self, path: &std::path::Path) {
+        let preset = Preset {
+            params: self.param_snapshot(),
+            automation: self.automation_take.clone(),
+        };
+        self.preset_error = preset.save(path).err().map(|e| format!("{:#}", e));
+    }
+
+    fn load_preset(&mut self, path: &std::path::Path) {
+        match Preset::load(path) {
+            Ok(preset) => {
+                self.restore_params(preset.params);
+                self.automation_take = preset.automation;
+                self.preset_error = None;
+            }
+            Err(e) => self.preset_error = Some(format!("{:#}", e)),
+        }
+    }
+
+    fn save_project_as(&mut self) {
+        let picked = project_dialog().set_file_name("show.scb").save_file();
+        self.open_file_dialog(FileRequest::SaveProjectAs, picked);
+    }
+
+    fn save_project(&mut self, path: &std::path::Path) {
+        match self.current_project().save(path) {
+            Ok(()) => {
+                self.project_path = path.display().to_string();
+                project::push_recent(&mut self.recent_projects, path);
+                self.project_error = None;
+            }
+            Err(e) => {
+                error!("{:#}", e);
+                self.project_error = Some(format!("{:#}", e));
+                self.show_project_window = true;
+            }
+        }
+    }
+
+    fn open_project(&mut self, path: &std::path::Path) {
+        let project = match Project::load(path) {
+            Ok(p) => p,
+            Err(e) => {
+                error!("{:#}", e);
+                self.project_error = Some(format!("{:#}", e));
+                self.show_project_window = true;
+                return;
+            }
+        };
+        self.restore_params(project.preset.params);
+        self.automation_take = project.preset.automation;
+        self.cue_player = CuePlayer::new(project.cues);
+        self.publish_control_actions();
+        self.lut_path = project.lut.path.clone();
+        self.lut = project.lut.clone();
+        self.send_interaction(UserInteractionSegMsg::SetLut(project.lut));
+        self.sprite_path = project.sprite.path.clone();
+        self.sprite_grid = [project.sprite.columns, project.sprite.rows];
+        self.sprite = project.sprite.clone();
+        self.send_interaction(UserInteractionSegMsg::SetSprite(project.sprite));
+        if let Some(pin) = project.layout.corner_pin {
+            self.corner_pin = pin;
+            self.send_interaction(UserInteractionSegMsg::SetCornerPin(pin));
+        }
+        self.show_settings = project.layout.show_settings;
+        if project.devices.audio_input != self.audio_input {
+            self.audio_input = project.devices.audio_input;
+            self.restart_audio_capture();
+        }
+        if project.devices.monitor_output != self.monitor_device {
+            self.monitor_device = project.devices.monitor_output;
+            if let LiveAudioStatus::Running(rate, _) = self.live_audio_status {
+                if self.monitor_stream.is_some() {
+                    self.open_monitor(rate);
+                }
+            }
+        }
+        self.project_path = path.display().to_string();
+        project::push_recent(&mut self.recent_projects, path);
+        self.project_error = None;
+        self.show_project_window = false;
+    }
+
+    fn draw_project_window(&mut self, ctx: &egui::Context) {
+        if !self.show_project_window {
+            return;
+        }
+        let mut open = true;
+        let mut action = None;
+        egui::Window::new(tr("Project"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.project_path).hint_text("show.scb"),
+                    );
+                    if ui.button(tr("Open")).clicked() {
+                        action = Some(false);
+                    }
+                    if ui.button(tr("Save")).clicked() {
+                        action = Some(true);
+                    }
+                });
+                ui.small(tr("Device picks, look, automation, cues, LUT/sprite paths and layout"));
+                if let Some(e) = &self.project_error {
+                    ui.colored_label(Color32::RED, e);
+                }
+            });
+        let path = project::with_extension(&self.project_path);
+        match action {
+            Some(true) => {
+                self.save_project(&path);
+                if self.project_error.is_none() {
+                    self.show_project_window = false;
+                }
+            }
+            Some(false) => self.open_project(&path),
+            None => {}
+        }
+        if !open {
+            self.show_project_window = false;
+        }
+    }
+
+    fn draw_profile_picker(&mut self, ctx: &egui::Context) {
+        if !self.show_profile_picker {
+            return;
+        }
+        let mut open = true;
+        let mut chosen = None;
+        egui::Window::new(tr("Choose a Profile"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                for profile in AppProfile::ALL {
+                    ui.horizontal(|ui| {
+                        let current = self.profile == Some(profile);
+                        if ui.selectable_label(current, tr(profile.label())).clicked() {
+                            chosen = Some(profile);
+                        }
+                        ui.small(profile.description());
+                    });
+                }
+                ui.separator();
+                ui.small(tr("Change it later from File → Choose profile…"));
+            });
+        if let Some(profile) = chosen {
+            self.apply_profile(ctx, profile);
+        }
+        if chosen.is_some() || !open {
+            self.show_profile_picker = false;
+        }
+    }
+
+    fn start_self_test(&mut self, ctx: &egui::Context) {
+        let (tx, rx) = unbounded();
+        diagnostics::start_self_test(
+            tx,
+            self.model_report.clone(),
+            self.loopback_names.clone(),
+            ctx.clone(),
+        );
+        self.self_test_rx = Some(rx);
+        self.self_test_results.clear();
+        self.show_self_test = true;
+    }
+
+    fn draw_self_test_window(&mut self, ctx: &egui::Context) {
+        if let Some(rx) = &self.self_test_rx {
+            self.self_test_results.extend(rx.try_iter());
+        }
+        if !self.show_self_test {
+            return;
+        }
+        let running = self.self_test_results.len() < DiagnosticStep::ALL.len();
+        let mut open = true;
+        let mut rerun = false;
+        let mut done = false;
+        egui::Window::new(tr("Setup Check"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                for step in DiagnosticStep::ALL {
+                    ui.horizontal(|ui| {
+                        match self.self_test_results.iter().find(|r| r.step == step) {
+                            Some(r) => {
+                                let (color, text) = match r.status {
+                                    DiagnosticStatus::Pass => (Color32::GREEN, "PASS"),
+                                    DiagnosticStatus::Warn => (Color32::YELLOW, "WARN"),
+                                    DiagnosticStatus::Fail => (Color32::RED, "FAIL"),
+                                };
+                                ui.colored_label(color, text);
+                                ui.strong(tr(step.label()));
+                            }
+                            None => {
+                                ui.spinner();
+                                ui.strong(tr(step.label()));
+                            }
+                        }
+                    });
+                    if let Some(r) = self.self_test_results.iter().find(|r| r.step == step) {
+                        ui.small(&r.detail);
+                        if let Some(hint) = &r.hint {
+                            ui.label(format!("→ {}", hint));
+                        }
+                    }
+                    ui.add_space(4.0);
+                }
+                ui.separator();
+                let live = if self.texture.is_some() {
+                    "receiving frames"
+                } else {
+                    "no frames yet"
+                };
+                ui.label(format!("{}: {}", tr("Live pipeline"), live));
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!running, egui::Button::new(tr("Re-run"))).clicked() {
+                        rerun = true;
+                    }
+                    if ui.button(tr("Done")).clicked() {
+                        done = true;
+                    }
+                });
+            });
+        if rerun {
+            self.start_self_test(ctx);
+        }
+        if done || !open {
+            self.show_self_test = false;
+            self.self_test_done = true;
+        }
+    }
+
+    // --- A/B compare: two snapshotted looks rendered over the same captured frame ---
+    fn draw_compare_window(&mut self, ctx: &egui::Context) {
+        if !self.show_compare {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new(tr("Compare Looks"))
+            .open(&mut open)
+            .default_width(720.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for (i, name) in ["A", "B"].into_iter().enumerate() {
+                        if ui
+                            .button(format!("{} {}", tr("Snapshot"), name))
+                            .on_hover_text(tr("Store the current look for this side"))
+                            .clicked()
+                        {
+                            self.compare_looks[i] = Some(self.current_look());
+                        }
+                    }
+                    let ready = self.compare_looks.iter().all(Option::is_some);
+                    let render = ui
+                        .add_enabled(
+                            ready && !self.compare_pending,
+                            egui::Button::new(tr("Render")),
+                        )
+                        .on_hover_text(tr("Apply both looks to the next frame"));
+                    if render.clicked() {
+                        if let [Some(a), Some(b)] = self.compare_looks.clone() {
+                            self.compare_pending = true;
+                            self.send_interaction(UserInteractionSegMsg::CompareLooks(
+                                Box::new(a),
+                                Box::new(b),
+                            ));
+                        }
+                    }
+                    if self.compare_pending {
+                        ui.spinner();
+                    }
+                });
+                let Some(textures) = &self.compare_textures else {
+                    ui.label(tr("Snapshot both looks, then render."));
+                    return;
+                };
+                let width = (ui.available_width() - ui.spacing().item_spacing.x) / 2.0;
+                ui.horizontal_top(|ui| {
+                    for (texture, name) in textures.iter().zip(["A", "B"]) {
+                        ui.vertical(|ui| {
+                            ui.strong(name);
+                            let size = texture.size_vec2();
+                            let scale = (width / size.x).min(1.0);
+                            ui.image((texture.id(), size * scale));
+                        });
+                    }
+                });
+            });
+        if !open {
+            self.show_compare = false;
+        }
+    }
+
+    fn upload_comparison(
+        &mut self,
+        ctx: &egui::Context,
+        a: Arc<egui::ColorImage>,
+        b: Arc<egui::ColorImage>,
+    ) {
+        self.compare_pending = false;
+        let a = ctx.load_texture("compare_a", ImageData::Color(a), TextureOptions::LINEAR);
+        let b = ctx.load_texture("compare_b", ImageData::Color(b), TextureOptions::LINEAR);
+        self.compare_textures = Some([a, b]);
+    }
+
+    // --- RTMP output ---
+    fn feed_stream(&mut self, frame: &Arc<egui::ColorImage>) {
+        if !self.go_live {
+            return;
+        }
+        // Display scale changes the frame size; ffmpeg's raw input can't follow, so restart
+        if let Some(stream) = self.rtmp_stream.take_if(|s| s.size() != frame.size) {
+            info!("Output size changed; restarting stream");
+            stream.stop();
+        }
+        if self.rtmp_stream.is_none() {
+            match RtmpStream::start(
+                &self.stream_config,
+                frame.size,
+                music::INTERNAL_SAMPLE_RATE,
+                &self.stream_audio_tap,
+            ) {
+                Ok(stream) => {
+                    self.rtmp_stream = Some(stream);
+                    self.stream_error = None;
+                }
+                Err(e) => {
+                    error!("Stream failed to start: {:#}", e);
+                    self.stream_error = Some(format!("{:#}", e));
+                    self.go_live = false;
+                    return;
+                }
+            }
+        }
+        if let Some(stream) = &self.rtmp_stream {
+            stream.push_frame(frame.clone());
+        }
+    }
+
+    fn draw_stream_controls(&mut self, ui: &mut egui::Ui) {
+        if let Some(status) = self.rtmp_stream.as_mut().and_then(RtmpStream::exit_status) {
+            error!("Stream ended: {}", status);
+            self.stream_error = Some(status);
+            self.go_live = false;
+            if let Some(stream) = self.rtmp_stream.take() {
+                stream.stop();
+            }
+        }
+        let configured = self.stream_config.rtmp_url.is_some();
+        ui.horizontal(|ui| {
+            let label = if self.go_live { "Stop stream" } else { "Go live" };
+            let button = ui
+                .add_enabled(configured, egui::Button::new(label))
+                .on_disabled_hover_text(tr("Set stream.rtmp_url in the config file"));
+            if button.clicked() {
+                self.go_live = !self.go_live;
+                if !self.go_live {
+                    if let Some(stream) = self.rtmp_stream.take() {
+                        stream.stop();
+                    }
+                }
+            }
+            if self.rtmp_stream.is_some() {
+                ui.colored_label(Color32::RED, "● LIVE").on_hover_text(format!(
+                    "{} kbps, keyframe every {} s",
+                    self.stream_config.video_bitrate_kbps,
+                    self.stream_config.keyframe_interval_secs
+                ));
+            } else if self.go_live {
+                ui.spinner();
+            }
+        });
+        if let Some(e) = &self.stream_error {
+            ui.colored_label(Color32::RED, e);
+        }
+    }
+
+    // --- Plugins: sent once per segmentation thread start, so assignments are replayed here ---
+    fn set_plugins(&mut self, plugins: Vec<PluginInfo>) {
+        for info in &plugins {
+            let values = self
+                .plugin_params
+                .entry(info.name.clone())
+                .or_insert_with(|| info.params.iter().map(|p| p.default).collect());
+            for (param, &value) in values.iter().enumerate() {
+                let plugin = info.name.clone();
+                let _ = self
+                    .user_interaction_tx
+                    .send(UserInteractionSegMsg::SetPluginParam { plugin, param, value });
+            }
+        }
+        for (slot, plugin) in self.slot_plugins.iter_mut().enumerate() {
+            if plugin.as_ref().is_some_and(|n| !plugins.iter().any(|p| &p.name == n)) {
+                *plugin = None;
+            }
+            let plugin = plugin.clone();
+            let _ = self
+                .user_interaction_tx
+                .send(UserInteractionSegMsg::SetSlotPlugin { slot, plugin });
+        }
+        self.plugins = plugins;
+    }
+
+    // Keyed overlay recording: visuals with mask alpha, for keying over a clean feed downstream
+    fn draw_keyed_controls(&mut self, ui: &mut egui::Ui) {
+        let recording = self.keyed_recording.is_some();
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!recording, |ui| {
+                egui::ComboBox::from_id_salt("keyed_codec")
+                    .selected_text(self.keyed_config.codec.label())
+                    .show_ui(ui, |ui| {
+                        for codec in KeyedCodec::ALL {
+                            ui.selectable_value(&mut self.keyed_config.codec, codec, codec.label());
+                        }
+                    });
+            });
+            let label = if recording {
+                tr("Stop keyed")
+            } else {
+                tr("Record keyed")
+            };
+            if ui
+                .button(label)
+                .on_hover_text(tr("Graded visuals with alpha from the tracked masks, via ffmpeg"))
+                .clicked()
+            {
+                let config = (!recording).then(|| self.keyed_config.clone());
+                if let Err(e) = self
+                    .user_interaction_tx
+                    .send(UserInteractionSegMsg::SetKeyedRecording(config))
+                {
+                    warn!("Failed to toggle keyed recording: {}", e);
+                }
+            }
+        });
+        if let Some(path) = &self.keyed_recording {
+            ui.colored_label(Color32::RED, "● KEY").on_hover_text(path);
+        }
+    }
+
+    fn draw_plugin_controls(&mut self, ui: &mut egui::Ui) {
+        for slot in 0..MAX_TRACKS {
+            let current = self.slot_plugins[slot].clone();
+            let mut selected = current.clone();
+            ui.horizontal(|ui| {
+                ui.label(format!("{} {}", tr("Slot"), slot + 1));
+                egui::ComboBox::from_id_salt(("slot_plugin", slot))
+                    .selected_text(selected.as_deref().unwrap_or(tr("None")))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut selected, None, tr("None"));
+                        for info in &self.plugins {
+                            ui.selectable_value(
+                                &mut selected,
+                                Some(info.name.clone()),
+                                &info.name,
+                            );
+                        }
+                    });
+            });
+            if selected != current {
+                self.slot_plugins[slot] = selected.clone();
+                self.send_interaction(UserInteractionSegMsg::SetSlotPlugin {
+                    slot,
+                    plugin: selected,
+                });
+            }
+        }
+        let mut changes = Vec::new();
+        for info in &self.plugins {
+            if info.params.is_empty() {
+                continue;
+            }
+            let Some(values) = self.plugin_params.get_mut(&info.name) else {
+                continue;
+            };
+            ui.collapsing(&info.name, |ui| {
+                for (param, (spec, value)) in info.params.iter().zip(values.iter_mut()).enumerate()
+                {
+                    if param_slider(ui, &spec.name, &spec.unit, spec.min..=spec.max, value) {
+                        changes.push((info.name.clone(), param, *value));
+                    }
+                }
+            });
+        }
+        for (plugin, param, value) in changes {
+            self.send_interaction(UserInteractionSegMsg::SetPluginParam { plugin, param, value });
+        }
+    }
+
+    // --- Slot x channel radio grid; changes are forwarded to the segmentation thread ---
+    fn draw_routing_matrix(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("routing_matrix")
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(tr("Slot"));
+                for channel in AnalysisChannel::ALL {
+                    ui.label(tr(channel.label()));
+                }
+                ui.end_row();
+                for slot in 0..MAX_TRACKS {
+                    ui.label(format!("{}", slot));
+                    for channel in AnalysisChannel::ALL {
+                        if ui
+                            .radio(self.slot_routing[slot] == channel, "")
+                            .clicked()
+                            && self.slot_routing[slot] != channel
+                        {
+                            self.slot_routing[slot] = channel;
+                            self.send_interaction(UserInteractionSegMsg::SetSlotRouting {
+                                slot,
+                                channel,
+                            });
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    // --- Supervision: respawn dead threads and restore state the new thread doesn't know about ---
+    fn supervise_threads(&mut self) {
+        for thread in [
+            self.cam_thread.as_mut(),
+            self.audio_processor_thread.as_mut(),
+            self.stem_thread.as_mut(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            let is_camera = thread.name() == "camera" || thread.name() == "replay";
+            match thread.check() {
+                SupervisorEvent::Died(reason) if is_camera => {
+                    self.camera_error = Some(format!("{} {}", tr("Camera thread"), reason));
+                }
+                SupervisorEvent::Restarted(_) if is_camera => {
+                    self.camera_error = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(seg) = self.seg_thread.as_mut() {
+            match seg.check() {
+                SupervisorEvent::Died(reason) => {
+                    self.seg_error = Some(format!("{} {}", tr("Segmentation thread"), reason));
+                    self.model_status = None;
+                    self.compare_pending = false;
+                    self.keyed_recording = None;
+                }
+                SupervisorEvent::Restarted(_) => {
+                    // Fresh thread starts with default routing/styles and no recorder
+                    self.session_recording = None;
+                    self.keyed_recording = None;
+                    self.timecode_sent = None; // Resent by the next chase check
+                    self.link_sent = None;
+                    self.compare_pending = false; // Request died with the old thread
+                    for (slot, channel) in self.slot_routing.iter().enumerate() {
+                        let _ = self
+                            .user_interaction_tx
+                            .send(UserInteractionSegMsg::SetSlotRouting {
+                                slot,
+                                channel: *channel,
+                            });
+                    }
+                    for (slot, style) in self.slot_styles.iter().enumerate() {
+                        let _ = self
+                            .user_interaction_tx
+                            .send(UserInteractionSegMsg::SetSlotStyle {
+                                slot,
+                                style: *style,
+                            });
+                    }
+                    for (slot, params) in self.slot_params.iter().enumerate() {
+                        let _ = self
+                            .user_interaction_tx
+                            .send(UserInteractionSegMsg::SetSlotParams {
+                                slot,
+                                params: *params,
+                            });
+                    }
+                    for (slot, outline) in self.slot_outlines.iter().enumerate() {
+                        let _ = self
+                            .user_interaction_tx
+                            .send(UserInteractionSegMsg::SetSlotOutline {
+                                slot,
+                                outline: *outline,
+                            });
+                    }
+                    for (slot, layers) in self.slot_layers.iter().enumerate() {
+                        let _ = self
+                            .user_interaction_tx
+                            .send(UserInteractionSegMsg::SetSlotLayers {
+                                slot,
+                                layers: layers.clone(),
+                            });
+                    }
+                    let _ = self.user_interaction_tx.send(
+                        UserInteractionSegMsg::SetBackgroundLayers(self.background_layers.clone()),
+                    );
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetOutlineBlend(self.outline_blend));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetShimmer(self.shimmer));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetDrumFx(self.drum_fx));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetBloom(self.bloom));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetPostChain(self.post_chain.clone()));
+                    let _ = self.user_interaction_tx.send(
+                        UserInteractionSegMsg::SetTextOverlay(self.text_overlay.clone()),
+                    );
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetLyrics(self.lyrics.clone()));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetHud(self.hud.clone()));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetSprite(self.sprite.clone()));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetLut(self.lut.clone()));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetToneMap(self.tone_map));
+                    if let Some(file) = self
+                        .model_file
+                        .as_ref()
+                        .filter(|f| self.launch_model_file.as_ref() != Some(*f))
+                    {
+                        let _ = self
+                            .user_interaction_tx
+                            .send(UserInteractionSegMsg::LoadModel(file.clone()));
+                    }
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetDisplayScale(self.display_scale));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetInferenceCadence(self.inference_cadence));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetFramePacing(self.frame_pacing));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetPartialUploads(self.partial_uploads));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetVisualQuality(self.visual_quality));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetPaintLayer(self.paint_layer));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetMatte(self.matte));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetAudioOffset(self.audio_offset_ms));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetCornerPin(self.corner_pin));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetMaskedOutput(self.masked_output));
+                    let _ = self.user_interaction_tx.send(UserInteractionSegMsg::SetBlackout {
+                        engaged: self.blackout,
+                        fade_secs: self.blackout_fade_secs,
+                    });
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetFreeze(self.frozen));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetQuantize(self.quantize_grid));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetTempo(self.tap_bpm));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetIntensityGain(self.intensity_gain));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetTrackZoom(self.track_zoom));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetEnsemble(self.ensemble_request()));
+                    for (channel, gate) in GATED_CHANNELS.iter().zip(self.noise_gates) {
+                        let _ = self.user_interaction_tx.send(UserInteractionSegMsg::SetNoiseGate {
+                            channel: *channel,
+                            gate,
+                        });
+                    }
+                    for channel in AnalysisChannel::ALL {
+                        let curve = self.response_curves.get(channel);
+                        if curve != ResponseCurve::Linear {
+                            let _ = self
+                                .user_interaction_tx
+                                .send(UserInteractionSegMsg::SetResponseCurve { channel, curve });
+                        }
+                    }
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetDeckB(self.deck_b.clone()));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetCrossfader(self.crossfader));
+                    let _ = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetMasterMix(self.master_mix));
+                    for (slot, locked) in self.slot_locks.iter().enumerate() {
+                        let _ = self
+                            .user_interaction_tx
+                            .send(UserInteractionSegMsg::SetSlotLock { slot, locked: *locked });
+                    }
+                    for (slot, prompt) in self.prompts.iter().enumerate() {
+                        let _ = self.user_interaction_tx.send(UserInteractionSegMsg::SelectObject {
+                            slot,
+                            prompt: prompt.clone(),
+                        });
+                    }
+                    for stroke in &self.mask_strokes {
+                        let _ = self
+                            .user_interaction_tx
+                            .send(UserInteractionSegMsg::PaintMask(*stroke));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn draw_style_pickers(&mut self, ui: &mut egui::Ui) {
+        for slot in 0..MAX_TRACKS {
+            let mut style = self.slot_styles[slot];
+            egui::ComboBox::from_id_salt(("slot_style", slot))
+                .selected_text(tr(style.label()))
+                .show_ui(ui, |ui| {
+                    for option in VisualStyle::ALL {
+                        ui.selectable_value(&mut style, option, tr(option.label()));
+                    }
+                });
+            self.set_slot_style(slot, style);
+            let mut params = self.slot_params[slot].for_style(style);
+            ui.push_id(("slot_params", slot), |ui| {
+                for (spec, value) in style.params().iter().zip(params.values_mut()) {
+                    param_slider(ui, spec.name, spec.unit, spec.min..=spec.max, value);
+                }
+            });
+            if params != self.slot_params[slot] {
+                self.slot_params[slot] = params;
+                self.send_interaction(UserInteractionSegMsg::SetSlotParams { slot, params });
+            }
+            let mut outline = self.slot_outlines[slot];
+            egui::ComboBox::from_id_salt(("slot_outline", slot))
+                .selected_text(format!("{}: {}", tr("Outline"), tr(outline.label())))
+                .show_ui(ui, |ui| {
+                    for option in OutlineStyle::ALL {
+                        ui.selectable_value(&mut outline, option, tr(option.label()));
+                    }
+                });
+            if outline != self.slot_outlines[slot] {
+                self.slot_outlines[slot] = outline;
+                self.send_interaction(UserInteractionSegMsg::SetSlotOutline { slot, outline });
+            }
+            self.draw_layer_editor(ui, slot);
+        }
+        self.draw_background_editor(ui);
+        let mut outline_blend = self.outline_blend;
+        egui::ComboBox::from_label(tr("Outline blend"))
+            .selected_text(tr(outline_blend.label()))
+            .show_ui(ui, |ui| {
+                for option in BlendMode::ALL {
+                    ui.selectable_value(&mut outline_blend, option, tr(option.label()));
+                }
+            });
+        if outline_blend != self.outline_blend {
+            self.outline_blend = outline_blend;
+            self.send_interaction(UserInteractionSegMsg::SetOutlineBlend(outline_blend));
+        }
+
+        let mut shimmer = self.shimmer;
+        ui.checkbox(&mut shimmer.enabled, tr("Heat shimmer"))
+            .on_hover_text(tr("Air ripples around every object once the bass stays loud"));
+        ui.add_enabled_ui(shimmer.enabled, |ui| {
+            ui.add(egui::Slider::new(&mut shimmer.hold_secs, 0.0..=10.0).text(tr("Bass hold (s)")));
+            ui.add(egui::Slider::new(&mut shimmer.width, 2..=48).text(tr("Width")));
+            ui.add(egui::Slider::new(&mut shimmer.amount, 0.5..=12.0).text(tr("Amount")));
+        });
+        if shimmer != self.shimmer {
+            self.shimmer = shimmer;
+            self.send_interaction(UserInteractionSegMsg::SetShimmer(shimmer));
+        }
+
+        let mut drum_fx = self.drum_fx;
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut drum_fx.kick_ring, tr("Kick ring"))
+                .on_hover_text(tr("A ring bursts out of every object on each kick"));
+            ui.checkbox(&mut drum_fx.hat_sparkle, tr("Hi-hat sparkle"))
+                .on_hover_text(tr("Glints flash across every object on each hi-hat"));
+        });
+        if drum_fx != self.drum_fx {
+            self.drum_fx = drum_fx;
+            self.send_interaction(UserInteractionSegMsg::SetDrumFx(drum_fx));
+        }
+    }
+
+    // Extra styles stacked over the slot's base style
+    fn draw_layer_editor(&mut self, ui: &mut egui::Ui, slot: usize) {
+        let mut layers = self.slot_layers[slot].clone();
+        let title = format!("{} ({})", tr("Layers"), layers.len());
+        egui::CollapsingHeader::new(title)
+            .id_salt(("slot_layers", slot))
+            .show(ui, |ui| {
+                let mut remove = None;
+                for (i, layer) in layers.iter_mut().enumerate() {
+                    if ui.push_id(("layer", slot, i), |ui| layer_editor(ui, layer)).inner {
+                        remove = Some(i);
+                    }
+                }
+                if let Some(i) = remove {
+                    layers.remove(i);
+                }
+                if layers.len() < MAX_SLOT_LAYERS && ui.button(tr("Add layer")).clicked() {
+                    layers.push(EffectLayer::new(self.slot_styles[slot]));
+                }
+            });
+        if layers != self.slot_layers[slot] {
+            self.slot_layers[slot] = layers.clone();
+            self.send_interaction(UserInteractionSegMsg::SetSlotLayers { slot, layers });
+        }
+    }
+
+    // Same editor for the layers outside every object; new ones start on the first slot's style,
+    // driven by the overall level
+    fn draw_background_editor(&mut self, ui: &mut egui::Ui) {
+        let mut layers = self.background_layers.clone();
+        let title = format!("{} ({})", tr("Background layers"), layers.len());
+        egui::CollapsingHeader::new(title)
+            .id_salt("background_layers")
+            .show(ui, |ui| {
+                let mut remove = None;
+                for (i, layer) in layers.iter_mut().enumerate() {
+                    if ui.push_id(("background_layer", i), |ui| layer_editor(ui, layer)).inner {
+                        remove = Some(i);
+                    }
+                }
+                if let Some(i) = remove {
+                    layers.remove(i);
+                }
+                if layers.len() < MAX_SLOT_LAYERS && ui.button(tr("Add layer")).clicked() {
+                    layers.push(EffectLayer {
+                        driver: Some(AnalysisChannel::Rms),
+                        ..EffectLayer::new(self.slot_styles[0])
+                    });
+                }
+            });
+        if layers != self.background_layers {
+            self.background_layers = layers.clone();
+            self.send_interaction(UserInteractionSegMsg::SetBackgroundLayers(layers));
+        }
+    }
+
+    // A new style starts from its own defaults (the segmentation thread ignores stale values)
+    fn set_slot_style(&mut self, slot: usize, style: VisualStyle) {
+        if style != self.slot_styles[slot] {
+            self.slot_styles[slot] = style;
+            self.slot_params[slot] = StyleParams::defaults(style);
+            self.send_interaction(UserInteractionSegMsg::SetSlotStyle { slot, style });
+        }
+    }
+
+    fn draw_mask_brush_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.brush_mode, None, tr("Off"));
+            ui.selectable_value(&mut self.brush_mode, Some(BrushMode::Add), tr("Add"));
+            ui.selectable_value(&mut self.brush_mode, Some(BrushMode::Remove), tr("Erase"));
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Slot"));
+            for slot in 0..MAX_TRACKS {
+                ui.selectable_value(&mut self.brush_slot, slot, slot.to_string());
+            }
+        });
+        ui.add(egui::Slider::new(&mut self.brush_radius, 0.005..=0.15).text(tr("Size")));
+        let slot = self.brush_slot;
+        let count = self.mask_strokes.iter().filter(|s| s.slot == slot).count();
+        ui.add_enabled_ui(count > 0, |ui| {
+            let label = format!("{} {} ({} {})", tr("Clear slot"), slot, count, tr("dabs"));
+            let button = ui.button(label);
+            let button = if count >= MAX_DABS {
+                button.on_hover_text(tr("Dab limit reached; clear the slot to paint again"))
+            } else {
+                button
+            };
+            if button.clicked() {
+                let before = self.slot_selection(slot);
+                self.mask_strokes.retain(|s| s.slot != slot);
+                self.send_interaction(UserInteractionSegMsg::ClearMaskEdits { slot });
+                self.record_slot_edit(slot, before);
+            }
+        });
+    }
+
+    fn draw_prompt_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Slot"));
+            for slot in 0..MAX_TRACKS {
+                ui.selectable_value(&mut self.prompt_slot, slot, slot.to_string());
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Lock"));
+            for slot in 0..MAX_TRACKS {
+                if ui
+                    .checkbox(&mut self.slot_locks[slot], slot.to_string())
+                    .on_hover_text(tr("Keep this slot's object; never reassign it"))
+                    .changed()
+                {
+                    let locked = self.slot_locks[slot];
+                    let before = SlotSelection {
+                        locked: !locked,
+                        ..self.slot_selection(slot)
+                    };
+                    self.send_interaction(UserInteractionSegMsg::SetSlotLock { slot, locked });
+                    self.record_slot_edit(slot, before);
+                }
+            }
+        });
+        if ui
+            .checkbox(&mut self.track_zoom, tr("Track and zoom"))
+            .on_hover_text(tr(
+                "Run the model only around prompted/locked slots while all are in view",
+            ))
+            .changed()
+        {
+            self.send_interaction(UserInteractionSegMsg::SetTrackZoom(self.track_zoom));
+        }
+        let has_refiner = self.ensemble_config.model_file.is_some();
+        if ui
+            .add_enabled(
+                has_refiner,
+                egui::Checkbox::new(&mut self.ensemble, tr("High-quality mask refresh")),
+            )
+            .on_hover_text(tr("Refine masks with ensemble.model_file a few times per second"))
+            .on_disabled_hover_text(tr("Set ensemble.model_file in the config to enable"))
+            .changed()
+        {
+            self.send_interaction(UserInteractionSegMsg::SetEnsemble(self.ensemble_request()));
+        }
+        ui.small(tr("Arrows nudge the slot's box, shift+arrows resize"));
+        let slot = self.prompt_slot;
+        let prompt = &self.prompts[slot];
+        ui.small(format!(
+            "{} include / {} exclude points",
+            prompt.positives.len(),
+            prompt.negatives.len()
+        ));
+        if ui
+            .add_enabled(
+                !prompt.is_empty(),
+                egui::Button::new(tr("Release slot")),
+            )
+            .clicked()
+        {
+            let before = self.slot_selection(slot);
+            self.prompts[slot] = PointPrompt::default();
+            self.send_interaction(UserInteractionSegMsg::SelectObject {
+                slot,
+                prompt: PointPrompt::default(),
+            });
+            self.record_slot_edit(slot, before);
+        }
+    }
+
+    // Arrow keys move the selected slot's drawn box, shift+arrows grow/shrink it
+    fn handle_track_nudge(&mut self, ctx: &egui::Context) {
+        if ctx.memory(|m| m.focused().is_some()) {
+            return; // Typing in a text field
+        }
+        const STEP: f32 = 0.01;
+        let (shift, dir) = ctx.input(|i| {
+            let mut dir = Vec2::ZERO;
+            if i.key_pressed(egui::Key::ArrowLeft) {
+                dir.x -= STEP;
+            }
+            if i.key_pressed(egui::Key::ArrowRight) {
+                dir.x += STEP;
+            }
+            if i.key_pressed(egui::Key::ArrowUp) {
+                dir.y -= STEP;
+            }
+            if i.key_pressed(egui::Key::ArrowDown) {
+                dir.y += STEP;
+            }
+            (i.modifiers.shift, dir)
+        });
+        if dir == Vec2::ZERO {
+            return;
+        }
+        let slot = self.prompt_slot;
+        let msg = if shift {
+            UserInteractionSegMsg::AdjustTrack {
+                slot,
+                dx: 0.0,
+                dy: 0.0,
+                dw: dir.x,
+                dh: dir.y,
+            }
+        } else {
+            UserInteractionSegMsg::AdjustTrack {
+                slot,
+                dx: dir.x,
+                dy: dir.y,
+                dw: 0.0,
+                dh: 0.0,
+            }
+        };
+        self.send_interaction(msg);
+    }
+
+    // Big buttons for the things an operator does mid-show without a keyboard
+    fn draw_touch_bar(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            for slot in 0..MAX_TRACKS {
+                let label = format!("{} {}", tr("Slot"), slot);
+                ui.selectable_value(&mut self.prompt_slot, slot, label);
+            }
+            ui.separator();
+            ui.selectable_value(&mut self.touch_exclude, false, tr("＋ Include"));
+            ui.selectable_value(&mut self.touch_exclude, true, tr("－ Exclude"));
+            let slot = self.prompt_slot;
+            if ui.button(tr("Release slot")).clicked() {
+                let before = self.slot_selection(slot);
+                self.clear_slot(slot);
+                self.record_slot_edit(slot, before);
+            }
+            let lock = if self.slot_locks[slot] { tr("Unlock") } else { tr("Lock") };
+            if ui.button(lock).clicked() {
+                let before = self.slot_selection(slot);
+                self.toggle_slot_lock(slot);
+                self.record_slot_edit(slot, before);
+            }
+            ui.separator();
+            if ui.selectable_label(self.blackout, tr("Blackout")).clicked() {
+                self.set_blackout(!self.blackout);
+            }
+            if ui.selectable_label(self.frozen, tr("Freeze")).clicked() {
+                self.set_freeze(!self.frozen);
+            }
+            ui.separator();
+            let next = self.cue_player.next_index();
+            if ui.add_enabled(next.is_some(), egui::Button::new(tr("GO ▶"))).clicked() {
+                if let Some(index) = next {
+                    self.fire_cue(index);
+                }
+            }
+        });
+    }
+
+    fn toggle_slot_lock(&mut self, slot: usize) {
+        let locked = !self.slot_locks[slot];
+        self.slot_locks[slot] = locked;
+        self.send_interaction(UserInteractionSegMsg::SetSlotLock { slot, locked });
+    }
+
+    // Releases the slot's prompt, lock and brush edits so the tracker may reassign it
+    fn clear_slot(&mut self, slot: usize) {
+        self.prompts[slot] = PointPrompt::default();
+        self.send_interaction(UserInteractionSegMsg::SelectObject {
+            slot,
+            prompt: PointPrompt::default(),
+        });
+        self.slot_locks[slot] = false;
+        self.send_interaction(UserInteractionSegMsg::SetSlotLock {
+            slot,
+            locked: false,
+        });
+        self.mask_strokes.retain(|s| s.slot != slot);
+        self.send_interaction(UserInteractionSegMsg::ClearMaskEdits { slot });
+        self.send_interaction(UserInteractionSegMsg::ClearTrackAdjust { slot });
+    }
+
+    // --- Gamepad: commands act on the selected prompt slot, like the mouse does ---
+    fn poll_gamepad(&mut self, ctx: &egui::Context) {
+        let dt = self.gamepad_polled.elapsed().as_secs_f32().min(0.1);
+        self.gamepad_polled = Instant::now();
+        let Some(gamepad) = self.gamepad.as_mut() else {
+            return;
+        };
+        let commands = gamepad.poll(dt);
+        if gamepad.is_connected() {
+            ctx.request_repaint_after(Duration::from_millis(16)); // Keep the stick smooth
+        }
+        let slot = self.prompt_slot;
+        for command in commands {
+            match command {
+                GamepadCommand::Include(point) | GamepadCommand::Exclude(point) => {
+                    // The cursor moves over the pinned output; prompts live in camera space
+                    let point = self.corner_pin.to_source(point);
+                    let prompt = &mut self.prompts[slot];
+                    if matches!(command, GamepadCommand::Include(_)) {
+                        prompt.positives.push(point);
+                    } else {
+                        prompt.negatives.push(point);
+                    }
+                    let prompt = prompt.clone();
+                    self.send_interaction(UserInteractionSegMsg::SelectObject { slot, prompt });
+                }
+                GamepadCommand::ClearSlot => self.clear_slot(slot),
+                GamepadCommand::ToggleLock => self.toggle_slot_lock(slot),
+                GamepadCommand::PrevSlot => {
+                    self.prompt_slot = (self.prompt_slot + MAX_TRACKS - 1) % MAX_TRACKS;
+                }
+                GamepadCommand::NextSlot => self.prompt_slot = (self.prompt_slot + 1) % MAX_TRACKS,
+                GamepadCommand::AdjustGain(delta) => {
+                    self.set_intensity_gain(self.intensity_gain + delta);
+                }
+            }
+        }
+    }
+
+    fn draw_gamepad_cursor(&self, ui: &egui::Ui, image_rect: Rect) {
+        let Some(gamepad) = self.gamepad.as_ref().filter(|g| g.is_connected()) else {
+            return;
+        };
+        let (x, y) = gamepad.cursor();
+        let center = image_rect.lerp_inside(Vec2::new(x, y));
+        let painter = ui.painter_at(image_rect);
+        let stroke = Stroke::new(2.0, Color32::WHITE);
+        for dir in [Vec2::X, Vec2::Y] {
+            painter.line_segment([center - dir * 10.0, center + dir * 10.0], stroke);
+        }
+        painter.text(
+            center + Vec2::new(8.0, 8.0),
+            egui::Align2::LEFT_TOP,
+            format!("{} {}", tr("slot"), self.prompt_slot),
+            egui::FontId::proportional(12.0),
+            Color32::WHITE,
+        );
+    }
+
+    // Shift-click adds an include point, alt-click an exclude point, for the selected slot.
+    // In touch mode a plain tap adds the kind picked in the touch bar
+    fn handle_prompt_click(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let image_rect = response.rect;
+        if response.clicked() {
+            let modifiers = ui.input(|i| i.modifiers);
+            if let Some(pos) = response.interact_pointer_pos() {
+                // The preview shows the pinned output; prompts live in camera space
+                let point = self.corner_pin.to_source((
+                    (pos.x - image_rect.left()) / image_rect.width().max(1.0),
+                    (pos.y - image_rect.top()) / image_rect.height().max(1.0),
+                ));
+                let tap = self.touch_mode && !modifiers.shift && !modifiers.alt;
+                let before = self.slot_selection(self.prompt_slot);
+                let prompt = &mut self.prompts[self.prompt_slot];
+                let changed = if modifiers.shift || (tap && !self.touch_exclude) {
+                    prompt.positives.push(point);
+                    true
+                } else if modifiers.alt || tap {
+                    prompt.negatives.push(point);
+                    true
+                } else {
+                    false
+                };
+                if changed {
+                    let prompt = prompt.clone();
+                    self.send_interaction(UserInteractionSegMsg::SelectObject {
+                        slot: self.prompt_slot,
+                        prompt,
+                    });
+                    self.record_slot_edit(self.prompt_slot, before);
+                }
+            }
+        }
+        let painter = ui.painter_at(image_rect);
+        let prompt = &self.prompts[self.prompt_slot];
+        for (points, color) in [
+            (&prompt.positives, Color32::GREEN),
+            (&prompt.negatives, Color32::RED),
+        ] {
+            for &point in points {
+                let (x, y) = self.corner_pin.to_output(point);
+                let center = image_rect.lerp_inside(Vec2::new(x, y));
+                painter.circle_filled(center, 4.0, color);
+                painter.circle_stroke(center, 4.0, Stroke::new(1.0, Color32::BLACK));
+            }
+        }
+    }
+
+    fn draw_post_controls(&mut self, ui: &mut egui::Ui) {
+        let mut bloom = self.bloom;
+        ui.checkbox(&mut bloom.enabled, tr("Bloom"))
+            .on_hover_text(tr("Glow around bright pixels, scaled by overall loudness"));
+        ui.add_enabled_ui(bloom.enabled, |ui| {
+            ui.add(egui::Slider::new(&mut bloom.threshold, 0.0..=0.99).text(tr("Threshold")));
+            ui.add(egui::Slider::new(&mut bloom.strength, 0.0..=3.0).text(tr("Strength")));
+            ui.add(egui::Slider::new(&mut bloom.radius, 1..=20).text(tr("Radius")));
+        });
+        if bloom != self.bloom {
+            self.bloom = bloom;
+            self.send_interaction(UserInteractionSegMsg::SetBloom(bloom));
+        }
+
+        let mut chain = self.post_chain.clone();
+        for (i, effect) in chain.effects.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut effect.enabled, tr(effect.kind.label()));
+                egui::ComboBox::from_id_salt(("post_driver", i))
+                    .selected_text(effect.driver.map_or(tr("Constant"), |c| tr(c.label())))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut effect.driver, None, tr("Constant"));
+                        for channel in AnalysisChannel::ALL {
+                            let label = tr(channel.label());
+                            ui.selectable_value(&mut effect.driver, Some(channel), label);
+                        }
+                    });
+            });
+            ui.add_enabled(
+                effect.enabled,
+                egui::Slider::new(&mut effect.amount, 0.0..=1.0).text(tr("Amount")),
+            );
+        }
+        if chain != self.post_chain {
+            self.post_chain = chain.clone();
+            self.send_interaction(UserInteractionSegMsg::SetPostChain(chain));
+        }
+    }
+
+    fn draw_text_overlay_controls(&mut self, ui: &mut egui::Ui) {
+        let mut settings = self.text_overlay.clone();
+        ui.checkbox(&mut settings.enabled, tr("Show text"));
+        ui.add_enabled_ui(settings.enabled, |ui| {
+            let hint = tr("Band / song title");
+            ui.add(egui::TextEdit::singleline(&mut settings.text).hint_text(hint));
+            ui.add(egui::Slider::new(&mut settings.size, 12.0..=200.0).text(tr("Size")));
+            ui.add(egui::Slider::new(&mut settings.jitter, 0.0..=1.0).text(tr("Jitter")));
+            ui.add(egui::Slider::new(&mut settings.y_position, 0.0..=1.0).text(tr("Height")));
+            egui::ComboBox::from_label(tr("Reacts to"))
+                .selected_text(tr(settings.driver.label()))
+                .show_ui(ui, |ui| {
+                    for channel in AnalysisChannel::ALL {
+                        ui.selectable_value(&mut settings.driver, channel, tr(channel.label()));
+                    }
+                });
+        });
+        if settings != self.text_overlay {
+            self.text_overlay = settings.clone();
+            self.send_interaction(UserInteractionSegMsg::SetTextOverlay(settings));
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.lrc_path).hint_text("lyrics.lrc"));
+            if ui.button(tr("Load")).clicked() {
+                match LyricTrack::load(std::path::Path::new(self.lrc_path.trim())) {
+                    Ok(track) => {
+                        self.lyrics = Some(track);
+                        self.lyrics_error = None;
+                        self.send_interaction(UserInteractionSegMsg::SetLyrics(self.lyrics.clone()));
+                    }
+                    Err(e) => {
+                        error!("{:#}", e);
+                        self.lyrics_error = Some(format!("{:#}", e));
+                    }
+                }
+            }
+        });
+        if let Some(line_count) = self.lyrics.as_ref().map(|t| t.len()) {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}: {} {}", tr("Lyrics"), line_count, tr("lines")));
+                if ui.button(tr("Restart")).clicked() {
+                    self.send_interaction(UserInteractionSegMsg::SetLyrics(self.lyrics.clone()));
+                }
+                if ui.button(tr("Clear")).clicked() {
+                    self.lyrics = None;
+                    self.send_interaction(UserInteractionSegMsg::SetLyrics(None));
+                }
+            });
+        }
+        if let Some(e) = &self.lyrics_error {
+            ui.colored_label(Color32::RED, e);
+        }
+    }
+
+    // Path and grid only go out on Load, so the segmentation thread doesn't reload per keystroke
+    fn draw_sprite_controls(&mut self, ui: &mut egui::Ui) {
+        let mut settings = self.sprite.clone();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.sprite_path)
+                    .hint_text("frames/ or sheet.png"),
+            );
+            if ui.button(tr("Load")).clicked() {
+                settings.path = self.sprite_path.trim().to_string();
+                [settings.columns, settings.rows] = self.sprite_grid;
+                settings.enabled = !settings.path.is_empty();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Sheet grid"));
+            ui.add(egui::DragValue::new(&mut self.sprite_grid[0]).range(1..=64));
+            ui.label("×");
+            ui.add(egui::DragValue::new(&mut self.sprite_grid[1]).range(1..=64));
+        })
+        .response
+        .on_hover_text(tr("Columns × rows of a single-image sprite sheet (ignored for folders)"));
+        ui.add_enabled_ui(!settings.path.is_empty(), |ui| {
+            ui.checkbox(&mut settings.enabled, tr("Show sprite"));
+            ui.add(egui::Slider::new(&mut settings.fps, 1.0..=60.0).text("FPS"));
+            ui.add(
+                egui::Slider::new(&mut settings.intensity_speed, 0.0..=8.0)
+                    .text(tr("Speed × intensity")),
+            );
+            egui::ComboBox::from_label(tr("Anchor"))
+                .selected_text(tr(settings.anchor.label()))
+                .show_ui(ui, |ui| {
+                    for anchor in SpriteAnchor::ALL {
+                        ui.selectable_value(&mut settings.anchor, anchor, tr(anchor.label()));
+                    }
+                });
+            egui::ComboBox::from_label(tr("Blend"))
+                .selected_text(tr(settings.blend.label()))
+                .show_ui(ui, |ui| {
+                    for blend in BlendMode::ALL {
+                        ui.selectable_value(&mut settings.blend, blend, tr(blend.label()));
+                    }
+                });
+            ui.horizontal(|ui| {
+                for (slot, on) in settings.slots.iter_mut().enumerate() {
+                    ui.checkbox(on, format!("{} {}", tr("Slot"), slot));
+                }
+            });
+        });
+        if settings != self.sprite {
+            self.sprite = settings.clone();
+            self.send_interaction(UserInteractionSegMsg::SetSprite(settings));
+        }
+    }
+
+    // (Re)opens the monitor on the selected output; the old stream goes first
+    fn open_monitor(&mut self, input_rate: u32) {
+        self.close_monitor();
+        match live_audio::start_monitor_output(
+            self.monitor_device.as_deref(),
+            &self.monitor,
+            input_rate,
+        ) {
+            Ok(stream) => {
+                self.monitor_stream = Some(stream);
+                self.monitor_error = None;
+            }
+            Err(e) => {
+                error!("Monitor output failed: {:#}", e);
+                self.monitor.stop();
+                self.monitor_error = Some(format!("{:#}", e));
+            }
+        }
+    }
+
+    fn close_monitor(&mut self) {
+        self.monitor_stream = None;
+        self.monitor.stop();
+    }
+
+    // Pass-through of the capture, so a loopback input doesn't leave the performer deaf
+    fn draw_monitor_controls(&mut self, ui: &mut egui::Ui) {
+        let LiveAudioStatus::Running(rate, _) = self.live_audio_status else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            let mut on = self.monitor_stream.is_some();
+            if ui
+                .toggle_value(&mut on, tr("Monitor"))
+                .on_hover_text(
+                    tr("Plays the captured audio; avoid an output that feeds the capture input"),
+                )
+                .changed()
+            {
+                if on {
+                    self.open_monitor(rate);
+                } else {
+                    self.close_monitor();
+                }
+            }
+            let mut gain = self.monitor.gain();
+            if ui
+                .add(egui::Slider::new(&mut gain, 0.0..=2.0).text(tr("Gain")))
+                .changed()
+            {
+                self.monitor.set_gain(gain);
+            }
+        });
+        let mut choice = None;
+        egui::ComboBox::from_id_salt("monitor_output")
+            .selected_text(self.monitor_device.as_deref().unwrap_or(tr("Default output")))
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(self.monitor_device.is_none(), tr("Default output"))
+                    .clicked()
+                {
+                    choice = Some(None);
+                }
+                for name in &self.monitor_outputs {
+                    let selected = self.monitor_device.as_ref() == Some(name);
+                    if ui.selectable_label(selected, name).clicked() {
+                        choice = Some(Some(name.clone()));
+                    }
+                }
+            });
+        if let Some(device) = choice {
+            self.monitor_device = device;
+            if self.monitor_stream.is_some() {
+                self.open_monitor(rate);
+            }
+        }
+        if let Some(e) = &self.monitor_error {
+            ui.colored_label(Color32::RED, tr("Monitor failed")).on_hover_text(e);
+        }
+    }
+
+    fn stop_audio_recording(&mut self) {
+        if let Some(recorder) = self.audio_recorder.take() {
+            self.audio_record_result = Some(
+                recorder
+                    .finish()
+                    .map(|path| path.display().to_string())
+                    .map_err(|e| format!("{:#}", e)),
+            );
+        }
+    }
+
+    // Raw capture to WAV, for re-rendering a performance offline
+    fn draw_audio_recorder(&mut self, ui: &mut egui::Ui) {
+        let LiveAudioStatus::Running(rate, channels) = self.live_audio_status else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            let recording = self.audio_recorder.is_some();
+            let label = if recording {
+                tr("Stop audio recording")
+            } else {
+                tr("Record audio")
+            };
+            if ui
+                .button(label)
+                .on_hover_text(tr("Writes the raw capture (all input channels) to recordings/"))
+                .clicked()
+            {
+                if recording {
+                    self.stop_audio_recording();
+                } else {
+                    match WavRecorder::start(&self.audio_record_tap, rate, channels) {
+                        Ok(recorder) => {
+                            self.audio_recorder = Some(recorder);
+                            self.audio_record_result = None;
+                        }
+                        Err(e) => self.audio_record_result = Some(Err(format!("{:#}", e))),
+                    }
+                }
+            }
+            if let Some(recorder) = &self.audio_recorder {
+                ui.colored_label(Color32::RED, "●")
+                    .on_hover_text(recorder.path().display().to_string());
+            }
+        });
+        let mut save_as = None;
+        match &self.audio_record_result {
+            Some(Ok(path)) => {
+                ui.horizontal(|ui| {
+                    ui.small(format!("{} {}", tr("Saved"), path));
+                    if ui.small_button(tr("Save as…")).clicked() {
+                        save_as = Some(std::path::PathBuf::from(path));
+                    }
+                });
+            }
+            Some(Err(e)) => {
+                ui.colored_label(Color32::RED, tr("Audio recording failed")).on_hover_text(e);
+            }
+            None => {}
+        }
+        if let Some(from) = save_as {
+            let picked = rfd::AsyncFileDialog::new()
+                .add_filter("WAV audio", &["wav"])
+                .set_file_name(from.file_name().map_or_else(
+                    || "audio.wav".to_string(),
+                    |n| n.to_string_lossy().into_owned(),
+                ))
+                .save_file();
+            self.open_file_dialog(FileRequest::SaveAudioAs(from), picked);
+        }
+    }
+
+    // Reopens live capture on `audio_input`. A new rate or channel count respawns the audio
+    // processor, and drops what was tied to the old format (monitor rate, recording, file audio)
+    fn restart_audio_capture(&mut self) {
+        if matches!(self.live_audio_status, LiveAudioStatus::Disabled) {
+            return; // Replay stands in for the capture
+        }
+        for stream in self.audio_capture_streams.drain(..) {
+            let _ = stream.pause();
+        }
+        self.audio_sources.clear();
+        let capture = live_audio::start_audio_capture(
+            self.file_audio_tx.clone(), // The same raw audio channel the capture fed
+            self.audio_capture_stop_signal.clone(),
+            self.audio_input.as_deref(),
+            &self.loopback_names,
+            self.monitor.clone(),
+            self.audio_record_tap.clone(),
+        );
+        let capture = match capture {
+            Ok(capture) => capture,
+            Err(e) => {
+                let m = format!("Audio capture failed: {}", e);
+                error!("{}", m);
+                self.live_audio_status = LiveAudioStatus::Error(m);
+                return;
+            }
+        };
+        let format = (capture.sample_rate, capture.channels);
+        let same_format = matches!(
+            self.live_audio_status,
+            LiveAudioStatus::Running(r, c) if (r, c) == format
+        );
+        if !same_format || self.audio_processor_thread.is_none() {
+            self.stop_audio_recording();
+            self.stop_media_file();
+            if let Some(thread) = self.audio_processor_thread.take() {
+                thread.stop();
+            }
+            self.audio_processor_thread = Some((self.spawn_audio_processor)(format.0, format.1));
+            if self.monitor_stream.is_some() {
+                self.open_monitor(format.0);
+            }
+        }
+        self.live_audio_status = LiveAudioStatus::Running(format.0, format.1);
+        self.audio_capture_streams = capture.streams;
+        self.audio_sources = capture.sources;
+    }
+
+    // Test tone through the default output; the input that hears it is the loopback
+    fn draw_loopback_wizard(&mut self, ui: &mut egui::Ui) {
+        if self.loopback_wizard.as_ref().is_some_and(|w| w.is_done()) {
+            if let Some(wizard) = self.loopback_wizard.take() {
+                let result = wizard.finish().map_err(|e| format!("{:#}", e));
+                if let Ok(name) = &result {
+                    info!("Loopback wizard selected '{}'", name);
+                    self.audio_input = Some(name.clone());
+                    self.restart_audio_capture();
+                }
+                self.loopback_result = Some(result);
+            }
+        }
+        ui.horizontal(|ui| {
+            if self.loopback_wizard.is_some() {
+                ui.spinner();
+                ui.label(tr("Playing test tone…"));
+                ui.ctx().request_repaint_after(Duration::from_millis(100));
+            } else if ui
+                .button(tr("Find loopback input"))
+                .on_hover_text(tr(
+                    "Plays a short tone and picks the input that carries system audio",
+                ))
+                .clicked()
+            {
+                match LoopbackWizard::start() {
+                    Ok(w) => {
+                        self.loopback_wizard = Some(w);
+                        self.loopback_result = None;
+                    }
+                    Err(e) => self.loopback_result = Some(Err(format!("{:#}", e))),
+                }
+            }
+        });
+        match &self.loopback_result {
+            Some(Ok(name)) => {
+                ui.small(format!("{} {}", tr("Capturing from:"), name));
+            }
+            Some(Err(e)) => {
+                ui.colored_label(Color32::RED, tr("No loopback found")).on_hover_text(e);
+            }
+            None => {}
+        }
+    }
+
+    // Click round-trip measurement, and the offset it sets on the intensity stream
+    fn draw_latency_controls(&mut self, ui: &mut egui::Ui) {
+        if self.latency_calibration.as_ref().is_some_and(|c| c.is_done()) {
+            if let Some(calibration) = self.latency_calibration.take() {
+                let result = calibration.finish().map_err(|e| e.to_string());
+                if let Ok(latency) = &result {
+                    self.audio_offset_ms = -(latency.as_millis() as i32);
+                    self.send_interaction(UserInteractionSegMsg::SetAudioOffset(
+                        self.audio_offset_ms,
+                    ));
+                }
+                self.latency_result = Some(result);
+            }
+        }
+        ui.horizontal(|ui| {
+            let running = matches!(self.live_audio_status, LiveAudioStatus::Running(..));
+            if self.latency_calibration.is_some() {
+                ui.spinner();
+                ui.label(tr("Listening for clicks…"));
+                ui.ctx().request_repaint_after(Duration::from_millis(100));
+            } else if ui
+                .add_enabled(running, egui::Button::new(tr("Calibrate latency")))
+                .on_hover_text(
+                    tr("Plays clicks through the default output; route it into the capture device"),
+                )
+                .clicked()
+            {
+                match Calibration::start(&self.onset_probe) {
+                    Ok(c) => {
+                        self.latency_calibration = Some(c);
+                        self.latency_result = None;
+                    }
+                    Err(e) => self.latency_result = Some(Err(format!("{:#}", e))),
+                }
+            }
+        });
+        match &self.latency_result {
+            Some(Ok(latency)) => {
+                ui.small(format!("{}: {} ms", tr("Measured round trip"), latency.as_millis()));
+            }
+            Some(Err(e)) => {
+                ui.colored_label(Color32::RED, tr("Calibration failed")).on_hover_text(e);
+            }
+            None => {}
+        }
+        if ui
+            .add(egui::Slider::new(&mut self.audio_offset_ms, -500..=500).text(tr("Offset (ms)")))
+            .on_hover_text(tr("Negative = look-ahead (extrapolated), positive = delay the visuals"))
+            .changed()
+        {
+            self.send_interaction(UserInteractionSegMsg::SetAudioOffset(self.audio_offset_ms));
+        }
+    }
+
+    fn set_intensity_gain(&mut self, gain: f32) {
+        self.intensity_gain = gain.clamp(0.0, MAX_INTENSITY_GAIN);
+        self.send_interaction(UserInteractionSegMsg::SetIntensityGain(self.intensity_gain));
+    }
+
+    fn draw_intensity_gain_controls(&mut self, ui: &mut egui::Ui) {
+        let mut gain = self.intensity_gain;
+        ui.horizontal(|ui| {
+            let slider = egui::Slider::new(&mut gain, 0.0..=MAX_INTENSITY_GAIN);
+            ui.add(slider.text(tr("Intensity gain")))
+                .on_hover_text(tr("Scales the level bands after the curves"));
+            if self.gamepad.as_ref().is_some_and(|g| g.is_connected()) {
+                ui.small("🎮").on_hover_text(tr("Gamepad triggers adjust this"));
+            }
+        });
+        if gain != self.intensity_gain {
+            self.set_intensity_gain(gain);
+        }
+    }
+
+    // How band energies become 0-1 intensities, and the FFT they come from
+    fn draw_analysis_controls(&mut self, ui: &mut egui::Ui) {
+        let mut settings = self.analysis.get();
+        let before = settings;
+        let mode = &mut settings.normalization;
+        ui.collapsing(tr("Normalization"), |ui| {
+            egui::ComboBox::from_id_salt("normalization_mode")
+                .selected_text(tr(mode.kind()))
+                .show_ui(ui, |ui| {
+                    for kind in Normalization::KINDS {
+                        if ui.selectable_label(mode.kind() == kind, tr(kind)).clicked()
+                            && mode.kind() != kind
+                        {
+                            *mode = Normalization::of_kind(kind);
+                        }
+                    }
+                });
+            match mode {
+                Normalization::Adaptive { window_hops } => {
+                    ui.add(egui::Slider::new(window_hops, 10..=1000).text(tr("Window (hops)")))
+                        .on_hover_text(tr("Each band against its own max over this many hops"));
+                }
+                Normalization::FixedDbfs {
+                    reference_db,
+                    floor_db,
+                } => {
+                    let reference = egui::Slider::new(reference_db, -40.0..=0.0);
+                    ui.add(reference.text(tr("Reference (dBFS)")))
+                        .on_hover_text(tr("Level that reads as full intensity"));
+                    ui.add(egui::Slider::new(floor_db, -96.0..=-20.0).text(tr("Floor (dBFS)")))
+                        .on_hover_text(tr("Level that reads as zero"));
+                }
+                Normalization::SlowAgc { time_secs } => {
+                    ui.add(egui::Slider::new(time_secs, 2.0..=120.0).text(tr("Time (s)")))
+                        .on_hover_text(tr("Louder sections read louder for about this long"));
+                }
+            }
+        });
+        ui.collapsing(tr("Analysis"), |ui| {
+            egui::ComboBox::from_id_salt("analysis_backend")
+                .selected_text(tr(settings.backend.label()))
+                .show_ui(ui, |ui| {
+                    for backend in AnalysisBackend::ALL {
+                        ui.selectable_value(&mut settings.backend, backend, tr(backend.label()));
+                    }
+                })
+                .response
+                .on_hover_text(tr("⅓-octave bands weigh every octave alike, as the ear does"));
+            let fft = &mut settings.fft;
+            // Overlap is kept when the size changes, so the hop follows the size
+            let (size, mut overlap) = (fft.size, fft.size / fft.hop.max(1));
+            let overlap_before = overlap;
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("fft_size")
+                    .selected_text(format!("{} pt", fft.size))
+                    .show_ui(ui, |ui| {
+                        for size in FftSettings::SIZES {
+                            ui.selectable_value(&mut fft.size, size, format!("{} pt", size));
+                        }
+                    })
+                    .response
+                    .on_hover_text(tr("Longer windows resolve bass better but react later"));
+                egui::ComboBox::from_id_salt("fft_window")
+                    .selected_text(fft.window.label())
+                    .show_ui(ui, |ui| {
+                        for window in WindowFunction::ALL {
+                            ui.selectable_value(&mut fft.window, window, window.label());
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr("Overlap:"));
+                for (factor, label) in [(2, "50%"), (4, "75%"), (8, "87.5%")] {
+                    ui.selectable_value(&mut overlap, factor, label);
+                }
+            });
+            if fft.size != size || overlap != overlap_before {
+                fft.hop = fft.size / overlap.max(1);
+            }
+        });
+        if settings != before {
+            settings.fft = settings.fft.sanitized();
+            self.analysis.set(settings);
+        }
+    }
+
+    // Per-band noise floor; a gated band reads zero until it crosses the threshold again
+    fn draw_noise_gate_controls(&mut self, ui: &mut egui::Ui) {
+        let mut changed = Vec::new();
+        ui.collapsing(tr("Noise gate"), |ui| {
+            for (i, channel) in GATED_CHANNELS.iter().enumerate() {
+                let gate = &mut self.noise_gates[i];
+                let before = *gate;
+                ui.horizontal(|ui| {
+                    ui.label(tr(channel.label()));
+                    ui.add(egui::Slider::new(&mut gate.threshold, 0.0..=0.5).text(tr("Floor")))
+                        .on_hover_text(tr("Below this the band outputs exactly zero; 0 = off"));
+                    ui.add(
+                        egui::Slider::new(&mut gate.hysteresis, 0.0..=0.2).text(tr("Hysteresis")),
+                    )
+                    .on_hover_text(tr("An open gate closes only this far below the floor"));
+                });
+                gate.hysteresis = gate.hysteresis.min(gate.threshold);
+                if *gate != before {
+                    changed.push((*channel, *gate));
+                }
+            }
+        });
+        for (channel, gate) in changed {
+            self.send_interaction(UserInteractionSegMsg::SetNoiseGate { channel, gate });
+        }
+    }
+
+    // Per-channel response: pick a channel, then a curve kind; splines are edited in the plot
+    // (drag a point, click to add one, right-click to remove it)
+    // VU-style bars per band with the held peak; red once normalization is saturating
+    fn draw_band_meters(&self, ui: &mut egui::Ui) {
+        const BAR_HEIGHT: f32 = 8.0;
+        let Some(meters) = &self.band_meters else {
+            return;
+        };
+        for (meter, channel) in meters.iter().zip(METERED_CHANNELS) {
+            ui.horizontal(|ui| {
+                ui.add_sized([40.0, BAR_HEIGHT], egui::Label::new(tr(channel.label())));
+                let width = ui.available_width();
+                let (rect, response) =
+                    ui.allocate_exact_size(Vec2::new(width, BAR_HEIGHT), Sense::hover());
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(rect, 1.0, Color32::from_gray(20));
+                let fill = if meter.clip {
+                    Color32::from_rgb(220, 60, 40)
+                } else {
+                    Color32::from_rgb(60, 190, 90)
+                };
+                let mut bar = rect;
+                bar.set_width(width * meter.level.clamp(0.0, 1.0));
+                painter.rect_filled(bar, 1.0, fill);
+                let x = rect.left() + width * meter.peak.clamp(0.0, 1.0);
+                painter.line_segment(
+                    [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+                    Stroke::new(2.0, Color32::from_gray(230)),
+                );
+                if meter.clip {
+                    response.on_hover_text(tr(
+                        "Normalization is saturating: the band sits at its max",
+                    ));
+                }
+            });
+        }
+    }
+
+    fn draw_response_curve_editor(&mut self, ui: &mut egui::Ui) {
+        const PLOT_SIZE: f32 = 140.0;
+        const POINT_RADIUS: f32 = 4.0;
+        let channel = self.curve_channel;
+        let mut curve = self.response_curves.get(channel);
+        let before = curve.clone();
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("response_channel")
+                .selected_text(tr(channel.label()))
+                .show_ui(ui, |ui| {
+                    for c in AnalysisChannel::ALL {
+                        ui.selectable_value(&mut self.curve_channel, c, tr(c.label()));
+                    }
+                });
+            egui::ComboBox::from_id_salt("response_kind")
+                .selected_text(curve.kind())
+                .show_ui(ui, |ui| {
+                    for kind in ResponseCurve::KINDS {
+                        if ui.selectable_label(curve.kind() == kind, kind).clicked()
+                            && curve.kind() != kind
+                        {
+                            curve = ResponseCurve::of_kind(kind);
+                        }
+                    }
+                });
+        })
+        .response
+        .on_hover_text(tr("Shapes how this channel's 0-1 intensity drives the visuals"));
+        if self.curve_channel != channel {
+            return; // Redraw next frame with the newly picked channel's curve
+        }
+        match &mut curve {
+            ResponseCurve::Gamma(gamma) => {
+                ui.add(egui::Slider::new(gamma, 0.2..=5.0).text(tr("Gamma")))
+                    .on_hover_text(tr(
+                        "< 1 lifts quiet passages, > 1 saves the effect for loud ones",
+                    ));
+            }
+            ResponseCurve::SCurve(amount) => {
+                ui.add(egui::Slider::new(amount, 0.0..=1.0).text(tr("Contrast")));
+            }
+            _ => {}
+        }
+
+        let (response, painter) =
+            ui.allocate_painter(Vec2::splat(PLOT_SIZE), Sense::click_and_drag());
+        let rect = response.rect;
+        let to_screen = |(x, y): (f32, f32)| {
+            Pos2::new(rect.left() + x * rect.width(), rect.bottom() - y * rect.height())
+        };
+        let from_screen = |p: Pos2| {
+            (
+                ((p.x - rect.left()) / rect.width()).clamp(0.0, 1.0),
+                ((rect.bottom() - p.y) / rect.height()).clamp(0.0, 1.0),
+            )
+        };
+        painter.rect_filled(rect, 2.0, Color32::from_gray(20));
+        painter.line_segment(
+            [rect.left_bottom(), rect.right_top()],
+            Stroke::new(1.0, Color32::from_gray(60)),
+        );
+        let steps = PLOT_SIZE as usize;
+        let line: Vec<Pos2> = (0..=steps)
+            .map(|i| {
+                let x = i as f32 / steps as f32;
+                to_screen((x, curve.apply(x)))
+            })
+            .collect();
+        painter.add(egui::Shape::line(line, Stroke::new(1.5, Color32::LIGHT_GREEN)));
+
+        let can_add = curve.can_add_point();
+        if let ResponseCurve::Spline(points) = &mut curve {
+            let pointer = response.interact_pointer_pos().or(response.hover_pos());
+            let nearest = pointer.and_then(|p| {
+                points
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pt)| (i, to_screen(*pt).distance(p)))
+                    .filter(|(_, d)| *d <= POINT_RADIUS * 2.0)
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(i, _)| i)
+            });
+            if response.dragged() {
+                let id = response.id.with("drag_point");
+                let dragging = ui.memory(|m| m.data.get_temp::<usize>(id)).or(nearest);
+                if let (Some(i), Some(p)) = (dragging.filter(|i| *i < points.len()), pointer) {
+                    // Held between its neighbours, so sorting never reorders a point mid-drag
+                    let lo = if i > 0 { points[i - 1].0 } else { 0.0 };
+                    let hi = points.get(i + 1).map_or(1.0, |n| n.0);
+                    let (x, y) = from_screen(p);
+                    points[i] = (x.clamp(lo, hi), y);
+                    ui.memory_mut(|m| m.data.insert_temp(id, i));
+                }
+            } else {
+                ui.memory_mut(|m| m.data.remove::<usize>(response.id.with("drag_point")));
+            }
+            if response.secondary_clicked() {
+                if let Some(i) = nearest {
+                    points.remove(i);
+                }
+            } else if response.clicked() && nearest.is_none() && can_add {
+                if let Some(p) = pointer {
+                    points.push(from_screen(p));
+                }
+            }
+            for pt in points.iter() {
+                painter.circle_filled(to_screen(*pt), POINT_RADIUS, Color32::WHITE);
+            }
+        }
+
+        if curve != before {
+            self.response_curves.set(channel, curve.clone());
+            self.send_interaction(UserInteractionSegMsg::SetResponseCurve { channel, curve });
+        }
+    }
+
+    fn draw_corner_pin_controls(&mut self, ui: &mut egui::Ui) {
+        let before = self.corner_pin;
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.corner_pin.enabled, tr("Corner pin"));
+            ui.add_enabled_ui(self.corner_pin.enabled, |ui| {
+                ui.toggle_value(&mut self.calibrating_pin, tr("Calibrate"))
+                    .on_hover_text(tr("Drag the handles on the video onto the surface's corners"));
+                if ui.button(tr("Reset")).clicked() {
+                    self.corner_pin.corners = CornerPin::IDENTITY;
+                }
+            });
+        });
+        if !self.corner_pin.enabled {
+            self.calibrating_pin = false;
+        }
+        if self.corner_pin != before {
+            self.send_interaction(UserInteractionSegMsg::SetCornerPin(self.corner_pin));
+        }
+        if ui
+            .checkbox(&mut self.masked_output, tr("Masked output"))
+            .on_hover_text(tr(
+                "Black outside the tracked objects: aim the projector back at the scene",
+            ))
+            .changed()
+        {
+            self.send_interaction(UserInteractionSegMsg::SetMaskedOutput(self.masked_output));
+        }
+        ui.horizontal(|ui| {
+            if ui
+                .selectable_label(self.blackout, tr("Blackout"))
+                .on_hover_text(tr("Shift+B: fade the output to black, and back"))
+                .clicked()
+            {
+                self.set_blackout(!self.blackout);
+            }
+            if ui
+                .selectable_label(self.frozen, tr("Freeze"))
+                .on_hover_text(tr("Shift+F: hold the current camera frame and audio analysis"))
+                .clicked()
+            {
+                self.set_freeze(!self.frozen);
+            }
+        });
+        let fade = ui.add(
+            egui::Slider::new(&mut self.blackout_fade_secs, 0.0..=10.0)
+                .text(tr("Blackout fade (s)")),
+        );
+        // The fade back up uses whatever was sent last
+        if fade.changed() && self.blackout {
+            self.set_blackout(true);
+        }
+        self.draw_quantize_controls(ui);
+    }
+
+    // Style/palette changes land on the next beat, bar or phrase instead of mid-beat
+    fn draw_quantize_controls(&mut self, ui: &mut egui::Ui) {
+        let before = self.quantize_grid;
+        ui.horizontal(|ui| {
+            ui.label(tr("Switch looks:"));
+            egui::ComboBox::from_id_salt("quantize_grid")
+                .selected_text(tr(self.quantize_grid.label()))
+                .show_ui(ui, |ui| {
+                    for grid in QuantizeGrid::ALL {
+                        ui.selectable_value(&mut self.quantize_grid, grid, tr(grid.label()));
+                    }
+                });
+        });
+        if self.quantize_grid != before {
+            self.send_interaction(UserInteractionSegMsg::SetQuantize(self.quantize_grid));
+        }
+        ui.horizontal(|ui| {
+            if ui
+                .button(tr("Tap"))
+                .on_hover_text(tr("Tap along with the beat to set the tempo"))
+                .clicked()
+            {
+                self.tap_tempo(Instant::now());
+            }
+            match self.tap_bpm {
+                Some(bpm) => {
+                    ui.label(format!("{:.1} BPM", bpm));
+                    if ui.small_button(tr("Auto")).clicked() {
+                        self.tap_bpm = None;
+                        self.tap_times.clear();
+                        self.send_interaction(UserInteractionSegMsg::SetTempo(None));
+                    }
+                }
+                None => {
+                    ui.label(tr("BPM from beat detection"));
+                }
+            }
+            if ui
+                .button(tr("Downbeat"))
+                .on_hover_text(tr("Press on the one: bars and phrases are counted from here"))
+                .clicked()
+            {
+                self.send_interaction(UserInteractionSegMsg::MarkDownbeat);
+            }
+        });
+    }
+
+    // Mean interval of the recent taps; a pause of two seconds starts over
+    fn tap_tempo(&mut self, now: Instant) {
+        if self.tap_times.last().is_some_and(|t| now - *t > Duration::from_secs(2)) {
+            self.tap_times.clear();
+        }
+        self.tap_times.push(now);
+        if self.tap_times.len() > 8 {
+            self.tap_times.remove(0);
+        }
+        if let [first, .., last] = self.tap_times[..] {
+            let taps = (self.tap_times.len() - 1) as f32;
+            let bpm = 60.0 * taps / (last - first).as_secs_f32();
+            self.tap_bpm = Some(bpm);
+            self.send_interaction(UserInteractionSegMsg::SetTempo(Some(bpm)));
+        }
+    }
+
+    fn set_blackout(&mut self, engaged: bool) {
+        self.blackout = engaged;
+        self.send_interaction(UserInteractionSegMsg::SetBlackout {
+            engaged,
+            fade_secs: self.blackout_fade_secs,
+        });
+    }
+
+    fn set_freeze(&mut self, frozen: bool) {
+        self.frozen = frozen;
+        self.send_interaction(UserInteractionSegMsg::SetFreeze(frozen));
+    }
+
+    // How often the model runs; skipped frames reuse masks and extrapolate bboxes
+    fn draw_cadence_controls(&mut self, ui: &mut egui::Ui) {
+        let before = self.inference_cadence;
+        ui.horizontal(|ui| {
+            ui.label(tr("Inference:"));
+            egui::ComboBox::from_id_salt("inference_cadence")
+                .selected_text(match self.inference_cadence {
+                    InferenceCadence::EveryFrame => "Every frame",
+                    InferenceCadence::Frames(_) => tr("Every N frames"),
+                    InferenceCadence::Millis(_) => tr("Every T ms"),
+                })
+                .show_ui(ui, |ui| {
+                    let cadence = &mut self.inference_cadence;
+                    ui.selectable_value(cadence, InferenceCadence::EveryFrame, tr("Every frame"));
+                    if ui
+                        .selectable_label(
+                            matches!(cadence, InferenceCadence::Frames(_)),
+                            tr("Every N frames"),
+                        )
+                        .clicked()
+                    {
+                        *cadence = InferenceCadence::Frames(2);
+                    }
+                    if ui
+                        .selectable_label(
+                            matches!(cadence, InferenceCadence::Millis(_)),
+                            tr("Every T ms"),
+                        )
+                        .clicked()
+                    {
+                        *cadence = InferenceCadence::Millis(100);
+                    }
+                });
+        });
+        match &mut self.inference_cadence {
+            InferenceCadence::EveryFrame => {}
+            InferenceCadence::Frames(n) => {
+                ui.add(egui::Slider::new(n, 2..=30).text(tr("Frames")));
+            }
+            InferenceCadence::Millis(ms) => {
+                ui.add(egui::Slider::new(ms, 20..=1000).text("ms"));
+            }
+        }
+        if self.inference_cadence != before {
+            self.send_interaction(UserInteractionSegMsg::SetInferenceCadence(
+                self.inference_cadence,
+            ));
+        }
+        let mut pacing = self.frame_pacing;
+        ui.checkbox(&mut pacing.paced, tr("Pace frames")).on_hover_text(tr(
+            "Present at even intervals from capture timestamps instead of always the newest frame",
+        ));
+        ui.checkbox(&mut pacing.interpolate, tr("Interpolate boxes"))
+            .on_hover_text(tr("Move boxes along their velocity on frames the model skips"));
+        if pacing != self.frame_pacing {
+            self.frame_pacing = pacing;
+            self.send_interaction(UserInteractionSegMsg::SetFramePacing(pacing));
+        }
+    }
+
+    fn draw_lut_controls(&mut self, ui: &mut egui::Ui) {
+        let mut tone = self.tone_map;
+        ui.checkbox(&mut tone.enabled, tr("Tone map"))
+            .on_hover_text(tr("Roll off highlights instead of clipping them to white"));
+        ui.add_enabled_ui(tone.enabled, |ui| {
+            egui::ComboBox::from_label(tr("Curve"))
+                .selected_text(tr(tone.curve.label()))
+                .show_ui(ui, |ui| {
+                    for curve in ToneCurve::ALL {
+                        ui.selectable_value(&mut tone.curve, curve, tr(curve.label()));
+                    }
+                });
+            ui.add(egui::Slider::new(&mut tone.exposure, -2.0..=2.0).text(tr("Exposure (stops)")));
+            ui.checkbox(&mut tone.auto_levels, tr("Auto levels"))
+                .on_hover_text(tr("Follow the frame's black and white points"));
+        });
+        if tone != self.tone_map {
+            self.tone_map = tone;
+            self.send_interaction(UserInteractionSegMsg::SetToneMap(tone));
+        }
+
+        let mut settings = self.lut.clone();
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("lut_picker")
+                .selected_text(tr("Pick…"))
+                .show_ui(ui, |ui| {
+                    for file in &self.lut_files {
+                        let name = file.file_name().unwrap_or_default().to_string_lossy();
+                        if ui.selectable_label(false, name).clicked() {
+                            self.lut_path = file.display().to_string();
+                        }
+                    }
+                });
+            if ui
+                .button("⟳")
+                .on_hover_text(format!("{} {}/", tr("Rescan"), lut::LUT_DIR))
+                .clicked()
+            {
+                self.lut_files = lut::list_luts();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.lut_path).hint_text("grade.cube"));
+            if ui.button(tr("Load")).clicked() {
+                settings.path = self.lut_path.trim().to_string();
+            }
+            if ui.button("…").on_hover_text(tr("Browse for a .cube file")).clicked() {
+                let picked = rfd::AsyncFileDialog::new()
+                    .add_filter("Cube LUT", &["cube"])
+                    .set_directory(lut::LUT_DIR)
+                    .pick_file();
+                self.open_file_dialog(FileRequest::Lut, picked);
+            }
+            if ui.add_enabled(!settings.path.is_empty(), egui::Button::new(tr("Clear"))).clicked() {
+                settings.path.clear();
+            }
+        });
+        ui.add_enabled(
+            !settings.path.is_empty(),
+            egui::Slider::new(&mut settings.strength, 0.0..=1.0).text(tr("Strength")),
+        );
+        if settings != self.lut {
+            self.lut = settings.clone();
+            self.send_interaction(UserInteractionSegMsg::SetLut(settings));
+        }
+    }
+
+    // --- Cues: a cue only touches the settings it names ---
+    fn apply_cue(&mut self, cue: Cue) {
+        if let Some(styles) = cue.slot_styles {
+            for (slot, style) in styles.into_iter().enumerate() {
+                self.set_slot_style(slot, style);
+            }
+        }
+        if let Some(routing) = cue.slot_routing {
+            self.slot_routing = routing;
+            for (slot, channel) in routing.into_iter().enumerate() {
+                self.send_interaction(UserInteractionSegMsg::SetSlotRouting { slot, channel });
+            }
+        }
+        if let Some(locks) = cue.slot_locks {
+            self.slot_locks = locks;
+            for (slot, locked) in locks.into_iter().enumerate() {
+                self.send_interaction(UserInteractionSegMsg::SetSlotLock { slot, locked });
+            }
+        }
+        if let Some(bloom) = cue.bloom {
+            self.bloom = bloom;
+            self.send_interaction(UserInteractionSegMsg::SetBloom(bloom));
+        }
+        if let Some(chain) = cue.post_chain {
+            self.post_chain = chain.clone();
+            self.send_interaction(UserInteractionSegMsg::SetPostChain(chain));
+        }
+        if let Some(text) = cue.text_overlay {
+            self.text_overlay = text.clone();
+            self.send_interaction(UserInteractionSegMsg::SetTextOverlay(text));
+        }
+    }
+
+    fn fire_cue(&mut self, index: usize) {
+        if let Some(cue) = self.cue_player.fire(index).cloned() {
+            self.apply_cue(cue);
+            // GO from the cue list buttons happens mid-frame; it isn't an edit to undo either
+            self.undo.resync(&self.param_snapshot());
+        }
+    }
+
+    // --- Remote control: actions from Companion / Stream Deck, state back for button feedback ---
+    fn publish_control_actions(&self) {
+        if let Some(control) = &self.control {
+            let numbers: Vec<u32> = self.cue_player.list.cues.iter().map(|c| c.number).collect();
+            control.set_actions(ControlAction::catalog(&numbers));
+        }
+    }
+
+    fn poll_control(&mut self) {
+        let Some(actions) = self.control.as_ref().map(|c| c.drain()) else {
+            return;
+        };
+        for action in actions {
+            self.run_control_action(action);
+        }
+        let state = ControlState {
+            cue: self
+                .cue_player
+                .current
+                .and_then(|i| self.cue_player.list.cues.get(i))
+                .map(|c| c.label()),
+            show_running: self.cue_player.show_elapsed_secs().is_some(),
+            recording: self.session_recording.is_some(),
+            keyed: self.keyed_recording.is_some(),
+            masked_output: self.masked_output,
+            blackout: self.blackout,
+            frozen: self.frozen,
+            crossfader: self.crossfader,
+            master_mix: self.master_mix,
+        };
+        if let Some(control) = &self.control {
+            control.publish_state(state);
+        }
+    }
+
+    fn run_control_action(&mut self, action: ControlAction) {
+        match action {
+            ControlAction::NextCue => {
+                if let Some(index) = self.cue_player.next_index() {
+                    self.fire_cue(index);
+                }
+            }
+            ControlAction::GoCue(number) => {
+                let index = self.cue_player.list.cues.iter().position(|c| c.number == number);
+                if let Some(index) = index {
+                    self.fire_cue(index);
+                }
+            }
+            ControlAction::StartShow => self.cue_player.start_show(),
+            ControlAction::StopShow => self.cue_player.stop_show(),
+            ControlAction::ClearSlot(slot) => self.clear_slot(slot),
+            ControlAction::ToggleRecording => {
+                let recording = self.session_recording.is_some();
+                if let Err(e) = self
+                    .user_interaction_tx
+                    .send(UserInteractionSegMsg::SetSessionRecording(!recording))
+                {
+                    warn!("Failed to toggle session recording: {}", e);
+                }
+            }
+            ControlAction::ToggleKeyed => {
+                let config = self
+                    .keyed_recording
+                    .is_none()
+                    .then(|| self.keyed_config.clone());
+                if let Err(e) = self
+                    .user_interaction_tx
+                    .send(UserInteractionSegMsg::SetKeyedRecording(config))
+                {
+                    warn!("Failed to toggle keyed recording: {}", e);
+                }
+            }
+            ControlAction::ToggleMaskedOutput => {
+                self.masked_output = !self.masked_output;
+                self.send_interaction(UserInteractionSegMsg::SetMaskedOutput(self.masked_output));
+            }
+            ControlAction::ToggleBlackout => self.set_blackout(!self.blackout),
+            ControlAction::ToggleFreeze => self.set_freeze(!self.frozen),
+            ControlAction::CrossfadeTo(value) => self.set_crossfader(value),
+            ControlAction::RevealTo(value) => self.set_master_mix(value),
+        }
+    }
+
+    // --- Opening hours: standby outside them, plus the nightly camera cycle ---
+    fn poll_schedule(&mut self, ctx: &egui::Context) {
+        let Some(scheduler) = self.scheduler.as_mut() else {
+            return;
+        };
+        ctx.request_repaint_after(SCHEDULE_CHECK_INTERVAL);
+        if self.last_schedule_check.elapsed() < SCHEDULE_CHECK_INTERVAL {
+            return;
+        }
+        self.last_schedule_check = Instant::now();
+        let now = chrono::Local::now().naive_local();
+        let open = scheduler.is_open(now);
+        if scheduler.camera_cycle_due(now) && open {
+            info!("Schedule: cycling the camera");
+            self.camera_control.request_cycle();
+        }
+        if open != self.standby {
+            return;
+        }
+        self.standby = !open;
+        let file_video = self.file_source.as_ref().is_some_and(FileSource::feeds_video);
+        self.camera_control.set_standby(self.standby || file_video);
+        if self.standby {
+            info!("Schedule: closed, standing by");
+            self.resume_live = self.go_live;
+            self.go_live = false;
+            if let Some(stream) = self.rtmp_stream.take() {
+                stream.stop();
+            }
+            // Browser viewers see the black standby screen rather than the last frame
+            if let (Some(preview), Some(size)) = (&self.preview, self.texture_size) {
+                let size = [size.x as usize, size.y as usize];
+                preview.publish(Arc::new(egui::ColorImage::new(size, Color32::BLACK)));
+            }
+            self.resume_recording = self.session_recording.is_some();
+            self.resume_keyed = self.keyed_recording.is_some();
+            self.set_recordings(false, false);
+        } else {
+            info!("Schedule: open");
+            self.go_live = self.resume_live;
+            self.set_recordings(self.resume_recording, self.resume_keyed);
+        }
+    }
+
+    // Starts or stops the session and keyed recordings; ones already in that state are left
+    fn set_recordings(&mut self, session: bool, keyed: bool) {
+        if session != self.session_recording.is_some() {
+            let _ = self
+                .user_interaction_tx
+                .send(UserInteractionSegMsg::SetSessionRecording(session));
+        }
+        if keyed != self.keyed_recording.is_some() {
+            let config = keyed.then(|| self.keyed_config.clone());
+            let _ = self
+                .user_interaction_tx
+                .send(UserInteractionSegMsg::SetKeyedRecording(config));
+        }
+    }
+
+    // Timed cues, hotkeys (space = GO next) and MIDI program changes
+    fn poll_cues(&mut self, ctx: &egui::Context) {
+        self.chase_timecode(ctx);
+        self.chase_link(ctx);
+        if let Some(index) = self.cue_player.due_timed_cue() {
+            self.fire_cue(index);
+        }
+        if self.cue_player.show_elapsed_secs().is_some() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+        if !ctx.memory(|m| m.focused().is_some()) {
+            // Consumed first, so a cue bound to the same letter doesn't fire as well
+            let (blackout, freeze) = ctx.input_mut(|i| {
+                (
+                    i.consume_key(egui::Modifiers::SHIFT, egui::Key::B),
+                    i.consume_key(egui::Modifiers::SHIFT, egui::Key::F),
+                )
+            });
+            if blackout {
+                self.set_blackout(!self.blackout);
+            }
+            if freeze {
+                self.set_freeze(!self.frozen);
+            }
+            let pressed: Vec<egui::Key> = ctx.input(|i| {
+                i.events
+                    .iter()
+                    .filter_map(|e| match e {
+                        egui::Event::Key {
+                            key,
+                            pressed: true,
+                            repeat: false,
+                            ..
+                        } => Some(*key),
+                        _ => None,
+                    })
+                    .collect()
+            });
+            for key in pressed {
+                let index = if key == egui::Key::Space {
+                    self.cue_player.next_index()
+                } else {
+                    self.cue_player.index_for_key(key)
+                };
+                if let Some(index) = index {
+                    self.fire_cue(index);
+                }
+            }
+        }
+        while let Ok(msg) = self.midi_rx.try_recv() {
+            match msg {
+                MidiMessage::ProgramChange { program, .. } => {
+                    if let Some(index) = self.cue_player.index_for_program(program) {
+                        self.fire_cue(index);
+                    }
+                }
+                MidiMessage::ControlChange {
+                    channel,
+                    controller,
+                    value,
+                } => {
+                    if self.learning_cc {
+                        info!("Crossfader bound to CC {} (ch {})", controller, channel + 1);
+                        self.crossfader_cc = Some((channel, controller));
+                        self.learning_cc = false;
+                    }
+                    if self.crossfader_cc == Some((channel, controller)) {
+                        self.set_crossfader(value as f32 / 127.0);
+                    }
+                }
+                MidiMessage::TimecodeQuarterFrame(data) => {
+                    if let Some(tc) = self.mtc_decoder.quarter_frame(data) {
+                        self.timecode_clock.update(tc, TimecodeSource::Mtc);
+                    }
+                }
+                MidiMessage::TimecodeFullFrame {
+                    hours,
+                    minutes,
+                    seconds,
+                    frames,
+                } => {
+                    let tc = MtcDecoder::full_frame(hours, minutes, seconds, frames);
+                    self.timecode_clock.update(tc, TimecodeSource::Mtc);
+                }
+            }
+        }
+    }
+
+    // --- Timecode chase: cues follow the master clock, and so do the animations ---
+    fn chase_timecode(&mut self, ctx: &egui::Context) {
+        let chased = self.timecode_clock.now();
+        let show_secs = chased.map(|(_, _, secs)| secs);
+        // Resend only on start/stop or drift, the segmentation thread extrapolates meanwhile
+        let expected = self
+            .timecode_sent
+            .map(|(secs, at)| secs + at.elapsed().as_secs_f64());
+        let resend = match (show_secs, expected) {
+            (Some(now), Some(expected)) => (now - expected).abs() > TIMECODE_DRIFT_SECS,
+            (None, None) => false,
+            _ => true,
+        };
+        if resend {
+            self.timecode_sent = show_secs.map(|s| (s, Instant::now()));
+            let _ = self
+                .user_interaction_tx
+                .send(UserInteractionSegMsg::SetTimecode(show_secs));
+        }
+        let Some((timecode, _, show_secs)) = chased else {
+            return;
+        };
+        let due = self.cue_player.timecode_cue(show_secs, timecode.fps);
+        if due.is_some() && due != self.chased_cue {
+            self.chased_cue = due;
+            if let Some(index) = due {
+                self.fire_cue(index);
+            }
+        }
+        ctx.request_repaint_after(Duration::from_millis(40));
+    }
+
+    // --- Link chase: cues with at_beat follow the transport of Live (or any Link peer) ---
+    fn chase_link(&mut self, ctx: &egui::Context) {
+        let Some(link) = self.link.as_mut() else {
+            return;
+        };
+        // The quantizer follows the session's tempo and bar lines; resent on change or drift
+        let (tempo, beat) = link.beat_time();
+        let resend = self.link_sent.map_or(true, |(sent_tempo, sent_beat, at)| {
+            let expected = sent_beat + at.elapsed().as_secs_f64() * sent_tempo / 60.0;
+            sent_tempo != tempo || (beat - expected).abs() > LINK_DRIFT_BEATS
+        });
+        if resend {
+            self.link_sent = Some((tempo, beat, Instant::now()));
+            let link_tempo = Some((tempo as f32, beat));
+            let _ = self
+                .user_interaction_tx
+                .send(UserInteractionSegMsg::SetLinkTempo(link_tempo));
+        }
+        self.link_position = link.song_position();
+        let Some(beats) = self.link_position else {
+            self.chased_beat_cue = None; // The next start chases from the top again
+            return;
+        };
+        let due = self.cue_player.beat_cue(beats);
+        if due.is_some() && due != self.chased_beat_cue {
+            self.chased_beat_cue = due;
+            if let Some(index) = due {
+                self.fire_cue(index);
+            }
+        }
+        ctx.request_repaint_after(Duration::from_millis(20));
+    }
+
+    // --- Decks: A is whatever the regular controls show, B is a stored look ---
+    fn current_look(&self) -> Look {
+        Look {
+            slot_styles: self.slot_styles,
+            slot_params: self.slot_params,
+            slot_outlines: self.slot_outlines,
+            slot_layers: self.slot_layers.clone(),
+            background_layers: self.background_layers.clone(),
+            outline_blend: self.outline_blend,
+            shimmer: self.shimmer,
+            drum_fx: self.drum_fx,
+            bloom: self.bloom,
+            post_chain: self.post_chain.clone(),
+        }
+    }
+
+    fn set_look_a(&mut self, look: Look) {
+        self.slot_styles = look.slot_styles;
+        for (slot, style) in look.slot_styles.into_iter().enumerate() {
+            self.send_interaction(UserInteractionSegMsg::SetSlotStyle { slot, style });
+        }
+        self.slot_params = look.slot_params;
+        for (slot, params) in look.slot_params.into_iter().enumerate() {
+            self.send_interaction(UserInteractionSegMsg::SetSlotParams { slot, params });
+        }
+        self.slot_outlines = look.slot_outlines;
+        for (slot, outline) in look.slot_outlines.into_iter().enumerate() {
+            self.send_interaction(UserInteractionSegMsg::SetSlotOutline { slot, outline });
+        }
+        self.slot_layers = look.slot_layers.clone();
+        for (slot, layers) in look.slot_layers.into_iter().enumerate() {
+            self.send_interaction(UserInteractionSegMsg::SetSlotLayers { slot, layers });
+        }
+        self.background_layers = look.background_layers.clone();
+        self.send_interaction(UserInteractionSegMsg::SetBackgroundLayers(look.background_layers));
+        self.outline_blend = look.outline_blend;
+        self.send_interaction(UserInteractionSegMsg::SetOutlineBlend(look.outline_blend));
+        self.shimmer = look.shimmer;
+        self.send_interaction(UserInteractionSegMsg::SetShimmer(look.shimmer));
+        self.drum_fx = look.drum_fx;
+        self.send_interaction(UserInteractionSegMsg::SetDrumFx(look.drum_fx));
+        self.bloom = look.bloom;
+        self.send_interaction(UserInteractionSegMsg::SetBloom(look.bloom));
+        self.post_chain = look.post_chain.clone();
+        self.send_interaction(UserInteractionSegMsg::SetPostChain(look.post_chain));
+    }
+
+    fn set_deck_b(&mut self, look: Look) {
+        self.deck_b = look.clone();
+        self.send_interaction(UserInteractionSegMsg::SetDeckB(look));
+    }
+
+    fn set_crossfader(&mut self, value: f32) {
+        self.crossfader = value.clamp(0.0, 1.0);
+        self.send_interaction(UserInteractionSegMsg::SetCrossfader(self.crossfader));
+    }
+
+    fn set_master_mix(&mut self, value: f32) {
+        self.master_mix = value.clamp(0.0, 2.0);
+        self.send_interaction(UserInteractionSegMsg::SetMasterMix(self.master_mix));
+    }
+
+    fn draw_crossfader_controls(&mut self, ui: &mut egui::Ui) {
+        let mut mix = self.master_mix;
+        ui.horizontal(|ui| {
+            ui.label(tr("Camera"));
+            ui.add(egui::Slider::new(&mut mix, 0.0..=2.0).show_value(false))
+                .on_hover_text(tr(
+                    "Raw camera, full composite in the middle, effects only on black",
+                ));
+            ui.label(tr("Effects"));
+        });
+        if mix != self.master_mix {
+            self.set_master_mix(mix);
+        }
+        let mut fader = self.crossfader;
+        ui.horizontal(|ui| {
+            ui.label("A");
+            ui.add(egui::Slider::new(&mut fader, 0.0..=1.0).show_value(false));
+            ui.label("B");
+        });
+        if fader != self.crossfader {
+            self.set_crossfader(fader);
+        }
+        ui.horizontal(|ui| {
+            if ui
+                .button(tr("Store A → B"))
+                .on_hover_text(tr("Copy the current look into deck B"))
+                .clicked()
+            {
+                self.set_deck_b(self.current_look());
+            }
+            if ui
+                .button(tr("Compare…"))
+                .on_hover_text(tr("Render two looks side by side on one frame"))
+                .clicked()
+            {
+                self.show_compare = true;
+            }
+            if ui.button(tr("Swap")).clicked() {
+                let a = self.current_look();
+                let b = self.deck_b.clone();
+                self.set_look_a(b);
+                self.set_deck_b(a);
+                self.set_crossfader(1.0 - self.crossfader);
+            }
+        });
+        ui.horizontal(|ui| {
+            let label = if self.learning_cc {
+                tr("Move a control…").to_string()
+            } else {
+                match self.crossfader_cc {
+                    Some((ch, cc)) => format!("MIDI CC {} {} {}", cc, tr("ch"), ch + 1),
+                    None => tr("MIDI learn").to_string(),
+                }
+            };
+            if ui.button(label).clicked() {
+                self.learning_cc = !self.learning_cc;
+            }
+        });
+    }
+
+    // --- Automation / undo: snapshots of every parameter undo can restore ---
+    fn param_snapshot(&self) -> ParamSnapshot {
+        ParamSnapshot {
+            look: self.current_look(),
+            deck_b: self.deck_b.clone(),
+            crossfader: self.crossfader,
+            slot_routing: self.slot_routing,
+            text_overlay: self.text_overlay.clone(),
+        }
+    }
+
+    fn restore_params(&mut self, params: ParamSnapshot) {
+        self.set_look_a(params.look);
+        self.set_deck_b(params.deck_b);
+        self.set_crossfader(params.crossfader);
+        self.slot_routing = params.slot_routing;
+        for (slot, channel) in params.slot_routing.into_iter().enumerate() {
+            self.send_interaction(UserInteractionSegMsg::SetSlotRouting { slot, channel });
+        }
+        self.text_overlay = params.text_overlay.clone();
+        self.send_interaction(UserInteractionSegMsg::SetTextOverlay(params.text_overlay));
+    }
+
+    // Replayed automation goes through the same mirrors as the controls
+    fn apply_param_msg(&mut self, msg: UserInteractionSegMsg) {
+        match &msg {
+            UserInteractionSegMsg::SetSlotRouting { slot, channel } if *slot < MAX_TRACKS => {
+                self.slot_routing[*slot] = *channel;
+            }
+            UserInteractionSegMsg::SetSlotStyle { slot, style } if *slot < MAX_TRACKS => {
+                if self.slot_styles[*slot] != *style {
+                    self.slot_params[*slot] = StyleParams::defaults(*style);
+                }
+                self.slot_styles[*slot] = *style;
+            }
+            UserInteractionSegMsg::SetSlotParams { slot, params } if *slot < MAX_TRACKS => {
+                self.slot_params[*slot] = *params;
+            }
+            UserInteractionSegMsg::SetSlotOutline { slot, outline } if *slot < MAX_TRACKS => {
+                self.slot_outlines[*slot] = *outline;
+            }
+            UserInteractionSegMsg::SetSlotLayers { slot, layers } if *slot < MAX_TRACKS => {
+                self.slot_layers[*slot] = layers.clone();
+            }
+            UserInteractionSegMsg::SetBackgroundLayers(layers) => {
+                self.background_layers = layers.clone();
+            }
+            UserInteractionSegMsg::SetOutlineBlend(blend) => self.outline_blend = *blend,
+            UserInteractionSegMsg::SetShimmer(shimmer) => self.shimmer = *shimmer,
+            UserInteractionSegMsg::SetDrumFx(drum_fx) => self.drum_fx = *drum_fx,
+            UserInteractionSegMsg::SetBloom(bloom) => self.bloom = *bloom,
+            UserInteractionSegMsg::SetPostChain(chain) => self.post_chain = chain.clone(),
+            UserInteractionSegMsg::SetTextOverlay(text) => self.text_overlay = text.clone(),
+            UserInteractionSegMsg::SetDeckB(look) => self.deck_b = look.clone(),
+            UserInteractionSegMsg::SetCrossfader(value) => self.crossfader = *value,
+            UserInteractionSegMsg::SetMasterMix(value) => self.master_mix = *value,
+            _ => {}
+        }
+        self.send_interaction(msg);
+    }
+
+    fn poll_automation(&mut self, ctx: &egui::Context) {
+        let take = std::mem::take(&mut self.automation_take);
+        for msg in self.automation_player.due(&take) {
+            self.apply_param_msg(msg);
+        }
+        self.automation_take = take;
+        if self.automation_player.is_playing() {
+            ctx.request_repaint_after(Duration::from_millis(16));
+        }
+    }
+
+    // Undo history only records what the operator changed through the controls; update()
+    // resyncs it after cues, MIDI, gamepad, remote actions and automation have run
+    fn observe_operator_edits(&mut self, ctx: &egui::Context) -> ParamSnapshot {
+        // Shift first: the plain shortcut would also match Cmd/Ctrl+Shift+Z
+        if !ctx.memory(|m| m.focused().is_some()) {
+            let redo_keys = egui::Modifiers::COMMAND | egui::Modifiers::SHIFT;
+            if ctx.input_mut(|i| i.consume_key(redo_keys, egui::Key::Z)) {
+                self.redo_operator();
+            } else if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z)) {
+                self.undo_operator();
+            }
+        }
+        let settling = ctx.input(|i| i.pointer.any_down());
+        let snapshot = self.param_snapshot();
+        self.undo.observe(&snapshot, settling);
+        snapshot
+    }
+
+    fn slot_selection(&self, slot: usize) -> SlotSelection {
+        SlotSelection {
+            prompt: self.prompts[slot].clone(),
+            locked: self.slot_locks[slot],
+            strokes: self.mask_strokes.iter().filter(|s| s.slot == slot).copied().collect(),
+        }
+    }
+
+    // Called by the controls after a select, release, lock or brush edit
+    fn record_slot_edit(&mut self, slot: usize, before: SlotSelection) {
+        let after = self.slot_selection(slot);
+        if after != before {
+            self.undo.record(UndoStep::Slot {
+                slot,
+                before,
+                after,
+            });
+        }
+    }
+
+    // Sends only the parts of the selection that differ
+    fn restore_slot(&mut self, slot: usize, selection: SlotSelection) {
+        if self.prompts[slot] != selection.prompt {
+            self.prompts[slot] = selection.prompt.clone();
+            self.send_interaction(UserInteractionSegMsg::SelectObject {
+                slot,
+                prompt: selection.prompt,
+            });
+        }
+        if self.slot_locks[slot] != selection.locked {
+            self.slot_locks[slot] = selection.locked;
+            self.send_interaction(UserInteractionSegMsg::SetSlotLock {
+                slot,
+                locked: selection.locked,
+            });
+        }
+        if !self.mask_strokes.iter().filter(|s| s.slot == slot).eq(&selection.strokes) {
+            self.mask_strokes.retain(|s| s.slot != slot);
+            self.send_interaction(UserInteractionSegMsg::ClearMaskEdits { slot });
+            for stroke in selection.strokes {
+                self.mask_strokes.push(stroke);
+                self.send_interaction(UserInteractionSegMsg::PaintMask(stroke));
+            }
+        }
+    }
+
+    fn undo_operator(&mut self) {
+        match self.undo.undo() {
+            Some(UndoStep::Params { before, .. }) => self.restore_params(before),
+            Some(UndoStep::Slot { slot, before, .. }) => self.restore_slot(slot, before),
+            None => {}
+        }
+    }
+
+    fn redo_operator(&mut self) {
+        match self.undo.redo() {
+            Some(UndoStep::Params { after, .. }) => self.restore_params(after),
+            Some(UndoStep::Slot { slot, after, .. }) => self.restore_slot(slot, after),
+            None => {}
+        }
+    }
+
+    fn draw_automation_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if self.automation_recorder.is_recording() {
+                if ui.button(tr("⏹ Stop rec")).clicked() {
+                    self.automation_take = self.automation_recorder.stop();
+                    info!("Automation take: {} moves", self.automation_take.len());
+                }
+            } else if ui
+                .button(tr("⏺ Rec"))
+                .on_hover_text(tr("Record control moves (replaces the current take)"))
+                .clicked()
+            {
+                self.automation_player.stop();
+                self.automation_recorder.start();
+            }
+            if self.automation_player.is_playing() {
+                if ui.button(tr("⏹ Stop")).clicked() {
+                    self.automation_player.stop();
+                }
+            } else if ui
+                .add_enabled(
+                    !self.automation_take.is_empty() && !self.automation_recorder.is_recording(),
+                    egui::Button::new(tr("▶ Play")),
+                )
+                .clicked()
+            {
+                self.automation_player.start();
+            }
+        });
+        let duration_secs = self.automation_take.last().map_or(0.0, |e| e.t_ms as f32 / 1000.0);
+        ui.small(format!(
+            "{}: {} {}, {:.1}s",
+            tr("Take"),
+            self.automation_take.len(),
+            tr("moves"),
+            duration_secs
+        ));
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.undo.is_empty(),
+                    egui::Button::new(format!("{} ({})", tr("Undo"), self.undo.len())),
+                )
+                .on_hover_text("Cmd/Ctrl+Z")
+                .clicked()
+            {
+                self.undo_operator();
+            }
+            if ui
+                .add_enabled(
+                    self.undo.redo_len() > 0,
+                    egui::Button::new(format!("{} ({})", tr("Redo"), self.undo.redo_len())),
+                )
+                .on_hover_text("Cmd/Ctrl+Shift+Z")
+                .clicked()
+            {
+                self.redo_operator();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.preset_path).hint_text("preset.json"));
+            let path = std::path::PathBuf::from(self.preset_path.trim());
+            if ui.button(tr("Save")).clicked() {
+                self.save_preset(&path);
+            }
+            if ui.button(tr("Load")).clicked() {
+                self.load_preset(&path);
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button(tr("Export preset…")).clicked() {
+                let picked = preset_dialog().set_file_name("preset.json").save_file();
+                self.open_file_dialog(FileRequest::ExportPreset, picked);
+            }
+            if ui.button(tr("Import preset…")).clicked() {
+                self.open_file_dialog(FileRequest::ImportPreset, preset_dialog().pick_file());
+            }
+        });
+        if let Some(e) = &self.preset_error {
+            ui.colored_label(Color32::RED, e);
+        }
+    }
+
+    fn draw_cue_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.cue_path).hint_text("cues.json"));
+            if ui.button(tr("Load")).clicked() {
+                match CueList::load(std::path::Path::new(self.cue_path.trim())) {
+                    Ok(list) => {
+                        self.cue_player = CuePlayer::new(list);
+                        self.cue_error = None;
+                        self.publish_control_actions();
+                    }
+                    Err(e) => {
+                        error!("{:#}", e);
+                        self.cue_error = Some(format!("{:#}", e));
+                    }
+                }
+            }
+        });
+        if let Some(e) = &self.cue_error {
+            ui.colored_label(Color32::RED, e);
+        }
+        ui.horizontal(|ui| {
+            match self.cue_player.show_elapsed_secs() {
+                Some(elapsed) => {
+                    if ui.button(tr("Stop show")).clicked() {
+                        self.cue_player.stop_show();
+                    }
+                    ui.label(format!("{:.0}s", elapsed));
+                }
+                None => {
+                    if ui
+                        .button(tr("Start show"))
+                        .on_hover_text(tr("Starts the clock for timed cues"))
+                        .clicked()
+                    {
+                        self.cue_player.start_show();
+                    }
+                }
+            }
+            let next = self.cue_player.next_index();
+            if ui
+                .add_enabled(next.is_some(), egui::Button::new(tr("GO ▶")))
+                .on_hover_text(tr("Fire the next cue (space)"))
+                .clicked()
+            {
+                if let Some(index) = next {
+                    self.fire_cue(index);
+                }
+            }
+        });
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .id_salt("cue_list")
+            .max_height(120.0)
+            .show(ui, |ui| {
+                for (i, cue) in self.cue_player.list.cues.iter().enumerate() {
+                    let current = self.cue_player.current == Some(i);
+                    if ui.selectable_label(current, cue.label()).clicked() {
+                        clicked = Some(i);
+                    }
+                }
+            });
+        if let Some(index) = clicked {
+            self.fire_cue(index);
+        }
+        if let Some(inputs) = &self.midi_inputs {
+            ui.small(format!("{}: {}", tr("MIDI inputs"), inputs.port_names.len()))
+                .on_hover_text(inputs.port_names.join("\n"));
+        }
+    }
+
+    fn draw_hud_controls(&mut self, ui: &mut egui::Ui) {
+        let mut hud = self.hud.clone();
+        ui.checkbox(&mut hud.show_clock, tr("Clock"));
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut hud.show_set_timer, tr("Set timer"));
+            let label = if hud.set_started_ms.is_some() { "Restart set" } else { "Start set" };
+            if ui.button(label).clicked() {
+                hud.set_started_ms = Some(chrono::Local::now().timestamp_millis());
             }
-        };
-        let (cam_to_seg_tx, cam_to_seg_rx) = unbounded();
-        let (seg_to_ui_tx, seg_to_ui_rx) = bounded(1);
-        let (_user_interaction_tx, user_interaction_rx) = unbounded(); // Keep channel
-        let (raw_samples_tx, raw_samples_rx) = bounded(10);
-        let (intensities_tx, intensities_rx) = bounded(5);
-        let cam_stop_signal = Arc::new(AtomicBool::new(false));
-        let seg_stop_signal = Arc::new(AtomicBool::new(false));
-        let audio_capture_stop_signal = Arc::new(AtomicBool::new(false));
-        let audio_processor_stop_signal = Arc::new(AtomicBool::new(false));
-        let cam_stop_clone = cam_stop_signal.clone();
-        let cam_ctx = cc.egui_ctx.clone();
-        let seg_stop_clone = seg_stop_signal.clone();
-        let seg_ctx = cc.egui_ctx.clone();
-        let audio_cap_stop = audio_capture_stop_signal.clone();
-        let audio_proc_stop = audio_processor_stop_signal.clone();
-        let cam_thread = Some(camera::start_camera_thread(
-            camera_index,
-            cam_to_seg_tx,
-            cam_stop_clone,
-            cam_ctx,
-        ));
-        let initial_audio_status;
-        let audio_capture_thread =
-            match live_audio::start_audio_capture(raw_samples_tx, audio_cap_stop) {
-                Ok((s, r, c)) => {
-                    initial_audio_status = LiveAudioStatus::Running(r, c);
-                    Some(s)
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut hud.show_countdown, tr("Countdown"));
+            ui.add(
+                egui::TextEdit::singleline(&mut hud.countdown_label)
+                    .hint_text("DOORS")
+                    .desired_width(60.0),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.countdown_input)
+                    .hint_text("HH:MM")
+                    .desired_width(60.0),
+            );
+            if ui.button(tr("Set target")).clicked() {
+                match HudSettings::parse_countdown_target(&self.countdown_input) {
+                    Some(target) => hud.countdown_target_ms = Some(target),
+                    None => warn!("Invalid countdown time '{}', expected HH:MM", self.countdown_input),
                 }
-                Err(e) => {
-                    let m = format!("Audio capture failed: {}", e);
-                    error!("{}", m);
-                    initial_audio_status = LiveAudioStatus::Error(m);
-                    None
+            }
+        });
+        ui.checkbox(&mut hud.burn_in, tr("Burn into output"))
+            .on_hover_text(tr("Off = only shown in this window"));
+        ui.checkbox(&mut hud.burn_stats, tr("Burn in performance stats"))
+            .on_hover_text(tr("FPS, inference time, audio levels and track states"));
+        if hud != self.hud {
+            self.hud = hud.clone();
+            self.send_interaction(UserInteractionSegMsg::SetHud(hud));
+        }
+    }
+
+    fn upload_frame(
+        &mut self,
+        ctx: &egui::Context,
+        f: Arc<egui::ColorImage>,
+        damage: &FrameDamage,
+    ) {
+        let s = f.size;
+        if self.camera_resolution.map_or(true, |r| {
+            r.width() != s[0] as u32 || r.height() != s[1] as u32
+        }) {
+            self.camera_resolution = Some(Resolution::new(s[0] as u32, s[1] as u32));
+            self.texture_size = Some(Vec2::new(s[0] as f32, s[1] as f32));
+        }
+        // Damage is relative to the last composited frame; a passthrough upload voids it
+        let regions = match damage {
+            FrameDamage::Regions(rects) if !self.texture_stale => Some(rects),
+            _ => None,
+        };
+        self.texture_stale = false;
+        match self.texture {
+            Some(ref mut t) if t.size() == s && regions.is_some() => {
+                for rect in regions.into_iter().flatten() {
+                    let part = ImageData::Color(Arc::new(crop_color_image(&f, rect)));
+                    t.set_partial(rect.pos, part, TextureOptions::LINEAR);
                 }
+            }
+            Some(ref mut t) => t.set(ImageData::Color(f), TextureOptions::LINEAR),
+            None => {
+                self.texture = Some(ctx.load_texture(
+                    "vis",
+                    ImageData::Color(f),
+                    TextureOptions::LINEAR,
+                ))
+            }
+        }
+    }
+
+    // --- Watchdog: segmentation silent past the deadline while the camera keeps delivering ---
+    fn run_watchdog(&mut self, ctx: &egui::Context) {
+        if !self.watchdog.enabled {
+            return;
+        }
+        // The splash covers the initial model load; that isn't lag
+        let Some(last_seg_frame_time) = self.last_seg_frame_time else {
+            return;
+        };
+        let deadline = Duration::from_millis(self.watchdog.deadline_ms);
+        if last_seg_frame_time.elapsed() < deadline {
+            return;
+        }
+        let Some((captured, frame)) = self.latest_raw_frame.get() else {
+            return;
+        };
+        // A stalled camera isn't a model problem; keep showing the last composited frame
+        if captured <= last_seg_frame_time {
+            return;
+        }
+        ctx.request_repaint_after(Duration::from_millis(33));
+        if self.last_passthrough_capture == Some(captured) {
+            return;
+        }
+        if !self.model_lagging {
+            warn!("No composited frame for {:?}; passing raw camera frames through.", deadline);
+        }
+        self.model_lagging = true;
+        self.last_passthrough_capture = Some(captured);
+        let (w, h) = frame.dimensions();
+        let size = [w as usize, h as usize];
+        let image = if self.display_blackout.level() > 0.0 {
+            let mut dimmed = (*frame).clone();
+            self.display_blackout.apply(&mut dimmed);
+            egui::ColorImage::from_rgb(size, dimmed.as_raw())
+        } else {
+            egui::ColorImage::from_rgb(size, frame.as_raw())
+        };
+        self.upload_frame(ctx, Arc::new(image), &FrameDamage::Full);
+        self.texture_stale = true;
+        self.paint_frame = None;
+    }
+
+    // Paint-layer mode: object effects drawn over the raw frame, through the corner pin
+    fn draw_paint_layer(&mut self, ctx: &egui::Context, ui: &egui::Ui, image_rect: Rect) {
+        let Some(layer) = &self.paint_frame else {
+            return;
+        };
+        let painter = ui.painter_at(image_rect);
+        let pin = &self.corner_pin;
+        let fade = 1.0 - self.display_blackout.level();
+        self.paint_renderer.draw(ctx, &painter, layer, fade, |point| {
+            let (x, y) = pin.to_output(point);
+            image_rect.lerp_inside(Vec2::new(x, y))
+        });
+    }
+
+    // Operator-only HUD, painted over the video instead of into it
+    fn draw_operator_hud(&self, ui: &egui::Ui, image_rect: Rect) {
+        if self.hud.burn_in || !self.hud.any_visible() {
+            return;
+        }
+        let painter = ui.painter_at(image_rect);
+        painter.text(
+            image_rect.left_top() + Vec2::new(8.0, 8.0),
+            egui::Align2::LEFT_TOP,
+            self.hud.lines().join("\n"),
+            egui::FontId::monospace(18.0),
+            Color32::WHITE,
+        );
+        ui.ctx().request_repaint_after(Duration::from_millis(250));
+    }
+
+    fn ensemble_request(&self) -> Option<EnsembleConfig> {
+        self.ensemble.then(|| self.ensemble_config.clone())
+    }
+
+    fn send_interaction(&mut self, msg: UserInteractionSegMsg) {
+        self.automation_recorder.record(&msg);
+        if let Err(e) = self.user_interaction_tx.send(msg) {
+            warn!("Failed to send interaction: {}", e);
+        }
+    }
+
+    // --- Mask brush: click/drag on the video lays dabs along the pointer path ---
+    fn handle_brush(&mut self, ui: &egui::Ui, response: &egui::Response, mode: BrushMode) {
+        let image_rect = response.rect;
+        let screen_radius = self.brush_radius * image_rect.width();
+        let painter = ui.painter_at(image_rect);
+        if let Some(hover) = response.hover_pos() {
+            let color = match mode {
+                BrushMode::Add => Color32::LIGHT_GREEN,
+                BrushMode::Remove => Color32::LIGHT_RED,
             };
-        let audio_processor_thread = match initial_audio_status {
-            LiveAudioStatus::Running(r, c) => {
-                let mut p =
-                    music::AudioProcessor::new(raw_samples_rx, intensities_tx, r, c, MAX_TRACKS);
-                Some(std::thread::spawn(move || p.run(audio_proc_stop)))
+            painter.circle_stroke(hover, screen_radius, Stroke::new(1.5, color));
+        }
+        let pointer = response.interact_pointer_pos();
+        let painting = response.is_pointer_button_down_on() || response.clicked();
+        match pointer.filter(|_| painting) {
+            Some(pos) if image_rect.contains(pos) => {
+                // Space dabs by half a radius so strokes stay continuous without flooding the channel
+                let far_enough = self
+                    .last_brush_pos
+                    .map_or(true, |last| last.distance(pos) >= screen_radius * 0.5);
+                let slot = self.brush_slot;
+                let dabs = self.mask_strokes.iter().filter(|s| s.slot == slot).count();
+                if far_enough && dabs < MAX_DABS {
+                    if self.brush_edit.is_none() {
+                        self.brush_edit = Some((slot, self.slot_selection(slot)));
+                    }
+                    let (x, y) = self.corner_pin.to_source((
+                        (pos.x - image_rect.left()) / image_rect.width().max(1.0),
+                        (pos.y - image_rect.top()) / image_rect.height().max(1.0),
+                    ));
+                    let stroke = BrushStroke {
+                        slot,
+                        x,
+                        y,
+                        radius: self.brush_radius,
+                        mode,
+                    };
+                    self.mask_strokes.push(stroke);
+                    self.send_interaction(UserInteractionSegMsg::PaintMask(stroke));
+                    self.last_brush_pos = Some(pos);
+                }
             }
             _ => {
-                warn!("No audio proc started.");
-                None
+                self.last_brush_pos = None;
+                // One undo step per stroke, from pointer down to release
+                if let Some((slot, before)) = self.brush_edit.take() {
+                    self.record_slot_edit(slot, before);
+                }
+            }
+        }
+    }
+
+    // --- Corner pin calibration: drag the four handles onto the projection surface's corners ---
+    fn handle_corner_pin(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let image_rect = response.rect;
+        let to_screen = |(x, y): (f32, f32)| image_rect.lerp_inside(Vec2::new(x, y));
+        if response.drag_started() {
+            self.pin_drag = response.interact_pointer_pos().and_then(|p| {
+                self.corner_pin
+                    .corners
+                    .iter()
+                    .map(|c| to_screen(*c).distance(p))
+                    .enumerate()
+                    .filter(|(_, d)| *d <= PIN_HANDLE_RADIUS * 2.0)
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(i, _)| i)
+            });
+        }
+        if let (Some(i), Some(p)) = (self.pin_drag, response.interact_pointer_pos()) {
+            let corner = (
+                ((p.x - image_rect.left()) / image_rect.width().max(1.0)).clamp(0.0, 1.0),
+                ((p.y - image_rect.top()) / image_rect.height().max(1.0)).clamp(0.0, 1.0),
+            );
+            if self.corner_pin.corners[i] != corner {
+                self.corner_pin.corners[i] = corner;
+                self.send_interaction(UserInteractionSegMsg::SetCornerPin(self.corner_pin));
             }
+        }
+        if response.drag_stopped() {
+            self.pin_drag = None;
+        }
+
+        let painter = ui.painter_at(image_rect);
+        let corners = self.corner_pin.corners.map(to_screen);
+        let outline = Stroke::new(1.5, Color32::LIGHT_BLUE);
+        for i in 0..corners.len() {
+            painter.line_segment([corners[i], corners[(i + 1) % corners.len()]], outline);
+        }
+        for (i, corner) in corners.iter().enumerate() {
+            let fill = if self.pin_drag == Some(i) {
+                Color32::YELLOW
+            } else {
+                Color32::WHITE
+            };
+            painter.circle_filled(*corner, PIN_HANDLE_RADIUS, fill);
+            painter.circle_stroke(*corner, PIN_HANDLE_RADIUS, Stroke::new(1.0, Color32::BLACK));
+        }
+    }
+
+    // --- ROI: drag a rectangle on the video; drawn as an outline over the image ---
+    fn handle_roi_drag(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let image_rect = response.rect;
+        let to_norm = |p: Pos2| {
+            (
+                (p.x - image_rect.left()) / image_rect.width().max(1.0),
+                (p.y - image_rect.top()) / image_rect.height().max(1.0),
+            )
+        };
+        let to_screen = |r: &NormalizedRect| {
+            Rect::from_min_max(
+                image_rect.lerp_inside(Vec2::new(r.x0, r.y0)),
+                image_rect.lerp_inside(Vec2::new(r.x1, r.y1)),
+            )
         };
-        let seg_thread = Some(segmentation::start_segmentation_thread(
-            seg_to_ui_tx,
-            cam_to_seg_rx,
-            user_interaction_rx,
-            intensities_rx,
-            seg_stop_clone,
-            seg_ctx,
-            model_options,
-        )); // Pass user_interaction_rx
 
-        Self {
-            texture: None,
-            seg_to_ui_rx,
-            _user_interaction_tx, // Store sender
-            cam_thread_handle: cam_thread,
-            cam_stop_signal,
-            seg_thread_handle: seg_thread,
-            seg_stop_signal,
-            audio_capture_thread_handle: audio_capture_thread,
-            audio_capture_stop_signal,
-            audio_processor_thread_handle: audio_processor_thread,
-            audio_processor_stop_signal,
-            camera_error: None,
-            seg_error: None,
-            live_audio_status: initial_audio_status,
-            camera_resolution: None,
-            texture_size: None,
-            last_fps_update_time: Instant::now(),
-            frames_since_last_update: 0,
-            last_calculated_fps: 0.0,
+        if response.drag_started() {
+            self.roi_drag_start = response.interact_pointer_pos();
+        }
+        let dragging = match (self.roi_drag_start, response.interact_pointer_pos()) {
+            (Some(start), Some(current)) => {
+                Some(NormalizedRect::from_corners(to_norm(start), to_norm(current)))
+            }
+            _ => None,
+        };
+        if response.drag_stopped() {
+            if let Some(r) = dragging {
+                if r.x1 - r.x0 > 0.02 && r.y1 - r.y0 > 0.02 {
+                    self.roi = Some(r);
+                    self.send_interaction(UserInteractionSegMsg::SetRoi(Some(r)));
+                }
+            }
+            self.roi_drag_start = None;
+        }
+
+        let painter = ui.painter_at(image_rect);
+        if let Some(r) = dragging.filter(|_| self.roi_drag_start.is_some()) {
+            painter.rect_stroke(
+                to_screen(&r),
+                0.0,
+                Stroke::new(1.5, Color32::LIGHT_YELLOW),
+                StrokeKind::Inside,
+            );
+        } else if let Some(r) = &self.roi {
+            painter.rect_stroke(
+                to_screen(r),
+                0.0,
+                Stroke::new(2.0, Color32::YELLOW),
+                StrokeKind::Inside,
+            );
+        }
+    }
+
+    fn refresh_permissions(&mut self) {
+        if self.last_permission_check.elapsed() < PERMISSION_POLL_INTERVAL {
+            return;
+        }
+        self.last_permission_check = Instant::now();
+        for (kind, status) in self.permission_status.iter_mut() {
+            let current = permissions::status(*kind);
+            if current != *status {
+                info!("{} permission changed: {:?} -> {:?}", kind.label(), status, current);
+                *status = current;
+            }
+        }
+    }
+
+    fn draw_permissions(&self, ui: &mut egui::Ui) {
+        if self
+            .permission_status
+            .iter()
+            .all(|(_, s)| *s == PermissionStatus::Authorized)
+        {
+            return;
+        }
+        ui.heading(tr("Permissions"))
+            .on_hover_text(tr("Camera and microphone access granted by the OS"));
+        ui.separator();
+        for (kind, status) in &self.permission_status {
+            match status {
+                PermissionStatus::Authorized => {
+                    ui.label(format!("{}: {}", tr(kind.label()), tr("allowed")));
+                }
+                PermissionStatus::NotDetermined => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("{}: {}", tr(kind.label()), tr("waiting for approval")));
+                    });
+                }
+                PermissionStatus::Denied | PermissionStatus::Restricted => {
+                    let denied = format!("{}: {}", tr(kind.label()), tr("access denied"));
+                    ui.colored_label(Color32::RED, denied);
+                    ui.small(tr("Enable it for this app, then restart."));
+                    if ui.button(tr("Open System Settings")).clicked() {
+                        permissions::open_settings(*kind);
+                    }
+                }
+            }
         }
+        ui.separator();
     }
 
     fn update_fps_counter(&mut self) {
@@ -162,141 +4490,629 @@ impl WebcamAppUI {
                 self.frames_since_last_update as f32 / e.as_secs_f32().max(0.001);
             self.frames_since_last_update = 0;
             self.last_fps_update_time = n;
+            metrics::global().set(metrics::UI_FPS, self.last_calculated_fps as f64);
         }
     }
 }
 
+// --- Native file dialogs ---
+// Shown without blocking the UI: the dialog runs on the shared runtime and a later frame picks
+// up the answer and acts on it
+#[derive(Debug, Clone)]
+enum FileRequest {
+    OpenProject,
+    SaveProjectAs,
+    ExportPreset,
+    ImportPreset,
+    Lut,
+    Model,
+    MediaFile,
+    SaveAudioAs(std::path::PathBuf), // The finished recording to move
+}
+
+// Audio processor for a capture's sample rate and channel count
+type ProcessorSpawner = Box<dyn Fn(u32, u16) -> SupervisedThread>;
+
+struct PendingDialog {
+    request: FileRequest,
+    answer: Receiver<Option<std::path::PathBuf>>,
+}
+
+fn project_dialog() -> rfd::AsyncFileDialog {
+    rfd::AsyncFileDialog::new()
+        .set_title("Show project")
+        .add_filter("Show project", &[project::PROJECT_EXTENSION])
+}
+
+fn preset_dialog() -> rfd::AsyncFileDialog {
+    rfd::AsyncFileDialog::new()
+        .set_title("Preset")
+        .add_filter("Preset", &["json"])
+}
+
 impl eframe::App for WebcamAppUI {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.update_fps_counter();
+        self.supervise_threads();
+        let dt = ctx.input(|i| i.unstable_dt);
+        self.display_blackout.step(self.blackout, self.blackout_fade_secs, dt);
+        self.poll_file_dialog(ctx);
+        self.refresh_permissions();
         let mut received_frame_this_update = false;
+        if let Some((seq, frame)) = self.seg_frames.take() {
+            received_frame_this_update = true;
+            self.last_seg_frame_time = Some(Instant::now());
+            self.model_lagging = false;
+            let damage = if seq == self.last_seg_frame_seq + 1 {
+                frame.damage
+            } else {
+                FrameDamage::Full
+            };
+            self.last_seg_frame_seq = seq;
+            self.paint_frame = frame.paint;
+            self.band_meters = frame.meters;
+            if let Some(preview) = self.preview.as_ref().filter(|_| !self.standby) {
+                preview.publish(frame.image.clone());
+            }
+            self.feed_stream(&frame.image);
+            self.upload_frame(ctx, frame.image, &damage);
+            self.seg_error = None;
+        }
         loop {
             match self.seg_to_ui_rx.try_recv() {
                 Ok(msg) => match msg {
-                    SegmentationThreadMsg::Frame(f) => {
-                        received_frame_this_update = true;
-                        let s = f.size;
-                        let sz = Vec2::new(s[0] as f32, s[1] as f32);
-                        if self.camera_resolution.map_or(true, |r| {
-                            r.width() != s[0] as u32 || r.height() != s[1] as u32
-                        }) {
-                            self.camera_resolution =
-                                Some(Resolution::new(s[0] as u32, s[1] as u32));
-                            self.texture_size = Some(sz);
-                        }
-                        match self.texture {
-                            Some(ref mut t) => t.set(ImageData::Color(f), TextureOptions::LINEAR),
-                            None => {
-                                self.texture = Some(ctx.load_texture(
-                                    "vis",
-                                    ImageData::Color(f),
-                                    TextureOptions::LINEAR,
-                                ))
-                            }
-                        }
-                        self.seg_error = None;
-                    }
                     SegmentationThreadMsg::Error(e) => {
                         if self.seg_error.as_ref() != Some(&e) {
                             self.seg_error = Some(e);
                         }
                     }
+                    SegmentationThreadMsg::SessionRecording(dir) => {
+                        self.session_recording = dir;
+                    }
+                    SegmentationThreadMsg::KeyedRecording(path) => {
+                        self.keyed_recording = path;
+                    }
+                    SegmentationThreadMsg::ModelStatus(status) => {
+                        self.model_status = status;
+                    }
+                    SegmentationThreadMsg::ModelLoaded(file, result) => {
+                        match result {
+                            Ok(()) => self.model_file = Some(file),
+                            // A failed model is never persisted; back to what the thread runs
+                            Err(e) => {
+                                if self.model_file.as_ref() == Some(&file) {
+                                    self.model_file = self.launch_model_file.clone();
+                                }
+                                self.seg_error = Some(e);
+                            }
+                        }
+                    }
+                    SegmentationThreadMsg::Comparison(a, b) => {
+                        self.upload_comparison(ctx, a, b);
+                    }
+                    SegmentationThreadMsg::Plugins(plugins) => {
+                        self.set_plugins(plugins);
+                    }
                 },
                 Err(TryRecvError::Empty) => {
                     break;
                 }
-                Err(TryRecvError::Disconnected) => {
+                Err(TryRecvError::Closed) => {
+                    // Supervisor holds a sender clone, so this only happens after shutdown
                     let m = "Seg disconnected.".to_string();
                     if self.seg_error.is_none() {
                         self.seg_error = Some(m);
                     }
-                    if let Some(h) = self.seg_thread_handle.take() {
-                        let _ = h.join();
-                    }
                     break;
                 }
             }
         }
 
+        if !received_frame_this_update {
+            self.run_watchdog(ctx);
+        }
+        self.handle_track_nudge(ctx);
+        self.poll_schedule(ctx);
+        self.status_board.publish(AppStatus {
+            camera_error: self.camera_error.clone(),
+            model_error: self.seg_error.clone(),
+            standby: self.standby,
+        });
+        // Last frame's control edits are committed before anything else moves the parameters
+        let observed = self.observe_operator_edits(ctx);
+        self.poll_cues(ctx);
+        self.poll_control();
+        self.poll_gamepad(ctx);
+        self.handle_dropped_files(ctx);
+        self.poll_file_source();
+        self.poll_automation(ctx);
+        // Only when something moved, so a drag in progress still becomes its own step
+        let current = self.param_snapshot();
+        if current != observed {
+            self.undo.resync(&current);
+        }
+
         // --- Simplified UI ---
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 if !cfg!(target_arch = "wasm32") {
-                    ui.menu_button("File", |ui| {
-                        if ui.button("Quit").clicked() {
+                    ui.menu_button(tr("File"), |ui| {
+                        if ui.button(tr("Run self-test")).clicked() {
+                            self.start_self_test(ctx);
+                            ui.close_menu();
+                        }
+                        if ui.button(tr("Choose profile…")).clicked() {
+                            self.show_profile_picker = true;
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if ui.button(tr("Open project…")).clicked() {
+                            ui.close_menu();
+                            let picked = project_dialog().pick_file();
+                            self.open_file_dialog(FileRequest::OpenProject, picked);
+                        }
+                        if ui.button(tr("Save project")).clicked() {
+                            ui.close_menu();
+                            let path = project::with_extension(&self.project_path);
+                            if path.as_os_str().is_empty() {
+                                self.save_project_as();
+                            } else {
+                                self.save_project(&path);
+                            }
+                        }
+                        if ui.button(tr("Save project as…")).clicked() {
+                            ui.close_menu();
+                            self.save_project_as();
+                        }
+                        ui.add_enabled_ui(!self.recent_projects.is_empty(), |ui| {
+                            ui.menu_button(tr("Recent projects"), |ui| {
+                                let mut chosen = None;
+                                for path in &self.recent_projects {
+                                    if ui.button(path).clicked() {
+                                        chosen = Some(std::path::PathBuf::from(path));
+                                    }
+                                }
+                                if let Some(path) = chosen {
+                                    self.open_project(&path);
+                                    ui.close_menu();
+                                }
+                            });
+                        });
+                        ui.separator();
+                        if ui
+                            .button(tr("Choose model…"))
+                            .on_hover_text(tr(
+                                "FastSAM ONNX export to use instead of the bundled one",
+                            ))
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            let picked = rfd::AsyncFileDialog::new()
+                                .set_title("Segmentation model")
+                                .add_filter("ONNX model", &["onnx"])
+                                .set_directory("models")
+                                .pick_file();
+                            self.open_file_dialog(FileRequest::Model, picked);
+                        }
+                        if ui
+                            .button(tr("Play media file…"))
+                            .on_hover_text(tr("Video replaces the camera, audio the live input"))
+                            .clicked()
+                        {
+                            ui.close_menu();
+                            let picked = rfd::AsyncFileDialog::new()
+                                .set_title("Media file")
+                                .add_filter("Video", file_source::VIDEO_EXTENSIONS)
+                                .add_filter("Audio", file_source::AUDIO_EXTENSIONS)
+                                .pick_file();
+                            self.open_file_dialog(FileRequest::MediaFile, picked);
+                        }
+                        if ui.button(tr("Quit")).clicked() {
                             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                         }
                     });
                     ui.add_space(16.0);
                 }
                 widgets::global_theme_preference_buttons(ui);
+                if let Some(source) = &self.file_source {
+                    ui.add_space(16.0);
+                    let name = source.path().file_name().unwrap_or_default().to_string_lossy();
+                    ui.label(format!("▶ {}", name));
+                    let back = ui.small_button("⏹").on_hover_text(tr("Back to the live inputs"));
+                    if back.clicked() {
+                        self.stop_media_file();
+                    }
+                }
+                if let Some((feedback, _)) = &self.file_feedback {
+                    ui.add_space(16.0);
+                    match feedback {
+                        Ok(m) => ui.label(m),
+                        Err(e) => ui.colored_label(Color32::RED, e),
+                    };
+                }
+                ui.add_space(16.0);
+                let mut language = i18n::language();
+                egui::ComboBox::from_id_salt("language")
+                    .selected_text(format!("🌐 {}", language.label()))
+                    .show_ui(ui, |ui| {
+                        for option in Language::ALL {
+                            ui.selectable_value(&mut language, option, option.label());
+                        }
+                    });
+                if language != i18n::language() {
+                    info!("UI language: {}", language.label());
+                    i18n::set_language(language);
+                }
+                // Touch mode and profiles that start without the panel need a way back to it
+                let panel_hidden = self.profile.is_some_and(|p| !p.settings().show_settings);
+                if self.touch_mode || panel_hidden {
+                    ui.add_space(16.0);
+                    ui.toggle_value(&mut self.show_settings, tr("⚙ Settings"));
+                }
             });
         });
+        if self.touch_mode {
+            egui::TopBottomPanel::bottom("touch_bar").show(ctx, |ui| self.draw_touch_bar(ui));
+        }
 
         egui::SidePanel::left("control_panel")
             .resizable(false)
             .default_width(180.0)
-            .show(ctx, |ui| {
-                ui.heading("Audio Status")
-                    .on_hover_text("Status of live audio capture");
+            .show_animated(ctx, self.show_settings, |ui| {
+                self.draw_permissions(ui);
+                ui.heading(tr("Audio Status"))
+                    .on_hover_text(tr("Status of live audio capture"));
                 ui.separator();
                 match &self.live_audio_status {
                     /* Display status */
                     LiveAudioStatus::Initializing => {
                         ui.horizontal(|ui| {
                             ui.spinner();
-                            ui.label("Initializing...");
+                            ui.label(tr("Initializing..."));
                         });
                     }
                     LiveAudioStatus::Running(r, c) => {
-                        ui.label(format!("Capturing: {} Hz, {} ch", r, c));
+                        ui.label(format!("{}: {} Hz, {} {}", tr("Capturing"), r, c, tr("ch")));
                     }
                     LiveAudioStatus::Error(e) => {
-                        ui.colored_label(Color32::RED, "Error").on_hover_text(e);
+                        ui.colored_label(Color32::RED, tr("Error")).on_hover_text(e);
                     }
                     LiveAudioStatus::Disabled => {
-                        ui.colored_label(Color32::GRAY, "Disabled/Stopped");
+                        ui.colored_label(Color32::GRAY, tr("Disabled/Stopped"));
+                    }
+                }
+                self.draw_band_meters(ui);
+                self.draw_loopback_wizard(ui);
+                self.draw_analysis_controls(ui);
+                self.draw_latency_controls(ui);
+                self.draw_intensity_gain_controls(ui);
+                self.draw_noise_gate_controls(ui);
+                self.draw_response_curve_editor(ui);
+                self.draw_monitor_controls(ui);
+                self.draw_audio_recorder(ui);
+                // --- Per-source mixing controls ---
+                for source in &self.audio_sources {
+                    ui.label(&source.name).on_hover_text(tr("Audio source mixed before analysis"));
+                    ui.horizontal(|ui| {
+                        let mut muted = source.is_muted();
+                        if ui.toggle_value(&mut muted, tr("Mute")).changed() {
+                            source.set_muted(muted);
+                        }
+                        let mut gain = source.gain();
+                        if ui
+                            .add(egui::Slider::new(&mut gain, 0.0..=2.0).text(tr("Gain")))
+                            .changed()
+                        {
+                            source.set_gain(gain);
+                        }
+                    });
+                }
+                ui.separator();
+                ui.heading(tr("Routing"))
+                    .on_hover_text(tr("Which analysis channel drives each track slot"));
+                self.draw_routing_matrix(ui);
+                ui.separator();
+                ui.heading(tr("Styles"))
+                    .on_hover_text(tr("Visual style and outline stroke drawn on each track slot"));
+                self.draw_style_pickers(ui);
+                ui.separator();
+                if !self.plugins.is_empty() {
+                    ui.heading(tr("Plugins"))
+                        .on_hover_text(tr(
+                            "Visuals loaded from the plugins/ directory, drawn over the mix",
+                        ));
+                    self.draw_plugin_controls(ui);
+                    ui.separator();
+                }
+                ui.heading(tr("Post"))
+                    .on_hover_text(tr("Full-frame effects applied after the object visuals"));
+                self.draw_post_controls(ui);
+                ui.separator();
+                ui.heading(tr("Grade"))
+                    .on_hover_text(tr(
+                        "Tone mapping and a 3D LUT (.cube) applied to the final mix",
+                    ));
+                self.draw_lut_controls(ui);
+                ui.separator();
+                ui.heading(tr("Text Overlay"));
+                self.draw_text_overlay_controls(ui);
+                ui.separator();
+                ui.heading(tr("Sprite"))
+                    .on_hover_text(tr("PNG sequence or sprite sheet drawn on each tracked object"));
+                self.draw_sprite_controls(ui);
+                ui.separator();
+                ui.heading(tr("HUD")).on_hover_text(tr("Clock and set timers for live shows"));
+                self.draw_hud_controls(ui);
+                ui.separator();
+                ui.heading(tr("Region of Interest"))
+                    .on_hover_text(tr("Drag on the video to limit inference to a region"));
+                match &self.roi {
+                    Some(r) => {
+                        ui.label(format!(
+                            "ROI: {:.0}%,{:.0}% – {:.0}%,{:.0}%",
+                            r.x0 * 100.0,
+                            r.y0 * 100.0,
+                            r.x1 * 100.0,
+                            r.y1 * 100.0
+                        ));
+                        if ui.button(tr("Clear ROI")).clicked() {
+                            self.roi = None;
+                            self.send_interaction(UserInteractionSegMsg::SetRoi(None));
+                        }
+                    }
+                    None => {
+                        ui.label(tr("Full frame"));
+                    }
+                }
+                ui.separator();
+                ui.heading(tr("Projection"))
+                    .on_hover_text(tr("Map the output onto physical surfaces and objects"));
+                self.draw_corner_pin_controls(ui);
+                ui.separator();
+                ui.heading(tr("Crossfader"))
+                    .on_hover_text(tr("Blend the live look (A) with a stored look (B)"));
+                self.draw_crossfader_controls(ui);
+                ui.separator();
+                ui.heading(tr("Automation"))
+                    .on_hover_text(tr("Record and replay control moves; presets store both"));
+                self.draw_automation_controls(ui);
+                ui.separator();
+                ui.heading(tr("Cues"))
+                    .on_hover_text(tr(
+                        "Numbered looks fired by GO, hotkeys, MIDI program changes or show time",
+                    ));
+                self.draw_cue_controls(ui);
+                ui.separator();
+                ui.heading(tr("Select"))
+                    .on_hover_text(tr(
+                        "Shift-click the video to include a point, alt-click to exclude one",
+                    ));
+                self.draw_prompt_controls(ui);
+                ui.separator();
+                ui.heading(tr("Mask Brush"))
+                    .on_hover_text(tr(
+                        "Paint onto or erase from a slot's mask; replaces ROI dragging while on",
+                    ));
+                self.draw_mask_brush_controls(ui);
+                ui.separator();
+                ui.heading(tr("Session"))
+                    .on_hover_text(tr("Record frames, intensities and interactions for replay"));
+                if let Some(dir) = &self.replay_dir {
+                    ui.label(format!("{}: {}", tr("Replaying"), dir.display()));
+                }
+                let recording = self.session_recording.is_some();
+                let label = if recording {
+                    tr("Stop recording")
+                } else {
+                    tr("Record session")
+                };
+                if ui.button(label).clicked() {
+                    if let Err(e) = self
+                        .user_interaction_tx
+                        .send(UserInteractionSegMsg::SetSessionRecording(!recording))
+                    {
+                        warn!("Failed to toggle session recording: {}", e);
                     }
                 }
+                if let Some(dir) = &self.session_recording {
+                    ui.colored_label(Color32::RED, "● REC").on_hover_text(dir);
+                }
+                self.draw_stream_controls(ui);
+                self.draw_keyed_controls(ui);
+                if let Some(alerter) = &self.alerter {
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Send test alert")).clicked() {
+                            alerter.send_test();
+                        }
+                        if let Some(result) = alerter.last_result() {
+                            ui.small(result);
+                        }
+                    });
+                }
                 ui.separator();
-                ui.heading("Info")
-                    .on_hover_text("Performance and status details");
+                ui.heading(tr("Info"))
+                    .on_hover_text(tr("Performance and status details"));
                 ui.separator();
-                ui.label(format!("UI FPS: {:.1}", self.last_calculated_fps));
+                ui.label(format!("{}: {:.1}", tr("UI FPS"), self.last_calculated_fps));
+                match self.timecode_clock.now() {
+                    Some((tc, source, _)) => {
+                        ui.monospace(format!("TC {} ({})", tc, source.label()));
+                    }
+                    None => {
+                        ui.small(tr("TC: not chasing")).on_hover_text(
+                            tr("Waiting for MIDI timecode, or LTC on timecode.ltc_device"),
+                        );
+                    }
+                }
+                if let Some(e) = &self.timecode_error {
+                    ui.colored_label(Color32::RED, tr("LTC input failed")).on_hover_text(e);
+                }
+                if let Some(link) = self.link.as_mut() {
+                    let (tempo, peers) = (link.tempo(), link.num_peers());
+                    match self.link_position {
+                        Some(beats) => {
+                            let beat = tr("Link beat");
+                            ui.monospace(format!("{} {:.1} @ {:.1} BPM", beat, beats, tempo));
+                        }
+                        None => {
+                            ui.small(format!("{} ({} {})", tr("Link stopped"), peers, tr("peers")));
+                        }
+                    }
+                }
+                if ui
+                    .add(
+                        egui::Slider::new(&mut self.display_scale, 0.25..=1.0)
+                            .text(tr("Display scale")),
+                    )
+                    .on_hover_text(tr(
+                        "Upload a smaller preview; effects still render at full resolution",
+                    ))
+                    .changed()
+                {
+                    self.send_interaction(UserInteractionSegMsg::SetDisplayScale(self.display_scale));
+                }
+                if ui
+                    .checkbox(&mut self.partial_uploads, tr("Partial texture uploads"))
+                    .on_hover_text(tr(
+                        "Re-upload only changed tiles; saves bandwidth on static scenes",
+                    ))
+                    .changed()
+                {
+                    self.send_interaction(UserInteractionSegMsg::SetPartialUploads(
+                        self.partial_uploads,
+                    ));
+                }
+                let mut quality = self.visual_quality;
+                egui::ComboBox::from_label(tr("Effect quality"))
+                    .selected_text(tr(quality.label()))
+                    .show_ui(ui, |ui| {
+                        for option in VisualQuality::ALL {
+                            ui.selectable_value(&mut quality, option, tr(option.label()));
+                        }
+                    })
+                    .response
+                    .on_hover_text(tr("Render effects at reduced resolution inside each object"));
+                if quality != self.visual_quality {
+                    self.visual_quality = quality;
+                    self.send_interaction(UserInteractionSegMsg::SetVisualQuality(quality));
+                }
+                if ui
+                    .checkbox(&mut self.paint_layer, tr("GPU effects layer"))
+                    .on_hover_text(tr(
+                        "Paint each slot's style and outline over the raw frame in this window; \
+                         stream and recordings get the frame without them",
+                    ))
+                    .changed()
+                {
+                    self.send_interaction(UserInteractionSegMsg::SetPaintLayer(self.paint_layer));
+                }
+                let mut matte_changed = ui
+                    .checkbox(&mut self.matte.enabled, tr("Refine mask edges"))
+                    .on_hover_text(tr(
+                        "Soften masks along the camera image's edges (guided filter)",
+                    ))
+                    .changed();
+                if self.matte.enabled {
+                    matte_changed |= ui
+                        .add(
+                            egui::Slider::new(&mut self.matte.radius, 1..=24)
+                                .text(tr("Edge radius")),
+                        )
+                        .changed();
+                }
+                if matte_changed {
+                    self.send_interaction(UserInteractionSegMsg::SetMatte(self.matte));
+                }
+                self.draw_cadence_controls(ui);
+                for thread in [
+                    self.cam_thread.as_ref(),
+                    self.seg_thread.as_ref(),
+                    self.audio_processor_thread.as_ref(),
+                    self.stem_thread.as_ref(),
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    let state = if thread.is_running() { "ok" } else { "down" };
+                    ui.small(format!(
+                        "{}: {} (restarts: {})",
+                        thread.name(),
+                        state,
+                        thread.restarts()
+                    ));
+                }
+                ui.small(tr("Pipeline (queued / dropped):"))
+                    .on_hover_text(tr(
+                        "Channel sizes and drop policies are set in the config file",
+                    ));
+                for stats in &self.channel_stats {
+                    let capacity = match stats.config.capacity {
+                        Some(c) => c.to_string(),
+                        None => "∞".to_string(),
+                    };
+                    ui.small(format!(
+                        "  {}: {}/{} ({}), {} dropped",
+                        stats.name,
+                        stats.queued(),
+                        capacity,
+                        stats.config.policy.label(),
+                        stats.drops()
+                    ));
+                }
+                ui.small(format!(
+                    "  seg -> ui frames: latest wins, {} of {} dropped",
+                    self.seg_frames.overwritten(),
+                    self.seg_frames.published()
+                ));
                 match &self.camera_resolution {
                     Some(r) => {
-                        ui.label(format!("Cam Res: {}x{}", r.width(), r.height()));
+                        ui.label(format!("{}: {}x{}", tr("Cam Res"), r.width(), r.height()));
                     }
                     None if self.camera_error.is_none()
                         && self.seg_error.is_none()
                         && self.texture.is_none() =>
                     {
-                        ui.label("Cam Res: Initializing...");
+                        ui.label(tr("Cam Res: Initializing..."));
                     }
                     None if self.camera_error.is_some() || self.seg_error.is_some() => {
-                        ui.label("Cam Res: Error");
+                        ui.label(tr("Cam Res: Error"));
                     }
                     _ => {
-                        ui.label("Cam Res: Waiting...");
+                        ui.label(tr("Cam Res: Waiting..."));
                     }
                 }
                 if let Some(err) = &self.camera_error {
                     ui.separator();
-                    ui.colored_label(Color32::YELLOW, "Camera Error:")
+                    ui.colored_label(Color32::YELLOW, tr("Camera Error:"))
                         .on_hover_text(err);
                     ui.small(err);
                 }
                 if let Some(err) = &self.seg_error {
                     ui.separator();
-                    ui.colored_label(Color32::RED, "Processing Error:")
+                    ui.colored_label(Color32::RED, tr("Processing Error:"))
                         .on_hover_text(err);
                     ui.small(err);
                 }
             });
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            match &self.texture {
-                Some(texture) => {
+            if self.standby {
+                let rect = ui.max_rect();
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(rect, 0.0, Color32::BLACK);
+                if let Some(scheduler) = &self.scheduler {
+                    painter.text(
+                        rect.right_bottom() - Vec2::new(8.0, 8.0),
+                        egui::Align2::RIGHT_BOTTOM,
+                        format!("{} {}", tr("Closed · opens"), scheduler.opens_at()),
+                        egui::FontId::proportional(14.0),
+                        Color32::from_gray(60),
+                    );
+                }
+                return;
+            }
+            match self.texture.as_ref().map(|t| t.id()) {
+                Some(texture_id) => {
                     if let Some(tex_size) = self.texture_size {
                         let ar = if tex_size.y > 0.0 {
                             tex_size.x / tex_size.y
@@ -312,12 +5128,45 @@ impl eframe::App for WebcamAppUI {
                             iw = ah * ar;
                         }
                         let ds = Vec2::new(iw, ih);
-                        ui.with_layout(Layout::top_down(Align::Center), |ui| {
-                            let sized_texture = egui::load::SizedTexture::new(texture.id(), ds);
-                            ui.add(egui::Image::new(sized_texture)); // Display image, no interaction
-                        });
+                        let response = ui
+                            .with_layout(Layout::top_down(Align::Center), |ui| {
+                                let sized_texture = egui::load::SizedTexture::new(texture_id, ds);
+                                ui.add(egui::Image::new(sized_texture).sense(Sense::click_and_drag()))
+                            })
+                            .inner;
+                        match self.brush_mode {
+                            _ if self.calibrating_pin => self.handle_corner_pin(ui, &response),
+                            Some(mode) => self.handle_brush(ui, &response, mode),
+                            None => {
+                                self.handle_prompt_click(ui, &response);
+                                self.handle_roi_drag(ui, &response);
+                            }
+                        }
+                        self.draw_paint_layer(ctx, ui, response.rect);
+                        self.draw_operator_hud(ui, response.rect);
+                        self.draw_gamepad_cursor(ui, response.rect);
+                        if let Some(status) = &self.model_status {
+                            let painter = ui.painter_at(response.rect);
+                            painter.rect_filled(response.rect, 0.0, Color32::from_black_alpha(160));
+                            painter.text(
+                                response.rect.center(),
+                                egui::Align2::CENTER_CENTER,
+                                status,
+                                egui::FontId::proportional(28.0),
+                                Color32::WHITE,
+                            );
+                            ctx.request_repaint_after(Duration::from_millis(100));
+                        } else if self.model_lagging {
+                            ui.painter_at(response.rect).text(
+                                response.rect.right_top() + Vec2::new(-8.0, 8.0),
+                                egui::Align2::RIGHT_TOP,
+                                tr("MODEL LAGGING · raw camera"),
+                                egui::FontId::proportional(18.0),
+                                Color32::from_rgb(255, 80, 80),
+                            );
+                        }
                     } else {
-                        ui.centered_and_justified(|ui| ui.label("Texture size unknown."));
+                        ui.centered_and_justified(|ui| ui.label(tr("Texture size unknown.")));
                     }
                 }
                 None if self.live_audio_status != LiveAudioStatus::Disabled
@@ -326,62 +5175,166 @@ impl eframe::App for WebcamAppUI {
                 {
                     ui.centered_and_justified(|ui| {
                         ui.spinner();
-                        ui.label("Initializing stream...");
+                        ui.label(self.model_status.as_deref().unwrap_or(tr("Initializing stream...")));
                     });
                 }
                 None => {
                     ui.centered_and_justified(|ui| {
                         ui.colored_label(ui.visuals().error_fg_color, "Stream unavailable");
-                        ui.label("Check status panel.");
+                        ui.label(tr("Check status panel."));
                     });
                 }
             }
         });
 
+        self.draw_self_test_window(ctx);
+        self.draw_profile_picker(ctx);
+        self.draw_project_window(ctx);
+        self.draw_compare_window(ctx);
+
         if !received_frame_this_update {
             ctx.request_repaint_after(Duration::from_millis(100));
         }
     } 
 
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SELF_TEST_DONE_KEY, &self.self_test_done);
+        eframe::set_value(storage, CORNER_PIN_KEY, &self.corner_pin);
+        eframe::set_value(storage, LANGUAGE_KEY, &i18n::language());
+        eframe::set_value(storage, PROFILE_KEY, &self.profile);
+        eframe::set_value(storage, AUDIO_INPUT_KEY, &self.audio_input);
+        eframe::set_value(storage, RECENT_PROJECTS_KEY, &self.recent_projects);
+        eframe::set_value(storage, MODEL_FILE_KEY, &self.model_file);
+    }
+
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
 
         info!("Exit requested...");
-        self.cam_stop_signal.store(true, Ordering::Relaxed);
-        self.seg_stop_signal.store(true, Ordering::Relaxed);
+        // Cancels every worker's stop signal at once; their selects wake, so the joins below
+        // return right away
+        self.shutdown.stop();
         self.audio_capture_stop_signal
             .store(true, Ordering::Relaxed);
-        self.audio_processor_stop_signal
-            .store(true, Ordering::Relaxed);
         info!("Stop signals sent.");
-        if let Some(stream) = self.audio_capture_thread_handle.take() {
+        self.ltc_stream = None;
+        self.close_monitor();
+        self.stop_audio_recording();
+        for stream in self.audio_capture_streams.drain(..) {
             if let Err(e) = stream.pause() {
                 error!("Error pausing audio stream: {}", e);
             }
             drop(stream);
             info!("Audio stream dropped.");
         }
-        if let Some(h) = self.cam_thread_handle.take() {
-            info!("Joining cam...");
-            if let Err(e) = h.join() {
-                error!("Cam join err: {:?}", e);
-            }
+        for thread in [
+            self.cam_thread.as_mut(),
+            self.audio_processor_thread.as_mut(),
+            self.stem_thread.as_mut(),
+            self.seg_thread.as_mut(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            thread.join();
         }
-        if let Some(h) = self.audio_processor_thread_handle.take() {
-            info!("Joining audio proc...");
-            if let Err(e) = h.join() {
-                error!("Audio proc join err: {:?}", e);
-            }
+        if let Some(handle) = self.metrics_thread.take() {
+            let _ = handle.join();
         }
-        if let Some(h) = self.seg_thread_handle.take() {
-            info!("Joining seg...");
-            if let Err(e) = h.join() {
-                error!("Seg join err: {:?}", e);
-            }
+        if let Some(handle) = self.telemetry_thread.take() {
+            let _ = handle.join();
+        }
+        if let Some(alerter) = self.alerter.as_mut() {
+            alerter.join();
+        }
+        if let Some(preview) = self.preview.as_mut() {
+            preview.stop();
+        }
+        if let Some(control) = self.control.as_mut() {
+            control.stop();
+        }
+        if let Some(stream) = self.rtmp_stream.take() {
+            stream.stop();
         }
         info!("All threads stopped/joined.");
     }
 }
 
+fn crop_color_image(image: &egui::ColorImage, rect: &DamageRect) -> egui::ColorImage {
+    let mut part = egui::ColorImage::new(rect.size, Color32::BLACK);
+    for (row, y) in (rect.pos[1]..rect.pos[1] + rect.size[1]).enumerate() {
+        let start = y * image.size[0] + rect.pos[0];
+        part.pixels[row * rect.size[0]..(row + 1) * rect.size[0]]
+            .copy_from_slice(&image.pixels[start..start + rect.size[0]]);
+    }
+    part
+}
+
+// Kiosk/touchscreen look: finger-sized widgets and no hover-only information
+fn apply_touch_style(ctx: &egui::Context) {
+    ctx.all_styles_mut(|style| {
+        style.spacing.interact_size = Vec2::new(64.0, 48.0);
+        style.spacing.button_padding = Vec2::new(16.0, 12.0);
+        style.spacing.item_spacing = Vec2::new(12.0, 12.0);
+        style.spacing.slider_width = 240.0;
+        style.spacing.icon_width = 28.0;
+        for font in style.text_styles.values_mut() {
+            font.size *= 1.4;
+        }
+        // Fingers don't hover; anything only in a tooltip must be on screen anyway
+        style.interaction.tooltip_delay = f32::INFINITY;
+    });
+}
+
+// One effect layer's controls; true = remove it
+fn layer_editor(ui: &mut egui::Ui, layer: &mut EffectLayer) -> bool {
+    let remove = ui
+        .horizontal(|ui| {
+            egui::ComboBox::from_id_salt("style")
+                .selected_text(tr(layer.style.label()))
+                .show_ui(ui, |ui| {
+                    for option in VisualStyle::ALL {
+                        ui.selectable_value(&mut layer.style, option, tr(option.label()));
+                    }
+                });
+            egui::ComboBox::from_id_salt("blend")
+                .selected_text(tr(layer.blend.label()))
+                .show_ui(ui, |ui| {
+                    for option in BlendMode::ALL {
+                        ui.selectable_value(&mut layer.blend, option, tr(option.label()));
+                    }
+                });
+            egui::ComboBox::from_id_salt("driver")
+                .selected_text(layer.driver.map_or(tr("Slot"), |c| tr(c.label())))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut layer.driver, None, tr("Slot"));
+                    for channel in AnalysisChannel::ALL {
+                        ui.selectable_value(&mut layer.driver, Some(channel), tr(channel.label()));
+                    }
+                });
+            ui.small_button("✖").clicked()
+        })
+        .inner;
+    layer.params = layer.params.for_style(layer.style);
+    let specs = layer.style.params();
+    for (spec, value) in specs.iter().zip(layer.params.values_mut()) {
+        param_slider(ui, spec.name, spec.unit, spec.min..=spec.max, value);
+    }
+    ui.add(egui::Slider::new(&mut layer.opacity, 0.0..=1.0).text(tr("Opacity")));
+    remove
+}
+
+// Slider generated from a parameter schema (built-in styles and plugins alike)
+fn param_slider(
+    ui: &mut egui::Ui,
+    name: &str,
+    unit: &str,
+    range: std::ops::RangeInclusive<f32>,
+    value: &mut f32,
+) -> bool {
+    ui.add(egui::Slider::new(value, range).text(name).suffix(unit))
+        .changed()
+}
+
 trait CenteredJustified {
     fn centered_and_justified(&mut self, add_contents: impl FnOnce(&mut egui::Ui));
 }
@@ -1,88 +1,704 @@
 // src/visuals.rs
-use image::{ImageBuffer, Luma, Rgb, RgbImage}; 
-use imageproc::rect::Rect;
+use image::{
+    imageops::{self, FilterType},
+    GrayImage, ImageBuffer, Luma, Rgb, RgbImage,
+}; 
+use imageproc::{
+    distance_transform::{distance_transform, Norm},
+    morphology::dilate,
+    rect::Rect,
+};
+use palette::{Hsv, IntoColor, Srgb};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::f32::consts::PI;
+use wide::f32x8;
 
+// Masks are 8-bit alpha: 0 = outside, 255 = fully inside, in between = soft edge. Feathered masks
+// (mattes) get effects over every pixel with any alpha, composited in proportion to it; the
+// model's own masks only have resampling ramps at the edge and are cut at the threshold.
+const MASK_THRESHOLD: u8 = 128; // Shape tests (seeds, distance fields) count alpha above this
 const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
 const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
 
-#[inline]
-fn lerp_color(c1: Rgb<u8>, c2: Rgb<u8>, t: f32) -> Rgb<u8> {
+// --- Visual styles selectable per track slot ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VisualStyle {
+    Pulse,
+    Vortex,
+    Static,
+    Bulge,
+    Ripple,
+    Shear,
+    Hue,
+    Extrude,
+    Clones,
+    Shatter,
+    Turing,
+    Life,
+}
+
+impl VisualStyle {
+    pub const ALL: [VisualStyle; 12] = [
+        VisualStyle::Pulse,
+        VisualStyle::Vortex,
+        VisualStyle::Static,
+        VisualStyle::Bulge,
+        VisualStyle::Ripple,
+        VisualStyle::Shear,
+        VisualStyle::Hue,
+        VisualStyle::Extrude,
+        VisualStyle::Clones,
+        VisualStyle::Shatter,
+        VisualStyle::Turing,
+        VisualStyle::Life,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            VisualStyle::Pulse => "Pulse",
+            VisualStyle::Vortex => "Vortex",
+            VisualStyle::Static => "Static",
+            VisualStyle::Bulge => "Warp: Bulge",
+            VisualStyle::Ripple => "Warp: Ripple",
+            VisualStyle::Shear => "Warp: Shear",
+            VisualStyle::Hue => "Hue (pitch)",
+            VisualStyle::Extrude => "Extrude",
+            VisualStyle::Clones => "Clones",
+            VisualStyle::Shatter => "Stained Glass",
+            VisualStyle::Turing => "Reaction-Diffusion",
+            VisualStyle::Life => "Cellular Automaton",
+        }
+    }
+
+    pub fn params(self) -> &'static [ParamSpec] {
+        match self {
+            VisualStyle::Pulse => PULSE_PARAMS,
+            VisualStyle::Vortex => VORTEX_PARAMS,
+            VisualStyle::Static => STATIC_PARAMS,
+            VisualStyle::Bulge | VisualStyle::Ripple | VisualStyle::Shear => WARP_PARAMS,
+            VisualStyle::Hue => HUE_PARAMS,
+            VisualStyle::Extrude => EXTRUDE_PARAMS,
+            VisualStyle::Clones => CLONE_PARAMS,
+            VisualStyle::Shatter => SHATTER_PARAMS,
+            VisualStyle::Turing => TURING_PARAMS,
+            VisualStyle::Life => LIFE_PARAMS,
+        }
+    }
+
+    // Stamps copies of the object elsewhere in the frame instead of painting inside its mask
+    fn draws_outside_mask(self) -> bool {
+        matches!(self, VisualStyle::Clones)
+    }
+
+    // Original slot -> style mapping (Bass, Mid, High)
+    pub fn default_for_slot(slot: usize) -> Self {
+        match slot {
+            0 => VisualStyle::Pulse,
+            1 => VisualStyle::Vortex,
+            _ => VisualStyle::Static,
+        }
+    }
+}
+
+// --- Declarative tunables: each style lists its parameters, the UI builds sliders from them ---
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+    pub unit: &'static str, // Slider suffix, e.g. "×" for multipliers of the built-in rate
+}
+
+const fn param(
+    name: &'static str,
+    min: f32,
+    max: f32,
+    default: f32,
+    unit: &'static str,
+) -> ParamSpec {
+    ParamSpec {
+        name,
+        min,
+        max,
+        default,
+        unit,
+    }
+}
+
+pub const MAX_STYLE_PARAMS: usize = 4;
+
+const PULSE_PARAMS: &[ParamSpec] = &[
+    param("Speed", 0.25, 4.0, 1.0, "×"),
+    param("Ring width", 0.02, 0.5, 0.1, ""),
+    param("Glow", 0.0, 1.0, 0.3, ""),
+];
+const VORTEX_PARAMS: &[ParamSpec] = &[
+    param("Spin", 0.0, 4.0, 1.0, "×"),
+    param("Turbulence", 0.0, 3.0, 1.0, "×"),
+    param("Sparkles", 0.0, 0.05, 0.005, ""),
+];
+const STATIC_PARAMS: &[ParamSpec] = &[
+    param("Lines", 2.0, 40.0, 8.0, ""),
+    param("Line speed", 0.0, 4.0, 1.0, "×"),
+];
+const WARP_PARAMS: &[ParamSpec] = &[
+    param("Amount", 0.0, 3.0, 1.0, "×"),
+    param("Speed", 0.0, 4.0, 1.0, "×"),
+];
+const HUE_PARAMS: &[ParamSpec] = &[
+    param("Hue range", 0.0, 360.0, 300.0, "°"),
+    param("Saturation", 0.0, 1.0, 0.85, ""),
+    param("Spread", 0.0, 90.0, 20.0, "°"),
+];
+const EXTRUDE_PARAMS: &[ParamSpec] = &[
+    param("Depth", 0.0, 3.0, 1.0, "×"),
+    param("Light speed", 0.0, 4.0, 1.0, "×"),
+];
+const CLONE_PARAMS: &[ParamSpec] = &[
+    param("Max clones", 1.0, 12.0, 6.0, ""),
+    param("Spread", 0.2, 2.0, 1.0, "×"),
+];
+const SHATTER_PARAMS: &[ParamSpec] = &[
+    param("Cells", 4.0, 96.0, 32.0, ""),
+    param("Shatter", 0.0, 3.0, 1.0, "×"),
+];
+const TURING_PARAMS: &[ParamSpec] = &[
+    param("Steps", 1.0, 32.0, 8.0, "/frame"),
+    param("Reseed rate", 0.0, 0.5, 0.05, ""),
+];
+const LIFE_PARAMS: &[ParamSpec] = &[
+    param("Cell size", 2.0, 16.0, 4.0, " px"),
+    param("Trail", 0.5, 0.99, 0.8, ""),
+];
+
+// Current values for one slot's style, in `VisualStyle::params` order. Tagged with the style
+// they belong to: values saved for another style (old presets, cues) fall back to the defaults
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StyleParams {
+    style: VisualStyle,
+    values: [f32; MAX_STYLE_PARAMS],
+}
+
+impl StyleParams {
+    pub fn defaults(style: VisualStyle) -> Self {
+        let mut values = [0.0; MAX_STYLE_PARAMS];
+        for (v, spec) in values.iter_mut().zip(style.params()) {
+            *v = spec.default;
+        }
+        Self { style, values }
+    }
+
+    pub fn for_style(self, style: VisualStyle) -> Self {
+        if self.style == style {
+            self
+        } else {
+            Self::defaults(style)
+        }
+    }
+
+    // One entry per spec in `VisualStyle::params`
+    pub fn values_mut(&mut self) -> &mut [f32] {
+        let len = self.style.params().len();
+        &mut self.values[..len]
+    }
+
+    // Clamped to the spec, so hand-edited presets can't push a style out of range
+    fn get(&self, style: VisualStyle, index: usize) -> f32 {
+        let spec = &style.params()[index];
+        if self.style != style {
+            return spec.default;
+        }
+        self.values[index].clamp(spec.min, spec.max)
+    }
+}
+
+// --- Animated strokes along the object outline, drawn on top of the style ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutlineStyle {
+    None,
+    Ants,
+    Dashes,
+    Arrows,
+    Particles,
+}
+
+impl OutlineStyle {
+    pub const ALL: [OutlineStyle; 5] = [
+        OutlineStyle::None,
+        OutlineStyle::Ants,
+        OutlineStyle::Dashes,
+        OutlineStyle::Arrows,
+        OutlineStyle::Particles,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OutlineStyle::None => "None",
+            OutlineStyle::Ants => "Marching Ants",
+            OutlineStyle::Dashes => "Dashes",
+            OutlineStyle::Arrows => "Arrows",
+            OutlineStyle::Particles => "Particles",
+        }
+    }
+}
+
+// --- Heat shimmer: rippling air around objects once the bass has stayed loud for a while ---
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ShimmerSettings {
+    pub enabled: bool,
+    pub hold_secs: f32, // Sustained bass needed before the shimmer starts
+    pub width: u8,      // Band around the mask, in pixels
+    pub amount: f32,    // Displacement in pixels at full strength
+}
+
+impl Default for ShimmerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hold_secs: 2.0,
+            width: 16,
+            amount: 4.0,
+        }
+    }
+}
+
+impl ShimmerSettings {
+    // Fades in over one second once the bass has been held for `hold_secs`
+    pub fn strength(&self, sustained_secs: f32) -> f32 {
+        if !self.enabled {
+            return 0.0;
+        }
+        (sustained_secs - self.hold_secs).clamp(0.0, 1.0)
+    }
+}
+
+// --- Drum hit reactions: a ring bursting out of the object on kicks, sparkles on hi-hats ---
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DrumFxSettings {
+    pub kick_ring: bool,
+    pub hat_sparkle: bool,
+}
+
+const KICK_RING_COLOR: Rgb<u8> = Rgb([255, 110, 40]);
+const HAT_SPARKLES: usize = 24; // Glints per object right at the hit, thinning as it fades
+// Plus-shaped glint: (dx, dy, share of the glint's alpha)
+const GLINT: [(i32, i32, f32); 9] = [
+    (0, 0, 1.0),
+    (-1, 0, 0.7),
+    (1, 0, 0.7),
+    (0, -1, 0.7),
+    (0, 1, 0.7),
+    (-2, 0, 0.3),
+    (2, 0, 0.3),
+    (0, -2, 0.3),
+    (0, 2, 0.3),
+];
+
+// --- Animation position that integrates a changing speed over frames ---
+// frame_count × speed would jump whenever the speed moves with the music; this bends instead
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Motion {
+    phase: f32,
+    last_frame: Option<u64>,
+}
+
+impl Motion {
+    // Adds speed (per frame) × frames since the last call. A fresh state starts at
+    // frame_count × speed, so one-off renders still animate; repeats and rewinds hold still
+    pub fn advance(&mut self, frame_count: u64, speed: f32) -> f32 {
+        let frames = match self.last_frame {
+            Some(last) => frame_count.saturating_sub(last) as f32,
+            None => frame_count as f32,
+        };
+        self.phase += frames * speed;
+        self.last_frame = Some(frame_count);
+        self.phase
+    }
+}
+
+// --- Persistent per-slot state for the simulation styles; owned by the caller across frames ---
+#[derive(Debug, Default)]
+pub struct SlotState {
+    reaction: Option<ReactionDiffusion>,
+    automaton: Option<CellAutomaton>,
+    motion: Motion,      // Speed-driven part of the style (pulse, spin, lines, light, warp)
+    pub outline: Motion, // Travel of the outline pattern along the contour
+    pub pan: f32, // Stereo position of the slot's band, -1 (left) to 1 (right); set by the caller
+    pub beat: f32, // Beat channel level, which Shatter triggers on; set by the caller
+    shatter: ShatterBurst,
+    reduced: bool, // Last frame rendered at reduced resolution
+    pub layers: Vec<SlotState>, // One per extra effect layer, in the slot's layer order
+}
+
+// --- Blend modes: how a layer (effect, outline, sprite) combines with the pixels beneath it ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Add,
+    Multiply,
+    Screen,
+    Overlay,
+    Difference,
+    ColorDodge,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 7] = [
+        BlendMode::Normal,
+        BlendMode::Add,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Difference,
+        BlendMode::ColorDodge,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BlendMode::Normal => "Normal",
+            BlendMode::Add => "Add",
+            BlendMode::Multiply => "Multiply",
+            BlendMode::Screen => "Screen",
+            BlendMode::Overlay => "Overlay",
+            BlendMode::Difference => "Difference",
+            BlendMode::ColorDodge => "Color dodge",
+        }
+    }
+
+    // `layer` over `base`, one channel
+    #[inline(always)]
+    pub fn blend(self, base: u8, layer: u8) -> u8 {
+        let (b, l) = (base as u32, layer as u32);
+        match self {
+            BlendMode::Normal => layer,
+            BlendMode::Add => (b + l).min(255) as u8,
+            BlendMode::Multiply => ((b * l + 127) / 255) as u8,
+            BlendMode::Screen => (255 - ((255 - b) * (255 - l) + 127) / 255) as u8,
+            // Multiply in the shadows, screen in the highlights, keyed on the base
+            BlendMode::Overlay if b < 128 => ((2 * b * l + 127) / 255) as u8,
+            BlendMode::Overlay => (255 - (2 * (255 - b) * (255 - l) + 127) / 255) as u8,
+            BlendMode::Difference => b.abs_diff(l) as u8,
+            BlendMode::ColorDodge if l == 255 => 255,
+            BlendMode::ColorDodge => (b * 255 / (255 - l)).min(255) as u8,
+        }
+    }
+
+    // `layer` blended into `px`, then faded back toward the original `px` by 1 - alpha/255
+    #[inline(always)]
+    pub fn composite(self, px: &mut [u8], layer: [u8; 3], alpha: u8) {
+        let under = [px[0], px[1], px[2]];
+        for (c, l) in px.iter_mut().zip(layer) {
+            *c = self.blend(*c, l);
+        }
+        if alpha < 255 {
+            mix_alpha(px, under, alpha);
+        }
+    }
+}
+
+// Centered styles drift toward the side the sound pans to, up to this share of the bbox width
+const PAN_SHIFT: f32 = 0.35;
+
+fn effect_center(bbox_rect: Rect, pan: f32) -> (f32, f32) {
+    let w = bbox_rect.width() as f32;
+    (
+        bbox_rect.left() as f32 + w / 2.0 + pan.clamp(-1.0, 1.0) * w * PAN_SHIFT,
+        bbox_rect.top() as f32 + bbox_rect.height() as f32 / 2.0,
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WarpMode {
+    Bulge,
+    Ripple,
+    Shear,
+}
+
+// a + (b - a) * t form; +0.5 truncation rounds the same as round() for non-negative values
+#[inline(always)]
+pub(crate) fn lerp_color(c1: Rgb<u8>, c2: Rgb<u8>, t: f32) -> Rgb<u8> {
     let t = t.clamp(0.0, 1.0);
-    Rgb([
-        (c1[0] as f32 * (1.0 - t) + c2[0] as f32 * t).round() as u8,
-        (c1[1] as f32 * (1.0 - t) + c2[1] as f32 * t).round() as u8,
-        (c1[2] as f32 * (1.0 - t) + c2[2] as f32 * t).round() as u8,
-    ])
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t + 0.5) as u8;
+    Rgb([lerp(c1[0], c2[0]), lerp(c1[1], c2[1]), lerp(c1[2], c2[2])])
 }
 
-#[inline]
-fn is_inside_mask(x: i32, y: i32, mask: &ImageBuffer<Luma<u8>, Vec<u8>>) -> bool {
-    if x < 0 || y < 0 {
-        return false;
+// --- Render resolution of the object effects ---
+// Reduced levels run the style on a downscaled copy of the bbox and upsample it under the
+// full-resolution mask, so weak machines hold frame rate when objects fill the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VisualQuality {
+    #[default]
+    Full,
+    Half,
+    Quarter,
+}
+
+impl VisualQuality {
+    pub const ALL: [VisualQuality; 3] = [
+        VisualQuality::Full,
+        VisualQuality::Half,
+        VisualQuality::Quarter,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            VisualQuality::Full => "Full",
+            VisualQuality::Half => "Half",
+            VisualQuality::Quarter => "Quarter",
+        }
+    }
+
+    // Downscale factor per side
+    pub fn factor(self) -> u32 {
+        match self {
+            VisualQuality::Full => 1,
+            VisualQuality::Half => 2,
+            VisualQuality::Quarter => 4,
+        }
+    }
+}
+
+const MIN_REDUCED_SIDE: u32 = 16; // Smaller reduced bboxes render at full resolution instead
+// A full-resolution slot switches to reduced only this far past the limit, so a bbox hovering
+// around it doesn't flip the look every frame
+const REDUCED_HYSTERESIS: u32 = 4;
+
+// --- Mask spans: the in-mask runs of each bbox row, found once per object per frame ---
+// Effects walk these instead of testing every bbox pixel, and skip rows the mask misses.
+struct MaskSpans {
+    x0: usize,
+    x1: usize,
+    y0: usize,
+    rows: Vec<(u32, u32)>, // Per bbox row: range into `runs`
+    runs: Vec<(u32, u32)>, // Absolute [start, end) x of each in-mask run
+    soft: bool,            // Feathered, and some run pixel is partially covered
+}
+
+impl MaskSpans {
+    // Mask must have the display's dimensions (checked in draw_visuals). Feathered masks count
+    // any alpha as inside, others only alpha above MASK_THRESHOLD
+    fn new(mask_image: &GrayImage, bbox_rect: Rect, feathered: bool) -> Self {
+        let threshold = if feathered { 0 } else { MASK_THRESHOLD };
+        let (w, h) = mask_image.dimensions();
+        let x0 = bbox_rect.left().clamp(0, w as i32) as usize;
+        let x1 = (bbox_rect.right().clamp(0, w as i32) as usize).max(x0);
+        let y0 = bbox_rect.top().clamp(0, h as i32) as usize;
+        let y1 = (bbox_rect.bottom().clamp(0, h as i32) as usize).max(y0);
+        let raw = mask_image.as_raw();
+        let mut rows = Vec::with_capacity(y1 - y0);
+        let mut runs = Vec::new();
+        let mut soft = false;
+        for y in y0..y1 {
+            let row = &raw[y * w as usize + x0..y * w as usize + x1];
+            let first = runs.len() as u32;
+            let mut x = 0;
+            while let Some(start) = row[x..].iter().position(|&m| m > threshold) {
+                let start = x + start;
+                let len = row[start..]
+                    .iter()
+                    .position(|&m| m <= threshold)
+                    .unwrap_or(row.len() - start);
+                soft = soft || (feathered && row[start..start + len].iter().any(|&m| m < 255));
+                runs.push(((x0 + start) as u32, (x0 + start + len) as u32));
+                x = start + len;
+            }
+            rows.push((first, runs.len() as u32));
+        }
+        Self {
+            x0,
+            x1,
+            y0,
+            rows,
+            runs,
+            soft,
+        }
+    }
+}
+
+// Masked pixels of one row as (x, rgb) pairs, walking the row's runs
+struct MaskedPixels<'a> {
+    runs: std::slice::Iter<'a, (u32, u32)>,
+    rest: &'a mut [u8], // RGB-interleaved, starting at pixel `x`
+    x: usize,
+    end: usize,
+}
+
+impl<'a> Iterator for MaskedPixels<'a> {
+    type Item = (usize, &'a mut [u8]);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.x >= self.end {
+            let &(start, end) = self.runs.next()?;
+            let skip = (start as usize - self.x) * 3;
+            self.rest = &mut std::mem::take(&mut self.rest)[skip..];
+            (self.x, self.end) = (start as usize, end as usize);
+        }
+        let (px, rest) = std::mem::take(&mut self.rest).split_at_mut(3);
+        self.rest = rest;
+        self.x += 1;
+        Some((self.x - 1, px))
     }
-    mask.get_pixel_checked(x as u32, y as u32)
-        .map_or(false, |p| p[0] > 128)
+}
+
+// Calls `f(y, x_start, width, pixels)` for each bbox row with at least one masked pixel;
+// x_start/width describe the clipped bbox row, `pixels` yields only the masked ones.
+fn for_each_masked_row(
+    display_image: &mut RgbImage,
+    spans: &MaskSpans,
+    mut f: impl FnMut(usize, usize, usize, MaskedPixels<'_>),
+) {
+    let w = display_image.width() as usize;
+    let pixels: &mut [u8] = display_image;
+    for (i, &(first, last)) in spans.rows.iter().enumerate() {
+        if first == last {
+            continue;
+        }
+        let row = (spans.y0 + i) * w;
+        let masked = MaskedPixels {
+            runs: spans.runs[first as usize..last as usize].iter(),
+            rest: &mut pixels[(row + spans.x0) * 3..(row + spans.x1) * 3],
+            x: spans.x0,
+            end: spans.x0,
+        };
+        f(spans.y0 + i, spans.x0, spans.x1 - spans.x0, masked);
+    }
+}
+
+// Moves `px` toward `under` by 1 - alpha/255
+#[inline(always)]
+fn mix_alpha(px: &mut [u8], under: [u8; 3], alpha: u8) {
+    let a = alpha as u32;
+    for (c, u) in px.iter_mut().zip(under) {
+        *c = ((*c as u32 * a + u as u32 * (255 - a) + 127) / 255) as u8;
+    }
+}
+
+// --- Soft edges: the bbox as it was before drawing, then faded back in by 1 - alpha ---
+fn snapshot_spans(display_image: &RgbImage, spans: &MaskSpans) -> RgbImage {
+    let (w, h) = ((spans.x1 - spans.x0) as u32, spans.rows.len() as u32);
+    imageops::crop_imm(display_image, spans.x0 as u32, spans.y0 as u32, w, h).to_image()
+}
+
+fn fade_by_alpha(
+    display_image: &mut RgbImage,
+    before: &RgbImage,
+    mask_image: &GrayImage,
+    spans: &MaskSpans,
+) {
+    let w = mask_image.width() as usize;
+    let alpha = mask_image.as_raw();
+    for_each_masked_row(display_image, spans, |y, _, _, pixels| {
+        for (x, px) in pixels {
+            let a = alpha[y * w + x];
+            if a < 255 {
+                let under = before.get_pixel((x - spans.x0) as u32, (y - spans.y0) as u32);
+                mix_alpha(px, under.0, a);
+            }
+        }
+    });
+}
+
+// Bilinear sample with edge clamping
+#[inline]
+fn sample_bilinear(image: &RgbImage, x: f32, y: f32) -> Rgb<u8> {
+    let max_x = image.width().saturating_sub(1) as f32;
+    let max_y = image.height().saturating_sub(1) as f32;
+    let x = x.clamp(0.0, max_x);
+    let y = y.clamp(0.0, max_y);
+    let x0 = x.floor() as u32;
+    let y0 = y.floor() as u32;
+    let x1 = (x0 + 1).min(max_x as u32);
+    let y1 = (y0 + 1).min(max_y as u32);
+    let tx = x - x0 as f32;
+    let ty = y - y0 as f32;
+    let top = lerp_color(*image.get_pixel(x0, y0), *image.get_pixel(x1, y0), tx);
+    let bottom = lerp_color(*image.get_pixel(x0, y1), *image.get_pixel(x1, y1), tx);
+    lerp_color(top, bottom, ty)
 }
 
 #[inline]
-fn spatial_noise(x: f32, y: f32, seed: f32) -> f32 {
+pub(crate) fn spatial_noise(x: f32, y: f32, seed: f32) -> f32 {
     let val = (x * 12.9898 + y * 78.233 + seed * 123.456).sin() * 43758.5453;
     val - val.floor()
 }
 
+// spatial_noise((x_start + i) * x_scale, y, seed) for each i in out, 8 lanes at a time.
+// The y/seed term is constant along a row, so it is hoisted out.
+pub(crate) fn spatial_noise_row(x_start: f32, x_scale: f32, y: f32, seed: f32, out: &mut [f32]) {
+    let row_term = f32x8::splat(y * 78.233 + seed * 123.456);
+    let step = f32x8::splat(x_scale * 12.9898);
+    let lanes = f32x8::from([0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+    let scale = f32x8::splat(43758.5453);
+    let mut base = x_start;
+    let mut chunks = out.chunks_exact_mut(8);
+    for chunk in &mut chunks {
+        let xs = f32x8::splat(base) + lanes;
+        let val = (xs * step + row_term).sin() * scale;
+        chunk.copy_from_slice(&(val - val.floor()).to_array());
+        base += 8.0;
+    }
+    for (i, o) in chunks.into_remainder().iter_mut().enumerate() {
+        *o = spatial_noise((base + i as f32) * x_scale, y, seed);
+    }
+}
+
 // --- Bass Visualization - "Expanding Pulse / Heartbeat" 
 fn draw_bass_visuals(
     display_image: &mut RgbImage,
-    mask_image: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    spans: &MaskSpans,
     bbox_rect: Rect,
+    (center_x, center_y): (f32, f32),
     intensity: f32,
     frame_count: u64,
     _animation_phase: f32,
+    motion: &mut Motion,
+    [speed, ring_width, glow]: [f32; 3],
     _rng: &mut impl Rng,
 ) {
-    let center_x = bbox_rect.left() + bbox_rect.width() as i32 / 2;
-    let center_y = bbox_rect.top() + bbox_rect.height() as i32 / 2;
     let max_dim = (bbox_rect.width().max(bbox_rect.height())) as f32;
     let color_low = Rgb([80, 0, 10]);
     let color_high = lerp_color(Rgb([255, 0, 0]), Rgb([255, 100, 0]), intensity);
-    let pulse_speed = 0.02 + intensity * 0.05;
-    let phase = (frame_count as f32 * pulse_speed) % 1.0;
+    let pulse_speed = (0.02 + intensity * 0.05) * speed;
+    let phase = motion.advance(frame_count, pulse_speed) % 1.0;
     let ring_center_norm = phase;
-    let ring_thickness_norm = (0.1 + intensity * 0.2).clamp(0.01, 0.5); // Ensure non-zero thickness
-    let background_color = lerp_color(BLACK, color_low, intensity * 0.3);
-
-    for y in bbox_rect.top()..bbox_rect.bottom() {
-        for x in bbox_rect.left()..bbox_rect.right() {
-            if is_inside_mask(x, y, mask_image) {
-                let dx = x as f32 - center_x as f32;
-                let dy = y as f32 - center_y as f32;
-                let dist_from_center = (dx * dx + dy * dy).sqrt();
-                let dist_norm = dist_from_center / (max_dim * 0.5).max(1.0);
-                let dist_from_ring_center = (dist_norm - ring_center_norm).abs();
-                let ring_value = (1.0 - (dist_from_ring_center / ring_thickness_norm))
-                    .clamp(0.0, 1.0)
-                    .powi(2);
-                let final_color = lerp_color(background_color, color_high, ring_value);
-                display_image.put_pixel(x as u32, y as u32, final_color);
-            }
+    let ring_thickness_norm = (ring_width + intensity * 0.2).clamp(0.01, 0.5); // Never zero
+    let background_color = lerp_color(BLACK, color_low, intensity * glow);
+    let inv_radius = 1.0 / (max_dim * 0.5).max(1.0);
+    let inv_thickness = 1.0 / ring_thickness_norm;
+
+    for_each_masked_row(display_image, spans, |y, _, _, pixels| {
+        let dy = y as f32 - center_y;
+        let dy2 = dy * dy;
+        for (x, px) in pixels {
+            let dx = x as f32 - center_x;
+            let dist_norm = (dx * dx + dy2).sqrt() * inv_radius;
+            let dist_from_ring_center = (dist_norm - ring_center_norm).abs();
+            let ring_value = (1.0 - dist_from_ring_center * inv_thickness)
+                .clamp(0.0, 1.0)
+                .powi(2);
+            let final_color = lerp_color(background_color, color_high, ring_value);
+            px.copy_from_slice(&final_color.0);
         }
-    }
+    });
 }
 
 // --- Mid Visualization - "Swirling Vortex / Galaxy"
 fn draw_mid_visuals(
     display_image: &mut RgbImage,
-    mask_image: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    spans: &MaskSpans,
     bbox_rect: Rect,
+    (center_x, center_y): (f32, f32),
     intensity: f32, // 0.0 to 1.0
     frame_count: u64,
     animation_phase: f32, // Use for rotation base
+    motion: &mut Motion,
+    [spin, turbulence, sparkles]: [f32; 3],
     rng: &mut impl Rng,
 ) {
-    let center_x = bbox_rect.left() as f32 + bbox_rect.width() as f32 / 2.0;
-    let center_y = bbox_rect.top() as f32 + bbox_rect.height() as f32 / 2.0;
     let max_dist = (bbox_rect.width().max(bbox_rect.height()) as f32 * 0.7).max(1.0);
 
     // Palette: Greens, Blues, Purples swirling
@@ -91,75 +707,88 @@ fn draw_mid_visuals(
     let color3 = Rgb([100, 50, 200]); // Purple
 
     // Intensity effects
-    let rotation_speed = 0.01 + intensity * 0.05;
-    let noise_amount = 0.1 + intensity * 0.4; // How much noise distorts the swirl
+    let rotation_speed = (0.01 + intensity * 0.05) * spin;
+    let noise_amount = (0.1 + intensity * 0.4) * turbulence; // How much noise distorts the swirl
     let brightness_boost = intensity * 0.5; // Boost overall brightness
+    let spin_phase = motion.advance(frame_count, rotation_speed);
+    let noise_seed = frame_count as f32 * 0.01;
+    let row_len = bbox_rect.width() as usize;
+    let mut angle_noise_row = vec![0.0f32; row_len];
+    let mut dist_noise_row = vec![0.0f32; row_len];
 
-    for y in bbox_rect.top()..bbox_rect.bottom() {
-        for x in bbox_rect.left()..bbox_rect.right() {
-            if is_inside_mask(x, y, mask_image) {
-                // --- Calculate coordinates relative to center ---
-                let rel_x = x as f32 - center_x;
-                let rel_y = y as f32 - center_y;
-                let dist = (rel_x * rel_x + rel_y * rel_y).sqrt();
-                let mut angle = rel_y.atan2(rel_x); // Current angle
-
-                // --- Add swirl based on distance and time/phase ---
-                // Rotate more closer to the center, speed based on intensity
-                let rotation_factor = (1.0 - (dist / max_dist)).powi(2); // Rotate more near center
-                let rotation_amount =
-                    animation_phase + frame_count as f32 * rotation_speed * rotation_factor;
-                angle += rotation_amount;
-
-                // --- Add noise distortion to angle and distance ---
-                let noise_seed = frame_count as f32 * 0.01;
-                let noise_val = spatial_noise(rel_x * 0.05, rel_y * 0.05, noise_seed);
-                angle += (noise_val - 0.5) * PI * 0.3 * noise_amount; // Distort angle
-                let noisy_dist = dist
-                    * (1.0
-                        + (spatial_noise(rel_x * 0.02, rel_y * 0.02, noise_seed + 10.0) - 0.5)
-                            * 0.4
-                            * noise_amount);
-
-                // --- Map angle and distance to color ---
-                let angle_norm = (angle / (2.0 * PI) + 10.0) % 1.0; // Normalize angle 0-1 (add offset to avoid seam issues)
-                let dist_norm = (noisy_dist / max_dist).clamp(0.0, 1.0);
-
-                // Blend colors based on angle
-                let color_mix;
-                if angle_norm < 0.333 {
-                    color_mix = lerp_color(color1, color2, angle_norm / 0.333);
-                } else if angle_norm < 0.666 {
-                    color_mix = lerp_color(color2, color3, (angle_norm - 0.333) / 0.333);
-                } else {
-                    color_mix = lerp_color(color3, color1, (angle_norm - 0.666) / 0.333);
-                }
+    for_each_masked_row(display_image, spans, |y, x_start, width, pixels| {
+        let rel_y = y as f32 - center_y;
+        let rel_x_start = x_start as f32 - center_x;
+        let n = width;
+        // Per-row noise, computed in SIMD batches instead of per pixel
+        spatial_noise_row(rel_x_start, 0.05, rel_y * 0.05, noise_seed, &mut angle_noise_row[..n]);
+        spatial_noise_row(
+            rel_x_start,
+            0.02,
+            rel_y * 0.02,
+            noise_seed + 10.0,
+            &mut dist_noise_row[..n],
+        );
+        for (x, px) in pixels {
+            let i = x - x_start;
+            // --- Calculate coordinates relative to center ---
+            let rel_x = x as f32 - center_x;
+            let dist = (rel_x * rel_x + rel_y * rel_y).sqrt();
+            let mut angle = rel_y.atan2(rel_x); // Current angle
 
-                // Fade to black at edges and based on distance noise
-                let fade = (1.0 - dist_norm.powf(1.5)) * (1.0 - (noise_val * 0.5 * noise_amount)); // Fade near edge and by noise
-                let base_color = lerp_color(BLACK, color_mix, fade.clamp(0.0, 1.0));
+            // --- Add swirl based on distance and time/phase ---
+            // Rotate more closer to the center, speed based on intensity
+            // Rotate more near center (the center may sit off-middle when panned)
+            let rotation_factor = (1.0 - (dist / max_dist)).max(0.0).powi(2);
+            let rotation_amount = animation_phase + spin_phase * rotation_factor;
+            angle += rotation_amount;
 
-                // Add brightness boost and random sparkles
-                let mut final_color = lerp_color(base_color, WHITE, brightness_boost * fade); // Boost brightness towards center
-                if rng.gen::<f32>() < 0.005 * intensity {
-                    // Sparse sparkles
-                    final_color = lerp_color(final_color, WHITE, 0.8);
-                }
+            // --- Add noise distortion to angle and distance ---
+            let noise_val = angle_noise_row[i];
+            angle += (noise_val - 0.5) * PI * 0.3 * noise_amount; // Distort angle
+            let noisy_dist =
+                dist * (1.0 + (dist_noise_row[i] - 0.5) * 0.4 * noise_amount);
+
+            // --- Map angle and distance to color ---
+            let angle_norm = (angle / (2.0 * PI) + 10.0) % 1.0; // Normalize angle 0-1 (add offset to avoid seam issues)
+            let dist_norm = (noisy_dist / max_dist).clamp(0.0, 1.0);
+
+            // Blend colors based on angle
+            let color_mix;
+            if angle_norm < 0.333 {
+                color_mix = lerp_color(color1, color2, angle_norm / 0.333);
+            } else if angle_norm < 0.666 {
+                color_mix = lerp_color(color2, color3, (angle_norm - 0.333) / 0.333);
+            } else {
+                color_mix = lerp_color(color3, color1, (angle_norm - 0.666) / 0.333);
+            }
+
+            // Fade to black at edges and based on distance noise
+            let fade = (1.0 - dist_norm.powf(1.5)) * (1.0 - (noise_val * 0.5 * noise_amount)); // Fade near edge and by noise
+            let base_color = lerp_color(BLACK, color_mix, fade.clamp(0.0, 1.0));
 
-                display_image.put_pixel(x as u32, y as u32, final_color);
+            // Add brightness boost and random sparkles
+            let mut final_color = lerp_color(base_color, WHITE, brightness_boost * fade); // Boost brightness towards center
+            if rng.gen::<f32>() < sparkles * intensity {
+                // Sparse sparkles
+                final_color = lerp_color(final_color, WHITE, 0.8);
             }
+
+            px.copy_from_slice(&final_color.0);
         }
-    }
+    });
 }
 
 // --- High Visualization - "Electric Static Field / Jagged Lines" 
 fn draw_high_visuals(
     display_image: &mut RgbImage,
-    mask_image: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    spans: &MaskSpans,
     bbox_rect: Rect,
     intensity: f32,
     frame_count: u64,
     _animation_phase: f32,
+    motion: &mut Motion,
+    [lines, line_speed]: [f32; 2],
     rng: &mut impl Rng,
 ) {
     let color_low = Rgb([0, 0, 50]);
@@ -169,52 +798,1080 @@ fn draw_high_visuals(
     let line_intensity = intensity.sqrt();
     let noise_seed1 = frame_count as f32 * 0.1;
     let noise_seed2 = frame_count as f32 * -0.07;
+    let num_lines = lines + line_intensity * 20.0;
+    let line_phase = motion.advance(frame_count, 0.15 * line_speed);
+    let jag_noise_scale = 0.1;
+    let row_len = bbox_rect.width() as usize;
+    let mut noise_row1 = vec![0.0f32; row_len];
+    let mut noise_row2 = vec![0.0f32; row_len];
+    let mut jag_row = vec![0.0f32; row_len];
+
+    for_each_masked_row(display_image, spans, |y, x_start, width, pixels| {
+        let n = width;
+        let yf = y as f32;
+        let xs = x_start as f32;
+        spatial_noise_row(xs, 0.08, yf * 0.08, noise_seed1, &mut noise_row1[..n]);
+        spatial_noise_row(xs, 0.03, yf * 0.03, noise_seed2, &mut noise_row2[..n]);
+        spatial_noise_row(
+            xs,
+            jag_noise_scale,
+            yf * jag_noise_scale,
+            noise_seed1 + 10.0,
+            &mut jag_row[..n],
+        );
+        // Line position only depends on the row
+        let line_y_norm = (yf / bbox_rect.height() as f32 * num_lines + line_phase) % 1.0; // Use bbox height
+        for (x, px) in pixels {
+            let i = x - x_start;
+            let combined_noise = (noise_row1[i] * 0.6 + noise_row2[i] * 0.4
+                + rng.gen::<f32>() * 0.2
+                - 0.1)
+                .clamp(0.0, 1.0);
+            let field_color =
+                lerp_color(color_low, color_mid, combined_noise * field_intensity * 1.5);
+
+            let line_threshold = 0.05 + jag_row[i] * 0.1;
+            let line_brightness = if line_y_norm < line_threshold {
+                (1.0 - line_y_norm / line_threshold) * line_intensity.powi(2) * 1.5
+            } else {
+                0.0
+            };
+
+            let final_color =
+                lerp_color(field_color, color_high, line_brightness.clamp(0.0, 1.0));
+            px.copy_from_slice(&final_color.0);
+        }
+    });
+}
+
+// --- Hue Visualization - "Melody Tint", meant to be driven by the Pitch channel
+// Intensity picks the hue; the original luminance is kept so the subject stays readable
+fn draw_hue_visuals(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    spans: &MaskSpans,
+    bbox_rect: Rect,
+    (center_x, center_y): (f32, f32),
+    intensity: f32,
+    frame_count: u64,
+    animation_phase: f32,
+    [hue_range, saturation, spread]: [f32; 3],
+) {
+    let max_dist = (bbox_rect.width().max(bbox_rect.height()) as f32 * 0.7).max(1.0);
+    let base_hue = intensity.clamp(0.0, 1.0) * hue_range; // Low notes red -> high notes violet
+    let swirl = animation_phase + frame_count as f32 * 0.02;
+
+    for_each_masked_row(display_image, spans, |y, _, _, pixels| {
+        let rel_y = y as f32 - center_y;
+        for (x, px) in pixels {
+            let rel_x = x as f32 - center_x;
+            let dist_norm = ((rel_x * rel_x + rel_y * rel_y).sqrt() / max_dist).min(1.0);
+            let angle = rel_y.atan2(rel_x) + swirl;
+            // Small hue spread around the pitch color so the region isn't flat
+            let hue = base_hue + angle.sin() * spread * (1.0 - dist_norm);
+
+            let src = source_image.get_pixel(x as u32, y as u32);
+            let luma =
+                (0.2126 * src[0] as f32 + 0.7152 * src[1] as f32 + 0.0722 * src[2] as f32)
+                    / 255.0;
+            let rgb: Srgb = Hsv::new(hue, saturation, (0.25 + luma * 0.75).min(1.0)).into_color();
+            let rgb = rgb.into_format::<u8>();
+            px.copy_from_slice(&[rgb.red, rgb.green, rgb.blue]);
+        }
+    });
+}
+
+// --- Extrude Visualization - "Chunky 3D", the mask as a bevelled heightmap
+// Height comes from the distance to the mask edge; intensity deepens the bevel and the
+// light circles the object over time
+fn draw_extrude_visuals(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    mask_image: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    spans: &MaskSpans,
+    bbox_rect: Rect,
+    intensity: f32,
+    frame_count: u64,
+    animation_phase: f32,
+    motion: &mut Motion,
+    [depth_scale, light_speed]: [f32; 2],
+) {
+    let (w, h) = display_image.dimensions();
+    let x0 = bbox_rect.left().clamp(0, w as i32) as u32;
+    let y0 = bbox_rect.top().clamp(0, h as i32) as u32;
+    let x1 = bbox_rect.right().clamp(0, w as i32) as u32;
+    let y1 = bbox_rect.bottom().clamp(0, h as i32) as u32;
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
+    let (cw, ch) = (x1 - x0, y1 - y0);
 
-    for y in bbox_rect.top()..bbox_rect.bottom() {
-        for x in bbox_rect.left()..bbox_rect.right() {
-            if is_inside_mask(x, y, mask_image) {
-                let noise_val1 = spatial_noise(x as f32 * 0.08, y as f32 * 0.08, noise_seed1);
-                let noise_val2 = spatial_noise(x as f32 * 0.03, y as f32 * 0.03, noise_seed2);
-                let combined_noise = (noise_val1 * 0.6 + noise_val2 * 0.4 + rng.gen::<f32>() * 0.2
-                    - 0.1)
-                    .clamp(0.0, 1.0);
-                let field_color =
-                    lerp_color(color_low, color_mid, combined_noise * field_intensity * 1.5);
-
-                let num_lines = 8.0 + line_intensity * 20.0;
-                let line_phase = frame_count as f32 * 0.15;
-                let line_y_norm =
-                    (y as f32 / bbox_rect.height() as f32 * num_lines + line_phase) % 1.0; // Use bbox height
-                let jag_noise_scale = 0.1;
-                let jag_noise = spatial_noise(
-                    x as f32 * jag_noise_scale,
-                    y as f32 * jag_noise_scale,
-                    noise_seed1 + 10.0,
-                );
-                let line_threshold = 0.05 + jag_noise * 0.1;
-                let line_brightness = if line_y_norm < line_threshold {
-                    (1.0 - line_y_norm / line_threshold) * line_intensity.powi(2) * 1.5
-                } else {
-                    0.0
+    // Distance transform measures distance to the nearest *foreground* pixel, so the
+    // outside of the mask is the foreground here
+    let outside = GrayImage::from_fn(cw, ch, |x, y| {
+        let inside = mask_image.get_pixel(x0 + x, y0 + y)[0] > MASK_THRESHOLD;
+        Luma([if inside { 0 } else { 255 }])
+    });
+    let distance = distance_transform(&outside, Norm::L1);
+
+    let intensity = intensity.clamp(0.0, 1.0);
+    let bevel = (4.0 + intensity * cw.min(ch) as f32 * 0.15).max(1.0);
+    let depth = (2.0 + intensity * 10.0) * depth_scale;
+    let height_at = |x: u32, y: u32| {
+        let t = (distance.get_pixel(x, y)[0] as f32 / bevel).min(1.0);
+        t * t * (3.0 - 2.0 * t) // Smoothstep: rounded edge, flat top
+    };
+    let light_angle = motion.advance(frame_count, 0.03 * light_speed) + animation_phase;
+    let light = {
+        let (lx, ly, lz) = (light_angle.cos(), light_angle.sin(), 0.8);
+        let len = (lx * lx + ly * ly + lz * lz).sqrt();
+        (lx / len, ly / len, lz / len)
+    };
+
+    for_each_masked_row(display_image, spans, |y, _, _, pixels| {
+        let cy = y as u32 - y0;
+        for (x, px) in pixels {
+            let cx = x as u32 - x0;
+            let dhdx = height_at((cx + 1).min(cw - 1), cy) - height_at(cx.saturating_sub(1), cy);
+            let dhdy = height_at(cx, (cy + 1).min(ch - 1)) - height_at(cx, cy.saturating_sub(1));
+            let (nx, ny, nz) = (-dhdx * depth, -dhdy * depth, 1.0);
+            let len = (nx * nx + ny * ny + nz * nz).sqrt();
+            let lambert = ((nx * light.0 + ny * light.1 + nz * light.2) / len).max(0.0);
+            // Specular-ish glint on faces turned straight at the light
+            let glint = lambert.powi(24) * intensity;
+            let shade = 0.25 + lambert * 0.9;
+            let src = source_image.get_pixel(x as u32, y as u32);
+            let lit = Rgb([
+                (src[0] as f32 * shade).min(255.0) as u8,
+                (src[1] as f32 * shade).min(255.0) as u8,
+                (src[2] as f32 * shade).min(255.0) as u8,
+            ]);
+            px.copy_from_slice(&lerp_color(lit, WHITE, glint).0);
+        }
+    });
+}
+
+// --- Masked compositing at an arbitrary placement ---
+// Copies the masked pixels of `bbox_rect` from source to display, rotated by `angle` (and
+// optionally mirrored) about the bbox center, which lands on `center`. Inverse-maps each
+// destination pixel, so rotated copies have no holes.
+pub(crate) fn composite_masked(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    mask_image: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    bbox_rect: Rect,
+    center: (f32, f32),
+    angle: f32,
+    mirror: bool,
+) {
+    let (w, h) = display_image.dimensions();
+    let src_cx = bbox_rect.left() as f32 + bbox_rect.width() as f32 / 2.0;
+    let src_cy = bbox_rect.top() as f32 + bbox_rect.height() as f32 / 2.0;
+    let (sin, cos) = angle.sin_cos();
+    // Destination footprint: the rotated bbox's bounding box
+    let half_w = bbox_rect.width() as f32 / 2.0;
+    let half_h = bbox_rect.height() as f32 / 2.0;
+    let reach_x = half_w * cos.abs() + half_h * sin.abs();
+    let reach_y = half_w * sin.abs() + half_h * cos.abs();
+    let x0 = (center.0 - reach_x).floor().clamp(0.0, w as f32) as u32;
+    let x1 = (center.0 + reach_x).ceil().clamp(0.0, w as f32) as u32;
+    let y0 = (center.1 - reach_y).floor().clamp(0.0, h as f32) as u32;
+    let y1 = (center.1 + reach_y).ceil().clamp(0.0, h as f32) as u32;
+    let flip = if mirror { -1.0 } else { 1.0 };
+
+    for y in y0..y1 {
+        let dy = y as f32 - center.1;
+        for x in x0..x1 {
+            let dx = x as f32 - center.0;
+            // Inverse rotation, then undo the mirror
+            let sx = src_cx + (dx * cos + dy * sin) * flip;
+            let sy = src_cy - dx * sin + dy * cos;
+            if sx < 0.0 || sy < 0.0 || sx >= w as f32 || sy >= h as f32 {
+                continue;
+            }
+            let alpha = mask_image.get_pixel(sx as u32, sy as u32)[0];
+            if alpha == 0 {
+                continue;
+            }
+            let sample = sample_bilinear(source_image, sx, sy);
+            let px = display_image.get_pixel_mut(x, y);
+            let under = px.0;
+            px.0 = sample.0;
+            mix_alpha(&mut px.0, under, alpha);
+        }
+    }
+}
+
+// --- Clones Visualization - "Echo Clones", copies of the object spun around the frame center
+// Intensity adds clones and pushes them further out; odd clones are mirrored
+fn draw_clone_visuals(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    mask_image: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    bbox_rect: Rect,
+    intensity: f32,
+    animation_phase: f32,
+    [max_clones, spread_scale]: [f32; 2],
+) {
+    let (w, h) = display_image.dimensions();
+    let intensity = intensity.clamp(0.0, 1.0);
+    let count = 1 + (intensity * (max_clones.round() - 1.0).max(0.0)).round() as usize;
+    let spread = (0.6 + intensity * 0.6) * spread_scale;
+    let frame_center = (w as f32 / 2.0, h as f32 / 2.0);
+    let obj_center = (
+        bbox_rect.left() as f32 + bbox_rect.width() as f32 / 2.0,
+        bbox_rect.top() as f32 + bbox_rect.height() as f32 / 2.0,
+    );
+    // Objects sitting on the frame center still need somewhere to orbit
+    let min_radius = w.min(h) as f32 * 0.25;
+    let (mut ox, mut oy) = (obj_center.0 - frame_center.0, obj_center.1 - frame_center.1);
+    let radius = (ox * ox + oy * oy).sqrt();
+    if radius < min_radius {
+        let a = animation_phase;
+        (ox, oy) = (a.cos() * min_radius, a.sin() * min_radius);
+    }
+    let drift = animation_phase * 0.2;
+
+    for k in 1..=count {
+        let angle = 2.0 * PI * k as f32 / (count + 1) as f32 + drift;
+        let (sin, cos) = angle.sin_cos();
+        let center = (
+            frame_center.0 + (ox * cos - oy * sin) * spread,
+            frame_center.1 + (ox * sin + oy * cos) * spread,
+        );
+        composite_masked(
+            display_image,
+            source_image,
+            mask_image,
+            bbox_rect,
+            center,
+            angle,
+            k % 2 == 1,
+        );
+    }
+}
+
+// --- Shatter burst: set by the Beat channel rising through a threshold, then decaying ---
+const SHATTER_BEAT_THRESHOLD: f32 = 0.5;
+const SHATTER_DECAY: f32 = 0.8; // Share of the burst left after each frame
+
+#[derive(Debug, Default)]
+struct ShatterBurst {
+    level: f32,
+    last_beat: f32,
+    last_frame: Option<u64>,
+}
+
+impl ShatterBurst {
+    // Repeats of a frame neither decay the burst nor retrigger it
+    fn step(&mut self, beat: f32, frame_count: u64) -> f32 {
+        let frames = self.last_frame.map_or(1, |f| frame_count.saturating_sub(f));
+        self.last_frame = Some(frame_count);
+        self.level *= SHATTER_DECAY.powi(frames.min(64) as i32);
+        if beat >= SHATTER_BEAT_THRESHOLD && self.last_beat < SHATTER_BEAT_THRESHOLD {
+            self.level = 1.0;
+        }
+        self.last_beat = beat;
+        self.level
+    }
+}
+
+// --- Shatter Visualization - "Stained Glass", Voronoi cells filled with a color picked from
+// the frame at each seed. Cells fly outward on each hit of the Beat channel, farther the louder
+// the slot's own channel, and settle back as the burst decays
+fn draw_voronoi_visuals(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    mask_image: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    spans: &MaskSpans,
+    bbox_rect: Rect,
+    intensity: f32,
+    burst: f32,
+    [cells, shatter_scale]: [f32; 2],
+    rng: &mut impl Rng,
+) {
+    const LEAD: Rgb<u8> = Rgb([15, 12, 10]);
+    let num_cells = cells.round().max(1.0) as usize;
+    let (w, h) = display_image.dimensions();
+    let x0 = bbox_rect.left().clamp(0, w as i32) as u32;
+    let y0 = bbox_rect.top().clamp(0, h as i32) as u32;
+    let x1 = bbox_rect.right().clamp(0, w as i32) as u32;
+    let y1 = bbox_rect.bottom().clamp(0, h as i32) as u32;
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
+    let (cw, ch) = ((x1 - x0) as usize, (y1 - y0) as usize);
+    let inside = |x: u32, y: u32| mask_image.get_pixel(x, y)[0] > MASK_THRESHOLD;
+
+    // Fresh seeds every frame, rejection-sampled into the mask
+    let mut seeds: Vec<(f32, f32)> = Vec::with_capacity(num_cells);
+    for _ in 0..num_cells * 4 {
+        if seeds.len() == num_cells {
+            break;
+        }
+        let x = rng.gen_range(x0..x1);
+        let y = rng.gen_range(y0..y1);
+        if inside(x, y) {
+            seeds.push((x as f32, y as f32));
+        }
+    }
+    if seeds.is_empty() {
+        return;
+    }
+    let colors: Vec<Rgb<u8>> = seeds
+        .iter()
+        .map(|&(x, y)| *source_image.get_pixel(x as u32, y as u32))
+        .collect();
+
+    // Nearest seed per masked pixel (usize::MAX = outside)
+    let mut labels = vec![usize::MAX; cw * ch];
+    for y in y0..y1 {
+        for x in x0..x1 {
+            if !inside(x, y) {
+                continue;
+            }
+            let (fx, fy) = (x as f32, y as f32);
+            let mut best = (f32::MAX, 0);
+            for (i, &(sx, sy)) in seeds.iter().enumerate() {
+                let d = (fx - sx) * (fx - sx) + (fy - sy) * (fy - sy);
+                if d < best.0 {
+                    best = (d, i);
+                }
+            }
+            labels[(y - y0) as usize * cw + (x - x0) as usize] = best.1;
+        }
+    }
+
+    // Shards move away from the object center; whole-pixel offsets keep each shard solid
+    let shatter = burst * (0.5 + 0.5 * intensity.clamp(0.0, 1.0)) * 0.5 * shatter_scale;
+    let center = (
+        bbox_rect.left() as f32 + bbox_rect.width() as f32 / 2.0,
+        bbox_rect.top() as f32 + bbox_rect.height() as f32 / 2.0,
+    );
+    let offsets: Vec<(i32, i32)> = seeds
+        .iter()
+        .map(|&(sx, sy)| {
+            (
+                ((sx - center.0) * shatter).round() as i32,
+                ((sy - center.1) * shatter).round() as i32,
+            )
+        })
+        .collect();
+
+    // Leading first, so gaps opened by the shatter read as dark cracks
+    for_each_masked_row(display_image, spans, |_, _, _, pixels| {
+        for (_, px) in pixels {
+            px.copy_from_slice(&LEAD.0);
+        }
+    });
+    for cy in 0..ch {
+        for cx in 0..cw {
+            let label = labels[cy * cw + cx];
+            if label == usize::MAX {
+                continue;
+            }
+            // Cell borders stay dark for the lead-came look
+            let border = (cx + 1 < cw && labels[cy * cw + cx + 1] != label)
+                || (cy + 1 < ch && labels[(cy + 1) * cw + cx] != label);
+            if border {
+                continue;
+            }
+            let (dx, dy) = offsets[label];
+            let tx = x0 as i32 + cx as i32 + dx;
+            let ty = y0 as i32 + cy as i32 + dy;
+            if tx < 0 || ty < 0 || tx >= w as i32 || ty >= h as i32 {
+                continue;
+            }
+            display_image.put_pixel(tx as u32, ty as u32, colors[label]);
+        }
+    }
+}
+
+// --- Gray-Scott reaction-diffusion on a coarse grid covering the bbox ---
+const RD_MAX_GRID: usize = 128; // Longest grid side; one cell spans several pixels
+
+#[derive(Debug)]
+struct ReactionDiffusion {
+    width: usize,
+    height: usize,
+    u: Vec<f32>,
+    v: Vec<f32>,
+}
+
+impl ReactionDiffusion {
+    fn new(width: usize, height: usize, rng: &mut impl Rng) -> Self {
+        let mut sim = Self {
+            width,
+            height,
+            u: vec![1.0; width * height],
+            v: vec![0.0; width * height],
+        };
+        for _ in 0..12 {
+            sim.seed_spot(rng);
+        }
+        sim
+    }
+
+    fn grid_size(bbox_rect: Rect) -> (usize, usize) {
+        let (bw, bh) = (bbox_rect.width() as f32, bbox_rect.height() as f32);
+        let scale = (RD_MAX_GRID as f32 / bw.max(bh)).min(1.0);
+        (((bw * scale) as usize).max(8), ((bh * scale) as usize).max(8))
+    }
+
+    // Nearest-neighbour resample, so a jittering bbox doesn't wipe the pattern
+    fn resized(&self, width: usize, height: usize) -> Self {
+        let mut u = Vec::with_capacity(width * height);
+        let mut v = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let sy = y * self.height / height;
+            for x in 0..width {
+                let i = sy * self.width + x * self.width / width;
+                u.push(self.u[i]);
+                v.push(self.v[i]);
+            }
+        }
+        Self { width, height, u, v }
+    }
+
+    fn seed_spot(&mut self, rng: &mut impl Rng) {
+        let cx = rng.gen_range(0..self.width);
+        let cy = rng.gen_range(0..self.height);
+        for y in cy.saturating_sub(2)..(cy + 3).min(self.height) {
+            for x in cx.saturating_sub(2)..(cx + 3).min(self.width) {
+                self.u[y * self.width + x] = 0.5;
+                self.v[y * self.width + x] = 0.25;
+            }
+        }
+    }
+
+    fn step(&mut self, feed: f32, kill: f32) {
+        // Classic 5-point-stencil rates; stable at a unit time step
+        const DU: f32 = 0.16;
+        const DV: f32 = 0.08;
+        let (w, h) = (self.width, self.height);
+        let mut next_u = self.u.clone();
+        let mut next_v = self.v.clone();
+        for y in 0..h {
+            let (up, down) = ((y + h - 1) % h * w, (y + 1) % h * w);
+            for x in 0..w {
+                let (left, right) = ((x + w - 1) % w, (x + 1) % w);
+                let i = y * w + x;
+                let lap = |f: &[f32]| {
+                    f[y * w + left] + f[y * w + right] + f[up + x] + f[down + x] - 4.0 * f[i]
                 };
+                let (u, v) = (self.u[i], self.v[i]);
+                let uvv = u * v * v;
+                next_u[i] = (u + DU * lap(&self.u) - uvv + feed * (1.0 - u)).clamp(0.0, 1.0);
+                next_v[i] = (v + DV * lap(&self.v) + uvv - (feed + kill) * v).clamp(0.0, 1.0);
+            }
+        }
+        self.u = next_u;
+        self.v = next_v;
+    }
+}
+
+// --- Turing Visualization - "Reaction-Diffusion", an evolving Gray-Scott pattern per slot
+// Intensity morphs feed/kill from spotty "mitosis" toward branching "coral" and speeds it up
+fn draw_reaction_visuals(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    spans: &MaskSpans,
+    bbox_rect: Rect,
+    intensity: f32,
+    state: &mut SlotState,
+    [steps_per_frame, reseed_rate]: [f32; 2],
+    rng: &mut impl Rng,
+) {
+    let (gw, gh) = ReactionDiffusion::grid_size(bbox_rect);
+    let sim = match state.reaction.take() {
+        Some(sim) if (sim.width, sim.height) == (gw, gh) => sim,
+        Some(sim) => sim.resized(gw, gh),
+        None => ReactionDiffusion::new(gw, gh, rng),
+    };
+    let sim = state.reaction.insert(sim);
+
+    let intensity = intensity.clamp(0.0, 1.0);
+    let feed = 0.0367 + (0.0545 - 0.0367) * intensity;
+    let kill = 0.0649 + (0.062 - 0.0649) * intensity;
+    let steps = (steps_per_frame * (1.0 + intensity)) as usize;
+    for _ in 0..steps {
+        sim.step(feed, kill);
+    }
+    // Keep the pattern from dying out on quiet passages
+    if rng.gen::<f32>() < reseed_rate + intensity * 0.2 {
+        sim.seed_spot(rng);
+    }
+
+    let color_low = Rgb([10, 10, 35]);
+    let color_high = lerp_color(Rgb([255, 120, 40]), Rgb([120, 255, 220]), intensity);
+    let sx = gw as f32 / bbox_rect.width() as f32;
+    let sy = gh as f32 / bbox_rect.height() as f32;
+    for_each_masked_row(display_image, spans, |y, _, _, pixels| {
+        let gy = (((y as i32 - bbox_rect.top()) as f32 * sy) as usize).min(gh - 1);
+        for (x, px) in pixels {
+            let gx = (((x as i32 - bbox_rect.left()) as f32 * sx) as usize).min(gw - 1);
+            let t = (sim.v[gy * gw + gx] * 3.0).clamp(0.0, 1.0);
+            let pattern = lerp_color(color_low, color_high, t);
+            // A little of the underlying frame keeps the object's shading visible
+            let src = source_image.get_pixel(x as u32, y as u32);
+            px.copy_from_slice(&lerp_color(pattern, *src, 0.15).0);
+        }
+    });
+}
+
+// --- Life-like cellular automaton on a coarse grid, confined to the mask ---
+const CA_BEAT_THRESHOLD: f32 = 0.6; // Intensity rising through this counts as a beat
+// (birth, survival) neighbour-count bitmasks, calmest first: Life, HighLife, Day & Night
+const CA_RULES: [(u16, u16); 3] = [
+    (1 << 3, (1 << 2) | (1 << 3)),
+    ((1 << 3) | (1 << 6), (1 << 2) | (1 << 3)),
+    (
+        (1 << 3) | (1 << 6) | (1 << 7) | (1 << 8),
+        (1 << 3) | (1 << 4) | (1 << 6) | (1 << 7) | (1 << 8),
+    ),
+];
+
+#[derive(Debug)]
+struct CellAutomaton {
+    width: usize,
+    height: usize,
+    alive: Vec<bool>,
+    trail: Vec<f32>, // Fades after a cell dies
+    last_intensity: f32,
+}
+
+impl CellAutomaton {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            alive: vec![false; width * height],
+            trail: vec![0.0; width * height],
+            last_intensity: 0.0,
+        }
+    }
+
+    // Nearest-neighbour resample like ReactionDiffusion::resized, so a resizing bbox keeps
+    // the colony instead of restarting it
+    fn resized(&self, width: usize, height: usize) -> Self {
+        let mut alive = Vec::with_capacity(width * height);
+        let mut trail = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let sy = y * self.height / height;
+            for x in 0..width {
+                let i = sy * self.width + x * self.width / width;
+                alive.push(self.alive[i]);
+                trail.push(self.trail[i]);
+            }
+        }
+        Self {
+            width,
+            height,
+            alive,
+            trail,
+            last_intensity: self.last_intensity,
+        }
+    }
+
+    // Cells inside the mask with an outside neighbour come alive at random
+    fn seed_edge(&mut self, inside: &[bool], rng: &mut impl Rng) {
+        let (w, h) = (self.width, self.height);
+        for y in 0..h {
+            for x in 0..w {
+                let i = y * w + x;
+                if !inside[i] {
+                    continue;
+                }
+                let edge = x == 0
+                    || y == 0
+                    || x + 1 == w
+                    || y + 1 == h
+                    || !inside[i - 1]
+                    || !inside[i + 1]
+                    || !inside[i - w]
+                    || !inside[i + w];
+                if edge && rng.gen::<f32>() < 0.5 {
+                    self.alive[i] = true;
+                }
+            }
+        }
+    }
+
+    fn step(&mut self, inside: &[bool], (birth, survive): (u16, u16), trail_fade: f32) {
+        let (w, h) = (self.width, self.height);
+        let mut next = vec![false; w * h];
+        for y in 0..h {
+            for x in 0..w {
+                let i = y * w + x;
+                if !inside[i] {
+                    continue;
+                }
+                let mut neighbours = 0;
+                for ny in y.saturating_sub(1)..(y + 2).min(h) {
+                    for nx in x.saturating_sub(1)..(x + 2).min(w) {
+                        if (nx, ny) != (x, y) && self.alive[ny * w + nx] {
+                            neighbours += 1;
+                        }
+                    }
+                }
+                let rule = if self.alive[i] { survive } else { birth };
+                next[i] = rule & (1 << neighbours) != 0;
+            }
+        }
+        self.alive = next;
+        for (trail, &alive) in self.trail.iter_mut().zip(&self.alive) {
+            *trail = if alive { 1.0 } else { *trail * trail_fade };
+        }
+    }
+}
+
+// --- Life Visualization - "Cellular Automaton", reseeded from the mask outline on every beat
+// Intensity picks a livelier rule set; the grid is resampled when the bbox changes size
+fn draw_automaton_visuals(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    mask_image: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    spans: &MaskSpans,
+    bbox_rect: Rect,
+    intensity: f32,
+    state: &mut SlotState,
+    [cell_size, trail_fade]: [f32; 2],
+    rng: &mut impl Rng,
+) {
+    let cell_px = cell_size.round().max(1.0) as u32;
+    let gw = (bbox_rect.width() / cell_px).max(1) as usize;
+    let gh = (bbox_rect.height() / cell_px).max(1) as usize;
+    let ca = match state.automaton.take() {
+        Some(ca) if (ca.width, ca.height) == (gw, gh) => ca,
+        Some(ca) => ca.resized(gw, gh),
+        None => CellAutomaton::new(gw, gh),
+    };
+    let ca = state.automaton.insert(ca);
+
+    let (w, h) = mask_image.dimensions();
+    let inside: Vec<bool> = (0..gw * gh)
+        .map(|i| {
+            let px = bbox_rect.left() + ((i % gw) as u32 * cell_px + cell_px / 2) as i32;
+            let py = bbox_rect.top() + ((i / gw) as u32 * cell_px + cell_px / 2) as i32;
+            px >= 0
+                && py >= 0
+                && (px as u32) < w
+                && (py as u32) < h
+                && mask_image.get_pixel(px as u32, py as u32)[0] > MASK_THRESHOLD
+        })
+        .collect();
+
+    let intensity = intensity.clamp(0.0, 1.0);
+    let beat = intensity >= CA_BEAT_THRESHOLD && ca.last_intensity < CA_BEAT_THRESHOLD;
+    ca.last_intensity = intensity;
+    if beat || !ca.alive.iter().any(|&a| a) {
+        ca.seed_edge(&inside, rng);
+    }
+    let rule = CA_RULES[((intensity * CA_RULES.len() as f32) as usize).min(CA_RULES.len() - 1)];
+    ca.step(&inside, rule, trail_fade);
+
+    let color_cell = lerp_color(Rgb([80, 255, 120]), WHITE, intensity * 0.5);
+    let color_trail = Rgb([0, 90, 140]);
+    for_each_masked_row(display_image, spans, |y, _, _, pixels| {
+        let gy = (((y as i32 - bbox_rect.top()) as u32 / cell_px) as usize).min(gh - 1);
+        for (x, px) in pixels {
+            let gx = (((x as i32 - bbox_rect.left()) as u32 / cell_px) as usize).min(gw - 1);
+            let i = gy * gw + gx;
+            let dimmed = lerp_color(*source_image.get_pixel(x as u32, y as u32), BLACK, 0.7);
+            let color = if ca.alive[i] {
+                color_cell
+            } else {
+                lerp_color(dimmed, color_trail, ca.trail[i])
+            };
+            px.copy_from_slice(&color.0);
+        }
+    });
+}
+
+// --- Warp Visualization - displaces the original pixels instead of synthesizing color
+fn draw_warp_visuals(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    spans: &MaskSpans,
+    bbox_rect: Rect,
+    (center_x, center_y): (f32, f32),
+    intensity: f32,
+    frame_count: u64,
+    animation_phase: f32,
+    motion: &mut Motion,
+    mode: WarpMode,
+    [amount, speed]: [f32; 2],
+) {
+    let radius = (bbox_rect.width().max(bbox_rect.height()) as f32 * 0.5).max(1.0);
+    let amplitude = intensity.clamp(0.0, 1.0) * amount;
+    let max_shift = radius * 0.25 * amplitude; // Pixels of displacement at full intensity
+    let time = motion.advance(frame_count, 0.1 * speed) + animation_phase;
+
+    for_each_masked_row(display_image, spans, |y, _, _, pixels| {
+        let rel_y = y as f32 - center_y;
+        for (x, px) in pixels {
+            let rel_x = x as f32 - center_x;
+            let (src_x, src_y) = match mode {
+                WarpMode::Bulge => {
+                    // Pull samples toward the center -> magnified middle
+                    let r = ((rel_x * rel_x + rel_y * rel_y).sqrt() / radius).min(1.0);
+                    let factor = (1.0 - amplitude * 0.6 * (1.0 - r).powi(2)).max(0.0);
+                    (center_x + rel_x * factor, center_y + rel_y * factor)
+                }
+                WarpMode::Ripple => {
+                    let dist = (rel_x * rel_x + rel_y * rel_y).sqrt().max(1e-3);
+                    let wave = (dist * 0.15 - time * 2.0).sin() * max_shift;
+                    (x as f32 + rel_x / dist * wave, y as f32 + rel_y / dist * wave)
+                }
+                WarpMode::Shear => {
+                    let dx = (y as f32 * 0.05 + time).sin() * max_shift;
+                    let dy = (x as f32 * 0.04 - time * 0.7).cos() * max_shift * 0.3;
+                    (x as f32 + dx, y as f32 + dy)
+                }
+            };
+            let final_color = sample_bilinear(source_image, src_x, src_y);
+            px.copy_from_slice(&final_color.0);
+        }
+    });
+}
+
+// --- Heat Shimmer - displaces the band just outside the mask (dilated mask minus mask)
+// with noise that scrolls upward, like hot air rising off the object
+pub fn draw_heat_shimmer(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    mask_image: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    bbox_rect: Rect,
+    settings: &ShimmerSettings,
+    strength: f32,
+    frame_count: u64,
+) {
+    if strength <= 0.0 || source_image.dimensions() != display_image.dimensions() {
+        return;
+    }
+    let (w, h) = display_image.dimensions();
+    let band = settings.width.max(1);
+    // The band reaches higher above the object than to the sides, since heat rises
+    let x0 = (bbox_rect.left() - band as i32).clamp(0, w as i32) as u32;
+    let x1 = (bbox_rect.right() + band as i32).clamp(0, w as i32) as u32;
+    let y0 = (bbox_rect.top() - 2 * band as i32).clamp(0, h as i32) as u32;
+    let y1 = (bbox_rect.bottom() + band as i32).clamp(0, h as i32) as u32;
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
+    // Alpha of the object itself: the shimmer shows through in proportion to 1 - alpha
+    let crop = GrayImage::from_fn(x1 - x0, y1 - y0, |x, y| *mask_image.get_pixel(x0 + x, y0 + y));
+    let grown = dilate(&crop, Norm::LInf, band);
+    let amplitude = settings.amount * strength;
+    let time = frame_count as f32 * 0.15;
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (cx, cy) = (x - x0, y - y0);
+            let alpha = crop.get_pixel(cx, cy)[0];
+            if grown.get_pixel(cx, cy)[0] == 0 || alpha == 255 {
+                continue;
+            }
+            let (fx, fy) = (x as f32, y as f32);
+            let wobble = ((fy + time * 8.0) * 0.12 + (fx * 0.05).sin() * 2.0).sin();
+            let rise =
+                spatial_noise((fx * 0.02).floor(), ((fy + time * 6.0) * 0.05).floor(), 3.0);
+            let dx = wobble * amplitude;
+            let dy = (rise - 0.5) * amplitude;
+            let sample = sample_bilinear(source_image, fx + dx, fy + dy);
+            let px = display_image.get_pixel_mut(x, y);
+            mix_alpha(&mut px.0, sample.0, alpha);
+        }
+    }
+}
+
+// --- Kick Ring - a band expanding from the object's center, `progress` 0 at the hit to 1 once
+// it has faded out. Screened once per pixel, so it brightens without clipping the video
+pub fn draw_kick_ring(display_image: &mut RgbImage, bbox_rect: Rect, progress: f32) {
+    if !(0.0..1.0).contains(&progress) {
+        return;
+    }
+    let (w, h) = display_image.dimensions();
+    let half = bbox_rect.width().max(bbox_rect.height()) as f32 * 0.5;
+    let (cx, cy) = effect_center(bbox_rect, 0.0);
+    let radius = half * (0.6 + 0.8 * progress);
+    let thickness = (half * 0.08).max(2.0) * (1.0 - 0.5 * progress);
+    let strength = (1.0 - progress) * 255.0;
+    let reach = radius + thickness;
+    let x0 = (cx - reach).floor().clamp(0.0, w as f32) as u32;
+    let x1 = (cx + reach).ceil().clamp(0.0, w as f32) as u32;
+    let y0 = (cy - reach).floor().clamp(0.0, h as f32) as u32;
+    let y1 = (cy + reach).ceil().clamp(0.0, h as f32) as u32;
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+            let edge = 1.0 - ((dx * dx + dy * dy).sqrt() - radius).abs() / thickness;
+            if edge <= 0.0 {
+                continue;
+            }
+            let alpha = (edge.min(1.0) * strength) as u8;
+            let px = display_image.get_pixel_mut(x, y);
+            BlendMode::Screen.composite(&mut px.0, KICK_RING_COLOR.0, alpha);
+        }
+    }
+}
+
+// --- Hat Sparkle - white glints scattered over the mask, thinning out as `progress` goes 0 to 1
+pub fn draw_hat_sparkle(
+    display_image: &mut RgbImage,
+    mask_image: &GrayImage,
+    bbox_rect: Rect,
+    progress: f32,
+    rng: &mut impl Rng,
+) {
+    if !(0.0..1.0).contains(&progress) {
+        return;
+    }
+    let (w, h) = display_image.dimensions();
+    let (w, h) = (w.min(mask_image.width()), h.min(mask_image.height()));
+    let x0 = bbox_rect.left().clamp(0, w as i32);
+    let x1 = (bbox_rect.right() + 1).clamp(0, w as i32);
+    let y0 = bbox_rect.top().clamp(0, h as i32);
+    let y1 = (bbox_rect.bottom() + 1).clamp(0, h as i32);
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
+    let count = (HAT_SPARKLES as f32 * (1.0 - progress)).ceil() as usize;
+    let strength = (1.0 - progress) * 255.0;
+    let mut placed = 0;
+    // Rejection sampling; thin masks may get fewer glints, never a stalled frame
+    for _ in 0..count * 4 {
+        if placed == count {
+            break;
+        }
+        let (x, y) = (rng.gen_range(x0..x1), rng.gen_range(y0..y1));
+        if mask_image.get_pixel(x as u32, y as u32)[0] < MASK_THRESHOLD {
+            continue;
+        }
+        placed += 1;
+        for (dx, dy, share) in GLINT {
+            let (gx, gy) = (x + dx, y + dy);
+            if gx < 0 || gy < 0 || gx >= w as i32 || gy >= h as i32 {
+                continue;
+            }
+            let px = display_image.get_pixel_mut(gx as u32, gy as u32);
+            BlendMode::Screen.composite(&mut px.0, WHITE.0, (strength * share) as u8);
+        }
+    }
+}
+
+// --- Outline strokes ---
+// Stamps overlap along the contour, so they are rasterized into a colour/coverage buffer over
+// the contour's bounds first and each covered pixel is blended into the frame exactly once
+const OUTLINE_MARGIN: i32 = 8; // Farthest a stamp reaches off the contour (drift + radius)
+
+struct OutlineCanvas {
+    x0: i32,
+    y0: i32,
+    width: i32,
+    height: i32,
+    pixels: Vec<Option<[u8; 3]>>, // Colour of the last stamp covering each pixel
+}
+
+impl OutlineCanvas {
+    // Bounds of the contour, grown by the margin and clipped to the frame
+    fn new(contour: &[(f32, f32)], (frame_w, frame_h): (u32, u32)) -> Self {
+        let (mut min_x, mut min_y) = (f32::MAX, f32::MAX);
+        let (mut max_x, mut max_y) = (f32::MIN, f32::MIN);
+        for &(x, y) in contour {
+            (min_x, min_y) = (min_x.min(x), min_y.min(y));
+            (max_x, max_y) = (max_x.max(x), max_y.max(y));
+        }
+        let x0 = (min_x.floor() as i32 - OUTLINE_MARGIN).max(0);
+        let y0 = (min_y.floor() as i32 - OUTLINE_MARGIN).max(0);
+        let x1 = (max_x.ceil() as i32 + OUTLINE_MARGIN + 1).min(frame_w as i32);
+        let y1 = (max_y.ceil() as i32 + OUTLINE_MARGIN + 1).min(frame_h as i32);
+        let (width, height) = ((x1 - x0).max(0), (y1 - y0).max(0));
+        Self {
+            x0,
+            y0,
+            width,
+            height,
+            pixels: vec![None; (width * height) as usize],
+        }
+    }
+
+    #[inline]
+    fn stamp(&mut self, x: f32, y: f32, radius: i32, color: Rgb<u8>) {
+        let (cx, cy) = (x.round() as i32 - self.x0, y.round() as i32 - self.y0);
+        for py in (cy - radius).max(0)..=(cy + radius).min(self.height - 1) {
+            for px in (cx - radius).max(0)..=(cx + radius).min(self.width - 1) {
+                let (dx, dy) = (px - cx, py - cy);
+                if dx * dx + dy * dy <= radius * radius {
+                    self.pixels[(py * self.width + px) as usize] = Some(color.0);
+                }
+            }
+        }
+    }
+
+    fn blend_into(&self, display_image: &mut RgbImage, blend: BlendMode) {
+        if self.width == 0 {
+            return;
+        }
+        for (row, colors) in self.pixels.chunks_exact(self.width as usize).enumerate() {
+            let y = (self.y0 + row as i32) as u32;
+            for (col, color) in colors.iter().enumerate() {
+                if let Some(color) = color {
+                    let pixel = display_image.get_pixel_mut(self.x0 as u32 + col as u32, y);
+                    blend.composite(&mut pixel.0, *color, 255);
+                }
+            }
+        }
+    }
+}
 
-                let final_color =
-                    lerp_color(field_color, color_high, line_brightness.clamp(0.0, 1.0));
-                display_image.put_pixel(x as u32, y as u32, final_color);
+// Calls `f(x, y, arc_length, (tx, ty))` roughly every pixel along the closed contour
+fn walk_contour(contour: &[(f32, f32)], mut f: impl FnMut(f32, f32, f32, (f32, f32))) {
+    let mut arc = 0.0;
+    for (i, &(x0, y0)) in contour.iter().enumerate() {
+        let (x1, y1) = contour[(i + 1) % contour.len()];
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len < 1e-3 {
+            continue;
+        }
+        let tangent = (dx / len, dy / len);
+        let mut t = 0.0;
+        while t < len {
+            f(x0 + tangent.0 * t, y0 + tangent.1 * t, arc + t, tangent);
+            t += 1.0;
+        }
+        arc += len;
+    }
+}
+
+// Contour in frame pixels; the pattern travels at a speed set by `intensity` (the High band)
+pub fn draw_outline(
+    display_image: &mut RgbImage,
+    contour: &[(f32, f32)],
+    style: OutlineStyle,
+    blend: BlendMode,
+    intensity: f32,
+    frame_count: u64,
+    motion: &mut Motion,
+) {
+    if style == OutlineStyle::None || contour.len() < 3 {
+        return;
+    }
+    let intensity = intensity.clamp(0.0, 1.0);
+    let speed = 0.5 + intensity * 6.0; // Pixels per frame
+    let offset = motion.advance(frame_count, speed);
+    let accent = lerp_color(Rgb([0, 200, 255]), Rgb([255, 60, 200]), intensity);
+    let mut canvas = OutlineCanvas::new(contour, display_image.dimensions());
+    match style {
+        OutlineStyle::None => {}
+        OutlineStyle::Ants => walk_contour(contour, |x, y, arc, _| {
+            let on = ((arc + offset) / 6.0) as i64 % 2 == 0;
+            canvas.stamp(x, y, 1, if on { WHITE } else { BLACK });
+        }),
+        OutlineStyle::Dashes => walk_contour(contour, |x, y, arc, _| {
+            if ((arc + offset) % 24.0) < 14.0 {
+                canvas.stamp(x, y, 2, accent);
             }
+        }),
+        OutlineStyle::Arrows => {
+            const SPACING: f32 = 40.0;
+            walk_contour(contour, |x, y, arc, (tx, ty)| {
+                let along = (arc + offset) % SPACING;
+                // Chevron: two short legs trailing back from the tip at +-45 degrees
+                if along < 1.0 {
+                    for side in [-1.0f32, 1.0] {
+                        let (lx, ly) = (-tx - side * ty, -ty + side * tx);
+                        for s in 0..8 {
+                            let d = s as f32 * 0.707;
+                            canvas.stamp(x + lx * d, y + ly * d, 1, accent);
+                        }
+                    }
+                }
+            })
+        }
+        OutlineStyle::Particles => {
+            const SPACING: f32 = 18.0;
+            walk_contour(contour, |x, y, arc, (tx, ty)| {
+                if ((arc + offset) % SPACING) < 1.0 {
+                    // Particles drift off the outline a little as they travel
+                    let drift = (spatial_noise(arc, frame_count as f32 * 0.05, 7.0) - 0.5) * 6.0;
+                    let (px, py) = (x - ty * drift, y + tx * drift);
+                    canvas.stamp(px, py, 2 + (intensity * 2.0) as i32, accent);
+                    canvas.stamp(px, py, 1, WHITE);
+                }
+            })
         }
     }
+    canvas.blend_into(display_image, blend);
+}
+
+// --- Fallback mask for detections that came without one ---
+// A rounded rectangle filling the bbox whose edge fades out over `feather` of the short side.
+// Styles threshold the mask, so the fade is an ordered dither rather than grey levels.
+pub fn bbox_falloff_mask(size: (u32, u32), bbox_rect: Rect, feather: f32) -> GrayImage {
+    const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+    let mut mask = GrayImage::new(size.0, size.1);
+    let (w, h) = (bbox_rect.width() as f32, bbox_rect.height() as f32);
+    let short = w.min(h);
+    if short < 1.0 {
+        return mask;
+    }
+    let radius = short * 0.25;
+    let feather = (short * feather.clamp(0.01, 0.5)).max(1.0);
+    let (cx, cy) = (bbox_rect.left() as f32 + w * 0.5, bbox_rect.top() as f32 + h * 0.5);
+    let (inner_x, inner_y) = (w * 0.5 - radius, h * 0.5 - radius);
+    for_each_bbox_row_mut(&mut mask, bbox_rect, |y, x_start, row| {
+        let qy = (y as f32 + 0.5 - cy).abs() - inner_y;
+        for (i, m) in row.iter_mut().enumerate() {
+            let x = x_start + i;
+            let qx = (x as f32 + 0.5 - cx).abs() - inner_x;
+            // Signed distance to the rounded rectangle, negative inside
+            let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+            let dist = outside + qx.max(qy).min(0.0) - radius;
+            let t = (-dist / feather).clamp(0.0, 1.0);
+            let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0;
+            *m = if t > threshold { 255 } else { 0 };
+        }
+    });
+    mask
+}
+
+// Every bbox row of a single-channel image, clipped to the frame
+fn for_each_bbox_row_mut(
+    image: &mut GrayImage,
+    bbox_rect: Rect,
+    mut f: impl FnMut(usize, usize, &mut [u8]),
+) {
+    let (w, h) = image.dimensions();
+    let x0 = bbox_rect.left().clamp(0, w as i32) as usize;
+    let x1 = bbox_rect.right().clamp(0, w as i32) as usize;
+    let y0 = bbox_rect.top().clamp(0, h as i32) as usize;
+    let y1 = bbox_rect.bottom().clamp(0, h as i32) as usize;
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
+    let w = w as usize;
+    let raw: &mut [u8] = image;
+    for y in y0..y1 {
+        f(y, x0, &mut raw[y * w + x0..y * w + x1]);
+    }
 }
 
 // --- Main Public Function --- RESTORED DISPATCHER ---
 pub fn draw_visuals(
     display_image: &mut RgbImage,
+    source_image: &RgbImage,
     mask_image: &ImageBuffer<Luma<u8>, Vec<u8>>,
     bbox_rect: Rect,
-    slot_index: usize,
+    feathered: bool, // Mask is a matte: fade effects across its soft edge
+    style: VisualStyle,
+    params: &StyleParams,
+    quality: VisualQuality,
     intensity: f32,
     frame_count: u64,
     animation_phase: f32,
+    state: &mut SlotState,
     rng: &mut impl Rng,
 ) {
     // Dimension check (important!)
@@ -226,37 +1883,396 @@ pub fn draw_visuals(
     if bbox_rect.width() == 0 || bbox_rect.height() == 0 {
         return;
     } // Skip empty rects
-
-    match slot_index {
-        0 => draw_bass_visuals(
+    let spans = MaskSpans::new(mask_image, bbox_rect, feathered);
+    // Clones paint copies elsewhere (blended by their own alpha) and leave the object alone
+    let before = (spans.soft && !style.draws_outside_mask())
+        .then(|| snapshot_spans(display_image, &spans));
+    // The reduced path copies back only the mask's spans, which would drop copies drawn elsewhere
+    let reduced = quality != VisualQuality::Full
+        && !style.draws_outside_mask()
+        && source_image.dimensions() == display_image.dimensions()
+        && draw_visuals_reduced(
+            display_image,
+            source_image,
+            mask_image,
+            &spans,
+            feathered,
+            style,
+            params,
+            quality.factor(),
+            intensity,
+            frame_count,
+            animation_phase,
+            state,
+            rng,
+        );
+    if !reduced {
+        draw_style(
             display_image,
+            source_image,
             mask_image,
+            &spans,
             bbox_rect,
+            style,
+            params,
             intensity,
             frame_count,
             animation_phase,
+            state,
+            rng,
+        );
+    }
+    if let Some(before) = before {
+        fade_by_alpha(display_image, &before, mask_image, &spans);
+    }
+}
+
+// One style over every span pixel at full strength; draw_visuals fades the soft edges
+fn draw_style(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    mask_image: &GrayImage,
+    spans: &MaskSpans,
+    bbox_rect: Rect,
+    style: VisualStyle,
+    params: &StyleParams,
+    intensity: f32,
+    frame_count: u64,
+    animation_phase: f32,
+    state: &mut SlotState,
+    rng: &mut impl Rng,
+) {
+    let center = effect_center(bbox_rect, state.pan);
+    let p = |i: usize| params.get(style, i);
+
+    match style {
+        VisualStyle::Pulse => draw_bass_visuals(
+            display_image,
+            spans,
+            bbox_rect,
+            center,
+            intensity,
+            frame_count,
+            animation_phase,
+            &mut state.motion,
+            [p(0), p(1), p(2)],
             rng,
         ),
-        1 => draw_mid_visuals(
+        VisualStyle::Vortex => draw_mid_visuals(
             display_image,
-            mask_image,
+            spans,
             bbox_rect,
+            center,
             intensity,
             frame_count,
             animation_phase,
+            &mut state.motion,
+            [p(0), p(1), p(2)],
             rng,
         ),
-        2 => draw_high_visuals(
+        VisualStyle::Static => draw_high_visuals(
             display_image,
-            mask_image,
+            spans,
             bbox_rect,
             intensity,
             frame_count,
             animation_phase,
+            &mut state.motion,
+            [p(0), p(1)],
             rng,
         ),
-        _ => {
-            log::warn!("Visuals: Invalid slot {}", slot_index);
+        VisualStyle::Hue => {
+            if source_image.dimensions() != display_image.dimensions() {
+                return;
+            }
+            draw_hue_visuals(
+                display_image,
+                source_image,
+                spans,
+                bbox_rect,
+                center,
+                intensity,
+                frame_count,
+                animation_phase,
+                [p(0), p(1), p(2)],
+            )
+        }
+        VisualStyle::Extrude => {
+            if source_image.dimensions() != display_image.dimensions() {
+                return;
+            }
+            draw_extrude_visuals(
+                display_image,
+                source_image,
+                mask_image,
+                spans,
+                bbox_rect,
+                intensity,
+                frame_count,
+                animation_phase,
+                &mut state.motion,
+                [p(0), p(1)],
+            )
+        }
+        VisualStyle::Clones => {
+            if source_image.dimensions() != display_image.dimensions() {
+                return;
+            }
+            draw_clone_visuals(
+                display_image,
+                source_image,
+                mask_image,
+                bbox_rect,
+                intensity,
+                animation_phase,
+                [p(0), p(1)],
+            )
+        }
+        VisualStyle::Shatter => {
+            if source_image.dimensions() != display_image.dimensions() {
+                return;
+            }
+            let burst = state.shatter.step(state.beat, frame_count);
+            draw_voronoi_visuals(
+                display_image,
+                source_image,
+                mask_image,
+                spans,
+                bbox_rect,
+                intensity,
+                burst,
+                [p(0), p(1)],
+                rng,
+            )
+        }
+        VisualStyle::Turing => {
+            if source_image.dimensions() != display_image.dimensions() {
+                return;
+            }
+            draw_reaction_visuals(
+                display_image,
+                source_image,
+                spans,
+                bbox_rect,
+                intensity,
+                state,
+                [p(0), p(1)],
+                rng,
+            )
         }
+        VisualStyle::Life => {
+            if source_image.dimensions() != display_image.dimensions() {
+                return;
+            }
+            draw_automaton_visuals(
+                display_image,
+                source_image,
+                mask_image,
+                spans,
+                bbox_rect,
+                intensity,
+                state,
+                [p(0), p(1)],
+                rng,
+            )
+        }
+        VisualStyle::Bulge | VisualStyle::Ripple | VisualStyle::Shear => {
+            let mode = match style {
+                VisualStyle::Bulge => WarpMode::Bulge,
+                VisualStyle::Ripple => WarpMode::Ripple,
+                _ => WarpMode::Shear,
+            };
+            if source_image.dimensions() != display_image.dimensions() {
+                return;
+            }
+            draw_warp_visuals(
+                display_image,
+                source_image,
+                spans,
+                bbox_rect,
+                center,
+                intensity,
+                frame_count,
+                animation_phase,
+                &mut state.motion,
+                mode,
+                [p(0), p(1)],
+            )
+        }
+    }
+}
+
+// --- Effect chain: a style drawn as a layer over whatever the object already shows ---
+// Renders like a base style, then combines with the pixels underneath by `blend`, faded
+// toward them by 1 - opacity. Styles that stamp outside the mask are blended wherever they
+// changed the frame.
+pub fn draw_blended(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    mask_image: &GrayImage,
+    bbox_rect: Rect,
+    feathered: bool,
+    style: VisualStyle,
+    params: &StyleParams,
+    (blend, opacity): (BlendMode, f32),
+    quality: VisualQuality,
+    intensity: f32,
+    frame_count: u64,
+    animation_phase: f32,
+    state: &mut SlotState,
+    rng: &mut impl Rng,
+) {
+    if mask_image.dimensions() != display_image.dimensions()
+        || bbox_rect.width() == 0
+        || bbox_rect.height() == 0
+    {
+        return;
+    }
+    let spans = MaskSpans::new(mask_image, bbox_rect, feathered);
+    let whole_frame = style.draws_outside_mask();
+    let under = if whole_frame {
+        display_image.clone()
+    } else {
+        snapshot_spans(display_image, &spans)
+    };
+    draw_visuals(
+        display_image,
+        source_image,
+        mask_image,
+        bbox_rect,
+        feathered,
+        style,
+        params,
+        quality,
+        intensity,
+        frame_count,
+        animation_phase,
+        state,
+        rng,
+    );
+    let alpha = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+    if blend == BlendMode::Normal && alpha == 255 {
+        return;
+    }
+    if whole_frame {
+        for (px, under) in display_image.pixels_mut().zip(under.pixels()) {
+            if px != under {
+                let layer = px.0;
+                *px = *under;
+                blend.composite(&mut px.0, layer, alpha);
+            }
+        }
+        return;
+    }
+    for_each_masked_row(display_image, &spans, |y, _, _, pixels| {
+        for (x, px) in pixels {
+            let layer = [px[0], px[1], px[2]];
+            px.copy_from_slice(&under.get_pixel((x - spans.x0) as u32, (y - spans.y0) as u32).0);
+            blend.composite(px, layer, alpha);
+        }
+    });
+}
+
+// Renders `style` on a 1/factor copy of the clipped bbox, then writes it back bilinearly
+// upsampled wherever the full-resolution mask is set. False = bbox too small, nothing drawn.
+fn draw_visuals_reduced(
+    display_image: &mut RgbImage,
+    source_image: &RgbImage,
+    mask_image: &GrayImage,
+    spans: &MaskSpans,
+    feathered: bool,
+    style: VisualStyle,
+    params: &StyleParams,
+    factor: u32,
+    intensity: f32,
+    frame_count: u64,
+    animation_phase: f32,
+    state: &mut SlotState,
+    rng: &mut impl Rng,
+) -> bool {
+    let (x0, y0) = (spans.x0, spans.y0);
+    let (w, h) = ((spans.x1 - x0) as u32, spans.rows.len() as u32);
+    let (small_w, small_h) = (w.div_ceil(factor), h.div_ceil(factor));
+    let min_side = if state.reduced {
+        MIN_REDUCED_SIDE
+    } else {
+        MIN_REDUCED_SIDE + REDUCED_HYSTERESIS
+    };
+    state.reduced = small_w.min(small_h) >= min_side;
+    if !state.reduced {
+        return false;
+    }
+    let (cx, cy) = (x0 as u32, y0 as u32);
+    let shrink = |image: &RgbImage| {
+        let crop = imageops::crop_imm(image, cx, cy, w, h).to_image();
+        imageops::resize(&crop, small_w, small_h, FilterType::Triangle)
+    };
+    let mut small_display = shrink(display_image);
+    let small_source = shrink(source_image);
+    let mask_crop = imageops::crop_imm(mask_image, cx, cy, w, h).to_image();
+    let small_mask = imageops::resize(&mask_crop, small_w, small_h, FilterType::Triangle);
+    let small_rect = Rect::at(0, 0).of_size(small_w, small_h);
+    draw_style(
+        &mut small_display,
+        &small_source,
+        &small_mask,
+        &MaskSpans::new(&small_mask, small_rect, feathered),
+        small_rect,
+        style,
+        params,
+        intensity,
+        frame_count,
+        animation_phase,
+        state,
+        rng,
+    );
+
+    // Pixel centers map back as (x + 0.5) / factor - 0.5
+    let scale = 1.0 / factor as f32;
+    for_each_masked_row(display_image, spans, |y, _, _, pixels| {
+        let sy = ((y - y0) as f32 + 0.5) * scale - 0.5;
+        for (x, px) in pixels {
+            let sx = ((x - x0) as f32 + 0.5) * scale - 0.5;
+            px.copy_from_slice(&sample_bilinear(&small_display, sx, sy).0);
+        }
+    });
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    #[test]
+    fn clones_draw_outside_the_mask_at_reduced_quality() {
+        let (w, h) = (96, 96);
+        let source = RgbImage::from_fn(w, h, |x, y| Rgb([(x * 2) as u8, (y * 2) as u8, 200]));
+        let bbox = Rect::at(56, 56).of_size(24, 24);
+        let mask = GrayImage::from_fn(w, h, |x, y| {
+            let inside = (56..80).contains(&x) && (56..80).contains(&y);
+            Luma([if inside { 255 } else { 0 }])
+        });
+        let style = VisualStyle::Clones;
+        let mut display = RgbImage::new(w, h);
+        draw_visuals(
+            &mut display,
+            &source,
+            &mask,
+            bbox,
+            false,
+            style,
+            &StyleParams::defaults(style),
+            VisualQuality::Half,
+            1.0,
+            10,
+            0.0,
+            &mut SlotState::default(),
+            &mut SmallRng::seed_from_u64(7),
+        );
+        let outside_changed = display
+            .enumerate_pixels()
+            .any(|(x, y, p)| mask.get_pixel(x, y)[0] == 0 && p.0 != [0, 0, 0]);
+        assert!(outside_changed);
     }
 }
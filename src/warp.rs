@@ -0,0 +1,179 @@
+// src/warp.rs
+use image::{Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+const MIN_DET: f32 = 1e-6; // Below this the corners are collinear and the warp is skipped
+
+// --- Corner pin: keystone correction of the final output onto a physical surface ---
+// Corners are normalized output positions of the frame's top-left, top-right, bottom-right and
+// bottom-left corners. Outside the pinned quad the output is black.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CornerPin {
+    pub enabled: bool,
+    pub corners: [(f32, f32); 4],
+}
+
+impl Default for CornerPin {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            corners: Self::IDENTITY,
+        }
+    }
+}
+
+impl CornerPin {
+    pub const IDENTITY: [(f32, f32); 4] = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+
+    pub fn is_active(&self) -> bool {
+        self.enabled && self.corners != Self::IDENTITY
+    }
+
+    // Normalized source position -> normalized output position
+    pub fn homography(&self) -> Option<Homography> {
+        Homography::square_to_quad(&self.corners)
+    }
+
+    // Output position -> source position, e.g. to map a click on the warped preview back to the
+    // camera frame. Identity while the pin is inactive.
+    pub fn to_source(&self, point: (f32, f32)) -> (f32, f32) {
+        if !self.is_active() {
+            return point;
+        }
+        self.homography()
+            .and_then(|h| h.invert())
+            .and_then(|inv| inv.map(point))
+            .unwrap_or(point)
+    }
+
+    pub fn to_output(&self, point: (f32, f32)) -> (f32, f32) {
+        if !self.is_active() {
+            return point;
+        }
+        self.homography()
+            .and_then(|h| h.map(point))
+            .unwrap_or(point)
+    }
+}
+
+// Row-major 3x3 projective transform on (x, y, 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Homography([f32; 9]);
+
+impl Homography {
+    // Unit square (0,0),(1,0),(1,1),(0,1) onto the quad, after Heckbert's closed form
+    fn square_to_quad(q: &[(f32, f32); 4]) -> Option<Self> {
+        let [(x0, y0), (x1, y1), (x2, y2), (x3, y3)] = *q;
+        let sx = x0 - x1 + x2 - x3;
+        let sy = y0 - y1 + y2 - y3;
+        let (g, h) = if sx.abs() < MIN_DET && sy.abs() < MIN_DET {
+            (0.0, 0.0) // Parallelogram: affine
+        } else {
+            let (dx1, dx2, dy1, dy2) = (x1 - x2, x3 - x2, y1 - y2, y3 - y2);
+            let det = dx1 * dy2 - dx2 * dy1;
+            if det.abs() < MIN_DET {
+                return None;
+            }
+            ((sx * dy2 - dx2 * sy) / det, (dx1 * sy - sx * dy1) / det)
+        };
+        let m = Self([
+            x1 - x0 + g * x1,
+            x3 - x0 + h * x3,
+            x0,
+            y1 - y0 + g * y1,
+            y3 - y0 + h * y3,
+            y0,
+            g,
+            h,
+            1.0,
+        ]);
+        (m.determinant().abs() >= MIN_DET).then_some(m)
+    }
+
+    fn determinant(&self) -> f32 {
+        let m = &self.0;
+        m[0] * (m[4] * m[8] - m[5] * m[7]) - m[1] * (m[3] * m[8] - m[5] * m[6])
+            + m[2] * (m[3] * m[7] - m[4] * m[6])
+    }
+
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det.abs() < MIN_DET {
+            return None;
+        }
+        let m = &self.0;
+        let adjugate = [
+            m[4] * m[8] - m[5] * m[7],
+            m[2] * m[7] - m[1] * m[8],
+            m[1] * m[5] - m[2] * m[4],
+            m[5] * m[6] - m[3] * m[8],
+            m[0] * m[8] - m[2] * m[6],
+            m[2] * m[3] - m[0] * m[5],
+            m[3] * m[7] - m[4] * m[6],
+            m[1] * m[6] - m[0] * m[7],
+            m[0] * m[4] - m[1] * m[3],
+        ];
+        Some(Self(adjugate.map(|v| v / det)))
+    }
+
+    // None for points mapped to (or behind) infinity
+    pub fn map(&self, (x, y): (f32, f32)) -> Option<(f32, f32)> {
+        let m = &self.0;
+        let w = m[6] * x + m[7] * y + m[8];
+        if w.abs() < MIN_DET {
+            return None;
+        }
+        Some((
+            (m[0] * x + m[1] * y + m[2]) / w,
+            (m[3] * x + m[4] * y + m[5]) / w,
+        ))
+    }
+}
+
+// --- Applies the pin in place; the scratch frame is reused between calls ---
+#[derive(Default)]
+pub struct CornerPinStage {
+    scratch: RgbImage,
+}
+
+impl CornerPinStage {
+    pub fn apply(&mut self, image: &mut RgbImage, pin: &CornerPin) {
+        if !pin.is_active() {
+            return;
+        }
+        let Some(inverse) = pin.homography().and_then(|h| h.invert()) else {
+            return;
+        };
+        let (w, h) = image.dimensions();
+        if self.scratch.dimensions() != (w, h) {
+            self.scratch = RgbImage::new(w, h);
+        }
+        let (fw, fh) = (w as f32, h as f32);
+        for (px, py, out) in self.scratch.enumerate_pixels_mut() {
+            let output = ((px as f32 + 0.5) / fw, (py as f32 + 0.5) / fh);
+            *out = match inverse.map(output) {
+                Some((u, v)) if (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v) => {
+                    sample_bilinear(image, u * fw - 0.5, v * fh - 0.5)
+                }
+                _ => Rgb([0, 0, 0]),
+            };
+        }
+        std::mem::swap(image, &mut self.scratch);
+    }
+}
+
+fn sample_bilinear(image: &RgbImage, x: f32, y: f32) -> Rgb<u8> {
+    let (w, h) = image.dimensions();
+    let x = x.clamp(0.0, (w - 1) as f32);
+    let y = y.clamp(0.0, (h - 1) as f32);
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+    let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+    let [a, b, c, d] =
+        [(x0, y0), (x1, y0), (x0, y1), (x1, y1)].map(|(x, y)| image.get_pixel(x, y).0);
+    Rgb(std::array::from_fn(|i| {
+        let top = a[i] as f32 + (b[i] as f32 - a[i] as f32) * tx;
+        let bottom = c[i] as f32 + (d[i] as f32 - c[i] as f32) * tx;
+        (top + (bottom - top) * ty).round() as u8
+    }))
+}
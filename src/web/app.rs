@@ -0,0 +1,222 @@
+// src/web/app.rs
+// Browser version of the show: camera, microphone bands and segmentation feed the same
+// visuals::draw_visuals as the native app, with one object per band. Everything runs on the
+// browser's event loop; the model and media requests are awaited in spawned futures.
+use eframe::egui;
+use image::RgbImage;
+use rand::{rngs::SmallRng, SeedableRng};
+use std::f32::consts::PI;
+
+use crate::camera::WebCamera;
+use crate::live_audio::WebAudioInput;
+use crate::segmentation::{WebDetection, WebSegmenter, DEFAULT_MODEL_URL, MAX_TRACKS};
+use crate::visuals::{draw_visuals, SlotState, StyleParams, VisualQuality, VisualStyle};
+
+const CAMERA_WIDTH: u32 = 640;
+const CAMERA_HEIGHT: u32 = 480;
+const BAND_NAMES: [&str; MAX_TRACKS] = ["Bass", "Mid", "High"];
+const PHASE_STEP: f32 = 0.05; // Animation phase advance per frame
+
+struct Slot {
+    style: VisualStyle,
+    params: StyleParams,
+    state: SlotState,
+}
+
+pub struct WebApp {
+    camera: Option<WebCamera>,
+    audio: Option<WebAudioInput>,
+    segmenter: WebSegmenter,
+    model_url: String,
+    detections: Vec<WebDetection>,
+    slots: Vec<Slot>,
+    quality: VisualQuality,
+    intensities: [f32; MAX_TRACKS],
+    frame_count: u64,
+    animation_phase: f32,
+    rng: SmallRng,
+    texture: Option<egui::TextureHandle>,
+    start_error: Option<String>,
+}
+
+impl WebApp {
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        let slots = [VisualStyle::Pulse, VisualStyle::Vortex, VisualStyle::Static]
+            .into_iter()
+            .map(|style| Slot {
+                style,
+                params: StyleParams::defaults(style),
+                state: SlotState::default(),
+            })
+            .collect();
+        Self {
+            camera: None,
+            audio: None,
+            segmenter: WebSegmenter::load(DEFAULT_MODEL_URL),
+            model_url: DEFAULT_MODEL_URL.to_string(),
+            detections: Vec::new(),
+            slots,
+            quality: VisualQuality::Half, // Browsers have less headroom than the native build
+            intensities: [0.0; MAX_TRACKS],
+            frame_count: 0,
+            animation_phase: 0.0,
+            rng: SmallRng::from_os_rng(),
+            texture: None,
+            start_error: None,
+        }
+    }
+
+    // Camera and microphone need a click: browsers refuse both without a user gesture
+    fn start_media(&mut self) {
+        self.start_error = None;
+        match WebCamera::open(CAMERA_WIDTH, CAMERA_HEIGHT) {
+            Ok(camera) => self.camera = Some(camera),
+            Err(e) => self.start_error = Some(format!("Camera: {:#}", e)),
+        }
+        match WebAudioInput::open() {
+            Ok(audio) => self.audio = Some(audio),
+            Err(e) => self.start_error = Some(format!("Microphone: {:#}", e)),
+        }
+    }
+
+    fn render(&mut self, frame: &RgbImage) -> RgbImage {
+        let mut display = frame.clone();
+        for (detection, (slot, intensity)) in self
+            .detections
+            .iter()
+            .zip(self.slots.iter_mut().zip(self.intensities))
+        {
+            draw_visuals(
+                &mut display,
+                frame,
+                &detection.mask,
+                detection.bbox,
+                slot.style,
+                &slot.params,
+                self.quality,
+                intensity,
+                self.frame_count,
+                self.animation_phase,
+                &mut slot.state,
+                &mut self.rng,
+            );
+        }
+        display
+    }
+
+    fn draw_controls(&mut self, ui: &mut egui::Ui) {
+        ui.heading("SAM_CAM_BAM");
+        if self.camera.is_none() && ui.button("Start camera & microphone").clicked() {
+            self.start_media();
+        }
+        if let Some(e) = &self.start_error {
+            ui.colored_label(egui::Color32::RED, e);
+        }
+        for e in [
+            self.camera.as_ref().and_then(WebCamera::error),
+            self.audio.as_ref().and_then(WebAudioInput::error),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            ui.colored_label(egui::Color32::RED, e);
+        }
+
+        ui.separator();
+        ui.label("Model");
+        match (self.segmenter.provider(), self.segmenter.error()) {
+            (_, Some(e)) => ui.colored_label(egui::Color32::RED, e),
+            (Some(provider), None) => ui.label(format!("Running on {}", provider)),
+            (None, None) => ui.label("Loading…"),
+        };
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.model_url);
+            if ui.button("Load").clicked() {
+                self.segmenter = WebSegmenter::load(&self.model_url);
+                self.detections.clear();
+            }
+        });
+
+        ui.separator();
+        egui::ComboBox::from_label("Quality")
+            .selected_text(self.quality.label())
+            .show_ui(ui, |ui| {
+                for quality in VisualQuality::ALL {
+                    ui.selectable_value(&mut self.quality, quality, quality.label());
+                }
+            });
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            ui.separator();
+            let confidence = self.detections.get(i).map(|d| d.confidence);
+            ui.horizontal(|ui| {
+                ui.label(BAND_NAMES[i]);
+                ui.add(egui::ProgressBar::new(self.intensities[i]).desired_width(80.0));
+                if let Some(confidence) = confidence {
+                    ui.weak(format!("{:.0}%", confidence * 100.0));
+                }
+            });
+            egui::ComboBox::from_id_salt(("web_style", i))
+                .selected_text(slot.style.label())
+                .show_ui(ui, |ui| {
+                    for style in VisualStyle::ALL {
+                        if ui
+                            .selectable_value(&mut slot.style, style, style.label())
+                            .changed()
+                        {
+                            slot.params = slot.params.for_style(style);
+                            slot.state = SlotState::default();
+                        }
+                    }
+                });
+            for (value, spec) in slot.params.values_mut().iter_mut().zip(slot.style.params()) {
+                ui.add(
+                    egui::Slider::new(value, spec.min..=spec.max)
+                        .text(spec.name)
+                        .suffix(spec.unit),
+                );
+            }
+        }
+    }
+}
+
+impl eframe::App for WebApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if let Some(audio) = self.audio.as_mut() {
+            self.intensities = audio.intensities();
+        }
+        let frame = self.camera.as_ref().and_then(WebCamera::frame);
+        if let Some(frame) = frame {
+            self.segmenter.submit(&frame);
+            if let Some(detections) = self.segmenter.take_detections() {
+                self.detections = detections;
+            }
+            let display = self.render(&frame);
+            let size = [display.width() as usize, display.height() as usize];
+            let image = egui::ColorImage::from_rgb(size, display.as_raw());
+            match self.texture.as_mut() {
+                Some(texture) => texture.set(image, egui::TextureOptions::LINEAR),
+                None => {
+                    self.texture =
+                        Some(ctx.load_texture("web_output", image, egui::TextureOptions::LINEAR))
+                }
+            }
+            self.frame_count += 1;
+            self.animation_phase = (self.animation_phase + PHASE_STEP) % (2.0 * PI);
+        }
+
+        egui::SidePanel::left("web_controls")
+            .resizable(true)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| self.draw_controls(ui));
+            });
+        egui::CentralPanel::default().show(ctx, |ui| match &self.texture {
+            Some(texture) => {
+                ui.add(egui::Image::new(texture).shrink_to_fit());
+            }
+            None => {
+                ui.centered_and_justified(|ui| ui.label("Waiting for the camera…"));
+            }
+        });
+        ctx.request_repaint();
+    }
+}
@@ -0,0 +1,167 @@
+// src/web/camera.rs
+// Browser camera for the wasm32 build: getUserMedia feeds a hidden <video>, and each frame is
+// read back through an offscreen 2D canvas. Replaces the nokhwa capture thread of the native app.
+use anyhow::{anyhow, Result};
+use image::RgbImage;
+use js_sys::{Object, Reflect};
+use log::{error, info};
+use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, HtmlVideoElement, MediaStream,
+    MediaStreamConstraints, MediaStreamTrack,
+};
+
+const HAVE_CURRENT_DATA: u16 = 2; // HTMLMediaElement.readyState once a frame can be drawn
+
+pub fn js_error(value: JsValue) -> anyhow::Error {
+    anyhow!(value
+        .as_string()
+        .or_else(|| Reflect::get(&value, &"message".into()).ok()?.as_string())
+        .unwrap_or_else(|| format!("{:?}", value)))
+}
+
+// --- Pending getUserMedia result, filled in by the spawned promise ---
+#[derive(Default)]
+struct CameraStatus {
+    stream: Option<MediaStream>,
+    error: Option<String>,
+}
+
+pub struct WebCamera {
+    video: HtmlVideoElement,
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    status: Rc<RefCell<CameraStatus>>,
+}
+
+impl WebCamera {
+    // Asks for the camera; frames arrive once the user grants access and the video starts
+    pub fn open(width: u32, height: u32) -> Result<Self> {
+        let window = web_sys::window().ok_or_else(|| anyhow!("No window"))?;
+        let document = window.document().ok_or_else(|| anyhow!("No document"))?;
+        let media_devices = window.navigator().media_devices().map_err(js_error)?;
+
+        let video: HtmlVideoElement = document
+            .create_element("video")
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(|_| anyhow!("<video> is not a HtmlVideoElement"))?;
+        video.set_autoplay(true);
+        video.set_muted(true);
+        video.set_attribute("playsinline", "").map_err(js_error)?; // iOS: no fullscreen player
+        let canvas: HtmlCanvasElement = document
+            .create_element("canvas")
+            .map_err(js_error)?
+            .dyn_into()
+            .map_err(|_| anyhow!("<canvas> is not a HtmlCanvasElement"))?;
+        let context: CanvasRenderingContext2d = canvas
+            .get_context("2d")
+            .map_err(js_error)?
+            .ok_or_else(|| anyhow!("2D canvas context unavailable"))?
+            .dyn_into()
+            .map_err(|_| anyhow!("Unexpected 2D context type"))?;
+
+        // { video: { width: { ideal }, height: { ideal }, facingMode: "user" }, audio: false }
+        let ideal = |value: u32| -> Result<Object> {
+            let constraint = Object::new();
+            Reflect::set(&constraint, &"ideal".into(), &value.into()).map_err(js_error)?;
+            Ok(constraint)
+        };
+        let video_constraints = Object::new();
+        Reflect::set(&video_constraints, &"width".into(), &ideal(width)?).map_err(js_error)?;
+        Reflect::set(&video_constraints, &"height".into(), &ideal(height)?).map_err(js_error)?;
+        Reflect::set(&video_constraints, &"facingMode".into(), &"user".into())
+            .map_err(js_error)?;
+        let constraints = MediaStreamConstraints::new();
+        constraints.set_video(&video_constraints);
+        constraints.set_audio(&JsValue::FALSE);
+        let request = media_devices
+            .get_user_media_with_constraints(&constraints)
+            .map_err(js_error)?;
+
+        let status = Rc::new(RefCell::new(CameraStatus::default()));
+        let pending = status.clone();
+        let pending_video = video.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = async {
+                let stream: MediaStream = JsFuture::from(request)
+                    .await
+                    .map_err(js_error)?
+                    .dyn_into()
+                    .map_err(|_| anyhow!("getUserMedia did not return a MediaStream"))?;
+                pending_video.set_src_object(Some(&stream));
+                JsFuture::from(pending_video.play().map_err(js_error)?)
+                    .await
+                    .map_err(js_error)?;
+                Ok::<_, anyhow::Error>(stream)
+            }
+            .await;
+            let mut status = pending.borrow_mut();
+            match result {
+                Ok(stream) => {
+                    info!("Camera stream started");
+                    status.stream = Some(stream);
+                }
+                Err(e) => {
+                    error!("Camera unavailable: {:#}", e);
+                    status.error = Some(format!("{:#}", e));
+                }
+            }
+        });
+
+        Ok(Self {
+            video,
+            canvas,
+            context,
+            status,
+        })
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.status.borrow().error.clone()
+    }
+
+    // Latest video frame, or None until the stream is playing
+    pub fn frame(&self) -> Option<RgbImage> {
+        if self.status.borrow().stream.is_none() || self.video.ready_state() < HAVE_CURRENT_DATA {
+            return None;
+        }
+        let (width, height) = (self.video.video_width(), self.video.video_height());
+        if width == 0 || height == 0 {
+            return None;
+        }
+        if self.canvas.width() != width || self.canvas.height() != height {
+            self.canvas.set_width(width);
+            self.canvas.set_height(height);
+        }
+        self.context
+            .draw_image_with_html_video_element(&self.video, 0.0, 0.0)
+            .ok()?;
+        let rgba = self
+            .context
+            .get_image_data(0.0, 0.0, width as f64, height as f64)
+            .ok()?
+            .data();
+        let rgb = rgba
+            .chunks_exact(4)
+            .flat_map(|px| [px[0], px[1], px[2]])
+            .collect();
+        RgbImage::from_raw(width, height, rgb)
+    }
+}
+
+impl Drop for WebCamera {
+    // Stops the tracks so the browser turns the camera light off
+    fn drop(&mut self) {
+        if let Some(stream) = self.status.borrow_mut().stream.take() {
+            for track in stream.get_tracks().iter() {
+                if let Ok(track) = track.dyn_into::<MediaStreamTrack>() {
+                    track.stop();
+                }
+            }
+        }
+        self.video.set_src_object(None);
+    }
+}
@@ -0,0 +1,149 @@
+// src/web/live_audio.rs
+// Microphone analysis for the wasm32 build: a Web Audio AnalyserNode does the FFT in the browser
+// and the low/mid/high band energies are read from its spectrum each UI frame. Replaces the cpal
+// capture and rustfft analysis of the native app; the bands match music.rs.
+use anyhow::{anyhow, Result};
+use js_sys::{Object, Reflect};
+use log::{error, info};
+use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    AnalyserNode, AudioContext, MediaStream, MediaStreamConstraints, MediaStreamTrack,
+};
+
+use crate::camera::js_error;
+
+const FFT_SIZE: u32 = 2048;
+const SMOOTHING: f64 = 0.6; // Analyser's own time smoothing, 0 (none) to 1
+const LOW_BAND_HZ: (f32, f32) = (50.0, 180.0);
+const MID_BAND_END_HZ: f32 = 3000.0;
+const DB_FLOOR: f32 = -90.0; // Band level shown as silence
+const DB_CEIL: f32 = -20.0; // Band level shown as full intensity
+
+#[derive(Default)]
+struct AudioStatus {
+    stream: Option<MediaStream>,
+    analyser: Option<AnalyserNode>,
+    error: Option<String>,
+}
+
+pub struct WebAudioInput {
+    context: AudioContext,
+    status: Rc<RefCell<AudioStatus>>,
+    spectrum: Vec<f32>,
+}
+
+impl WebAudioInput {
+    // Must be called from a user gesture (a click) or the browser keeps the context suspended
+    pub fn open() -> Result<Self> {
+        let window = web_sys::window().ok_or_else(|| anyhow!("No window"))?;
+        let media_devices = window.navigator().media_devices().map_err(js_error)?;
+        let context = AudioContext::new().map_err(js_error)?;
+
+        // Raw signal: the browser's voice processing would flatten the music
+        let audio_constraints = Object::new();
+        for key in ["echoCancellation", "noiseSuppression", "autoGainControl"] {
+            Reflect::set(&audio_constraints, &key.into(), &JsValue::FALSE).map_err(js_error)?;
+        }
+        let constraints = MediaStreamConstraints::new();
+        constraints.set_audio(&audio_constraints);
+        constraints.set_video(&JsValue::FALSE);
+        let request = media_devices
+            .get_user_media_with_constraints(&constraints)
+            .map_err(js_error)?;
+
+        let status = Rc::new(RefCell::new(AudioStatus::default()));
+        let pending = status.clone();
+        let pending_context = context.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = async {
+                let stream: MediaStream = JsFuture::from(request)
+                    .await
+                    .map_err(js_error)?
+                    .dyn_into()
+                    .map_err(|_| anyhow!("getUserMedia did not return a MediaStream"))?;
+                let source = pending_context
+                    .create_media_stream_source(&stream)
+                    .map_err(js_error)?;
+                let analyser = pending_context.create_analyser().map_err(js_error)?;
+                analyser.set_fft_size(FFT_SIZE);
+                analyser.set_smoothing_time_constant(SMOOTHING);
+                source
+                    .connect_with_audio_node(&analyser)
+                    .map_err(js_error)?;
+                JsFuture::from(pending_context.resume().map_err(js_error)?)
+                    .await
+                    .map_err(js_error)?;
+                Ok::<_, anyhow::Error>((stream, analyser))
+            }
+            .await;
+            let mut status = pending.borrow_mut();
+            match result {
+                Ok((stream, analyser)) => {
+                    info!(
+                        "Web Audio analysis started ({} Hz)",
+                        pending_context.sample_rate()
+                    );
+                    status.stream = Some(stream);
+                    status.analyser = Some(analyser);
+                }
+                Err(e) => {
+                    error!("Microphone unavailable: {:#}", e);
+                    status.error = Some(format!("{:#}", e));
+                }
+            }
+        });
+
+        Ok(Self {
+            context,
+            status,
+            spectrum: vec![0.0; FFT_SIZE as usize / 2],
+        })
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.status.borrow().error.clone()
+    }
+
+    // Low, mid and high intensity in 0..1; silent until the microphone is granted
+    pub fn intensities(&mut self) -> [f32; 3] {
+        let status = self.status.borrow();
+        let Some(analyser) = status.analyser.as_ref() else {
+            return [0.0; 3];
+        };
+        analyser.get_float_frequency_data(&mut self.spectrum); // dBFS per bin
+        let bin_hz = self.context.sample_rate() / FFT_SIZE as f32;
+        let nyquist = self.spectrum.len() as f32 * bin_hz;
+        let band = |(start_hz, end_hz): (f32, f32)| -> f32 {
+            let start = ((start_hz / bin_hz) as usize).max(1);
+            let end = ((end_hz / bin_hz) as usize).clamp(start + 1, self.spectrum.len());
+            // Mean power across the band, then back to decibels
+            let power = self.spectrum[start..end]
+                .iter()
+                .map(|db| 10f32.powf(db / 10.0))
+                .sum::<f32>()
+                / (end - start) as f32;
+            let db = 10.0 * power.max(f32::MIN_POSITIVE).log10();
+            ((db - DB_FLOOR) / (DB_CEIL - DB_FLOOR)).clamp(0.0, 1.0)
+        };
+        [
+            band(LOW_BAND_HZ),
+            band((LOW_BAND_HZ.1, MID_BAND_END_HZ)),
+            band((MID_BAND_END_HZ, nyquist)),
+        ]
+    }
+}
+
+impl Drop for WebAudioInput {
+    fn drop(&mut self) {
+        if let Some(stream) = self.status.borrow_mut().stream.take() {
+            for track in stream.get_tracks().iter() {
+                if let Ok(track) = track.dyn_into::<MediaStreamTrack>() {
+                    track.stop();
+                }
+            }
+        }
+        let _ = self.context.close();
+    }
+}
@@ -0,0 +1,323 @@
+// src/web/segmentation.rs
+// Segmentation for the wasm32 build: the FastSAM (YOLOv8-seg) ONNX model runs in onnxruntime-web,
+// on WebGPU when the browser has it and on its wasm backend otherwise. Frames are letterboxed to
+// the model input here, inference is awaited on the browser event loop (one frame in flight), and
+// the raw outputs are decoded into boxes and frame-sized masks. Replaces the usls/ort thread.
+use anyhow::{anyhow, Result};
+use image::{imageops, GrayImage, Luma, Rgb, RgbImage};
+use imageproc::rect::Rect;
+use js_sys::{Array, Float32Array, Reflect};
+use log::{error, info};
+use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+
+use crate::camera::js_error;
+
+pub const DEFAULT_MODEL_URL: &str = "models/FastSAM-s.onnx";
+pub const MAX_TRACKS: usize = 3; // One object per band, as in the native app
+const INPUT_SIZE: u32 = 640; // Square model input
+const LETTERBOX_FILL: u8 = 114; // YOLO's padding grey
+const CONF_THRESHOLD: f32 = 0.35;
+const NMS_IOU_THRESHOLD: f32 = 0.5;
+
+#[wasm_bindgen(module = "/assets/ort_glue.js")]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = loadSession)]
+    async fn load_session(url: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = runSession)]
+    async fn run_session(input: Float32Array, size: u32) -> Result<JsValue, JsValue>;
+}
+
+// --- One detection in frame coordinates ---
+#[derive(Debug, Clone)]
+pub struct WebDetection {
+    pub bbox: Rect,
+    pub confidence: f32,
+    pub mask: GrayImage, // Frame-sized, 255 inside the object
+}
+
+// Where the frame sits inside the square model input
+#[derive(Debug, Clone, Copy)]
+struct Letterbox {
+    scale: f32,
+    pad_x: f32,
+    pad_y: f32,
+    frame_width: u32,
+    frame_height: u32,
+}
+
+fn letterbox(frame: &RgbImage) -> (Float32Array, Letterbox) {
+    let (width, height) = frame.dimensions();
+    let scale = (INPUT_SIZE as f32 / width as f32).min(INPUT_SIZE as f32 / height as f32);
+    let scaled_w = ((width as f32 * scale).round() as u32).clamp(1, INPUT_SIZE);
+    let scaled_h = ((height as f32 * scale).round() as u32).clamp(1, INPUT_SIZE);
+    let resized = imageops::resize(frame, scaled_w, scaled_h, imageops::FilterType::Triangle);
+    let mut canvas = RgbImage::from_pixel(INPUT_SIZE, INPUT_SIZE, Rgb([LETTERBOX_FILL; 3]));
+    let pad_x = (INPUT_SIZE - scaled_w) / 2;
+    let pad_y = (INPUT_SIZE - scaled_h) / 2;
+    imageops::replace(&mut canvas, &resized, pad_x as i64, pad_y as i64);
+
+    // NCHW, 0..1
+    let plane = (INPUT_SIZE * INPUT_SIZE) as usize;
+    let mut input = vec![0.0f32; plane * 3];
+    for (i, pixel) in canvas.pixels().enumerate() {
+        for c in 0..3 {
+            input[c * plane + i] = pixel[c] as f32 / 255.0;
+        }
+    }
+    let geometry = Letterbox {
+        scale,
+        pad_x: pad_x as f32,
+        pad_y: pad_y as f32,
+        frame_width: width,
+        frame_height: height,
+    };
+    (Float32Array::from(input.as_slice()), geometry)
+}
+
+// Box corners in model pixels
+#[derive(Debug, Clone)]
+struct Candidate {
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+    score: f32,
+    coefficients: Vec<f32>,
+}
+
+fn iou(a: &Candidate, b: &Candidate) -> f32 {
+    let w = (a.x1.min(b.x1) - a.x0.max(b.x0)).max(0.0);
+    let h = (a.y1.min(b.y1) - a.y0.max(b.y0)).max(0.0);
+    let inter = w * h;
+    let union = (a.x1 - a.x0) * (a.y1 - a.y0) + (b.x1 - b.x0) * (b.y1 - b.y0) - inter;
+    if union > 0.0 {
+        inter / union
+    } else {
+        0.0
+    }
+}
+
+// YOLOv8-seg heads: output [1, 4 + classes + M, anchors] and prototypes [1, M, PH, PW]
+fn decode(
+    output: &[f32],
+    output_dims: &[usize],
+    protos: &[f32],
+    proto_dims: &[usize],
+    geometry: Letterbox,
+) -> Result<Vec<WebDetection>> {
+    let [_, channels, anchors] = output_dims[..] else {
+        return Err(anyhow!("Unexpected model output shape {:?}", output_dims));
+    };
+    let [_, mask_dims, proto_h, proto_w] = proto_dims[..] else {
+        return Err(anyhow!("Unexpected prototype shape {:?}", proto_dims));
+    };
+    if channels <= 4 + mask_dims
+        || output.len() != channels * anchors
+        || protos.len() != mask_dims * proto_h * proto_w
+    {
+        return Err(anyhow!(
+            "Model outputs {:?} and {:?} don't match a segmentation head",
+            output_dims,
+            proto_dims
+        ));
+    }
+    let classes = channels - 4 - mask_dims;
+    let at = |channel: usize, anchor: usize| output[channel * anchors + anchor];
+
+    let mut candidates: Vec<Candidate> = (0..anchors)
+        .filter_map(|i| {
+            let score = (4..4 + classes)
+                .map(|c| at(c, i))
+                .fold(f32::MIN, f32::max);
+            if score < CONF_THRESHOLD {
+                return None;
+            }
+            let (cx, cy, w, h) = (at(0, i), at(1, i), at(2, i), at(3, i));
+            Some(Candidate {
+                x0: cx - w / 2.0,
+                y0: cy - h / 2.0,
+                x1: cx + w / 2.0,
+                y1: cy + h / 2.0,
+                score,
+                coefficients: (4 + classes..channels).map(|c| at(c, i)).collect(),
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut kept: Vec<Candidate> = Vec::new();
+    for candidate in candidates {
+        if kept.len() == MAX_TRACKS {
+            break;
+        }
+        if kept.iter().all(|k| iou(k, &candidate) < NMS_IOU_THRESHOLD) {
+            kept.push(candidate);
+        }
+    }
+
+    let proto_scale_x = proto_w as f32 / INPUT_SIZE as f32;
+    let proto_scale_y = proto_h as f32 / INPUT_SIZE as f32;
+    let plane = proto_h * proto_w;
+    let detections = kept
+        .into_iter()
+        .filter_map(|c| {
+            // Box in frame pixels
+            let to_frame_x = |x: f32| ((x - geometry.pad_x) / geometry.scale).round();
+            let to_frame_y = |y: f32| ((y - geometry.pad_y) / geometry.scale).round();
+            let fx0 = to_frame_x(c.x0).clamp(0.0, geometry.frame_width as f32) as u32;
+            let fy0 = to_frame_y(c.y0).clamp(0.0, geometry.frame_height as f32) as u32;
+            let fx1 = to_frame_x(c.x1).clamp(0.0, geometry.frame_width as f32) as u32;
+            let fy1 = to_frame_y(c.y1).clamp(0.0, geometry.frame_height as f32) as u32;
+            if fx1 <= fx0 || fy1 <= fy0 {
+                return None;
+            }
+
+            // Mask logits at prototype resolution, only inside the box; logit > 0 is sigmoid > 0.5
+            let px0 = ((c.x0 * proto_scale_x).floor().max(0.0) as usize).min(proto_w - 1);
+            let py0 = ((c.y0 * proto_scale_y).floor().max(0.0) as usize).min(proto_h - 1);
+            let px1 = ((c.x1 * proto_scale_x).ceil() as usize).clamp(px0 + 1, proto_w);
+            let py1 = ((c.y1 * proto_scale_y).ceil() as usize).clamp(py0 + 1, proto_h);
+            let mut inside = vec![false; plane];
+            for py in py0..py1 {
+                for px in px0..px1 {
+                    let offset = py * proto_w + px;
+                    let logit: f32 = c
+                        .coefficients
+                        .iter()
+                        .enumerate()
+                        .map(|(k, coefficient)| coefficient * protos[k * plane + offset])
+                        .sum();
+                    inside[offset] = logit > 0.0;
+                }
+            }
+
+            let mut mask = GrayImage::new(geometry.frame_width, geometry.frame_height);
+            for y in fy0..fy1 {
+                let py = ((y as f32 * geometry.scale + geometry.pad_y) * proto_scale_y) as usize;
+                for x in fx0..fx1 {
+                    let px =
+                        ((x as f32 * geometry.scale + geometry.pad_x) * proto_scale_x) as usize;
+                    if inside[py.min(proto_h - 1) * proto_w + px.min(proto_w - 1)] {
+                        mask.put_pixel(x, y, Luma([255]));
+                    }
+                }
+            }
+            Some(WebDetection {
+                bbox: Rect::at(fx0 as i32, fy0 as i32).of_size(fx1 - fx0, fy1 - fy0),
+                confidence: c.score,
+                mask,
+            })
+        })
+        .collect();
+    Ok(detections)
+}
+
+// `{ data: Float32Array, dims: number[] }` from the glue
+fn tensor(result: &JsValue, key: &str) -> Result<(Vec<f32>, Vec<usize>)> {
+    let value = Reflect::get(result, &key.into()).map_err(js_error)?;
+    let data: Float32Array = Reflect::get(&value, &"data".into())
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(|_| anyhow!("{} is not a float32 tensor", key))?;
+    let dims: Array = Reflect::get(&value, &"dims".into())
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(|_| anyhow!("{} has no dims", key))?;
+    let dims = dims
+        .iter()
+        .map(|d| d.as_f64().map(|d| d as usize))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| anyhow!("{} dims are not numbers", key))?;
+    Ok((data.to_vec(), dims))
+}
+
+#[derive(Default)]
+struct SegmenterState {
+    provider: Option<String>, // "webgpu" or "wasm" once the session is up
+    busy: bool,
+    error: Option<String>,
+    detections: Option<Vec<WebDetection>>,
+}
+
+pub struct WebSegmenter {
+    state: Rc<RefCell<SegmenterState>>,
+}
+
+impl WebSegmenter {
+    // Fetches and compiles the model in the background
+    pub fn load(url: &str) -> Self {
+        let state = Rc::new(RefCell::new(SegmenterState {
+            busy: true,
+            ..Default::default()
+        }));
+        let pending = state.clone();
+        let url = url.to_string();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = load_session(&url).await;
+            let mut state = pending.borrow_mut();
+            state.busy = false;
+            match result {
+                Ok(provider) => {
+                    let provider = provider.as_string().unwrap_or_default();
+                    info!("Loaded {} on onnxruntime-web ({})", url, provider);
+                    state.provider = Some(provider);
+                }
+                Err(e) => {
+                    let e = js_error(e);
+                    error!("Failed to load {}: {:#}", url, e);
+                    state.error = Some(format!("{:#}", e));
+                }
+            }
+        });
+        Self { state }
+    }
+
+    pub fn provider(&self) -> Option<String> {
+        self.state.borrow().provider.clone()
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.state.borrow().error.clone()
+    }
+
+    // Starts inference on `frame` unless the model is loading or a frame is already in flight
+    pub fn submit(&self, frame: &RgbImage) -> bool {
+        {
+            let mut state = self.state.borrow_mut();
+            if state.busy || state.provider.is_none() {
+                return false;
+            }
+            state.busy = true;
+        }
+        let (input, geometry) = letterbox(frame);
+        let pending = self.state.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = async {
+                let outputs = run_session(input, INPUT_SIZE).await.map_err(js_error)?;
+                let (output, output_dims) = tensor(&outputs, "output")?;
+                let (protos, proto_dims) = tensor(&outputs, "protos")?;
+                decode(&output, &output_dims, &protos, &proto_dims, geometry)
+            }
+            .await;
+            let mut state = pending.borrow_mut();
+            state.busy = false;
+            match result {
+                Ok(detections) => {
+                    state.error = None;
+                    state.detections = Some(detections);
+                }
+                Err(e) => {
+                    error!("Inference failed: {:#}", e);
+                    state.error = Some(format!("{:#}", e));
+                }
+            }
+        });
+        true
+    }
+
+    // Detections of the last finished inference, once
+    pub fn take_detections(&self) -> Option<Vec<WebDetection>> {
+        self.state.borrow_mut().detections.take()
+    }
+}